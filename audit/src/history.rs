@@ -0,0 +1,106 @@
+//! Transactions of the shares mint within the audited range
+//!
+//! Every share movement touches the shares mint: deposits mint, withdrawals
+//! burn, and confidential transfers name the mint, so its signature history
+//! covers them all. Signatures are paged newest first until the range's
+//! start, then fetched oldest first.
+
+use std::str::FromStr;
+
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+
+/// `getSignaturesForAddress` page size (the RPC maximum)
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Slots and unix times to audit; every bound is optional
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    /// First slot, inclusive
+    pub from_slot: Option<u64>,
+    /// Last slot, inclusive
+    pub to_slot: Option<u64>,
+    /// Start time, inclusive
+    pub since: Option<i64>,
+    /// End time, exclusive
+    pub until: Option<i64>,
+}
+
+impl Range {
+    pub fn contains(&self, slot: u64, block_time: Option<i64>) -> bool {
+        !self.before(slot, block_time)
+            && self.to_slot.is_none_or(|to| slot <= to)
+            && match (self.until, block_time) {
+                (Some(until), Some(time)) => time < until,
+                _ => true,
+            }
+    }
+
+    /// Whether `slot` precedes the range, and so does everything older
+    fn before(&self, slot: u64, block_time: Option<i64>) -> bool {
+        self.from_slot.is_some_and(|from| slot < from)
+            || matches!((self.since, block_time), (Some(since), Some(time)) if time < since)
+    }
+}
+
+/// Successful transactions of `address` in `range`, oldest first
+pub fn signatures(
+    client: &RpcClient,
+    address: &Pubkey,
+    range: &Range,
+) -> anyhow::Result<Vec<Signature>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = client.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(client.commitment()),
+            },
+        )?;
+        let full = page.len() == SIGNATURE_PAGE;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+
+        let mut done = !full;
+        for status in page {
+            if range.before(status.slot, status.block_time) {
+                done = true;
+                break;
+            }
+            if status.err.is_none() && range.contains(status.slot, status.block_time) {
+                signatures.push(Signature::from_str(&status.signature)?);
+            }
+        }
+        if done {
+            break;
+        }
+    }
+
+    signatures.reverse();
+    Ok(signatures)
+}
+
+/// Fetch a transaction with its instructions and logs
+pub fn transaction(
+    client: &RpcClient,
+    signature: &Signature,
+) -> anyhow::Result<EncodedConfirmedTransactionWithStatusMeta> {
+    Ok(client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?)
+}
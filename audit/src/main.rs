@@ -0,0 +1,199 @@
+//! svs-audit
+//!
+//! Share movement report of an SVS-2 vault for the holder of its auditor
+//! ElGamal keypair. Deposits and withdrawals are public in the vault's
+//! events; confidential transfers between shares accounts carry the amount
+//! encrypted under the auditor key, which this tool decrypts. See README.md.
+
+mod history;
+mod movement;
+mod report;
+mod transfer;
+
+use std::{fs::File, io, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context as _};
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use clap::Parser;
+use solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker;
+use solana_cli_config::{Config, CONFIG_FILE};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_zk_sdk::encryption::elgamal::ElGamalKeypair;
+use svs_client::{decode_confidential_vault, rpc::fetch_vault_keys};
+
+use history::Range;
+
+#[derive(Parser)]
+#[clap(
+    name = "svs-audit",
+    version,
+    about = "Decrypt the share movements of an SVS-2 vault with its auditor key"
+)]
+struct Cli {
+    /// SVS-2 vault
+    vault: Pubkey,
+
+    /// Auditor ElGamal keypair file (JSON)
+    #[clap(long, value_name = "PATH")]
+    auditor_keypair: PathBuf,
+
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[clap(short = 'u', long, value_name = "URL")]
+    url: Option<String>,
+
+    /// Solana CLI config file, for the default RPC URL
+    #[clap(long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// First slot to audit
+    #[clap(long, value_name = "SLOT")]
+    from_slot: Option<u64>,
+
+    /// Last slot to audit
+    #[clap(long, value_name = "SLOT")]
+    to_slot: Option<u64>,
+
+    /// Start of the audited period: YYYY-MM-DD (UTC) or RFC 3339
+    #[clap(long, value_name = "TIME", value_parser = parse_time)]
+    since: Option<i64>,
+
+    /// End of the audited period, exclusive: YYYY-MM-DD (UTC) or RFC 3339
+    #[clap(long, value_name = "TIME", value_parser = parse_time)]
+    until: Option<i64>,
+
+    /// Also write every movement to this CSV file
+    #[clap(long, value_name = "PATH")]
+    csv: Option<PathBuf>,
+}
+
+/// Unix time of a UTC date or an RFC 3339 time
+fn parse_time(value: &str) -> Result<i64, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc().timestamp())
+        .map_err(|_| format!("expected YYYY-MM-DD or an RFC 3339 time, got {value}"))
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("Error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> anyhow::Result<()> {
+    let config = match cli.config.as_ref().or(CONFIG_FILE.as_ref()) {
+        Some(path) => Config::load(path).unwrap_or_default(),
+        None => Config::default(),
+    };
+    let url = normalize_to_url_if_moniker(cli.url.as_ref().unwrap_or(&config.json_rpc_url));
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+
+    let auditor = ElGamalKeypair::read_json_file(&cli.auditor_keypair).map_err(|e| {
+        anyhow!(
+            "Failed to read auditor keypair {}: {e}",
+            cli.auditor_keypair.display()
+        )
+    })?;
+    let vault = decode_confidential_vault(&cli.vault, &client.get_account_data(&cli.vault)?)
+        .context("Only SVS-2 vaults have an auditor")?;
+    match vault.auditor_elgamal_pubkey {
+        None => bail!("Vault {} has no auditor", cli.vault),
+        Some(pubkey) if pubkey != <[u8; 32]>::from(auditor.pubkey()) => {
+            bail!("The keypair is not the auditor of vault {}", cli.vault)
+        }
+        Some(_) => {}
+    }
+    let keys = fetch_vault_keys(&client, &cli.vault)?;
+
+    let range = Range {
+        from_slot: cli.from_slot,
+        to_slot: cli.to_slot,
+        since: cli.since,
+        until: cli.until,
+    };
+    let signatures = history::signatures(&client, &keys.shares_mint, &range)?;
+    eprintln!("Decrypting {} transactions...", signatures.len());
+
+    let mut movements = Vec::new();
+    for signature in &signatures {
+        let transaction = history::transaction(&client, signature)?;
+        movements.extend(
+            movement::movements(&transaction, &keys, &auditor)
+                .with_context(|| format!("Transaction {signature}"))?,
+        );
+    }
+
+    let accounts = report::summarize(&movements);
+    report::write_summary(io::stdout().lock(), &accounts)?;
+    let undecrypted = movements
+        .iter()
+        .filter(|movement| movement.shares.is_none())
+        .count();
+    if undecrypted > 0 {
+        eprintln!("{undecrypted} transfers did not decrypt under the auditor key");
+    }
+
+    if let Some(path) = cli.csv {
+        let file = File::create(&path).with_context(|| format!("Creating {}", path.display()))?;
+        report::write_csv(io::BufWriter::new(file), &movements)?;
+        eprintln!("Wrote {} movements to {}", movements.len(), path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let vault = Pubkey::new_unique().to_string();
+        let cli = Cli::try_parse_from([
+            "svs-audit",
+            &vault,
+            "--auditor-keypair",
+            "auditor.json",
+            "--since",
+            "2025-01-01",
+            "--until",
+            "2025-02-01T00:00:00+01:00",
+            "--from-slot",
+            "300000000",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.since, Some(1_735_689_600));
+        assert_eq!(cli.until, Some(1_738_364_400));
+        assert_eq!(cli.from_slot, Some(300_000_000));
+
+        let range = Range {
+            since: cli.since,
+            ..Range::default()
+        };
+        assert!(range.contains(1, Some(1_735_689_600)));
+        assert!(!range.contains(1, Some(1_735_689_599)));
+        // Without a block time only the slot bounds apply
+        assert!(range.contains(1, None));
+
+        assert!(Cli::try_parse_from([
+            "svs-audit",
+            &vault,
+            "--auditor-keypair",
+            "a",
+            "--since",
+            "Jan 1"
+        ])
+        .is_err());
+    }
+}
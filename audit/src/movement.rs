@@ -0,0 +1,194 @@
+//! Share movements of one transaction
+//!
+//! Deposits, withdrawals and emergency redemptions are read from the vault's
+//! events, whose amounts are public. Confidential transfers are found among
+//! the transaction's instructions, top-level and inner, and decrypted with
+//! the auditor key.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Context as _};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInnerInstructions, UiInstruction,
+    UiLoadedAddresses, UiTransactionStatusMeta,
+};
+use solana_zk_sdk::encryption::elgamal::ElGamalKeypair;
+use svs_client::VaultKeys;
+use svs_events::{parse_transaction, VaultEvent};
+
+use crate::transfer::ConfidentialTransfer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Deposit,
+    Withdraw,
+    EmergencyRedeem,
+    Transfer,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Deposit => "deposit",
+            Kind::Withdraw => "withdraw",
+            Kind::EmergencyRedeem => "emergency_redeem",
+            Kind::Transfer => "transfer",
+        })
+    }
+}
+
+/// Shares entering, leaving or moving between shares accounts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movement {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub kind: Kind,
+    /// Shares account debited; `None` for deposits
+    pub from: Option<Pubkey>,
+    /// Shares account credited; `None` for withdrawals
+    pub to: Option<Pubkey>,
+    /// `None` when a transfer did not decrypt
+    pub shares: Option<u64>,
+    /// Assets paid in or out; `None` for transfers
+    pub assets: Option<u64>,
+}
+
+/// Movements of `keys`' shares in `transaction`: the vault events, then the
+/// transfers; none for a failed transaction
+pub fn movements(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    keys: &VaultKeys,
+    auditor: &ElGamalKeypair,
+) -> anyhow::Result<Vec<Movement>> {
+    let meta = transaction
+        .transaction
+        .meta
+        .as_ref()
+        .context("Transaction has no status meta")?;
+    if meta.err.is_some() {
+        return Ok(Vec::new());
+    }
+    let decoded = transaction
+        .transaction
+        .transaction
+        .decode()
+        .context("Transaction is not base64")?;
+    let signature = *decoded
+        .signatures
+        .first()
+        .context("Transaction has no signature")?;
+
+    let movement = |kind, from, to, shares, assets| Movement {
+        signature,
+        slot: transaction.slot,
+        block_time: transaction.block_time,
+        kind,
+        from,
+        to,
+        shares,
+        assets,
+    };
+
+    let mut movements = Vec::new();
+    for parsed in parse_transaction(transaction, &[keys.program_id])? {
+        match &parsed.event {
+            VaultEvent::Deposit(deposit) if deposit.vault == keys.vault => {
+                movements.push(movement(
+                    Kind::Deposit,
+                    None,
+                    Some(keys.shares_account(&deposit.owner)),
+                    Some(deposit.shares),
+                    Some(deposit.assets),
+                ));
+            }
+            VaultEvent::Withdraw(withdraw) if withdraw.vault == keys.vault => {
+                movements.push(movement(
+                    Kind::Withdraw,
+                    Some(keys.shares_account(&withdraw.owner)),
+                    None,
+                    Some(withdraw.shares),
+                    Some(withdraw.assets),
+                ));
+            }
+            VaultEvent::EmergencyRedeem(redeem) if redeem.vault == keys.vault => {
+                movements.push(movement(
+                    Kind::EmergencyRedeem,
+                    Some(keys.shares_account(&redeem.owner)),
+                    None,
+                    Some(redeem.shares),
+                    Some(redeem.assets),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for transfer in transfers(&decoded, meta, &keys.shares_mint)? {
+        movements.push(movement(
+            Kind::Transfer,
+            Some(transfer.source),
+            Some(transfer.destination),
+            transfer.decrypt(auditor),
+            None,
+        ));
+    }
+    Ok(movements)
+}
+
+/// Confidential transfers of `mint` in a transaction
+fn transfers(
+    transaction: &VersionedTransaction,
+    meta: &UiTransactionStatusMeta,
+    mint: &Pubkey,
+) -> anyhow::Result<Vec<ConfidentialTransfer>> {
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    let loaded: Option<&UiLoadedAddresses> = meta.loaded_addresses.as_ref().into();
+    for key in loaded
+        .map(|loaded| loaded.writable.iter().chain(&loaded.readonly))
+        .into_iter()
+        .flatten()
+    {
+        keys.push(Pubkey::from_str(key)?);
+    }
+    let key = |index: u8| {
+        keys.get(index as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("Account index {index} out of range"))
+    };
+
+    let mut instructions = Vec::new();
+    for instruction in transaction.message.instructions() {
+        instructions.push((
+            instruction.program_id_index,
+            instruction.accounts.clone(),
+            instruction.data.clone(),
+        ));
+    }
+    let inner: Option<&Vec<UiInnerInstructions>> = meta.inner_instructions.as_ref().into();
+    for instruction in inner
+        .into_iter()
+        .flatten()
+        .flat_map(|inner| &inner.instructions)
+    {
+        if let UiInstruction::Compiled(compiled) = instruction {
+            let data = bs58::decode(&compiled.data).into_vec()?;
+            instructions.push((compiled.program_id_index, compiled.accounts.clone(), data));
+        }
+    }
+
+    let mut transfers = Vec::new();
+    for (program_id_index, accounts, data) in instructions {
+        let accounts = accounts
+            .iter()
+            .map(|index| key(*index))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if let Some(transfer) =
+            ConfidentialTransfer::decode(&key(program_id_index)?, &accounts, &data, mint)
+        {
+            transfers.push(transfer);
+        }
+    }
+    Ok(transfers)
+}
@@ -0,0 +1,175 @@
+//! Movement report: totals per shares account and the CSV listing
+
+use std::{collections::BTreeMap, io};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::movement::{Kind, Movement};
+
+/// Shares that entered and left one shares account
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSummary {
+    pub deposited: u128,
+    pub withdrawn: u128,
+    pub received: u128,
+    pub sent: u128,
+    /// Transfers whose amount did not decrypt, left out of the totals
+    pub undecrypted: usize,
+}
+
+impl AccountSummary {
+    /// Change of the account's shares over the range
+    pub fn net(&self) -> i128 {
+        (self.deposited + self.received) as i128 - (self.withdrawn + self.sent) as i128
+    }
+}
+
+/// Totals of every shares account that appears in `movements`
+pub fn summarize(movements: &[Movement]) -> BTreeMap<Pubkey, AccountSummary> {
+    let mut accounts = BTreeMap::<Pubkey, AccountSummary>::new();
+    for movement in movements {
+        let shares = movement.shares.map(u128::from);
+        if let Some(to) = movement.to {
+            let summary = accounts.entry(to).or_default();
+            match (movement.kind, shares) {
+                (_, None) => summary.undecrypted += 1,
+                (Kind::Transfer, Some(shares)) => summary.received += shares,
+                (_, Some(shares)) => summary.deposited += shares,
+            }
+        }
+        if let Some(from) = movement.from {
+            let summary = accounts.entry(from).or_default();
+            match (movement.kind, shares) {
+                (_, None) => summary.undecrypted += 1,
+                (Kind::Transfer, Some(shares)) => summary.sent += shares,
+                (_, Some(shares)) => summary.withdrawn += shares,
+            }
+        }
+    }
+    accounts
+}
+
+/// Print the totals as a table
+pub fn write_summary(
+    mut writer: impl io::Write,
+    accounts: &BTreeMap<Pubkey, AccountSummary>,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:<44}  {:>20}  {:>20}  {:>20}  {:>20}  {:>21}  {:>11}",
+        "Shares account", "Deposited", "Withdrawn", "Received", "Sent", "Net", "Undecrypted"
+    )?;
+    for (account, summary) in accounts {
+        writeln!(
+            writer,
+            "{:<44}  {:>20}  {:>20}  {:>20}  {:>20}  {:>21}  {:>11}",
+            account.to_string(),
+            summary.deposited,
+            summary.withdrawn,
+            summary.received,
+            summary.sent,
+            summary.net(),
+            summary.undecrypted,
+        )?;
+    }
+    writer.flush()
+}
+
+/// Write every movement as CSV, with a header row; unknown values are empty
+pub fn write_csv(mut writer: impl io::Write, movements: &[Movement]) -> io::Result<()> {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    writeln!(
+        writer,
+        "slot,block_time,signature,kind,from,to,shares,assets"
+    )?;
+    for movement in movements {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{}",
+            movement.slot,
+            optional(movement.block_time.map(|time| time.to_string())),
+            movement.signature,
+            movement.kind,
+            optional(movement.from.map(|from| from.to_string())),
+            optional(movement.to.map(|to| to.to_string())),
+            optional(movement.shares.map(|shares| shares.to_string())),
+            optional(movement.assets.map(|assets| assets.to_string())),
+        )?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Signature;
+
+    use super::*;
+
+    fn movement(
+        kind: Kind,
+        from: Option<Pubkey>,
+        to: Option<Pubkey>,
+        shares: Option<u64>,
+    ) -> Movement {
+        Movement {
+            signature: Signature::default(),
+            slot: 7,
+            block_time: Some(1_700_000_000),
+            kind,
+            from,
+            to,
+            shares,
+            assets: (kind != Kind::Transfer).then_some(10),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let movements = [
+            movement(Kind::Deposit, None, Some(alice), Some(1_000)),
+            movement(Kind::Transfer, Some(alice), Some(bob), Some(400)),
+            movement(Kind::Transfer, Some(bob), Some(alice), None),
+            movement(Kind::Withdraw, Some(bob), None, Some(100)),
+        ];
+
+        let accounts = summarize(&movements);
+        let (alice, bob) = (accounts[&alice], accounts[&bob]);
+        assert_eq!(
+            (alice.deposited, alice.sent, alice.undecrypted),
+            (1_000, 400, 1)
+        );
+        assert_eq!(alice.net(), 600);
+        assert_eq!(
+            (bob.received, bob.withdrawn, bob.undecrypted),
+            (400, 100, 1)
+        );
+        assert_eq!(bob.net(), 300);
+    }
+
+    #[test]
+    fn test_csv() {
+        let account = Pubkey::new_unique();
+        let movements = [
+            movement(Kind::Deposit, None, Some(account), Some(1_000)),
+            movement(Kind::Transfer, Some(account), Some(account), None),
+        ];
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &movements).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "slot,block_time,signature,kind,from,to,shares,assets"
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "7,1700000000,{},deposit,,{account},1000,10",
+                Signature::default()
+            )
+        );
+        assert!(lines[2].ends_with(&format!(",transfer,{account},{account},,")));
+    }
+}
@@ -0,0 +1,129 @@
+//! Token-2022 confidential transfers of the shares mint
+//!
+//! `Transfer` carries the amount encrypted under the mint's auditor key in
+//! its instruction data, as a 16-bit low and a 32-bit high part, so the
+//! auditor decrypts every transfer from the transaction alone, even after
+//! the proof context accounts are closed.
+
+use anchor_spl::token_2022;
+use solana_sdk::pubkey::Pubkey;
+use solana_zk_sdk::encryption::elgamal::{ElGamalCiphertext, ElGamalKeypair};
+
+/// `TokenInstruction::ConfidentialTransferExtension`
+const CONFIDENTIAL_TRANSFER_EXTENSION: u8 = 27;
+/// `ConfidentialTransferInstruction::Transfer`
+const TRANSFER: u8 = 7;
+
+/// New source decryptable balance preceding the auditor ciphertexts
+const DECRYPTABLE_BALANCE_LEN: usize = 36;
+const CIPHERTEXT_LEN: usize = 64;
+const AMOUNT_LO_BITS: u32 = 16;
+
+/// A confidential transfer between two shares accounts
+#[derive(Debug, Clone)]
+pub struct ConfidentialTransfer {
+    /// Source token account
+    pub source: Pubkey,
+    /// Destination token account
+    pub destination: Pubkey,
+    pub amount_lo: ElGamalCiphertext,
+    pub amount_hi: ElGamalCiphertext,
+}
+
+impl ConfidentialTransfer {
+    /// Decode a confidential `Transfer` of `mint`; `None` for any other
+    /// instruction
+    pub fn decode(
+        program_id: &Pubkey,
+        accounts: &[Pubkey],
+        data: &[u8],
+        mint: &Pubkey,
+    ) -> Option<Self> {
+        if *program_id != token_2022::ID {
+            return None;
+        }
+        let [source, transfer_mint, destination, ..] = accounts else {
+            return None;
+        };
+        if transfer_mint != mint {
+            return None;
+        }
+
+        let body = data.strip_prefix(&[CONFIDENTIAL_TRANSFER_EXTENSION, TRANSFER])?;
+        let ciphertexts =
+            body.get(DECRYPTABLE_BALANCE_LEN..DECRYPTABLE_BALANCE_LEN + 2 * CIPHERTEXT_LEN)?;
+        let (lo, hi) = ciphertexts.split_at(CIPHERTEXT_LEN);
+
+        Some(Self {
+            source: *source,
+            destination: *destination,
+            amount_lo: ElGamalCiphertext::from_bytes(lo)?,
+            amount_hi: ElGamalCiphertext::from_bytes(hi)?,
+        })
+    }
+
+    /// Transferred shares; `None` if the parts do not decrypt under
+    /// `auditor`, i.e. it is not the mint's auditor key
+    pub fn decrypt(&self, auditor: &ElGamalKeypair) -> Option<u64> {
+        let lo = auditor.secret().decrypt_u32(&self.amount_lo)?;
+        let hi = auditor.secret().decrypt_u32(&self.amount_hi)?;
+        hi.checked_shl(AMOUNT_LO_BITS)?.checked_add(lo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_data(auditor: &ElGamalKeypair, amount: u64) -> Vec<u8> {
+        let lo = auditor.pubkey().encrypt(amount & 0xffff);
+        let hi = auditor.pubkey().encrypt(amount >> AMOUNT_LO_BITS);
+
+        let mut data = vec![CONFIDENTIAL_TRANSFER_EXTENSION, TRANSFER];
+        data.extend([0; DECRYPTABLE_BALANCE_LEN]);
+        data.extend(lo.to_bytes());
+        data.extend(hi.to_bytes());
+        // Proof instruction offsets
+        data.extend([1, 2, 3]);
+        data
+    }
+
+    #[test]
+    fn test_decrypt_transfer() {
+        let auditor = ElGamalKeypair::new_rand();
+        let mint = Pubkey::new_unique();
+        let accounts = [
+            Pubkey::new_unique(),
+            mint,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let data = transfer_data(&auditor, 5_000_000);
+
+        let transfer =
+            ConfidentialTransfer::decode(&token_2022::ID, &accounts, &data, &mint).unwrap();
+        assert_eq!(transfer.source, accounts[0]);
+        assert_eq!(transfer.destination, accounts[2]);
+        assert_eq!(transfer.decrypt(&auditor), Some(5_000_000));
+    }
+
+    #[test]
+    fn test_other_instructions() {
+        let auditor = ElGamalKeypair::new_rand();
+        let mint = Pubkey::new_unique();
+        let accounts = [Pubkey::new_unique(), mint, Pubkey::new_unique()];
+        let data = transfer_data(&auditor, 1);
+
+        let decode = |program_id: Pubkey, mint: Pubkey, data: &[u8]| {
+            ConfidentialTransfer::decode(&program_id, &accounts, data, &mint).is_some()
+        };
+        assert!(decode(token_2022::ID, mint, &data));
+        // Another mint, another program, a truncated or different instruction
+        assert!(!decode(token_2022::ID, Pubkey::new_unique(), &data));
+        assert!(!decode(Pubkey::new_unique(), mint, &data));
+        assert!(!decode(token_2022::ID, mint, &data[..100]));
+        let mut apply_pending = data.clone();
+        apply_pending[1] = 8;
+        assert!(!decode(token_2022::ID, mint, &apply_pending));
+    }
+}
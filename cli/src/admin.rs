@@ -0,0 +1,300 @@
+//! Vault administration commands
+
+use std::fmt::Display;
+
+use anyhow::{bail, Context as _};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::{Args, ValueEnum};
+use solana_sdk::{
+    account::from_account,
+    pubkey::Pubkey,
+    sysvar::clock::{self, Clock},
+};
+use svs_client::{svs1, svs2, view::VaultSnapshot, VaultKeys};
+
+use crate::context::{Context, VaultState};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Program {
+    /// Public vault
+    #[clap(name = "svs-1")]
+    Svs1,
+    /// Confidential vault
+    #[clap(name = "svs-2")]
+    Svs2,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    #[clap(long, value_enum)]
+    pub program: Program,
+
+    /// Program id, for deployments other than the canonical one
+    #[clap(long)]
+    pub program_id: Option<Pubkey>,
+
+    #[clap(long)]
+    pub asset_mint: Pubkey,
+
+    /// Distinguishes several vaults of the same asset
+    #[clap(long, default_value_t = 0)]
+    pub vault_id: u64,
+
+    /// Shares token name
+    #[clap(long)]
+    pub name: String,
+
+    /// Shares token symbol
+    #[clap(long)]
+    pub symbol: String,
+
+    /// Shares token metadata URI
+    #[clap(long, default_value = "")]
+    pub uri: String,
+
+    /// Make the shares non-transferable (soulbound) (SVS-1)
+    #[clap(long)]
+    pub non_transferable: bool,
+
+    /// Auditor ElGamal public key, base64 (SVS-2)
+    #[clap(long)]
+    pub auditor: Option<String>,
+}
+
+/// Create a vault; the signer becomes its authority
+pub fn init(ctx: &Context, args: InitArgs) -> anyhow::Result<()> {
+    let program_id = args.program_id.unwrap_or(match args.program {
+        Program::Svs1 => svs_1::ID,
+        Program::Svs2 => svs_2::ID,
+    });
+    let asset_token_program = ctx
+        .client
+        .get_account(&args.asset_mint)
+        .with_context(|| format!("Failed to fetch asset mint {}", args.asset_mint))?
+        .owner;
+    let keys = VaultKeys::derive(
+        &program_id,
+        &args.asset_mint,
+        args.vault_id,
+        &asset_token_program,
+    );
+    let authority = ctx.pubkey();
+
+    let instruction = match args.program {
+        Program::Svs1 => {
+            if args.auditor.is_some() {
+                bail!("--auditor is only supported by SVS-2 vaults");
+            }
+            svs1::initialize(
+                &keys,
+                &authority,
+                args.vault_id,
+                args.name,
+                args.symbol,
+                args.uri,
+                args.non_transferable,
+            )
+        }
+        Program::Svs2 => {
+            if args.non_transferable {
+                bail!("--non-transferable is only supported by SVS-1 vaults");
+            }
+            let auditor = args
+                .auditor
+                .map(|auditor| -> anyhow::Result<[u8; 32]> {
+                    BASE64
+                        .decode(&auditor)
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .context("--auditor must be a base64 32-byte ElGamal public key")
+                })
+                .transpose()?;
+            svs2::initialize(
+                &keys,
+                &authority,
+                &authority,
+                args.vault_id,
+                args.name,
+                args.symbol,
+                args.uri,
+                auditor,
+            )
+        }
+    };
+
+    let signature = ctx.send([instruction])?;
+    println!("Vault: {}", keys.vault);
+    println!("Shares mint: {}", keys.shares_mint);
+    println!("Signature: {signature}");
+    Ok(())
+}
+
+pub fn pause(ctx: &Context, vault: &Pubkey) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let instruction = match state {
+        VaultState::Public(_) => svs1::pause(&keys, &ctx.pubkey()),
+        VaultState::Confidential(_) => svs2::pause(&keys, &ctx.pubkey()),
+    };
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+pub fn unpause(ctx: &Context, vault: &Pubkey) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let instruction = match state {
+        VaultState::Public(_) => svs1::unpause(&keys, &ctx.pubkey()),
+        VaultState::Confidential(_) => svs2::unpause(&keys, &ctx.pubkey()),
+    };
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+pub fn transfer_authority(
+    ctx: &Context,
+    vault: &Pubkey,
+    new_authority: Pubkey,
+) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let instruction = match state {
+        VaultState::Public(_) => svs1::transfer_authority(&keys, &ctx.pubkey(), new_authority),
+        VaultState::Confidential(_) => {
+            svs2::transfer_authority(&keys, &ctx.pubkey(), new_authority)
+        }
+    };
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+pub fn sync(ctx: &Context, vault: &Pubkey) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let instruction = match state {
+        VaultState::Public(_) => svs1::sync(&keys, &ctx.pubkey()),
+        VaultState::Confidential(_) => svs2::sync(&keys, &ctx.pubkey()),
+    };
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+pub fn set_guardian(ctx: &Context, vault: &Pubkey, guardian: Pubkey) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    if let VaultState::Public(_) = state {
+        bail!("SVS-1 vaults have no guardian");
+    }
+    println!(
+        "Signature: {}",
+        ctx.send([svs2::set_guardian(&keys, &ctx.pubkey(), guardian)])?
+    );
+    Ok(())
+}
+
+#[derive(Args)]
+pub struct HarvestConfigArgs {
+    pub vault: Pubkey,
+
+    /// Whitelisted swap adapter program
+    #[clap(long)]
+    pub swap_adapter: Pubkey,
+
+    /// Account allowed to harvest
+    #[clap(long)]
+    pub harvester: Pubkey,
+
+    /// Seconds over which harvested profit unlocks
+    #[clap(long)]
+    pub profit_unlock_period: i64,
+}
+
+pub fn set_harvest_config(ctx: &Context, args: HarvestConfigArgs) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(&args.vault)?;
+    if let VaultState::Public(_) = state {
+        bail!("SVS-1 vaults do not harvest");
+    }
+    let instruction = svs2::set_harvest_config(
+        &keys,
+        &ctx.pubkey(),
+        args.swap_adapter,
+        args.harvester,
+        args.profit_unlock_period,
+    );
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+/// Print the vault's configuration and accounting
+pub fn inspect(ctx: &Context, vault: &Pubkey) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let asset_balance: u64 = ctx
+        .client
+        .get_token_account_balance(&keys.asset_vault)?
+        .amount
+        .parse()?;
+
+    let supply = ctx
+        .client
+        .get_token_supply(&keys.shares_mint)?
+        .amount
+        .parse()?;
+
+    let (program, authority, vault_id, total_assets, snapshot) = match &state {
+        VaultState::Public(vault) => {
+            let snapshot = VaultSnapshot::from_vault(vault, supply);
+            (
+                "SVS-1",
+                vault.authority,
+                vault.vault_id,
+                vault.total_assets,
+                snapshot,
+            )
+        }
+        VaultState::Confidential(vault) => {
+            let clock: Clock = from_account(&ctx.client.get_account(&clock::ID)?)
+                .context("Failed to decode the clock sysvar")?;
+            let snapshot =
+                VaultSnapshot::from_confidential_vault(vault, supply, clock.unix_timestamp)?;
+            (
+                "SVS-2",
+                vault.authority,
+                vault.vault_id,
+                vault.total_assets,
+                snapshot,
+            )
+        }
+    };
+
+    let field = |label: &str, value: &dyn Display| println!("{:<21}{value}", format!("{label}:"));
+    field("Vault", &format!("{vault} ({program})"));
+    field("Program", &keys.program_id);
+    field("Authority", &authority);
+    field("Vault id", &vault_id);
+    field("Asset mint", &keys.asset_mint);
+    field("Asset token program", &keys.asset_token_program);
+    field("Shares mint", &keys.shares_mint);
+    field("Asset vault", &keys.asset_vault);
+    field("Paused", &snapshot.paused);
+    field("Decimals offset", &snapshot.decimals_offset);
+    field("Total assets", &total_assets);
+    field("Total shares", &snapshot.total_shares);
+    field("Asset vault balance", &asset_balance);
+    if asset_balance != total_assets {
+        println!("{:<21}differs from total assets, see `sync`", "");
+    }
+
+    if let VaultState::Confidential(vault) = &state {
+        let auditor = vault
+            .auditor_elgamal_pubkey
+            .map(|auditor| BASE64.encode(auditor))
+            .unwrap_or_else(|| "none".to_string());
+
+        field("Guardian", &vault.guardian);
+        field("Auditor", &auditor);
+        field("Swap adapter", &vault.swap_adapter);
+        field("Harvester", &vault.harvester);
+        field(
+            "Profit unlock period",
+            &format!("{}s", vault.profit_unlock_period),
+        );
+        // Still-locked harvest profit is excluded from the snapshot
+        field("Locked profit", &(total_assets - snapshot.total_assets));
+    }
+    Ok(())
+}
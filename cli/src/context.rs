@@ -0,0 +1,133 @@
+//! Connection, signer and transaction settings shared by every command
+
+use std::{rc::Rc, str::FromStr};
+
+use anyhow::{anyhow, Context as _};
+use clap::{ArgMatches, Args};
+use solana_clap_v3_utils::{
+    input_validators::normalize_to_url_if_moniker, keypair::signer_from_path,
+};
+use solana_cli_config::{Config, CONFIG_FILE};
+use solana_client::rpc_client::RpcClient;
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use svs_client::{
+    accounts::{decode_confidential_vault, decode_vault},
+    rpc::fetch_vault_keys,
+    ConfidentialVault, Vault, VaultKeys,
+};
+
+/// Options shared by every command; defaults come from the Solana CLI config
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Solana CLI config file
+    #[clap(long, global = true, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[clap(short = 'u', long, global = true, value_name = "URL")]
+    pub url: Option<String>,
+
+    /// Signer: keypair file, usb://ledger, prompt:// or stdin
+    #[clap(short = 'k', long, global = true, value_name = "KEYPAIR")]
+    pub keypair: Option<String>,
+
+    /// Priority fee, in micro-lamports per compute unit
+    #[clap(long, global = true, value_name = "MICRO_LAMPORTS")]
+    pub with_compute_unit_price: Option<u64>,
+
+    /// Compute unit limit of each transaction
+    #[clap(long, global = true, value_name = "UNITS")]
+    pub with_compute_unit_limit: Option<u32>,
+}
+
+/// State of an SVS-1 or SVS-2 vault
+pub enum VaultState {
+    Public(Vault),
+    Confidential(ConfidentialVault),
+}
+
+pub struct Context {
+    pub client: RpcClient,
+    pub signer: Box<dyn Signer>,
+    compute_unit_price: Option<u64>,
+    compute_unit_limit: Option<u32>,
+}
+
+impl Context {
+    pub fn new(args: &ConfigArgs, matches: &ArgMatches) -> anyhow::Result<Self> {
+        let config = match args.config.as_ref().or(CONFIG_FILE.as_ref()) {
+            Some(path) => Config::load(path).unwrap_or_default(),
+            None => Config::default(),
+        };
+
+        let url = normalize_to_url_if_moniker(args.url.as_ref().unwrap_or(&config.json_rpc_url));
+        let commitment = CommitmentConfig::from_str(&config.commitment)
+            .with_context(|| format!("Invalid commitment {}", config.commitment))?;
+
+        let keypair = args.keypair.as_ref().unwrap_or(&config.keypair_path);
+        let mut wallet_manager: Option<Rc<RemoteWalletManager>> = None;
+        let signer = signer_from_path(matches, keypair, "keypair", &mut wallet_manager)
+            .map_err(|e| anyhow!("Failed to load signer {keypair}: {e}"))?;
+
+        Ok(Self {
+            client: RpcClient::new_with_commitment(url, commitment),
+            signer,
+            compute_unit_price: args.with_compute_unit_price,
+            compute_unit_limit: args.with_compute_unit_limit,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    pub fn compute_unit_price(&self) -> Option<u64> {
+        self.compute_unit_price
+    }
+
+    /// Accounts and state of `vault`
+    pub fn vault(&self, vault: &Pubkey) -> anyhow::Result<(VaultKeys, VaultState)> {
+        let keys = fetch_vault_keys(&self.client, vault)?;
+        let data = self.client.get_account_data(vault)?;
+
+        let state = match decode_vault(vault, &data) {
+            Ok(state) => VaultState::Public(state),
+            Err(_) => VaultState::Confidential(decode_confidential_vault(vault, &data)?),
+        };
+        Ok((keys, state))
+    }
+
+    /// Send `instructions` in one transaction signed and paid for by the
+    /// signer, with the configured priority fee
+    pub fn send(
+        &self,
+        instructions: impl IntoIterator<Item = Instruction>,
+    ) -> anyhow::Result<Signature> {
+        let mut all = Vec::new();
+        if let Some(units) = self.compute_unit_limit {
+            all.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(price) = self.compute_unit_price {
+            all.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        all.extend(instructions);
+
+        let transaction = Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.pubkey()),
+            &[self.signer.as_ref()],
+            self.client.get_latest_blockhash()?,
+        );
+        Ok(self
+            .client
+            .send_and_confirm_transaction_with_spinner(&transaction)?)
+    }
+}
@@ -0,0 +1,239 @@
+//! svs-cli
+//!
+//! Command line tool for SVS-1 and SVS-2 vault operators. Commands take the
+//! vault address and work out the program and accounts from the vault
+//! account, so the same command line works for either program.
+//!
+//! Connection and signer default to the Solana CLI config. The signer can be
+//! any Solana CLI signer source, including a Ledger (`-k usb://ledger`).
+//! Confidential (SVS-2) commands need a signer that can sign arbitrary
+//! messages, such as a keypair file.
+
+mod admin;
+mod context;
+mod user;
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+
+use admin::{HarvestConfigArgs, InitArgs};
+use context::{ConfigArgs, Context};
+use user::{ConfidentialWithdrawArgs, SlippageArgs};
+
+#[derive(Parser)]
+#[clap(
+    name = "svs-cli",
+    version,
+    about = "Operate Solana Vault Standard vaults"
+)]
+struct Cli {
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a vault with the signer as its authority
+    Init(InitArgs),
+    /// Pause every vault operation (authority, or guardian on SVS-2)
+    Pause { vault: Pubkey },
+    /// Resume vault operations
+    Unpause { vault: Pubkey },
+    /// Hand the vault over to a new authority
+    TransferAuthority {
+        vault: Pubkey,
+        new_authority: Pubkey,
+    },
+    /// Set total assets to the asset vault balance
+    Sync { vault: Pubkey },
+    /// Set the pause-only guardian, or 11111111111111111111111111111111 to remove it (SVS-2)
+    SetGuardian { vault: Pubkey, guardian: Pubkey },
+    /// Set the swap adapter, harvester and profit unlock period (SVS-2)
+    SetHarvestConfig(HarvestConfigArgs),
+    /// Print the vault's configuration and accounting
+    Inspect { vault: Pubkey },
+    /// Deposit assets; SVS-2 credits the shares to the pending balance
+    Deposit {
+        vault: Pubkey,
+        assets: u64,
+        #[clap(flatten)]
+        slippage: SlippageArgs,
+    },
+    /// Withdraw exactly this many assets (SVS-1)
+    Withdraw {
+        vault: Pubkey,
+        assets: u64,
+        #[clap(flatten)]
+        slippage: SlippageArgs,
+    },
+    /// Redeem shares for assets (SVS-1)
+    Redeem {
+        vault: Pubkey,
+        shares: u64,
+        #[clap(flatten)]
+        slippage: SlippageArgs,
+    },
+    /// Print the signer's shares
+    Balance {
+        vault: Pubkey,
+        /// Decrypt the confidential available and pending balances (SVS-2)
+        #[clap(long)]
+        confidential: bool,
+    },
+    /// Create and configure the signer's confidential shares account (SVS-2)
+    ConfigureAccount { vault: Pubkey },
+    /// Make pending shares spendable (SVS-2)
+    ApplyPending { vault: Pubkey },
+    /// Withdraw or redeem confidential shares with ZK proofs (SVS-2)
+    ConfidentialWithdraw(ConfidentialWithdrawArgs),
+}
+
+fn main() {
+    let matches = Cli::command().get_matches();
+    let result = Cli::from_arg_matches(&matches)
+        .map_err(anyhow::Error::from)
+        .and_then(|cli| {
+            let ctx = Context::new(&cli.config, &matches)?;
+            run(&ctx, cli.command)
+        });
+
+    if let Err(e) = result {
+        eprintln!("Error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run(ctx: &Context, command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::Init(args) => admin::init(ctx, args),
+        Command::Pause { vault } => admin::pause(ctx, &vault),
+        Command::Unpause { vault } => admin::unpause(ctx, &vault),
+        Command::TransferAuthority {
+            vault,
+            new_authority,
+        } => admin::transfer_authority(ctx, &vault, new_authority),
+        Command::Sync { vault } => admin::sync(ctx, &vault),
+        Command::SetGuardian { vault, guardian } => admin::set_guardian(ctx, &vault, guardian),
+        Command::SetHarvestConfig(args) => admin::set_harvest_config(ctx, args),
+        Command::Inspect { vault } => admin::inspect(ctx, &vault),
+        Command::Deposit {
+            vault,
+            assets,
+            slippage,
+        } => user::deposit(ctx, &vault, assets, &slippage),
+        Command::Withdraw {
+            vault,
+            assets,
+            slippage,
+        } => user::withdraw(ctx, &vault, assets, &slippage),
+        Command::Redeem {
+            vault,
+            shares,
+            slippage,
+        } => user::redeem(ctx, &vault, shares, &slippage),
+        Command::Balance {
+            vault,
+            confidential,
+        } => user::balance(ctx, &vault, confidential),
+        Command::ConfigureAccount { vault } => user::configure_account(ctx, &vault),
+        Command::ApplyPending { vault } => user::apply_pending(ctx, &vault),
+        Command::ConfidentialWithdraw(args) => user::confidential_withdraw(ctx, args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_admin_commands() {
+        let vault = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "svs-cli",
+            "pause",
+            &vault.to_string(),
+            "-k",
+            "usb://ledger",
+            "--with-compute-unit-price",
+            "5000",
+        ])
+        .unwrap();
+
+        assert!(matches!(cli.command, Command::Pause { vault: v } if v == vault));
+        assert_eq!(cli.config.keypair.as_deref(), Some("usb://ledger"));
+        assert_eq!(cli.config.with_compute_unit_price, Some(5000));
+
+        let cli = Cli::try_parse_from([
+            "svs-cli",
+            "init",
+            "--program",
+            "svs-2",
+            "--asset-mint",
+            &Pubkey::new_unique().to_string(),
+            "--name",
+            "Vault Shares",
+            "--symbol",
+            "vSHR",
+        ])
+        .unwrap();
+        let Command::Init(args) = cli.command else {
+            panic!("expected init");
+        };
+        assert!(matches!(args.program, admin::Program::Svs2));
+        assert_eq!(args.vault_id, 0);
+        assert!(args.program_id.is_none());
+    }
+
+    #[test]
+    fn test_parse_user_commands() {
+        let vault = Pubkey::new_unique().to_string();
+
+        let cli = Cli::try_parse_from(["svs-cli", "deposit", &vault, "1000"]).unwrap();
+        let Command::Deposit {
+            assets, slippage, ..
+        } = cli.command
+        else {
+            panic!("expected deposit");
+        };
+        assert_eq!(assets, 1_000);
+        assert_eq!(slippage.slippage_bps, 50);
+
+        let cli = Cli::try_parse_from(["svs-cli", "balance", &vault, "--confidential"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Balance {
+                confidential: true,
+                ..
+            }
+        ));
+
+        let withdraw = |args: &[&str]| {
+            Cli::try_parse_from(
+                ["svs-cli", "confidential-withdraw", &vault]
+                    .iter()
+                    .chain(args),
+            )
+        };
+        assert!(withdraw(&[
+            "--shares",
+            "5",
+            "--proof-backend",
+            "https://proofs.example.com"
+        ])
+        .is_ok());
+        // Exactly one of --assets and --shares
+        assert!(withdraw(&[]).is_err());
+        assert!(withdraw(&["--assets", "5", "--shares", "5"]).is_err());
+        // An API key without a backend is a mistake
+        assert!(withdraw(&["--assets", "5", "--api-key", "key"]).is_err());
+        assert!(withdraw(&["--assets", "5", "--slippage-bps", "10001"]).is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Deposits and withdrawals, including the SVS-2 confidential sequence
+//!
+//! SVS-2 shares go through three steps: `configure-account` once, then
+//! `deposit` credits a pending balance that `apply-pending` makes spendable,
+//! and `confidential-withdraw` burns available shares with ZK proofs.
+//!
+//! Confidential keys are derived by having the signer sign messages bound to
+//! the shares account, which hardware wallets refuse; use a keypair file.
+
+use anchor_spl::{associated_token::spl_associated_token_account::instruction as ata, token_2022};
+use anyhow::{anyhow, bail, Context as _};
+use clap::Args;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    instruction::ProofInstruction, proof_data::PubkeyValidityProofData,
+};
+use svs_client::{
+    confidential::{ConfidentialAccountState, ConfidentialKeys},
+    rpc::Views,
+    svs1, svs2,
+    svs2::PubkeyValidityProof,
+    withdraw_flow::{ConfidentialWithdrawFlow, ProofSource, Withdrawal},
+    VaultKeys,
+};
+
+use crate::context::{Context, VaultState};
+
+const BPS: u128 = 10_000;
+
+#[derive(Args)]
+pub struct SlippageArgs {
+    /// Tolerated price move against the preview, in basis points
+    #[clap(
+        long,
+        default_value_t = 50,
+        value_parser = clap::value_parser!(u16).range(..=10_000),
+    )]
+    pub slippage_bps: u16,
+}
+
+impl SlippageArgs {
+    /// `amount` reduced by the slippage, for minimum outputs
+    fn min(&self, amount: u64) -> u64 {
+        (amount as u128 * (BPS - self.slippage_bps as u128) / BPS) as u64
+    }
+
+    /// `amount` increased by the slippage, for maximum inputs
+    fn max(&self, amount: u64) -> u64 {
+        let max = (amount as u128 * (BPS + self.slippage_bps as u128)).div_ceil(BPS);
+        max.min(u64::MAX as u128) as u64
+    }
+}
+
+/// Deposit `assets` into either vault kind; SVS-2 credits the pending balance
+pub fn deposit(
+    ctx: &Context,
+    vault: &Pubkey,
+    assets: u64,
+    slippage: &SlippageArgs,
+) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let user = ctx.pubkey();
+    let shares = Views::new(&ctx.client, keys, user).preview_deposit(assets)?;
+
+    let instruction = match state {
+        VaultState::Public(_) => svs1::deposit(&keys, &user, assets, slippage.min(shares)),
+        VaultState::Confidential(_) => {
+            svs2::deposit(&keys, &user, assets, slippage.min(shares), None)
+        }
+    };
+    println!("Depositing {assets} assets for about {shares} shares");
+    println!("Signature: {}", ctx.send([instruction])?);
+    if let VaultState::Confidential(_) = state {
+        println!("Shares are pending until `svs-cli apply-pending {vault}`");
+    }
+    Ok(())
+}
+
+/// Withdraw exactly `assets` from an SVS-1 vault
+pub fn withdraw(
+    ctx: &Context,
+    vault: &Pubkey,
+    assets: u64,
+    slippage: &SlippageArgs,
+) -> anyhow::Result<()> {
+    let keys = public_vault(ctx, vault)?;
+    let user = ctx.pubkey();
+    let shares = Views::new(&ctx.client, keys, user).preview_withdraw(assets)?;
+
+    let instruction = svs1::withdraw(&keys, &user, assets, slippage.max(shares));
+    println!("Withdrawing {assets} assets for about {shares} shares");
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+/// Redeem `shares` of an SVS-1 vault
+pub fn redeem(
+    ctx: &Context,
+    vault: &Pubkey,
+    shares: u64,
+    slippage: &SlippageArgs,
+) -> anyhow::Result<()> {
+    let keys = public_vault(ctx, vault)?;
+    let user = ctx.pubkey();
+    let assets = Views::new(&ctx.client, keys, user).preview_redeem(shares)?;
+
+    let instruction = svs1::redeem(&keys, &user, shares, slippage.min(assets));
+    println!("Redeeming {shares} shares for about {assets} assets");
+    println!("Signature: {}", ctx.send([instruction])?);
+    Ok(())
+}
+
+fn public_vault(ctx: &Context, vault: &Pubkey) -> anyhow::Result<VaultKeys> {
+    match ctx.vault(vault)? {
+        (keys, VaultState::Public(_)) => Ok(keys),
+        (_, VaultState::Confidential(_)) => {
+            bail!("{vault} is an SVS-2 vault, use `confidential-withdraw`")
+        }
+    }
+}
+
+fn confidential_vault(ctx: &Context, vault: &Pubkey) -> anyhow::Result<VaultKeys> {
+    match ctx.vault(vault)? {
+        (keys, VaultState::Confidential(_)) => Ok(keys),
+        (_, VaultState::Public(_)) => bail!("{vault} is an SVS-1 vault"),
+    }
+}
+
+/// Print the signer's shares: the token balance, or with `confidential` the
+/// decrypted SVS-2 balances
+pub fn balance(ctx: &Context, vault: &Pubkey, confidential: bool) -> anyhow::Result<()> {
+    let (keys, state) = ctx.vault(vault)?;
+    let user = ctx.pubkey();
+
+    if !confidential {
+        let address = keys.shares_account(&user);
+        let exists = ctx
+            .client
+            .get_account_with_commitment(&address, ctx.client.commitment())?
+            .value
+            .is_some();
+        let shares: u64 = if exists {
+            ctx.client
+                .get_token_account_balance(&address)?
+                .amount
+                .parse()?
+        } else {
+            0
+        };
+        println!("Shares account: {address}");
+        println!("Shares: {shares}");
+        if shares > 0 {
+            let assets = Views::new(&ctx.client, keys, user).preview_redeem(shares)?;
+            println!("Redeemable for: {assets} assets");
+        }
+        if let VaultState::Confidential(_) = state {
+            println!("SVS-2 shares are confidential, add --confidential to decrypt them");
+        }
+        return Ok(());
+    }
+
+    if let VaultState::Public(_) = state {
+        bail!("{vault} is an SVS-1 vault, its shares are not confidential");
+    }
+    let (encryption, state) = shares_account(ctx, &keys, &user)?;
+    let available = encryption.available_balance(&state)?;
+    let pending = encryption.pending_balance(&state)?;
+
+    println!("Shares account: {}", keys.shares_account(&user));
+    println!("Available shares: {available}");
+    println!("Pending shares: {pending}");
+    println!(
+        "Pending credit counter: {}",
+        state.pending_balance_credit_counter
+    );
+    if pending > 0 {
+        println!("Run `svs-cli apply-pending {vault}` to make pending shares spendable");
+    }
+    Ok(())
+}
+
+/// Create the signer's shares account if needed and enable confidential
+/// transfers on it, proving the derived ElGamal key in the same transaction
+pub fn configure_account(ctx: &Context, vault: &Pubkey) -> anyhow::Result<()> {
+    let keys = confidential_vault(ctx, vault)?;
+    let user = ctx.pubkey();
+    let shares_account = keys.shares_account(&user);
+    let encryption = ConfidentialKeys::derive(&ctx.signer, &shares_account)?;
+    let proof = PubkeyValidityProofData::new(&encryption.elgamal)
+        .map_err(|e| anyhow!("Failed to generate pubkey validity proof: {e}"))?;
+
+    let instructions = [
+        ata::create_associated_token_account_idempotent(
+            &user,
+            &user,
+            &keys.shares_mint,
+            &token_2022::ID,
+        ),
+        ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof),
+        // The proof is the previous instruction
+        svs2::configure_account(
+            &keys,
+            &user,
+            None,
+            encryption.encrypt_balance(0),
+            PubkeyValidityProof::InstructionOffset(-1),
+        ),
+    ];
+    println!("Shares account: {shares_account}");
+    println!("Signature: {}", ctx.send(instructions)?);
+    Ok(())
+}
+
+/// Move the signer's pending shares into their available balance
+pub fn apply_pending(ctx: &Context, vault: &Pubkey) -> anyhow::Result<()> {
+    let keys = confidential_vault(ctx, vault)?;
+    let user = ctx.pubkey();
+    let (encryption, state) = shares_account(ctx, &keys, &user)?;
+
+    let pending = encryption.pending_balance(&state)?;
+    if pending == 0 {
+        println!("No pending shares");
+        return Ok(());
+    }
+    let applied = encryption.apply_pending(&state)?;
+
+    let instruction = svs2::apply_pending(
+        &keys,
+        &user,
+        applied.new_decryptable_available_balance,
+        applied.expected_pending_balance_credit_counter,
+    );
+    println!("Applying {pending} pending shares");
+    println!("Signature: {}", ctx.send([instruction])?);
+    println!("Available shares: {}", applied.new_available_balance);
+    Ok(())
+}
+
+/// Keys and confidential state of `user`'s shares account
+fn shares_account(
+    ctx: &Context,
+    keys: &VaultKeys,
+    user: &Pubkey,
+) -> anyhow::Result<(ConfidentialKeys, ConfidentialAccountState)> {
+    let address = keys.shares_account(user);
+    let data = ctx
+        .client
+        .get_account_data(&address)
+        .with_context(|| format!("No shares account {address}, run `configure-account` first"))?;
+
+    Ok((
+        ConfidentialKeys::derive(&ctx.signer, &address)?,
+        ConfidentialAccountState::parse(&address, &data)?,
+    ))
+}
+
+#[derive(Args)]
+pub struct ConfidentialWithdrawArgs {
+    pub vault: Pubkey,
+
+    /// Withdraw exactly this many assets
+    #[clap(long, required_unless_present = "shares", conflicts_with = "shares")]
+    pub assets: Option<u64>,
+
+    /// Redeem this many shares
+    #[clap(long)]
+    pub shares: Option<u64>,
+
+    #[clap(flatten)]
+    pub slippage: SlippageArgs,
+
+    /// Generate the proofs with this proof backend instead of locally
+    #[clap(long, value_name = "URL")]
+    pub proof_backend: Option<String>,
+
+    /// API key of the proof backend
+    #[clap(long, env = "SVS_PROOF_API_KEY", requires = "proof_backend")]
+    pub api_key: Option<String>,
+}
+
+/// Withdraw or redeem available shares of an SVS-2 vault: generate the
+/// proofs, verify them into context accounts and execute, in three
+/// transactions
+pub fn confidential_withdraw(ctx: &Context, args: ConfidentialWithdrawArgs) -> anyhow::Result<()> {
+    let keys = confidential_vault(ctx, &args.vault)?;
+    let user = ctx.pubkey();
+
+    let withdrawal = match (args.assets, args.shares) {
+        (Some(assets), _) => Withdrawal::Withdraw {
+            assets,
+            memo_hash: None,
+        },
+        (None, Some(shares)) => {
+            let assets = Views::new(&ctx.client, keys, user).preview_redeem(shares)?;
+            Withdrawal::Redeem {
+                shares,
+                min_assets_out: args.slippage.min(assets),
+            }
+        }
+        (None, None) => unreachable!("clap requires --assets or --shares"),
+    };
+    let proofs = match args.proof_backend {
+        Some(url) => ProofSource::Backend {
+            url,
+            api_key: args.api_key,
+        },
+        None => ProofSource::InProcess,
+    };
+
+    let client = RpcClient::new_with_commitment(ctx.client.url(), ctx.client.commitment());
+    let mut flow = ConfidentialWithdrawFlow::new(&client, keys, proofs);
+    if let Some(price) = ctx.compute_unit_price() {
+        flow = flow.with_compute_unit_price(price);
+    }
+
+    println!("Generating proofs...");
+    let built = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(flow.run(&ctx.signer, &ctx.signer, withdrawal))?;
+
+    println!("Burning {} shares", built.shares);
+    let steps = ["Verify equality proof", "Verify range proof", "Withdraw"];
+    for (step, transaction) in steps.iter().zip(&built.transactions) {
+        let signature = ctx
+            .client
+            .send_and_confirm_transaction_with_spinner(transaction)
+            .with_context(|| {
+                format!(
+                    "{step} failed; proof context accounts {} and {} may hold rent until closed",
+                    built.proof_contexts.equality, built.proof_contexts.range
+                )
+            })?;
+        println!("{step}: {signature}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slippage_bounds() {
+        let slippage = SlippageArgs { slippage_bps: 50 };
+        assert_eq!(slippage.min(10_000), 9_950);
+        assert_eq!(slippage.max(10_000), 10_050);
+        // Maximum inputs round up
+        assert_eq!(slippage.max(1), 2);
+        assert_eq!(slippage.max(u64::MAX), u64::MAX);
+
+        let none = SlippageArgs { slippage_bps: 0 };
+        assert_eq!(none.min(123), 123);
+        assert_eq!(none.max(123), 123);
+    }
+}
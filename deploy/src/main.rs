@@ -0,0 +1,215 @@
+//! svs-deploy
+//!
+//! Initializes and configures a fleet of SVS-1 and SVS-2 vaults described by
+//! a TOML manifest, then records their addresses. Every run compares the
+//! vaults on chain with the manifest and only sends what differs, so a
+//! failed launch is finished by running it again. See README.md.
+
+mod manifest;
+mod plan;
+mod record;
+
+use std::{path::PathBuf, rc::Rc};
+
+use anyhow::{anyhow, Context as _};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use solana_clap_v3_utils::{
+    input_validators::normalize_to_url_if_moniker, keypair::signer_from_path,
+};
+use solana_cli_config::{Config, CONFIG_FILE};
+use solana_client::rpc_client::RpcClient;
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use svs_client::{decode_confidential_vault, decode_vault, VaultKeys};
+
+use manifest::{Manifest, Program, VaultSpec};
+use plan::OnChain;
+use record::{Deployed, Record};
+
+#[derive(Parser)]
+#[clap(
+    name = "svs-deploy",
+    version,
+    about = "Initialize and configure SVS vaults from a manifest"
+)]
+struct Cli {
+    /// Fleet manifest (TOML)
+    manifest: String,
+
+    /// Solana CLI config file
+    #[clap(long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[clap(short = 'u', long, value_name = "URL")]
+    url: Option<String>,
+
+    /// Signer and fee payer: keypair file, usb://ledger, prompt:// or stdin
+    #[clap(short = 'k', long, value_name = "KEYPAIR")]
+    keypair: Option<String>,
+
+    /// Address record written after the run (JSON)
+    #[clap(long, value_name = "PATH", default_value = "deployment.json")]
+    record: PathBuf,
+
+    /// Print what each vault needs without sending anything
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Priority fee, in micro-lamports per compute unit
+    #[clap(long, value_name = "MICRO_LAMPORTS")]
+    with_compute_unit_price: Option<u64>,
+}
+
+struct Deployer {
+    client: RpcClient,
+    signer: Box<dyn Signer>,
+    compute_unit_price: Option<u64>,
+    dry_run: bool,
+}
+
+fn main() {
+    let matches = Cli::command().get_matches();
+    let result = Cli::from_arg_matches(&matches)
+        .map_err(anyhow::Error::from)
+        .and_then(|cli| run(cli, &matches));
+
+    if let Err(e) = result {
+        eprintln!("Error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli, matches: &clap::ArgMatches) -> anyhow::Result<()> {
+    let manifest = Manifest::load(&cli.manifest)?;
+
+    let config = match cli.config.as_ref().or(CONFIG_FILE.as_ref()) {
+        Some(path) => Config::load(path).unwrap_or_default(),
+        None => Config::default(),
+    };
+    let url = normalize_to_url_if_moniker(cli.url.as_ref().unwrap_or(&config.json_rpc_url));
+    let keypair = cli.keypair.as_ref().unwrap_or(&config.keypair_path);
+    let mut wallet_manager: Option<Rc<RemoteWalletManager>> = None;
+    let signer = signer_from_path(matches, keypair, "keypair", &mut wallet_manager)
+        .map_err(|e| anyhow!("Failed to load signer {keypair}: {e}"))?;
+
+    let deployer = Deployer {
+        client: RpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed()),
+        signer,
+        compute_unit_price: cli.with_compute_unit_price,
+        dry_run: cli.dry_run,
+    };
+    let mut record = Record::new(url);
+    let result = manifest.vaults.iter().try_for_each(|spec| {
+        let deployed = deployer
+            .deploy(spec)
+            .with_context(|| format!("Vault {}", spec.label))?;
+        record.vaults.insert(spec.label.clone(), deployed);
+        anyhow::Ok(())
+    });
+
+    // Keep the addresses of the vaults done before a failure
+    if !cli.dry_run && !record.vaults.is_empty() {
+        record.save(&cli.record)?;
+        eprintln!(
+            "Recorded {} vaults in {}",
+            record.vaults.len(),
+            cli.record.display()
+        );
+    }
+    result
+}
+
+impl Deployer {
+    /// Bring one vault in line with `spec`
+    fn deploy(&self, spec: &VaultSpec) -> anyhow::Result<Deployed> {
+        let asset_token_program = self
+            .client
+            .get_account(&spec.asset_mint)
+            .with_context(|| format!("Failed to fetch asset mint {}", spec.asset_mint))?
+            .owner;
+        let keys = VaultKeys::derive(
+            &spec.program_id(),
+            &spec.asset_mint,
+            spec.vault_id,
+            &asset_token_program,
+        );
+
+        let on_chain = self.on_chain(spec, &keys.vault)?;
+        let signer = self.signer.pubkey();
+        let actions = plan::plan(spec, on_chain.as_ref(), &signer)?;
+
+        println!("{} ({})", spec.label, keys.vault);
+        if actions.is_empty() {
+            println!("  up to date");
+        }
+        for action in &actions {
+            if self.dry_run {
+                println!("  {action}");
+            } else {
+                let signature = self.send(action.instruction(spec, &keys, &signer))?;
+                println!("  {action}: {signature}");
+            }
+        }
+
+        let authority = spec
+            .authority
+            .or(on_chain.map(|state| state.authority))
+            .unwrap_or(signer);
+        Ok(Deployed::new(&keys, spec.vault_id, &authority))
+    }
+
+    /// Managed settings of the vault, `None` if it does not exist yet
+    fn on_chain(&self, spec: &VaultSpec, vault: &Pubkey) -> anyhow::Result<Option<OnChain>> {
+        let Some(account) = self
+            .client
+            .get_account_with_commitment(vault, self.client.commitment())?
+            .value
+        else {
+            return Ok(None);
+        };
+        let state = match spec.program {
+            Program::Svs1 => OnChain::from_vault(&decode_vault(vault, &account.data)?),
+            Program::Svs2 => {
+                OnChain::from_confidential_vault(&decode_confidential_vault(vault, &account.data)?)
+            }
+        };
+        Ok(Some(state))
+    }
+
+    /// Send `instruction` in its own transaction, with the priority fee
+    fn send(&self, instruction: Instruction) -> anyhow::Result<Signature> {
+        let mut instructions = Vec::new();
+        if let Some(price) = self.compute_unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions.push(instruction);
+
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.signer.pubkey()),
+            &[self.signer.as_ref()],
+            self.client.get_latest_blockhash()?,
+        );
+        Ok(self
+            .client
+            .send_and_confirm_transaction_with_spinner(&transaction)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+}
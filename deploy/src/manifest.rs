@@ -0,0 +1,219 @@
+//! Fleet manifest
+//!
+//! One `[[vaults]]` table per vault (see `manifest.example.toml`). The
+//! program, asset mint and vault id give the vault's address; name, symbol,
+//! uri, non-transferable shares and the auditor key are fixed at
+//! initialization; the other settings
+//! are kept up to date on every run. Settings left out are not managed.
+
+use std::{collections::HashSet, str::FromStr};
+
+use anyhow::{anyhow, bail, Context as _};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub vaults: Vec<VaultSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Program {
+    #[serde(rename = "svs-1")]
+    Svs1,
+    #[serde(rename = "svs-2")]
+    Svs2,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VaultSpec {
+    /// Key of the vault in the address record
+    pub label: String,
+    pub program: Program,
+    /// Program id, for deployments other than the canonical one
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub program_id: Option<Pubkey>,
+    #[serde(deserialize_with = "pubkey")]
+    pub asset_mint: Pubkey,
+    #[serde(default)]
+    pub vault_id: u64,
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub uri: String,
+    /// Soulbound shares that holders cannot transfer (SVS-1)
+    #[serde(default)]
+    pub non_transferable: bool,
+    /// Auditor ElGamal public key, base64 (SVS-2)
+    #[serde(default, deserialize_with = "optional_elgamal_pubkey")]
+    pub auditor: Option<[u8; 32]>,
+    /// Authority the vault is handed to once configured; the signer keeps it
+    /// when unset
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub authority: Option<Pubkey>,
+    pub paused: Option<bool>,
+    /// Pause-only guardian, `11111111111111111111111111111111` for none (SVS-2)
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub guardian: Option<Pubkey>,
+    /// Harvest settings (SVS-2)
+    pub harvest: Option<HarvestSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HarvestSpec {
+    #[serde(deserialize_with = "pubkey")]
+    pub swap_adapter: Pubkey,
+    #[serde(deserialize_with = "pubkey")]
+    pub harvester: Pubkey,
+    /// Seconds over which harvested profit unlocks
+    pub profit_unlock_period: i64,
+}
+
+impl VaultSpec {
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id.unwrap_or(match self.program {
+            Program::Svs1 => svs_1::ID,
+            Program::Svs2 => svs_2::ID,
+        })
+    }
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s).map_err(|e| D::Error::custom(format!("invalid pubkey {s}: {e}")))
+}
+
+fn optional_pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+    pubkey(deserializer).map(Some)
+}
+
+fn optional_elgamal_pubkey<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<[u8; 32]>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    BASE64
+        .decode(&s)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(Some)
+        .ok_or_else(|| D::Error::custom("auditor must be a base64 32-byte ElGamal public key"))
+}
+
+impl Manifest {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read manifest {path}: {e}"))?;
+        let manifest =
+            Self::from_toml(&contents).map_err(|e| anyhow!("Invalid manifest {path}: {e}"))?;
+        manifest
+            .validate()
+            .with_context(|| format!("Invalid manifest {path}"))?;
+        Ok(manifest)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.vaults.is_empty() {
+            bail!("No vaults");
+        }
+
+        let mut labels = HashSet::new();
+        let mut addresses = HashSet::new();
+        for vault in &self.vaults {
+            let label = &vault.label;
+            if label.is_empty() {
+                bail!("A vault has an empty label");
+            }
+            if !labels.insert(label) {
+                bail!("Duplicate label {label}");
+            }
+            if !addresses.insert((vault.program_id(), vault.asset_mint, vault.vault_id)) {
+                bail!("Vault {label} has the same program, asset mint and vault id as another");
+            }
+            if vault.program == Program::Svs1
+                && (vault.auditor.is_some() || vault.guardian.is_some() || vault.harvest.is_some())
+            {
+                bail!("Vault {label}: auditor, guardian and harvest are only supported by SVS-2");
+            }
+            if vault.program == Program::Svs2 && vault.non_transferable {
+                bail!("Vault {label}: non_transferable is only supported by SVS-1");
+            }
+            if vault
+                .harvest
+                .is_some_and(|harvest| harvest.profit_unlock_period < 0)
+            {
+                bail!("Vault {label}: profit_unlock_period must not be negative");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(contents: &str) -> anyhow::Result<Manifest> {
+        let manifest = Manifest::from_toml(contents)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    #[test]
+    fn test_example_manifest() {
+        let manifest = manifest(include_str!("../manifest.example.toml")).unwrap();
+        assert_eq!(manifest.vaults.len(), 2);
+
+        let public = &manifest.vaults[0];
+        assert_eq!(public.program, Program::Svs1);
+        assert_eq!(public.program_id(), svs_1::ID);
+        assert_eq!(public.paused, Some(false));
+        assert!(public.authority.is_some());
+
+        let confidential = &manifest.vaults[1];
+        assert_eq!(confidential.program_id(), svs_2::ID);
+        assert!(confidential.auditor.is_some());
+        assert_eq!(confidential.guardian, Some(Pubkey::default()));
+        assert_eq!(
+            confidential.harvest.unwrap().profit_unlock_period,
+            6 * 60 * 60
+        );
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        let mint = Pubkey::new_unique();
+        let vault = |label: &str, program: &str, extra: &str| {
+            format!(
+                "[[vaults]]\nlabel = \"{label}\"\nprogram = \"{program}\"\n\
+                 asset_mint = \"{mint}\"\nname = \"Vault\"\nsymbol = \"sv\"\n{extra}\n"
+            )
+        };
+
+        assert!(manifest(&vault("a", "svs-1", "")).is_ok());
+        assert!(manifest("vaults = []").is_err());
+        // Duplicate labels, and the same vault address twice
+        let a = vault("a", "svs-1", "");
+        assert!(manifest(&(a.clone() + &vault("a", "svs-1", "vault_id = 1"))).is_err());
+        assert!(manifest(&(a.clone() + &vault("b", "svs-1", ""))).is_err());
+        assert!(manifest(&(a + &vault("b", "svs-2", ""))).is_ok());
+        // SVS-2 settings on an SVS-1 vault
+        let guardian = format!("guardian = \"{}\"", Pubkey::new_unique());
+        assert!(manifest(&vault("a", "svs-1", &guardian)).is_err());
+        assert!(manifest(&vault("a", "svs-2", &guardian)).is_ok());
+        // Confidential shares can't be soulbound
+        assert!(manifest(&vault("a", "svs-1", "non_transferable = true")).is_ok());
+        assert!(manifest(&vault("a", "svs-2", "non_transferable = true")).is_err());
+        // The programs have no fees or caps
+        assert!(manifest(&vault("a", "svs-1", "deposit_cap = 5")).is_err());
+        // Not a 32-byte ElGamal public key
+        assert!(manifest(&vault("a", "svs-2", "auditor = \"AAAA\"")).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! Instructions that bring a vault in line with its manifest entry
+//!
+//! A missing vault is initialized by the signer, so its settings start from
+//! the program defaults. Each setting that differs from the manifest gets its
+//! admin instruction, and the authority is handed over last. Vaults that
+//! already match need nothing, which makes reruns idempotent.
+
+use std::fmt;
+
+use anyhow::bail;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use svs_client::{svs1, svs2, ConfidentialVault, Vault, VaultKeys};
+
+use crate::manifest::{HarvestSpec, Program, VaultSpec};
+
+/// Managed settings of a deployed vault
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChain {
+    pub authority: Pubkey,
+    pub paused: bool,
+    /// `None` for SVS-1 vaults
+    pub confidential: Option<ConfidentialSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfidentialSettings {
+    pub auditor: Option<[u8; 32]>,
+    pub guardian: Pubkey,
+    pub harvest: HarvestSpec,
+}
+
+impl OnChain {
+    pub fn from_vault(vault: &Vault) -> Self {
+        Self {
+            authority: vault.authority,
+            paused: vault.paused,
+            confidential: None,
+        }
+    }
+
+    pub fn from_confidential_vault(vault: &ConfidentialVault) -> Self {
+        Self {
+            authority: vault.authority,
+            paused: vault.paused,
+            confidential: Some(ConfidentialSettings {
+                auditor: vault.auditor_elgamal_pubkey,
+                guardian: vault.guardian,
+                harvest: HarvestSpec {
+                    swap_adapter: vault.swap_adapter,
+                    harvester: vault.harvester,
+                    profit_unlock_period: vault.profit_unlock_period,
+                },
+            }),
+        }
+    }
+
+    /// Settings right after `signer` initializes the vault of `spec`
+    fn initialized(spec: &VaultSpec, signer: &Pubkey) -> Self {
+        Self {
+            authority: *signer,
+            paused: false,
+            confidential: (spec.program == Program::Svs2).then(|| ConfidentialSettings {
+                auditor: spec.auditor,
+                guardian: Pubkey::default(),
+                harvest: HarvestSpec {
+                    swap_adapter: Pubkey::default(),
+                    harvester: Pubkey::default(),
+                    profit_unlock_period: svs_2::constants::DEFAULT_PROFIT_UNLOCK_PERIOD,
+                },
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Initialize,
+    SetGuardian(Pubkey),
+    SetHarvestConfig(HarvestSpec),
+    Pause,
+    Unpause,
+    TransferAuthority(Pubkey),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Initialize => write!(f, "initialize"),
+            Action::SetGuardian(guardian) => write!(f, "set guardian to {guardian}"),
+            Action::SetHarvestConfig(harvest) => write!(
+                f,
+                "set swap adapter {}, harvester {}, profit unlock period {}s",
+                harvest.swap_adapter, harvest.harvester, harvest.profit_unlock_period
+            ),
+            Action::Pause => write!(f, "pause"),
+            Action::Unpause => write!(f, "unpause"),
+            Action::TransferAuthority(authority) => write!(f, "transfer authority to {authority}"),
+        }
+    }
+}
+
+impl Action {
+    /// The instruction carrying out the action, signed by `signer`
+    pub fn instruction(&self, spec: &VaultSpec, keys: &VaultKeys, signer: &Pubkey) -> Instruction {
+        match (spec.program, self) {
+            (Program::Svs1, Action::Initialize) => svs1::initialize(
+                keys,
+                signer,
+                spec.vault_id,
+                spec.name.clone(),
+                spec.symbol.clone(),
+                spec.uri.clone(),
+                spec.non_transferable,
+            ),
+            (Program::Svs2, Action::Initialize) => svs2::initialize(
+                keys,
+                signer,
+                signer,
+                spec.vault_id,
+                spec.name.clone(),
+                spec.symbol.clone(),
+                spec.uri.clone(),
+                spec.auditor,
+            ),
+            (_, Action::SetGuardian(guardian)) => svs2::set_guardian(keys, signer, *guardian),
+            (_, Action::SetHarvestConfig(harvest)) => svs2::set_harvest_config(
+                keys,
+                signer,
+                harvest.swap_adapter,
+                harvest.harvester,
+                harvest.profit_unlock_period,
+            ),
+            (Program::Svs1, Action::Pause) => svs1::pause(keys, signer),
+            (Program::Svs2, Action::Pause) => svs2::pause(keys, signer),
+            (Program::Svs1, Action::Unpause) => svs1::unpause(keys, signer),
+            (Program::Svs2, Action::Unpause) => svs2::unpause(keys, signer),
+            (Program::Svs1, Action::TransferAuthority(authority)) => {
+                svs1::transfer_authority(keys, signer, *authority)
+            }
+            (Program::Svs2, Action::TransferAuthority(authority)) => {
+                svs2::transfer_authority(keys, signer, *authority)
+            }
+        }
+    }
+}
+
+/// Actions taking the vault from `on_chain` (`None` if it does not exist) to
+/// `spec`, sent by `signer`
+pub fn plan(
+    spec: &VaultSpec,
+    on_chain: Option<&OnChain>,
+    signer: &Pubkey,
+) -> anyhow::Result<Vec<Action>> {
+    let label = &spec.label;
+    let mut actions = Vec::new();
+    let state = match on_chain {
+        Some(state) => state.clone(),
+        None => {
+            actions.push(Action::Initialize);
+            OnChain::initialized(spec, signer)
+        }
+    };
+    let created = actions.len();
+
+    match (&state.confidential, spec.program) {
+        (None, Program::Svs2) => bail!("Vault {label} exists as an SVS-1 vault"),
+        (Some(_), Program::Svs1) => bail!("Vault {label} exists as an SVS-2 vault"),
+        (None, Program::Svs1) => {}
+        (Some(confidential), Program::Svs2) => {
+            if confidential.auditor != spec.auditor {
+                bail!("Vault {label} has another auditor, which is fixed at initialization");
+            }
+            if let Some(guardian) = spec.guardian.filter(|g| *g != confidential.guardian) {
+                actions.push(Action::SetGuardian(guardian));
+            }
+            if let Some(harvest) = spec.harvest.filter(|h| *h != confidential.harvest) {
+                actions.push(Action::SetHarvestConfig(harvest));
+            }
+        }
+    }
+    match spec.paused {
+        Some(true) if !state.paused => actions.push(Action::Pause),
+        Some(false) if state.paused => actions.push(Action::Unpause),
+        _ => {}
+    }
+    let authority = spec
+        .authority
+        .filter(|authority| *authority != state.authority);
+
+    if (actions.len() > created || authority.is_some()) && state.authority != *signer {
+        bail!(
+            "Vault {label} differs from the manifest, but its authority is {}, not the signer",
+            state.authority
+        );
+    }
+    actions.extend(authority.map(Action::TransferAuthority));
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_spec(program: Program) -> VaultSpec {
+        VaultSpec {
+            label: "usdc".to_string(),
+            program,
+            program_id: None,
+            asset_mint: Pubkey::new_unique(),
+            vault_id: 1,
+            name: "SVS USDC".to_string(),
+            symbol: "svUSDC".to_string(),
+            uri: String::new(),
+            non_transferable: false,
+            auditor: None,
+            authority: None,
+            paused: None,
+            guardian: None,
+            harvest: None,
+        }
+    }
+
+    #[test]
+    fn test_new_vault() {
+        let signer = Pubkey::new_unique();
+        let multisig = Pubkey::new_unique();
+        let harvest = HarvestSpec {
+            swap_adapter: Pubkey::new_unique(),
+            harvester: Pubkey::new_unique(),
+            profit_unlock_period: 3_600,
+        };
+        let spec = VaultSpec {
+            auditor: Some([7; 32]),
+            authority: Some(multisig),
+            paused: Some(false),
+            guardian: Some(Pubkey::default()),
+            harvest: Some(harvest),
+            ..vault_spec(Program::Svs2)
+        };
+
+        // Unpaused and without guardian already after initialization
+        assert_eq!(
+            plan(&spec, None, &signer).unwrap(),
+            [
+                Action::Initialize,
+                Action::SetHarvestConfig(harvest),
+                Action::TransferAuthority(multisig),
+            ]
+        );
+
+        // Once done, the multisig holds the vault and nothing is left
+        let deployed = OnChain {
+            authority: multisig,
+            paused: false,
+            confidential: Some(ConfidentialSettings {
+                auditor: Some([7; 32]),
+                guardian: Pubkey::default(),
+                harvest,
+            }),
+        };
+        assert!(plan(&spec, Some(&deployed), &signer).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_updates() {
+        let signer = Pubkey::new_unique();
+        let on_chain = OnChain {
+            authority: signer,
+            paused: false,
+            confidential: None,
+        };
+        let spec = VaultSpec {
+            paused: Some(true),
+            ..vault_spec(Program::Svs1)
+        };
+        assert_eq!(
+            plan(&spec, Some(&on_chain), &signer).unwrap(),
+            [Action::Pause]
+        );
+        assert!(plan(&vault_spec(Program::Svs1), Some(&on_chain), &signer)
+            .unwrap()
+            .is_empty());
+
+        // Changes need the authority's signature
+        assert!(plan(&spec, Some(&on_chain), &Pubkey::new_unique()).is_err());
+        // The auditor and the program can't change
+        assert!(plan(&vault_spec(Program::Svs2), Some(&on_chain), &signer).is_err());
+        let confidential = OnChain::initialized(&vault_spec(Program::Svs2), &signer);
+        let audited = VaultSpec {
+            auditor: Some([1; 32]),
+            ..vault_spec(Program::Svs2)
+        };
+        assert!(plan(&audited, Some(&confidential), &signer).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Address record
+//!
+//! Written after every run that sends transactions: the addresses of each
+//! vault the run got to, by manifest label, for launch scripts and frontends.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use svs_client::VaultKeys;
+
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub rpc_url: String,
+    pub vaults: BTreeMap<String, Deployed>,
+}
+
+/// A vault of the manifest, as deployed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Deployed {
+    pub program_id: String,
+    pub vault: String,
+    pub vault_id: u64,
+    pub asset_mint: String,
+    pub asset_token_program: String,
+    pub shares_mint: String,
+    pub asset_vault: String,
+    pub authority: String,
+}
+
+impl Deployed {
+    pub fn new(keys: &VaultKeys, vault_id: u64, authority: &Pubkey) -> Self {
+        Self {
+            program_id: keys.program_id.to_string(),
+            vault: keys.vault.to_string(),
+            vault_id,
+            asset_mint: keys.asset_mint.to_string(),
+            asset_token_program: keys.asset_token_program.to_string(),
+            shares_mint: keys.shares_mint.to_string(),
+            asset_vault: keys.asset_vault.to_string(),
+            authority: authority.to_string(),
+        }
+    }
+}
+
+impl Record {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc_url,
+            vaults: BTreeMap::new(),
+        }
+    }
+
+    /// Write the record as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").with_context(|| format!("Writing {}", path.display()))
+    }
+}
@@ -0,0 +1,381 @@
+//! The SVS-2 confidential lifecycle, step by step
+//!
+//! A fresh asset mint gets a fresh vault, so runs never collide, even
+//! against a long-running validator. A 6-decimal asset gives a decimals
+//! offset of 3, so every asset is worth exactly 1_000 shares while no yield
+//! accrues and each step has exact expected balances.
+//!
+//! 1. Configure the user's shares account, proving the ElGamal key inline
+//! 2. Deposit; the shares land in the pending balance
+//! 3. Apply the pending balance
+//! 4. Withdraw part of the assets through `ConfidentialWithdrawFlow`
+//! 5. Redeem the remaining shares, emptying the vault
+
+use anchor_spl::{
+    associated_token::{
+        self, spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    },
+    token::{self, spl_token},
+    token_2022,
+};
+use anyhow::{anyhow, ensure, Context as _};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    self, instruction::ProofInstruction, proof_data::PubkeyValidityProofData,
+};
+use svs_client::{
+    confidential::{ConfidentialAccountState, ConfidentialKeys},
+    rpc::nonblocking::fetch_confidential_vault,
+    svs2::{self, PubkeyValidityProof},
+    withdraw_flow::{ConfidentialWithdrawFlow, ProofSource, Withdrawal},
+    VaultKeys,
+};
+
+const ASSET_DECIMALS: u8 = 6;
+/// Shares minted per asset: 10^(9 - ASSET_DECIMALS)
+const SHARES_PER_ASSET: u64 = 1_000;
+const VAULT_ID: u64 = 1;
+
+const DEPOSIT_ASSETS: u64 = 1_000_000;
+const WITHDRAW_ASSETS: u64 = 400_000;
+
+const PAYER_LAMPORTS: u64 = 10 * LAMPORTS_PER_SOL;
+const USER_LAMPORTS: u64 = LAMPORTS_PER_SOL;
+
+/// Compute limit of transactions running confidential Token-2022
+/// instructions
+const CONFIDENTIAL_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Everything the lifecycle asserts after each step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Balances {
+    user_assets: u64,
+    pending_shares: u64,
+    available_shares: u64,
+    total_assets: u64,
+    /// Shares mint supply
+    total_shares: u64,
+    asset_vault: u64,
+}
+
+/// Check that the validator runs the vault programs and everything they
+/// depend on
+pub async fn preflight(client: &RpcClient) -> anyhow::Result<()> {
+    for (program_id, name) in [
+        (svs_1::ID, "SVS-1"),
+        (svs_2::ID, "SVS-2"),
+        (token::ID, "SPL Token"),
+        (token_2022::ID, "Token-2022"),
+        (associated_token::ID, "Associated Token"),
+    ] {
+        ensure!(
+            is_executable(client, &program_id).await?,
+            "{name} program {program_id} is not deployed"
+        );
+    }
+
+    let zk_program = zk_elgamal_proof_program::id();
+    ensure!(
+        is_executable(client, &zk_program).await?,
+        "ZK ElGamal proof program {zk_program} is not active; if a feature disables it \
+         on this validator version, pass `-- --deactivate-feature <FEATURE_ID>`"
+    );
+    Ok(())
+}
+
+/// Run the whole lifecycle with a new user, paid for by `payer`
+pub async fn run(client: &RpcClient, payer: &Keypair) -> anyhow::Result<()> {
+    airdrop(client, &payer.pubkey(), PAYER_LAMPORTS).await?;
+    let user = Keypair::new();
+    airdrop(client, &user.pubkey(), USER_LAMPORTS).await?;
+
+    let asset_mint = create_asset_mint(client, payer).await?;
+    let keys = VaultKeys::derive(&svs_2::ID, &asset_mint, VAULT_ID, &token::ID);
+    let user_assets = keys.asset_account(&user.pubkey());
+    let fund_user = [
+        create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &user.pubkey(),
+            &asset_mint,
+            &token::ID,
+        ),
+        spl_token::instruction::mint_to(
+            &token::ID,
+            &asset_mint,
+            &user_assets,
+            &payer.pubkey(),
+            &[],
+            DEPOSIT_ASSETS,
+        )?,
+    ];
+    send(client, payer, &fund_user, &[]).await?;
+
+    let initialize = svs2::initialize(
+        &keys,
+        &payer.pubkey(),
+        &payer.pubkey(),
+        VAULT_ID,
+        "SVS-2 E2E Vault".to_string(),
+        "csE2E".to_string(),
+        "https://example.com/svs-2-e2e.json".to_string(),
+        None,
+    );
+    send(client, payer, &[initialize], &[]).await?;
+    println!("Vault: {}", keys.vault);
+
+    let shares_account = keys.shares_account(&user.pubkey());
+    let encryption = ConfidentialKeys::derive(&user, &shares_account)?;
+    let proof = PubkeyValidityProofData::new(&encryption.elgamal)
+        .map_err(|e| anyhow!("Failed to generate pubkey validity proof: {e}"))?;
+    let configure = [
+        create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &user.pubkey(),
+            &keys.shares_mint,
+            &token_2022::ID,
+        ),
+        ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof),
+        // The proof is the previous instruction
+        svs2::configure_account(
+            &keys,
+            &user.pubkey(),
+            Some(&payer.pubkey()),
+            encryption.encrypt_balance(0),
+            PubkeyValidityProof::InstructionOffset(-1),
+        ),
+    ];
+    send(client, payer, &configure, &[&user]).await?;
+    let mut expected = Balances {
+        user_assets: DEPOSIT_ASSETS,
+        pending_shares: 0,
+        available_shares: 0,
+        total_assets: 0,
+        total_shares: 0,
+        asset_vault: 0,
+    };
+    check(client, &keys, &user, &encryption, "configure", expected).await?;
+
+    let deposit_shares = DEPOSIT_ASSETS * SHARES_PER_ASSET;
+    let deposit = svs2::deposit(&keys, &user.pubkey(), DEPOSIT_ASSETS, deposit_shares, None);
+    send(client, payer, &[compute_limit(), deposit], &[&user]).await?;
+    expected = Balances {
+        user_assets: 0,
+        pending_shares: deposit_shares,
+        total_assets: DEPOSIT_ASSETS,
+        total_shares: deposit_shares,
+        asset_vault: DEPOSIT_ASSETS,
+        ..expected
+    };
+    check(client, &keys, &user, &encryption, "deposit", expected).await?;
+
+    let state = shares_state(client, &shares_account).await?;
+    let application = encryption.apply_pending(&state)?;
+    let apply = svs2::apply_pending(
+        &keys,
+        &user.pubkey(),
+        application.new_decryptable_available_balance,
+        application.expected_pending_balance_credit_counter,
+    );
+    send(client, payer, &[compute_limit(), apply], &[&user]).await?;
+    expected = Balances {
+        pending_shares: 0,
+        available_shares: deposit_shares,
+        ..expected
+    };
+    check(client, &keys, &user, &encryption, "apply_pending", expected).await?;
+
+    let flow = ConfidentialWithdrawFlow::new(client, keys, ProofSource::InProcess);
+    let withdraw_shares = WITHDRAW_ASSETS * SHARES_PER_ASSET;
+    let withdrawal = Withdrawal::Withdraw {
+        assets: WITHDRAW_ASSETS,
+        memo_hash: None,
+    };
+    let burned = run_withdrawal(client, &flow, &user, payer, withdrawal).await?;
+    ensure!(
+        burned == withdraw_shares,
+        "withdraw: expected to burn {withdraw_shares} shares, burned {burned}"
+    );
+    let remaining_assets = DEPOSIT_ASSETS - WITHDRAW_ASSETS;
+    let remaining_shares = deposit_shares - withdraw_shares;
+    expected = Balances {
+        user_assets: WITHDRAW_ASSETS,
+        available_shares: remaining_shares,
+        total_assets: remaining_assets,
+        total_shares: remaining_shares,
+        asset_vault: remaining_assets,
+        ..expected
+    };
+    check(client, &keys, &user, &encryption, "withdraw", expected).await?;
+
+    let redemption = Withdrawal::Redeem {
+        shares: remaining_shares,
+        min_assets_out: remaining_assets,
+    };
+    run_withdrawal(client, &flow, &user, payer, redemption).await?;
+    expected = Balances {
+        user_assets: DEPOSIT_ASSETS,
+        available_shares: 0,
+        total_assets: 0,
+        total_shares: 0,
+        asset_vault: 0,
+        ..expected
+    };
+    check(client, &keys, &user, &encryption, "redeem", expected).await?;
+
+    Ok(())
+}
+
+/// Send the transactions of a confidential withdrawal in order and check
+/// that the proof context accounts were closed; returns the burned shares
+async fn run_withdrawal(
+    client: &RpcClient,
+    flow: &ConfidentialWithdrawFlow<'_>,
+    user: &Keypair,
+    payer: &Keypair,
+    withdrawal: Withdrawal,
+) -> anyhow::Result<u64> {
+    let built = flow.run(user, payer, withdrawal).await?;
+    let steps = ["Verify equality proof", "Verify range proof", "Withdraw"];
+    for (step, transaction) in steps.iter().zip(&built.transactions) {
+        client
+            .send_and_confirm_transaction(transaction)
+            .await
+            .with_context(|| format!("{withdrawal:?}: {step} failed"))?;
+    }
+
+    for context in [built.proof_contexts.equality, built.proof_contexts.range] {
+        let account = client
+            .get_account_with_commitment(&context, client.commitment())
+            .await?
+            .value;
+        ensure!(
+            account.is_none(),
+            "Proof context account {context} still open"
+        );
+    }
+    Ok(built.shares)
+}
+
+/// Compare the on-chain balances with `expected`
+async fn check(
+    client: &RpcClient,
+    keys: &VaultKeys,
+    user: &Keypair,
+    encryption: &ConfidentialKeys,
+    step: &str,
+    expected: Balances,
+) -> anyhow::Result<()> {
+    let state = shares_state(client, &keys.shares_account(&user.pubkey())).await?;
+    let vault = fetch_confidential_vault(client, &keys.vault).await?;
+    let actual = Balances {
+        user_assets: token_balance(client, &keys.asset_account(&user.pubkey())).await?,
+        pending_shares: encryption.pending_balance(&state)?,
+        available_shares: encryption.available_balance(&state)?,
+        total_assets: vault.total_assets,
+        total_shares: token_supply(client, &keys.shares_mint).await?,
+        asset_vault: token_balance(client, &keys.asset_vault).await?,
+    };
+    ensure!(
+        actual == expected,
+        "after {step}:\n  expected {expected:?}\n  got      {actual:?}"
+    );
+    println!("{step}: ok {actual:?}");
+    Ok(())
+}
+
+/// SPL Token mint with the payer as mint authority
+async fn create_asset_mint(client: &RpcClient, payer: &Keypair) -> anyhow::Result<Pubkey> {
+    let mint = Keypair::new();
+    let space = spl_token::state::Mint::LEN;
+    let rent = client.get_minimum_balance_for_rent_exemption(space).await?;
+    let instructions = [
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent,
+            space as u64,
+            &token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(
+            &token::ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            None,
+            ASSET_DECIMALS,
+        )?,
+    ];
+    send(client, payer, &instructions, &[&mint]).await?;
+    Ok(mint.pubkey())
+}
+
+async fn shares_state(
+    client: &RpcClient,
+    shares_account: &Pubkey,
+) -> anyhow::Result<ConfidentialAccountState> {
+    let data = client.get_account_data(shares_account).await?;
+    Ok(ConfidentialAccountState::parse(shares_account, &data)?)
+}
+
+async fn token_balance(client: &RpcClient, account: &Pubkey) -> anyhow::Result<u64> {
+    let amount = client.get_token_account_balance(account).await?.amount;
+    amount
+        .parse()
+        .with_context(|| format!("Invalid balance {amount} of {account}"))
+}
+
+async fn token_supply(client: &RpcClient, mint: &Pubkey) -> anyhow::Result<u64> {
+    let amount = client.get_token_supply(mint).await?.amount;
+    amount
+        .parse()
+        .with_context(|| format!("Invalid supply {amount} of {mint}"))
+}
+
+async fn is_executable(client: &RpcClient, program_id: &Pubkey) -> anyhow::Result<bool> {
+    Ok(client
+        .get_account_with_commitment(program_id, client.commitment())
+        .await?
+        .value
+        .is_some_and(|account| account.executable))
+}
+
+async fn airdrop(client: &RpcClient, to: &Pubkey, lamports: u64) -> anyhow::Result<()> {
+    let signature = client.request_airdrop(to, lamports).await?;
+    client
+        .poll_for_signature(&signature)
+        .await
+        .with_context(|| format!("Airdrop to {to} not confirmed"))?;
+    Ok(())
+}
+
+/// Send `instructions` paid for by `payer` and signed by `signers`
+async fn send(
+    client: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> anyhow::Result<Signature> {
+    let mut all_signers = vec![payer];
+    all_signers.extend(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        client.get_latest_blockhash().await?,
+    );
+    Ok(client.send_and_confirm_transaction(&transaction).await?)
+}
+
+fn compute_limit() -> Instruction {
+    ComputeBudgetInstruction::set_compute_unit_limit(CONFIDENTIAL_COMPUTE_UNITS)
+}
@@ -0,0 +1,91 @@
+//! svs-e2e
+//!
+//! End-to-end regression gate for SVS-2: starts a `solana-test-validator`
+//! with the compiled vault programs, then runs the confidential lifecycle
+//! (configure, deposit, apply pending, withdraw, redeem) against it and
+//! asserts every balance on the way. Exits non-zero on the first mismatch.
+//! See README.md.
+
+mod lifecycle;
+mod validator;
+
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Keypair};
+
+use validator::{TestValidator, ValidatorConfig};
+
+#[derive(Parser)]
+#[clap(
+    name = "svs-e2e",
+    about = "Run the SVS-2 confidential lifecycle against a local validator"
+)]
+struct Cli {
+    /// Use this running validator instead of starting one; the vault programs
+    /// must be deployed at their declared ids
+    #[clap(short = 'u', long, value_name = "URL")]
+    url: Option<String>,
+
+    /// solana-test-validator executable
+    #[clap(long, default_value = "solana-test-validator", value_name = "PATH")]
+    validator: PathBuf,
+
+    /// Directory holding svs_1.so and svs_2.so [default: target/deploy]
+    #[clap(long, env = "SBF_OUT_DIR", value_name = "DIR")]
+    program_dir: Option<PathBuf>,
+
+    /// Ledger of the started validator, wiped on start
+    #[clap(long, default_value = "test-ledger-e2e", value_name = "DIR")]
+    ledger: PathBuf,
+
+    #[clap(long, default_value = "8899")]
+    rpc_port: u16,
+
+    /// Seconds to wait for the started validator to become healthy
+    #[clap(long, default_value = "60", value_name = "SECONDS")]
+    startup_timeout: u64,
+
+    /// Extra solana-test-validator arguments, e.g.
+    /// `-- --deactivate-feature <FEATURE_ID>`
+    #[clap(last = true)]
+    validator_args: Vec<String>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("Error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // The validator is kept alive until the lifecycle is done; dropping it
+    // kills the process
+    let (url, _validator) = match cli.url {
+        Some(url) => (url, None),
+        None => {
+            let config = ValidatorConfig {
+                binary: cli.validator,
+                program_dir: cli.program_dir.unwrap_or_else(|| {
+                    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/deploy")
+                }),
+                ledger: cli.ledger,
+                rpc_port: cli.rpc_port,
+                extra_args: cli.validator_args,
+                startup_timeout: Duration::from_secs(cli.startup_timeout),
+            };
+            println!("Starting {}...", config.binary.display());
+            let validator = TestValidator::start(&config).await?;
+            (validator.rpc_url().to_string(), Some(validator))
+        }
+    };
+
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    lifecycle::preflight(&client).await?;
+    lifecycle::run(&client, &Keypair::new()).await?;
+    println!("SVS-2 lifecycle passed");
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! `solana-test-validator` with the vault programs preloaded
+//!
+//! The validator runs from a fresh ledger with `svs_1.so` and `svs_2.so`
+//! loaded at their declared program ids. SPL Token, Token-2022 and Associated
+//! Token are part of its genesis; the ZK ElGamal proof program is a builtin,
+//! active unless a feature disables it (checked by
+//! [`crate::lifecycle::preflight`]). The process is killed on drop.
+
+use std::{
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, ensure, Context as _};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+/// Vault programs and the `.so` files they are loaded from
+const PROGRAMS: [(Pubkey, &str); 2] = [(svs_1::ID, "svs_1.so"), (svs_2::ID, "svs_2.so")];
+
+/// How often readiness is polled while the validator starts
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct ValidatorConfig {
+    /// `solana-test-validator` executable
+    pub binary: PathBuf,
+    /// Directory holding `svs_1.so` and `svs_2.so`
+    pub program_dir: PathBuf,
+    /// Ledger directory, wiped on start; `validator.log` is written here
+    pub ledger: PathBuf,
+    pub rpc_port: u16,
+    /// Passed to the validator as is, e.g. `--deactivate-feature <id>`
+    pub extra_args: Vec<String>,
+    /// How long to wait for the RPC to report healthy
+    pub startup_timeout: Duration,
+}
+
+/// A running validator, killed when dropped
+pub struct TestValidator {
+    child: Child,
+    rpc_url: String,
+}
+
+impl TestValidator {
+    /// Start the validator and wait until its RPC is healthy
+    pub async fn start(config: &ValidatorConfig) -> anyhow::Result<Self> {
+        let mut command = Command::new(&config.binary);
+        command
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(&config.ledger)
+            .arg("--rpc-port")
+            .arg(config.rpc_port.to_string());
+        for (program_id, file) in PROGRAMS {
+            let path = config.program_dir.join(file);
+            ensure!(
+                path.is_file(),
+                "{} not found; run `anchor build` or pass --program-dir",
+                path.display()
+            );
+            command
+                .arg("--bpf-program")
+                .arg(program_id.to_string())
+                .arg(path);
+        }
+        command
+            .args(&config.extra_args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit());
+
+        let child = command
+            .spawn()
+            .with_context(|| format!("Failed to start {}", config.binary.display()))?;
+        let mut validator = Self {
+            child,
+            rpc_url: format!("http://127.0.0.1:{}", config.rpc_port),
+        };
+        validator.wait_until_healthy(config).await?;
+        Ok(validator)
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    async fn wait_until_healthy(&mut self, config: &ValidatorConfig) -> anyhow::Result<()> {
+        let client = RpcClient::new(self.rpc_url.clone());
+        let deadline = Instant::now() + config.startup_timeout;
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                bail!(
+                    "Validator exited with {status}; see {}",
+                    config.ledger.join("validator.log").display()
+                );
+            }
+            if client.get_health().await.is_ok() {
+                return Ok(());
+            }
+            if Instant::now() > deadline {
+                bail!(
+                    "Validator not healthy after {:?}; see {}",
+                    config.startup_timeout,
+                    config.ledger.join("validator.log").display()
+                );
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,86 @@
+//! Events emitted with `emit_cpi!`
+//!
+//! The program invokes itself, signed by its event authority PDA, with the
+//! instruction data `EVENT_IX_TAG_LE || discriminator || event`. Only the
+//! program can sign for that PDA, so such an inner instruction of an SVS
+//! program in a successful transaction is always a genuine event. Events are
+//! not subject to the runtime's log limit this way.
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::VaultEvent;
+
+/// Prefix of every `emit_cpi!` instruction: Anchor's `EVENT_IX_TAG`,
+/// little-endian
+pub const EVENT_IX_TAG_LE: [u8; 8] = 0x1d9a_cb51_2ea5_45e4_u64.to_le_bytes();
+
+/// Decode the data of an event instruction, or `None` for anything else
+pub fn decode_event_instruction(data: &[u8]) -> Option<VaultEvent> {
+    VaultEvent::decode(data.strip_prefix(EVENT_IX_TAG_LE.as_slice())?)
+}
+
+/// Events among a successful transaction's inner instructions, given as
+/// `(program_id, data)` in execution order, with the emitting program
+pub fn decode_inner_instructions<'a>(
+    instructions: impl IntoIterator<Item = (Pubkey, &'a [u8])>,
+    programs: &[Pubkey],
+) -> Vec<(Pubkey, VaultEvent)> {
+    instructions
+        .into_iter()
+        .filter(|(program_id, _)| programs.contains(program_id))
+        .filter_map(|(program_id, data)| Some((program_id, decode_event_instruction(data)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Event;
+
+    use super::*;
+
+    fn event_instruction_data(event: &impl Event) -> Vec<u8> {
+        [EVENT_IX_TAG_LE.as_slice(), &event.data()].concat()
+    }
+
+    #[test]
+    fn test_event_ix_tag_matches_anchor() {
+        assert_eq!(
+            EVENT_IX_TAG_LE.as_slice(),
+            anchor_lang::event::EVENT_IX_TAG_LE
+        );
+    }
+
+    #[test]
+    fn test_decode_inner_instructions() {
+        let vault = Pubkey::new_unique();
+        let harvested = svs_2::events::Harvested {
+            vault,
+            reward_mint: Pubkey::new_unique(),
+            reward_amount: 10,
+            assets_added: 9,
+            locked_profit: 9,
+            op_counter: 3,
+            sequence: 3,
+            timestamp: 1_700_000_000,
+        };
+        let event_data = event_instruction_data(&harvested);
+        // The bare event, as logged, is not an event instruction
+        let logged_data = harvested.data();
+        let other = Pubkey::new_unique();
+
+        let instructions = [
+            (other, event_data.as_slice()),
+            (svs_2::ID, logged_data.as_slice()),
+            (svs_2::ID, &event_data[..20]),
+            (svs_2::ID, event_data.as_slice()),
+        ];
+        let events = decode_inner_instructions(instructions, &[svs_1::ID, svs_2::ID]);
+
+        let [(program_id, VaultEvent::Harvested(decoded))] = events.as_slice() else {
+            panic!("not one harvest: {events:?}");
+        };
+        assert_eq!(*program_id, svs_2::ID);
+        assert_eq!(decoded.vault, vault);
+        assert_eq!(decoded.assets_added, 9);
+    }
+}
@@ -0,0 +1,110 @@
+//! Typed events of SVS-1 and SVS-2 vaults
+//!
+//! Anchor programs publish events in two ways: `emit!` logs the event as a
+//! `Program data: <base64>` line ([`logs`]), and `emit_cpi!` invokes the
+//! program itself with the event as instruction data ([`cpi`]). Either way
+//! the payload is the event discriminator followed by the Borsh-encoded
+//! event, decoded here into the [`VaultEvent`] types of `svs-interface`.
+//!
+//! [`parse_transaction`] finds both kinds in a fetched transaction and tags
+//! each event with its signature and slot. Log notifications carry no
+//! instructions, so [`parse_logs`] only finds the logged ones.
+//!
+//! ```ignore
+//! let transaction = rpc.get_transaction_with_config(&signature, config).await?;
+//! for parsed in svs_events::parse_transaction(&transaction, &[svs_1::ID, svs_2::ID])? {
+//!     if let VaultEvent::Deposit(deposit) = &parsed.event {
+//!         let slot = parsed.context.slot;
+//!         println!("{} deposited {} in slot {slot}", deposit.owner, deposit.assets);
+//!     }
+//! }
+//! ```
+//!
+//! SVS-1 events are a subset of SVS-2's with identical layouts and
+//! discriminators, so one set of types decodes both programs.
+
+pub mod cpi;
+pub mod logs;
+pub mod transaction;
+
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+pub use svs_interface::events::*;
+pub use transaction::parse_transaction;
+
+/// How an event was published
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventSource {
+    /// `emit!`: a `Program data:` log line
+    Log,
+    /// `emit_cpi!`: an inner instruction of the program to itself
+    Cpi,
+}
+
+/// The transaction an event was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventContext {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Unknown for log notifications and recent blocks
+    pub block_time: Option<i64>,
+}
+
+/// One decoded event and where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedEvent {
+    pub context: EventContext,
+    /// Position among the transaction's events: logged events in log order,
+    /// then CPI events in instruction order
+    pub index: usize,
+    /// Program that emitted the event
+    pub program_id: Pubkey,
+    pub source: EventSource,
+    pub event: VaultEvent,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EventError {
+    #[error("Transaction has no status meta")]
+    MissingMeta,
+
+    #[error("Unsupported transaction encoding, use json, jsonParsed, base58 or base64")]
+    UnsupportedEncoding,
+
+    #[error("Invalid {field}: {value}")]
+    InvalidField { field: &'static str, value: String },
+
+    #[error("Instruction references account index {0} outside the account keys")]
+    AccountIndex(u8),
+}
+
+pub type Result<T> = std::result::Result<T, EventError>;
+
+/// Events logged by any of `programs` in a successful transaction's logs,
+/// e.g. from a `logsSubscribe` notification
+pub fn parse_logs(context: EventContext, logs: &[String], programs: &[Pubkey]) -> Vec<ParsedEvent> {
+    collect(
+        context,
+        logs::decode_logs(logs, programs)
+            .into_iter()
+            .map(|(program_id, event)| (program_id, EventSource::Log, event)),
+    )
+}
+
+/// Number the events of one transaction
+fn collect(
+    context: EventContext,
+    events: impl IntoIterator<Item = (Pubkey, EventSource, VaultEvent)>,
+) -> Vec<ParsedEvent> {
+    events
+        .into_iter()
+        .enumerate()
+        .map(|(index, (program_id, source, event))| ParsedEvent {
+            context,
+            index,
+            program_id,
+            source,
+            event,
+        })
+        .collect()
+}
@@ -0,0 +1,144 @@
+//! Events logged with `emit!`
+//!
+//! Each event is a `Program data: <base64>` line. The line is attributed to
+//! the program on top of the invocation stack, so events of SVS programs
+//! invoked through CPI are found and other programs' data lines are ignored,
+//! even when they carry an SVS discriminator.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::VaultEvent;
+
+/// Events emitted by any of `programs` in a successful transaction's logs,
+/// with the emitting program, in log order
+pub fn decode_logs(logs: &[String], programs: &[Pubkey]) -> Vec<(Pubkey, VaultEvent)> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            let Some(program_id) = stack.last().filter(|id| programs.contains(id)) else {
+                continue;
+            };
+            if let Some(event) = BASE64
+                .decode(data)
+                .ok()
+                .and_then(|data| VaultEvent::decode(&data))
+            {
+                events.push((*program_id, event));
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (Some(program), Some(action)) = (words.next(), words.next()) else {
+                continue;
+            };
+            match action {
+                "invoke" => stack.push(program.parse().unwrap_or_default()),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Event;
+
+    use super::*;
+    use crate::VaultStatusChanged;
+
+    fn data_line(event: &impl Event) -> String {
+        format!("Program data: {}", BASE64.encode(event.data()))
+    }
+
+    #[test]
+    fn test_decode_logs_follows_invocations() {
+        let vault = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let deposit = svs_2::events::Deposit {
+            vault,
+            caller: other,
+            owner: Pubkey::new_unique(),
+            assets: 5,
+            shares: 5_000,
+            op_counter: 2,
+            sequence: 2,
+            timestamp: 1_700_000_000,
+        };
+        let paused = svs_2::events::VaultStatusChanged {
+            vault,
+            paused: true,
+            op_counter: 3,
+            sequence: 3,
+            timestamp: 1_700_000_000,
+        };
+
+        let logs = vec![
+            format!("Program {other} invoke [1]"),
+            // Data of another program, even with an SVS discriminator
+            data_line(&paused),
+            format!("Program {} invoke [2]", svs_2::ID),
+            "Program log: Instruction: Deposit".to_string(),
+            data_line(&deposit),
+            format!(
+                "Program {} consumed 40000 of 180000 compute units",
+                svs_2::ID
+            ),
+            format!("Program {} success", svs_2::ID),
+            data_line(&paused),
+            format!("Program {other} success"),
+            format!("Program {} invoke [1]", svs_2::ID),
+            data_line(&paused),
+            "Program data: not base64!".to_string(),
+            format!("Program {} success", svs_2::ID),
+        ];
+
+        let events = decode_logs(&logs, &[svs_1::ID, svs_2::ID]);
+        assert_eq!(events.len(), 2);
+        let (program_id, VaultEvent::Deposit(decoded)) = &events[0] else {
+            panic!("not a deposit: {:?}", events[0]);
+        };
+        assert_eq!(*program_id, svs_2::ID);
+        assert_eq!(decoded.caller, other);
+        assert_eq!(decoded.shares, 5_000);
+        assert!(matches!(
+            events[1].1,
+            VaultEvent::VaultStatusChanged(VaultStatusChanged { paused: true, .. })
+        ));
+
+        assert!(decode_logs(&logs, &[svs_1::ID]).is_empty());
+    }
+
+    #[test]
+    fn test_svs1_events_decode_as_svs2() {
+        let withdraw = svs_1::events::Withdraw {
+            vault: Pubkey::new_unique(),
+            caller: Pubkey::new_unique(),
+            receiver: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            assets: 1_000,
+            shares: 999_000,
+            op_counter: 1,
+            sequence: 1,
+            timestamp: 1_700_000_000,
+        };
+        let logs = vec![
+            format!("Program {} invoke [1]", svs_1::ID),
+            data_line(&withdraw),
+            format!("Program {} success", svs_1::ID),
+        ];
+
+        let events = decode_logs(&logs, &[svs_1::ID]);
+        let [(program_id, VaultEvent::Withdraw(decoded))] = events.as_slice() else {
+            panic!("not one withdraw: {events:?}");
+        };
+        assert_eq!(*program_id, svs_1::ID);
+        assert_eq!(decoded.receiver, withdraw.receiver);
+        assert_eq!((decoded.assets, decoded.shares), (1_000, 999_000));
+    }
+}
@@ -0,0 +1,152 @@
+//! Events of a fetched transaction (`getTransaction`, `getBlock`)
+//!
+//! Inner instructions reference their program by account index, so the
+//! account keys are read from the transaction: the static keys followed by
+//! the keys loaded from lookup tables. Any encoding works except `accounts`,
+//! which carries no instructions; `jsonParsed` names the program of every
+//! instruction directly.
+
+use std::str::FromStr;
+
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInnerInstructions,
+    UiInstruction, UiLoadedAddresses, UiMessage, UiParsedInstruction, UiTransactionStatusMeta,
+};
+
+use crate::{collect, cpi, logs, EventContext, EventError, EventSource, ParsedEvent, Result};
+
+/// Events emitted by any of `programs` in `transaction`, logged and CPI;
+/// none for a failed transaction, whose events were rolled back
+///
+/// Fetch the transaction with `max_supported_transaction_version: Some(0)`
+/// so versioned transactions are returned.
+pub fn parse_transaction(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    programs: &[Pubkey],
+) -> Result<Vec<ParsedEvent>> {
+    let meta = transaction
+        .transaction
+        .meta
+        .as_ref()
+        .ok_or(EventError::MissingMeta)?;
+    let (signature, account_keys) = signature_and_keys(&transaction.transaction.transaction, meta)?;
+    let context = EventContext {
+        signature,
+        slot: transaction.slot,
+        block_time: transaction.block_time,
+    };
+    if meta.err.is_some() {
+        return Ok(Vec::new());
+    }
+
+    let log_messages: Option<&Vec<String>> = meta.log_messages.as_ref().into();
+    let logged = logs::decode_logs(
+        log_messages.map(Vec::as_slice).unwrap_or_default(),
+        programs,
+    )
+    .into_iter()
+    .map(|(program_id, event)| (program_id, EventSource::Log, event));
+
+    let inner_instructions: Option<&Vec<UiInnerInstructions>> =
+        meta.inner_instructions.as_ref().into();
+    let mut instructions = Vec::new();
+    for instruction in inner_instructions
+        .into_iter()
+        .flatten()
+        .flat_map(|inner| &inner.instructions)
+    {
+        let (program_id, data) = match instruction {
+            UiInstruction::Compiled(compiled) => (
+                *account_keys
+                    .get(compiled.program_id_index as usize)
+                    .ok_or(EventError::AccountIndex(compiled.program_id_index))?,
+                &compiled.data,
+            ),
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => {
+                (parse_pubkey(&decoded.program_id)?, &decoded.data)
+            }
+            // Parsed by the RPC node, so a known program other than ours
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => continue,
+        };
+        let data = bs58::decode(data)
+            .into_vec()
+            .map_err(|_| invalid("instruction data", data))?;
+        instructions.push((program_id, data));
+    }
+    let emitted = cpi::decode_inner_instructions(
+        instructions
+            .iter()
+            .map(|(program_id, data)| (*program_id, data.as_slice())),
+        programs,
+    )
+    .into_iter()
+    .map(|(program_id, event)| (program_id, EventSource::Cpi, event));
+
+    Ok(collect(context, logged.chain(emitted)))
+}
+
+/// First signature and account keys of an encoded transaction
+fn signature_and_keys(
+    encoded: &EncodedTransaction,
+    meta: &UiTransactionStatusMeta,
+) -> Result<(Signature, Vec<Pubkey>)> {
+    if let Some(transaction) = encoded.decode() {
+        let signature = *transaction
+            .signatures
+            .first()
+            .ok_or_else(|| invalid("signatures", "[]"))?;
+        let mut keys = transaction.message.static_account_keys().to_vec();
+        keys.extend(loaded_addresses(meta)?);
+        return Ok((signature, keys));
+    }
+
+    let EncodedTransaction::Json(transaction) = encoded else {
+        return Err(EventError::UnsupportedEncoding);
+    };
+    let signature = transaction
+        .signatures
+        .first()
+        .ok_or_else(|| invalid("signatures", "[]"))?;
+    let signature = Signature::from_str(signature).map_err(|_| invalid("signature", signature))?;
+    let keys = match &transaction.message {
+        UiMessage::Raw(message) => {
+            let mut keys = message
+                .account_keys
+                .iter()
+                .map(|key| parse_pubkey(key))
+                .collect::<Result<Vec<_>>>()?;
+            keys.extend(loaded_addresses(meta)?);
+            keys
+        }
+        // Already includes the loaded keys
+        UiMessage::Parsed(message) => message
+            .account_keys
+            .iter()
+            .map(|account| parse_pubkey(&account.pubkey))
+            .collect::<Result<_>>()?,
+    };
+    Ok((signature, keys))
+}
+
+/// Keys loaded from lookup tables: writable, then read-only
+fn loaded_addresses(meta: &UiTransactionStatusMeta) -> Result<Vec<Pubkey>> {
+    let loaded: Option<&UiLoadedAddresses> = meta.loaded_addresses.as_ref().into();
+    loaded
+        .map(|loaded| loaded.writable.iter().chain(&loaded.readonly))
+        .into_iter()
+        .flatten()
+        .map(|key| parse_pubkey(key))
+        .collect()
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| invalid("pubkey", value))
+}
+
+fn invalid(field: &'static str, value: &str) -> EventError {
+    EventError::InvalidField {
+        field,
+        value: value.to_string(),
+    }
+}
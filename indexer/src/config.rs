@@ -0,0 +1,94 @@
+//! Configuration from environment variables
+
+use std::{str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Context};
+use solana_sdk::pubkey::Pubkey;
+
+pub struct Config {
+    /// `DATABASE_URL`, e.g. `postgres://indexer@localhost/svs`
+    pub database_url: String,
+    /// `SOLANA_RPC_URL`
+    pub rpc_url: String,
+    /// `SOLANA_WS_URL`, derived from the RPC URL when unset
+    pub ws_url: String,
+    /// `SVS_PROGRAM_IDS`, comma separated; both canonical programs by default
+    pub programs: Vec<Pubkey>,
+    /// `FINALIZE_INTERVAL_SECS`: how often confirmed rows are finalized
+    pub finalize_interval: Duration,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let database_url = std::env::var("DATABASE_URL").context("DATABASE_URL is required")?;
+        let rpc_url = std::env::var("SOLANA_RPC_URL")
+            .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+        let ws_url = std::env::var("SOLANA_WS_URL").unwrap_or_else(|_| ws_url(&rpc_url));
+
+        let programs = match std::env::var("SVS_PROGRAM_IDS") {
+            Ok(ids) => parse_programs(&ids)?,
+            Err(_) => vec![svs_1::ID, svs_2::ID],
+        };
+
+        let finalize_interval = match std::env::var("FINALIZE_INTERVAL_SECS") {
+            Ok(secs) => Duration::from_secs(
+                secs.parse()
+                    .map_err(|e| anyhow!("Invalid FINALIZE_INTERVAL_SECS {secs}: {e}"))?,
+            ),
+            Err(_) => Duration::from_secs(10),
+        };
+
+        Ok(Self {
+            database_url,
+            rpc_url,
+            ws_url,
+            programs,
+            finalize_interval,
+        })
+    }
+}
+
+fn parse_programs(ids: &str) -> anyhow::Result<Vec<Pubkey>> {
+    ids.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| Pubkey::from_str(id).map_err(|e| anyhow!("Invalid program id {id}: {e}")))
+        .collect()
+}
+
+/// Websocket URL of an RPC URL; Solana RPC nodes serve both on one port
+fn ws_url(rpc_url: &str) -> String {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        // The local test validator serves websockets on the next port
+        match rest.strip_suffix(":8899") {
+            Some(host) => format!("ws://{host}:8900"),
+            None => format!("ws://{rest}"),
+        }
+    } else {
+        rpc_url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_url() {
+        assert_eq!(
+            ws_url("https://api.devnet.solana.com"),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(ws_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8900");
+        assert_eq!(ws_url("http://rpc.internal"), "ws://rpc.internal");
+    }
+
+    #[test]
+    fn test_parse_programs() {
+        let ids = format!("{}, {},", svs_1::ID, svs_2::ID);
+        assert_eq!(parse_programs(&ids).unwrap(), vec![svs_1::ID, svs_2::ID]);
+        assert!(parse_programs("not-a-key").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Postgres storage of decoded events
+
+use anyhow::Context;
+use tokio::{sync::Mutex, task::JoinHandle};
+use tokio_postgres::{Client, NoTls};
+use tracing::error;
+
+use crate::events::EventRecord;
+
+const SCHEMA: &str = include_str!("schema.sql");
+
+pub struct Database {
+    // Transactions need exclusive access to the client
+    client: Mutex<Client>,
+}
+
+impl Database {
+    /// Connect and apply the schema; the returned task ends when the
+    /// connection is lost
+    pub async fn connect(url: &str) -> anyhow::Result<(Self, JoinHandle<()>)> {
+        let (client, connection) = tokio_postgres::connect(url, NoTls)
+            .await
+            .context("Failed to connect to Postgres")?;
+        let connection = tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("Postgres connection failed: {e}");
+            }
+        });
+
+        client
+            .batch_execute(SCHEMA)
+            .await
+            .context("Failed to apply the schema")?;
+        Ok((
+            Self {
+                client: Mutex::new(client),
+            },
+            connection,
+        ))
+    }
+
+    /// Store the events of one transaction, unfinalized; storing a
+    /// transaction again only updates the slot of rows not yet finalized
+    pub async fn store(
+        &self,
+        signature: &str,
+        slot: u64,
+        block_time: Option<i64>,
+        events: &[EventRecord],
+    ) -> anyhow::Result<()> {
+        let mut client = self.client.lock().await;
+        let transaction = client.transaction().await?;
+        let statement = transaction
+            .prepare(
+                "INSERT INTO vault_events (signature, event_index, slot, block_time, program_id, \
+                 vault, kind, owner, caller, receiver, assets, shares, sequence, data) \
+                 VALUES ($1, $2, $3, to_timestamp($4::BIGINT), $5, $6, $7, $8, $9, $10, \
+                 CAST($11::TEXT AS NUMERIC), CAST($12::TEXT AS NUMERIC), $13, $14) \
+                 ON CONFLICT (signature, event_index) DO UPDATE SET slot = EXCLUDED.slot \
+                 WHERE NOT vault_events.finalized",
+            )
+            .await?;
+
+        for (index, event) in events.iter().enumerate() {
+            transaction
+                .execute(
+                    &statement,
+                    &[
+                        &signature,
+                        &(index as i32),
+                        &(slot as i64),
+                        &block_time,
+                        &event.program_id.to_string(),
+                        &event.vault.to_string(),
+                        &event.kind,
+                        &event.owner.map(|key| key.to_string()),
+                        &event.caller.map(|key| key.to_string()),
+                        &event.receiver.map(|key| key.to_string()),
+                        &event.assets.map(|amount| amount.to_string()),
+                        &event.shares.map(|amount| amount.to_string()),
+                        &(event.sequence as i64),
+                        &event.data,
+                    ],
+                )
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Newest finalized transaction with events of `program_id`, where
+    /// backfilling stops
+    pub async fn latest_finalized_signature(
+        &self,
+        program_id: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let row = self
+            .client
+            .lock()
+            .await
+            .query_opt(
+                "SELECT signature FROM vault_events WHERE program_id = $1 AND finalized \
+                 ORDER BY slot DESC LIMIT 1",
+                &[&program_id],
+            )
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    /// Unfinalized transactions at or below `max_slot`, oldest first
+    pub async fn unfinalized(
+        &self,
+        max_slot: u64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<(String, u64)>> {
+        let rows = self
+            .client
+            .lock()
+            .await
+            .query(
+                "SELECT signature, MIN(slot) FROM vault_events \
+                 WHERE NOT finalized AND slot <= $1 \
+                 GROUP BY signature ORDER BY 2 LIMIT $2",
+                &[&(max_slot as i64), &limit],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get(0), row.get::<_, i64>(1) as u64))
+            .collect())
+    }
+
+    /// Mark a transaction finalized in `slot`, which differs from the stored
+    /// slot when it landed again on another fork
+    pub async fn finalize(
+        &self,
+        signature: &str,
+        slot: u64,
+        block_time: Option<i64>,
+    ) -> anyhow::Result<()> {
+        self.client
+            .lock()
+            .await
+            .execute(
+                "UPDATE vault_events SET finalized = TRUE, slot = $2, \
+                 block_time = COALESCE(to_timestamp($3::BIGINT), block_time) \
+                 WHERE signature = $1",
+                &[&signature, &(slot as i64), &block_time],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a transaction whose fork was abandoned
+    pub async fn discard(&self, signature: &str) -> anyhow::Result<()> {
+        self.client
+            .lock()
+            .await
+            .execute(
+                "DELETE FROM vault_events WHERE signature = $1",
+                &[&signature],
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+//! Vault events in the columns of `vault_events`
+//!
+//! Events are decoded by `svs-events`. Each becomes one row, with the
+//! accounts and amounts queries filter on in their own columns and the whole
+//! event as JSON.
+
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use svs_events::{ComplianceOperation, ParsedEvent, VaultEvent};
+
+/// One decoded event, in the columns of `vault_events`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub program_id: Pubkey,
+    pub vault: Pubkey,
+    pub kind: &'static str,
+    pub owner: Option<Pubkey>,
+    pub caller: Option<Pubkey>,
+    pub receiver: Option<Pubkey>,
+    pub assets: Option<u64>,
+    pub shares: Option<u64>,
+    /// Position among the vault's events, from the event itself
+    pub sequence: u64,
+    pub data: Value,
+}
+
+impl EventRecord {
+    fn new(program_id: Pubkey, kind: &'static str, vault: Pubkey, data: Value) -> Self {
+        Self {
+            program_id,
+            vault,
+            kind,
+            owner: None,
+            caller: None,
+            receiver: None,
+            assets: None,
+            shares: None,
+            sequence: 0,
+            data,
+        }
+    }
+}
+
+impl From<&ParsedEvent> for EventRecord {
+    fn from(parsed: &ParsedEvent) -> Self {
+        let program_id = parsed.program_id;
+        let mut record = match &parsed.event {
+            VaultEvent::Deposit(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "caller": e.caller.to_string(),
+                    "owner": e.owner.to_string(),
+                    "assets": e.assets,
+                    "shares": e.shares,
+                });
+                Self {
+                    owner: Some(e.owner),
+                    caller: Some(e.caller),
+                    assets: Some(e.assets),
+                    shares: Some(e.shares),
+                    ..Self::new(program_id, "deposit", e.vault, data)
+                }
+            }
+            VaultEvent::Withdraw(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "caller": e.caller.to_string(),
+                    "receiver": e.receiver.to_string(),
+                    "owner": e.owner.to_string(),
+                    "assets": e.assets,
+                    "shares": e.shares,
+                });
+                Self {
+                    owner: Some(e.owner),
+                    caller: Some(e.caller),
+                    receiver: Some(e.receiver),
+                    assets: Some(e.assets),
+                    shares: Some(e.shares),
+                    ..Self::new(program_id, "withdraw", e.vault, data)
+                }
+            }
+            VaultEvent::EmergencyRedeem(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "owner": e.owner.to_string(),
+                    "assets": e.assets,
+                    "shares": e.shares,
+                });
+                Self {
+                    owner: Some(e.owner),
+                    assets: Some(e.assets),
+                    shares: Some(e.shares),
+                    ..Self::new(program_id, "emergency_redeem", e.vault, data)
+                }
+            }
+            VaultEvent::ConfidentialDepositResumed(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "owner": e.owner.to_string(),
+                    "shares": e.shares,
+                });
+                Self {
+                    owner: Some(e.owner),
+                    shares: Some(e.shares),
+                    ..Self::new(program_id, "resume_confidential_deposit", e.vault, data)
+                }
+            }
+            VaultEvent::VaultSynced(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "previous_total": e.previous_total,
+                    "new_total": e.new_total,
+                });
+                Self {
+                    assets: Some(e.new_total),
+                    ..Self::new(program_id, "sync", e.vault, data)
+                }
+            }
+            VaultEvent::Harvested(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "reward_mint": e.reward_mint.to_string(),
+                    "reward_amount": e.reward_amount,
+                    "assets_added": e.assets_added,
+                    "locked_profit": e.locked_profit,
+                });
+                Self {
+                    assets: Some(e.assets_added),
+                    ..Self::new(program_id, "harvest", e.vault, data)
+                }
+            }
+            VaultEvent::ComplianceMemo(e) => {
+                let operation = match e.operation {
+                    ComplianceOperation::Deposit => "deposit",
+                    ComplianceOperation::Withdraw => "withdraw",
+                };
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "owner": e.owner.to_string(),
+                    "operation": operation,
+                    "memo_hash": hex::encode(e.memo_hash),
+                });
+                Self {
+                    owner: Some(e.owner),
+                    ..Self::new(program_id, "compliance_memo", e.vault, data)
+                }
+            }
+            VaultEvent::VaultInitialized(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "authority": e.authority.to_string(),
+                    "asset_mint": e.asset_mint.to_string(),
+                    "shares_mint": e.shares_mint.to_string(),
+                    "vault_id": e.vault_id,
+                    "non_transferable": e.non_transferable,
+                });
+                Self::new(program_id, "initialize", e.vault, data)
+            }
+            VaultEvent::VaultStatusChanged(e) => {
+                let kind = if e.paused { "pause" } else { "unpause" };
+                let data = json!({ "vault": e.vault.to_string(), "paused": e.paused });
+                Self::new(program_id, kind, e.vault, data)
+            }
+            VaultEvent::AuthorityTransferred(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "previous_authority": e.previous_authority.to_string(),
+                    "new_authority": e.new_authority.to_string(),
+                });
+                Self::new(program_id, "transfer_authority", e.vault, data)
+            }
+            VaultEvent::GuardianUpdated(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "previous_guardian": e.previous_guardian.to_string(),
+                    "new_guardian": e.new_guardian.to_string(),
+                });
+                Self::new(program_id, "set_guardian", e.vault, data)
+            }
+            VaultEvent::HarvestConfigUpdated(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "swap_adapter": e.swap_adapter.to_string(),
+                    "harvester": e.harvester.to_string(),
+                    "profit_unlock_period": e.profit_unlock_period,
+                });
+                Self::new(program_id, "set_harvest_config", e.vault, data)
+            }
+            VaultEvent::MinDepositUpdated(e) => {
+                let data = json!({
+                    "vault": e.vault.to_string(),
+                    "previous": e.previous,
+                    "new": e.new,
+                });
+                Self::new(program_id, "set_min_deposit", e.vault, data)
+            }
+            VaultEvent::ProtocolStatusChanged(e) => {
+                let kind = if e.paused {
+                    "pause_protocol"
+                } else {
+                    "unpause_protocol"
+                };
+                let data = json!({
+                    "config": e.config.to_string(),
+                    "caller": e.caller.to_string(),
+                    "paused": e.paused,
+                });
+                Self {
+                    caller: Some(e.caller),
+                    ..Self::new(program_id, kind, e.config, data)
+                }
+            }
+            VaultEvent::ProtocolConfigUpdated(e) => {
+                let data = json!({
+                    "config": e.config.to_string(),
+                    "authority": e.authority.to_string(),
+                    "guardian": e.guardian.to_string(),
+                    "min_deposit_amount": e.min_deposit_amount,
+                    "max_decimals": e.max_decimals,
+                    "shares_decimals": e.shares_decimals,
+                });
+                Self::new(program_id, "update_config", e.config, data)
+            }
+        };
+        record.sequence = parsed.event.sequence();
+        record.data["sequence"] = json!(record.sequence);
+        record.data["op_counter"] = json!(parsed.event.op_counter());
+        record.data["timestamp"] = json!(parsed.event.timestamp());
+        record
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Event;
+    use solana_sdk::signature::Signature;
+    use svs_events::{EventContext, EventSource};
+
+    use super::*;
+
+    fn parsed(program_id: Pubkey, data: &[u8]) -> ParsedEvent {
+        ParsedEvent {
+            context: EventContext {
+                signature: Signature::default(),
+                slot: 1,
+                block_time: None,
+            },
+            index: 0,
+            program_id,
+            source: EventSource::Log,
+            event: VaultEvent::decode(data).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_svs1_event_records() {
+        let event = svs_1::events::Withdraw {
+            vault: Pubkey::new_unique(),
+            caller: Pubkey::new_unique(),
+            receiver: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            assets: 1_000,
+            shares: 999_000,
+            op_counter: 4,
+            sequence: 4,
+            timestamp: 1_700_000_000,
+        };
+
+        let record = EventRecord::from(&parsed(svs_1::ID, &event.data()));
+        assert_eq!(record.kind, "withdraw");
+        assert_eq!(record.program_id, svs_1::ID);
+        assert_eq!(record.vault, event.vault);
+        assert_eq!(record.receiver, Some(event.receiver));
+        assert_eq!(record.shares, Some(999_000));
+        assert_eq!(record.data["assets"], 1_000);
+        assert_eq!(record.sequence, 4);
+        assert_eq!(record.data["timestamp"], 1_700_000_000);
+
+        let synced = svs_1::events::VaultSynced {
+            vault: event.vault,
+            previous_total: 10,
+            new_total: 12,
+            op_counter: 5,
+            sequence: 5,
+            timestamp: 1_700_000_000,
+        };
+        assert_eq!(
+            EventRecord::from(&parsed(svs_1::ID, &synced.data())).assets,
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_status_change_kinds() {
+        let vault = Pubkey::new_unique();
+        for (paused, kind) in [(true, "pause"), (false, "unpause")] {
+            let event = svs_2::events::VaultStatusChanged {
+                vault,
+                paused,
+                op_counter: 1,
+                sequence: 1,
+                timestamp: 0,
+            };
+            let record = EventRecord::from(&parsed(svs_2::ID, &event.data()));
+            assert_eq!(record.kind, kind);
+            assert_eq!(record.data["paused"], paused);
+        }
+    }
+
+    #[test]
+    fn test_protocol_event_records() {
+        let config = Pubkey::new_unique();
+        let guardian = Pubkey::new_unique();
+        let event = svs_1::events::ProtocolStatusChanged {
+            config,
+            caller: guardian,
+            paused: true,
+            sequence: 3,
+            timestamp: 0,
+        };
+        let record = EventRecord::from(&parsed(svs_1::ID, &event.data()));
+        assert_eq!(
+            (record.kind, record.vault, record.caller),
+            ("pause_protocol", config, Some(guardian))
+        );
+        assert_eq!(record.sequence, 3);
+
+        let event = svs_1::events::ProtocolConfigUpdated {
+            config,
+            authority: Pubkey::new_unique(),
+            guardian,
+            min_deposit_amount: 1_000,
+            max_decimals: 9,
+            shares_decimals: 9,
+            sequence: 4,
+            timestamp: 0,
+        };
+        let record = EventRecord::from(&parsed(svs_1::ID, &event.data()));
+        assert_eq!(record.kind, "update_config");
+        assert_eq!(record.data["guardian"], guardian.to_string());
+    }
+}
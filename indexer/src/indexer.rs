@@ -0,0 +1,240 @@
+//! Ingestion: live log subscriptions, backfill and finalization
+//!
+//! Transactions are ingested at `confirmed` commitment, so their rows can
+//! still be rolled back by a fork. The finalizer waits for the finalized slot
+//! to pass each unfinalized transaction, then marks it finalized, moving it
+//! to the slot it finally landed in, or deletes it if the cluster no longer
+//! knows it.
+
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{TransactionConfirmationStatus, UiTransactionEncoding};
+use svs_events::{parse_logs, parse_transaction, EventContext, ParsedEvent};
+use tracing::{debug, info, warn};
+
+use crate::{db::Database, events::EventRecord};
+
+/// `getSignaturesForAddress` page size (the RPC maximum)
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// `getSignatureStatuses` batch size (the RPC maximum)
+const STATUS_BATCH: usize = 256;
+
+/// Slots an unknown transaction is kept past the finalized slot before it is
+/// considered rolled back, in case the RPC node lags
+const ROLLBACK_MARGIN: u64 = 150;
+
+pub struct Indexer {
+    pub db: Database,
+    pub rpc: RpcClient,
+    pub ws_url: String,
+    pub programs: Vec<Pubkey>,
+}
+
+impl Indexer {
+    /// Store the events of one transaction
+    async fn ingest(&self, events: &[ParsedEvent]) -> anyhow::Result<()> {
+        let Some(EventContext {
+            signature,
+            slot,
+            block_time,
+        }) = events.first().map(|event| event.context)
+        else {
+            return Ok(());
+        };
+        let signature = signature.to_string();
+        debug!(signature, slot, events = events.len(), "Storing events");
+        let records: Vec<EventRecord> = events.iter().map(EventRecord::from).collect();
+        self.db.store(&signature, slot, block_time, &records).await
+    }
+
+    /// Stream `program`'s transactions, reconnecting and backfilling the gap
+    /// whenever the subscription drops
+    pub async fn subscribe(self: Arc<Self>, program: Pubkey) {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match self.clone().stream(program).await {
+                Ok(()) => warn!(%program, "Log subscription closed"),
+                Err(e) => warn!(%program, "Log subscription failed: {e:#}"),
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(60));
+        }
+    }
+
+    async fn stream(self: Arc<Self>, program: Pubkey) -> anyhow::Result<()> {
+        let pubsub = PubsubClient::new(&self.ws_url).await?;
+        let (mut logs, unsubscribe) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![program.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+        info!(%program, "Subscribed to program logs");
+
+        // Subscribed first, so nothing between the backfill and the stream is missed
+        let backfill = tokio::spawn({
+            let indexer = self.clone();
+            async move {
+                match indexer.backfill(&program).await {
+                    Ok(count) => info!(%program, transactions = count, "Backfill complete"),
+                    Err(e) => warn!(%program, "Backfill failed: {e:#}"),
+                }
+            }
+        });
+
+        while let Some(response) = logs.next().await {
+            let value = response.value;
+            if value.err.is_some() {
+                continue;
+            }
+            let context = EventContext {
+                signature: Signature::from_str(&value.signature)?,
+                slot: response.context.slot,
+                block_time: None,
+            };
+            self.ingest(&parse_logs(context, &value.logs, &self.programs))
+                .await?;
+        }
+
+        backfill.abort();
+        unsubscribe().await;
+        Ok(())
+    }
+
+    /// Ingest `program`'s transactions since the newest finalized one stored,
+    /// oldest first; returns the number of transactions fetched
+    pub async fn backfill(&self, program: &Pubkey) -> anyhow::Result<usize> {
+        let until = self
+            .db
+            .latest_finalized_signature(&program.to_string())
+            .await?
+            .map(|signature| Signature::from_str(&signature))
+            .transpose()?;
+
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    program,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(SIGNATURE_PAGE),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                    },
+                )
+                .await?;
+            let full = page.len() == SIGNATURE_PAGE;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(Signature::from_str(&last.signature)?);
+            signatures.extend(page.into_iter().filter(|status| status.err.is_none()));
+            if !full {
+                break;
+            }
+        }
+        info!(%program, transactions = signatures.len(), "Backfilling");
+
+        for status in signatures.iter().rev() {
+            let transaction = self
+                .rpc
+                .get_transaction_with_config(
+                    &Signature::from_str(&status.signature)?,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Json),
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await?;
+            self.ingest(&parse_transaction(&transaction, &self.programs)?)
+                .await?;
+        }
+        Ok(signatures.len())
+    }
+
+    /// Finalize or discard stored transactions every `interval`
+    pub async fn finalize(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.finalize_once().await {
+                warn!("Finalization failed: {e:#}");
+            }
+        }
+    }
+
+    async fn finalize_once(&self) -> anyhow::Result<()> {
+        let finalized_slot = self
+            .rpc
+            .get_slot_with_commitment(CommitmentConfig::finalized())
+            .await?;
+        let pending = self
+            .db
+            .unfinalized(finalized_slot, STATUS_BATCH as i64)
+            .await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let signatures = pending
+            .iter()
+            .map(|(signature, _)| Signature::from_str(signature))
+            .collect::<Result<Vec<_>, _>>()?;
+        let statuses = self
+            .rpc
+            .get_signature_statuses_with_history(&signatures)
+            .await?
+            .value;
+
+        let mut block_times = HashMap::new();
+        let (mut finalized, mut discarded) = (0, 0);
+        for ((signature, slot), status) in pending.iter().zip(statuses) {
+            match status {
+                Some(status)
+                    if status.confirmation_status
+                        == Some(TransactionConfirmationStatus::Finalized) =>
+                {
+                    if status.err.is_some() {
+                        self.db.discard(signature).await?;
+                        discarded += 1;
+                        continue;
+                    }
+                    let block_time = match block_times.get(&status.slot) {
+                        Some(time) => *time,
+                        None => {
+                            let time = self.rpc.get_block_time(status.slot).await.ok();
+                            block_times.insert(status.slot, time);
+                            time
+                        }
+                    };
+                    self.db.finalize(signature, status.slot, block_time).await?;
+                    finalized += 1;
+                }
+                // Landed again on another fork, not finalized yet
+                Some(_) => {}
+                None if slot + ROLLBACK_MARGIN < finalized_slot => {
+                    warn!(%signature, slot, "Transaction rolled back, discarding its events");
+                    self.db.discard(signature).await?;
+                    discarded += 1;
+                }
+                None => {}
+            }
+        }
+        debug!(finalized, discarded, finalized_slot, "Finalization pass");
+        Ok(())
+    }
+}
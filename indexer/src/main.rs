@@ -0,0 +1,88 @@
+//! SVS Indexer
+//!
+//! Indexes the events of SVS-1 and SVS-2 vaults into Postgres for analytics
+//! and dashboards. See README.md for configuration and the table layout.
+
+mod config;
+mod db;
+mod events;
+mod indexer;
+
+use std::sync::Arc;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::{error, info};
+
+use config::Config;
+use db::Database;
+use indexer::Indexer;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "svs_indexer=info".into()),
+        )
+        .init();
+
+    if let Err(e) = run().await {
+        error!("{e:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let (db, connection) = Database::connect(&config.database_url).await?;
+    info!(
+        rpc_url = %config.rpc_url,
+        ws_url = %config.ws_url,
+        programs = ?config.programs,
+        "Starting SVS Indexer"
+    );
+
+    let indexer = Arc::new(Indexer {
+        db,
+        rpc: RpcClient::new_with_commitment(config.rpc_url, CommitmentConfig::confirmed()),
+        ws_url: config.ws_url,
+        programs: config.programs.clone(),
+    });
+
+    // Each subscription backfills on (re)connect
+    for program in config.programs {
+        tokio::spawn(indexer.clone().subscribe(program));
+    }
+    tokio::spawn(indexer.clone().finalize(config.finalize_interval));
+
+    tokio::select! {
+        _ = connection => anyhow::bail!("Lost the Postgres connection"),
+        _ = shutdown_signal() => info!("Shutting down"),
+    }
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
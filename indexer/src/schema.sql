@@ -0,0 +1,33 @@
+-- Applied at startup; every statement is idempotent
+
+CREATE TABLE IF NOT EXISTS vault_events (
+    signature    TEXT        NOT NULL,
+    -- Position of the event among the transaction's SVS events
+    event_index  INTEGER     NOT NULL,
+    slot         BIGINT      NOT NULL,
+    block_time   TIMESTAMPTZ,
+    program_id   TEXT        NOT NULL,
+    vault        TEXT        NOT NULL,
+    kind         TEXT        NOT NULL,
+    owner        TEXT,
+    caller       TEXT,
+    receiver     TEXT,
+    assets       NUMERIC(20, 0),
+    shares       NUMERIC(20, 0),
+    -- Position of the event among the vault's events
+    sequence     BIGINT,
+    -- Every field of the decoded event
+    data         JSONB       NOT NULL,
+    -- Confirmed rows are finalized, or deleted if their fork is abandoned
+    finalized    BOOLEAN     NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Tables created before events carried sequence numbers
+ALTER TABLE vault_events ADD COLUMN IF NOT EXISTS sequence BIGINT;
+
+CREATE INDEX IF NOT EXISTS vault_events_vault_sequence ON vault_events (vault, sequence);
+CREATE INDEX IF NOT EXISTS vault_events_vault_slot ON vault_events (vault, slot);
+CREATE INDEX IF NOT EXISTS vault_events_owner ON vault_events (owner) WHERE owner IS NOT NULL;
+CREATE INDEX IF NOT EXISTS vault_events_unfinalized ON vault_events (slot) WHERE NOT finalized;
+CREATE INDEX IF NOT EXISTS vault_events_program_slot ON vault_events (program_id, slot) WHERE finalized;
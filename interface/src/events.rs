@@ -0,0 +1,576 @@
+//! Events of SVS-1 and SVS-2
+//!
+//! The programs emit events as `Program data: <base64>` log lines. The decoded
+//! bytes are the event discriminator (`sha256("event:<Name>")[..8]`) followed
+//! by the Borsh-encoded event. SVS-1 emits the first seven events and the
+//! protocol config events; the rest are SVS-2 only.
+//!
+//! Every event ends with the vault's operation counter, the number of
+//! mutating instructions run on it so far, then the vault's event sequence
+//! number, which starts at 1 and increases by one per event of that vault,
+//! and the cluster timestamp.
+//!
+//! The protocol config events, `ProtocolStatusChanged` and
+//! `ProtocolConfigUpdated`, are the exception: they are numbered by the
+//! config's own sequence and carry no operation counter.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// An event struct and its discriminator
+pub trait Event: BorshDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+
+    /// Decode `data` if it is this event
+    fn decode(data: &[u8]) -> Option<Self> {
+        let body = data.strip_prefix(Self::DISCRIMINATOR.as_slice())?;
+        Self::try_from_slice(body).ok()
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub asset_mint: Pubkey,
+    pub shares_mint: Pubkey,
+    pub vault_id: u64,
+    pub non_transferable: bool,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Deposit {
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub owner: Pubkey,
+    /// Assets credited to the vault, net of the asset's transfer fee (SVS-2)
+    pub assets: u64,
+    pub shares: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Withdraw {
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub receiver: Pubkey,
+    pub owner: Pubkey,
+    /// Assets debited from the vault, gross of the asset's transfer fee (SVS-2)
+    pub assets: u64,
+    pub shares: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultSynced {
+    pub vault: Pubkey,
+    pub previous_total: u64,
+    pub new_total: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultStatusChanged {
+    pub vault: Pubkey,
+    pub paused: bool,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthorityTransferred {
+    pub vault: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MinDepositUpdated {
+    pub vault: Pubkey,
+    pub previous: u64,
+    pub new: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmergencyRedeem {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub assets: u64,
+    pub shares: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfidentialDepositResumed {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GuardianUpdated {
+    pub vault: Pubkey,
+    pub previous_guardian: Pubkey,
+    pub new_guardian: Pubkey,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Harvested {
+    pub vault: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_amount: u64,
+    pub assets_added: u64,
+    pub locked_profit: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HarvestConfigUpdated {
+    pub vault: Pubkey,
+    pub swap_adapter: Pubkey,
+    pub harvester: Pubkey,
+    pub profit_unlock_period: i64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComplianceOperation {
+    Deposit,
+    Withdraw,
+}
+
+/// Hash of the off-chain compliance record attached to a deposit or withdrawal
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ComplianceMemo {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub operation: ComplianceOperation,
+    pub memo_hash: [u8; 32],
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// The protocol was paused or resumed by `caller`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolStatusChanged {
+    pub config: Pubkey,
+    pub caller: Pubkey,
+    pub paused: bool,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// The protocol config after a config instruction changed it
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolConfigUpdated {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+    pub min_deposit_amount: u64,
+    pub max_decimals: u8,
+    pub shares_decimals: u8,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+impl Event for VaultInitialized {
+    const DISCRIMINATOR: [u8; 8] = [180, 43, 207, 2, 18, 71, 3, 75];
+}
+
+impl Event for Deposit {
+    const DISCRIMINATOR: [u8; 8] = [62, 205, 242, 175, 244, 169, 136, 52];
+}
+
+impl Event for Withdraw {
+    const DISCRIMINATOR: [u8; 8] = [192, 241, 201, 217, 70, 150, 90, 247];
+}
+
+impl Event for VaultSynced {
+    const DISCRIMINATOR: [u8; 8] = [113, 150, 126, 33, 213, 233, 201, 26];
+}
+
+impl Event for VaultStatusChanged {
+    const DISCRIMINATOR: [u8; 8] = [154, 211, 55, 218, 107, 9, 239, 215];
+}
+
+impl Event for AuthorityTransferred {
+    const DISCRIMINATOR: [u8; 8] = [245, 109, 179, 54, 135, 92, 22, 64];
+}
+
+impl Event for MinDepositUpdated {
+    const DISCRIMINATOR: [u8; 8] = [25, 23, 150, 164, 135, 162, 172, 163];
+}
+
+impl Event for EmergencyRedeem {
+    const DISCRIMINATOR: [u8; 8] = [170, 171, 138, 92, 107, 197, 111, 52];
+}
+
+impl Event for ConfidentialDepositResumed {
+    const DISCRIMINATOR: [u8; 8] = [77, 161, 161, 190, 232, 224, 3, 149];
+}
+
+impl Event for GuardianUpdated {
+    const DISCRIMINATOR: [u8; 8] = [31, 95, 81, 24, 90, 9, 246, 32];
+}
+
+impl Event for Harvested {
+    const DISCRIMINATOR: [u8; 8] = [249, 229, 78, 151, 106, 185, 149, 11];
+}
+
+impl Event for HarvestConfigUpdated {
+    const DISCRIMINATOR: [u8; 8] = [221, 207, 117, 204, 55, 238, 190, 11];
+}
+
+impl Event for ComplianceMemo {
+    const DISCRIMINATOR: [u8; 8] = [4, 18, 85, 126, 15, 70, 5, 157];
+}
+
+impl Event for ProtocolStatusChanged {
+    const DISCRIMINATOR: [u8; 8] = [120, 122, 255, 13, 15, 135, 49, 115];
+}
+
+impl Event for ProtocolConfigUpdated {
+    const DISCRIMINATOR: [u8; 8] = [20, 99, 32, 237, 111, 86, 195, 199];
+}
+
+/// Any event of either program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultEvent {
+    VaultInitialized(VaultInitialized),
+    Deposit(Deposit),
+    Withdraw(Withdraw),
+    VaultSynced(VaultSynced),
+    VaultStatusChanged(VaultStatusChanged),
+    AuthorityTransferred(AuthorityTransferred),
+    MinDepositUpdated(MinDepositUpdated),
+    EmergencyRedeem(EmergencyRedeem),
+    ConfidentialDepositResumed(ConfidentialDepositResumed),
+    GuardianUpdated(GuardianUpdated),
+    Harvested(Harvested),
+    HarvestConfigUpdated(HarvestConfigUpdated),
+    ComplianceMemo(ComplianceMemo),
+    ProtocolStatusChanged(ProtocolStatusChanged),
+    ProtocolConfigUpdated(ProtocolConfigUpdated),
+}
+
+impl VaultEvent {
+    /// Decode event data (discriminator and body), or `None` for anything
+    /// else, such as events of other programs
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        match discriminator {
+            VaultInitialized::DISCRIMINATOR => {
+                VaultInitialized::decode(data).map(Self::VaultInitialized)
+            }
+            Deposit::DISCRIMINATOR => Deposit::decode(data).map(Self::Deposit),
+            Withdraw::DISCRIMINATOR => Withdraw::decode(data).map(Self::Withdraw),
+            VaultSynced::DISCRIMINATOR => VaultSynced::decode(data).map(Self::VaultSynced),
+            VaultStatusChanged::DISCRIMINATOR => {
+                VaultStatusChanged::decode(data).map(Self::VaultStatusChanged)
+            }
+            AuthorityTransferred::DISCRIMINATOR => {
+                AuthorityTransferred::decode(data).map(Self::AuthorityTransferred)
+            }
+            MinDepositUpdated::DISCRIMINATOR => {
+                MinDepositUpdated::decode(data).map(Self::MinDepositUpdated)
+            }
+            EmergencyRedeem::DISCRIMINATOR => {
+                EmergencyRedeem::decode(data).map(Self::EmergencyRedeem)
+            }
+            ConfidentialDepositResumed::DISCRIMINATOR => {
+                ConfidentialDepositResumed::decode(data).map(Self::ConfidentialDepositResumed)
+            }
+            GuardianUpdated::DISCRIMINATOR => {
+                GuardianUpdated::decode(data).map(Self::GuardianUpdated)
+            }
+            Harvested::DISCRIMINATOR => Harvested::decode(data).map(Self::Harvested),
+            HarvestConfigUpdated::DISCRIMINATOR => {
+                HarvestConfigUpdated::decode(data).map(Self::HarvestConfigUpdated)
+            }
+            ComplianceMemo::DISCRIMINATOR => ComplianceMemo::decode(data).map(Self::ComplianceMemo),
+            ProtocolStatusChanged::DISCRIMINATOR => {
+                ProtocolStatusChanged::decode(data).map(Self::ProtocolStatusChanged)
+            }
+            ProtocolConfigUpdated::DISCRIMINATOR => {
+                ProtocolConfigUpdated::decode(data).map(Self::ProtocolConfigUpdated)
+            }
+            _ => None,
+        }
+    }
+
+    /// The vault the event belongs to, or the config PDA for protocol config
+    /// events
+    pub fn vault(&self) -> &Pubkey {
+        match self {
+            Self::VaultInitialized(event) => &event.vault,
+            Self::Deposit(event) => &event.vault,
+            Self::Withdraw(event) => &event.vault,
+            Self::VaultSynced(event) => &event.vault,
+            Self::VaultStatusChanged(event) => &event.vault,
+            Self::AuthorityTransferred(event) => &event.vault,
+            Self::MinDepositUpdated(event) => &event.vault,
+            Self::EmergencyRedeem(event) => &event.vault,
+            Self::ConfidentialDepositResumed(event) => &event.vault,
+            Self::GuardianUpdated(event) => &event.vault,
+            Self::Harvested(event) => &event.vault,
+            Self::HarvestConfigUpdated(event) => &event.vault,
+            Self::ComplianceMemo(event) => &event.vault,
+            Self::ProtocolStatusChanged(event) => &event.config,
+            Self::ProtocolConfigUpdated(event) => &event.config,
+        }
+    }
+
+    /// Mutating instructions run on the vault up to and including the one
+    /// that emitted the event. Every config instruction emits exactly one
+    /// event, so for protocol config events this is their sequence.
+    pub fn op_counter(&self) -> u64 {
+        match self {
+            Self::VaultInitialized(event) => event.op_counter,
+            Self::Deposit(event) => event.op_counter,
+            Self::Withdraw(event) => event.op_counter,
+            Self::VaultSynced(event) => event.op_counter,
+            Self::VaultStatusChanged(event) => event.op_counter,
+            Self::AuthorityTransferred(event) => event.op_counter,
+            Self::MinDepositUpdated(event) => event.op_counter,
+            Self::EmergencyRedeem(event) => event.op_counter,
+            Self::ConfidentialDepositResumed(event) => event.op_counter,
+            Self::GuardianUpdated(event) => event.op_counter,
+            Self::Harvested(event) => event.op_counter,
+            Self::HarvestConfigUpdated(event) => event.op_counter,
+            Self::ComplianceMemo(event) => event.op_counter,
+            Self::ProtocolStatusChanged(event) => event.sequence,
+            Self::ProtocolConfigUpdated(event) => event.sequence,
+        }
+    }
+
+    /// Position of the event among its vault's events, starting at 1
+    pub fn sequence(&self) -> u64 {
+        match self {
+            Self::VaultInitialized(event) => event.sequence,
+            Self::Deposit(event) => event.sequence,
+            Self::Withdraw(event) => event.sequence,
+            Self::VaultSynced(event) => event.sequence,
+            Self::VaultStatusChanged(event) => event.sequence,
+            Self::AuthorityTransferred(event) => event.sequence,
+            Self::MinDepositUpdated(event) => event.sequence,
+            Self::EmergencyRedeem(event) => event.sequence,
+            Self::ConfidentialDepositResumed(event) => event.sequence,
+            Self::GuardianUpdated(event) => event.sequence,
+            Self::Harvested(event) => event.sequence,
+            Self::HarvestConfigUpdated(event) => event.sequence,
+            Self::ComplianceMemo(event) => event.sequence,
+            Self::ProtocolStatusChanged(event) => event.sequence,
+            Self::ProtocolConfigUpdated(event) => event.sequence,
+        }
+    }
+
+    /// Cluster unix timestamp when the event was emitted
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            Self::VaultInitialized(event) => event.timestamp,
+            Self::Deposit(event) => event.timestamp,
+            Self::Withdraw(event) => event.timestamp,
+            Self::VaultSynced(event) => event.timestamp,
+            Self::VaultStatusChanged(event) => event.timestamp,
+            Self::AuthorityTransferred(event) => event.timestamp,
+            Self::MinDepositUpdated(event) => event.timestamp,
+            Self::EmergencyRedeem(event) => event.timestamp,
+            Self::ConfidentialDepositResumed(event) => event.timestamp,
+            Self::GuardianUpdated(event) => event.timestamp,
+            Self::Harvested(event) => event.timestamp,
+            Self::HarvestConfigUpdated(event) => event.timestamp,
+            Self::ComplianceMemo(event) => event.timestamp,
+            Self::ProtocolStatusChanged(event) => event.timestamp,
+            Self::ProtocolConfigUpdated(event) => event.timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{Discriminator, Event as _};
+
+    use super::*;
+
+    #[test]
+    fn test_discriminators_match_program() {
+        assert_eq!(
+            VaultInitialized::DISCRIMINATOR,
+            svs_2::events::VaultInitialized::DISCRIMINATOR
+        );
+        assert_eq!(
+            Deposit::DISCRIMINATOR,
+            svs_2::events::Deposit::DISCRIMINATOR
+        );
+        assert_eq!(
+            Withdraw::DISCRIMINATOR,
+            svs_2::events::Withdraw::DISCRIMINATOR
+        );
+        assert_eq!(
+            VaultSynced::DISCRIMINATOR,
+            svs_2::events::VaultSynced::DISCRIMINATOR
+        );
+        assert_eq!(
+            VaultStatusChanged::DISCRIMINATOR,
+            svs_2::events::VaultStatusChanged::DISCRIMINATOR
+        );
+        assert_eq!(
+            AuthorityTransferred::DISCRIMINATOR,
+            svs_2::events::AuthorityTransferred::DISCRIMINATOR
+        );
+        assert_eq!(
+            MinDepositUpdated::DISCRIMINATOR,
+            svs_2::events::MinDepositUpdated::DISCRIMINATOR
+        );
+        assert_eq!(
+            MinDepositUpdated::DISCRIMINATOR,
+            svs_1::events::MinDepositUpdated::DISCRIMINATOR
+        );
+        assert_eq!(
+            EmergencyRedeem::DISCRIMINATOR,
+            svs_2::events::EmergencyRedeem::DISCRIMINATOR
+        );
+        assert_eq!(
+            ConfidentialDepositResumed::DISCRIMINATOR,
+            svs_2::events::ConfidentialDepositResumed::DISCRIMINATOR
+        );
+        assert_eq!(
+            GuardianUpdated::DISCRIMINATOR,
+            svs_2::events::GuardianUpdated::DISCRIMINATOR
+        );
+        assert_eq!(
+            Harvested::DISCRIMINATOR,
+            svs_2::events::Harvested::DISCRIMINATOR
+        );
+        assert_eq!(
+            HarvestConfigUpdated::DISCRIMINATOR,
+            svs_2::events::HarvestConfigUpdated::DISCRIMINATOR
+        );
+        assert_eq!(
+            ComplianceMemo::DISCRIMINATOR,
+            svs_2::events::ComplianceMemo::DISCRIMINATOR
+        );
+        assert_eq!(
+            ProtocolStatusChanged::DISCRIMINATOR,
+            svs_2::events::ProtocolStatusChanged::DISCRIMINATOR
+        );
+        assert_eq!(
+            ProtocolConfigUpdated::DISCRIMINATOR,
+            svs_2::events::ProtocolConfigUpdated::DISCRIMINATOR
+        );
+        assert_eq!(
+            Deposit::DISCRIMINATOR,
+            svs_1::events::Deposit::DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn test_decode_program_events() {
+        let vault = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        let data = svs_1::events::Withdraw {
+            vault,
+            caller: owner,
+            receiver: owner,
+            owner,
+            assets: 1_000,
+            shares: 990,
+            op_counter: 7,
+            sequence: 7,
+            timestamp: 1_700_000_000,
+        }
+        .data();
+        let Some(VaultEvent::Withdraw(event)) = VaultEvent::decode(&data) else {
+            panic!("not a withdraw");
+        };
+        assert_eq!((event.assets, event.shares), (1_000, 990));
+        assert_eq!((event.sequence, event.timestamp), (7, 1_700_000_000));
+
+        let data = svs_2::events::ComplianceMemo {
+            vault,
+            owner,
+            operation: svs_2::events::ComplianceOperation::Withdraw,
+            memo_hash: [9; 32],
+            op_counter: 8,
+            sequence: 8,
+            timestamp: 1_700_000_000,
+        }
+        .data();
+        let event = VaultEvent::decode(&data).unwrap();
+        assert_eq!(event.vault(), &vault);
+        assert_eq!(event.sequence(), 8);
+        assert_eq!(
+            event,
+            VaultEvent::ComplianceMemo(ComplianceMemo {
+                vault,
+                owner,
+                operation: ComplianceOperation::Withdraw,
+                memo_hash: [9; 32],
+                op_counter: 8,
+                sequence: 8,
+                timestamp: 1_700_000_000,
+            })
+        );
+
+        let data = svs_2::events::HarvestConfigUpdated {
+            vault,
+            swap_adapter: owner,
+            harvester: owner,
+            profit_unlock_period: -1,
+            op_counter: 1,
+            sequence: 1,
+            timestamp: 0,
+        }
+        .data();
+        assert_eq!(
+            HarvestConfigUpdated::decode(&data)
+                .unwrap()
+                .profit_unlock_period,
+            -1
+        );
+        assert_eq!(Deposit::decode(&data), None);
+        assert_eq!(VaultEvent::decode(&data[..20]), None);
+
+        let data = svs_1::events::ProtocolStatusChanged {
+            config: vault,
+            caller: owner,
+            paused: true,
+            sequence: 3,
+            timestamp: 0,
+        }
+        .data();
+        let event = VaultEvent::decode(&data).unwrap();
+        assert_eq!(event.vault(), &vault);
+        assert_eq!((event.op_counter(), event.sequence()), (3, 3));
+    }
+}
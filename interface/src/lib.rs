@@ -0,0 +1,137 @@
+//! Interface of the Solana Vault Standard programs, for other programs
+//!
+//! Program ids, PDA derivation, instruction builders for the user
+//! instructions and views, view return data and event layouts of SVS-1 and
+//! SVS-2, with no dependency on Anchor or the program crates. An on-chain
+//! program builds an instruction here and invokes it with the matching
+//! account infos:
+//!
+//! ```ignore
+//! let vault = VaultKeys::derive(&svs_interface::SVS_1_ID, &asset_mint, 0, &spl_token::ID);
+//! let user = UserAccounts::associated(&vault, &strategy_pda);
+//! let ix = svs_interface::svs1::deposit(&vault, &user, assets, min_shares_out);
+//! invoke_signed(&ix, &account_infos, &[strategy_seeds])?;
+//!
+//! let ix = svs_interface::view::instruction(&vault, &View::ConvertToAssets(shares));
+//! invoke(&ix, &[vault_info, shares_mint_info, config_info, asset_mint_info])?;
+//! let assets = svs_interface::view::get_return_value(&vault.program_id)?;
+//! ```
+//!
+//! The account order of every instruction is the programs' own, checked
+//! against the Anchor program crates in this crate's tests.
+
+pub mod events;
+pub mod pda;
+pub mod svs1;
+pub mod svs2;
+pub mod view;
+
+use solana_program::{pubkey, pubkey::Pubkey};
+
+pub use pda::{UserAccounts, VaultKeys};
+
+/// Program id of the canonical SVS-1 deployment
+pub const SVS_1_ID: Pubkey = pubkey!("Bv8aVSQ3DJUe3B7TqQZRZgrNvVTh8TjfpwpoeR1ckDMC");
+
+/// Program id of the canonical SVS-2 deployment
+pub const SVS_2_ID: Pubkey = pubkey!("3UrYrxh1HmVgq7WPygZ5x1gNEaWFwqTMs7geNqMnsrtD");
+
+/// Token-2022, the program of every shares mint
+pub const TOKEN_2022_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
+/// Anchor instruction discriminators: `sha256("global:<name>")[..8]`. SVS-1
+/// and SVS-2 share every name, so one set serves both programs.
+pub mod discriminator {
+    pub const DEPOSIT: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+    pub const MINT: [u8; 8] = [51, 57, 225, 47, 182, 146, 137, 166];
+    pub const WITHDRAW: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+    pub const REDEEM: [u8; 8] = [184, 12, 86, 149, 70, 196, 97, 225];
+    /// SVS-2 only
+    pub const EMERGENCY_REDEEM: [u8; 8] = [209, 100, 143, 232, 38, 85, 19, 115];
+
+    pub const PREVIEW_DEPOSIT: [u8; 8] = [16, 61, 8, 235, 146, 126, 80, 84];
+    pub const PREVIEW_MINT: [u8; 8] = [42, 71, 86, 125, 215, 190, 214, 81];
+    pub const PREVIEW_WITHDRAW: [u8; 8] = [66, 3, 217, 38, 187, 176, 144, 135];
+    pub const PREVIEW_REDEEM: [u8; 8] = [122, 195, 125, 8, 168, 149, 50, 40];
+    pub const SATURATING_PREVIEW_DEPOSIT: [u8; 8] = [6, 205, 67, 111, 236, 102, 77, 117];
+    pub const SATURATING_PREVIEW_MINT: [u8; 8] = [53, 42, 197, 76, 150, 83, 114, 247];
+    pub const SATURATING_PREVIEW_WITHDRAW: [u8; 8] = [34, 148, 70, 43, 163, 135, 6, 146];
+    pub const SATURATING_PREVIEW_REDEEM: [u8; 8] = [160, 93, 194, 154, 173, 142, 245, 14];
+    pub const CONVERT_TO_SHARES: [u8; 8] = [105, 52, 27, 205, 69, 47, 239, 221];
+    pub const CONVERT_TO_ASSETS: [u8; 8] = [229, 109, 118, 143, 110, 190, 39, 123];
+    pub const SHARE_PRICE: [u8; 8] = [174, 102, 57, 197, 231, 233, 7, 41];
+    pub const TOTAL_ASSETS: [u8; 8] = [21, 211, 56, 201, 244, 191, 106, 230];
+    pub const MAX_DEPOSIT: [u8; 8] = [160, 113, 66, 36, 132, 127, 192, 252];
+    pub const MAX_MINT: [u8; 8] = [110, 223, 78, 157, 235, 80, 65, 229];
+    pub const MAX_WITHDRAW: [u8; 8] = [120, 205, 134, 47, 124, 123, 74, 119];
+    pub const MAX_REDEEM: [u8; 8] = [65, 154, 51, 37, 235, 156, 235, 72];
+
+    /// Account discriminator of SVS-1 vaults
+    pub const VAULT_ACCOUNT: [u8; 8] = [211, 8, 232, 43, 2, 152, 117, 119];
+    /// Account discriminator of SVS-2 vaults
+    pub const CONFIDENTIAL_VAULT_ACCOUNT: [u8; 8] = [107, 161, 220, 30, 88, 176, 39, 252];
+}
+
+/// Instruction data: `discriminator` followed by Borsh-encoded arguments
+fn data(discriminator: [u8; 8], args: &impl borsh::BorshSerialize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    args.serialize(&mut data)
+        .expect("serializing into a Vec cannot fail");
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Discriminator;
+
+    use super::*;
+
+    #[test]
+    fn test_ids_match_programs() {
+        assert_eq!(SVS_1_ID, svs_1::ID);
+        assert_eq!(SVS_2_ID, svs_2::ID);
+        assert_eq!(TOKEN_2022_ID, anchor_spl::token_2022::ID);
+        assert_eq!(
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            anchor_spl::associated_token::ID
+        );
+        assert_eq!(SYSTEM_PROGRAM_ID, anchor_lang::system_program::ID);
+    }
+
+    #[test]
+    fn test_seeds_match_programs() {
+        for (vault, shares, config) in [
+            (
+                svs_1::constants::VAULT_SEED,
+                svs_1::constants::SHARES_MINT_SEED,
+                svs_1::constants::CONFIG_SEED,
+            ),
+            (
+                svs_2::constants::VAULT_SEED,
+                svs_2::constants::SHARES_MINT_SEED,
+                svs_2::constants::CONFIG_SEED,
+            ),
+        ] {
+            assert_eq!(pda::VAULT_SEED, vault);
+            assert_eq!(pda::SHARES_MINT_SEED, shares);
+            assert_eq!(pda::CONFIG_SEED, config);
+        }
+    }
+
+    #[test]
+    fn test_account_discriminators_match_programs() {
+        assert_eq!(
+            discriminator::VAULT_ACCOUNT,
+            svs_1::state::Vault::DISCRIMINATOR
+        );
+        assert_eq!(
+            discriminator::CONFIDENTIAL_VAULT_ACCOUNT,
+            svs_2::state::ConfidentialVault::DISCRIMINATOR
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Program-derived addresses and token accounts of a vault
+//!
+//! SVS-1 and SVS-2 derive their accounts with the same seeds, so every
+//! function takes the program id of the vault's program.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_ID};
+
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const SHARES_MINT_SEED: &[u8] = b"shares";
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Vault PDA: `["vault", asset_mint, vault_id (u64 LE)]`
+pub fn find_vault_address(program_id: &Pubkey, asset_mint: &Pubkey, vault_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_SEED, asset_mint.as_ref(), &vault_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Shares mint PDA: `["shares", vault]`
+pub fn find_shares_mint_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SHARES_MINT_SEED, vault.as_ref()], program_id)
+}
+
+/// Protocol config PDA of a program: `["config"]`
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Associated token account of `owner` for `mint` under `token_program`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Accounts every instruction of one vault refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultKeys {
+    /// Program the vault belongs to (SVS-1 or SVS-2)
+    pub program_id: Pubkey,
+    pub vault: Pubkey,
+    pub asset_mint: Pubkey,
+    pub shares_mint: Pubkey,
+    /// The vault's associated token account for the asset mint
+    pub asset_vault: Pubkey,
+    /// Token program owning the asset mint (SPL Token or Token-2022)
+    pub asset_token_program: Pubkey,
+    /// Protocol config PDA of the program, read for the protocol pause
+    pub config: Pubkey,
+}
+
+impl VaultKeys {
+    /// Derive the accounts of vault `vault_id` for `asset_mint`
+    ///
+    /// This costs three PDA searches; programs that store a vault's addresses
+    /// can fill in the struct directly.
+    pub fn derive(
+        program_id: &Pubkey,
+        asset_mint: &Pubkey,
+        vault_id: u64,
+        asset_token_program: &Pubkey,
+    ) -> Self {
+        let (vault, _) = find_vault_address(program_id, asset_mint, vault_id);
+        let (shares_mint, _) = find_shares_mint_address(program_id, &vault);
+        let (config, _) = find_config_address(program_id);
+
+        Self {
+            program_id: *program_id,
+            vault,
+            asset_mint: *asset_mint,
+            shares_mint,
+            asset_vault: associated_token_address(&vault, asset_mint, asset_token_program),
+            asset_token_program: *asset_token_program,
+            config,
+        }
+    }
+}
+
+/// The signer of a user instruction and its token accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserAccounts {
+    pub user: Pubkey,
+    /// Token account of `user` for the asset mint
+    pub asset_account: Pubkey,
+    /// Token-2022 account of `user` for the shares mint. SVS-1 deposits
+    /// create it if needed, so it must be the associated token account.
+    pub shares_account: Pubkey,
+}
+
+impl UserAccounts {
+    /// `user` with its associated token accounts
+    pub fn associated(keys: &VaultKeys, user: &Pubkey) -> Self {
+        Self {
+            user: *user,
+            asset_account: associated_token_address(
+                user,
+                &keys.asset_mint,
+                &keys.asset_token_program,
+            ),
+            shares_account: associated_token_address(user, &keys.shares_mint, &TOKEN_2022_ID),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! SVS-1 user instructions
+//!
+//! `keys.program_id` must be the SVS-1 program. Deposits create the user's
+//! shares account if needed, so it must be their associated token account;
+//! the user pays its rent.
+
+use solana_program::instruction::{AccountMeta, Instruction};
+
+use crate::{
+    data, discriminator, UserAccounts, VaultKeys, ASSOCIATED_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+    TOKEN_2022_ID,
+};
+
+/// Deposit `assets` and receive at least `min_shares_out` shares
+pub fn deposit(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    assets: u64,
+    min_shares_out: u64,
+) -> Instruction {
+    Instruction {
+        program_id: keys.program_id,
+        accounts: deposit_accounts(keys, user),
+        data: data(discriminator::DEPOSIT, &(assets, min_shares_out)),
+    }
+}
+
+/// Mint exactly `shares` for at most `max_assets_in` assets
+pub fn mint(keys: &VaultKeys, user: &UserAccounts, shares: u64, max_assets_in: u64) -> Instruction {
+    Instruction {
+        program_id: keys.program_id,
+        accounts: deposit_accounts(keys, user),
+        data: data(discriminator::MINT, &(shares, max_assets_in)),
+    }
+}
+
+/// Withdraw exactly `assets`, burning at most `max_shares_in` shares
+pub fn withdraw(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    assets: u64,
+    max_shares_in: u64,
+) -> Instruction {
+    Instruction {
+        program_id: keys.program_id,
+        accounts: withdraw_accounts(keys, user),
+        data: data(discriminator::WITHDRAW, &(assets, max_shares_in)),
+    }
+}
+
+/// Redeem `shares` for at least `min_assets_out` assets
+pub fn redeem(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    shares: u64,
+    min_assets_out: u64,
+) -> Instruction {
+    Instruction {
+        program_id: keys.program_id,
+        accounts: withdraw_accounts(keys, user),
+        data: data(discriminator::REDEEM, &(shares, min_assets_out)),
+    }
+}
+
+/// Accounts of `deposit` and `mint`
+fn deposit_accounts(keys: &VaultKeys, user: &UserAccounts) -> Vec<AccountMeta> {
+    let mut accounts = user_accounts(keys, user);
+    accounts.extend([
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(keys.config, false),
+    ]);
+    accounts
+}
+
+/// Accounts of `withdraw` and `redeem`
+fn withdraw_accounts(keys: &VaultKeys, user: &UserAccounts) -> Vec<AccountMeta> {
+    let mut accounts = user_accounts(keys, user);
+    accounts.push(AccountMeta::new_readonly(keys.config, false));
+    accounts
+}
+
+/// The user, vault, token accounts and token programs every instruction starts with
+fn user_accounts(keys: &VaultKeys, user: &UserAccounts) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(user.user, true),
+        AccountMeta::new(keys.vault, false),
+        AccountMeta::new_readonly(keys.asset_mint, false),
+        AccountMeta::new(user.asset_account, false),
+        AccountMeta::new(keys.asset_vault, false),
+        AccountMeta::new(keys.shares_mint, false),
+        AccountMeta::new(user.shares_account, false),
+        AccountMeta::new_readonly(keys.asset_token_program, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use solana_program::pubkey::Pubkey;
+
+    use super::*;
+
+    fn fixture() -> (VaultKeys, UserAccounts) {
+        let keys = VaultKeys::derive(&crate::SVS_1_ID, &Pubkey::new_unique(), 3, &spl_token());
+        let user = UserAccounts::associated(&keys, &Pubkey::new_unique());
+        (keys, user)
+    }
+
+    fn spl_token() -> Pubkey {
+        solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+    }
+
+    fn anchor_deposit(keys: &VaultKeys, user: &UserAccounts) -> svs_1::accounts::Deposit {
+        svs_1::accounts::Deposit {
+            user: user.user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: user.asset_account,
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: user.shares_account,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: TOKEN_2022_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            config: keys.config,
+        }
+    }
+
+    fn anchor_withdraw(keys: &VaultKeys, user: &UserAccounts) -> svs_1::accounts::Withdraw {
+        svs_1::accounts::Withdraw {
+            user: user.user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: user.asset_account,
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: user.shares_account,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: TOKEN_2022_ID,
+            config: keys.config,
+        }
+    }
+
+    #[test]
+    fn test_deposit_and_mint_match_program() {
+        let (keys, user) = fixture();
+        let expected = anchor_deposit(&keys, &user).to_account_metas(None);
+
+        let ix = deposit(&keys, &user, 1_000, 990);
+        assert_eq!(ix.program_id, svs_1::ID);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_1::instruction::Deposit {
+            assets: 1_000,
+            min_shares_out: 990,
+        };
+        assert_eq!(ix.data, args.data());
+
+        let ix = mint(&keys, &user, 500, 510);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_1::instruction::Mint {
+            shares: 500,
+            max_assets_in: 510,
+        };
+        assert_eq!(ix.data, args.data());
+    }
+
+    #[test]
+    fn test_withdraw_and_redeem_match_program() {
+        let (keys, user) = fixture();
+        let expected = anchor_withdraw(&keys, &user).to_account_metas(None);
+
+        let ix = withdraw(&keys, &user, 1_000, 1_010);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_1::instruction::Withdraw {
+            assets: 1_000,
+            max_shares_in: 1_010,
+        };
+        assert_eq!(ix.data, args.data());
+
+        let ix = redeem(&keys, &user, 500, 490);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_1::instruction::Redeem {
+            shares: 500,
+            min_assets_out: 490,
+        };
+        assert_eq!(ix.data, args.data());
+    }
+}
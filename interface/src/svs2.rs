@@ -0,0 +1,275 @@
+//! SVS-2 user instructions
+//!
+//! `keys.program_id` must be the SVS-2 program. The user's shares account
+//! must already be configured for confidential transfers; deposits and mints
+//! never charge the user rent, so the user only needs to sign. Withdrawals
+//! and redemptions need the proof context accounts that the client verified
+//! beforehand (see the SDK's withdraw flow).
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    data, discriminator, UserAccounts, VaultKeys, ASSOCIATED_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+    TOKEN_2022_ID,
+};
+
+/// Pre-verified proof context state accounts of a confidential withdrawal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofContexts {
+    /// CiphertextCommitmentEqualityProof context
+    pub equality: Pubkey,
+    /// BatchedRangeProofU64 context
+    pub range: Pubkey,
+}
+
+/// Deposit `assets` for at least `min_shares_out` shares, credited to the
+/// pending confidential balance. `memo_hash` links the deposit to an
+/// off-chain compliance record.
+pub fn deposit(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    assets: u64,
+    min_shares_out: u64,
+    memo_hash: Option<[u8; 32]>,
+) -> Instruction {
+    Instruction {
+        program_id: keys.program_id,
+        accounts: deposit_accounts(keys, user),
+        data: data(discriminator::DEPOSIT, &(assets, min_shares_out, memo_hash)),
+    }
+}
+
+/// Mint exactly `shares` for at most `max_assets_in` assets
+pub fn mint(keys: &VaultKeys, user: &UserAccounts, shares: u64, max_assets_in: u64) -> Instruction {
+    Instruction {
+        program_id: keys.program_id,
+        accounts: deposit_accounts(keys, user),
+        data: data(discriminator::MINT, &(shares, max_assets_in)),
+    }
+}
+
+/// Withdraw exactly `assets`, burning at most `max_shares_in` confidential
+/// shares. `new_decryptable_available_balance` is the AE ciphertext of the
+/// available balance after the burn.
+pub fn withdraw(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    proofs: &ProofContexts,
+    assets: u64,
+    max_shares_in: u64,
+    new_decryptable_available_balance: [u8; 36],
+    memo_hash: Option<[u8; 32]>,
+) -> Instruction {
+    let args = (
+        assets,
+        max_shares_in,
+        new_decryptable_available_balance,
+        memo_hash,
+    );
+    Instruction {
+        program_id: keys.program_id,
+        accounts: withdraw_accounts(keys, user, proofs),
+        data: data(discriminator::WITHDRAW, &args),
+    }
+}
+
+/// Redeem `shares` confidential shares for at least `min_assets_out` assets
+pub fn redeem(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    proofs: &ProofContexts,
+    shares: u64,
+    min_assets_out: u64,
+    new_decryptable_available_balance: [u8; 36],
+) -> Instruction {
+    let args = (shares, min_assets_out, new_decryptable_available_balance);
+    Instruction {
+        program_id: keys.program_id,
+        accounts: withdraw_accounts(keys, user, proofs),
+        data: data(discriminator::REDEEM, &args),
+    }
+}
+
+/// Redeem `shares` from the public shares balance pro rata while the vault is
+/// paused
+pub fn emergency_redeem(keys: &VaultKeys, user: &UserAccounts, shares: u64) -> Instruction {
+    let mut accounts = user_accounts(keys, user, false);
+    accounts.extend(token_programs(keys));
+    Instruction {
+        program_id: keys.program_id,
+        accounts,
+        data: data(discriminator::EMERGENCY_REDEEM, &shares),
+    }
+}
+
+/// Accounts of `deposit` and `mint`
+fn deposit_accounts(keys: &VaultKeys, user: &UserAccounts) -> Vec<AccountMeta> {
+    let mut accounts = user_accounts(keys, user, false);
+    accounts.extend(token_programs(keys));
+    accounts.extend([
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(keys.config, false),
+    ]);
+    accounts
+}
+
+/// Accounts of `withdraw` and `redeem`
+fn withdraw_accounts(
+    keys: &VaultKeys,
+    user: &UserAccounts,
+    proofs: &ProofContexts,
+) -> Vec<AccountMeta> {
+    let mut accounts = user_accounts(keys, user, true);
+    accounts.push(AccountMeta::new_readonly(proofs.equality, false));
+    accounts.push(AccountMeta::new_readonly(proofs.range, false));
+    accounts.extend(token_programs(keys));
+    accounts.push(AccountMeta::new_readonly(keys.config, false));
+    accounts
+}
+
+/// The user, vault and token accounts every user instruction starts with
+fn user_accounts(keys: &VaultKeys, user: &UserAccounts, user_writable: bool) -> Vec<AccountMeta> {
+    let signer = if user_writable {
+        AccountMeta::new(user.user, true)
+    } else {
+        AccountMeta::new_readonly(user.user, true)
+    };
+    vec![
+        signer,
+        AccountMeta::new(keys.vault, false),
+        AccountMeta::new_readonly(keys.asset_mint, false),
+        AccountMeta::new(user.asset_account, false),
+        AccountMeta::new(keys.asset_vault, false),
+        AccountMeta::new(keys.shares_mint, false),
+        AccountMeta::new(user.shares_account, false),
+    ]
+}
+
+fn token_programs(keys: &VaultKeys) -> [AccountMeta; 2] {
+    [
+        AccountMeta::new_readonly(keys.asset_token_program, false),
+        AccountMeta::new_readonly(TOKEN_2022_ID, false),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+
+    use super::*;
+
+    fn fixture() -> (VaultKeys, UserAccounts, ProofContexts) {
+        let keys = VaultKeys::derive(&crate::SVS_2_ID, &Pubkey::new_unique(), 0, &TOKEN_2022_ID);
+        let user = UserAccounts::associated(&keys, &Pubkey::new_unique());
+        let proofs = ProofContexts {
+            equality: Pubkey::new_unique(),
+            range: Pubkey::new_unique(),
+        };
+        (keys, user, proofs)
+    }
+
+    #[test]
+    fn test_deposit_and_mint_match_program() {
+        let (keys, user, _) = fixture();
+        let expected = svs_2::accounts::Deposit {
+            user: user.user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: user.asset_account,
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: user.shares_account,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: TOKEN_2022_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            config: keys.config,
+        }
+        .to_account_metas(None);
+
+        let ix = deposit(&keys, &user, 1_000, 990, Some([7; 32]));
+        assert_eq!(ix.program_id, svs_2::ID);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_2::instruction::Deposit {
+            assets: 1_000,
+            min_shares_out: 990,
+            memo_hash: Some([7; 32]),
+        };
+        assert_eq!(ix.data, args.data());
+
+        let ix = mint(&keys, &user, 500, 510);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_2::instruction::Mint {
+            shares: 500,
+            max_assets_in: 510,
+        };
+        assert_eq!(ix.data, args.data());
+    }
+
+    #[test]
+    fn test_withdraw_and_redeem_match_program() {
+        let (keys, user, proofs) = fixture();
+        let expected = svs_2::accounts::Withdraw {
+            user: user.user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: user.asset_account,
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: user.shares_account,
+            equality_proof_context: proofs.equality,
+            range_proof_context: proofs.range,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: TOKEN_2022_ID,
+            config: keys.config,
+        }
+        .to_account_metas(None);
+
+        let ix = withdraw(&keys, &user, &proofs, 1_000, 1_010, [3; 36], None);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_2::instruction::Withdraw {
+            assets: 1_000,
+            max_shares_in: 1_010,
+            new_decryptable_available_balance: [3; 36],
+            memo_hash: None,
+        };
+        assert_eq!(ix.data, args.data());
+
+        let ix = redeem(&keys, &user, &proofs, 500, 490, [3; 36]);
+        assert_eq!(ix.accounts, expected);
+        let args = svs_2::instruction::Redeem {
+            shares: 500,
+            min_assets_out: 490,
+            new_decryptable_available_balance: [3; 36],
+        };
+        assert_eq!(ix.data, args.data());
+    }
+
+    #[test]
+    fn test_emergency_redeem_matches_program() {
+        let (keys, user, _) = fixture();
+        let expected = svs_2::accounts::EmergencyRedeem {
+            user: user.user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: user.asset_account,
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: user.shares_account,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: TOKEN_2022_ID,
+        }
+        .to_account_metas(None);
+
+        let ix = emergency_redeem(&keys, &user, 42);
+        assert_eq!(ix.accounts, expected);
+        assert_eq!(
+            ix.data,
+            svs_2::instruction::EmergencyRedeem { shares: 42 }.data()
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! View instructions and their return data
+//!
+//! Views return a `u64` through return data. A program invokes the view,
+//! then reads the value with [`get_return_value`] in the same instruction.
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::get_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{data, discriminator, VaultKeys};
+
+/// A view instruction and its argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Shares minted for depositing `assets`
+    PreviewDeposit(u64),
+    /// Assets required to mint `shares`
+    PreviewMint(u64),
+    /// Shares burned to withdraw `assets`
+    PreviewWithdraw(u64),
+    /// Assets received for redeeming `shares`
+    PreviewRedeem(u64),
+    /// `PreviewDeposit`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewDeposit(u64),
+    /// `PreviewMint`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewMint(u64),
+    /// `PreviewWithdraw`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewWithdraw(u64),
+    /// `PreviewRedeem`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewRedeem(u64),
+    /// `assets` in shares, rounded down
+    ConvertToShares(u64),
+    /// `shares` in assets, rounded down
+    ConvertToAssets(u64),
+    /// Assets per share, in `SHARE_PRICE_SCALE` (1e12) fixed point
+    SharePrice,
+    TotalAssets,
+    MaxDeposit,
+    MaxMint,
+    /// Assets the owner of this shares account can withdraw
+    MaxWithdraw(Pubkey),
+    /// Shares the owner of this shares account can redeem
+    MaxRedeem(Pubkey),
+}
+
+/// Instruction calling `view` on the vault described by `keys`. The
+/// accounts are the vault and shares mint, plus the shares account for
+/// `MaxWithdraw` and `MaxRedeem`, then the protocol config and the asset
+/// mint, all read-only. SVS-2 reads the asset mint's transfer fee; SVS-1
+/// ignores the account.
+pub fn instruction(keys: &VaultKeys, view: &View) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(keys.vault, false),
+        AccountMeta::new_readonly(keys.shares_mint, false),
+    ];
+
+    let data = match *view {
+        View::PreviewDeposit(assets) => data(discriminator::PREVIEW_DEPOSIT, &assets),
+        View::PreviewMint(shares) => data(discriminator::PREVIEW_MINT, &shares),
+        View::PreviewWithdraw(assets) => data(discriminator::PREVIEW_WITHDRAW, &assets),
+        View::PreviewRedeem(shares) => data(discriminator::PREVIEW_REDEEM, &shares),
+        View::SaturatingPreviewDeposit(assets) => {
+            data(discriminator::SATURATING_PREVIEW_DEPOSIT, &assets)
+        }
+        View::SaturatingPreviewMint(shares) => {
+            data(discriminator::SATURATING_PREVIEW_MINT, &shares)
+        }
+        View::SaturatingPreviewWithdraw(assets) => {
+            data(discriminator::SATURATING_PREVIEW_WITHDRAW, &assets)
+        }
+        View::SaturatingPreviewRedeem(shares) => {
+            data(discriminator::SATURATING_PREVIEW_REDEEM, &shares)
+        }
+        View::ConvertToShares(assets) => data(discriminator::CONVERT_TO_SHARES, &assets),
+        View::ConvertToAssets(shares) => data(discriminator::CONVERT_TO_ASSETS, &shares),
+        View::SharePrice => discriminator::SHARE_PRICE.to_vec(),
+        View::TotalAssets => discriminator::TOTAL_ASSETS.to_vec(),
+        View::MaxDeposit => discriminator::MAX_DEPOSIT.to_vec(),
+        View::MaxMint => discriminator::MAX_MINT.to_vec(),
+        View::MaxWithdraw(shares_account) => {
+            accounts.push(AccountMeta::new_readonly(shares_account, false));
+            discriminator::MAX_WITHDRAW.to_vec()
+        }
+        View::MaxRedeem(shares_account) => {
+            accounts.push(AccountMeta::new_readonly(shares_account, false));
+            discriminator::MAX_REDEEM.to_vec()
+        }
+    };
+    accounts.extend([
+        AccountMeta::new_readonly(keys.config, false),
+        AccountMeta::new_readonly(keys.asset_mint, false),
+    ]);
+
+    Instruction {
+        program_id: keys.program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Value of a view's return data: a little-endian `u64`. The runtime strips
+/// trailing zero bytes from return data, so shorter data is zero-padded.
+pub fn decode_return_data(data: &[u8]) -> Option<u64> {
+    if data.len() > 8 {
+        return None;
+    }
+    let mut bytes = [0u8; 8];
+    bytes[..data.len()].copy_from_slice(data);
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// Value returned by the view just invoked on `program_id`
+///
+/// Fails with `InvalidAccountData` if the last return data was not set by
+/// `program_id` or is not a `u64`.
+pub fn get_return_value(program_id: &Pubkey) -> Result<u64, ProgramError> {
+    match get_return_data() {
+        Some((program, data)) if program == *program_id => {
+            decode_return_data(&data).ok_or(ProgramError::InvalidAccountData)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use svs_1::instruction as ix;
+
+    use super::*;
+
+    #[test]
+    fn test_views_match_program() {
+        let keys = VaultKeys::derive(
+            &crate::SVS_1_ID,
+            &Pubkey::new_unique(),
+            0,
+            &crate::TOKEN_2022_ID,
+        );
+        // SVS-2's layout: SVS-1 ignores the trailing asset mint
+        let vault_view = svs_2::accounts::VaultView {
+            vault: keys.vault,
+            shares_mint: keys.shares_mint,
+            config: keys.config,
+            asset_mint: keys.asset_mint,
+        }
+        .to_account_metas(None);
+        let svs1_view = svs_1::accounts::VaultView {
+            vault: keys.vault,
+            shares_mint: keys.shares_mint,
+            config: keys.config,
+        }
+        .to_account_metas(None);
+        assert!(vault_view.starts_with(&svs1_view));
+
+        let cases: [(View, Vec<u8>); 14] = [
+            (
+                View::PreviewDeposit(1),
+                ix::PreviewDeposit { assets: 1 }.data(),
+            ),
+            (View::PreviewMint(2), ix::PreviewMint { shares: 2 }.data()),
+            (
+                View::PreviewWithdraw(3),
+                ix::PreviewWithdraw { assets: 3 }.data(),
+            ),
+            (
+                View::PreviewRedeem(4),
+                ix::PreviewRedeem { shares: 4 }.data(),
+            ),
+            (
+                View::SaturatingPreviewDeposit(1),
+                ix::SaturatingPreviewDeposit { assets: 1 }.data(),
+            ),
+            (
+                View::SaturatingPreviewMint(2),
+                ix::SaturatingPreviewMint { shares: 2 }.data(),
+            ),
+            (
+                View::SaturatingPreviewWithdraw(3),
+                ix::SaturatingPreviewWithdraw { assets: 3 }.data(),
+            ),
+            (
+                View::SaturatingPreviewRedeem(4),
+                ix::SaturatingPreviewRedeem { shares: 4 }.data(),
+            ),
+            (
+                View::ConvertToShares(5),
+                ix::ConvertToShares { assets: 5 }.data(),
+            ),
+            (
+                View::ConvertToAssets(6),
+                ix::ConvertToAssets { shares: 6 }.data(),
+            ),
+            (View::SharePrice, ix::SharePrice {}.data()),
+            (View::TotalAssets, ix::TotalAssets {}.data()),
+            (View::MaxDeposit, ix::MaxDeposit {}.data()),
+            (View::MaxMint, ix::MaxMint {}.data()),
+        ];
+        for (view, data) in cases {
+            let ix = instruction(&keys, &view);
+            assert_eq!(ix.accounts, vault_view, "{view:?}");
+            assert_eq!(ix.data, data, "{view:?}");
+        }
+
+        let shares_account = Pubkey::new_unique();
+        let with_owner = svs_2::accounts::VaultViewWithOwner {
+            vault: keys.vault,
+            shares_mint: keys.shares_mint,
+            owner_shares_account: shares_account,
+            config: keys.config,
+            asset_mint: keys.asset_mint,
+        }
+        .to_account_metas(None);
+        let ix = instruction(&keys, &View::MaxWithdraw(shares_account));
+        assert_eq!(ix.accounts, with_owner);
+        assert_eq!(ix.data, ix::MaxWithdraw {}.data());
+        let ix = instruction(&keys, &View::MaxRedeem(shares_account));
+        assert_eq!(ix.accounts, with_owner);
+        assert_eq!(ix.data, ix::MaxRedeem {}.data());
+    }
+
+    #[test]
+    fn test_decode_return_data() {
+        assert_eq!(decode_return_data(&1_000u64.to_le_bytes()), Some(1_000));
+        // Trailing zeros stripped by the runtime
+        assert_eq!(decode_return_data(&[0xe8, 0x03]), Some(1_000));
+        assert_eq!(decode_return_data(&[]), Some(0));
+        assert_eq!(decode_return_data(&[1; 9]), None);
+    }
+}
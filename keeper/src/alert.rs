@@ -0,0 +1,108 @@
+//! Failure alerting
+//!
+//! A job alerts once when it has failed `failures_before_alert` times in a
+//! row, and again when it next succeeds, so an outage produces two messages
+//! however long it lasts.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::config::AlertConfig;
+
+pub struct Alerts {
+    http: reqwest::Client,
+    config: AlertConfig,
+    failures: Mutex<Failures>,
+}
+
+impl Alerts {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            config,
+            failures: Mutex::new(Failures::default()),
+        }
+    }
+
+    /// Record a failed run of `job`
+    pub async fn failure(&self, job: &str, err: &anyhow::Error) {
+        warn!(job, "Job failed: {err:#}");
+        let threshold = self.config.failures_before_alert;
+        let count = self.failures.lock().await.fail(job);
+        if count == threshold.max(1) {
+            let text = format!("svs-keeper: {job} failed {count} times in a row: {err:#}");
+            self.send(job, text, false).await;
+        }
+    }
+
+    /// Record a successful run of `job`
+    pub async fn success(&self, job: &str) {
+        let count = self.failures.lock().await.succeed(job);
+        if count >= self.config.failures_before_alert.max(1) {
+            let text = format!("svs-keeper: {job} recovered after {count} failures");
+            self.send(job, text, true).await;
+        }
+    }
+
+    async fn send(&self, job: &str, text: String, resolved: bool) {
+        if resolved {
+            info!(job, "{text}");
+        } else {
+            error!(job, "{text}");
+        }
+        let Some(url) = &self.config.webhook_url else {
+            return;
+        };
+
+        let body = json!({ "text": text, "job": job, "resolved": resolved });
+        let result = self
+            .http
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(e) = result {
+            error!(job, "Failed to deliver alert: {e}");
+        }
+    }
+}
+
+/// Consecutive failures per job
+#[derive(Default)]
+struct Failures {
+    counts: HashMap<String, u32>,
+}
+
+impl Failures {
+    /// Count a failure; returns the failures in a row
+    fn fail(&mut self, job: &str) -> u32 {
+        let count = self.counts.entry(job.to_string()).or_default();
+        *count = count.saturating_add(1);
+        *count
+    }
+
+    /// Reset the count; returns the failures before this success
+    fn succeed(&mut self, job: &str) -> u32 {
+        self.counts.remove(job).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_counts() {
+        let mut failures = Failures::default();
+        assert_eq!(failures.fail("sync A"), 1);
+        assert_eq!(failures.fail("sync A"), 2);
+        assert_eq!(failures.fail("checkpoint A"), 1);
+        assert_eq!(failures.succeed("sync A"), 2);
+        assert_eq!(failures.succeed("sync A"), 0);
+        assert_eq!(failures.fail("sync A"), 1);
+    }
+}
@@ -0,0 +1,221 @@
+//! Configuration
+//!
+//! Loaded from the TOML file in `CONFIG_FILE` (see `config.example.toml`),
+//! then overridden by any of `SOLANA_RPC_URL`, `KEEPER_KEYPAIR` and
+//! `ALERT_WEBHOOK_URL` that are set.
+
+use std::{str::FromStr, time::Duration};
+
+use anyhow::{anyhow, bail, Context};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default = "default_rpc_url")]
+    pub rpc_url: String,
+    /// Keypair that signs and pays for every transaction
+    #[serde(default)]
+    pub keypair_path: String,
+    /// JSON lines file share-price checkpoints are appended to
+    #[serde(default = "default_checkpoint_path")]
+    pub checkpoint_path: String,
+    #[serde(default)]
+    pub priority_fee: PriorityFeeConfig,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    pub vaults: Vec<VaultConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityFeeConfig {
+    /// Percentile of the recent prioritization fees paid for the vault's
+    /// accounts that transactions bid
+    pub percentile: u8,
+    pub min_micro_lamports: u64,
+    pub max_micro_lamports: u64,
+    pub compute_unit_limit: u32,
+    /// Tries per job run; every retry doubles the fee
+    pub max_attempts: u32,
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 75,
+            min_micro_lamports: 0,
+            max_micro_lamports: 1_000_000,
+            compute_unit_limit: 50_000,
+            max_attempts: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Receives a JSON POST for every alert; alerts are only logged without it
+    pub webhook_url: Option<String>,
+    /// Consecutive failures of a job before it alerts
+    pub failures_before_alert: u32,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            failures_before_alert: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VaultConfig {
+    #[serde(deserialize_with = "pubkey")]
+    pub address: Pubkey,
+    pub sync: Option<SyncJob>,
+    pub checkpoint: Option<CheckpointJob>,
+}
+
+/// Sync `total_assets` up to the asset vault balance
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncJob {
+    pub interval_secs: u64,
+    /// Balance growth, in asset base units, worth a transaction
+    #[serde(default = "default_min_gain")]
+    pub min_gain: u64,
+}
+
+/// Record the vault's share price
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckpointJob {
+    pub interval_secs: u64,
+}
+
+fn default_rpc_url() -> String {
+    "https://api.mainnet-beta.solana.com".to_string()
+}
+
+fn default_checkpoint_path() -> String {
+    "checkpoints.jsonl".to_string()
+}
+
+fn default_min_gain() -> u64 {
+    1
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s).map_err(|e| D::Error::custom(format!("invalid pubkey {s}: {e}")))
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = std::env::var("CONFIG_FILE").context("CONFIG_FILE is required")?;
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read config file {path}: {e}"))?;
+        let mut config =
+            Self::from_toml(&contents).map_err(|e| anyhow!("Invalid config file {path}: {e}"))?;
+
+        if let Ok(url) = std::env::var("SOLANA_RPC_URL") {
+            config.rpc_url = url;
+        }
+        if let Ok(path) = std::env::var("KEEPER_KEYPAIR") {
+            config.keypair_path = path;
+        }
+        if let Ok(url) = std::env::var("ALERT_WEBHOOK_URL") {
+            config.alerts.webhook_url = Some(url);
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.keypair_path.is_empty() {
+            bail!("keypair_path or KEEPER_KEYPAIR is required");
+        }
+        let fees = &self.priority_fee;
+        if fees.percentile > 100 {
+            bail!("priority_fee.percentile must be at most 100");
+        }
+        if fees.min_micro_lamports > fees.max_micro_lamports {
+            bail!("priority_fee.min_micro_lamports exceeds max_micro_lamports");
+        }
+        if fees.max_attempts == 0 {
+            bail!("priority_fee.max_attempts must be at least 1");
+        }
+        if self.vaults.is_empty() {
+            bail!("No vaults configured");
+        }
+
+        for vault in &self.vaults {
+            let intervals = [
+                vault.sync.as_ref().map(|job| job.interval_secs),
+                vault.checkpoint.as_ref().map(|job| job.interval_secs),
+            ];
+            if intervals.iter().all(Option::is_none) {
+                bail!("Vault {} has no jobs", vault.address);
+            }
+            if intervals.contains(&Some(0)) {
+                bail!("Vault {} has a job with a zero interval", vault.address);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SyncJob {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+impl CheckpointJob {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_config() {
+        let config = Config::from_toml(include_str!("../config.example.toml")).unwrap();
+        config.validate().unwrap();
+
+        let vault = &config.vaults[0];
+        assert_eq!(vault.sync.as_ref().unwrap().min_gain, 1000);
+        assert_eq!(vault.checkpoint.as_ref().unwrap().interval_secs, 300);
+        assert_eq!(config.priority_fee.max_attempts, 3);
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let vault = "[[vaults]]\naddress = \"AMmnjS49zawFLYrHKrDacbREUc5WVyQ7yG6hsSBi5Cro\"\n";
+
+        let no_jobs = Config::from_toml(&format!("keypair_path = \"k.json\"\n{vault}")).unwrap();
+        assert!(no_jobs.validate().is_err());
+
+        let zero_interval =
+            format!("keypair_path = \"k.json\"\n{vault}[vaults.checkpoint]\ninterval_secs = 0\n");
+        assert!(Config::from_toml(&zero_interval)
+            .unwrap()
+            .validate()
+            .is_err());
+
+        assert!(Config::from_toml("[[vaults]]\naddress = \"not-a-key\"\n").is_err());
+        assert!(Config::from_toml(&format!("keypair = \"k.json\"\n{vault}")).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Priority fees
+//!
+//! Transactions bid a percentile of the prioritization fees recently paid for
+//! the accounts they write, clamped to the configured range. A transaction
+//! that expires unconfirmed is retried at double the fee.
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tracing::warn;
+
+use crate::config::PriorityFeeConfig;
+
+pub struct PriorityFees {
+    config: PriorityFeeConfig,
+}
+
+impl PriorityFees {
+    pub fn new(config: PriorityFeeConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn compute_unit_limit(&self) -> u32 {
+        self.config.compute_unit_limit
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.config.max_attempts
+    }
+
+    /// Compute unit price of try `attempt` (from 0) of a transaction writing
+    /// `accounts`
+    pub async fn price(&self, rpc: &RpcClient, accounts: &[Pubkey], attempt: u32) -> u64 {
+        let recent = match rpc.get_recent_prioritization_fees(accounts).await {
+            Ok(fees) => fees.iter().map(|fee| fee.prioritization_fee).collect(),
+            Err(e) => {
+                warn!("Failed to fetch recent prioritization fees: {e}");
+                Vec::new()
+            }
+        };
+        escalate(
+            percentile(recent, self.config.percentile),
+            attempt,
+            &self.config,
+        )
+    }
+}
+
+/// Nearest-rank `percentile` of `fees`, 0 without any
+fn percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() * percentile as usize).div_ceil(100);
+    fees[rank.saturating_sub(1)]
+}
+
+/// `base` clamped to the configured range, doubled for every retry
+fn escalate(base: u64, attempt: u32, config: &PriorityFeeConfig) -> u64 {
+    base.max(config.min_micro_lamports)
+        .saturating_mul(1 << attempt.min(32))
+        .min(config.max_micro_lamports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let fees: Vec<u64> = (1..=100).rev().collect();
+        assert_eq!(percentile(fees.clone(), 75), 75);
+        assert_eq!(percentile(fees.clone(), 100), 100);
+        assert_eq!(percentile(fees, 0), 1);
+        assert_eq!(percentile(vec![0, 0, 5_000], 50), 0);
+        assert_eq!(percentile(Vec::new(), 75), 0);
+    }
+
+    #[test]
+    fn test_escalate() {
+        let config = PriorityFeeConfig {
+            min_micro_lamports: 1_000,
+            max_micro_lamports: 10_000,
+            ..Default::default()
+        };
+        assert_eq!(escalate(0, 0, &config), 1_000);
+        assert_eq!(escalate(3_000, 0, &config), 3_000);
+        assert_eq!(escalate(3_000, 1, &config), 6_000);
+        assert_eq!(escalate(3_000, 2, &config), 10_000);
+        assert_eq!(escalate(u64::MAX, 40, &config), 10_000);
+    }
+}
@@ -0,0 +1,256 @@
+//! Scheduled vault jobs
+//!
+//! Every job runs on its own interval. `sync` only ever raises
+//! `total_assets`: an asset vault holding less than `total_assets` means
+//! assets left the vault outside of withdrawals, and syncing would write the
+//! loss into the share price, so the job fails and alerts instead.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use anyhow::{anyhow, bail};
+use serde_json::json;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::{from_account, Account},
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    sysvar::clock::{self, Clock},
+    transaction::Transaction,
+};
+use svs_client::{
+    accounts::{decode, decode_confidential_vault, decode_vault},
+    rpc::nonblocking::fetch_vault_keys,
+    svs1, svs2,
+    view::{VaultSnapshot, View},
+    ConfidentialVault, Vault, VaultKeys,
+};
+use tokio::{io::AsyncWriteExt, time::MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use crate::{alert::Alerts, fees::PriorityFees};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Job {
+    Sync { min_gain: u64 },
+    Checkpoint,
+}
+
+impl Job {
+    fn name(&self) -> &'static str {
+        match self {
+            Job::Sync { .. } => "sync",
+            Job::Checkpoint => "checkpoint",
+        }
+    }
+}
+
+/// State of an SVS-1 or SVS-2 vault
+enum VaultState {
+    Public(Vault),
+    Confidential(ConfidentialVault),
+}
+
+impl VaultState {
+    fn decode(address: &Pubkey, data: &[u8]) -> anyhow::Result<Self> {
+        if let Ok(vault) = decode_vault(address, data) {
+            return Ok(Self::Public(vault));
+        }
+        let vault = decode_confidential_vault(address, data)?;
+        Ok(Self::Confidential(vault))
+    }
+
+    fn authority(&self) -> Pubkey {
+        match self {
+            Self::Public(vault) => vault.authority,
+            Self::Confidential(vault) => vault.authority,
+        }
+    }
+
+    fn total_assets(&self) -> u64 {
+        match self {
+            Self::Public(vault) => vault.total_assets,
+            Self::Confidential(vault) => vault.total_assets,
+        }
+    }
+}
+
+pub struct Keeper {
+    pub rpc: RpcClient,
+    pub signer: Keypair,
+    pub fees: PriorityFees,
+    pub alerts: Alerts,
+    pub checkpoint_path: PathBuf,
+}
+
+impl Keeper {
+    /// Run `job` on `vault` every `interval`
+    pub async fn run(self: Arc<Self>, vault: Pubkey, job: Job, interval: Duration) {
+        let label = format!("{} {vault}", job.name());
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut keys = None;
+
+        loop {
+            ticker.tick().await;
+            match self.run_once(&vault, job, &mut keys).await {
+                Ok(()) => self.alerts.success(&label).await,
+                Err(e) => self.alerts.failure(&label, &e).await,
+            }
+        }
+    }
+
+    async fn run_once(
+        &self,
+        vault: &Pubkey,
+        job: Job,
+        keys: &mut Option<VaultKeys>,
+    ) -> anyhow::Result<()> {
+        let keys = match keys {
+            Some(keys) => *keys,
+            None => *keys.insert(fetch_vault_keys(&self.rpc, vault).await?),
+        };
+        match job {
+            Job::Sync { min_gain } => self.sync(&keys, min_gain).await,
+            Job::Checkpoint => self.checkpoint(&keys).await,
+        }
+    }
+
+    async fn sync(&self, keys: &VaultKeys, min_gain: u64) -> anyhow::Result<()> {
+        let [vault, asset_vault] = self.fetch([keys.vault, keys.asset_vault]).await?.1;
+        let state = VaultState::decode(&keys.vault, &vault.data)?;
+        let balance =
+            decode::<TokenAccount>(&keys.asset_vault, "asset vault", &asset_vault.data)?.amount;
+
+        let total_assets = state.total_assets();
+        if balance < total_assets {
+            bail!(
+                "Asset vault holds {balance}, less than total_assets {total_assets}; \
+                 not syncing the loss into the share price"
+            );
+        }
+        let gain = balance - total_assets;
+        if gain < min_gain {
+            debug!(vault = %keys.vault, gain, "Nothing to sync");
+            return Ok(());
+        }
+
+        let authority = self.signer.pubkey();
+        if state.authority() != authority {
+            bail!(
+                "Keeper {authority} is not the vault authority {}",
+                state.authority()
+            );
+        }
+        let instruction = match state {
+            VaultState::Public(_) => svs1::sync(keys, &authority),
+            VaultState::Confidential(_) => svs2::sync(keys, &authority),
+        };
+        let signature = self.send(instruction, &[keys.vault]).await?;
+        info!(vault = %keys.vault, gain, %signature, "Synced");
+        Ok(())
+    }
+
+    /// Append the share price to the checkpoint file
+    async fn checkpoint(&self, keys: &VaultKeys) -> anyhow::Result<()> {
+        let (slot, [vault, shares_mint, clock]) = self
+            .fetch([keys.vault, keys.shares_mint, clock::ID])
+            .await?;
+        let clock: Clock =
+            from_account(&clock).ok_or_else(|| anyhow!("Undecodable clock sysvar"))?;
+        let mint = decode::<Mint>(&keys.shares_mint, "shares mint", &shares_mint.data)?;
+
+        let snapshot = match VaultState::decode(&keys.vault, &vault.data)? {
+            VaultState::Public(vault) => VaultSnapshot::from_vault(&vault, mint.supply),
+            VaultState::Confidential(vault) => {
+                VaultSnapshot::from_confidential_vault(&vault, mint.supply, clock.unix_timestamp)?
+            }
+        };
+        let one_share = 10u64.pow(mint.decimals.into());
+        let assets_per_share = snapshot.evaluate(&View::ConvertToAssets(one_share), 0)?;
+
+        let record = json!({
+            "vault": keys.vault.to_string(),
+            "slot": slot,
+            "unix_timestamp": clock.unix_timestamp,
+            "total_assets": snapshot.total_assets,
+            "total_shares": snapshot.total_shares,
+            "assets_per_share": assets_per_share,
+            "paused": snapshot.paused,
+        });
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.checkpoint_path)
+            .await?;
+        file.write_all(format!("{record}\n").as_bytes()).await?;
+
+        debug!(vault = %keys.vault, slot, assets_per_share, "Checkpoint");
+        Ok(())
+    }
+
+    /// Accounts at `addresses` and the slot they were read at
+    async fn fetch<const N: usize>(
+        &self,
+        addresses: [Pubkey; N],
+    ) -> anyhow::Result<(u64, [Account; N])> {
+        let response = self
+            .rpc
+            .get_multiple_accounts_with_commitment(&addresses, self.rpc.commitment())
+            .await?;
+        let accounts = response
+            .value
+            .into_iter()
+            .zip(addresses)
+            .map(|(account, address)| account.ok_or_else(|| anyhow!("No account {address}")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let accounts = accounts
+            .try_into()
+            .map_err(|_| anyhow!("RPC returned the wrong number of accounts"))?;
+        Ok((response.context.slot, accounts))
+    }
+
+    /// Send `instruction` with a priority fee for the `writable` accounts,
+    /// retrying at a higher fee while it expires unconfirmed
+    async fn send(
+        &self,
+        instruction: Instruction,
+        writable: &[Pubkey],
+    ) -> anyhow::Result<Signature> {
+        let payer = self.signer.pubkey();
+        let mut attempt = 0;
+        loop {
+            let price = self.fees.price(&self.rpc, writable, attempt).await;
+            let instructions = [
+                ComputeBudgetInstruction::set_compute_unit_limit(self.fees.compute_unit_limit()),
+                ComputeBudgetInstruction::set_compute_unit_price(price),
+                instruction.clone(),
+            ];
+            let blockhash = self.rpc.get_latest_blockhash().await?;
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer),
+                &[&self.signer],
+                blockhash,
+            );
+
+            match self.rpc.send_and_confirm_transaction(&transaction).await {
+                Ok(signature) => return Ok(signature),
+                // Failed transactions fail again at any fee
+                Err(e)
+                    if e.get_transaction_error().is_none()
+                        && attempt + 1 < self.fees.max_attempts() =>
+                {
+                    warn!(
+                        price,
+                        "Transaction not confirmed, retrying at a higher fee: {e}"
+                    );
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! SVS Keeper
+//!
+//! Runs scheduled maintenance on SVS-1 and SVS-2 vaults: syncing
+//! `total_assets` after yield or donations and recording share-price
+//! checkpoints, with priority fees and failure alerts. See README.md.
+
+mod alert;
+mod config;
+mod fees;
+mod keeper;
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Signer},
+};
+use tracing::{error, info};
+
+use alert::Alerts;
+use config::Config;
+use fees::PriorityFees;
+use keeper::{Job, Keeper};
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "svs_keeper=info".into()),
+        )
+        .init();
+
+    if let Err(e) = run().await {
+        error!("{e:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let signer = read_keypair_file(&config.keypair_path)
+        .map_err(|e| anyhow!("Failed to read keypair {}: {e}", config.keypair_path))?;
+    info!(
+        rpc_url = %config.rpc_url,
+        keeper = %signer.pubkey(),
+        vaults = config.vaults.len(),
+        "Starting SVS Keeper"
+    );
+
+    let keeper = Arc::new(Keeper {
+        rpc: RpcClient::new_with_commitment(config.rpc_url, CommitmentConfig::confirmed()),
+        signer,
+        fees: PriorityFees::new(config.priority_fee),
+        alerts: Alerts::new(config.alerts),
+        checkpoint_path: config.checkpoint_path.into(),
+    });
+
+    for vault in config.vaults {
+        if let Some(sync) = vault.sync {
+            let job = Job::Sync {
+                min_gain: sync.min_gain,
+            };
+            tokio::spawn(keeper.clone().run(vault.address, job, sync.interval()));
+        }
+        if let Some(checkpoint) = vault.checkpoint {
+            let interval = checkpoint.interval();
+            tokio::spawn(keeper.clone().run(vault.address, Job::Checkpoint, interval));
+        }
+    }
+
+    shutdown_signal().await;
+    info!("Shutting down");
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,189 @@
+//! Basis-point fee math
+//!
+//! Every fee-charging flow goes through [`FeeFlow::split`], which picks the
+//! fee formula for the flow and always rounds the fee up, so the fee
+//! recipient never receives less than its exact share.
+//!
+//! A fee is either contained in the amount it is charged on (inclusive:
+//! the user hands over a gross amount, as in deposit and redeem) or added on
+//! top of it (exclusive: the user names the net amount, as in mint and
+//! withdraw). The two formulas agree: charging the exclusive fee of `net`
+//! and then the inclusive fee of the resulting gross yields the same fee.
+
+use crate::{mul_div_wide, MathError, Result, Rounding};
+
+/// `fee_bps` of this is 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Fee added on top of `amount`: `amount × fee_bps / 10_000`
+pub fn fee_on_raw(amount: u64, fee_bps: u16, rounding: Rounding) -> Result<u64> {
+    mul_div_wide(
+        amount as u128,
+        fee_bps as u128,
+        BPS_DENOMINATOR as u128,
+        rounding,
+    )
+}
+
+/// Fee contained in `amount`: `amount × fee_bps / (10_000 + fee_bps)`
+///
+/// The inverse of [`fee_on_raw`]: the part of a gross amount that is fee
+/// when the rest is the amount the fee was charged on.
+pub fn fee_on_total(amount: u64, fee_bps: u16, rounding: Rounding) -> Result<u64> {
+    mul_div_wide(
+        amount as u128,
+        fee_bps as u128,
+        BPS_DENOMINATOR as u128 + fee_bps as u128,
+        rounding,
+    )
+}
+
+/// A user flow charging a fee on its asset amount
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeeFlow {
+    /// Assets paid in include the fee; shares are minted for the net
+    Deposit,
+    /// Assets for the exact shares are net; the fee is paid on top
+    Mint,
+    /// Assets paid out are net; shares are burned for the gross
+    Withdraw,
+    /// Assets for the exact shares are gross; the fee is taken out
+    Redeem,
+}
+
+/// An amount split into the part the flow acts on and its fee
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeeSplit {
+    /// Amount net of the fee
+    pub net: u64,
+    /// Fee, rounded up
+    pub fee: u64,
+    /// `net + fee`
+    pub gross: u64,
+}
+
+impl FeeFlow {
+    /// Whether the flow's amount already contains the fee
+    pub fn is_inclusive(self) -> bool {
+        matches!(self, FeeFlow::Deposit | FeeFlow::Redeem)
+    }
+
+    /// Split the flow's `amount` at `fee_bps`
+    ///
+    /// `amount` is gross for inclusive flows and net for the others. The fee
+    /// always rounds up, in the protocol's favor.
+    pub fn split(self, amount: u64, fee_bps: u16) -> Result<FeeSplit> {
+        if self.is_inclusive() {
+            let fee = fee_on_total(amount, fee_bps, Rounding::Ceiling)?;
+            Ok(FeeSplit {
+                // The fee never exceeds a gross amount
+                net: amount - fee,
+                fee,
+                gross: amount,
+            })
+        } else {
+            let fee = fee_on_raw(amount, fee_bps, Rounding::Ceiling)?;
+            Ok(FeeSplit {
+                net: amount,
+                fee,
+                gross: amount.checked_add(fee).ok_or(MathError::Overflow)?,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BPS: [u16; 9] = [0, 1, 5, 30, 100, 2_500, 9_999, 10_000, u16::MAX];
+
+    #[test]
+    fn test_fee_on_raw() {
+        // 1% of 1_000
+        assert_eq!(fee_on_raw(1_000, 100, Rounding::Floor).unwrap(), 10);
+        // 0.3% of 1_001 = 3.003
+        assert_eq!(fee_on_raw(1_001, 30, Rounding::Floor).unwrap(), 3);
+        assert_eq!(fee_on_raw(1_001, 30, Rounding::Ceiling).unwrap(), 4);
+        assert_eq!(
+            fee_on_raw(u64::MAX, 10_000, Rounding::Floor).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            fee_on_raw(u64::MAX, 10_001, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_fee_on_total() {
+        // 1_010 is 1_000 plus 1%
+        assert_eq!(fee_on_total(1_010, 100, Rounding::Floor).unwrap(), 10);
+        // 1_000 × 100 / 10_100 = 9.9
+        assert_eq!(fee_on_total(1_000, 100, Rounding::Floor).unwrap(), 9);
+        assert_eq!(fee_on_total(1_000, 100, Rounding::Ceiling).unwrap(), 10);
+        // 100% on top: half of the total
+        assert_eq!(
+            fee_on_total(u64::MAX, 10_000, Rounding::Ceiling).unwrap(),
+            1 << 63
+        );
+    }
+
+    #[test]
+    fn test_flows() {
+        assert_eq!(
+            FeeFlow::Deposit.split(1_000, 100).unwrap(),
+            FeeSplit {
+                net: 990,
+                fee: 10,
+                gross: 1_000
+            }
+        );
+        assert_eq!(
+            FeeFlow::Withdraw.split(1_000, 100).unwrap(),
+            FeeSplit {
+                net: 1_000,
+                fee: 10,
+                gross: 1_010
+            }
+        );
+        // A dust amount still pays a fee
+        assert_eq!(FeeFlow::Redeem.split(1, 1).unwrap().fee, 1);
+        assert_eq!(FeeFlow::Mint.split(1, 1).unwrap().fee, 1);
+        assert_eq!(FeeFlow::Mint.split(u64::MAX, 1), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_fees_round_in_protocol_favor() {
+        for fee_bps in BPS {
+            for amount in (0..20_000).chain(u64::MAX - 20_000..=u64::MAX) {
+                let exact_raw = amount as u128 * fee_bps as u128;
+                if let Ok(raw) = fee_on_raw(amount, fee_bps, Rounding::Ceiling) {
+                    // Never below the exact fee, and less than one unit above
+                    let scaled = raw as u128 * BPS_DENOMINATOR as u128;
+                    assert!(scaled >= exact_raw && scaled - exact_raw < BPS_DENOMINATOR as u128);
+                }
+
+                let split = FeeFlow::Deposit.split(amount, fee_bps).unwrap();
+                assert_eq!(split.net + split.fee, amount);
+                // Paying the exclusive fee on what is left never costs more
+                // than the gross the user handed over
+                let paid = FeeFlow::Mint.split(split.net, fee_bps).unwrap();
+                assert!(paid.gross <= amount, "{amount} at {fee_bps} bps");
+            }
+        }
+    }
+
+    #[test]
+    fn test_inclusive_inverts_exclusive() {
+        for fee_bps in BPS {
+            for net in (0..20_000).chain([1 << 40, 1 << 50, u64::MAX / 8]) {
+                let Ok(exclusive) = FeeFlow::Withdraw.split(net, fee_bps) else {
+                    continue;
+                };
+                let inclusive = FeeFlow::Redeem.split(exclusive.gross, fee_bps).unwrap();
+                assert_eq!(inclusive, exclusive, "{net} at {fee_bps} bps");
+            }
+        }
+    }
+}
@@ -0,0 +1,512 @@
+//! Share/asset conversion math of the Solana Vault Standard
+//!
+//! The single implementation of the conversions SVS-1 and SVS-2 run on-chain,
+//! shared by the programs, the Rust SDK, the proof backend and the fuzzer so
+//! that previews and invariant checks round exactly like the vaults do.
+//!
+//! `no_std` with no dependencies, so it builds for SBF, wasm and hosts alike.
+
+#![no_std]
+
+pub mod fee_math;
+
+use core::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Floor,
+    Ceiling,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MathError {
+    Overflow,
+    DivisionByZero,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::Overflow => f.write_str("Arithmetic overflow"),
+            MathError::DivisionByZero => f.write_str("Division by zero"),
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, MathError>;
+
+/// Convert assets to shares with virtual offset protection against inflation attacks.
+///
+/// Formula: shares = assets × (total_shares + 10^offset) / (total_assets + 1)
+///
+/// The virtual offset ensures that even in an empty vault, there's a "virtual"
+/// share supply that prevents attackers from manipulating the share price.
+pub fn convert_to_shares(
+    assets: u64,
+    total_assets: u64,
+    total_shares: u64,
+    decimals_offset: u8,
+    rounding: Rounding,
+) -> Result<u64> {
+    let (virtual_assets, virtual_shares) =
+        virtual_totals(total_assets, total_shares, decimals_offset)?;
+    mul_div_wide(assets as u128, virtual_shares, virtual_assets, rounding)
+}
+
+/// Convert shares to assets with virtual offset protection.
+///
+/// Formula: assets = shares × (total_assets + 1) / (total_shares + 10^offset)
+pub fn convert_to_assets(
+    shares: u64,
+    total_assets: u64,
+    total_shares: u64,
+    decimals_offset: u8,
+    rounding: Rounding,
+) -> Result<u64> {
+    let (virtual_assets, virtual_shares) =
+        virtual_totals(total_assets, total_shares, decimals_offset)?;
+    mul_div_wide(shares as u128, virtual_assets, virtual_shares, rounding)
+}
+
+/// Fixed-point scale of the `share_price` views: 1.0 is `10^12`
+pub const SHARE_PRICE_SCALE: u64 = 1_000_000_000_000;
+
+/// Price of one whole share in whole assets, as a fixed-point number with
+/// `scale` as 1.0
+///
+/// Formula: price = (total_assets + 1) × 10^offset × scale / (total_shares + 10^offset)
+///
+/// Shares carry `offset` more decimals than the asset, so an empty vault
+/// prices a share at exactly `scale`. Computed in one division, unlike
+/// deriving it from [`convert_to_assets`] of a whole share. Views use
+/// [`SHARE_PRICE_SCALE`]; `10^18` gives more precision to callers that can
+/// live with overflowing once the price passes ~18.4.
+pub fn share_price(
+    total_assets: u64,
+    total_shares: u64,
+    decimals_offset: u8,
+    scale: u64,
+    rounding: Rounding,
+) -> Result<u64> {
+    let (virtual_assets, virtual_shares) =
+        virtual_totals(total_assets, total_shares, decimals_offset)?;
+    // 10^offset fits u64 past virtual_totals, so this is below 2^128
+    let scaled_assets = virtual_assets * 10u128.pow(decimals_offset as u32);
+    mul_div_wide(scaled_assets, scale as u128, virtual_shares, rounding)
+}
+
+/// `(total_assets + 1, total_shares + 10^offset)`, widened so that totals
+/// near `u64::MAX` don't overflow
+fn virtual_totals(
+    total_assets: u64,
+    total_shares: u64,
+    decimals_offset: u8,
+) -> Result<(u128, u128)> {
+    let offset = 10u64
+        .checked_pow(decimals_offset as u32)
+        .ok_or(MathError::Overflow)?;
+
+    Ok((
+        total_assets as u128 + 1,
+        total_shares as u128 + offset as u128,
+    ))
+}
+
+/// Safe multiplication then division with configurable rounding.
+///
+/// Computes: (value × numerator) / denominator
+/// Exact over the whole `u64` domain: fails only on a zero denominator or a
+/// result above `u64::MAX`.
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    mul_div_wide(
+        value as u128,
+        numerator as u128,
+        denominator as u128,
+        rounding,
+    )
+}
+
+/// [`mul_div`] of `u128` operands, such as the virtual totals
+///
+/// Products that fit in `u128` take a single division; larger ones go
+/// through a 256-bit intermediate.
+fn mul_div_wide(
+    value: u128,
+    numerator: u128,
+    denominator: u128,
+    rounding: Rounding,
+) -> Result<u64> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+
+    let (quotient, remainder) = match value.checked_mul(numerator) {
+        Some(product) => (product / denominator, product % denominator),
+        None => {
+            let (high, low) = widening_mul(value, numerator);
+            // A quotient of 2^128 or more can't fit in u64 either
+            if high >= denominator {
+                return Err(MathError::Overflow);
+            }
+            div_rem_wide(high, low, denominator)
+        }
+    };
+
+    let result = match rounding {
+        Rounding::Floor => quotient,
+        Rounding::Ceiling => quotient
+            .checked_add((remainder > 0) as u128)
+            .ok_or(MathError::Overflow)?,
+    };
+
+    u64::try_from(result).map_err(|_| MathError::Overflow)
+}
+
+/// Full 256-bit product of `a` and `b` as `(high, low)` 128-bit halves
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+
+    // Middle 64-bit column with the carries out of the low column; at most
+    // 3 × (2^64 - 1), which fits
+    let middle = (low_low >> 64) + (high_low & MASK) + (low_high & MASK);
+    let low = (middle << 64) | (low_low & MASK);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// `(high × 2^128 + low) / divisor` and its remainder, for `high < divisor`
+/// so that the quotient fits in `u128`
+///
+/// Restoring binary long division, one quotient bit per step.
+fn div_rem_wide(high: u128, low: u128, divisor: u128) -> (u128, u128) {
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        // The remainder is below the divisor, so after shifting it is below
+        // 2 × divisor: one subtraction restores it, including when the shift
+        // carries out of u128
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+    (quotient, remainder)
+}
+
+/// Portion of harvested profit that has not yet unlocked at `now`
+///
+/// SVS-2 streams `locked_profit` into the share price linearly over
+/// `profit_unlock_period` seconds from `last_harvest_ts`. A period of zero or
+/// less unlocks immediately.
+pub fn locked_profit(
+    locked_profit: u64,
+    last_harvest_ts: i64,
+    profit_unlock_period: i64,
+    now: i64,
+) -> Result<u64> {
+    let elapsed = now.saturating_sub(last_harvest_ts);
+    if profit_unlock_period <= 0 || elapsed >= profit_unlock_period {
+        return Ok(0);
+    }
+
+    mul_div(
+        locked_profit,
+        (profit_unlock_period - elapsed) as u64,
+        profit_unlock_period as u64,
+        Rounding::Floor,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_div_floor() {
+        // 100 * 3 / 2 = 150 (floor)
+        assert_eq!(mul_div(100, 3, 2, Rounding::Floor).unwrap(), 150);
+        // 100 * 1 / 3 = 33 (floor)
+        assert_eq!(mul_div(100, 1, 3, Rounding::Floor).unwrap(), 33);
+    }
+
+    #[test]
+    fn test_mul_div_ceiling() {
+        // 100 * 3 / 2 = 150 (exact)
+        assert_eq!(mul_div(100, 3, 2, Rounding::Ceiling).unwrap(), 150);
+        // 100 * 1 / 3 = 34 (ceiling)
+        assert_eq!(mul_div(100, 1, 3, Rounding::Ceiling).unwrap(), 34);
+    }
+
+    #[test]
+    fn test_convert_to_shares_empty_vault() {
+        // Empty vault with 6-decimal asset (USDC), offset = 3
+        // Virtual shares = 0 + 10^3 = 1000
+        // Virtual assets = 0 + 1 = 1
+        // shares = 1_000_000 * 1000 / 1 = 1_000_000_000
+        let shares = convert_to_shares(1_000_000, 0, 0, 3, Rounding::Floor).unwrap();
+        assert_eq!(shares, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_convert_to_shares_proportional() {
+        // Vault has 1M assets and 1M shares, offset = 3
+        // User deposits 100k assets
+        // shares = 100_000 * (1_000_000 + 1000) / (1_000_000 + 1)
+        //        ≈ 100_000 * 1.000999 ≈ 100_099 (floor)
+        let shares = convert_to_shares(100_000, 1_000_000, 1_000_000, 3, Rounding::Floor).unwrap();
+        assert!(shares > 99_000 && shares < 101_000);
+    }
+
+    #[test]
+    fn test_convert_to_assets_proportional() {
+        // Vault has 1M assets and 1M shares, offset = 3
+        // User redeems 100k shares
+        let assets = convert_to_assets(100_000, 1_000_000, 1_000_000, 3, Rounding::Floor).unwrap();
+        assert!(assets > 99_000 && assets < 101_000);
+    }
+
+    #[test]
+    fn test_inflation_attack_protection() {
+        // Attacker scenario: donate 1M to empty vault, then deposit 1
+        // Without offset: attacker could manipulate price
+        // With offset (3): virtual shares = 1000, virtual assets = 1M + 1
+        // Attacker deposits 1: shares = 1 * 1000 / 1_000_001 = 0 (floor)
+        let shares = convert_to_shares(1, 1_000_000, 0, 3, Rounding::Floor).unwrap();
+        assert_eq!(shares, 0); // Attack yields nothing
+    }
+
+    #[test]
+    fn test_rounding_favors_vault() {
+        // deposit: floor (user gets less)
+        let deposit_shares = convert_to_shares(100, 1000, 1000, 3, Rounding::Floor).unwrap();
+
+        // redeem: floor (user gets less)
+        let redeem_assets = convert_to_assets(100, 1000, 1000, 3, Rounding::Floor).unwrap();
+
+        // withdraw: ceiling shares (user burns more)
+        let withdraw_shares = convert_to_shares(100, 1000, 1000, 3, Rounding::Ceiling).unwrap();
+
+        // mint: ceiling assets (user pays more)
+        let mint_assets = convert_to_assets(100, 1000, 1000, 3, Rounding::Ceiling).unwrap();
+
+        // Ceiling should be >= Floor
+        assert!(withdraw_shares >= deposit_shares);
+        assert!(mint_assets >= redeem_assets);
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let result = mul_div(100, 100, 0, Rounding::Floor);
+        assert_eq!(result, Err(MathError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_max_values() {
+        // Test with large but valid values
+        let large = u64::MAX / 2;
+        let result = convert_to_shares(large, large, large, 0, Rounding::Floor);
+        assert!(result.is_ok());
+
+        assert_eq!(
+            mul_div(u64::MAX, 2, 1, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            convert_to_shares(1, 0, 0, 20, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+    }
+
+    const EDGES: [u64; 12] = [
+        0,
+        1,
+        2,
+        3,
+        1_000,
+        u32::MAX as u64,
+        1 << 32,
+        (1 << 63) - 1,
+        1 << 63,
+        u64::MAX - 2,
+        u64::MAX - 1,
+        u64::MAX,
+    ];
+
+    #[test]
+    fn test_mul_div_exact_on_u64_edges() {
+        for &value in &EDGES {
+            for &numerator in &EDGES {
+                for &denominator in &EDGES[1..] {
+                    let product = value as u128 * numerator as u128;
+                    let floor = product / denominator as u128;
+                    let ceiling = floor + !product.is_multiple_of(denominator as u128) as u128;
+                    for (rounding, expected) in
+                        [(Rounding::Floor, floor), (Rounding::Ceiling, ceiling)]
+                    {
+                        let expected = u64::try_from(expected).map_err(|_| MathError::Overflow);
+                        assert_eq!(
+                            mul_div(value, numerator, denominator, rounding),
+                            expected,
+                            "{value} * {numerator} / {denominator} ({rounding:?})"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_div_wide_matches_narrow() {
+        // Scaling value and denominator by 2^64 forces the 256-bit path
+        // without changing the quotient
+        for &value in &EDGES {
+            for &numerator in &EDGES[1..] {
+                for &denominator in &EDGES[1..] {
+                    for rounding in [Rounding::Floor, Rounding::Ceiling] {
+                        assert_eq!(
+                            mul_div_wide(
+                                (value as u128) << 64,
+                                numerator as u128,
+                                (denominator as u128) << 64,
+                                rounding,
+                            ),
+                            mul_div(value, numerator, denominator, rounding),
+                            "{value} * {numerator} / {denominator} ({rounding:?})"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_widening_mul() {
+        assert_eq!(widening_mul(0, u128::MAX), (0, 0));
+        assert_eq!(widening_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(widening_mul(u128::MAX, 2), (1, u128::MAX - 1));
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(widening_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+    }
+
+    #[test]
+    fn test_div_rem_wide() {
+        assert_eq!(div_rem_wide(0, 7, 2), (3, 1));
+        assert_eq!(div_rem_wide(1, 0, 2), (1 << 127, 0));
+        // Divisor above 2^127 exercises the carry out of the shifted remainder
+        assert_eq!(div_rem_wide(u128::MAX - 1, 1, u128::MAX), (u128::MAX, 0));
+        assert_eq!(
+            div_rem_wide(u128::MAX - 1, u128::MAX, u128::MAX),
+            (u128::MAX, u128::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_wide_overflow() {
+        // 2^200 / 2^136 = 2^64, one past u64::MAX
+        assert_eq!(
+            mul_div_wide(1 << 100, 1 << 100, 1 << 127, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            mul_div_wide(u128::MAX, u128::MAX, u128::MAX, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+        // u64::MAX exactly, and one more once rounded up
+        let max = u64::MAX as u128;
+        assert_eq!(
+            mul_div_wide(max << 64, 1 << 64, 1 << 64, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            mul_div_wide(max << 64, 3, 3 << 64, Rounding::Floor),
+            Ok(u64::MAX)
+        );
+        assert_eq!(
+            mul_div_wide((max << 64) + 1, 3, 3 << 64, Rounding::Ceiling),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_conversions_near_u64_max_totals() {
+        // Virtual totals above u64::MAX no longer overflow
+        assert_eq!(
+            convert_to_shares(1_000, u64::MAX, u64::MAX, 9, Rounding::Floor),
+            Ok(1_000)
+        );
+        assert_eq!(
+            convert_to_assets(1, u64::MAX, u64::MAX, 9, Rounding::Ceiling),
+            Ok(1)
+        );
+        assert_eq!(
+            convert_to_assets(u64::MAX, u64::MAX, 0, 0, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_share_price() {
+        // Empty vault: one share is worth one asset at any offset
+        for offset in [0, 3, 9] {
+            assert_eq!(
+                share_price(0, 0, offset, SHARE_PRICE_SCALE, Rounding::Floor),
+                Ok(SHARE_PRICE_SCALE)
+            );
+        }
+
+        // 1.25 USDC backing 1 share (offset 3), with the virtual unit and
+        // shares: 1_250_001 × 10^3 / 1_000_001_000
+        assert_eq!(
+            share_price(
+                1_250_000,
+                1_000_000_000,
+                3,
+                SHARE_PRICE_SCALE,
+                Rounding::Floor
+            ),
+            Ok(1_249_999_750_000)
+        );
+        assert_eq!(
+            share_price(
+                1_250_000,
+                1_000_000_000,
+                3,
+                1_000_000_000_000_000_000,
+                Rounding::Ceiling
+            ),
+            Ok(1_249_999_750_000_250_000)
+        );
+
+        // More precise than a whole share's convert_to_assets
+        let whole_share =
+            convert_to_assets(1_000_000_000, 1_250_000, 1_000_000_000, 3, Rounding::Floor).unwrap();
+        assert_eq!(whole_share, 1_249_999);
+
+        // Donated-to vault with a dust supply
+        assert_eq!(
+            share_price(u64::MAX, 0, 0, 1_000_000_000_000_000_000, Rounding::Floor),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_locked_profit_unlocks_linearly() {
+        // 1000 locked at t=100 over 10s
+        assert_eq!(locked_profit(1000, 100, 10, 100).unwrap(), 1000);
+        assert_eq!(locked_profit(1000, 100, 10, 103).unwrap(), 700);
+        assert_eq!(locked_profit(1000, 100, 10, 110).unwrap(), 0);
+        assert_eq!(locked_profit(1000, 100, 10, 1_000).unwrap(), 0);
+        // No streaming
+        assert_eq!(locked_profit(1000, 100, 0, 100).unwrap(), 0);
+    }
+}
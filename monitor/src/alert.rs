@@ -0,0 +1,173 @@
+//! Alert delivery to webhooks and PagerDuty
+//!
+//! Every alert is logged and POSTed to each webhook. Critical and warning
+//! alerts also trigger PagerDuty events, deduplicated per vault and check for
+//! conditions so a condition pages once and resolves its incident when it
+//! clears.
+
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::AlertConfig,
+    invariants::{Finding, Severity},
+};
+
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    /// Vault name and address
+    pub source: String,
+    pub check: &'static str,
+    pub severity: Severity,
+    pub summary: String,
+    pub dedup_key: String,
+}
+
+impl Alert {
+    /// Alert for `finding` about vault `source`, observed at `slot`
+    pub fn new(source: &str, vault: &str, finding: &Finding, slot: u64) -> Self {
+        // Every occurrence of an event is its own incident
+        let dedup_key = if finding.condition {
+            format!("svs-monitor:{vault}:{}", finding.check)
+        } else {
+            format!("svs-monitor:{vault}:{}:{slot}", finding.check)
+        };
+        Self {
+            source: source.to_string(),
+            check: finding.check,
+            severity: finding.severity,
+            summary: format!("{source}: {}", finding.message),
+            dedup_key,
+        }
+    }
+}
+
+pub struct Alerter {
+    http: reqwest::Client,
+    config: AlertConfig,
+}
+
+impl Alerter {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    pub async fn trigger(&self, alert: &Alert) {
+        match alert.severity {
+            Severity::Critical => error!(check = alert.check, "{}", alert.summary),
+            Severity::Warning => warn!(check = alert.check, "{}", alert.summary),
+            Severity::Info => info!(check = alert.check, "{}", alert.summary),
+        }
+        let text = format!("[{}] {}", alert.severity.as_str(), alert.summary);
+        self.webhooks(alert, &text, false).await;
+        if alert.severity > Severity::Info {
+            self.pagerduty(pagerduty_event("trigger", alert)).await;
+        }
+    }
+
+    /// Report that the condition behind `alert` cleared
+    pub async fn resolve(&self, alert: &Alert) {
+        info!(check = alert.check, "Resolved: {}", alert.summary);
+        let text = format!("[resolved] {}", alert.summary);
+        self.webhooks(alert, &text, true).await;
+        if alert.severity > Severity::Info {
+            self.pagerduty(pagerduty_event("resolve", alert)).await;
+        }
+    }
+
+    async fn webhooks(&self, alert: &Alert, text: &str, resolved: bool) {
+        let body = json!({
+            "text": text,
+            "source": alert.source,
+            "check": alert.check,
+            "severity": alert.severity.as_str(),
+            "resolved": resolved,
+        });
+        for url in &self.config.webhook_urls {
+            self.post(url, &body).await;
+        }
+    }
+
+    async fn pagerduty(&self, mut event: Value) {
+        if let Some(key) = &self.config.pagerduty_routing_key {
+            event["routing_key"] = json!(key);
+            self.post(PAGERDUTY_EVENTS_URL, &event).await;
+        }
+    }
+
+    async fn post(&self, url: &str, body: &Value) {
+        let result = self
+            .http
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(e) = result {
+            error!("Failed to deliver alert to {url}: {e}");
+        }
+    }
+}
+
+/// PagerDuty Events API v2 event, without the routing key
+fn pagerduty_event(action: &str, alert: &Alert) -> Value {
+    let mut event = json!({
+        "event_action": action,
+        "dedup_key": alert.dedup_key,
+    });
+    if action == "trigger" {
+        event["payload"] = json!({
+            "summary": alert.summary,
+            "source": alert.source,
+            "severity": alert.severity.as_str(),
+            "component": alert.check,
+        });
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(condition: bool) -> Finding {
+        Finding {
+            check: "asset_backing",
+            severity: Severity::Critical,
+            condition,
+            message: "Asset vault holds 1, 1 less than total_assets 2".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dedup_keys() {
+        let condition = Alert::new("USDC vault (V)", "V", &finding(true), 7);
+        assert_eq!(condition.dedup_key, "svs-monitor:V:asset_backing");
+        assert_eq!(
+            condition.summary,
+            "USDC vault (V): Asset vault holds 1, 1 less than total_assets 2"
+        );
+
+        let event = Alert::new("V", "V", &finding(false), 7);
+        assert_eq!(event.dedup_key, "svs-monitor:V:asset_backing:7");
+    }
+
+    #[test]
+    fn test_pagerduty_events() {
+        let alert = Alert::new("V", "V", &finding(true), 7);
+
+        let trigger = pagerduty_event("trigger", &alert);
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["payload"]["severity"], "critical");
+        assert_eq!(trigger["payload"]["component"], "asset_backing");
+
+        let resolve = pagerduty_event("resolve", &alert);
+        assert_eq!(resolve["dedup_key"], "svs-monitor:V:asset_backing");
+        assert!(resolve.get("payload").is_none());
+    }
+}
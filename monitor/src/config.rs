@@ -0,0 +1,138 @@
+//! Configuration
+//!
+//! Loaded from the TOML file in `CONFIG_FILE` (see `config.example.toml`),
+//! then overridden by any of `SOLANA_RPC_URL`, `ALERT_WEBHOOK_URLS` and
+//! `PAGERDUTY_ROUTING_KEY` that are set.
+
+use std::{str::FromStr, time::Duration};
+
+use anyhow::{anyhow, bail, Context};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default = "default_rpc_url")]
+    pub rpc_url: String,
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Failed reads of a vault in a row before it alerts
+    #[serde(default = "default_max_read_failures")]
+    pub max_read_failures: u32,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    pub vaults: Vec<VaultConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    pub webhook_urls: Vec<String>,
+    /// PagerDuty Events API v2 integration key
+    pub pagerduty_routing_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VaultConfig {
+    #[serde(deserialize_with = "pubkey")]
+    pub address: Pubkey,
+    /// Shown in alerts next to the address
+    pub name: Option<String>,
+}
+
+fn default_rpc_url() -> String {
+    "https://api.mainnet-beta.solana.com".to_string()
+}
+
+fn default_poll_interval_secs() -> u64 {
+    10
+}
+
+fn default_max_read_failures() -> u32 {
+    6
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s).map_err(|e| D::Error::custom(format!("invalid pubkey {s}: {e}")))
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = std::env::var("CONFIG_FILE").context("CONFIG_FILE is required")?;
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read config file {path}: {e}"))?;
+        let mut config =
+            Self::from_toml(&contents).map_err(|e| anyhow!("Invalid config file {path}: {e}"))?;
+
+        if let Ok(url) = std::env::var("SOLANA_RPC_URL") {
+            config.rpc_url = url;
+        }
+        if let Ok(urls) = std::env::var("ALERT_WEBHOOK_URLS") {
+            config.alerts.webhook_urls = urls
+                .split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect();
+        }
+        if let Ok(key) = std::env::var("PAGERDUTY_ROUTING_KEY") {
+            config.alerts.pagerduty_routing_key = Some(key);
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.poll_interval_secs == 0 {
+            bail!("poll_interval_secs must be at least 1");
+        }
+        if self.max_read_failures == 0 {
+            bail!("max_read_failures must be at least 1");
+        }
+        if self.vaults.is_empty() {
+            bail!("No vaults configured");
+        }
+        Ok(())
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_config() {
+        let config = Config::from_toml(include_str!("../config.example.toml")).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.vaults[0].name.as_deref(), Some("USDC vault"));
+        assert_eq!(config.alerts.webhook_urls.len(), 1);
+        assert!(config.alerts.pagerduty_routing_key.is_some());
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::from_toml(
+            "[[vaults]]\naddress = \"AMmnjS49zawFLYrHKrDacbREUc5WVyQ7yG6hsSBi5Cro\"\n",
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.poll_interval(), Duration::from_secs(10));
+        assert!(config.alerts.webhook_urls.is_empty());
+
+        assert!(Config::from_toml("vaults = []")
+            .unwrap()
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Vault invariants
+//!
+//! [`check`] inspects one observation of a vault, and compares it with the
+//! previous one. Conditions are wrong states that stay active, and alert
+//! once, until an observation no longer shows them. Events are one-off
+//! changes, alerted every time they happen.
+
+use solana_sdk::pubkey::Pubkey;
+
+/// A vault's accounts as read at one slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    pub slot: u64,
+    pub authority: Pubkey,
+    pub paused: bool,
+    /// The program's protocol-wide pause, which halts this vault too
+    pub protocol_paused: bool,
+    /// `total_assets` as recorded, including SVS-2 profit still unlocking
+    pub total_assets: u64,
+    pub shares_supply: u64,
+    pub shares_mint_authority: Option<Pubkey>,
+    pub asset_balance: u64,
+    pub asset_vault_owner: Pubkey,
+    /// Value of one whole share in asset base units
+    pub assets_per_share: u64,
+    /// SVS-2 only
+    pub guardian: Option<Pubkey>,
+    /// SVS-2 only
+    pub harvest_config: Option<HarvestConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HarvestConfig {
+    pub swap_adapter: Pubkey,
+    pub harvester: Pubkey,
+    pub profit_unlock_period: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    /// Name in PagerDuty events
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub check: &'static str,
+    pub severity: Severity,
+    /// A condition rather than an event
+    pub condition: bool,
+    pub message: String,
+}
+
+impl Finding {
+    fn condition(check: &'static str, severity: Severity, message: String) -> Self {
+        Self {
+            check,
+            severity,
+            condition: true,
+            message,
+        }
+    }
+
+    fn event(check: &'static str, severity: Severity, message: String) -> Self {
+        Self {
+            check,
+            severity,
+            condition: false,
+            message,
+        }
+    }
+}
+
+/// Violations and notable changes in `now`, the observation after `previous`
+pub fn check(vault: &Pubkey, previous: Option<&Observation>, now: &Observation) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if now.asset_balance < now.total_assets {
+        findings.push(Finding::condition(
+            "asset_backing",
+            Severity::Critical,
+            format!(
+                "Asset vault holds {}, {} less than total_assets {}",
+                now.asset_balance,
+                now.total_assets - now.asset_balance,
+                now.total_assets
+            ),
+        ));
+    }
+    if now.asset_vault_owner != *vault {
+        findings.push(Finding::condition(
+            "asset_vault_owner",
+            Severity::Critical,
+            format!("Asset vault is owned by {}", now.asset_vault_owner),
+        ));
+    }
+    if now.shares_mint_authority != Some(*vault) {
+        let authority = now
+            .shares_mint_authority
+            .map_or("nobody".to_string(), |key| key.to_string());
+        findings.push(Finding::condition(
+            "shares_mint_authority",
+            Severity::Critical,
+            format!("Shares mint authority is {authority}"),
+        ));
+    }
+
+    let Some(previous) = previous else {
+        return findings;
+    };
+
+    if now.authority != previous.authority {
+        findings.push(Finding::event(
+            "authority",
+            Severity::Critical,
+            format!(
+                "Authority changed from {} to {}",
+                previous.authority, now.authority
+            ),
+        ));
+    }
+    if now.assets_per_share < previous.assets_per_share {
+        findings.push(Finding::event(
+            "share_price",
+            Severity::Warning,
+            format!(
+                "Share price fell from {} to {} assets per share",
+                previous.assets_per_share, now.assets_per_share
+            ),
+        ));
+    }
+    if now.paused != previous.paused {
+        findings.push(if now.paused {
+            Finding::event("paused", Severity::Warning, "Vault paused".to_string())
+        } else {
+            Finding::event("paused", Severity::Info, "Vault unpaused".to_string())
+        });
+    }
+    if now.protocol_paused != previous.protocol_paused {
+        findings.push(if now.protocol_paused {
+            Finding::event(
+                "protocol_paused",
+                Severity::Warning,
+                "Protocol paused".to_string(),
+            )
+        } else {
+            Finding::event(
+                "protocol_paused",
+                Severity::Info,
+                "Protocol unpaused".to_string(),
+            )
+        });
+    }
+    if now.guardian != previous.guardian {
+        findings.push(Finding::event(
+            "guardian",
+            Severity::Warning,
+            format!(
+                "Guardian changed from {} to {}",
+                previous.guardian.unwrap_or_default(),
+                now.guardian.unwrap_or_default()
+            ),
+        ));
+    }
+    if let (Some(old), Some(new)) = (previous.harvest_config, now.harvest_config) {
+        if old != new {
+            findings.push(Finding::event(
+                "harvest_config",
+                Severity::Warning,
+                format!(
+                    "Harvest config changed: swap adapter {}, harvester {}, \
+                     profit unlock period {}s",
+                    new.swap_adapter, new.harvester, new.profit_unlock_period
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy(vault: Pubkey) -> Observation {
+        Observation {
+            slot: 100,
+            authority: Pubkey::new_unique(),
+            paused: false,
+            protocol_paused: false,
+            total_assets: 1_000_000,
+            shares_supply: 1_000_000_000,
+            shares_mint_authority: Some(vault),
+            asset_balance: 1_000_000,
+            asset_vault_owner: vault,
+            assets_per_share: 1_000,
+            guardian: None,
+            harvest_config: None,
+        }
+    }
+
+    fn checks(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|finding| finding.check).collect()
+    }
+
+    #[test]
+    fn test_healthy_vault() {
+        let vault = Pubkey::new_unique();
+        let previous = healthy(vault);
+        let now = Observation {
+            slot: 101,
+            // Donations and harvests raise the balance and the price
+            asset_balance: 1_500_000,
+            assets_per_share: 1_001,
+            ..previous.clone()
+        };
+        assert!(check(&vault, None, &previous).is_empty());
+        assert!(check(&vault, Some(&previous), &now).is_empty());
+    }
+
+    #[test]
+    fn test_drained_vault() {
+        let vault = Pubkey::new_unique();
+        let attacker = Pubkey::new_unique();
+        let now = Observation {
+            asset_balance: 400_000,
+            shares_supply: 2_000_000_000,
+            asset_vault_owner: attacker,
+            shares_mint_authority: Some(attacker),
+            ..healthy(vault)
+        };
+
+        let findings = check(&vault, None, &now);
+        assert_eq!(
+            checks(&findings),
+            [
+                "asset_backing",
+                "asset_vault_owner",
+                "shares_mint_authority"
+            ]
+        );
+        assert!(findings
+            .iter()
+            .all(|finding| finding.condition && finding.severity == Severity::Critical));
+        assert_eq!(
+            findings[0].message,
+            "Asset vault holds 400000, 600000 less than total_assets 1000000"
+        );
+    }
+
+    #[test]
+    fn test_changes() {
+        let vault = Pubkey::new_unique();
+        let previous = Observation {
+            guardian: Some(Pubkey::default()),
+            harvest_config: Some(HarvestConfig {
+                swap_adapter: Pubkey::default(),
+                harvester: Pubkey::default(),
+                profit_unlock_period: 0,
+            }),
+            ..healthy(vault)
+        };
+        let now = Observation {
+            authority: Pubkey::new_unique(),
+            paused: true,
+            protocol_paused: true,
+            assets_per_share: 999,
+            guardian: Some(Pubkey::new_unique()),
+            harvest_config: Some(HarvestConfig {
+                swap_adapter: Pubkey::new_unique(),
+                harvester: Pubkey::new_unique(),
+                profit_unlock_period: 86_400,
+            }),
+            ..previous.clone()
+        };
+
+        let findings = check(&vault, Some(&previous), &now);
+        assert_eq!(
+            checks(&findings),
+            [
+                "authority",
+                "share_price",
+                "paused",
+                "protocol_paused",
+                "guardian",
+                "harvest_config"
+            ]
+        );
+        assert!(findings.iter().all(|finding| !finding.condition));
+        assert_eq!(findings[0].severity, Severity::Critical);
+
+        let unpaused = Observation {
+            paused: false,
+            ..now.clone()
+        };
+        let findings = check(&vault, Some(&now), &unpaused);
+        assert_eq!(findings[0].severity, Severity::Info);
+        assert_eq!(findings[0].message, "Vault unpaused");
+
+        let resumed = Observation {
+            protocol_paused: false,
+            ..unpaused.clone()
+        };
+        let findings = check(&vault, Some(&unpaused), &resumed);
+        assert_eq!(checks(&findings), ["protocol_paused"]);
+        assert_eq!(findings[0].severity, Severity::Info);
+    }
+}
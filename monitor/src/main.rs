@@ -0,0 +1,85 @@
+//! SVS Monitor
+//!
+//! Checks the on-chain invariants of SVS-1 and SVS-2 vaults and alerts
+//! webhooks and PagerDuty when they break or when a vault's control changes.
+//! See README.md for the checks.
+
+mod alert;
+mod config;
+mod invariants;
+mod monitor;
+
+use std::sync::Arc;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::{error, info};
+
+use alert::Alerter;
+use config::Config;
+use monitor::Monitor;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "svs_monitor=info".into()),
+        )
+        .init();
+
+    if let Err(e) = run().await {
+        error!("{e:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let config = Config::load()?;
+    info!(
+        rpc_url = %config.rpc_url,
+        vaults = config.vaults.len(),
+        webhooks = config.alerts.webhook_urls.len(),
+        pagerduty = config.alerts.pagerduty_routing_key.is_some(),
+        "Starting SVS Monitor"
+    );
+
+    let monitor = Arc::new(Monitor {
+        // Confirmed state alerts within seconds of an exploit landing
+        rpc: RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed()),
+        alerter: Alerter::new(config.alerts.clone()),
+        poll_interval: config.poll_interval(),
+        max_read_failures: config.max_read_failures,
+    });
+    for vault in config.vaults {
+        tokio::spawn(monitor.clone().watch(vault));
+    }
+
+    shutdown_signal().await;
+    info!("Shutting down");
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
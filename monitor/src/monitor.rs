@@ -0,0 +1,217 @@
+//! Polling vaults and reporting findings
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use anyhow::anyhow;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account,
+    pubkey::Pubkey,
+    sysvar::clock::{self, Clock},
+};
+use svs_client::{
+    accounts::{decode, decode_confidential_vault, decode_protocol_config, decode_vault},
+    pda::find_config_address,
+    rpc::nonblocking::fetch_vault_keys,
+    view::{VaultSnapshot, View},
+    VaultKeys,
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+use crate::{
+    alert::{Alert, Alerter},
+    config::VaultConfig,
+    invariants::{check, Finding, HarvestConfig, Observation, Severity},
+};
+
+pub struct Monitor {
+    pub rpc: RpcClient,
+    pub alerter: Alerter,
+    pub poll_interval: Duration,
+    pub max_read_failures: u32,
+}
+
+impl Monitor {
+    /// Check `vault` every poll interval
+    pub async fn watch(self: Arc<Self>, vault: VaultConfig) {
+        let address = vault.address.to_string();
+        let source = match &vault.name {
+            Some(name) => format!("{name} ({address})"),
+            None => address.clone(),
+        };
+        let mut ticker = tokio::time::interval(self.poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut keys = None;
+        let mut previous: Option<Observation> = None;
+        let mut active = HashMap::new();
+        let mut read_failures = 0;
+
+        loop {
+            ticker.tick().await;
+            match self.observe(&vault.address, &mut keys).await {
+                Ok(observation) => {
+                    read_failures = 0;
+                    let slot = observation.slot;
+                    let findings = check(&vault.address, previous.as_ref(), &observation);
+                    debug!(vault = %address, slot, findings = findings.len(), "Checked");
+                    previous = Some(observation);
+                    self.report(&source, &address, slot, findings, &mut active, true)
+                        .await;
+                }
+                Err(e) => {
+                    read_failures += 1;
+                    warn!(vault = %address, "Failed to read the vault: {e:#}");
+                    if read_failures < self.max_read_failures {
+                        continue;
+                    }
+                    // Blind, so conditions seen before stay active
+                    let finding = Finding {
+                        check: "read",
+                        severity: Severity::Warning,
+                        condition: true,
+                        message: format!(
+                            "Failed to read the vault {read_failures} times in a row: {e:#}"
+                        ),
+                    };
+                    self.report(&source, &address, 0, vec![finding], &mut active, false)
+                        .await;
+                }
+            }
+        }
+    }
+
+    /// Alert new findings and, with `resolve`, resolve active conditions that
+    /// are no longer found
+    async fn report(
+        &self,
+        source: &str,
+        vault: &str,
+        slot: u64,
+        findings: Vec<Finding>,
+        active: &mut HashMap<&'static str, Alert>,
+        resolve: bool,
+    ) {
+        let found: HashSet<_> = findings
+            .iter()
+            .filter(|finding| finding.condition)
+            .map(|finding| finding.check)
+            .collect();
+
+        for finding in &findings {
+            if finding.condition && active.contains_key(finding.check) {
+                continue;
+            }
+            let alert = Alert::new(source, vault, finding, slot);
+            self.alerter.trigger(&alert).await;
+            if finding.condition {
+                active.insert(finding.check, alert);
+            }
+        }
+
+        if resolve {
+            let cleared: Vec<_> = active
+                .keys()
+                .copied()
+                .filter(|check| !found.contains(check))
+                .collect();
+            for check in cleared {
+                if let Some(alert) = active.remove(check) {
+                    self.alerter.resolve(&alert).await;
+                }
+            }
+        }
+    }
+
+    async fn observe(
+        &self,
+        vault: &Pubkey,
+        keys: &mut Option<VaultKeys>,
+    ) -> anyhow::Result<Observation> {
+        let keys = match keys {
+            Some(keys) => *keys,
+            None => *keys.insert(fetch_vault_keys(&self.rpc, vault).await?),
+        };
+        let (config_address, _) = find_config_address(&keys.program_id);
+        let addresses = [keys.vault, keys.asset_vault, keys.shares_mint, clock::ID];
+        let response = self
+            .rpc
+            .get_multiple_accounts_with_commitment(
+                &[addresses.as_slice(), &[config_address]].concat(),
+                self.rpc.commitment(),
+            )
+            .await?;
+        let mut accounts = response.value;
+        // The config is optional: without one the protocol cannot be paused
+        let config = accounts.pop().flatten();
+        let accounts = accounts
+            .into_iter()
+            .zip(addresses)
+            .map(|(account, address)| account.ok_or_else(|| anyhow!("No account {address}")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let [vault_account, asset_vault, shares_mint, clock] = <[_; 4]>::try_from(accounts)
+            .map_err(|_| anyhow!("RPC returned the wrong number of accounts"))?;
+        let protocol_paused = match config {
+            Some(config) => decode_protocol_config(&config_address, &config.data)?.paused,
+            None => false,
+        };
+
+        let asset_vault =
+            decode::<TokenAccount>(&keys.asset_vault, "asset vault", &asset_vault.data)?;
+        let mint = decode::<Mint>(&keys.shares_mint, "shares mint", &shares_mint.data)?;
+        let clock: Clock =
+            from_account(&clock).ok_or_else(|| anyhow!("Undecodable clock sysvar"))?;
+
+        let (snapshot, authority, total_assets, guardian, harvest_config) =
+            match decode_vault(vault, &vault_account.data) {
+                Ok(state) => (
+                    VaultSnapshot::from_vault(&state, mint.supply),
+                    state.authority,
+                    state.total_assets,
+                    None,
+                    None,
+                ),
+                Err(_) => {
+                    let state = decode_confidential_vault(vault, &vault_account.data)?;
+                    let harvest_config = HarvestConfig {
+                        swap_adapter: state.swap_adapter,
+                        harvester: state.harvester,
+                        profit_unlock_period: state.profit_unlock_period,
+                    };
+                    (
+                        VaultSnapshot::from_confidential_vault(
+                            &state,
+                            mint.supply,
+                            clock.unix_timestamp,
+                        )?,
+                        state.authority,
+                        state.total_assets,
+                        Some(state.guardian),
+                        Some(harvest_config),
+                    )
+                }
+            };
+        let one_share = 10u64.pow(mint.decimals.into());
+
+        Ok(Observation {
+            slot: response.context.slot,
+            authority,
+            paused: snapshot.paused,
+            protocol_paused,
+            total_assets,
+            shares_supply: mint.supply,
+            shares_mint_authority: mint.mint_authority.into(),
+            asset_balance: asset_vault.amount,
+            asset_vault_owner: asset_vault.owner,
+            assets_per_share: snapshot.evaluate(&View::ConvertToAssets(one_share), 0)?,
+            guardian,
+            harvest_config,
+        })
+    }
+}
@@ -0,0 +1,62 @@
+//! PDA vault authority for the SVS-2 admin CPI tests
+//!
+//! `execute` invokes any instruction with the `["governance"]` PDA as a
+//! signer, the way a governance program such as Realms executes an approved
+//! proposal. The remaining accounts are the instruction's accounts, in order;
+//! wherever the PDA appears it is marked as signing.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke_signed},
+};
+
+declare_id!("GovernanceMock11111111111111111111111111111");
+
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+#[program]
+pub mod mock_governance {
+    use super::*;
+
+    pub fn execute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Execute<'info>>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let governance = ctx.accounts.governance.key();
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == governance,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        let instruction = Instruction {
+            program_id: ctx.accounts.target_program.key(),
+            accounts,
+            data,
+        };
+
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.target_program.to_account_info());
+        invoke_signed(
+            &instruction,
+            &account_infos,
+            &[&[GOVERNANCE_SEED, &[ctx.bumps.governance]]],
+        )?;
+        Ok(())
+    }
+}
+
+/// The rest are the invoked instruction's accounts
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    /// CHECK: PDA signing the invoked instruction
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: UncheckedAccount<'info>,
+
+    /// CHECK: Program the instruction is invoked on
+    #[account(executable)]
+    pub target_program: UncheckedAccount<'info>,
+}
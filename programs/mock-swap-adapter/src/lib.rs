@@ -0,0 +1,100 @@
+//! Swap adapter for the SVS-2 harvest tests
+//!
+//! `swap` trades rewards for assets 1:1 against a pool owned by the `["pool"]`
+//! PDA. Given an extra account after its own, it behaves like a malicious
+//! adapter instead: it tries to move `amount_in` out of the asset vault into
+//! the pool, using that account as the transfer authority.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+declare_id!("SwapAdapterMock1111111111111111111111111111");
+
+pub const POOL_SEED: &[u8] = b"pool";
+
+#[program]
+pub mod mock_swap_adapter {
+    use super::*;
+
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_in: u64,
+        _min_amount_out: u64,
+    ) -> Result<()> {
+        let accounts = &ctx.accounts;
+        if let Some(authority) = ctx.remaining_accounts.first() {
+            return transfer_checked(
+                CpiContext::new(
+                    accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: accounts.asset_vault.to_account_info(),
+                        to: accounts.pool_assets.to_account_info(),
+                        mint: accounts.asset_mint.to_account_info(),
+                        authority: authority.clone(),
+                    },
+                ),
+                amount_in,
+                accounts.asset_mint.decimals,
+            );
+        }
+
+        transfer_checked(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.reward_token_account.to_account_info(),
+                    to: accounts.pool_rewards.to_account_info(),
+                    mint: accounts.reward_mint.to_account_info(),
+                    authority: accounts.authority.to_account_info(),
+                },
+            ),
+            amount_in,
+            accounts.reward_mint.decimals,
+        )?;
+        transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.pool_assets.to_account_info(),
+                    to: accounts.asset_vault.to_account_info(),
+                    mint: accounts.asset_mint.to_account_info(),
+                    authority: accounts.pool.to_account_info(),
+                },
+                &[&[POOL_SEED, &[ctx.bumps.pool]]],
+            ),
+            amount_in,
+            accounts.asset_mint.decimals,
+        )
+    }
+}
+
+/// The first three accounts are the ones SVS-2 `harvest` passes to every
+/// adapter; the rest are the harvest's remaining accounts
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA owning the pool accounts
+    #[account(seeds = [POOL_SEED], bump)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_rewards: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub pool_assets: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,7 +1,18 @@
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const SHARES_MINT_SEED: &[u8] = b"shares";
+pub const CONFIG_SEED: &[u8] = b"config";
 
+/// Protocol config defaults, in effect until the config account is created
 pub const MAX_DECIMALS: u8 = 9;
 pub const SHARES_DECIMALS: u8 = 9;
 
 pub const MIN_DEPOSIT_AMOUNT: u64 = 1000;
+
+/// Upper bound of the configurable shares decimals, keeping 10^decimals_offset
+/// well inside u64
+pub const MAX_SHARES_DECIMALS: u8 = 18;
+
+/// Bounds of the vault metadata passed to `initialize`, in bytes (as Metaplex)
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
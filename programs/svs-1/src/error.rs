@@ -11,7 +11,7 @@ pub enum VaultError {
     #[msg("Vault is paused")]
     VaultPaused,
 
-    #[msg("Asset decimals must be <= 9")]
+    #[msg("Asset decimals exceed the configured maximum")]
     InvalidAssetDecimals,
 
     #[msg("Arithmetic overflow")]
@@ -31,4 +31,40 @@ pub enum VaultError {
 
     #[msg("Deposit amount below minimum threshold")]
     DepositTooSmall,
+
+    #[msg("CPI Guard is enabled on token account - disable it for this transaction")]
+    CpiGuardEnabled,
+
+    #[msg("Vault invariant violated")]
+    InvariantViolated,
+
+    #[msg("Vault or protocol is already paused")]
+    AlreadyPaused,
+
+    #[msg("Vault is not paused")]
+    VaultNotPaused,
+
+    #[msg("Shares mint size could not be calculated for its extensions")]
+    MintSizeUnavailable,
+
+    #[msg("Invalid protocol config parameters")]
+    InvalidConfig,
+
+    #[msg("Minimum deposit below the protocol minimum")]
+    MinDepositTooLow,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Protocol is not paused")]
+    ProtocolNotPaused,
+
+    #[msg("Vault name must be 1-32 bytes without control characters")]
+    InvalidVaultName,
+
+    #[msg("Vault symbol must be 1-10 ASCII letters or digits")]
+    InvalidVaultSymbol,
+
+    #[msg("Vault uri must be at most 200 bytes without whitespace or control characters")]
+    InvalidVaultUri,
 }
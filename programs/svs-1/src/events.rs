@@ -1,3 +1,13 @@
+//! Every event ends with `op_counter`, the number of mutating instructions
+//! run on the vault so far (events of one instruction share it), `sequence`,
+//! the vault's event counter (1 for its first event, gapless and increasing),
+//! and `timestamp`, the cluster's unix time when it was emitted. Indexers can
+//! order a vault's events and detect missed ones from the sequence alone.
+//!
+//! Protocol config events are numbered by the config's own `sequence`
+//! instead, and carry no `op_counter`: every config instruction emits
+//! exactly one event.
+
 use anchor_lang::prelude::*;
 
 #[event]
@@ -7,6 +17,10 @@ pub struct VaultInitialized {
     pub asset_mint: Pubkey,
     pub shares_mint: Pubkey,
     pub vault_id: u64,
+    pub non_transferable: bool,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -16,6 +30,9 @@ pub struct Deposit {
     pub owner: Pubkey,
     pub assets: u64,
     pub shares: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -26,6 +43,9 @@ pub struct Withdraw {
     pub owner: Pubkey,
     pub assets: u64,
     pub shares: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -33,12 +53,18 @@ pub struct VaultSynced {
     pub vault: Pubkey,
     pub previous_total: u64,
     pub new_total: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
 }
 
 #[event]
 pub struct VaultStatusChanged {
     pub vault: Pubkey,
     pub paused: bool,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -46,4 +72,43 @@ pub struct AuthorityTransferred {
     pub vault: Pubkey,
     pub previous_authority: Pubkey,
     pub new_authority: Pubkey,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// The vault's minimum deposit was changed with `set_min_deposit`
+#[event]
+pub struct MinDepositUpdated {
+    pub vault: Pubkey,
+    pub previous: u64,
+    pub new: u64,
+    pub op_counter: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// The protocol was paused or resumed by `caller`, the config authority or
+/// the protocol guardian
+#[event]
+pub struct ProtocolStatusChanged {
+    pub config: Pubkey,
+    pub caller: Pubkey,
+    pub paused: bool,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// The protocol config after `initialize_config`, `update_config`,
+/// `transfer_config_authority` or `set_protocol_guardian`
+#[event]
+pub struct ProtocolConfigUpdated {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+    pub min_deposit_amount: u64,
+    pub max_decimals: u8,
+    pub shares_decimals: u8,
+    pub sequence: u64,
+    pub timestamp: i64,
 }
@@ -2,9 +2,10 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::TokenAccount;
 
 use crate::{
+    constants::CONFIG_SEED,
     error::VaultError,
-    events::{AuthorityTransferred, VaultStatusChanged, VaultSynced},
-    state::Vault,
+    events::{AuthorityTransferred, MinDepositUpdated, VaultStatusChanged, VaultSynced},
+    state::{ProtocolConfig, Vault},
 };
 
 #[derive(Accounts)]
@@ -34,17 +35,38 @@ pub struct Sync<'info> {
     pub asset_vault: InterfaceAccount<'info, TokenAccount>,
 }
 
+#[derive(Accounts)]
+pub struct SetMinDeposit<'info> {
+    #[account(
+        constraint = authority.key() == vault.authority @ VaultError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
 /// Pause all vault operations (emergency circuit breaker)
 pub fn pause(ctx: Context<Admin>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
-    require!(!vault.paused, VaultError::VaultPaused);
+    require!(!vault.paused, VaultError::AlreadyPaused);
 
     vault.paused = true;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultStatusChanged {
         vault: vault.key(),
         paused: true,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -54,13 +76,18 @@ pub fn pause(ctx: Context<Admin>) -> Result<()> {
 pub fn unpause(ctx: Context<Admin>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
-    require!(vault.paused, VaultError::VaultPaused);
+    require!(vault.paused, VaultError::VaultNotPaused);
 
     vault.paused = false;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultStatusChanged {
         vault: vault.key(),
         paused: false,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -73,10 +100,15 @@ pub fn transfer_authority(ctx: Context<Admin>, new_authority: Pubkey) -> Result<
 
     vault.authority = new_authority;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(AuthorityTransferred {
         vault: vault.key(),
         previous_authority,
         new_authority,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -91,10 +123,41 @@ pub fn sync(ctx: Context<Sync>) -> Result<()> {
 
     vault.total_assets = actual_balance;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultSynced {
         vault: vault.key(),
         previous_total,
         new_total: actual_balance,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Override the vault's minimum deposit, no lower than the protocol minimum
+pub fn set_min_deposit(ctx: Context<SetMinDeposit>, min_deposit_amount: u64) -> Result<()> {
+    let config = ProtocolConfig::load_or_default(&ctx.accounts.config)?;
+    require!(
+        min_deposit_amount >= config.min_deposit_amount,
+        VaultError::MinDepositTooLow
+    );
+
+    let vault = &mut ctx.accounts.vault;
+    let previous = vault.min_deposit_amount;
+    vault.min_deposit_amount = min_deposit_amount;
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(MinDepositUpdated {
+        vault: vault.key(),
+        previous,
+        new: min_deposit_amount,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -6,11 +6,12 @@ use anchor_spl::{
 };
 
 use crate::{
-    constants::{MIN_DEPOSIT_AMOUNT, VAULT_SEED},
+    constants::{CONFIG_SEED, VAULT_SEED},
     error::VaultError,
     events::Deposit as DepositEvent,
     math::{convert_to_shares, Rounding},
-    state::Vault,
+    state::{ProtocolConfig, Vault},
+    token_extensions::require_cpi_guard_disabled,
 };
 
 #[derive(Accounts)]
@@ -61,11 +62,21 @@ pub struct Deposit<'info> {
     pub token_2022_program: Program<'info, Token2022>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 pub fn handler(ctx: Context<Deposit>, assets: u64, min_shares_out: u64) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     require!(assets > 0, VaultError::ZeroAmount);
-    require!(assets >= MIN_DEPOSIT_AMOUNT, VaultError::DepositTooSmall);
+    require!(
+        assets >= ctx.accounts.vault.min_deposit(),
+        VaultError::DepositTooSmall
+    );
+    require_cpi_guard_disabled(&ctx.accounts.user_asset_account.to_account_info())?;
 
     let vault = &ctx.accounts.vault;
     let total_shares = ctx.accounts.shares_mint.supply;
@@ -128,12 +139,17 @@ pub fn handler(ctx: Context<Deposit>, assets: u64, min_shares_out: u64) -> Resul
         .checked_add(assets)
         .ok_or(VaultError::MathOverflow)?;
 
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
     emit!(DepositEvent {
         vault: ctx.accounts.vault.key(),
         caller: ctx.accounts.user.key(),
         owner: ctx.accounts.user.key(),
         assets,
         shares,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -3,17 +3,21 @@ use anchor_lang::solana_program::program::invoke_signed;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_2022::{
-        spl_token_2022::{extension::ExtensionType, instruction::initialize_mint2},
+        spl_token_2022::{
+            extension::ExtensionType,
+            instruction::{initialize_mint2, initialize_non_transferable_mint},
+        },
         Token2022,
     },
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 use crate::{
-    constants::{MAX_DECIMALS, SHARES_DECIMALS, SHARES_MINT_SEED, VAULT_SEED},
+    constants::{CONFIG_SEED, SHARES_MINT_SEED, VAULT_SEED},
     error::VaultError,
     events::VaultInitialized,
-    state::Vault,
+    metadata::{metadata_hash, validate_metadata},
+    state::{ProtocolConfig, Vault},
 };
 
 #[derive(Accounts)]
@@ -31,6 +35,11 @@ pub struct Initialize<'info> {
     )]
     pub vault: Account<'info, Vault>,
 
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
     pub asset_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Shares mint is initialized via CPI in handler
@@ -62,11 +71,15 @@ pub fn handler(
     vault_id: u64,
     name: String,
     symbol: String,
-    _uri: String,
+    uri: String,
+    non_transferable: bool,
 ) -> Result<()> {
+    validate_metadata(&name, &symbol, &uri)?;
+
+    let config = ProtocolConfig::load_or_default(&ctx.accounts.config)?;
     let asset_decimals = ctx.accounts.asset_mint.decimals;
     require!(
-        asset_decimals <= MAX_DECIMALS,
+        asset_decimals <= config.max_decimals,
         VaultError::InvalidAssetDecimals
     );
 
@@ -74,10 +87,17 @@ pub fn handler(
     let vault_bump = ctx.bumps.vault;
     let shares_mint_bump = ctx.bumps.shares_mint;
 
-    // Calculate space for a basic Token-2022 mint (no extensions for now)
-    // We keep it simple - metadata can be added via Metaplex if needed
-    let mint_size = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[])
-        .map_err(|_| VaultError::MathOverflow)?;
+    // Plain Token-2022 mint, or NonTransferable for soulbound shares: holders
+    // cannot move them, but the vault still mints on deposit and burns on redeem.
+    // Metadata can be added via Metaplex if needed
+    let extensions: &[ExtensionType] = if non_transferable {
+        &[ExtensionType::NonTransferable]
+    } else {
+        &[]
+    };
+    let mint_size =
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions)
+            .map_err(|_| VaultError::MintSizeUnavailable)?;
 
     let rent = &ctx.accounts.rent;
     let lamports = rent.minimum_balance(mint_size);
@@ -118,13 +138,27 @@ pub fn handler(
         &[shares_mint_seeds],
     )?;
 
+    // Extensions must be initialized before the mint itself
+    if non_transferable {
+        let non_transferable_ix = initialize_non_transferable_mint(
+            &ctx.accounts.token_2022_program.key(),
+            &ctx.accounts.shares_mint.key(),
+        )?;
+
+        invoke_signed(
+            &non_transferable_ix,
+            &[ctx.accounts.shares_mint.to_account_info()],
+            &[shares_mint_seeds],
+        )?;
+    }
+
     // Initialize mint (vault PDA is mint authority, no freeze authority)
     let init_mint_ix = initialize_mint2(
         &ctx.accounts.token_2022_program.key(),
         &ctx.accounts.shares_mint.key(),
         &vault_key,
         None,
-        SHARES_DECIMALS,
+        config.shares_decimals,
     )?;
 
     invoke_signed(
@@ -140,18 +174,29 @@ pub fn handler(
     vault.shares_mint = ctx.accounts.shares_mint.key();
     vault.asset_vault = ctx.accounts.asset_vault.key();
     vault.total_assets = 0;
-    vault.decimals_offset = MAX_DECIMALS - asset_decimals;
+    vault.decimals_offset = config.shares_decimals - asset_decimals;
     vault.bump = vault_bump;
     vault.paused = false;
     vault.vault_id = vault_id;
-    vault._reserved = [0u8; 64];
-
+    vault.event_sequence = 0;
+    vault.min_deposit_amount = config.min_deposit_amount;
+    vault.metadata_hash = metadata_hash(&name, &symbol, &uri);
+    vault.op_counter = 0;
+    vault.non_transferable = non_transferable;
+    vault._reserved = [0u8; 7];
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultInitialized {
         vault: vault.key(),
         authority: vault.authority,
         asset_mint: vault.asset_mint,
         shares_mint: vault.shares_mint,
         vault_id,
+        non_transferable,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     msg!("Vault initialized: {} for asset {}", name, symbol);
@@ -0,0 +1,55 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{error::VaultError, state::Vault};
+
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    pub vault: Account<'info, Vault>,
+
+    #[account(constraint = asset_mint.key() == vault.asset_mint)]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(constraint = shares_mint.key() == vault.shares_mint)]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(constraint = asset_vault.key() == vault.asset_vault)]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Fail unless the vault's state is internally consistent (testing builds only)
+pub fn handler(ctx: Context<AssertInvariants>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let supply = ctx.accounts.shares_mint.supply;
+
+    check(
+        vault.total_assets <= ctx.accounts.asset_vault.amount,
+        "total assets exceed the asset vault balance",
+    )?;
+    check(
+        supply == 0 || vault.total_assets > 0,
+        "shares outstanding without assets",
+    )?;
+    check(
+        ctx.accounts
+            .asset_mint
+            .decimals
+            .checked_add(vault.decimals_offset)
+            == Some(ctx.accounts.shares_mint.decimals),
+        "decimals offset does not bridge the asset and shares decimals",
+    )?;
+    check(
+        ctx.accounts.shares_mint.mint_authority == COption::Some(vault.key()),
+        "vault is not the shares mint authority",
+    )?;
+
+    Ok(())
+}
+
+fn check(holds: bool, invariant: &str) -> Result<()> {
+    if !holds {
+        msg!("Invariant violated: {}", invariant);
+        return err!(VaultError::InvariantViolated);
+    }
+    Ok(())
+}
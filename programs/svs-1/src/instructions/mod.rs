@@ -1,6 +1,10 @@
 pub mod admin;
+#[path = "../../../../shared/config.rs"]
+pub mod config;
 pub mod deposit;
 pub mod initialize;
+#[cfg(feature = "testing")]
+pub mod invariants;
 pub mod mint;
 pub mod redeem;
 pub mod view;
@@ -9,9 +13,14 @@ pub mod withdraw;
 #[allow(ambiguous_glob_reexports)]
 pub use admin::*;
 #[allow(ambiguous_glob_reexports)]
+pub use config::*;
+#[allow(ambiguous_glob_reexports)]
 pub use deposit::*;
 #[allow(ambiguous_glob_reexports)]
 pub use initialize::*;
+#[cfg(feature = "testing")]
+#[allow(ambiguous_glob_reexports)]
+pub use invariants::*;
 #[allow(ambiguous_glob_reexports)]
 pub use mint::*;
 #[allow(ambiguous_glob_reexports)]
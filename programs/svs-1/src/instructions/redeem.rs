@@ -5,11 +5,12 @@ use anchor_spl::{
 };
 
 use crate::{
-    constants::VAULT_SEED,
+    constants::{CONFIG_SEED, VAULT_SEED},
     error::VaultError,
     events::Withdraw as WithdrawEvent,
     math::{convert_to_assets, Rounding},
-    state::Vault,
+    state::{ProtocolConfig, Vault},
+    token_extensions::require_cpi_guard_disabled,
 };
 
 #[derive(Accounts)]
@@ -56,11 +57,18 @@ pub struct Redeem<'info> {
 
     pub asset_token_program: Interface<'info, TokenInterface>,
     pub token_2022_program: Program<'info, Token2022>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Redeem shares for assets (floor rounding - protects vault)
 pub fn handler(ctx: Context<Redeem>, shares: u64, min_assets_out: u64) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     require!(shares > 0, VaultError::ZeroAmount);
+    require_cpi_guard_disabled(&ctx.accounts.user_shares_account.to_account_info())?;
 
     // Check user has enough shares
     require!(
@@ -132,6 +140,8 @@ pub fn handler(ctx: Context<Redeem>, shares: u64, min_assets_out: u64) -> Result
         .checked_sub(assets)
         .ok_or(VaultError::MathOverflow)?;
 
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
     emit!(WithdrawEvent {
         vault: ctx.accounts.vault.key(),
         caller: ctx.accounts.user.key(),
@@ -139,6 +149,9 @@ pub fn handler(ctx: Context<Redeem>, shares: u64, min_assets_out: u64) -> Result
         owner: ctx.accounts.user.key(),
         assets,
         shares,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
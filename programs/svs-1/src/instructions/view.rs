@@ -3,8 +3,10 @@ use anchor_lang::solana_program::program::set_return_data;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
 use crate::{
-    math::{convert_to_assets, convert_to_shares, Rounding},
-    state::Vault,
+    constants::CONFIG_SEED,
+    error::VaultError,
+    math::{convert_to_assets, convert_to_shares, share_price, Rounding, SHARE_PRICE_SCALE},
+    state::{ProtocolConfig, Vault},
 };
 
 #[derive(Accounts)]
@@ -13,6 +15,11 @@ pub struct VaultView<'info> {
 
     #[account(constraint = shares_mint.key() == vault.shares_mint)]
     pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -26,107 +33,135 @@ pub struct VaultViewWithOwner<'info> {
         constraint = owner_shares_account.mint == vault.shares_mint,
     )]
     pub owner_shares_account: InterfaceAccount<'info, TokenAccount>,
-}
 
-/// Preview how many shares would be minted for given assets (floor rounding)
-pub fn preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
 
-    let shares = convert_to_shares(
+/// `assets` in shares at the vault's current price
+fn shares_for(accounts: &VaultView, assets: u64, rounding: Rounding) -> Result<u64> {
+    let vault = &accounts.vault;
+    convert_to_shares(
         assets,
         vault.total_assets,
-        total_shares,
+        accounts.shares_mint.supply,
         vault.decimals_offset,
-        Rounding::Floor,
-    )?;
+        rounding,
+    )
+}
+
+/// `shares` in assets at the vault's current price
+fn assets_for(accounts: &VaultView, shares: u64, rounding: Rounding) -> Result<u64> {
+    let vault = &accounts.vault;
+    convert_to_assets(
+        shares,
+        vault.total_assets,
+        accounts.shares_mint.supply,
+        vault.decimals_offset,
+        rounding,
+    )
+}
+
+/// Whether the vault or the whole protocol is paused, so deposits and
+/// withdrawals fail
+fn paused(vault: &Vault, config: &AccountInfo) -> Result<bool> {
+    Ok(vault.paused || ProtocolConfig::load_or_default(config)?.paused)
+}
+
+/// `value`, or `u64::MAX` if computing it overflowed
+fn saturate(value: Result<u64>) -> Result<u64> {
+    match value {
+        Err(error) if error == VaultError::MathOverflow.into() => Ok(u64::MAX),
+        value => value,
+    }
+}
 
+/// Preview how many shares would be minted for given assets (floor rounding)
+pub fn preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = shares_for(ctx.accounts, assets, Rounding::Floor)?;
     set_return_data(&shares.to_le_bytes());
     Ok(())
 }
 
 /// Preview how many assets are required to mint exact shares (ceiling rounding)
 pub fn preview_mint(ctx: Context<VaultView>, shares: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
-
-    let assets = convert_to_assets(
-        shares,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Ceiling,
-    )?;
-
+    let assets = assets_for(ctx.accounts, shares, Rounding::Ceiling)?;
     set_return_data(&assets.to_le_bytes());
     Ok(())
 }
 
 /// Preview how many shares must be burned to withdraw exact assets (ceiling rounding)
 pub fn preview_withdraw(ctx: Context<VaultView>, assets: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
-
-    let shares = convert_to_shares(
-        assets,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Ceiling,
-    )?;
-
+    let shares = shares_for(ctx.accounts, assets, Rounding::Ceiling)?;
     set_return_data(&shares.to_le_bytes());
     Ok(())
 }
 
 /// Preview how many assets would be received for redeeming shares (floor rounding)
 pub fn preview_redeem(ctx: Context<VaultView>, shares: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
+    let assets = assets_for(ctx.accounts, shares, Rounding::Floor)?;
+    set_return_data(&assets.to_le_bytes());
+    Ok(())
+}
 
-    let assets = convert_to_assets(
-        shares,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Floor,
-    )?;
+/// [`preview_deposit`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = saturate(shares_for(ctx.accounts, assets, Rounding::Floor))?;
+    set_return_data(&shares.to_le_bytes());
+    Ok(())
+}
 
+/// [`preview_mint`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_mint(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+    let assets = saturate(assets_for(ctx.accounts, shares, Rounding::Ceiling))?;
     set_return_data(&assets.to_le_bytes());
     Ok(())
 }
 
-/// Convert assets to shares using floor rounding
-pub fn convert_to_shares_view(ctx: Context<VaultView>, assets: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
+/// [`preview_withdraw`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_withdraw(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = saturate(shares_for(ctx.accounts, assets, Rounding::Ceiling))?;
+    set_return_data(&shares.to_le_bytes());
+    Ok(())
+}
 
-    let shares = convert_to_shares(
-        assets,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Floor,
-    )?;
+/// [`preview_redeem`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_redeem(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+    let assets = saturate(assets_for(ctx.accounts, shares, Rounding::Floor))?;
+    set_return_data(&assets.to_le_bytes());
+    Ok(())
+}
 
+/// Convert assets to shares using floor rounding
+pub fn convert_to_shares_view(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = shares_for(ctx.accounts, assets, Rounding::Floor)?;
     set_return_data(&shares.to_le_bytes());
     Ok(())
 }
 
 /// Convert shares to assets using floor rounding
 pub fn convert_to_assets_view(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+    let assets = assets_for(ctx.accounts, shares, Rounding::Floor)?;
+    set_return_data(&assets.to_le_bytes());
+    Ok(())
+}
+
+/// Price of one whole share in whole assets, scaled by `SHARE_PRICE_SCALE` (floor rounding)
+pub fn share_price_view(ctx: Context<VaultView>) -> Result<()> {
     let vault = &ctx.accounts.vault;
     let total_shares = ctx.accounts.shares_mint.supply;
 
-    let assets = convert_to_assets(
-        shares,
+    let price = share_price(
         vault.total_assets,
         total_shares,
         vault.decimals_offset,
+        SHARE_PRICE_SCALE,
         Rounding::Floor,
     )?;
 
-    set_return_data(&assets.to_le_bytes());
+    set_return_data(&price.to_le_bytes());
     Ok(())
 }
 
@@ -136,9 +171,9 @@ pub fn get_total_assets(ctx: Context<VaultView>) -> Result<()> {
     Ok(())
 }
 
-/// Maximum assets that can be deposited (u64::MAX if not paused, 0 if paused)
+/// Maximum assets that can be deposited (u64::MAX, or 0 if the vault or protocol is paused)
 pub fn max_deposit(ctx: Context<VaultView>) -> Result<()> {
-    let max = if ctx.accounts.vault.paused {
+    let max = if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         0u64
     } else {
         u64::MAX
@@ -147,9 +182,9 @@ pub fn max_deposit(ctx: Context<VaultView>) -> Result<()> {
     Ok(())
 }
 
-/// Maximum shares that can be minted (u64::MAX if not paused, 0 if paused)
+/// Maximum shares that can be minted (u64::MAX, or 0 if the vault or protocol is paused)
 pub fn max_mint(ctx: Context<VaultView>) -> Result<()> {
-    let max = if ctx.accounts.vault.paused {
+    let max = if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         0u64
     } else {
         u64::MAX
@@ -160,7 +195,7 @@ pub fn max_mint(ctx: Context<VaultView>) -> Result<()> {
 
 /// Maximum assets that owner can withdraw (limited by their shares)
 pub fn max_withdraw(ctx: Context<VaultViewWithOwner>) -> Result<()> {
-    if ctx.accounts.vault.paused {
+    if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         set_return_data(&0u64.to_le_bytes());
         return Ok(());
     }
@@ -186,7 +221,7 @@ pub fn max_withdraw(ctx: Context<VaultViewWithOwner>) -> Result<()> {
 
 /// Maximum shares that owner can redeem (their share balance)
 pub fn max_redeem(ctx: Context<VaultViewWithOwner>) -> Result<()> {
-    let max = if ctx.accounts.vault.paused {
+    let max = if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         0u64
     } else {
         ctx.accounts.owner_shares_account.amount
@@ -5,10 +5,16 @@ pub mod error;
 pub mod events;
 pub mod instructions;
 pub mod math;
+#[path = "../../../shared/metadata.rs"]
+pub mod metadata;
 pub mod state;
+pub mod token_extensions;
 
 use instructions::*;
 
+/// This program, as named by the modules in `shared/`
+pub(crate) use program::Svs1 as VaultProgram;
+
 declare_id!("Bv8aVSQ3DJUe3B7TqQZRZgrNvVTh8TjfpwpoeR1ckDMC");
 
 #[program]
@@ -16,14 +22,16 @@ pub mod svs_1 {
     use super::*;
 
     /// Initialize a new vault for the given asset
+    /// `non_transferable` makes the shares soulbound (NonTransferable mint)
     pub fn initialize(
         ctx: Context<Initialize>,
         vault_id: u64,
         name: String,
         symbol: String,
         uri: String,
+        non_transferable: bool,
     ) -> Result<()> {
-        instructions::initialize::handler(ctx, vault_id, name, symbol, uri)
+        instructions::initialize::handler(ctx, vault_id, name, symbol, uri, non_transferable)
     }
 
     /// Deposit assets and receive shares
@@ -70,6 +78,61 @@ pub mod svs_1 {
         instructions::admin::sync(ctx)
     }
 
+    /// Override the vault's minimum deposit (at least the protocol minimum)
+    pub fn set_min_deposit(ctx: Context<SetMinDeposit>, min_deposit_amount: u64) -> Result<()> {
+        instructions::admin::set_min_deposit(ctx, min_deposit_amount)
+    }
+
+    // ============ Protocol Config ============
+
+    /// Create the protocol config (program upgrade authority only)
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        min_deposit_amount: u64,
+        max_decimals: u8,
+        shares_decimals: u8,
+    ) -> Result<()> {
+        instructions::config::initialize_config(
+            ctx,
+            min_deposit_amount,
+            max_decimals,
+            shares_decimals,
+        )
+    }
+
+    /// Update the parameters of new vaults
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        min_deposit_amount: u64,
+        max_decimals: u8,
+        shares_decimals: u8,
+    ) -> Result<()> {
+        instructions::config::update_config(ctx, min_deposit_amount, max_decimals, shares_decimals)
+    }
+
+    /// Transfer the protocol config authority
+    pub fn transfer_config_authority(
+        ctx: Context<UpdateConfig>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::config::transfer_config_authority(ctx, new_authority)
+    }
+
+    /// Set the protocol guardian (Pubkey::default() to remove)
+    pub fn set_protocol_guardian(ctx: Context<UpdateConfig>, new_guardian: Pubkey) -> Result<()> {
+        instructions::config::set_protocol_guardian(ctx, new_guardian)
+    }
+
+    /// Pause every vault of the program (config authority or guardian)
+    pub fn pause_protocol(ctx: Context<ProtocolGuardian>) -> Result<()> {
+        instructions::config::pause_protocol(ctx)
+    }
+
+    /// Resume every vault of the program (config authority or guardian)
+    pub fn unpause_protocol(ctx: Context<ProtocolGuardian>) -> Result<()> {
+        instructions::config::unpause_protocol(ctx)
+    }
+
     // ============ View Functions (CPI composable) ============
 
     /// Preview shares for deposit (floor rounding)
@@ -92,6 +155,26 @@ pub mod svs_1 {
         instructions::view::preview_redeem(ctx, shares)
     }
 
+    /// Preview shares for deposit, u64::MAX instead of an overflow error
+    pub fn saturating_preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+        instructions::view::saturating_preview_deposit(ctx, assets)
+    }
+
+    /// Preview assets required for mint, u64::MAX instead of an overflow error
+    pub fn saturating_preview_mint(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+        instructions::view::saturating_preview_mint(ctx, shares)
+    }
+
+    /// Preview shares to burn for withdraw, u64::MAX instead of an overflow error
+    pub fn saturating_preview_withdraw(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+        instructions::view::saturating_preview_withdraw(ctx, assets)
+    }
+
+    /// Preview assets for redeem, u64::MAX instead of an overflow error
+    pub fn saturating_preview_redeem(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+        instructions::view::saturating_preview_redeem(ctx, shares)
+    }
+
     /// Convert assets to shares (floor rounding)
     pub fn convert_to_shares(ctx: Context<VaultView>, assets: u64) -> Result<()> {
         instructions::view::convert_to_shares_view(ctx, assets)
@@ -102,6 +185,11 @@ pub mod svs_1 {
         instructions::view::convert_to_assets_view(ctx, shares)
     }
 
+    /// Price of one share in assets, 1e12 fixed point (floor rounding)
+    pub fn share_price(ctx: Context<VaultView>) -> Result<()> {
+        instructions::view::share_price_view(ctx)
+    }
+
     /// Get total assets in vault
     pub fn total_assets(ctx: Context<VaultView>) -> Result<()> {
         instructions::view::get_total_assets(ctx)
@@ -126,4 +214,12 @@ pub mod svs_1 {
     pub fn max_redeem(ctx: Context<VaultViewWithOwner>) -> Result<()> {
         instructions::view::max_redeem(ctx)
     }
+
+    // ============ Testing ============
+
+    /// Fail unless the vault's state is internally consistent
+    #[cfg(feature = "testing")]
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        instructions::invariants::handler(ctx)
+    }
 }
@@ -1,6 +1,14 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
 
-use crate::constants::VAULT_SEED;
+use crate::{
+    constants::{MIN_DEPOSIT_AMOUNT, VAULT_SEED},
+    error::VaultError,
+};
+
+#[path = "../../../shared/protocol_config.rs"]
+mod protocol_config;
+pub use protocol_config::ProtocolConfig;
 
 #[account]
 pub struct Vault {
@@ -22,8 +30,22 @@ pub struct Vault {
     pub paused: bool,
     /// Unique vault identifier (allows multiple vaults per asset)
     pub vault_id: u64,
+    /// Sequence number of the last emitted event (0 before the first)
+    pub event_sequence: u64,
+    /// Smallest accepted deposit; 0 for vaults created before it was stored,
+    /// which use `MIN_DEPOSIT_AMOUNT`
+    pub min_deposit_amount: u64,
+    /// `metadata::metadata_hash` of the name, symbol and uri given at
+    /// initialize; zeroed for vaults created before it was stored
+    pub metadata_hash: [u8; 32],
+    /// Number of mutating instructions run on the vault, initialize included.
+    /// Counts instructions where `event_sequence` counts events.
+    pub op_counter: u64,
+    /// Shares mint created with the NonTransferable extension; false for
+    /// vaults created before it was stored
+    pub non_transferable: bool,
     /// Reserved for future upgrades
-    pub _reserved: [u8; 64],
+    pub _reserved: [u8; 7],
 }
 
 impl Vault {
@@ -37,7 +59,46 @@ impl Vault {
         1 +   // bump
         1 +   // paused
         8 +   // vault_id
-        64; // _reserved
+        8 +   // event_sequence
+        8 +   // min_deposit_amount
+        32 +  // metadata_hash
+        8 +   // op_counter
+        1 +   // non_transferable
+        7; // _reserved
 
     pub const SEED_PREFIX: &'static [u8] = VAULT_SEED;
+
+    /// Count a mutating instruction, returning the new `op_counter` to the
+    /// caller as return data (u64 LE). Call after the instruction's CPIs,
+    /// which may set return data of their own.
+    pub fn record_op(&mut self) -> Result<()> {
+        self.op_counter = self
+            .op_counter
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        set_return_data(&self.op_counter.to_le_bytes());
+        Ok(())
+    }
+
+    /// Operation counter, sequence number and timestamp for the next emitted
+    /// event
+    pub fn stamp_event(&mut self) -> Result<(u64, u64, i64)> {
+        self.event_sequence = self
+            .event_sequence
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok((
+            self.op_counter,
+            self.event_sequence,
+            Clock::get()?.unix_timestamp,
+        ))
+    }
+
+    /// Smallest deposit the vault accepts
+    pub fn min_deposit(&self) -> u64 {
+        match self.min_deposit_amount {
+            0 => MIN_DEPOSIT_AMOUNT,
+            amount => amount,
+        }
+    }
 }
@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+use crate::error::VaultError;
+
+/// Fail fast if a Token-2022 account has CPI Guard enabled.
+///
+/// Without this check the Token-2022 program aborts mid-flow with a generic
+/// error (see [`svs_token_extensions::cpi_guard_enabled`]). Users can wrap the
+/// vault instruction between `DisableCpiGuard` and `EnableCpiGuard` in the same
+/// transaction.
+pub fn require_cpi_guard_disabled(account: &AccountInfo) -> Result<()> {
+    require!(
+        !svs_token_extensions::cpi_guard_enabled(account)?,
+        VaultError::CpiGuardEnabled
+    );
+    Ok(())
+}
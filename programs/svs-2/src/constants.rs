@@ -1,7 +1,37 @@
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const SHARES_MINT_SEED: &[u8] = b"shares";
+pub const PERMIT_SEED: &[u8] = b"permit";
+pub const PERMIT_DELEGATE_SEED: &[u8] = b"permit_delegate";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const HARVEST_AUTHORITY_SEED: &[u8] = b"harvest_authority";
 
+/// Protocol config defaults, in effect until the config account is created
 pub const MAX_DECIMALS: u8 = 9;
 pub const SHARES_DECIMALS: u8 = 9;
 
 pub const MIN_DEPOSIT_AMOUNT: u64 = 1000;
+
+/// Upper bound of the configurable shares decimals, keeping 10^decimals_offset
+/// well inside u64
+pub const MAX_SHARES_DECIMALS: u8 = 18;
+
+/// Default window over which harvested profit is streamed into share price
+pub const DEFAULT_PROFIT_UNLOCK_PERIOD: i64 = 6 * 60 * 60;
+
+/// Anchor-style discriminator of the swap adapter `swap(amount_in: u64, min_amount_out: u64)`
+/// instruction: sha256("global:swap")[..8]
+pub const SWAP_ADAPTER_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Domain separator prefixed to signed deposit permits
+pub const DEPOSIT_PERMIT_DOMAIN: &[u8] = b"svs-2:deposit-permit:v1";
+
+/// Compute units that must remain when `withdraw` or `redeem` starts. Covers
+/// the confidential withdraw, burn and asset transfer CPIs; the harness test
+/// `test_withdraw_compute_units` measures both instructions and fails if this
+/// drops below their cost.
+pub const WITHDRAW_MIN_COMPUTE_UNITS: u64 = 100_000;
+
+/// Bounds of the vault metadata passed to `initialize`, in bytes (as Metaplex)
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
@@ -11,7 +11,7 @@ pub enum VaultError {
     #[msg("Vault is paused")]
     VaultPaused,
 
-    #[msg("Asset decimals must be <= 9")]
+    #[msg("Asset decimals exceed the configured maximum")]
     InvalidAssetDecimals,
 
     #[msg("Arithmetic overflow")]
@@ -46,4 +46,73 @@ pub enum VaultError {
 
     #[msg("Invalid ciphertext format")]
     InvalidCiphertext,
+
+    #[msg("CPI Guard is enabled on token account - disable it for this transaction")]
+    CpiGuardEnabled,
+
+    #[msg("Vault is not paused")]
+    VaultNotPaused,
+
+    #[msg("Shares mint does not meet vault requirements")]
+    InvalidSharesMint,
+
+    #[msg("Swap adapter is not whitelisted for this vault")]
+    InvalidSwapAdapter,
+
+    #[msg("Reward account cannot be the asset vault")]
+    InvalidRewardAccount,
+
+    #[msg("Profit unlock period must not be negative")]
+    InvalidProfitUnlockPeriod,
+
+    #[msg("Deposit permit signature is missing or does not match")]
+    InvalidPermit,
+
+    #[msg("Deposit permit has expired")]
+    PermitExpired,
+
+    #[msg("Insufficient compute budget - add ComputeBudgetProgram.setComputeUnitLimit to the transaction")]
+    InsufficientComputeBudget,
+
+    #[msg("Vault invariant violated")]
+    InvariantViolated,
+
+    #[msg("Vault or protocol is already paused")]
+    AlreadyPaused,
+
+    #[msg("Shares mint size could not be calculated for its extensions")]
+    MintSizeUnavailable,
+
+    #[msg("Proof context account is not owned by the ZK ElGamal proof program")]
+    InvalidProofContextOwner,
+
+    #[msg("Proof context account holds a different proof type")]
+    ProofTypeMismatch,
+
+    #[msg("Invalid protocol config parameters")]
+    InvalidConfig,
+
+    #[msg("Minimum deposit below the protocol minimum")]
+    MinDepositTooLow,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Protocol is not paused")]
+    ProtocolNotPaused,
+
+    #[msg("Vault name must be 1-32 bytes without control characters")]
+    InvalidVaultName,
+
+    #[msg("Vault symbol must be 1-10 ASCII letters or digits")]
+    InvalidVaultSymbol,
+
+    #[msg("Vault uri must be at most 200 bytes without whitespace or control characters")]
+    InvalidVaultUri,
+
+    #[msg("Vault account already has the current layout")]
+    VaultAlreadyMigrated,
+
+    #[msg("Harvest took assets from the asset vault or changed the share supply")]
+    HarvestTouchedVault,
 }
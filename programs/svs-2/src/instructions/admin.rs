@@ -2,11 +2,18 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::TokenAccount;
 
 use crate::{
+    constants::CONFIG_SEED,
     error::VaultError,
-    events::{AuthorityTransferred, VaultStatusChanged, VaultSynced},
-    state::ConfidentialVault,
+    events::{
+        AuthorityTransferred, GuardianUpdated, HarvestConfigUpdated, MinDepositUpdated,
+        VaultStatusChanged, VaultSynced,
+    },
+    state::{ConfidentialVault, ProtocolConfig},
 };
 
+/// Admin instructions only need `authority` to sign: it is never mutable or
+/// charged rent, so a governance or multisig PDA can act by invoking via CPI
+/// with its signer seeds, while a plain keypair keeps signing directly.
 #[derive(Accounts)]
 pub struct Admin<'info> {
     #[account(
@@ -18,6 +25,36 @@ pub struct Admin<'info> {
     pub vault: Account<'info, ConfidentialVault>,
 }
 
+#[derive(Accounts)]
+pub struct SetMinDeposit<'info> {
+    #[account(
+        constraint = authority.key() == vault.authority @ VaultError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, ConfidentialVault>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+/// Pause is the only instruction the guardian may call, so bots can trip the
+/// circuit breaker without holding authority over funds or auditor settings.
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        constraint = authority.key() == vault.authority
+            || authority.key() == vault.guardian @ VaultError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, ConfidentialVault>,
+}
+
 #[derive(Accounts)]
 pub struct Sync<'info> {
     #[account(
@@ -35,16 +72,21 @@ pub struct Sync<'info> {
 }
 
 /// Pause all vault operations (emergency circuit breaker)
-pub fn pause(ctx: Context<Admin>) -> Result<()> {
+pub fn pause(ctx: Context<Pause>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
-    require!(!vault.paused, VaultError::VaultPaused);
+    require!(!vault.paused, VaultError::AlreadyPaused);
 
     vault.paused = true;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultStatusChanged {
         vault: vault.key(),
         paused: true,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -54,13 +96,18 @@ pub fn pause(ctx: Context<Admin>) -> Result<()> {
 pub fn unpause(ctx: Context<Admin>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
-    require!(vault.paused, VaultError::VaultPaused);
+    require!(vault.paused, VaultError::VaultNotPaused);
 
     vault.paused = false;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultStatusChanged {
         vault: vault.key(),
         paused: false,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -73,10 +120,75 @@ pub fn transfer_authority(ctx: Context<Admin>, new_authority: Pubkey) -> Result<
 
     vault.authority = new_authority;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(AuthorityTransferred {
         vault: vault.key(),
         previous_authority,
         new_authority,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Set or clear (Pubkey::default()) the pause-only guardian key
+pub fn set_guardian(ctx: Context<Admin>, new_guardian: Pubkey) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let previous_guardian = vault.guardian;
+
+    vault.guardian = new_guardian;
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(GuardianUpdated {
+        vault: vault.key(),
+        previous_guardian,
+        new_guardian,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Configure harvesting: whitelisted swap adapter, keeper key and profit unlock window.
+/// Pubkey::default() disables the adapter / harvester respectively.
+pub fn set_harvest_config(
+    ctx: Context<Admin>,
+    swap_adapter: Pubkey,
+    harvester: Pubkey,
+    profit_unlock_period: i64,
+) -> Result<()> {
+    require!(
+        profit_unlock_period >= 0,
+        VaultError::InvalidProfitUnlockPeriod
+    );
+
+    let vault = &mut ctx.accounts.vault;
+
+    // Settle the current stream so changing the window never re-locks unlocked profit
+    let now = Clock::get()?.unix_timestamp;
+    vault.locked_profit = vault.locked_profit_at(now)?;
+    vault.last_harvest_ts = now;
+
+    vault.swap_adapter = swap_adapter;
+    vault.harvester = harvester;
+    vault.profit_unlock_period = profit_unlock_period;
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(HarvestConfigUpdated {
+        vault: vault.key(),
+        swap_adapter,
+        harvester,
+        profit_unlock_period,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -91,10 +203,41 @@ pub fn sync(ctx: Context<Sync>) -> Result<()> {
 
     vault.total_assets = actual_balance;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultSynced {
         vault: vault.key(),
         previous_total,
         new_total: actual_balance,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Override the vault's minimum deposit, no lower than the protocol minimum
+pub fn set_min_deposit(ctx: Context<SetMinDeposit>, min_deposit_amount: u64) -> Result<()> {
+    let config = ProtocolConfig::load_or_default(&ctx.accounts.config)?;
+    require!(
+        min_deposit_amount >= config.min_deposit_amount,
+        VaultError::MinDepositTooLow
+    );
+
+    let vault = &mut ctx.accounts.vault;
+    let previous = vault.min_deposit_amount;
+    vault.min_deposit_amount = min_deposit_amount;
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(MinDepositUpdated {
+        vault: vault.key(),
+        previous,
+        new: min_deposit_amount,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
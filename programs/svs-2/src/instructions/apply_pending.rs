@@ -8,7 +8,10 @@ use crate::error::VaultError;
 use solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext;
 use spl_token_2022::extension::confidential_transfer::instruction::apply_pending_balance;
 
-use crate::state::ConfidentialVault;
+use crate::{
+    constants::CONFIG_SEED,
+    state::{ConfidentialVault, ProtocolConfig},
+};
 
 /// Apply pending balance to available balance.
 /// Must be called after deposit/mint before shares can be used.
@@ -32,6 +35,11 @@ pub struct ApplyPending<'info> {
     pub user_shares_account: InterfaceAccount<'info, TokenAccount>,
 
     pub token_2022_program: Program<'info, Token2022>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Apply pending confidential balance to available balance
@@ -46,6 +54,7 @@ pub fn handler(
     new_decryptable_available_balance: [u8; 36], // PodAeCiphertext is 36 bytes
     expected_pending_balance_credit_counter: u64,
 ) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     let user = &ctx.accounts.user;
     let user_shares_account = &ctx.accounts.user_shares_account;
 
@@ -4,14 +4,18 @@ use anchor_spl::token_2022::Token2022;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 use bytemuck::{try_from_bytes, Zeroable};
 use solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext;
-use solana_zk_sdk::zk_elgamal_proof_program::proof_data::PubkeyValidityProofData;
+use solana_zk_sdk::zk_elgamal_proof_program::proof_data::{ProofType, PubkeyValidityProofData};
 use spl_token_2022::extension::confidential_transfer::instruction::inner_configure_account;
 use spl_token_2022::extension::confidential_transfer::DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER;
 use spl_token_2022::extension::ExtensionType;
 use spl_token_2022::instruction::reallocate;
 use spl_token_confidential_transfer_proof_extraction::instruction::{ProofData, ProofLocation};
 
-use crate::state::ConfidentialVault;
+use crate::{
+    constants::CONFIG_SEED,
+    state::{ConfidentialVault, ProtocolConfig},
+    token_extensions::require_proof_context,
+};
 
 /// Configure a user's shares account for confidential transfers.
 /// Must be called before the user can receive confidential shares.
@@ -20,11 +24,18 @@ use crate::state::ConfidentialVault;
 /// 1. Include a VerifyPubkeyValidity instruction in the same transaction
 ///    (at offset -1 from this instruction)
 /// 2. OR provide a pre-verified proof context account
+///
+/// Rent for the account reallocation is paid by `payer` when provided, so a
+/// sponsor can onboard users who hold no SOL; otherwise the user pays.
 #[derive(Accounts)]
 pub struct ConfigureAccount<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// Optional sponsor paying reallocation rent instead of the user
+    #[account(mut)]
+    pub payer: Option<Signer<'info>>,
+
     pub vault: Account<'info, ConfidentialVault>,
 
     #[account(constraint = shares_mint.key() == vault.shares_mint)]
@@ -47,6 +58,11 @@ pub struct ConfigureAccount<'info> {
 
     pub token_2022_program: Program<'info, Token2022>,
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Configure account for confidential transfers
@@ -61,15 +77,22 @@ pub fn handler(
     decryptable_zero_balance: [u8; 36], // PodAeCiphertext is 36 bytes
     proof_instruction_offset: i8,
 ) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     let user = &ctx.accounts.user;
     let user_shares_account = &ctx.accounts.user_shares_account;
     let shares_mint = &ctx.accounts.shares_mint;
+    let payer = ctx
+        .accounts
+        .payer
+        .as_ref()
+        .map(|payer| payer.to_account_info())
+        .unwrap_or_else(|| user.to_account_info());
 
     // Step 1: Reallocate account to add ConfidentialTransferAccount extension
     let reallocate_ix = reallocate(
         &ctx.accounts.token_2022_program.key(),
         &user_shares_account.key(),
-        &user.key(),
+        payer.key,
         &user.key(),
         &[],
         &[ExtensionType::ConfidentialTransferAccount],
@@ -79,8 +102,9 @@ pub fn handler(
         &reallocate_ix,
         &[
             user_shares_account.to_account_info(),
-            user.to_account_info(),
+            payer.clone(),
             ctx.accounts.system_program.to_account_info(),
+            user.to_account_info(),
         ],
     )?;
 
@@ -93,6 +117,7 @@ pub fn handler(
     // Create configure instruction based on proof location
     let configure_ix = if let Some(proof_context) = &ctx.accounts.proof_context_account {
         // Use pre-verified context state account
+        require_proof_context(proof_context, ProofType::PubkeyValidity)?;
         inner_configure_account(
             &ctx.accounts.token_2022_program.key(),
             &user_shares_account.key(),
@@ -8,16 +8,18 @@ use anchor_spl::{
 use spl_token_2022::extension::confidential_transfer::instruction::deposit as confidential_deposit;
 
 use crate::{
-    constants::{MIN_DEPOSIT_AMOUNT, SHARES_DECIMALS, VAULT_SEED},
+    constants::{CONFIG_SEED, VAULT_SEED},
     error::VaultError,
-    events::Deposit as DepositEvent,
+    events::{ComplianceMemo, ComplianceOperation, Deposit as DepositEvent},
     math::{convert_to_shares, Rounding},
-    state::ConfidentialVault,
+    state::{ConfidentialVault, ProtocolConfig},
+    token_extensions::{require_cpi_guard_disabled, transfer_fee},
 };
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
-    #[account(mut)]
+    /// Only signs ownership-related CPIs; it is never charged rent, so a sponsor
+    /// can be the transaction fee payer for users holding no SOL
     pub user: Signer<'info>,
 
     #[account(
@@ -62,6 +64,11 @@ pub struct Deposit<'info> {
     pub token_2022_program: Program<'info, Token2022>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Deposit assets and receive confidential shares
@@ -72,17 +79,31 @@ pub struct Deposit<'info> {
 ///
 /// NOTE: User's shares account must be configured for confidential transfers
 /// (call configure_account first)
-pub fn handler(ctx: Context<Deposit>, assets: u64, min_shares_out: u64) -> Result<()> {
+pub fn handler(
+    ctx: Context<Deposit>,
+    assets: u64,
+    min_shares_out: u64,
+    memo_hash: Option<[u8; 32]>,
+) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     require!(assets > 0, VaultError::ZeroAmount);
-    require!(assets >= MIN_DEPOSIT_AMOUNT, VaultError::DepositTooSmall);
+    require!(
+        assets >= ctx.accounts.vault.min_deposit(),
+        VaultError::DepositTooSmall
+    );
+    require_cpi_guard_disabled(&ctx.accounts.user_asset_account.to_account_info())?;
+
+    // Transfer-fee mints withhold part of the transfer; only the net amount reaches the vault
+    let fee = transfer_fee(&ctx.accounts.asset_mint.to_account_info(), assets)?;
+    let net_assets = assets.checked_sub(fee).ok_or(VaultError::MathOverflow)?;
 
     let vault = &ctx.accounts.vault;
     let total_shares = ctx.accounts.shares_mint.supply;
 
     // Calculate shares to mint (floor rounding - favors vault)
     let shares = convert_to_shares(
-        assets,
-        vault.total_assets,
+        net_assets,
+        vault.unlocked_assets()?,
         total_shares,
         vault.decimals_offset,
         Rounding::Floor,
@@ -136,7 +157,7 @@ pub fn handler(ctx: Context<Deposit>, assets: u64, min_shares_out: u64) -> Resul
         &ctx.accounts.user_shares_account.key(),
         &ctx.accounts.shares_mint.key(),
         shares,
-        SHARES_DECIMALS,
+        ctx.accounts.shares_mint.decimals,
         &ctx.accounts.user.key(),
         &[],
     )?;
@@ -154,16 +175,34 @@ pub fn handler(ctx: Context<Deposit>, assets: u64, min_shares_out: u64) -> Resul
     let vault = &mut ctx.accounts.vault;
     vault.total_assets = vault
         .total_assets
-        .checked_add(assets)
+        .checked_add(net_assets)
         .ok_or(VaultError::MathOverflow)?;
 
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
     emit!(DepositEvent {
         vault: ctx.accounts.vault.key(),
         caller: ctx.accounts.user.key(),
         owner: ctx.accounts.user.key(),
-        assets,
+        assets: net_assets,
         shares,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
+    if let Some(memo_hash) = memo_hash {
+        let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
+        emit!(ComplianceMemo {
+            vault: ctx.accounts.vault.key(),
+            owner: ctx.accounts.user.key(),
+            operation: ComplianceOperation::Deposit,
+            memo_hash,
+            op_counter,
+            sequence,
+            timestamp,
+        });
+    }
+
     Ok(())
 }
@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::ID as INSTRUCTIONS_SYSVAR_ID;
+use anchor_spl::{
+    token_2022::{self, MintTo, Token2022},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::{CONFIG_SEED, PERMIT_DELEGATE_SEED, PERMIT_SEED, VAULT_SEED},
+    error::VaultError,
+    events::Deposit as DepositEvent,
+    math::{convert_to_shares, Rounding},
+    permit::{deposit_permit_message, require_ed25519_signature},
+    state::{ConfidentialVault, PermitReceipt, ProtocolConfig},
+    token_extensions::transfer_fee,
+};
+
+/// Relayed deposit authorized by an off-chain Ed25519 signature from the owner.
+///
+/// The relayer signs and pays for the transaction. Assets are pulled from the
+/// owner's account by the vault's permit delegate PDA
+/// (`["permit_delegate", vault]`), so the owner must have approved it as token
+/// delegate (e.g. once during custodial onboarding). The program signs as the
+/// permit delegate here and nowhere else, so only a signed permit spends the
+/// allowance.
+#[derive(Accounts)]
+#[instruction(assets: u64, min_shares_out: u64, nonce: u64)]
+pub struct DepositWithPermit<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: Authenticated by the Ed25519 permit signature
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = !vault.paused @ VaultError::VaultPaused,
+    )]
+    pub vault: Account<'info, ConfidentialVault>,
+
+    /// CHECK: PDA the owner approved as delegate of their asset account
+    #[account(
+        seeds = [PERMIT_DELEGATE_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub permit_delegate: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = asset_mint.key() == vault.asset_mint,
+    )]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = owner_asset_account.mint == vault.asset_mint,
+        constraint = owner_asset_account.owner == owner.key(),
+    )]
+    pub owner_asset_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = asset_vault.key() == vault.asset_vault,
+    )]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = shares_mint.key() == vault.shares_mint,
+    )]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = owner_shares_account.mint == vault.shares_mint,
+        constraint = owner_shares_account.owner == owner.key(),
+    )]
+    pub owner_shares_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Creation fails if this nonce was already used, preventing replay
+    #[account(
+        init,
+        payer = relayer,
+        space = PermitReceipt::LEN,
+        seeds = [PERMIT_SEED, vault.key().as_ref(), owner.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub permit_receipt: Account<'info, PermitReceipt>,
+
+    /// CHECK: Address constrained to the instructions sysvar
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub asset_token_program: Interface<'info, TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+/// Deposit on behalf of `owner` using a signed permit
+///
+/// The transaction must place an Ed25519 verify instruction over
+/// `deposit_permit_message(..)` immediately before this one. Shares are minted to
+/// the owner's non-confidential balance: moving them into the confidential
+/// balance requires the owner's signature (Token-2022 confidential deposit +
+/// apply_pending), which a relayer can also fee-pay.
+pub fn handler(
+    ctx: Context<DepositWithPermit>,
+    assets: u64,
+    min_shares_out: u64,
+    nonce: u64,
+    expires_at: i64,
+) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
+    require!(assets > 0, VaultError::ZeroAmount);
+    require!(
+        assets >= ctx.accounts.vault.min_deposit(),
+        VaultError::DepositTooSmall
+    );
+    require!(
+        Clock::get()?.unix_timestamp <= expires_at,
+        VaultError::PermitExpired
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+    let owner_key = ctx.accounts.owner.key();
+    let message = deposit_permit_message(
+        &vault_key,
+        &owner_key,
+        assets,
+        min_shares_out,
+        nonce,
+        expires_at,
+    );
+    require_ed25519_signature(
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+        &owner_key,
+        &message,
+    )?;
+
+    // Transfer-fee mints withhold part of the transfer; only the net amount reaches the vault
+    let fee = transfer_fee(&ctx.accounts.asset_mint.to_account_info(), assets)?;
+    let net_assets = assets.checked_sub(fee).ok_or(VaultError::MathOverflow)?;
+
+    let vault = &ctx.accounts.vault;
+    let total_shares = ctx.accounts.shares_mint.supply;
+
+    // Calculate shares to mint (floor rounding - favors vault)
+    let shares = convert_to_shares(
+        net_assets,
+        vault.unlocked_assets()?,
+        total_shares,
+        vault.decimals_offset,
+        Rounding::Floor,
+    )?;
+
+    // Slippage check
+    require!(shares >= min_shares_out, VaultError::SlippageExceeded);
+
+    let asset_mint_key = ctx.accounts.vault.asset_mint;
+    let vault_id_bytes = ctx.accounts.vault.vault_id.to_le_bytes();
+    let bump = ctx.accounts.vault.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        VAULT_SEED,
+        asset_mint_key.as_ref(),
+        vault_id_bytes.as_ref(),
+        &[bump],
+    ]];
+
+    // Pull assets with the permit delegate (token program enforces the allowance).
+    // Delegated transfers are permitted under CPI Guard, so no guard check is needed.
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.asset_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.owner_asset_account.to_account_info(),
+                to: ctx.accounts.asset_vault.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                authority: ctx.accounts.permit_delegate.to_account_info(),
+            },
+            &[&[
+                PERMIT_DELEGATE_SEED,
+                vault_key.as_ref(),
+                &[ctx.bumps.permit_delegate],
+            ]],
+        ),
+        assets,
+        ctx.accounts.asset_mint.decimals,
+    )?;
+
+    // Mint shares to owner's non-confidential balance (vault PDA is mint authority)
+    token_2022::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_2022_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                to: ctx.accounts.owner_shares_account.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        shares,
+    )?;
+
+    let receipt = &mut ctx.accounts.permit_receipt;
+    receipt.vault = vault_key;
+    receipt.owner = owner_key;
+    receipt.nonce = nonce;
+    receipt.bump = ctx.bumps.permit_receipt;
+
+    // Update cached total assets
+    let vault = &mut ctx.accounts.vault;
+    vault.total_assets = vault
+        .total_assets
+        .checked_add(net_assets)
+        .ok_or(VaultError::MathOverflow)?;
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(DepositEvent {
+        vault: vault_key,
+        caller: ctx.accounts.relayer.key(),
+        owner: owner_key,
+        assets: net_assets,
+        shares,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{self, Burn, Token2022},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::VAULT_SEED,
+    error::VaultError,
+    events::EmergencyRedeem as EmergencyRedeemEvent,
+    math::{mul_div, Rounding},
+    state::ConfidentialVault,
+    token_extensions::require_cpi_guard_disabled,
+};
+
+#[derive(Accounts)]
+pub struct EmergencyRedeem<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = vault.paused @ VaultError::VaultNotPaused,
+    )]
+    pub vault: Account<'info, ConfidentialVault>,
+
+    #[account(
+        constraint = asset_mint.key() == vault.asset_mint,
+    )]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = user_asset_account.mint == vault.asset_mint,
+        constraint = user_asset_account.owner == user.key(),
+    )]
+    pub user_asset_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = asset_vault.key() == vault.asset_vault,
+    )]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = shares_mint.key() == vault.shares_mint,
+    )]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = user_shares_account.mint == vault.shares_mint,
+        constraint = user_shares_account.owner == user.key(),
+    )]
+    pub user_shares_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub asset_token_program: Interface<'info, TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+/// Emergency exit while the vault is paused: redeem public-balance shares
+/// pro-rata against the actual asset balance
+///
+/// Burns shares from the user's public (non-confidential) balance. Users first
+/// move their confidential balance to the public balance with a direct Token-2022
+/// confidential `Withdraw` (no vault involvement), then call this instruction.
+///
+/// Deliberately bypasses the cached `total_assets` and virtual offset so that
+/// funds can always leave, even if the normal accounting path is the reason the
+/// vault was paused: assets = shares × asset_vault.amount / shares_supply (floor).
+pub fn handler(ctx: Context<EmergencyRedeem>, shares: u64) -> Result<()> {
+    require!(shares > 0, VaultError::ZeroAmount);
+    require!(
+        shares <= ctx.accounts.user_shares_account.amount,
+        VaultError::InsufficientShares
+    );
+    require_cpi_guard_disabled(&ctx.accounts.user_shares_account.to_account_info())?;
+
+    let assets = mul_div(
+        shares,
+        ctx.accounts.asset_vault.amount,
+        ctx.accounts.shares_mint.supply,
+        Rounding::Floor,
+    )?;
+
+    // Burn shares from user's public balance
+    token_2022::burn(
+        CpiContext::new(
+            ctx.accounts.token_2022_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.shares_mint.to_account_info(),
+                from: ctx.accounts.user_shares_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    // Transfer pro-rata assets from vault to user
+    let asset_mint_key = ctx.accounts.vault.asset_mint;
+    let vault_id_bytes = ctx.accounts.vault.vault_id.to_le_bytes();
+    let bump = ctx.accounts.vault.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        VAULT_SEED,
+        asset_mint_key.as_ref(),
+        vault_id_bytes.as_ref(),
+        &[bump],
+    ]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.asset_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.asset_vault.to_account_info(),
+                to: ctx.accounts.user_asset_account.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        assets,
+        ctx.accounts.asset_mint.decimals,
+    )?;
+
+    // Cached total may already be out of sync; never let it block the exit
+    let vault = &mut ctx.accounts.vault;
+    vault.total_assets = vault.total_assets.saturating_sub(assets);
+
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
+    emit!(EmergencyRedeemEvent {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.user.key(),
+        assets,
+        shares,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    constants::{CONFIG_SEED, HARVEST_AUTHORITY_SEED, SWAP_ADAPTER_SWAP_DISCRIMINATOR},
+    error::VaultError,
+    events::Harvested,
+    state::{ConfidentialVault, ProtocolConfig},
+};
+
+/// Harvest rewards accrued to a token account of the vault's harvest authority.
+///
+/// Rewards in the asset mint are moved straight into the asset vault. Any other
+/// mint is swapped through the whitelisted adapter program, which receives
+/// `[harvest_authority (signer), reward_token_account, asset_vault,
+/// ..remaining_accounts]` and must deliver at least `min_assets_out` into the
+/// asset vault.
+///
+/// The harvest authority PDA (`["harvest_authority", vault]`) is the only
+/// signature the adapter gets. It owns the reward accounts and nothing else,
+/// so the adapter cannot move the vault's assets or mint shares; remaining
+/// accounts are passed on without their signer flags. The asset vault balance
+/// and the share supply are checked again after the swap regardless.
+#[derive(Accounts)]
+pub struct Harvest<'info> {
+    #[account(
+        constraint = caller.key() == vault.authority
+            || caller.key() == vault.harvester @ VaultError::Unauthorized,
+    )]
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = !vault.paused @ VaultError::VaultPaused,
+    )]
+    pub vault: Account<'info, ConfidentialVault>,
+
+    /// CHECK: PDA owning the reward accounts; signs their transfer or swap
+    #[account(
+        seeds = [HARVEST_AUTHORITY_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub harvest_authority: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = asset_mint.key() == vault.asset_mint,
+    )]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = asset_vault.key() == vault.asset_vault,
+    )]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = shares_mint.key() == vault.shares_mint,
+    )]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = reward_token_account.owner == harvest_authority.key() @ VaultError::Unauthorized,
+        constraint = reward_token_account.key() != vault.asset_vault @ VaultError::InvalidRewardAccount,
+    )]
+    pub reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Must match the adapter whitelisted in vault state; only used as a CPI target
+    #[account(
+        executable,
+        constraint = swap_adapter.key() == vault.swap_adapter @ VaultError::InvalidSwapAdapter,
+    )]
+    pub swap_adapter: UncheckedAccount<'info>,
+
+    pub asset_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Harvest<'info>>,
+    min_assets_out: u64,
+) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
+    let reward_amount = ctx.accounts.reward_token_account.amount;
+    require!(reward_amount > 0, VaultError::ZeroAmount);
+
+    let asset_mint_key = ctx.accounts.vault.asset_mint;
+    let vault_key = ctx.accounts.vault.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        HARVEST_AUTHORITY_SEED,
+        vault_key.as_ref(),
+        &[ctx.bumps.harvest_authority],
+    ]];
+
+    let balance_before = ctx.accounts.asset_vault.amount;
+    let supply_before = ctx.accounts.shares_mint.supply;
+
+    if ctx.accounts.reward_token_account.mint == asset_mint_key {
+        // Rewards already in the asset: no swap needed
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.asset_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.reward_token_account.to_account_info(),
+                    to: ctx.accounts.asset_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info(),
+                    authority: ctx.accounts.harvest_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            reward_amount,
+            ctx.accounts.asset_mint.decimals,
+        )?;
+    } else {
+        let authority_info = ctx.accounts.harvest_authority.to_account_info();
+        let reward_info = ctx.accounts.reward_token_account.to_account_info();
+        let asset_vault_info = ctx.accounts.asset_vault.to_account_info();
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(authority_info.key(), true),
+            AccountMeta::new(reward_info.key(), false),
+            AccountMeta::new(asset_vault_info.key(), false),
+        ];
+        // The caller's signatures stay with the caller
+        accounts.extend(ctx.remaining_accounts.iter().map(|account| {
+            if account.is_writable {
+                AccountMeta::new(account.key(), false)
+            } else {
+                AccountMeta::new_readonly(account.key(), false)
+            }
+        }));
+
+        let mut data = SWAP_ADAPTER_SWAP_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&reward_amount.to_le_bytes());
+        data.extend_from_slice(&min_assets_out.to_le_bytes());
+
+        let mut account_infos = vec![authority_info, reward_info, asset_vault_info];
+        account_infos.extend_from_slice(ctx.remaining_accounts);
+
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.swap_adapter.key(),
+                accounts,
+                data,
+            },
+            &account_infos,
+            signer_seeds,
+        )?;
+    }
+
+    // Measure proceeds from the balance change so adapter and transfer-fee
+    // behaviour cannot inflate the recorded amount
+    ctx.accounts.asset_vault.reload()?;
+    ctx.accounts.shares_mint.reload()?;
+    let assets_added = ctx
+        .accounts
+        .asset_vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(VaultError::HarvestTouchedVault)?;
+    require!(
+        ctx.accounts.shares_mint.supply == supply_before,
+        VaultError::HarvestTouchedVault
+    );
+    require!(assets_added >= min_assets_out, VaultError::SlippageExceeded);
+
+    // Stream the new profit in on top of whatever is still locked from earlier harvests
+    let now = Clock::get()?.unix_timestamp;
+    let vault = &mut ctx.accounts.vault;
+    let still_locked = vault.locked_profit_at(now)?;
+
+    vault.total_assets = vault
+        .total_assets
+        .checked_add(assets_added)
+        .ok_or(VaultError::MathOverflow)?;
+    vault.locked_profit = still_locked
+        .checked_add(assets_added)
+        .ok_or(VaultError::MathOverflow)?;
+    vault.last_harvest_ts = now;
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(Harvested {
+        vault: vault.key(),
+        reward_mint: ctx.accounts.reward_token_account.mint,
+        reward_amount,
+        assets_added,
+        locked_profit: vault.locked_profit,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
@@ -13,27 +13,38 @@ use solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey;
 use spl_token_2022::extension::confidential_transfer::instruction::initialize_mint as initialize_confidential_mint;
 
 use crate::{
-    constants::{MAX_DECIMALS, SHARES_DECIMALS, SHARES_MINT_SEED, VAULT_SEED},
+    constants::{CONFIG_SEED, DEFAULT_PROFIT_UNLOCK_PERIOD, SHARES_MINT_SEED, VAULT_SEED},
     error::VaultError,
     events::VaultInitialized,
-    state::ConfidentialVault,
+    metadata::{metadata_hash, validate_metadata},
+    state::{ConfidentialVault, ProtocolConfig},
 };
 
 #[derive(Accounts)]
 #[instruction(vault_id: u64)]
 pub struct Initialize<'info> {
+    /// Pays rent for the vault accounts; may differ from the authority
     #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Initial vault authority. Not mutable and never charged rent, so a
+    /// governance or multisig PDA signing via CPI can initialize directly.
     pub authority: Signer<'info>,
 
     #[account(
         init,
-        payer = authority,
+        payer = payer,
         space = ConfidentialVault::LEN,
         seeds = [VAULT_SEED, asset_mint.key().as_ref(), &vault_id.to_le_bytes()],
         bump
     )]
     pub vault: Account<'info, ConfidentialVault>,
 
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
     pub asset_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Shares mint is initialized via CPI in handler
@@ -46,7 +57,7 @@ pub struct Initialize<'info> {
 
     #[account(
         init,
-        payer = authority,
+        payer = payer,
         associated_token::mint = asset_mint,
         associated_token::authority = vault,
         associated_token::token_program = asset_token_program,
@@ -65,12 +76,15 @@ pub fn handler(
     vault_id: u64,
     name: String,
     symbol: String,
-    _uri: String,
+    uri: String,
     auditor_elgamal_pubkey: Option<[u8; 32]>,
 ) -> Result<()> {
+    validate_metadata(&name, &symbol, &uri)?;
+
+    let config = ProtocolConfig::load_or_default(&ctx.accounts.config)?;
     let asset_decimals = ctx.accounts.asset_mint.decimals;
     require!(
-        asset_decimals <= MAX_DECIMALS,
+        asset_decimals <= config.max_decimals,
         VaultError::InvalidAssetDecimals
     );
 
@@ -82,7 +96,7 @@ pub fn handler(
     let extensions = [ExtensionType::ConfidentialTransferMint];
     let mint_size =
         ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)
-            .map_err(|_| VaultError::MathOverflow)?;
+            .map_err(|_| VaultError::MintSizeUnavailable)?;
 
     let rent = &ctx.accounts.rent;
     let lamports = rent.minimum_balance(mint_size);
@@ -109,14 +123,14 @@ pub fn handler(
     // Create shares mint account with space for extensions
     invoke_signed(
         &anchor_lang::solana_program::system_instruction::create_account(
-            &ctx.accounts.authority.key(),
+            &ctx.accounts.payer.key(),
             &ctx.accounts.shares_mint.key(),
             lamports,
             mint_size as u64,
             &ctx.accounts.token_2022_program.key(),
         ),
         &[
-            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
             ctx.accounts.shares_mint.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
         ],
@@ -149,7 +163,7 @@ pub fn handler(
         &ctx.accounts.shares_mint.key(),
         &vault_key,
         None,
-        SHARES_DECIMALS,
+        config.shares_decimals,
     )?;
 
     invoke_signed(
@@ -165,20 +179,37 @@ pub fn handler(
     vault.shares_mint = ctx.accounts.shares_mint.key();
     vault.asset_vault = ctx.accounts.asset_vault.key();
     vault.total_assets = 0;
-    vault.decimals_offset = MAX_DECIMALS - asset_decimals;
+    vault.decimals_offset = config.shares_decimals - asset_decimals;
     vault.bump = vault_bump;
     vault.paused = false;
     vault.vault_id = vault_id;
     vault.auditor_elgamal_pubkey = auditor_elgamal_pubkey;
     vault.confidential_authority = vault_key;
-    vault._reserved = [0u8; 32];
-
+    vault.guardian = Pubkey::default();
+    vault.swap_adapter = Pubkey::default();
+    vault.harvester = Pubkey::default();
+    vault.locked_profit = 0;
+    vault.last_harvest_ts = 0;
+    vault.profit_unlock_period = DEFAULT_PROFIT_UNLOCK_PERIOD;
+    vault.event_sequence = 0;
+    vault.min_deposit_amount = config.min_deposit_amount;
+    vault.metadata_hash = metadata_hash(&name, &symbol, &uri);
+    vault.op_counter = 0;
+    vault._reserved = [0u8; 8];
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(VaultInitialized {
         vault: vault.key(),
         authority: vault.authority,
         asset_mint: vault.asset_mint,
         shares_mint: vault.shares_mint,
         vault_id,
+        // Confidential shares must stay transferable
+        non_transferable: false,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     msg!(
@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use bytemuck::cast;
+use solana_zk_sdk::encryption::pod::elgamal::PodElGamalPubkey;
+use spl_token_2022::extension::{
+    confidential_transfer::ConfidentialTransferMint, BaseStateWithExtensions, ExtensionType,
+    StateWithExtensions,
+};
+
+use crate::{
+    constants::{CONFIG_SEED, DEFAULT_PROFIT_UNLOCK_PERIOD, VAULT_SEED},
+    error::VaultError,
+    events::VaultInitialized,
+    metadata::{metadata_hash, validate_metadata},
+    state::{ConfidentialVault, ProtocolConfig},
+};
+
+/// The only extensions a shares mint may carry. Anything else could let a
+/// third party seize, freeze, tax, gate or strand vault shares (permanent
+/// delegate, close authority, default frozen state, pausing, transfer fees or
+/// hooks), or is not vetted yet; extensions this program's Token-2022 version
+/// cannot parse are rejected as well.
+const ALLOWED_SHARES_MINT_EXTENSIONS: [ExtensionType; 5] = [
+    ExtensionType::ConfidentialTransferMint,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+];
+
+/// Initialize a vault around a pre-created Token-2022 shares mint.
+///
+/// The deployer creates the mint (adding metadata or group membership if wanted,
+/// see `ALLOWED_SHARES_MINT_EXTENSIONS`), configures ConfidentialTransferMint
+/// with the vault PDA as authority, and sets the vault PDA as mint authority
+/// before calling this instruction. The vault PDA address is deterministic, so it
+/// can be derived before the vault exists.
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct InitializeWithMint<'info> {
+    /// Pays rent for the vault accounts; may differ from the authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Initial vault authority. Not mutable and never charged rent, so a
+    /// governance or multisig PDA signing via CPI can initialize directly.
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ConfidentialVault::LEN,
+        seeds = [VAULT_SEED, asset_mint.key().as_ref(), &vault_id.to_le_bytes()],
+        bump
+    )]
+    pub vault: Account<'info, ConfidentialVault>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mint::token_program = token_2022_program,
+        constraint = shares_mint.mint_authority == COption::Some(vault.key()) @ VaultError::InvalidSharesMint,
+        constraint = shares_mint.freeze_authority.is_none() @ VaultError::InvalidSharesMint,
+        constraint = shares_mint.supply == 0 @ VaultError::InvalidSharesMint,
+    )]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = asset_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = asset_token_program,
+    )]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub asset_token_program: Interface<'info, TokenInterface>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeWithMint>,
+    vault_id: u64,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    validate_metadata(&name, &symbol, &uri)?;
+
+    let config = ProtocolConfig::load_or_default(&ctx.accounts.config)?;
+    let asset_decimals = ctx.accounts.asset_mint.decimals;
+    require!(
+        asset_decimals <= config.max_decimals,
+        VaultError::InvalidAssetDecimals
+    );
+    require!(
+        ctx.accounts.shares_mint.decimals == config.shares_decimals,
+        VaultError::InvalidSharesMint
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+
+    // The auditor key is taken from the mint so vault state cannot disagree with Token-2022
+    let auditor_elgamal_pubkey = {
+        let mint_info = ctx.accounts.shares_mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+        let extension_types = mint
+            .get_extension_types()
+            .map_err(|_| VaultError::InvalidSharesMint)?;
+        require!(
+            extension_types
+                .iter()
+                .all(|extension| ALLOWED_SHARES_MINT_EXTENSIONS.contains(extension)),
+            VaultError::InvalidSharesMint
+        );
+
+        let ct_mint = mint
+            .get_extension::<ConfidentialTransferMint>()
+            .map_err(|_| VaultError::ConfidentialTransferNotInitialized)?;
+        require!(
+            Option::<Pubkey>::from(ct_mint.authority) == Some(vault_key),
+            VaultError::InvalidSharesMint
+        );
+        require!(
+            bool::from(ct_mint.auto_approve_new_accounts),
+            VaultError::InvalidSharesMint
+        );
+
+        Option::<PodElGamalPubkey>::from(ct_mint.auditor_elgamal_pubkey)
+            .map(cast::<PodElGamalPubkey, [u8; 32]>)
+    };
+
+    // Set vault state
+    let vault = &mut ctx.accounts.vault;
+    vault.authority = ctx.accounts.authority.key();
+    vault.asset_mint = ctx.accounts.asset_mint.key();
+    vault.shares_mint = ctx.accounts.shares_mint.key();
+    vault.asset_vault = ctx.accounts.asset_vault.key();
+    vault.total_assets = 0;
+    vault.decimals_offset = config.shares_decimals - asset_decimals;
+    vault.bump = ctx.bumps.vault;
+    vault.paused = false;
+    vault.vault_id = vault_id;
+    vault.auditor_elgamal_pubkey = auditor_elgamal_pubkey;
+    vault.confidential_authority = vault_key;
+    vault.guardian = Pubkey::default();
+    vault.swap_adapter = Pubkey::default();
+    vault.harvester = Pubkey::default();
+    vault.locked_profit = 0;
+    vault.last_harvest_ts = 0;
+    vault.profit_unlock_period = DEFAULT_PROFIT_UNLOCK_PERIOD;
+    vault.event_sequence = 0;
+    vault.min_deposit_amount = config.min_deposit_amount;
+    vault.metadata_hash = metadata_hash(&name, &symbol, &uri);
+    vault.op_counter = 0;
+    vault._reserved = [0u8; 8];
+
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
+    emit!(VaultInitialized {
+        vault: vault.key(),
+        authority: vault.authority,
+        asset_mint: vault.asset_mint,
+        shares_mint: vault.shares_mint,
+        vault_id,
+        // Confidential shares must stay transferable
+        non_transferable: false,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    msg!(
+        "Confidential vault initialized with external shares mint: {} for asset {}",
+        name,
+        symbol
+    );
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{error::VaultError, state::ConfidentialVault};
+
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    pub vault: Account<'info, ConfidentialVault>,
+
+    #[account(constraint = asset_mint.key() == vault.asset_mint)]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(constraint = shares_mint.key() == vault.shares_mint)]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(constraint = asset_vault.key() == vault.asset_vault)]
+    pub asset_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Fail unless the vault's state is internally consistent (testing builds only)
+///
+/// `emergency_redeem` bypasses the cached totals on purpose, so the checks on
+/// them can fail after it until the vault is synced.
+pub fn handler(ctx: Context<AssertInvariants>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let now = Clock::get()?.unix_timestamp;
+
+    check(
+        vault.total_assets <= ctx.accounts.asset_vault.amount,
+        "total assets exceed the asset vault balance",
+    )?;
+    check(
+        ctx.accounts.shares_mint.supply == 0 || vault.total_assets > 0,
+        "shares outstanding without assets",
+    )?;
+    check(
+        vault.locked_profit <= vault.total_assets,
+        "locked profit exceeds the total assets",
+    )?;
+    check(
+        vault.profit_unlock_period >= 0 && vault.last_harvest_ts <= now,
+        "harvest schedule is negative or in the future",
+    )?;
+    check(
+        ctx.accounts
+            .asset_mint
+            .decimals
+            .checked_add(vault.decimals_offset)
+            == Some(ctx.accounts.shares_mint.decimals),
+        "decimals offset does not bridge the asset and shares decimals",
+    )?;
+    check(
+        ctx.accounts.shares_mint.mint_authority == COption::Some(vault.key()),
+        "vault is not the shares mint authority",
+    )?;
+
+    Ok(())
+}
+
+fn check(holds: bool, invariant: &str) -> Result<()> {
+    if !holds {
+        msg!("Invariant violated: {}", invariant);
+        return err!(VaultError::InvariantViolated);
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::{constants::DEFAULT_PROFIT_UNLOCK_PERIOD, error::VaultError, state::ConfidentialVault};
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    /// Pays the rent of the grown account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: A vault in an older, shorter layout, which `Account` cannot
+    /// deserialize; the handler checks its discriminator and size
+    #[account(mut, owner = crate::ID)]
+    pub vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a vault created before the guardian, harvest, event sequence,
+/// minimum deposit, metadata and operation counter fields to the current
+/// layout
+///
+/// Those fields were appended after `confidential_authority`, so older vault
+/// accounts are too short to deserialize and every other instruction fails
+/// on them. The old `_reserved` bytes were zero and read as an unset
+/// guardian, and the new bytes are zeroed, so every new field starts unset
+/// except `profit_unlock_period`, which gets the default new vaults use.
+/// Permissionless: anyone may pay to migrate a vault, and a vault already in
+/// the current layout is rejected.
+pub fn handler(ctx: Context<MigrateVault>) -> Result<()> {
+    let vault = ctx.accounts.vault.to_account_info();
+    {
+        let data = vault.try_borrow_data()?;
+        require!(
+            data.get(..8) == Some(ConfidentialVault::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            data.len() < ConfidentialVault::LEN,
+            VaultError::VaultAlreadyMigrated
+        );
+    }
+
+    let rent = Rent::get()?
+        .minimum_balance(ConfidentialVault::LEN)
+        .saturating_sub(vault.lamports());
+    if rent > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: vault.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    vault.realloc(ConfidentialVault::LEN, true)?;
+
+    let mut state = ConfidentialVault::try_deserialize(&mut &vault.try_borrow_data()?[..])?;
+    state.profit_unlock_period = DEFAULT_PROFIT_UNLOCK_PERIOD;
+    state.record_op()?;
+    state.try_serialize(&mut &mut vault.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
@@ -8,16 +8,18 @@ use anchor_spl::{
 use spl_token_2022::extension::confidential_transfer::instruction::deposit as confidential_deposit;
 
 use crate::{
-    constants::{SHARES_DECIMALS, VAULT_SEED},
+    constants::{CONFIG_SEED, VAULT_SEED},
     error::VaultError,
     events::Deposit as DepositEvent,
     math::{convert_to_assets, Rounding},
-    state::ConfidentialVault,
+    state::{ConfidentialVault, ProtocolConfig},
+    token_extensions::{gross_amount_for_net, require_cpi_guard_disabled},
 };
 
 #[derive(Accounts)]
 pub struct MintShares<'info> {
-    #[account(mut)]
+    /// Only signs ownership-related CPIs; it is never charged rent, so a sponsor
+    /// can be the transaction fee payer for users holding no SOL
     pub user: Signer<'info>,
 
     #[account(
@@ -62,6 +64,11 @@ pub struct MintShares<'info> {
     pub token_2022_program: Program<'info, Token2022>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Mint exact shares, paying required assets (ceiling rounding - protects vault)
@@ -73,7 +80,9 @@ pub struct MintShares<'info> {
 /// NOTE: User's shares account must be configured for confidential transfers
 /// (call configure_account first)
 pub fn handler(ctx: Context<MintShares>, shares: u64, max_assets_in: u64) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     require!(shares > 0, VaultError::ZeroAmount);
+    require_cpi_guard_disabled(&ctx.accounts.user_asset_account.to_account_info())?;
 
     let vault = &ctx.accounts.vault;
     let total_shares = ctx.accounts.shares_mint.supply;
@@ -81,14 +90,17 @@ pub fn handler(ctx: Context<MintShares>, shares: u64, max_assets_in: u64) -> Res
     // Calculate required assets (ceiling rounding - user pays more)
     let assets = convert_to_assets(
         shares,
-        vault.total_assets,
+        vault.unlocked_assets()?,
         total_shares,
         vault.decimals_offset,
         Rounding::Ceiling,
     )?;
 
+    // Gross up for transfer fees so the vault is credited the full amount
+    let gross_assets = gross_amount_for_net(&ctx.accounts.asset_mint.to_account_info(), assets)?;
+
     // Slippage check
-    require!(assets <= max_assets_in, VaultError::SlippageExceeded);
+    require!(gross_assets <= max_assets_in, VaultError::SlippageExceeded);
 
     // Transfer assets from user to vault
     transfer_checked(
@@ -101,7 +113,7 @@ pub fn handler(ctx: Context<MintShares>, shares: u64, max_assets_in: u64) -> Res
                 authority: ctx.accounts.user.to_account_info(),
             },
         ),
-        assets,
+        gross_assets,
         ctx.accounts.asset_mint.decimals,
     )?;
 
@@ -135,7 +147,7 @@ pub fn handler(ctx: Context<MintShares>, shares: u64, max_assets_in: u64) -> Res
         &ctx.accounts.user_shares_account.key(),
         &ctx.accounts.shares_mint.key(),
         shares,
-        SHARES_DECIMALS,
+        ctx.accounts.shares_mint.decimals,
         &ctx.accounts.user.key(),
         &[],
     )?;
@@ -156,12 +168,17 @@ pub fn handler(ctx: Context<MintShares>, shares: u64, max_assets_in: u64) -> Res
         .checked_add(assets)
         .ok_or(VaultError::MathOverflow)?;
 
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
     emit!(DepositEvent {
         vault: ctx.accounts.vault.key(),
         caller: ctx.accounts.user.key(),
         owner: ctx.accounts.user.key(),
         assets,
         shares,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
@@ -1,10 +1,20 @@
 pub mod admin;
 pub mod apply_pending;
+#[path = "../../../../shared/config.rs"]
+pub mod config;
 pub mod configure_account;
 pub mod deposit;
+pub mod deposit_with_permit;
+pub mod emergency_redeem;
+pub mod harvest;
 pub mod initialize;
+pub mod initialize_with_mint;
+#[cfg(feature = "testing")]
+pub mod invariants;
+pub mod migrate;
 pub mod mint;
 pub mod redeem;
+pub mod resume_confidential_deposit;
 pub mod view;
 pub mod withdraw;
 
@@ -13,16 +23,33 @@ pub use admin::*;
 #[allow(ambiguous_glob_reexports)]
 pub use apply_pending::*;
 #[allow(ambiguous_glob_reexports)]
+pub use config::*;
+#[allow(ambiguous_glob_reexports)]
 pub use configure_account::*;
 #[allow(ambiguous_glob_reexports)]
 pub use deposit::*;
 #[allow(ambiguous_glob_reexports)]
+pub use deposit_with_permit::*;
+#[allow(ambiguous_glob_reexports)]
+pub use emergency_redeem::*;
+#[allow(ambiguous_glob_reexports)]
+pub use harvest::*;
+#[allow(ambiguous_glob_reexports)]
 pub use initialize::*;
 #[allow(ambiguous_glob_reexports)]
+pub use initialize_with_mint::*;
+#[cfg(feature = "testing")]
+#[allow(ambiguous_glob_reexports)]
+pub use invariants::*;
+#[allow(ambiguous_glob_reexports)]
+pub use migrate::*;
+#[allow(ambiguous_glob_reexports)]
 pub use mint::*;
 #[allow(ambiguous_glob_reexports)]
 pub use redeem::*;
 #[allow(ambiguous_glob_reexports)]
+pub use resume_confidential_deposit::*;
+#[allow(ambiguous_glob_reexports)]
 pub use view::*;
 #[allow(ambiguous_glob_reexports)]
 pub use withdraw::*;
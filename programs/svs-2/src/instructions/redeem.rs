@@ -1,20 +1,24 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::{compute_units::sol_remaining_compute_units, program::invoke};
 use anchor_spl::{
     token_2022::{self, Burn, Token2022},
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use bytemuck::try_from_bytes;
-use solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext;
+use solana_zk_sdk::{
+    encryption::pod::auth_encryption::PodAeCiphertext,
+    zk_elgamal_proof_program::proof_data::ProofType,
+};
 use spl_token_2022::extension::confidential_transfer::instruction::inner_withdraw;
 use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
 
 use crate::{
-    constants::{SHARES_DECIMALS, VAULT_SEED},
+    constants::{CONFIG_SEED, VAULT_SEED, WITHDRAW_MIN_COMPUTE_UNITS},
     error::VaultError,
     events::Withdraw as WithdrawEvent,
     math::{convert_to_assets, Rounding},
-    state::ConfidentialVault,
+    state::{ConfidentialVault, ProtocolConfig},
+    token_extensions::{require_cpi_guard_disabled, require_proof_context, transfer_fee},
 };
 
 /// Redeem confidential shares for assets
@@ -74,6 +78,11 @@ pub struct Redeem<'info> {
 
     pub asset_token_program: Interface<'info, TokenInterface>,
     pub token_2022_program: Program<'info, Token2022>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Redeem shares for assets (floor rounding - protects vault)
@@ -89,7 +98,23 @@ pub fn handler(
     min_assets_out: u64,
     new_decryptable_available_balance: [u8; 36],
 ) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     require!(shares > 0, VaultError::ZeroAmount);
+    // The confidential withdraw CPI dominates cost; fail with a typed error
+    // before doing any work rather than aborting mid-CPI
+    require!(
+        sol_remaining_compute_units() >= WITHDRAW_MIN_COMPUTE_UNITS,
+        VaultError::InsufficientComputeBudget
+    );
+
+    // Resolve account infos once and reuse them across all three CPIs
+    let user_info = ctx.accounts.user.to_account_info();
+    let user_shares_info = ctx.accounts.user_shares_account.to_account_info();
+    let shares_mint_info = ctx.accounts.shares_mint.to_account_info();
+    let asset_mint_info = ctx.accounts.asset_mint.to_account_info();
+    let token_2022_info = ctx.accounts.token_2022_program.to_account_info();
+
+    require_cpi_guard_disabled(&user_shares_info)?;
 
     let vault = &ctx.accounts.vault;
     let total_shares = ctx.accounts.shares_mint.supply;
@@ -97,18 +122,29 @@ pub fn handler(
     // Calculate assets to receive (floor rounding - user gets less)
     let assets = convert_to_assets(
         shares,
-        vault.total_assets,
+        vault.unlocked_assets()?,
         total_shares,
         vault.decimals_offset,
         Rounding::Floor,
     )?;
 
-    // Slippage check
-    require!(assets >= min_assets_out, VaultError::SlippageExceeded);
-
     // Check vault has enough assets
     require!(assets <= vault.total_assets, VaultError::InsufficientAssets);
 
+    // Slippage check against what the user actually receives after transfer fees
+    let fee = transfer_fee(&asset_mint_info, assets)?;
+    let net_assets = assets.checked_sub(fee).ok_or(VaultError::MathOverflow)?;
+    require!(net_assets >= min_assets_out, VaultError::SlippageExceeded);
+
+    require_proof_context(
+        &ctx.accounts.equality_proof_context,
+        ProofType::CiphertextCommitmentEquality,
+    )?;
+    require_proof_context(
+        &ctx.accounts.range_proof_context,
+        ProofType::BatchedRangeProofU64,
+    )?;
+
     // Convert bytes to PodAeCiphertext (safe conversion)
     let new_decryptable_balance: PodAeCiphertext =
         *try_from_bytes::<PodAeCiphertext>(&new_decryptable_available_balance)
@@ -116,13 +152,13 @@ pub fn handler(
 
     // Step 1: Withdraw from confidential to non-confidential balance
     let withdraw_ix = inner_withdraw(
-        &ctx.accounts.token_2022_program.key(),
-        &ctx.accounts.user_shares_account.key(),
-        &ctx.accounts.shares_mint.key(),
+        token_2022_info.key,
+        user_shares_info.key,
+        shares_mint_info.key,
         shares,
-        SHARES_DECIMALS,
+        ctx.accounts.shares_mint.decimals,
         new_decryptable_balance,
-        &ctx.accounts.user.key(),
+        user_info.key,
         &[],
         ProofLocation::ContextStateAccount(ctx.accounts.equality_proof_context.key),
         ProofLocation::ContextStateAccount(ctx.accounts.range_proof_context.key),
@@ -131,31 +167,31 @@ pub fn handler(
     invoke(
         &withdraw_ix,
         &[
-            ctx.accounts.user_shares_account.to_account_info(),
-            ctx.accounts.shares_mint.to_account_info(),
+            user_shares_info.clone(),
+            shares_mint_info.clone(),
             ctx.accounts.equality_proof_context.to_account_info(),
             ctx.accounts.range_proof_context.to_account_info(),
-            ctx.accounts.user.to_account_info(),
+            user_info.clone(),
         ],
     )?;
 
     // Step 2: Burn shares from user's non-confidential balance
     token_2022::burn(
         CpiContext::new(
-            ctx.accounts.token_2022_program.to_account_info(),
+            token_2022_info,
             Burn {
-                mint: ctx.accounts.shares_mint.to_account_info(),
-                from: ctx.accounts.user_shares_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
+                mint: shares_mint_info,
+                from: user_shares_info,
+                authority: user_info,
             },
         ),
         shares,
     )?;
 
     // Step 3: Transfer assets from vault to user
-    let asset_mint_key = ctx.accounts.vault.asset_mint;
-    let vault_id_bytes = ctx.accounts.vault.vault_id.to_le_bytes();
-    let bump = ctx.accounts.vault.bump;
+    let asset_mint_key = vault.asset_mint;
+    let vault_id_bytes = vault.vault_id.to_le_bytes();
+    let bump = vault.bump;
     let signer_seeds: &[&[&[u8]]] = &[&[
         VAULT_SEED,
         asset_mint_key.as_ref(),
@@ -169,7 +205,7 @@ pub fn handler(
             TransferChecked {
                 from: ctx.accounts.asset_vault.to_account_info(),
                 to: ctx.accounts.user_asset_account.to_account_info(),
-                mint: ctx.accounts.asset_mint.to_account_info(),
+                mint: asset_mint_info,
                 authority: ctx.accounts.vault.to_account_info(),
             },
             signer_seeds,
@@ -185,13 +221,18 @@ pub fn handler(
         .checked_sub(assets)
         .ok_or(VaultError::MathOverflow)?;
 
+    vault.record_op()?;
+    let (op_counter, sequence, timestamp) = vault.stamp_event()?;
     emit!(WithdrawEvent {
-        vault: ctx.accounts.vault.key(),
+        vault: vault.key(),
         caller: ctx.accounts.user.key(),
         receiver: ctx.accounts.user.key(),
         owner: ctx.accounts.user.key(),
         assets,
         shares,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
     Ok(())
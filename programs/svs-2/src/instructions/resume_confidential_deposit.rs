@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use spl_token_2022::extension::confidential_transfer::instruction::deposit as confidential_deposit;
+
+use crate::{
+    constants::CONFIG_SEED,
+    error::VaultError,
+    events::ConfidentialDepositResumed,
+    state::{ConfidentialVault, ProtocolConfig},
+};
+
+#[derive(Accounts)]
+pub struct ResumeConfidentialDeposit<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, ConfidentialVault>,
+
+    #[account(
+        constraint = shares_mint.key() == vault.shares_mint,
+    )]
+    pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    /// The user's shares account (must already be configured for confidential transfers)
+    #[account(
+        mut,
+        constraint = user_shares_account.mint == vault.shares_mint,
+        constraint = user_shares_account.owner == user.key(),
+    )]
+    pub user_shares_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+/// Move `shares` stranded in the user's non-confidential balance into the
+/// confidential pending balance
+///
+/// Shares end up there when the confidential half of a deposit did not run,
+/// e.g. `deposit_with_permit` mints to the public balance because only the
+/// owner can sign a confidential deposit. Works while the vault is paused,
+/// since no assets or shares are created or destroyed, but not while the
+/// protocol is paused. User must call apply_pending after this to use the
+/// shares.
+pub fn handler(ctx: Context<ResumeConfidentialDeposit>, shares: u64) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
+    require!(shares > 0, VaultError::ZeroAmount);
+    require!(
+        shares <= ctx.accounts.user_shares_account.amount,
+        VaultError::InsufficientShares
+    );
+
+    let deposit_ix = confidential_deposit(
+        &ctx.accounts.token_2022_program.key(),
+        &ctx.accounts.user_shares_account.key(),
+        &ctx.accounts.shares_mint.key(),
+        shares,
+        ctx.accounts.shares_mint.decimals,
+        &ctx.accounts.user.key(),
+        &[],
+    )?;
+
+    invoke(
+        &deposit_ix,
+        &[
+            ctx.accounts.user_shares_account.to_account_info(),
+            ctx.accounts.shares_mint.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+        ],
+    )?;
+
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
+    emit!(ConfidentialDepositResumed {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.user.key(),
+        shares,
+        op_counter,
+        sequence,
+        timestamp,
+    });
+
+    Ok(())
+}
@@ -3,8 +3,11 @@ use anchor_lang::solana_program::program::set_return_data;
 use anchor_spl::token_interface::{Mint, TokenAccount};
 
 use crate::{
-    math::{convert_to_assets, convert_to_shares, Rounding},
-    state::ConfidentialVault,
+    constants::CONFIG_SEED,
+    error::VaultError,
+    math::{convert_to_assets, convert_to_shares, share_price, Rounding, SHARE_PRICE_SCALE},
+    state::{ConfidentialVault, ProtocolConfig},
+    token_extensions::{gross_amount_for_net, transfer_fee},
 };
 
 #[derive(Accounts)]
@@ -13,6 +16,15 @@ pub struct VaultView<'info> {
 
     #[account(constraint = shares_mint.key() == vault.shares_mint)]
     pub shares_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// Read for its transfer fee, which the previews include
+    #[account(constraint = asset_mint.key() == vault.asset_mint)]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
 }
 
 #[derive(Accounts)]
@@ -26,119 +38,180 @@ pub struct VaultViewWithOwner<'info> {
         constraint = owner_shares_account.mint == vault.shares_mint,
     )]
     pub owner_shares_account: InterfaceAccount<'info, TokenAccount>,
-}
 
-/// Preview how many shares would be minted for given assets (floor rounding)
-pub fn preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::load_or_default`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// Read for its transfer fee, which `max_withdraw` includes
+    #[account(constraint = asset_mint.key() == vault.asset_mint)]
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+}
 
-    let shares = convert_to_shares(
+/// `assets` in shares at the vault's current price
+fn shares_for(accounts: &VaultView, assets: u64, rounding: Rounding) -> Result<u64> {
+    let vault = &accounts.vault;
+    convert_to_shares(
         assets,
-        vault.total_assets,
-        total_shares,
+        vault.unlocked_assets()?,
+        accounts.shares_mint.supply,
         vault.decimals_offset,
-        Rounding::Floor,
-    )?;
+        rounding,
+    )
+}
+
+/// `shares` in assets at the vault's current price
+fn assets_for(accounts: &VaultView, shares: u64, rounding: Rounding) -> Result<u64> {
+    let vault = &accounts.vault;
+    convert_to_assets(
+        shares,
+        vault.unlocked_assets()?,
+        accounts.shares_mint.supply,
+        vault.decimals_offset,
+        rounding,
+    )
+}
+
+/// Whether the vault or the whole protocol is paused, so deposits and
+/// withdrawals fail
+fn paused(vault: &ConfidentialVault, config: &AccountInfo) -> Result<bool> {
+    Ok(vault.paused || ProtocolConfig::load_or_default(config)?.paused)
+}
+
+/// `value`, or `u64::MAX` if computing it overflowed
+fn saturate(value: Result<u64>) -> Result<u64> {
+    match value {
+        Err(error) if error == VaultError::MathOverflow.into() => Ok(u64::MAX),
+        value => value,
+    }
+}
+
+// The previews mirror their instructions, transfer fees included: `assets`
+// is what the user sends (deposit, mint) or receives (withdraw, redeem).
+
+/// Shares `deposit` mints for `assets`, after the fee withheld on the way in
+fn deposit_shares(accounts: &VaultView, assets: u64) -> Result<u64> {
+    let fee = transfer_fee(&accounts.asset_mint.to_account_info(), assets)?;
+    let net_assets = assets.checked_sub(fee).ok_or(VaultError::MathOverflow)?;
+    shares_for(accounts, net_assets, Rounding::Floor)
+}
+
+/// Assets `mint` takes for `shares`, grossed up so the vault is credited in full
+fn mint_assets(accounts: &VaultView, shares: u64) -> Result<u64> {
+    let assets = assets_for(accounts, shares, Rounding::Ceiling)?;
+    gross_amount_for_net(&accounts.asset_mint.to_account_info(), assets)
+}
 
+/// Shares `withdraw` burns so the user receives exactly `assets`
+fn withdraw_shares(accounts: &VaultView, assets: u64) -> Result<u64> {
+    let gross_assets = gross_amount_for_net(&accounts.asset_mint.to_account_info(), assets)?;
+    shares_for(accounts, gross_assets, Rounding::Ceiling)
+}
+
+/// Assets the user receives from `redeem` for `shares`, after the fee
+fn redeem_assets(accounts: &VaultView, shares: u64) -> Result<u64> {
+    let assets = assets_for(accounts, shares, Rounding::Floor)?;
+    let fee = transfer_fee(&accounts.asset_mint.to_account_info(), assets)?;
+    Ok(assets.checked_sub(fee).ok_or(VaultError::MathOverflow)?)
+}
+
+/// Preview how many shares would be minted for given assets (floor rounding)
+pub fn preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = deposit_shares(ctx.accounts, assets)?;
     set_return_data(&shares.to_le_bytes());
     Ok(())
 }
 
 /// Preview how many assets are required to mint exact shares (ceiling rounding)
 pub fn preview_mint(ctx: Context<VaultView>, shares: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
-
-    let assets = convert_to_assets(
-        shares,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Ceiling,
-    )?;
-
+    let assets = mint_assets(ctx.accounts, shares)?;
     set_return_data(&assets.to_le_bytes());
     Ok(())
 }
 
 /// Preview how many shares must be burned to withdraw exact assets (ceiling rounding)
 pub fn preview_withdraw(ctx: Context<VaultView>, assets: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
-
-    let shares = convert_to_shares(
-        assets,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Ceiling,
-    )?;
-
+    let shares = withdraw_shares(ctx.accounts, assets)?;
     set_return_data(&shares.to_le_bytes());
     Ok(())
 }
 
 /// Preview how many assets would be received for redeeming shares (floor rounding)
 pub fn preview_redeem(ctx: Context<VaultView>, shares: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
+    let assets = redeem_assets(ctx.accounts, shares)?;
+    set_return_data(&assets.to_le_bytes());
+    Ok(())
+}
 
-    let assets = convert_to_assets(
-        shares,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Floor,
-    )?;
+/// [`preview_deposit`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = saturate(deposit_shares(ctx.accounts, assets))?;
+    set_return_data(&shares.to_le_bytes());
+    Ok(())
+}
 
+/// [`preview_mint`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_mint(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+    let assets = saturate(mint_assets(ctx.accounts, shares))?;
     set_return_data(&assets.to_le_bytes());
     Ok(())
 }
 
-/// Convert assets to shares using floor rounding
-pub fn convert_to_shares_view(ctx: Context<VaultView>, assets: u64) -> Result<()> {
-    let vault = &ctx.accounts.vault;
-    let total_shares = ctx.accounts.shares_mint.supply;
+/// [`preview_withdraw`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_withdraw(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = saturate(withdraw_shares(ctx.accounts, assets))?;
+    set_return_data(&shares.to_le_bytes());
+    Ok(())
+}
 
-    let shares = convert_to_shares(
-        assets,
-        vault.total_assets,
-        total_shares,
-        vault.decimals_offset,
-        Rounding::Floor,
-    )?;
+/// [`preview_redeem`], returning `u64::MAX` instead of failing on overflow
+pub fn saturating_preview_redeem(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+    let assets = saturate(redeem_assets(ctx.accounts, shares))?;
+    set_return_data(&assets.to_le_bytes());
+    Ok(())
+}
 
+/// Convert assets to shares using floor rounding, ignoring transfer fees
+pub fn convert_to_shares_view(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+    let shares = shares_for(ctx.accounts, assets, Rounding::Floor)?;
     set_return_data(&shares.to_le_bytes());
     Ok(())
 }
 
-/// Convert shares to assets using floor rounding
+/// Convert shares to assets using floor rounding, ignoring transfer fees
 pub fn convert_to_assets_view(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+    let assets = assets_for(ctx.accounts, shares, Rounding::Floor)?;
+    set_return_data(&assets.to_le_bytes());
+    Ok(())
+}
+
+/// Price of one whole share in whole assets, scaled by `SHARE_PRICE_SCALE` (floor rounding)
+pub fn share_price_view(ctx: Context<VaultView>) -> Result<()> {
     let vault = &ctx.accounts.vault;
     let total_shares = ctx.accounts.shares_mint.supply;
 
-    let assets = convert_to_assets(
-        shares,
-        vault.total_assets,
+    let price = share_price(
+        vault.unlocked_assets()?,
         total_shares,
         vault.decimals_offset,
+        SHARE_PRICE_SCALE,
         Rounding::Floor,
     )?;
 
-    set_return_data(&assets.to_le_bytes());
+    set_return_data(&price.to_le_bytes());
     Ok(())
 }
 
-/// Get total assets managed by the vault
+/// Get total assets managed by the vault, excluding harvested profit that is still locked
 pub fn get_total_assets(ctx: Context<VaultView>) -> Result<()> {
-    set_return_data(&ctx.accounts.vault.total_assets.to_le_bytes());
+    set_return_data(&ctx.accounts.vault.unlocked_assets()?.to_le_bytes());
     Ok(())
 }
 
-/// Maximum assets that can be deposited (u64::MAX if not paused, 0 if paused)
+/// Maximum assets that can be deposited (u64::MAX, or 0 if the vault or protocol is paused)
 pub fn max_deposit(ctx: Context<VaultView>) -> Result<()> {
-    let max = if ctx.accounts.vault.paused {
+    let max = if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         0u64
     } else {
         u64::MAX
@@ -147,9 +220,9 @@ pub fn max_deposit(ctx: Context<VaultView>) -> Result<()> {
     Ok(())
 }
 
-/// Maximum shares that can be minted (u64::MAX if not paused, 0 if paused)
+/// Maximum shares that can be minted (u64::MAX, or 0 if the vault or protocol is paused)
 pub fn max_mint(ctx: Context<VaultView>) -> Result<()> {
-    let max = if ctx.accounts.vault.paused {
+    let max = if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         0u64
     } else {
         u64::MAX
@@ -158,9 +231,9 @@ pub fn max_mint(ctx: Context<VaultView>) -> Result<()> {
     Ok(())
 }
 
-/// Maximum assets that owner can withdraw (limited by their shares)
+/// Maximum assets that owner can withdraw (limited by their shares), net of the transfer fee
 pub fn max_withdraw(ctx: Context<VaultViewWithOwner>) -> Result<()> {
-    if ctx.accounts.vault.paused {
+    if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         set_return_data(&0u64.to_le_bytes());
         return Ok(());
     }
@@ -172,21 +245,23 @@ pub fn max_withdraw(ctx: Context<VaultViewWithOwner>) -> Result<()> {
     // Calculate max assets owner can receive for their shares
     let max_assets = convert_to_assets(
         owner_shares,
-        vault.total_assets,
+        vault.unlocked_assets()?,
         total_shares,
         vault.decimals_offset,
         Rounding::Floor,
     )?;
 
-    // Cap at vault's total assets
-    let max = max_assets.min(vault.total_assets);
+    // Cap at vault's unlocked assets, then deduct the fee withheld on the way out
+    let max = max_assets.min(vault.unlocked_assets()?);
+    let fee = transfer_fee(&ctx.accounts.asset_mint.to_account_info(), max)?;
+    let max = max.checked_sub(fee).ok_or(VaultError::MathOverflow)?;
     set_return_data(&max.to_le_bytes());
     Ok(())
 }
 
 /// Maximum shares that owner can redeem (their share balance)
 pub fn max_redeem(ctx: Context<VaultViewWithOwner>) -> Result<()> {
-    let max = if ctx.accounts.vault.paused {
+    let max = if paused(&ctx.accounts.vault, &ctx.accounts.config)? {
         0u64
     } else {
         ctx.accounts.owner_shares_account.amount
@@ -1,20 +1,24 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::{compute_units::sol_remaining_compute_units, program::invoke};
 use anchor_spl::{
     token_2022::{self, Burn, Token2022},
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use bytemuck::try_from_bytes;
-use solana_zk_sdk::encryption::pod::auth_encryption::PodAeCiphertext;
+use solana_zk_sdk::{
+    encryption::pod::auth_encryption::PodAeCiphertext,
+    zk_elgamal_proof_program::proof_data::ProofType,
+};
 use spl_token_2022::extension::confidential_transfer::instruction::inner_withdraw;
 use spl_token_confidential_transfer_proof_extraction::instruction::ProofLocation;
 
 use crate::{
-    constants::{SHARES_DECIMALS, VAULT_SEED},
+    constants::{CONFIG_SEED, VAULT_SEED, WITHDRAW_MIN_COMPUTE_UNITS},
     error::VaultError,
-    events::Withdraw as WithdrawEvent,
+    events::{ComplianceMemo, ComplianceOperation, Withdraw as WithdrawEvent},
     math::{convert_to_shares, Rounding},
-    state::ConfidentialVault,
+    state::{ConfidentialVault, ProtocolConfig},
+    token_extensions::{gross_amount_for_net, require_cpi_guard_disabled, require_proof_context},
 };
 
 /// Withdraw exact assets by burning confidential shares
@@ -74,6 +78,11 @@ pub struct Withdraw<'info> {
 
     pub asset_token_program: Interface<'info, TokenInterface>,
     pub token_2022_program: Program<'info, Token2022>,
+
+    /// CHECK: Protocol config PDA, which may not exist yet; read by
+    /// `ProtocolConfig::require_not_paused`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
 }
 
 /// Withdraw exact assets, burning required confidential shares (ceiling rounding - protects vault)
@@ -88,10 +97,21 @@ pub fn handler(
     assets: u64,
     max_shares_in: u64,
     new_decryptable_available_balance: [u8; 36],
+    memo_hash: Option<[u8; 32]>,
 ) -> Result<()> {
+    ProtocolConfig::require_not_paused(&ctx.accounts.config)?;
     require!(assets > 0, VaultError::ZeroAmount);
+    // Same CPIs as redeem: fail with a typed error rather than mid-CPI
+    require!(
+        sol_remaining_compute_units() >= WITHDRAW_MIN_COMPUTE_UNITS,
+        VaultError::InsufficientComputeBudget
+    );
+    require_cpi_guard_disabled(&ctx.accounts.user_shares_account.to_account_info())?;
+
+    // Gross up for transfer fees so the user receives exactly `assets`
+    let gross_assets = gross_amount_for_net(&ctx.accounts.asset_mint.to_account_info(), assets)?;
     require!(
-        assets <= ctx.accounts.vault.total_assets,
+        gross_assets <= ctx.accounts.vault.total_assets,
         VaultError::InsufficientAssets
     );
 
@@ -100,8 +120,8 @@ pub fn handler(
 
     // Calculate shares to burn (ceiling rounding - user burns more)
     let shares = convert_to_shares(
-        assets,
-        vault.total_assets,
+        gross_assets,
+        vault.unlocked_assets()?,
         total_shares,
         vault.decimals_offset,
         Rounding::Ceiling,
@@ -110,6 +130,15 @@ pub fn handler(
     // Slippage check
     require!(shares <= max_shares_in, VaultError::SlippageExceeded);
 
+    require_proof_context(
+        &ctx.accounts.equality_proof_context,
+        ProofType::CiphertextCommitmentEquality,
+    )?;
+    require_proof_context(
+        &ctx.accounts.range_proof_context,
+        ProofType::BatchedRangeProofU64,
+    )?;
+
     // Convert bytes to PodAeCiphertext (safe conversion)
     let new_decryptable_balance: PodAeCiphertext =
         *try_from_bytes::<PodAeCiphertext>(&new_decryptable_available_balance)
@@ -121,7 +150,7 @@ pub fn handler(
         &ctx.accounts.user_shares_account.key(),
         &ctx.accounts.shares_mint.key(),
         shares,
-        SHARES_DECIMALS,
+        ctx.accounts.shares_mint.decimals,
         new_decryptable_balance,
         &ctx.accounts.user.key(),
         &[],
@@ -175,7 +204,7 @@ pub fn handler(
             },
             signer_seeds,
         ),
-        assets,
+        gross_assets,
         ctx.accounts.asset_mint.decimals,
     )?;
 
@@ -183,17 +212,35 @@ pub fn handler(
     let vault = &mut ctx.accounts.vault;
     vault.total_assets = vault
         .total_assets
-        .checked_sub(assets)
+        .checked_sub(gross_assets)
         .ok_or(VaultError::MathOverflow)?;
 
+    ctx.accounts.vault.record_op()?;
+    let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
     emit!(WithdrawEvent {
         vault: ctx.accounts.vault.key(),
         caller: ctx.accounts.user.key(),
         receiver: ctx.accounts.user.key(),
         owner: ctx.accounts.user.key(),
-        assets,
+        assets: gross_assets,
         shares,
+        op_counter,
+        sequence,
+        timestamp,
     });
 
+    if let Some(memo_hash) = memo_hash {
+        let (op_counter, sequence, timestamp) = ctx.accounts.vault.stamp_event()?;
+        emit!(ComplianceMemo {
+            vault: ctx.accounts.vault.key(),
+            owner: ctx.accounts.user.key(),
+            operation: ComplianceOperation::Withdraw,
+            memo_hash,
+            op_counter,
+            sequence,
+            timestamp,
+        });
+    }
+
     Ok(())
 }
@@ -5,10 +5,17 @@ pub mod error;
 pub mod events;
 pub mod instructions;
 pub mod math;
+#[path = "../../../shared/metadata.rs"]
+pub mod metadata;
+pub mod permit;
 pub mod state;
+pub mod token_extensions;
 
 use instructions::*;
 
+/// This program, as named by the modules in `shared/`
+pub(crate) use program::Svs2 as VaultProgram;
+
 declare_id!("3UrYrxh1HmVgq7WPygZ5x1gNEaWFwqTMs7geNqMnsrtD");
 
 #[program]
@@ -28,6 +35,19 @@ pub mod svs_2 {
         instructions::initialize::handler(ctx, vault_id, name, symbol, uri, auditor_elgamal_pubkey)
     }
 
+    /// Initialize a confidential vault around a pre-created Token-2022 shares mint
+    /// The mint must already have ConfidentialTransferMint configured with the vault PDA
+    /// as authority, and the vault PDA as mint authority
+    pub fn initialize_with_shares_mint(
+        ctx: Context<InitializeWithMint>,
+        vault_id: u64,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        instructions::initialize_with_mint::handler(ctx, vault_id, name, symbol, uri)
+    }
+
     /// Configure user's shares account for confidential transfers
     /// Must be called before first deposit
     /// Requires PubkeyValidityProof to be submitted in same transaction (or pre-verified context)
@@ -49,8 +69,26 @@ pub mod svs_2 {
 
     /// Deposit assets and receive confidential shares
     /// Shares go to pending balance (must call apply_pending to use)
-    pub fn deposit(ctx: Context<Deposit>, assets: u64, min_shares_out: u64) -> Result<()> {
-        instructions::deposit::handler(ctx, assets, min_shares_out)
+    /// Optional `memo_hash` links the deposit to an off-chain compliance record
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        assets: u64,
+        min_shares_out: u64,
+        memo_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::deposit::handler(ctx, assets, min_shares_out, memo_hash)
+    }
+
+    /// Relayed deposit authorized by the owner's off-chain Ed25519 signature
+    /// Relayer pays fees; shares go to the owner's non-confidential balance
+    pub fn deposit_with_permit(
+        ctx: Context<DepositWithPermit>,
+        assets: u64,
+        min_shares_out: u64,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::deposit_with_permit::handler(ctx, assets, min_shares_out, nonce, expires_at)
     }
 
     /// Mint exact confidential shares by depositing required assets
@@ -76,6 +114,20 @@ pub mod svs_2 {
         )
     }
 
+    /// Move shares left in the public balance into the confidential pending
+    /// balance, e.g. after `deposit_with_permit`
+    /// Works while the vault (not the protocol) is paused; apply_pending makes
+    /// the shares usable
+    ///
+    /// # Arguments
+    /// * `shares` - Public-balance shares to deposit
+    pub fn resume_confidential_deposit(
+        ctx: Context<ResumeConfidentialDeposit>,
+        shares: u64,
+    ) -> Result<()> {
+        instructions::resume_confidential_deposit::handler(ctx, shares)
+    }
+
     /// Withdraw exact assets by burning confidential shares
     /// Requires pre-verified range proof and ciphertext equality proof context accounts
     ///
@@ -83,17 +135,20 @@ pub mod svs_2 {
     /// * `assets` - Exact amount of assets to withdraw
     /// * `max_shares_in` - Maximum shares willing to burn (slippage protection)
     /// * `new_decryptable_available_balance` - AE ciphertext of balance after withdrawal
+    /// * `memo_hash` - Optional hash of an off-chain compliance record
     pub fn withdraw(
         ctx: Context<Withdraw>,
         assets: u64,
         max_shares_in: u64,
         new_decryptable_available_balance: [u8; 36],
+        memo_hash: Option<[u8; 32]>,
     ) -> Result<()> {
         instructions::withdraw::handler(
             ctx,
             assets,
             max_shares_in,
             new_decryptable_available_balance,
+            memo_hash,
         )
     }
 
@@ -118,8 +173,25 @@ pub mod svs_2 {
         )
     }
 
+    /// Emergency pro-rata exit while the vault is paused
+    /// Burns public-balance shares against the actual asset vault balance; users
+    /// first move confidential shares public with a Token-2022 confidential withdraw
+    pub fn emergency_redeem(ctx: Context<EmergencyRedeem>, shares: u64) -> Result<()> {
+        instructions::emergency_redeem::handler(ctx, shares)
+    }
+
+    /// Harvest rewards from a harvest-authority-owned account into total_assets
+    /// Non-asset rewards are swapped via the whitelisted adapter; profit unlocks linearly
+    pub fn harvest<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Harvest<'info>>,
+        min_assets_out: u64,
+    ) -> Result<()> {
+        instructions::harvest::handler(ctx, min_assets_out)
+    }
+
     /// Pause all vault operations (emergency)
-    pub fn pause(ctx: Context<Admin>) -> Result<()> {
+    /// Callable by the authority or the guardian
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
         instructions::admin::pause(ctx)
     }
 
@@ -133,11 +205,87 @@ pub mod svs_2 {
         instructions::admin::transfer_authority(ctx, new_authority)
     }
 
+    /// Set the pause-only guardian (Pubkey::default() to remove)
+    pub fn set_guardian(ctx: Context<Admin>, new_guardian: Pubkey) -> Result<()> {
+        instructions::admin::set_guardian(ctx, new_guardian)
+    }
+
+    /// Set swap adapter, harvester and profit unlock period
+    pub fn set_harvest_config(
+        ctx: Context<Admin>,
+        swap_adapter: Pubkey,
+        harvester: Pubkey,
+        profit_unlock_period: i64,
+    ) -> Result<()> {
+        instructions::admin::set_harvest_config(ctx, swap_adapter, harvester, profit_unlock_period)
+    }
+
     /// Sync total_assets with actual vault balance
     pub fn sync(ctx: Context<Sync>) -> Result<()> {
         instructions::admin::sync(ctx)
     }
 
+    /// Override the vault's minimum deposit (at least the protocol minimum)
+    pub fn set_min_deposit(ctx: Context<SetMinDeposit>, min_deposit_amount: u64) -> Result<()> {
+        instructions::admin::set_min_deposit(ctx, min_deposit_amount)
+    }
+
+    /// Grow a vault created with an older account layout to the current one
+    /// Permissionless; the payer funds the extra rent
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        instructions::migrate::handler(ctx)
+    }
+
+    // ============ Protocol Config ============
+
+    /// Create the protocol config (program upgrade authority only)
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        min_deposit_amount: u64,
+        max_decimals: u8,
+        shares_decimals: u8,
+    ) -> Result<()> {
+        instructions::config::initialize_config(
+            ctx,
+            min_deposit_amount,
+            max_decimals,
+            shares_decimals,
+        )
+    }
+
+    /// Update the parameters of new vaults
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        min_deposit_amount: u64,
+        max_decimals: u8,
+        shares_decimals: u8,
+    ) -> Result<()> {
+        instructions::config::update_config(ctx, min_deposit_amount, max_decimals, shares_decimals)
+    }
+
+    /// Transfer the protocol config authority
+    pub fn transfer_config_authority(
+        ctx: Context<UpdateConfig>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::config::transfer_config_authority(ctx, new_authority)
+    }
+
+    /// Set the protocol guardian (Pubkey::default() to remove)
+    pub fn set_protocol_guardian(ctx: Context<UpdateConfig>, new_guardian: Pubkey) -> Result<()> {
+        instructions::config::set_protocol_guardian(ctx, new_guardian)
+    }
+
+    /// Pause every vault of the program (config authority or guardian)
+    pub fn pause_protocol(ctx: Context<ProtocolGuardian>) -> Result<()> {
+        instructions::config::pause_protocol(ctx)
+    }
+
+    /// Resume every vault of the program (config authority or guardian)
+    pub fn unpause_protocol(ctx: Context<ProtocolGuardian>) -> Result<()> {
+        instructions::config::unpause_protocol(ctx)
+    }
+
     // ============ View Functions (CPI composable) ============
 
     /// Preview shares for deposit (floor rounding)
@@ -160,6 +308,26 @@ pub mod svs_2 {
         instructions::view::preview_redeem(ctx, shares)
     }
 
+    /// Preview shares for deposit, u64::MAX instead of an overflow error
+    pub fn saturating_preview_deposit(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+        instructions::view::saturating_preview_deposit(ctx, assets)
+    }
+
+    /// Preview assets required for mint, u64::MAX instead of an overflow error
+    pub fn saturating_preview_mint(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+        instructions::view::saturating_preview_mint(ctx, shares)
+    }
+
+    /// Preview shares to burn for withdraw, u64::MAX instead of an overflow error
+    pub fn saturating_preview_withdraw(ctx: Context<VaultView>, assets: u64) -> Result<()> {
+        instructions::view::saturating_preview_withdraw(ctx, assets)
+    }
+
+    /// Preview assets for redeem, u64::MAX instead of an overflow error
+    pub fn saturating_preview_redeem(ctx: Context<VaultView>, shares: u64) -> Result<()> {
+        instructions::view::saturating_preview_redeem(ctx, shares)
+    }
+
     /// Convert assets to shares (floor rounding)
     pub fn convert_to_shares(ctx: Context<VaultView>, assets: u64) -> Result<()> {
         instructions::view::convert_to_shares_view(ctx, assets)
@@ -170,6 +338,11 @@ pub mod svs_2 {
         instructions::view::convert_to_assets_view(ctx, shares)
     }
 
+    /// Price of one share in assets, 1e12 fixed point (floor rounding)
+    pub fn share_price(ctx: Context<VaultView>) -> Result<()> {
+        instructions::view::share_price_view(ctx)
+    }
+
     /// Get total assets in vault
     pub fn total_assets(ctx: Context<VaultView>) -> Result<()> {
         instructions::view::get_total_assets(ctx)
@@ -194,4 +367,12 @@ pub mod svs_2 {
     pub fn max_redeem(ctx: Context<VaultViewWithOwner>) -> Result<()> {
         instructions::view::max_redeem(ctx)
     }
+
+    // ============ Testing ============
+
+    /// Fail unless the vault's state is internally consistent
+    #[cfg(feature = "testing")]
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        instructions::invariants::handler(ctx)
+    }
 }
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::{constants::DEPOSIT_PERMIT_DOMAIN, error::VaultError};
+
+/// Size of the Ed25519 program's per-signature offsets block
+const ED25519_OFFSETS_LEN: usize = 14;
+/// Offsets block starts after `num_signatures: u8` and one padding byte
+const ED25519_OFFSETS_START: usize = 2;
+/// Instruction index meaning "data lives in the Ed25519 instruction itself"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Canonical bytes the depositor signs to authorize a relayed deposit
+///
+/// Layout: domain || vault || owner || assets || min_shares_out || nonce || expires_at
+/// (integers little-endian). Clients must produce exactly these bytes.
+pub fn deposit_permit_message(
+    vault: &Pubkey,
+    owner: &Pubkey,
+    assets: u64,
+    min_shares_out: u64,
+    nonce: u64,
+    expires_at: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(DEPOSIT_PERMIT_DOMAIN.len() + 32 + 32 + 8 * 4);
+    message.extend_from_slice(DEPOSIT_PERMIT_DOMAIN);
+    message.extend_from_slice(vault.as_ref());
+    message.extend_from_slice(owner.as_ref());
+    message.extend_from_slice(&assets.to_le_bytes());
+    message.extend_from_slice(&min_shares_out.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Require that the instruction immediately before this one is an Ed25519
+/// signature verification of exactly `message` by `signer`.
+///
+/// The Ed25519 program fails the whole transaction on a bad signature, so only
+/// the pubkey and message need to be matched here.
+pub fn require_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, VaultError::InvalidPermit);
+
+    let ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        VaultError::InvalidPermit
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_OFFSETS_START + ED25519_OFFSETS_LEN && data[0] == 1,
+        VaultError::InvalidPermit
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_OFFSETS_START;
+    let signature_ix = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_len = read_u16(offsets + 10) as usize;
+    let message_ix = read_u16(offsets + 12);
+
+    // All verified data must come from the Ed25519 instruction we just inspected
+    require!(
+        signature_ix == CURRENT_INSTRUCTION
+            && pubkey_ix == CURRENT_INSTRUCTION
+            && message_ix == CURRENT_INSTRUCTION,
+        VaultError::InvalidPermit
+    );
+
+    let signed_pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(VaultError::InvalidPermit)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(VaultError::InvalidPermit)?;
+
+    require!(
+        signed_pubkey == signer.as_ref() && signed_message == message,
+        VaultError::InvalidPermit
+    );
+
+    Ok(())
+}
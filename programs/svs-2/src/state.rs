@@ -1,6 +1,14 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
 
-use crate::constants::VAULT_SEED;
+use crate::{
+    constants::{MIN_DEPOSIT_AMOUNT, PERMIT_SEED, VAULT_SEED},
+    error::VaultError,
+};
+
+#[path = "../../../shared/protocol_config.rs"]
+mod protocol_config;
+pub use protocol_config::ProtocolConfig;
 
 #[account]
 pub struct ConfidentialVault {
@@ -26,8 +34,30 @@ pub struct ConfidentialVault {
     pub auditor_elgamal_pubkey: Option<[u8; 32]>,
     /// Authority for confidential transfer operations
     pub confidential_authority: Pubkey,
+    /// Pause-only key for automated circuit breakers (Pubkey::default() if unset)
+    pub guardian: Pubkey,
+    /// Whitelisted swap adapter program used by harvest (Pubkey::default() if unset)
+    pub swap_adapter: Pubkey,
+    /// Key allowed to call harvest besides the authority (Pubkey::default() if unset)
+    pub harvester: Pubkey,
+    /// Harvested profit still being streamed into share price
+    pub locked_profit: u64,
+    /// Unix timestamp of the last harvest
+    pub last_harvest_ts: i64,
+    /// Seconds over which harvested profit unlocks linearly
+    pub profit_unlock_period: i64,
+    /// Sequence number of the last emitted event (0 before the first)
+    pub event_sequence: u64,
+    /// Smallest accepted deposit; 0 for vaults created before it was stored,
+    /// which use `MIN_DEPOSIT_AMOUNT`
+    pub min_deposit_amount: u64,
+    /// `metadata::metadata_hash` of the name, symbol and uri given at initialize
+    pub metadata_hash: [u8; 32],
+    /// Number of mutating instructions run on the vault, initialize included.
+    /// Counts instructions where `event_sequence` counts events.
+    pub op_counter: u64,
     /// Reserved for future upgrades
-    pub _reserved: [u8; 32],
+    pub _reserved: [u8; 8],
 }
 
 impl ConfidentialVault {
@@ -43,7 +73,96 @@ impl ConfidentialVault {
         8 +   // vault_id
         1 + 32 + // auditor_elgamal_pubkey (Option<[u8; 32]>)
         32 +  // confidential_authority
-        32; // _reserved
+        32 +  // guardian
+        32 +  // swap_adapter
+        32 +  // harvester
+        8 +   // locked_profit
+        8 +   // last_harvest_ts
+        8 +   // profit_unlock_period
+        8 +   // event_sequence
+        8 +   // min_deposit_amount
+        32 +  // metadata_hash
+        8 +   // op_counter
+        8; // _reserved
 
     pub const SEED_PREFIX: &'static [u8] = VAULT_SEED;
+
+    /// Count a mutating instruction, returning the new `op_counter` to the
+    /// caller as return data (u64 LE). Call after the instruction's CPIs,
+    /// which may set return data of their own.
+    pub fn record_op(&mut self) -> Result<()> {
+        self.op_counter = self
+            .op_counter
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        set_return_data(&self.op_counter.to_le_bytes());
+        Ok(())
+    }
+
+    /// Operation counter, sequence number and timestamp for the next emitted
+    /// event
+    pub fn stamp_event(&mut self) -> Result<(u64, u64, i64)> {
+        self.event_sequence = self
+            .event_sequence
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok((
+            self.op_counter,
+            self.event_sequence,
+            Clock::get()?.unix_timestamp,
+        ))
+    }
+
+    /// Smallest deposit the vault accepts
+    pub fn min_deposit(&self) -> u64 {
+        match self.min_deposit_amount {
+            0 => MIN_DEPOSIT_AMOUNT,
+            amount => amount,
+        }
+    }
+
+    /// Portion of the last harvest that has not yet unlocked at `now`
+    pub fn locked_profit_at(&self, now: i64) -> Result<u64> {
+        svs_math::locked_profit(
+            self.locked_profit,
+            self.last_harvest_ts,
+            self.profit_unlock_period,
+            now,
+        )
+        .map_err(|e| VaultError::from(e).into())
+    }
+
+    /// Assets backing shares for conversions: cached total minus still-locked profit.
+    ///
+    /// Streaming harvested profit prevents depositors from sandwiching a harvest
+    /// to capture yield they were not exposed to.
+    pub fn unlocked_assets(&self) -> Result<u64> {
+        let now = Clock::get()?.unix_timestamp;
+        Ok(self
+            .total_assets
+            .saturating_sub(self.locked_profit_at(now)?))
+    }
+}
+
+/// Marks a deposit permit nonce as consumed; its existence blocks replay
+#[account]
+pub struct PermitReceipt {
+    /// Vault the permit was redeemed against
+    pub vault: Pubkey,
+    /// Depositor who signed the permit
+    pub owner: Pubkey,
+    /// Permit nonce chosen by the depositor
+    pub nonce: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PermitReceipt {
+    pub const LEN: usize = 8 +   // discriminator
+        32 +  // vault
+        32 +  // owner
+        8 +   // nonce
+        1; // bump
+
+    pub const SEED_PREFIX: &'static [u8] = PERMIT_SEED;
 }
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    proof_data::{pod::PodProofType, ProofType},
+    state::ProofContextStateMeta,
+};
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint as MintState,
+};
+
+use crate::error::VaultError;
+
+/// Fail fast if a Token-2022 account has CPI Guard enabled.
+///
+/// Without this check the Token-2022 program aborts mid-flow with a generic
+/// error (see [`svs_token_extensions::cpi_guard_enabled`]). Users can wrap the
+/// vault instruction between `DisableCpiGuard` and `EnableCpiGuard` in the same
+/// transaction.
+pub fn require_cpi_guard_disabled(account: &AccountInfo) -> Result<()> {
+    require!(
+        !svs_token_extensions::cpi_guard_enabled(account)?,
+        VaultError::CpiGuardEnabled
+    );
+    Ok(())
+}
+
+/// Fail fast unless `account` is a proof context state account holding a
+/// verified `proof_type` proof.
+///
+/// Token-2022 rejects a wrong context too, but only as a generic error from
+/// deep inside the CPI; checking up front tells the client which account is
+/// wrong.
+pub fn require_proof_context(account: &AccountInfo, proof_type: ProofType) -> Result<()> {
+    require_keys_eq!(
+        *account.owner,
+        zk_elgamal_proof_program::id(),
+        VaultError::InvalidProofContextOwner
+    );
+
+    let data = account.try_borrow_data()?;
+    let meta =
+        ProofContextStateMeta::try_from_bytes(&data).map_err(|_| VaultError::ProofTypeMismatch)?;
+    require!(
+        meta.proof_type == PodProofType::from(proof_type),
+        VaultError::ProofTypeMismatch
+    );
+
+    Ok(())
+}
+
+/// Fee withheld by the asset mint when transferring `amount` in the current epoch.
+/// Returns zero for SPL Token mints and Token-2022 mints without `TransferFeeConfig`.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    match transfer_fee_config(mint)? {
+        Some(config) => Ok(config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(VaultError::MathOverflow)?),
+        None => Ok(0),
+    }
+}
+
+/// Gross amount that must be transferred so the recipient is credited exactly `net`.
+pub fn gross_amount_for_net(mint: &AccountInfo, net: u64) -> Result<u64> {
+    match transfer_fee_config(mint)? {
+        Some(config) => {
+            let fee = config
+                .calculate_inverse_epoch_fee(Clock::get()?.epoch, net)
+                .ok_or(VaultError::MathOverflow)?;
+            Ok(net.checked_add(fee).ok_or(VaultError::MathOverflow)?)
+        }
+        None => Ok(net),
+    }
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if mint.owner != &spl_token_2022::ID {
+        return Ok(None);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    Ok(state.get_extension::<TransferFeeConfig>().ok().copied())
+}
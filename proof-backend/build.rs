@@ -0,0 +1,20 @@
+//! Records the resolved solana-zk-sdk version for the readiness endpoint
+
+use std::fs;
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let version = fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| zk_sdk_version(&lock))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=ZK_SDK_VERSION={version}");
+}
+
+fn zk_sdk_version(lock: &str) -> Option<String> {
+    let mut lines = lock.lines();
+    lines.find(|line| *line == r#"name = "solana-zk-sdk""#)?;
+    let version = lines.next()?.strip_prefix("version = ")?;
+    Some(version.trim_matches('"').to_string())
+}
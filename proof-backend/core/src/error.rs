@@ -0,0 +1,46 @@
+//! Error types for proof generation
+
+use thiserror::Error;
+
+/// Proof generation error types
+#[derive(Debug, Error)]
+pub enum ProofError {
+    /// Inputs that cannot be proven, e.g. an insufficient balance
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A malformed input value
+    #[error("{message}")]
+    InvalidFormat {
+        /// Stable machine-readable reason: `INVALID_ENCODING`,
+        /// `INVALID_LENGTH`, `INVALID_PUBKEY`, `INVALID_AMOUNT` or
+        /// `INVALID_VALUE`
+        code: &'static str,
+        /// Expected format, e.g. `base64, 32 bytes`
+        expected: String,
+        message: String,
+    },
+
+    #[error("{0}")]
+    ProofGeneration(String),
+
+    #[error("{0}")]
+    InvalidSignature(String),
+}
+
+impl ProofError {
+    pub(crate) fn invalid_format(
+        code: &'static str,
+        expected: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        ProofError::InvalidFormat {
+            code,
+            expected: expected.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Result type alias for proof generation
+pub type Result<T> = std::result::Result<T, ProofError>;
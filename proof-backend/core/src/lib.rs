@@ -0,0 +1,19 @@
+//! SVS Proof Core
+//!
+//! ZK proof generation for SVS-2 Confidential Vaults, as used by the SVS
+//! Proof Backend: ElGamal and AE key derivation from wallet signatures, the
+//! Token-2022 confidential transfer proofs, and parsing of their inputs.
+//! Clients and tests can generate proofs in-process with it instead of
+//! calling the backend over HTTP.
+//!
+//! Key-derivation signatures are secret key material: the derived seeds are
+//! zeroized, and callers should zeroize the signatures they pass in.
+
+pub mod error;
+pub mod proof_generator;
+
+pub use error::{ProofError, Result};
+pub use proof_generator::{
+    AppliedPendingBalance, ContextProofType, ProofGenerator, TransferProofs, WithdrawProofs,
+    MAX_RANGE_PROOF_AMOUNTS,
+};
@@ -0,0 +1,333 @@
+//! API key and JWT bearer authentication, and scope enforcement
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use solana_sdk::hash::hash;
+use std::time::Instant;
+use subtle::ConstantTimeEq;
+use tracing::{debug, warn};
+
+use crate::{
+    error::BackendError,
+    jwt::JwtVerifier,
+    routes::{
+        admin::ADMIN_PATH_PREFIX, docs, estimate::ESTIMATE_PATH, proofs::AppState,
+        sealing_key::SEALING_KEY_PATH, service_key::SERVICE_KEY_PATH, usage::USAGE_PATH,
+    },
+    services::{usage::proof_ms, ApiKeyStore},
+    types::{ApiKeyConfig, ApiScope, QuotaAction, QuotaRemaining, UsageCounts},
+};
+
+/// Response header with the billable requests charged for the request
+pub const USAGE_REQUESTS_HEADER: &str = "x-usage-requests";
+
+/// Response header with the proof generation time charged for the request,
+/// in milliseconds
+pub const USAGE_PROOF_MS_HEADER: &str = "x-usage-proof-ms";
+
+/// Response headers with what is left of each configured quota limit
+pub const QUOTA_REMAINING_HEADERS: [&str; 3] = [
+    "x-quota-remaining-daily-requests",
+    "x-quota-remaining-monthly-requests",
+    "x-quota-remaining-monthly-proof-secs",
+];
+
+/// The API key that authenticated a request, stored in request extensions
+///
+/// A JWT is represented by a key named after its subject. Absent when
+/// neither API keys nor JWT authentication are configured (development mode).
+#[derive(Clone)]
+pub struct AuthenticatedKey(pub ApiKeyConfig);
+
+/// API key authentication middleware
+///
+/// Accepts an `X-API-Key` header or, when JWT authentication is configured,
+/// an `Authorization: Bearer` token.
+/// Restricted keys may only call endpoints covered by their scopes. Routes
+/// outside every scope need an unrestricted key. POST requests count towards
+/// the key's usage and quota, and their responses report the cost charged
+/// and the quota left.
+pub async fn api_key_middleware(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut request: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let config = &state.config;
+    // Skip auth for health check, public keys and API docs; the admin API
+    // checks its own key
+    let path = request.uri().path();
+    if path == "/health"
+        || path.starts_with("/health/")
+        || path == SERVICE_KEY_PATH
+        || path == SEALING_KEY_PATH
+        || path == docs::OPENAPI_PATH
+        || path.starts_with(docs::SWAGGER_UI_PATH)
+        || path.starts_with(ADMIN_PATH_PREFIX)
+    {
+        return Ok(next.run(request).await);
+    }
+
+    // If no credentials configured, allow all requests (development mode)
+    if config.api_keys.is_empty() && state.api_keys.is_empty() && state.jwt.is_none() {
+        warn!("No API keys configured - running in development mode");
+        return Ok(next.run(request).await);
+    }
+
+    let key = authenticate(
+        &config.api_keys,
+        &state.api_keys,
+        state.jwt.as_ref(),
+        &headers,
+    )
+    .await?;
+
+    let scope = ApiScope::for_path(path);
+    let allowed = match scope {
+        Some(scope) => key.allows(scope),
+        None => path == USAGE_PATH || path == ESTIMATE_PATH || key.scopes.is_none(),
+    };
+    if !allowed {
+        warn!(
+            api_key = key.label(),
+            path, "API key not permitted for endpoint"
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+    debug!(api_key = key.label(), "Authenticated request");
+
+    // Polling, usage lookups and estimates are free; everything else is metered
+    let billable = request.method() == Method::POST && path != ESTIMATE_PATH;
+    // Usage before this request, to report the quota left after it
+    let mut usage_before = None;
+    if billable {
+        if let Some(quota) = &key.quota {
+            let usage = match state.usage.snapshot(&key.key).await {
+                Ok(usage) => usage,
+                Err(e) => return Ok(e.into_response()),
+            };
+            if let Some(limit) = usage.exhausted_limit(quota) {
+                warn!(api_key = key.label(), limit, "API key quota exhausted");
+                if quota.on_exhausted == QuotaAction::Reject {
+                    let mut response =
+                        BackendError::QuotaExceeded(limit.to_string()).into_response();
+                    let remaining = usage.remaining(quota);
+                    set_usage_headers(&mut response, &UsageCounts::default(), Some(&remaining));
+                    return Ok(response);
+                }
+            }
+            usage_before = Some((usage, quota.clone()));
+        }
+        state.usage.record_request(&key.key);
+    }
+
+    let key_name = key.key.clone();
+    request.extensions_mut().insert(AuthenticatedKey(key));
+
+    // Synchronous proof endpoints spend their time generating proofs; jobs
+    // record theirs when they run
+    let started = Instant::now();
+    let mut response = next.run(request).await;
+    if !billable {
+        return Ok(response);
+    }
+
+    let mut cost = UsageCounts {
+        requests: 1,
+        proof_ms: 0,
+    };
+    if scope.is_some_and(ApiScope::is_proof) {
+        let elapsed = started.elapsed();
+        state.usage.record_proof_time(&key_name, elapsed);
+        cost.proof_ms = proof_ms(elapsed);
+    }
+    let remaining = usage_before.map(|(mut usage, quota)| {
+        usage.charge(&cost);
+        usage.remaining(&quota)
+    });
+    set_usage_headers(&mut response, &cost, remaining.as_ref());
+
+    Ok(response)
+}
+
+/// Report the cost charged for a request and the quota left after it
+fn set_usage_headers(
+    response: &mut Response,
+    cost: &UsageCounts,
+    remaining: Option<&QuotaRemaining>,
+) {
+    let headers = response.headers_mut();
+    headers.insert(USAGE_REQUESTS_HEADER, HeaderValue::from(cost.requests));
+    headers.insert(USAGE_PROOF_MS_HEADER, HeaderValue::from(cost.proof_ms));
+
+    let Some(remaining) = remaining else {
+        return;
+    };
+    let limits = [
+        remaining.daily_requests,
+        remaining.monthly_requests,
+        remaining.monthly_proof_secs,
+    ];
+    for (name, left) in QUOTA_REMAINING_HEADERS.into_iter().zip(limits) {
+        if let Some(left) = left {
+            headers.insert(name, HeaderValue::from(left));
+        }
+    }
+}
+
+/// Resolve the request's bearer token or API key to the key it stands for
+async fn authenticate(
+    api_keys: &[ApiKeyConfig],
+    managed_keys: &ApiKeyStore,
+    jwt: Option<&JwtVerifier>,
+    headers: &HeaderMap,
+) -> Result<ApiKeyConfig, StatusCode> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if let (Some(token), Some(jwt)) = (bearer, jwt) {
+        return jwt.verify(token.trim()).await.map_err(|e| {
+            warn!(error = %e, "Invalid bearer token");
+            StatusCode::UNAUTHORIZED
+        });
+    }
+
+    // Check for API key header
+    let api_key = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| {
+            warn!("Request missing API key");
+            StatusCode::UNAUTHORIZED
+        })?;
+
+    // Validate API key (don't log the actual key for security); both sets
+    // are always searched, so timing does not reveal which one matched
+    let configured = find_api_key(api_keys, api_key).cloned();
+    let managed = managed_keys.find(api_key);
+    configured.or(managed).ok_or_else(|| {
+        warn!("Invalid API key provided");
+        StatusCode::UNAUTHORIZED
+    })
+}
+
+/// The configured key equal to `api_key`
+///
+/// Keys are compared as SHA-256 digests in constant time, and every key is
+/// compared, so response timing reveals neither a key's length nor how much
+/// of it a guess got right.
+fn find_api_key<'a>(api_keys: &'a [ApiKeyConfig], api_key: &str) -> Option<&'a ApiKeyConfig> {
+    let presented = hash(api_key.as_bytes());
+    api_keys.iter().fold(None, |found, key| {
+        let matches = hash(key.key.as_bytes()).as_ref().ct_eq(presented.as_ref());
+        if bool::from(matches) {
+            Some(key)
+        } else {
+            found
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PriorityTier;
+
+    fn key(scopes: Option<Vec<ApiScope>>) -> ApiKeyConfig {
+        ApiKeyConfig {
+            key: "k".to_string(),
+            name: None,
+            scopes,
+            quota: None,
+            priority: PriorityTier::default(),
+        }
+    }
+
+    #[test]
+    fn test_scope_for_path() {
+        assert_eq!(
+            ApiScope::for_path("/v1/proofs/pubkey-validity"),
+            Some(ApiScope::PubkeyValidity)
+        );
+        assert_eq!(
+            ApiScope::for_path("/v1/proofs/pubkey-validity/batch"),
+            Some(ApiScope::PubkeyValidity)
+        );
+        assert_eq!(
+            ApiScope::for_path("/v1/balance/decrypt"),
+            Some(ApiScope::Balance)
+        );
+        assert_eq!(ApiScope::for_path("/v1/tx/withdraw"), Some(ApiScope::Tx));
+        assert_eq!(
+            ApiScope::for_path("/v1/keys/elgamal-pubkey"),
+            Some(ApiScope::Keys)
+        );
+        assert_eq!(
+            ApiScope::for_path("/v1/jobs/abc/events"),
+            Some(ApiScope::Jobs)
+        );
+        assert_eq!(
+            ApiScope::for_path("/v1/relay/submit"),
+            Some(ApiScope::Relay)
+        );
+        assert_eq!(ApiScope::for_path("/v1/proofs/unknown"), None);
+    }
+
+    #[test]
+    fn test_usage_headers() {
+        let mut response = Response::new(Body::empty());
+        let cost = UsageCounts {
+            requests: 1,
+            proof_ms: 420,
+        };
+        let remaining = QuotaRemaining {
+            daily_requests: Some(99),
+            ..Default::default()
+        };
+
+        set_usage_headers(&mut response, &cost, Some(&remaining));
+
+        let headers = response.headers();
+        assert_eq!(headers[USAGE_REQUESTS_HEADER], "1");
+        assert_eq!(headers[USAGE_PROOF_MS_HEADER], "420");
+        assert_eq!(headers[QUOTA_REMAINING_HEADERS[0]], "99");
+        // Limits that are not configured are not reported
+        assert!(!headers.contains_key(QUOTA_REMAINING_HEADERS[1]));
+        assert!(!headers.contains_key(QUOTA_REMAINING_HEADERS[2]));
+    }
+
+    #[test]
+    fn test_find_api_key() {
+        let keys = [
+            ApiKeyConfig {
+                key: "first".to_string(),
+                ..key(None)
+            },
+            ApiKeyConfig {
+                key: "second".to_string(),
+                ..key(None)
+            },
+        ];
+
+        assert_eq!(find_api_key(&keys, "second").unwrap().key, "second");
+        assert!(find_api_key(&keys, "secon").is_none());
+        assert!(find_api_key(&keys, "second ").is_none());
+        assert!(find_api_key(&[], "first").is_none());
+    }
+
+    #[test]
+    fn test_restricted_key() {
+        let partner = key(Some(vec![ApiScope::PubkeyValidity]));
+
+        assert!(partner.allows(ApiScope::PubkeyValidity));
+        assert!(!partner.allows(ApiScope::Transfer));
+        assert!(!partner.allows(ApiScope::Tx));
+        assert!(key(None).allows(ApiScope::Tx));
+    }
+}
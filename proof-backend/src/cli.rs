@@ -0,0 +1,590 @@
+//! Offline proof generation
+//!
+//! `svs-proof-backend prove <proof> --<input> <value>...` generates a proof
+//! without starting the server, for air-gapped machines and scripts. Inputs
+//! are the fields of the matching HTTP request. A value of `@path` is read
+//! from a file and `@-` from stdin, which keeps key-derivation signatures out
+//! of shell history and process listings.
+//!
+//! Outputs are printed as JSON, or written as raw bytes to `<name>.bin` files
+//! with `--out <dir>`.
+
+use anyhow::{bail, Context};
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    encoding::{split_prefix, Encoding},
+    error::{parse_each, parse_fields, BackendError, Result},
+    services::ProofGenerator,
+};
+use svs_proof_core::{Result as ProofResult, MAX_RANGE_PROOF_AMOUNTS};
+
+const USAGE: &str = "\
+Usage: svs-proof-backend prove <proof> [--<input> <value>]... [options]
+
+Proofs and their inputs:
+  pubkey-validity  --token-account --elgamal-signature
+  equality         --token-account --elgamal-signature --ciphertext --amount
+  zero-ciphertext  --token-account --elgamal-signature --ciphertext
+  range            --amount --bit-length --opening, each once per amount
+  withdraw         --token-account --elgamal-signature --ae-signature
+                   --ciphertext --decryptable-balance --amount
+  transfer         --token-account --elgamal-signature --ae-signature
+                   --ciphertext --decryptable-balance --amount
+                   --destination-pubkey [--auditor-pubkey]
+
+Values:
+  <text>    base58 token account, decimal amounts, base64 binary data
+            (or prefixed with base58: or hex:)
+  @<path>   read the value from a file, as text or raw bytes
+  @-        read the value from stdin
+
+Options:
+  --out <dir>         write each output to <dir>/<name>.bin instead of
+                      printing it
+  --encoding <name>   encoding of printed outputs: base64 (default), base58
+                      or hex
+";
+
+/// Run the `prove` subcommand and return the process exit code
+pub fn run(args: impl IntoIterator<Item = String>) -> i32 {
+    let result = parse_args(args).and_then(|args| match args {
+        Some(args) => {
+            let outputs = prove(&args).map_err(|e| anyhow::anyhow!(e))?;
+            write_outputs(&outputs, &args)
+        }
+        None => {
+            print!("{USAGE}");
+            Ok(())
+        }
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {e:#}\n\nRun `svs-proof-backend prove --help` for usage.");
+            1
+        }
+    }
+}
+
+/// An input value as given, or the contents of a file or stdin
+enum Input {
+    Text(Zeroizing<String>),
+    File(Zeroizing<Vec<u8>>),
+}
+
+/// Parsed command line of the `prove` subcommand
+struct Args {
+    proof: String,
+    inputs: HashMap<String, Vec<Input>>,
+    out: Option<PathBuf>,
+    encoding: Encoding,
+}
+
+/// Parse the arguments after `prove`; `None` when help was requested
+fn parse_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Option<Args>> {
+    let mut args = args.into_iter();
+    let proof = match args.next() {
+        None => bail!("missing proof type"),
+        Some(arg) if arg == "--help" || arg == "-h" => return Ok(None),
+        Some(proof) => proof,
+    };
+
+    let mut parsed = Args {
+        proof,
+        inputs: HashMap::new(),
+        out: None,
+        encoding: Encoding::default(),
+    };
+    let mut read_stdin = false;
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Ok(None);
+        }
+        let Some(name) = arg.strip_prefix("--") else {
+            bail!("unexpected argument {arg:?}");
+        };
+        let value = args
+            .next()
+            .with_context(|| format!("--{name} needs a value"))?;
+
+        match name {
+            "out" => parsed.out = Some(PathBuf::from(value)),
+            "encoding" => parsed.encoding = value.parse()?,
+            _ => {
+                let input = read_input(value, &mut read_stdin)
+                    .with_context(|| format!("failed to read --{name}"))?;
+                parsed
+                    .inputs
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(input);
+            }
+        }
+    }
+
+    Ok(Some(parsed))
+}
+
+/// Resolve `@path` and `@-` values; stdin can be read only once
+fn read_input(value: String, read_stdin: &mut bool) -> io::Result<Input> {
+    let value = Zeroizing::new(value);
+    let contents = match value.strip_prefix('@') {
+        None => return Ok(Input::Text(value)),
+        Some("-") if *read_stdin => {
+            return Err(io::Error::other("stdin was already read by another input"))
+        }
+        Some("-") => {
+            *read_stdin = true;
+            let mut bytes = Zeroizing::new(Vec::new());
+            io::stdin().read_to_end(&mut bytes)?;
+            bytes
+        }
+        Some(path) => Zeroizing::new(fs::read(path)?),
+    };
+
+    Ok(Input::File(contents))
+}
+
+impl Args {
+    /// Every value given for `--name`
+    fn all(&self, name: &str) -> &[Input] {
+        self.inputs.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The single value of `--name`
+    fn one(&self, name: &str) -> Result<&Input> {
+        match self.all(name) {
+            [] => Err(BackendError::missing()),
+            [input] => Ok(input),
+            _ => Err(BackendError::invalid_format(
+                "INVALID_VALUE",
+                "one value",
+                "given more than once",
+            )),
+        }
+    }
+
+    fn text(&self, name: &str) -> Result<Zeroizing<String>> {
+        self.one(name).and_then(text)
+    }
+
+    fn binary(&self, name: &str) -> Result<Zeroizing<String>> {
+        self.one(name).and_then(binary)
+    }
+
+    /// Parse the binary input `--name`
+    fn parse<T>(&self, name: &str, parse: impl Fn(&str) -> ProofResult<T>) -> Result<T> {
+        Ok(parse(&self.binary(name)?)?)
+    }
+
+    fn token_account(&self) -> Result<Pubkey> {
+        Ok(ProofGenerator::parse_pubkey(&self.text("token-account")?)?)
+    }
+
+    fn amount(&self) -> Result<u64> {
+        Ok(ProofGenerator::parse_amount(&self.text("amount")?)?)
+    }
+
+    /// A key-derivation signature, zeroized after use
+    fn signature(&self, name: &str) -> Result<Zeroizing<[u8; 64]>> {
+        let signature = self.parse(name, ProofGenerator::parse_signature)?;
+        Ok(Zeroizing::new(signature.into()))
+    }
+}
+
+/// A text input; file contents are trimmed
+fn text(input: &Input) -> Result<Zeroizing<String>> {
+    match input {
+        Input::Text(text) => Ok(text.clone()),
+        Input::File(contents) => match std::str::from_utf8(contents) {
+            Ok(text) => Ok(Zeroizing::new(text.trim().to_string())),
+            Err(_) => Err(BackendError::invalid_format(
+                "INVALID_ENCODING",
+                "text",
+                "expected text, got binary data",
+            )),
+        },
+    }
+}
+
+/// A binary input as base64, the format the parsers take
+///
+/// Files hold either encoded text or the raw bytes; raw bytes are assumed
+/// when the contents do not decode as text.
+fn binary(input: &Input) -> Result<Zeroizing<String>> {
+    let decoded = text(input).and_then(|text| {
+        let (encoding, data) = split_prefix(&text, Encoding::Base64)?;
+        encoding.decode(data).map(Zeroizing::new).map_err(|e| {
+            BackendError::invalid_format(
+                "INVALID_ENCODING",
+                "base64, base58: or hex:",
+                format!("invalid encoding: {e}"),
+            )
+        })
+    });
+    let bytes = match (decoded, input) {
+        (Ok(bytes), _) => bytes,
+        (Err(_), Input::File(contents)) => contents.clone(),
+        (Err(e), Input::Text(_)) => return Err(e),
+    };
+
+    Ok(Zeroizing::new(Encoding::Base64.encode(&bytes)))
+}
+
+/// Outputs of a proof: binary data, and plain values such as the proof type
+#[derive(Default)]
+struct Outputs {
+    binary: Vec<(&'static str, Vec<u8>)>,
+    plain: Vec<(&'static str, Value)>,
+}
+
+impl Outputs {
+    fn binary<const N: usize>(binary: [(&'static str, Vec<u8>); N]) -> Self {
+        Self {
+            binary: binary.into(),
+            plain: vec![],
+        }
+    }
+}
+
+fn prove(args: &Args) -> Result<Outputs> {
+    let known: &[&str] = match args.proof.as_str() {
+        "pubkey-validity" => &["token-account", "elgamal-signature"],
+        "equality" => &["token-account", "elgamal-signature", "ciphertext", "amount"],
+        "zero-ciphertext" => &["token-account", "elgamal-signature", "ciphertext"],
+        "range" => &["amount", "bit-length", "opening"],
+        "withdraw" => &[
+            "token-account",
+            "elgamal-signature",
+            "ae-signature",
+            "ciphertext",
+            "decryptable-balance",
+            "amount",
+        ],
+        "transfer" => &[
+            "token-account",
+            "elgamal-signature",
+            "ae-signature",
+            "ciphertext",
+            "decryptable-balance",
+            "amount",
+            "destination-pubkey",
+            "auditor-pubkey",
+        ],
+        other => {
+            return Err(BackendError::BadRequest(format!(
+                "unknown proof type {other:?}"
+            )))
+        }
+    };
+    if let Some(name) = args
+        .inputs
+        .keys()
+        .find(|name| !known.contains(&name.as_str()))
+    {
+        return Err(BackendError::BadRequest(format!(
+            "--{name} is not an input of {} proofs",
+            args.proof
+        )));
+    }
+
+    match args.proof.as_str() {
+        "pubkey-validity" => {
+            parse_fields!(
+                token_account = "--token-account" => args.token_account(),
+                elgamal_signature = "--elgamal-signature" => args.signature("elgamal-signature"),
+            );
+            let keypair =
+                ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+            let (proof_data, elgamal_pubkey) =
+                ProofGenerator::generate_pubkey_validity_proof(&keypair)?;
+
+            Ok(Outputs::binary([
+                ("proof_data", proof_data),
+                ("elgamal_pubkey", elgamal_pubkey.to_vec()),
+            ]))
+        }
+        "equality" => {
+            parse_fields!(
+                token_account = "--token-account" => args.token_account(),
+                elgamal_signature = "--elgamal-signature" => args.signature("elgamal-signature"),
+                ciphertext = "--ciphertext"
+                    => args.parse("ciphertext", ProofGenerator::parse_ciphertext),
+                amount = "--amount" => args.amount(),
+            );
+            let keypair =
+                ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+            let proof_data =
+                ProofGenerator::generate_equality_proof(&keypair, &ciphertext, amount)?;
+
+            Ok(Outputs::binary([("proof_data", proof_data)]))
+        }
+        "zero-ciphertext" => {
+            parse_fields!(
+                token_account = "--token-account" => args.token_account(),
+                elgamal_signature = "--elgamal-signature" => args.signature("elgamal-signature"),
+                ciphertext = "--ciphertext"
+                    => args.parse("ciphertext", ProofGenerator::parse_ciphertext),
+            );
+            let keypair =
+                ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+            let proof_data = ProofGenerator::generate_zero_ciphertext_proof(&keypair, &ciphertext)?;
+
+            Ok(Outputs::binary([("proof_data", proof_data)]))
+        }
+        "range" => {
+            let texts = |name| {
+                args.all(name)
+                    .iter()
+                    .map(|input| text(input).map(|s| s.to_string()))
+                    .collect::<Result<Vec<_>>>()
+            };
+            let binaries = |name| {
+                args.all(name)
+                    .iter()
+                    .map(|input| binary(input).map(|s| s.to_string()))
+                    .collect::<Result<Vec<_>>>()
+            };
+            parse_fields!(
+                amounts = "--amount"
+                    => texts("amount").and_then(|v| {
+                        parse_each(&v, MAX_RANGE_PROOF_AMOUNTS, ProofGenerator::parse_amount)
+                    }),
+                bit_lengths = "--bit-length"
+                    => texts("bit-length")
+                        .and_then(|v| parse_each(&v, MAX_RANGE_PROOF_AMOUNTS, parse_bit_length)),
+                openings = "--opening"
+                    => binaries("opening").and_then(|v| {
+                        parse_each(&v, MAX_RANGE_PROOF_AMOUNTS, ProofGenerator::parse_opening)
+                    }),
+            );
+            let (proof_data, proof_type) =
+                ProofGenerator::generate_range_proof(&amounts, &bit_lengths, &openings)?;
+
+            let mut outputs = Outputs::binary([("proof_data", proof_data)]);
+            outputs.plain.push((
+                "proof_type",
+                serde_json::to_value(proof_type).unwrap_or_default(),
+            ));
+            Ok(outputs)
+        }
+        "withdraw" => {
+            parse_fields!(
+                token_account = "--token-account" => args.token_account(),
+                elgamal_signature = "--elgamal-signature" => args.signature("elgamal-signature"),
+                ae_signature = "--ae-signature" => args.signature("ae-signature"),
+                ciphertext = "--ciphertext"
+                    => args.parse("ciphertext", ProofGenerator::parse_ciphertext),
+                decryptable_balance = "--decryptable-balance"
+                    => args.parse("decryptable-balance", ProofGenerator::parse_ae_ciphertext),
+                amount = "--amount" => args.amount(),
+            );
+            let keypair =
+                ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+            let ae_key = ProofGenerator::derive_ae_key(&(*ae_signature).into())?;
+            let proofs = ProofGenerator::generate_withdraw_proof(
+                &keypair,
+                &ae_key,
+                &ciphertext,
+                &decryptable_balance,
+                amount,
+            )?;
+
+            Ok(Outputs::binary([
+                (
+                    "equality_proof_data",
+                    bytemuck::bytes_of(&proofs.equality_proof_data).to_vec(),
+                ),
+                (
+                    "range_proof_data",
+                    bytemuck::bytes_of(&proofs.range_proof_data).to_vec(),
+                ),
+                (
+                    "remaining_balance_commitment",
+                    proofs.remaining_balance_commitment.to_vec(),
+                ),
+                (
+                    "remaining_balance_opening",
+                    proofs.remaining_balance_opening.to_bytes().to_vec(),
+                ),
+                (
+                    "new_decryptable_available_balance",
+                    proofs.new_decryptable_available_balance.to_vec(),
+                ),
+            ]))
+        }
+        // transfer
+        _ => {
+            parse_fields!(
+                token_account = "--token-account" => args.token_account(),
+                elgamal_signature = "--elgamal-signature" => args.signature("elgamal-signature"),
+                ae_signature = "--ae-signature" => args.signature("ae-signature"),
+                ciphertext = "--ciphertext"
+                    => args.parse("ciphertext", ProofGenerator::parse_ciphertext),
+                decryptable_balance = "--decryptable-balance"
+                    => args.parse("decryptable-balance", ProofGenerator::parse_ae_ciphertext),
+                amount = "--amount" => args.amount(),
+                destination = "--destination-pubkey"
+                    => args.parse("destination-pubkey", ProofGenerator::parse_elgamal_pubkey),
+                auditor = "--auditor-pubkey" => match args.all("auditor-pubkey") {
+                    [] => Ok(None),
+                    _ => args
+                        .parse("auditor-pubkey", ProofGenerator::parse_elgamal_pubkey)
+                        .map(Some),
+                },
+            );
+            let keypair =
+                ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+            let ae_key = ProofGenerator::derive_ae_key(&(*ae_signature).into())?;
+            let proofs = ProofGenerator::generate_transfer_proof(
+                &keypair,
+                &ae_key,
+                &ciphertext,
+                &decryptable_balance,
+                amount,
+                &destination,
+                auditor.as_ref(),
+            )?;
+
+            Ok(Outputs::binary([
+                ("equality_proof_data", proofs.equality_proof),
+                (
+                    "ciphertext_validity_proof_data",
+                    proofs.ciphertext_validity_proof,
+                ),
+                ("range_proof_data", proofs.range_proof),
+                (
+                    "auditor_ciphertext_lo",
+                    proofs.auditor_ciphertext_lo.to_vec(),
+                ),
+                (
+                    "auditor_ciphertext_hi",
+                    proofs.auditor_ciphertext_hi.to_vec(),
+                ),
+                (
+                    "new_decryptable_available_balance",
+                    proofs.new_decryptable_available_balance.to_vec(),
+                ),
+            ]))
+        }
+    }
+}
+
+fn parse_bit_length(s: &str) -> Result<usize> {
+    s.parse().map_err(|_| {
+        BackendError::invalid_format(
+            "INVALID_VALUE",
+            "integer",
+            format!("expected a bit length, got {s:?}"),
+        )
+    })
+}
+
+/// Print the outputs as JSON, or write them to `--out` and print the paths
+fn write_outputs(outputs: &Outputs, args: &Args) -> anyhow::Result<()> {
+    let mut json: Map<String, Value> = outputs
+        .plain
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+
+    match &args.out {
+        Some(dir) => {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+            let mut files = Map::new();
+            for (name, bytes) in &outputs.binary {
+                let path = dir.join(format!("{name}.bin"));
+                fs::write(&path, bytes)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                files.insert(name.to_string(), path.display().to_string().into());
+            }
+            json.insert("files".to_string(), files.into());
+        }
+        None => {
+            for (name, bytes) in &outputs.binary {
+                json.insert(name.to_string(), args.encoding.encode(bytes).into());
+            }
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_zk_sdk::zk_elgamal_proof_program::proof_data::{
+        PubkeyValidityProofData, ZkProofData,
+    };
+
+    fn args(args: &[&str]) -> Args {
+        parse_args(args.iter().map(|s| s.to_string()))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pubkey_validity_from_file() {
+        let dir = std::env::temp_dir().join(format!("svs-prove-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let signature_path = dir.join("elgamal.sig");
+        fs::write(&signature_path, [7u8; 64]).unwrap();
+        let token_account = Pubkey::new_unique().to_string();
+
+        let args = args(&[
+            "pubkey-validity",
+            "--token-account",
+            &token_account,
+            "--elgamal-signature",
+            &format!("@{}", signature_path.display()),
+            "--out",
+            &dir.display().to_string(),
+        ]);
+        write_outputs(&prove(&args).unwrap(), &args).unwrap();
+
+        let proof_data = fs::read(dir.join("proof_data.bin")).unwrap();
+        let proof = bytemuck::try_from_bytes::<PubkeyValidityProofData>(&proof_data).unwrap();
+        proof.verify_proof().unwrap();
+        assert_eq!(fs::read(dir.join("elgamal_pubkey.bin")).unwrap().len(), 32);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_inputs_reported_together() {
+        let args = args(&[
+            "equality",
+            "--token-account",
+            "nope",
+            "--elgamal-signature",
+            &format!("hex:{}", hex::encode([7u8; 64])),
+            "--ciphertext",
+            "AAAA",
+        ]);
+
+        let body = prove(&args).err().unwrap().to_error_response();
+        let fields: Vec<_> = body.fields.iter().map(|f| f.field.as_str()).collect();
+
+        assert_eq!(fields, ["--token-account", "--ciphertext", "--amount"]);
+    }
+
+    #[test]
+    fn test_unknown_inputs_rejected() {
+        let args = args(&["range", "--token-account", "x"]);
+
+        assert!(matches!(prove(&args), Err(BackendError::BadRequest(_))));
+        assert!(parse_args(["range".to_string(), "--amount".to_string()]).is_err());
+    }
+}
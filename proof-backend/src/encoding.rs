@@ -0,0 +1,428 @@
+//! Binary field encodings
+//!
+//! Handlers work with base64. This middleware lets clients use base58 or hex
+//! instead, by transcoding the binary fields of JSON bodies:
+//! - `?encoding=base58|hex|base64` sets the encoding of every binary field in
+//!   the request and the response
+//! - a request field can name its own encoding with a `base58:`, `hex:` or
+//!   `base64:` prefix, which takes precedence
+//!
+//! Binary fields are recognized by name, so the lists below must follow the
+//! request and response types.
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::Request,
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::error::{BackendError, Result};
+
+/// Query parameter selecting the encoding of binary fields
+const ENCODING_PARAM: &str = "encoding";
+
+/// Longest binary request field that is transcoded, in characters
+///
+/// The largest binary field, a U256 range proof, is under 2 KiB. Base58
+/// decoding takes quadratic time, so longer values are rejected unread.
+const MAX_FIELD_LEN: usize = 8192;
+
+/// Binary request fields, including nested ones
+const REQUEST_FIELDS: &[&str] = &[
+    "request_signature",
+    "elgamal_signature",
+    "ae_signature",
+    "sealed",
+    "ciphertext",
+    "current_ciphertext",
+    "current_decryptable_balance",
+    "available_balance",
+    "pending_balance_lo",
+    "pending_balance_hi",
+    "decryptable_available_balance",
+    "destination_elgamal_pubkey",
+    "auditor_elgamal_pubkey",
+    "commitment_blindings",
+    "memo_hash",
+    "proof_data",
+];
+
+/// Binary response fields, including nested ones
+const RESPONSE_FIELDS: &[&str] = &[
+    "proof_data",
+    "elgamal_pubkey",
+    "ae_key_fingerprint",
+    "equality_proof_data",
+    "ciphertext_validity_proof_data",
+    "range_proof_data",
+    "remaining_balance_commitment",
+    "remaining_balance_opening",
+    "auditor_ciphertext_lo",
+    "auditor_ciphertext_hi",
+    "new_decryptable_available_balance",
+    "decryptable_balance",
+    "transactions",
+    "data",
+];
+
+/// Text encoding of binary data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Base64,
+    Base58,
+    Hex,
+}
+
+impl FromStr for Encoding {
+    type Err = BackendError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "base64" => Ok(Self::Base64),
+            "base58" => Ok(Self::Base58),
+            "hex" => Ok(Self::Hex),
+            _ => Err(BackendError::BadRequest(format!(
+                "Unsupported encoding {s:?}; expected base64, base58 or hex"
+            ))),
+        }
+    }
+}
+
+impl Encoding {
+    pub fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Base64 => STANDARD.encode(bytes),
+            Self::Base58 => bs58::encode(bytes).into_string(),
+            Self::Hex => hex::encode(bytes),
+        }
+    }
+
+    pub fn decode(self, s: &str) -> std::result::Result<Vec<u8>, String> {
+        match self {
+            Self::Base64 => STANDARD.decode(s).map_err(|e| e.to_string()),
+            Self::Base58 => bs58::decode(s).into_vec().map_err(|e| e.to_string()),
+            Self::Hex => hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Base64 => "base64",
+            Self::Base58 => "base58",
+            Self::Hex => "hex",
+        }
+    }
+}
+
+/// Transcode binary fields between the client's encoding and base64
+pub async fn encoding_middleware(request: Request<Body>, next: Next) -> Response {
+    let (parts, body) = request.into_parts();
+    let encoding = match requested_encoding(&parts) {
+        Ok(encoding) => encoding,
+        Err(e) => return e.into_response(),
+    };
+
+    // The body size limit layer runs first, so this is bounded
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        return BackendError::BadRequest("Failed to read request body".to_string()).into_response();
+    };
+    let body = match transcode_request(&parts, body, encoding) {
+        Ok(body) => body,
+        Err(e) => return e.into_response(),
+    };
+
+    let mut request = Request::from_parts(parts, Body::from(body));
+    request.headers_mut().remove(header::CONTENT_LENGTH);
+    let response = next.run(request).await;
+    if encoding == Encoding::Base64 || !is_json(response.headers()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(body) = to_bytes(body, usize::MAX).await else {
+        return BackendError::Internal("Failed to read response body".to_string()).into_response();
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&body) else {
+        return Response::from_parts(parts, Body::from(body));
+    };
+    reencode_fields(&mut value, encoding);
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+/// Encoding named by the `encoding` query parameter, base64 by default
+fn requested_encoding(parts: &Parts) -> Result<Encoding> {
+    parts
+        .uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == ENCODING_PARAM)
+        .map(|(_, value)| value.parse())
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"))
+}
+
+/// Rewrite the binary fields of a JSON request body as base64
+fn transcode_request(parts: &Parts, body: Bytes, encoding: Encoding) -> Result<Bytes> {
+    if body.is_empty() || !is_json(&parts.headers) {
+        return Ok(body);
+    }
+    // Malformed JSON is left for the handler to reject
+    let Ok(mut value) = serde_json::from_slice::<Value>(&body) else {
+        return Ok(body);
+    };
+
+    let mut changed = false;
+    decode_fields(&mut value, encoding, &mut changed)?;
+    if !changed {
+        return Ok(body);
+    }
+
+    Ok(Bytes::from(value.to_string()))
+}
+
+fn decode_fields(value: &mut Value, encoding: Encoding, changed: &mut bool) -> Result<()> {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if REQUEST_FIELDS.contains(&name.as_str()) {
+                    decode_field(name, field, encoding, changed)?;
+                } else {
+                    decode_fields(field, encoding, changed)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                decode_fields(item, encoding, changed)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn decode_field(
+    name: &str,
+    field: &mut Value,
+    encoding: Encoding,
+    changed: &mut bool,
+) -> Result<()> {
+    match field {
+        Value::String(s) => {
+            let (encoding, data) = split_prefix(s, encoding)?;
+            if encoding != Encoding::Base64 || data.len() != s.len() {
+                if data.len() > MAX_FIELD_LEN {
+                    return Err(BackendError::invalid_format(
+                        "INVALID_LENGTH",
+                        &format!("at most {MAX_FIELD_LEN} characters"),
+                        format!(
+                            "expected at most {MAX_FIELD_LEN} characters, got {}",
+                            data.len()
+                        ),
+                    )
+                    .for_field(name));
+                }
+                let bytes = encoding.decode(data).map_err(|e| {
+                    BackendError::invalid_format(
+                        "INVALID_ENCODING",
+                        encoding.name(),
+                        format!("invalid {}: {e}", encoding.name()),
+                    )
+                    .for_field(name)
+                })?;
+                *s = STANDARD.encode(bytes);
+                *changed = true;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                decode_field(name, item, encoding, changed)?;
+            }
+        }
+        // A sealed secret: `{"sealed": "..."}`
+        other => decode_fields(other, encoding, changed)?,
+    }
+
+    Ok(())
+}
+
+/// Split an `encoding:` prefix off a field value
+pub(crate) fn split_prefix(s: &str, default: Encoding) -> Result<(Encoding, &str)> {
+    // None of the alphabets contain ':', so a prefix is unambiguous
+    match s.split_once(':') {
+        Some((encoding, data)) => Ok((encoding.parse()?, data)),
+        None => Ok((default, s)),
+    }
+}
+
+fn reencode_fields(value: &mut Value, encoding: Encoding) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if RESPONSE_FIELDS.contains(&name.as_str()) {
+                    reencode_field(field, encoding);
+                } else {
+                    reencode_fields(field, encoding);
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| reencode_fields(item, encoding)),
+        _ => {}
+    }
+}
+
+fn reencode_field(field: &mut Value, encoding: Encoding) {
+    match field {
+        Value::String(s) => {
+            if let Ok(bytes) = STANDARD.decode(&*s) {
+                *s = encoding.encode(&bytes);
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| reencode_field(item, encoding)),
+        other => reencode_fields(other, encoding),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Json, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    async fn echo(body: &str, query: &str) -> (u16, Value) {
+        // Echo the decoded request back, as text so it is not re-encoded
+        let router = Router::new()
+            .route(
+                "/v1/echo",
+                post(|Json(req): Json<Value>| async move {
+                    Json(json!({ "proof_data": req["proof_data"], "seen": req.to_string() }))
+                }),
+            )
+            .layer(middleware::from_fn(encoding_middleware));
+        let response = router
+            .oneshot(
+                Request::post(format!("/v1/echo{query}"))
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status().as_u16();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut body: Value = serde_json::from_slice(&body).unwrap();
+        if let Some(seen) = body["seen"].as_str() {
+            body["seen"] = serde_json::from_str(seen).unwrap();
+        }
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn test_request_parameter() {
+        let (status, body) = echo(
+            r#"{"proof_data": "0a0b0c", "amount": "10"}"#,
+            "?encoding=hex",
+        )
+        .await;
+
+        assert_eq!(status, 200);
+        // Handlers see base64, the client gets its own encoding back
+        assert_eq!(body["seen"]["proof_data"], STANDARD.encode([10, 11, 12]));
+        assert_eq!(body["seen"]["amount"], "10");
+        assert_eq!(body["proof_data"], "0a0b0c");
+    }
+
+    #[tokio::test]
+    async fn test_field_prefix() {
+        let base58 = bs58::encode([1u8; 64]).into_string();
+        let body = json!({
+            "request_signature": format!("base58:{base58}"),
+            "elgamal_signature": { "sealed": "hex:ff00" },
+            "commitment_blindings": ["hex:01", STANDARD.encode([2u8])],
+        });
+
+        let (status, body) = echo(&body.to_string(), "").await;
+
+        assert_eq!(status, 200);
+        let seen = &body["seen"];
+        assert_eq!(seen["request_signature"], STANDARD.encode([1u8; 64]));
+        assert_eq!(
+            seen["elgamal_signature"]["sealed"],
+            STANDARD.encode([0xff, 0])
+        );
+        assert_eq!(
+            seen["commitment_blindings"],
+            json!([STANDARD.encode([1u8]), STANDARD.encode([2u8])])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_encoding_rejected() {
+        let (status, body) = echo(r#"{"proof_data": "zz"}"#, "?encoding=hex").await;
+        assert_eq!(status, 400);
+        assert_eq!(body["code"], "VALIDATION_FAILED");
+        assert_eq!(body["fields"][0]["field"], "proof_data");
+
+        let (status, _) = echo(r#"{"proof_data": "AA=="}"#, "?encoding=base32").await;
+        assert_eq!(status, 400);
+
+        let long = format!(r#"{{"proof_data": "{}"}}"#, "1".repeat(MAX_FIELD_LEN + 1));
+        let (status, body) = echo(&long, "?encoding=base58").await;
+        assert_eq!(status, 400);
+        assert_eq!(body["fields"][0]["code"], "INVALID_LENGTH");
+    }
+
+    /// Property-based fuzzing of request transcoding
+    mod fuzz {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn transcode(value: &str, encoding: Encoding) -> Result<Value> {
+            let mut body = json!({ "proof_data": value });
+            decode_fields(&mut body, encoding, &mut false)?;
+            Ok(body["proof_data"].clone())
+        }
+
+        proptest! {
+            #[test]
+            fn transcoding_never_panics(s in "\\PC{0,200}") {
+                for encoding in [Encoding::Base64, Encoding::Base58, Encoding::Hex] {
+                    let _ = transcode(&s, encoding);
+                }
+            }
+
+            #[test]
+            fn transcoding_preserves_bytes(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+                let base64 = Value::String(STANDARD.encode(&bytes));
+                for encoding in [Encoding::Base64, Encoding::Base58, Encoding::Hex] {
+                    prop_assert_eq!(transcode(&encoding.encode(&bytes), encoding).unwrap(), base64.clone());
+                    let prefixed = format!("{}:{}", encoding.name(), encoding.encode(&bytes));
+                    prop_assert_eq!(transcode(&prefixed, Encoding::Base64).unwrap(), base64.clone());
+                }
+            }
+        }
+    }
+}
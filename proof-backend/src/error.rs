@@ -1,12 +1,17 @@
 //! Error types for the SVS Proof Backend
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use svs_proof_core::ProofError;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Seconds shed clients are asked to wait before retrying
+const RETRY_AFTER_SECS: u64 = 1;
 
 /// Backend error types
 #[derive(Debug, Error)]
@@ -14,48 +19,276 @@ pub enum BackendError {
     #[error("Invalid request: {0}")]
     BadRequest(String),
 
+    #[error("Invalid request: {}", describe_fields(.0))]
+    InvalidFields(Vec<FieldError>),
+
     #[error("Proof generation failed: {0}")]
     ProofGeneration(String),
 
     #[error("Invalid signature: {0}")]
     InvalidSignature(String),
 
-    #[error("Invalid public key: {0}")]
-    InvalidPubkey(String),
-
     #[error("Request expired: timestamp too old")]
     RequestExpired,
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Request already used")]
+    ReplayedRequest,
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    #[error("Server overloaded: {0}")]
+    Overloaded(String),
+
+    #[error("Shared state unavailable: {0}")]
+    StateStore(String),
+
+    #[error("Request timed out")]
+    Timeout,
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
 /// Error response body
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub code: String,
+
+    /// Each invalid field, for `VALIDATION_FAILED`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
 }
 
-impl IntoResponse for BackendError {
-    fn into_response(self) -> Response {
-        let (status, code) = match &self {
+/// An invalid request field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field, e.g. `commitment_blindings[2]` or `transfer.amount`
+    pub field: String,
+
+    /// Stable machine-readable reason: `MISSING`, `INVALID_ENCODING`,
+    /// `INVALID_LENGTH`, `INVALID_PUBKEY`, `INVALID_AMOUNT` or `INVALID_VALUE`
+    pub code: String,
+
+    /// Expected format, e.g. `base64, 32 bytes`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    pub message: String,
+}
+
+fn describe_fields(fields: &[FieldError]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("{}: {}", f.field, f.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl BackendError {
+    /// HTTP status and machine-readable code for this error
+    fn status_and_code(&self) -> (StatusCode, &'static str) {
+        match self {
             BackendError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BAD_REQUEST"),
+            BackendError::InvalidFields(_) => (StatusCode::BAD_REQUEST, "VALIDATION_FAILED"),
             BackendError::ProofGeneration(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "PROOF_GENERATION_FAILED")
             }
             BackendError::InvalidSignature(_) => (StatusCode::BAD_REQUEST, "INVALID_SIGNATURE"),
-            BackendError::InvalidPubkey(_) => (StatusCode::BAD_REQUEST, "INVALID_PUBKEY"),
             BackendError::RequestExpired => (StatusCode::BAD_REQUEST, "REQUEST_EXPIRED"),
+            BackendError::Forbidden(_) => (StatusCode::FORBIDDEN, "FORBIDDEN"),
+            BackendError::QuotaExceeded(_) => (StatusCode::TOO_MANY_REQUESTS, "QUOTA_EXCEEDED"),
+            BackendError::ReplayedRequest => (StatusCode::CONFLICT, "REPLAYED_REQUEST"),
+            BackendError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            BackendError::Rpc(_) => (StatusCode::BAD_GATEWAY, "RPC_ERROR"),
+            BackendError::Overloaded(_) => (StatusCode::SERVICE_UNAVAILABLE, "OVERLOADED"),
+            BackendError::StateStore(_) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "STATE_STORE_UNAVAILABLE")
+            }
+            BackendError::Timeout => (StatusCode::GATEWAY_TIMEOUT, "TIMEOUT"),
             BackendError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
-        };
+        }
+    }
 
-        let body = Json(ErrorResponse {
+    /// Error body as returned to clients
+    pub fn to_error_response(&self) -> ErrorResponse {
+        ErrorResponse {
             error: self.to_string(),
+            code: self.status_and_code().1.to_string(),
+            fields: match self {
+                BackendError::InvalidFields(fields) => fields.clone(),
+                _ => vec![],
+            },
+        }
+    }
+
+    /// A malformed value; attributed to a field with [`BackendError::for_field`]
+    pub fn invalid_format(code: &str, expected: &str, message: impl Into<String>) -> Self {
+        BackendError::InvalidFields(vec![FieldError {
+            field: String::new(),
             code: code.to_string(),
-        });
+            expected: Some(expected.to_string()),
+            message: message.into(),
+        }])
+    }
+
+    /// A required field that was not sent
+    pub fn missing() -> Self {
+        BackendError::InvalidFields(vec![FieldError {
+            field: String::new(),
+            code: "MISSING".to_string(),
+            expected: None,
+            message: "required field is missing".to_string(),
+        }])
+    }
+
+    /// Attribute a validation error to `field`
+    ///
+    /// Field errors get `field` as their path, or as a prefix of a nested
+    /// or indexed path; other errors are not about a field and are returned
+    /// unchanged.
+    pub fn for_field(self, field: &str) -> Self {
+        match self {
+            BackendError::InvalidFields(fields) => BackendError::InvalidFields(
+                fields
+                    .into_iter()
+                    .map(|mut f| {
+                        f.field = match f.field.chars().next() {
+                            None => field.to_string(),
+                            Some('[') => format!("{field}{}", f.field),
+                            Some(_) => format!("{field}.{}", f.field),
+                        };
+                        f
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// Collects the errors of several fields so a request reports all of them
+/// at once; see [`parse_fields!`]
+#[derive(Default)]
+pub struct FieldErrors {
+    fields: Vec<FieldError>,
+    other: Option<BackendError>,
+}
+
+impl FieldErrors {
+    /// The value of `result`, or `None` after recording its error against
+    /// `field`
+    pub fn check<T, E: Into<BackendError>>(
+        &mut self,
+        field: &str,
+        result: std::result::Result<T, E>,
+    ) -> Option<T> {
+        match result.map_err(|e| e.into().for_field(field)) {
+            Ok(value) => Some(value),
+            Err(BackendError::InvalidFields(fields)) => {
+                self.fields.extend(fields);
+                None
+            }
+            Err(other) => {
+                self.other.get_or_insert(other);
+                None
+            }
+        }
+    }
+
+    /// Fail with every recorded field error; errors that are not about a
+    /// field take precedence
+    pub fn finish(self) -> Result<()> {
+        match (self.other, self.fields.is_empty()) {
+            (Some(other), _) => Err(other),
+            (None, false) => Err(BackendError::InvalidFields(self.fields)),
+            (None, true) => Ok(()),
+        }
+    }
+}
+
+/// Reject a list field with more than `max` items
+pub fn max_items<T>(values: &[T], max: usize) -> Result<&[T]> {
+    if values.len() > max {
+        return Err(BackendError::invalid_format(
+            "INVALID_LENGTH",
+            &format!("at most {max} items"),
+            format!("expected at most {max} items, got {}", values.len()),
+        ));
+    }
+    Ok(values)
+}
+
+/// Parse every element of a list field of at most `max` items, reporting
+/// each invalid one by index
+pub fn parse_each<T, E: Into<BackendError>>(
+    values: &[String],
+    max: usize,
+    parse: impl Fn(&str) -> std::result::Result<T, E>,
+) -> Result<Vec<T>> {
+    max_items(values, max)?;
 
-        (status, body).into_response()
+    let mut errors = FieldErrors::default();
+    let parsed: Vec<_> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| errors.check(&format!("[{i}]"), parse(value)))
+        .collect();
+    errors.finish()?;
+
+    Ok(parsed)
+}
+
+/// Parse several request fields, reporting every invalid one at once
+///
+/// `parse_fields!(name = "field" => expr, ...)` binds each `name` to the
+/// value of its `Result` expression, or returns a `VALIDATION_FAILED` error
+/// listing every field whose expression failed.
+macro_rules! parse_fields {
+    ($($name:ident = $field:expr => $parse:expr),+ $(,)?) => {
+        let mut errors = $crate::error::FieldErrors::default();
+        $(let $name = errors.check($field, $parse);)+
+        errors.finish()?;
+        $(let $name = $name.expect("checked by finish");)+
+    };
+}
+pub(crate) use parse_fields;
+
+impl IntoResponse for BackendError {
+    fn into_response(self) -> Response {
+        let (status, _) = self.status_and_code();
+
+        let mut response = (status, Json(self.to_error_response())).into_response();
+        if matches!(self, BackendError::Overloaded(_)) {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+        }
+        response
+    }
+}
+
+impl From<ProofError> for BackendError {
+    fn from(err: ProofError) -> Self {
+        match err {
+            ProofError::InvalidInput(message) => BackendError::BadRequest(message),
+            ProofError::InvalidFormat {
+                code,
+                expected,
+                message,
+            } => BackendError::invalid_format(code, &expected, message),
+            ProofError::ProofGeneration(message) => BackendError::ProofGeneration(message),
+            ProofError::InvalidSignature(message) => BackendError::InvalidSignature(message),
+        }
     }
 }
 
@@ -67,3 +300,63 @@ impl From<anyhow::Error> for BackendError {
 
 /// Result type alias for backend operations
 pub type Result<T> = std::result::Result<T, BackendError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ProofGenerator;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    fn parse(amount: &str, blindings: &[String]) -> Result<(u64, usize)> {
+        parse_fields!(
+            amount = "amount" => ProofGenerator::parse_amount(amount),
+            openings = "commitment_blindings"
+                => parse_each(blindings, 8, ProofGenerator::parse_opening),
+        );
+        Ok((amount, openings.len()))
+    }
+
+    #[test]
+    fn test_every_invalid_field_reported() {
+        let valid = STANDARD.encode([1u8; 32]);
+        let blindings = vec![valid.clone(), valid, STANDARD.encode([1u8; 31])];
+
+        let body = parse("ten", &blindings).unwrap_err().to_error_response();
+
+        assert_eq!(body.code, "VALIDATION_FAILED");
+        assert_eq!(body.fields.len(), 2);
+        assert_eq!(body.fields[0].field, "amount");
+        assert_eq!(body.fields[0].code, "INVALID_AMOUNT");
+        assert_eq!(body.fields[1].field, "commitment_blindings[2]");
+        assert_eq!(body.fields[1].code, "INVALID_LENGTH");
+        assert_eq!(body.fields[1].expected.as_deref(), Some("base64, 32 bytes"));
+        assert!(body
+            .error
+            .contains("commitment_blindings[2]: expected 32 bytes, got 31"));
+    }
+
+    #[test]
+    fn test_oversized_list_rejected_before_parsing() {
+        let blindings = vec!["not base64".to_string(); 9];
+
+        let body = parse("10", &blindings).unwrap_err().to_error_response();
+
+        assert_eq!(body.fields.len(), 1);
+        assert_eq!(body.fields[0].field, "commitment_blindings");
+        assert_eq!(body.fields[0].code, "INVALID_LENGTH");
+    }
+
+    #[test]
+    fn test_nested_fields_and_other_errors() {
+        let err = BackendError::from(ProofGenerator::parse_pubkey("nope").unwrap_err())
+            .for_field("mint")
+            .for_field("transfer");
+        let body = err.to_error_response();
+        assert_eq!(body.fields[0].field, "transfer.mint");
+
+        // Errors that are not about a field pass through unchanged
+        let err = BackendError::Forbidden("no".to_string()).for_field("transfer");
+        assert!(matches!(err, BackendError::Forbidden(_)));
+        assert!(err.to_error_response().fields.is_empty());
+    }
+}
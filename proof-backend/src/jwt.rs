@@ -0,0 +1,312 @@
+//! JWT bearer authentication
+//!
+//! An alternative to static API keys: `Authorization: Bearer <jwt>` tokens
+//! issued by the integrator's identity provider, verified against a shared
+//! HMAC secret or the signing keys published at a JWKS URL.
+//!
+//! A verified token is treated as an API key named after its `sub` claim.
+//! Its scopes come from the configured claim (a space-separated string or an
+//! array), and usage and quotas are tracked per subject.
+
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+use crate::types::{ApiKeyConfig, ApiScope, JwtConfig};
+
+/// Scope value granting every endpoint
+const ALL_SCOPES: &str = "*";
+
+/// Minimum time between JWKS fetches triggered by unknown key ids
+const JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Smallest accepted shared secret, in bytes
+const MIN_SECRET_LEN: usize = 32;
+
+/// Claims read from a token; `exp`, `iss` and `aud` are checked by the
+/// validation
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(flatten)]
+    other: HashMap<String, Value>,
+}
+
+/// JWKS signing keys by key id
+#[derive(Default)]
+struct CachedJwks {
+    keys: HashMap<String, DecodingKey>,
+    fetched_at: Option<Instant>,
+}
+
+/// Verifies bearer tokens and maps them to API keys
+#[derive(Clone)]
+pub struct JwtVerifier {
+    config: Arc<JwtConfig>,
+    secret: Option<DecodingKey>,
+    jwks: Arc<RwLock<CachedJwks>>,
+    client: reqwest::Client,
+}
+
+impl JwtVerifier {
+    /// Build a verifier; exactly one of `secret` and `jwks_url` must be set
+    pub fn new(config: JwtConfig) -> anyhow::Result<Self> {
+        let secret = match (&config.secret, &config.jwks_url) {
+            (Some(secret), None) => {
+                if secret.len() < MIN_SECRET_LEN {
+                    anyhow::bail!("JWT secret must be at least {MIN_SECRET_LEN} bytes");
+                }
+                Some(DecodingKey::from_secret(secret.as_bytes()))
+            }
+            (None, Some(_)) => None,
+            _ => anyhow::bail!("JWT authentication needs exactly one of secret and jwks_url"),
+        };
+
+        Ok(Self {
+            config: Arc::new(config),
+            secret,
+            jwks: Arc::default(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Verify `token` and return the API key it stands for
+    pub async fn verify(&self, token: &str) -> anyhow::Result<ApiKeyConfig> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key = match &self.secret {
+            Some(secret) => secret.clone(),
+            None => {
+                let kid = header
+                    .kid
+                    .ok_or_else(|| anyhow::anyhow!("Token has no key id"))?;
+                self.jwks_key(&kid).await?
+            }
+        };
+
+        // The key family check rejects algorithms that do not match the key
+        let claims =
+            jsonwebtoken::decode::<Claims>(token, &key, &self.validation(header.alg))?.claims;
+
+        Ok(ApiKeyConfig {
+            key: format!("jwt:{}", claims.sub),
+            scopes: self.scopes(&claims),
+            name: Some(claims.sub),
+            quota: self.config.quota.clone(),
+            priority: self.config.priority,
+        })
+    }
+
+    fn validation(&self, alg: Algorithm) -> Validation {
+        let mut validation = Validation::new(alg);
+        let mut required = vec!["exp", "sub"];
+        if let Some(issuer) = &self.config.issuer {
+            validation.set_issuer(&[issuer]);
+            required.push("iss");
+        }
+        match &self.config.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                required.push("aud");
+            }
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required);
+
+        validation
+    }
+
+    /// Scopes granted by the token; a token without the claim gets none
+    fn scopes(&self, claims: &Claims) -> Option<Vec<ApiScope>> {
+        let values: Vec<&str> = match claims.other.get(&self.config.scope_claim) {
+            Some(Value::String(s)) => s.split_whitespace().collect(),
+            Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+
+        let prefix = &self.config.scope_prefix;
+        let names: Vec<&str> = values
+            .into_iter()
+            .filter_map(|value| value.strip_prefix(prefix.as_str()))
+            .collect();
+        if names.contains(&ALL_SCOPES) {
+            return None;
+        }
+
+        // Scopes meant for other services are ignored
+        Some(
+            names
+                .into_iter()
+                .filter_map(|name| serde_json::from_value(Value::String(name.to_string())).ok())
+                .collect(),
+        )
+    }
+
+    /// JWKS key for `kid`, refetching the set when the key is unknown or the
+    /// cached set is due for refresh
+    async fn jwks_key(&self, kid: &str) -> anyhow::Result<DecodingKey> {
+        let refresh = Duration::from_secs(self.config.jwks_refresh_secs);
+        let (cached, fetch) = {
+            let jwks = self.jwks.read().unwrap_or_else(|e| e.into_inner());
+            let age = jwks.fetched_at.map(|at| at.elapsed());
+            let cached = jwks.keys.get(kid).cloned();
+            let fetch = match (&cached, age) {
+                (_, None) => true,
+                (Some(_), Some(age)) => age >= refresh,
+                (None, Some(age)) => age >= JWKS_REFETCH_INTERVAL,
+            };
+            (cached, fetch)
+        };
+
+        if fetch {
+            match self.fetch_jwks().await {
+                Ok(keys) => {
+                    let mut jwks = self.jwks.write().unwrap_or_else(|e| e.into_inner());
+                    jwks.keys = keys;
+                    jwks.fetched_at = Some(Instant::now());
+                    if let Some(key) = jwks.keys.get(kid) {
+                        return Ok(key.clone());
+                    }
+                }
+                // Keep serving the cached keys while the provider is unreachable
+                Err(e) => warn!(error = %e, "Failed to fetch JWKS"),
+            }
+        }
+
+        cached.ok_or_else(|| anyhow::anyhow!("Unknown signing key {kid}"))
+    }
+
+    async fn fetch_jwks(&self) -> anyhow::Result<HashMap<String, DecodingKey>> {
+        let url = self.config.jwks_url.as_deref().unwrap_or_default();
+        let set: JwkSet = self
+            .client
+            .get(url)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let keys = parse_jwks(&set);
+        info!(keys = keys.len(), "Fetched JWKS");
+
+        Ok(keys)
+    }
+}
+
+/// Asymmetric signing keys of a JWKS by key id
+fn parse_jwks(set: &JwkSet) -> HashMap<String, DecodingKey> {
+    set.keys
+        .iter()
+        // Symmetric keys have no business in a published key set
+        .filter(|jwk| !matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)))
+        .filter_map(|jwk| {
+            let kid = jwk.common.key_id.clone()?;
+            let key = DecodingKey::from_jwk(jwk).ok()?;
+            Some((kid, key))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn verifier() -> JwtVerifier {
+        JwtVerifier::new(JwtConfig {
+            secret: Some(SECRET.to_string()),
+            issuer: Some("https://idp.example.com".to_string()),
+            scope_prefix: "svs:".to_string(),
+            ..JwtConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn token(claims: Value, secret: &str) -> String {
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn claims(scope: Value) -> Value {
+        json!({
+            "sub": "partner-1",
+            "iss": "https://idp.example.com",
+            "exp": jsonwebtoken::get_current_timestamp() + 600,
+            "scope": scope,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_claims_scoping() {
+        let verifier = verifier();
+
+        let key = verifier
+            .verify(&token(
+                claims(json!("svs:pubkey-validity svs:jobs other:read")),
+                SECRET,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(key.label(), "partner-1");
+        assert_eq!(key.key, "jwt:partner-1");
+        assert_eq!(
+            key.scopes,
+            Some(vec![ApiScope::PubkeyValidity, ApiScope::Jobs])
+        );
+
+        let admin = verifier
+            .verify(&token(claims(json!(["svs:*"])), SECRET))
+            .await
+            .unwrap();
+        assert!(admin.allows(ApiScope::Tx));
+
+        let unscoped = verifier
+            .verify(&token(claims(Value::Null), SECRET))
+            .await
+            .unwrap();
+        assert!(!unscoped.allows(ApiScope::Balance));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_tokens_rejected() {
+        let verifier = verifier();
+
+        let forged = token(claims(json!("svs:*")), "fedcba9876543210fedcba9876543210");
+        assert!(verifier.verify(&forged).await.is_err());
+
+        let mut expired = claims(json!("svs:*"));
+        expired["exp"] = json!(1_000_000);
+        assert!(verifier.verify(&token(expired, SECRET)).await.is_err());
+
+        let mut other_issuer = claims(json!("svs:*"));
+        other_issuer["iss"] = json!("https://evil.example.com");
+        assert!(verifier.verify(&token(other_issuer, SECRET)).await.is_err());
+    }
+
+    #[test]
+    fn test_config_needs_one_key_source() {
+        assert!(JwtVerifier::new(JwtConfig::default()).is_err());
+        assert!(JwtVerifier::new(JwtConfig {
+            secret: Some("short".to_string()),
+            ..JwtConfig::default()
+        })
+        .is_err());
+    }
+}
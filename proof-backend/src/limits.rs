@@ -0,0 +1,184 @@
+//! Load limits
+//!
+//! Proof generation is CPU-bound, so a burst of requests must not pile
+//! unbounded work onto the process:
+//! - API requests beyond `MAX_IN_FLIGHT_REQUESTS` are shed
+//! - proof work runs on the blocking pool, at most `MAX_CONCURRENT_PROOFS` at
+//!   a time; requests that find every slot busy are shed
+//! - each route group has a timeout
+//!
+//! Shed requests get `503` with `Retry-After`, timed out requests `504`. A
+//! timed out proof keeps its slot until the work finishes, so timeouts never
+//! let more proofs run than the limit allows.
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    response::{IntoResponse, Response},
+    BoxError, Router,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
+use tower::{load_shed::error::Overloaded, timeout::error::Elapsed, ServiceBuilder};
+use tracing::{warn, Span};
+
+use crate::error::{BackendError, Result};
+
+/// Runs proof work on the blocking pool with a bounded number of slots
+#[derive(Clone)]
+pub struct ProofLimiter {
+    slots: Arc<Semaphore>,
+}
+
+impl ProofLimiter {
+    /// Allow `max_concurrent` proofs at once
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// Run `work` on the blocking pool, or fail with `Overloaded` if every
+    /// slot is busy
+    pub async fn run<T, F>(&self, work: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let slot = self.slots.clone().try_acquire_owned().map_err(|_| {
+            warn!("All proof slots busy, shedding request");
+            BackendError::Overloaded("All proof workers are busy".to_string())
+        })?;
+
+        let span = Span::current();
+        tokio::task::spawn_blocking(move || {
+            // Held until the work finishes, even if the request timed out
+            let _slot = slot;
+            span.in_scope(work)
+        })
+        .await
+        .map_err(|e| BackendError::Internal(format!("Proof task failed: {e}")))?
+    }
+}
+
+/// Fail requests to `router` that take longer than `timeout`
+pub fn with_timeout(router: Router, timeout: Duration) -> Router {
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(layer_error))
+            .timeout(timeout),
+    )
+}
+
+/// Shed requests to `router` beyond `max_in_flight` (0 disables the limit)
+pub fn with_in_flight_limit(router: Router, max_in_flight: usize) -> Router {
+    if max_in_flight == 0 {
+        return router;
+    }
+
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(layer_error))
+            .load_shed()
+            .concurrency_limit(max_in_flight),
+    )
+}
+
+async fn layer_error(err: BoxError) -> Response {
+    let error = if err.is::<Overloaded>() {
+        warn!("Too many requests in flight, shedding request");
+        BackendError::Overloaded("Too many requests in flight".to_string())
+    } else if err.is::<Elapsed>() {
+        BackendError::Timeout
+    } else {
+        BackendError::Internal(err.to_string())
+    };
+
+    error.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        extract::{DefaultBodyLimit, Request},
+        http::StatusCode,
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_proof_limiter_sheds_when_busy() {
+        let limiter = ProofLimiter::new(1);
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+        let busy = tokio::spawn({
+            let limiter = limiter.clone();
+            async move {
+                limiter
+                    .run(move || {
+                        started_tx.send(()).unwrap();
+                        release_rx.recv().unwrap();
+                        Ok(1)
+                    })
+                    .await
+            }
+        });
+        tokio::task::spawn_blocking(move || started_rx.recv().unwrap())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            limiter.run(|| Ok(2)).await,
+            Err(BackendError::Overloaded(_))
+        ));
+
+        release_tx.send(()).unwrap();
+        assert_eq!(busy.await.unwrap().unwrap(), 1);
+        assert_eq!(limiter.run(|| Ok(3)).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_route_raises_body_limit() {
+        let app = Router::new()
+            .route("/single", post(|_: String| async { "ok" }))
+            .route(
+                "/batch",
+                post(|_: String| async { "ok" }).layer(DefaultBodyLimit::max(1024)),
+            )
+            .layer(DefaultBodyLimit::max(16));
+        let send = |path: &str| {
+            app.clone().oneshot(
+                Request::post(path)
+                    .body(Body::from("x".repeat(64)))
+                    .unwrap(),
+            )
+        };
+
+        assert_eq!(
+            send("/single").await.unwrap().status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(send("/batch").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_returns_504() {
+        let router = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "done"
+            }),
+        );
+        let app = with_timeout(router, Duration::from_millis(10));
+
+        let response = app
+            .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+}
@@ -3,74 +3,381 @@
 //! ZK proof generation backend for SVS-2 Confidential Vaults.
 //! Provides REST API endpoints for generating Token-2022 Confidential Transfer proofs.
 
+mod auth;
+mod cli;
+mod encoding;
 mod error;
+mod jwt;
+mod limits;
 mod routes;
+mod sealing;
 mod services;
+mod signing;
+mod telemetry;
+mod tls;
 mod types;
+mod versioning;
 
 use axum::{
-    body::Body,
-    extract::Request,
-    http::{header, HeaderMap, Method, StatusCode},
-    middleware::{self, Next},
-    response::Response,
-    Router,
+    extract::{DefaultBodyLimit, Request},
+    http::{header, Method},
+    middleware, Router, ServiceExt,
 };
-use std::sync::Arc;
+use std::{future::IntoFuture, sync::Arc, time::Duration};
+use tokio::sync::Notify;
+use tower::Layer;
 use tower_http::{
+    compression::CompressionLayer,
     cors::CorsLayer,
     limit::RequestBodyLimitLayer,
     trace::TraceLayer,
 };
-use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{error, info, warn};
 
-use routes::{health_router, proofs_router};
+use routes::{
+    admin_router, balance_router, docs_router, estimate_router, health_router, jobs_router,
+    keys_router, proofs::AppState, proofs_router, relay_router, sealing_key_router,
+    service_key_router, tx_router, usage_router, HealthState, Readiness,
+};
+use services::{
+    job_store, AccountStateFetcher, ApiKeyStore, DurableJobStore, IdempotencyStore, JobQueue,
+    PostgresStore, PubkeyValidityCache, RedisStore, Relayer, ReplayCache, SelfTest, UsageTracker,
+};
 use types::Config;
 
 #[tokio::main]
 async fn main() {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "svs_proof_backend=info,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // `prove` generates a single proof offline instead of serving
+    if std::env::args().nth(1).as_deref() == Some("prove") {
+        std::process::exit(cli::run(std::env::args().skip(2)));
+    }
 
     // Load configuration
-    let config = Arc::new(Config::from_env());
+    let config = match Config::load() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    // Initialize tracing, with OTLP export when an endpoint is configured
+    let tracer_provider = telemetry::init(&config);
 
     info!(port = config.port, "Starting SVS Proof Backend");
     info!(
         cors_origins = ?config.cors_origins,
         api_keys_configured = !config.api_keys.is_empty(),
+        admin_api = config.admin_api_key.is_some(),
+        jwt_auth = config.jwt.is_some(),
+        relayer = config.relayer.is_some(),
+        otlp_export = tracer_provider.is_some(),
         "Configuration loaded"
     );
 
+    // Replicas share replay, usage, idempotency and job state through Redis
+    let shared = match &config.redis_url {
+        Some(url) => match RedisStore::connect(url, &config.redis_key_prefix).await {
+            Ok(store) => Some(store),
+            Err(e) => {
+                error!("Failed to connect to Redis: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    info!(shared_state = shared.is_some(), "State store ready");
+
+    // Proof jobs run on the blocking pool, bounded by JOB_WORKERS
+    let job_queue = JobQueue::new(
+        config.job_workers,
+        Duration::from_secs(config.job_max_wait_secs),
+        Duration::from_secs(config.job_ttl_secs),
+        shared.clone(),
+    );
+
+    // Durable jobs persist their requests, encrypted, in Postgres or Redis so
+    // another replica can take them over
+    let job_queue = if config.durable_jobs {
+        let cipher = match &config.job_store_key {
+            Some(key) => job_store::PayloadCipher::new(key),
+            None => Err(anyhow::anyhow!(
+                "DURABLE_JOBS requires JOB_STORE_KEY, and JOB_DATABASE_URL or REDIS_URL"
+            )),
+        };
+        let store = match (cipher, &config.job_database_url, &shared) {
+            (Err(e), _, _) => Err(e),
+            (Ok(cipher), Some(url), _) => PostgresStore::connect(url)
+                .await
+                .map(|store| DurableJobStore::postgres(store, cipher))
+                .map_err(|e| anyhow::anyhow!("Failed to connect to the job database: {e}")),
+            (Ok(cipher), None, Some(shared)) => Ok(DurableJobStore::new(shared.clone(), cipher)),
+            (Ok(_), None, None) => Err(anyhow::anyhow!(
+                "DURABLE_JOBS requires JOB_DATABASE_URL or REDIS_URL"
+            )),
+        };
+        match store {
+            Ok(store) => job_queue.with_durable_store(store),
+            Err(e) => {
+                error!("{e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        job_queue
+    };
+    info!(durable_jobs = job_queue.is_durable(), "Job queue ready");
+
+    // Retried submissions resolve to their job for as long as it is kept
+    let idempotency =
+        IdempotencyStore::new(Duration::from_secs(config.job_ttl_secs), shared.clone());
+
+    // Request signatures are remembered for as long as their timestamp is accepted
+    let replay_cache = ReplayCache::new(
+        config.timestamp_tolerance_secs,
+        config.replay_cache_capacity,
+        shared.clone(),
+    );
+
+    // Pubkey validity proofs are reusable per keypair; a zero TTL disables caching
+    let pubkey_validity_cache = PubkeyValidityCache::new(
+        Duration::from_secs(config.pubkey_proof_cache_ttl_secs),
+        config.pubkey_proof_cache_capacity,
+    );
+
+    // Key-derivation signatures can be sealed to an in-memory X25519 key
+    let sealing = sealing::SealingKeys::new(
+        config.sealing_key_rotation_secs,
+        config.require_sealed_secrets,
+    );
+    sealing.spawn_rotation();
+
+    // Keys created through the admin API, persisted in Redis when shared
+    let api_keys = match ApiKeyStore::load(shared.clone()).await {
+        Ok(store) => store,
+        Err(e) => {
+            error!("Failed to load API keys: {e}");
+            std::process::exit(1);
+        }
+    };
+    api_keys.spawn_sync();
+    if config.admin_api_key.is_some() && shared.is_none() {
+        warn!("Admin API enabled without REDIS_URL; created API keys are lost on restart");
+    }
+
+    // Bearer tokens are accepted alongside API keys when configured
+    let jwt = match config.jwt.clone().map(jwt::JwtVerifier::new).transpose() {
+        Ok(jwt) => jwt,
+        Err(e) => {
+            eprintln!("Invalid JWT configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // Relayed transactions are paid for by the relayer's fee payer
+    let relayer = match Relayer::load(&config, shared.clone()) {
+        Ok(relayer) => relayer,
+        Err(e) => {
+            error!("Invalid relayer configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Some(relayer) = &relayer {
+        info!(fee_payer = %relayer.pubkey(), "Transaction relayer enabled");
+    }
+
+    let state = AppState {
+        config: config.clone(),
+        replay_cache,
+        pubkey_validity_cache,
+        usage: UsageTracker::new(shared),
+        sealing: sealing.clone(),
+        // Omitted balance ciphertexts are read from the token account
+        accounts: AccountStateFetcher::new(config.rpc_url.clone()),
+        // Synchronous proofs run on the blocking pool, bounded
+        proof_limiter: limits::ProofLimiter::new(config.max_concurrent_proofs),
+        jwt,
+        api_keys,
+        relayer,
+    };
+
+    // Take over persisted jobs of stopped replicas, including this one's
+    routes::jobs::spawn_job_recovery(&job_queue, state.clone());
+
+    // Successful responses are signed with the service key
+    let signer = match signing::ResponseSigner::load(config.service_keypair_path.as_deref()) {
+        Ok(signer) => signer,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    };
+    info!(service_key = %signer.pubkey(), "Response signing enabled");
+
+    // Health reports not-ready once shutdown starts
+    let readiness = Readiness::default();
+
+    // Readiness also requires a recent passing proof self-test
+    let self_test = SelfTest::new(config.self_test_interval_secs);
+    self_test.spawn();
+
+    // HTTPS unless plain HTTP is explicitly allowed
+    let tls_acceptor = match tls::load_acceptor(&config) {
+        Ok(acceptor) => acceptor,
+        Err(e) => {
+            error!("{e:#}");
+            std::process::exit(1);
+        }
+    };
+    match &tls_acceptor {
+        Some(_) => info!(
+            client_auth = config.tls_client_ca_path.is_some(),
+            "TLS enabled"
+        ),
+        None => warn!("Serving plain HTTP; TLS must be terminated in front of this server"),
+    }
+
+    // Unversioned /api/* paths are deprecated aliases of /v1/*
+    let version_policy = match versioning::VersionPolicy::new(
+        config.legacy_api_enabled,
+        config.legacy_api_sunset.as_deref(),
+    ) {
+        Ok(policy) => policy,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    };
+
     // Build CORS layer
     let cors = build_cors_layer(&config);
 
-    // Build the router
+    // Proof-generating routes get a longer timeout than the rest
+    let proof_routes = Router::new()
+        .merge(proofs_router(state.clone()))
+        .merge(balance_router(state.clone()))
+        .merge(tx_router(state.clone()))
+        .merge(relay_router(state.clone()));
+    let other_routes = Router::new()
+        .merge(keys_router(state.clone()))
+        .merge(usage_router(state.clone()))
+        .merge(estimate_router())
+        .merge(jobs_router(state.clone(), job_queue.clone(), idempotency));
+    let api = Router::new()
+        .merge(limits::with_timeout(
+            proof_routes,
+            Duration::from_secs(config.proof_timeout_secs),
+        ))
+        .merge(limits::with_timeout(
+            other_routes,
+            Duration::from_secs(config.request_timeout_secs),
+        ));
+    let api = limits::with_in_flight_limit(api, config.max_in_flight_requests);
+
+    // Build the router; health checks, docs, keys and the admin API are exempt
+    // from load shedding
     let app = Router::new()
-        .merge(health_router())
-        .merge(proofs_router(config.clone()))
+        .merge(health_router(HealthState {
+            readiness: readiness.clone(),
+            self_test,
+            job_queue: job_queue.clone(),
+        }))
+        .merge(docs_router())
+        .merge(service_key_router(signer.clone()))
+        .merge(sealing_key_router(sealing))
+        .merge(admin_router(state.clone()))
+        .merge(api)
+        // Inside signing, so signatures cover the bodies as sent and received
+        .layer(middleware::from_fn(encoding::encoding_middleware))
+        .layer(middleware::from_fn_with_state(
+            signer,
+            signing::sign_response_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
-            config.clone(),
-            api_key_middleware,
+            state,
+            auth::api_key_middleware,
         ))
         .layer(cors)
-        .layer(RequestBodyLimitLayer::new(64 * 1024)) // 64KB max request body
-        .layer(TraceLayer::new_for_http());
+        // Batch routes raise the default limit; the outer limit bounds buffering
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
+        .layer(RequestBodyLimitLayer::new(
+            config.max_body_bytes.max(config.max_batch_body_bytes),
+        ))
+        // Outside signing, so signatures cover the uncompressed body
+        .layer(CompressionLayer::new())
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::make_request_span));
+
+    // Legacy paths are rewritten before routing
+    let app =
+        middleware::from_fn_with_state(version_policy, versioning::version_middleware).layer(app);
 
     // Start server
     let addr = format!("0.0.0.0:{}", config.port);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    let listener = tls::ServerListener::new(listener, tls_acceptor);
 
     info!(address = %addr, "Server listening");
 
-    axum::serve(listener, app).await.unwrap();
+    // Stop accepting connections on shutdown, then let in-flight work finish
+    let shutdown = Arc::new(Notify::new());
+    let server = tokio::spawn(
+        axum::serve(listener, ServiceExt::<Request>::into_make_service(app))
+            .with_graceful_shutdown({
+                let shutdown = shutdown.clone();
+                async move { shutdown.notified().await }
+            })
+            .into_future(),
+    );
+
+    shutdown_signal().await;
+    let grace = Duration::from_secs(config.shutdown_grace_secs);
+    info!(
+        grace_secs = grace.as_secs(),
+        "Shutdown signal received, draining"
+    );
+    readiness.start_draining();
+    shutdown.notify_one();
+
+    let drained = tokio::time::timeout(grace, async {
+        let _ = server.await;
+        job_queue.wait_idle().await;
+    })
+    .await;
+    match drained {
+        Ok(()) => info!("Drained, shutting down"),
+        Err(_) => warn!("Grace period elapsed with work in flight, shutting down"),
+    }
+
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            warn!(error = %e, "Failed to flush traces");
+        }
+    }
+}
+
+/// Resolve on SIGTERM or Ctrl-C
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
 
 /// Build CORS layer from config
@@ -84,41 +391,26 @@ fn build_cors_layer(config: &Config) -> CorsLayer {
     CorsLayer::new()
         .allow_origin(origins)
         .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::HeaderName::from_static("x-api-key")])
-}
-
-/// API key authentication middleware
-async fn api_key_middleware(
-    axum::extract::State(config): axum::extract::State<Arc<Config>>,
-    headers: HeaderMap,
-    request: Request<Body>,
-    next: Next,
-) -> Result<Response, StatusCode> {
-    // Skip auth for health check
-    if request.uri().path() == "/health" {
-        return Ok(next.run(request).await);
-    }
-
-    // If no API keys configured, allow all requests (development mode)
-    if config.api_keys.is_empty() {
-        warn!("No API keys configured - running in development mode");
-        return Ok(next.run(request).await);
-    }
-
-    // Check for API key header
-    let api_key = headers
-        .get("x-api-key")
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| {
-            warn!("Request missing API key");
-            StatusCode::UNAUTHORIZED
-        })?;
-
-    // Validate API key (don't log the actual key for security)
-    if !config.api_keys.contains(&api_key.to_string()) {
-        warn!("Invalid API key provided");
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    Ok(next.run(request).await)
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::HeaderName::from_static("x-api-key"),
+            header::HeaderName::from_static("traceparent"),
+            header::HeaderName::from_static("tracestate"),
+            header::HeaderName::from_static(versioning::API_VERSION_HEADER),
+            header::HeaderName::from_static(routes::jobs::IDEMPOTENCY_KEY_HEADER),
+        ])
+        .expose_headers([
+            header::HeaderName::from_static(signing::SIGNATURE_HEADER),
+            header::HeaderName::from_static(signing::REQUEST_HASH_HEADER),
+            header::HeaderName::from_static(versioning::API_VERSION_HEADER),
+            header::HeaderName::from_static("deprecation"),
+            header::HeaderName::from_static("sunset"),
+            header::LINK,
+            header::HeaderName::from_static(auth::USAGE_REQUESTS_HEADER),
+            header::HeaderName::from_static(auth::USAGE_PROOF_MS_HEADER),
+            header::HeaderName::from_static(auth::QUOTA_REMAINING_HEADERS[0]),
+            header::HeaderName::from_static(auth::QUOTA_REMAINING_HEADERS[1]),
+            header::HeaderName::from_static(auth::QUOTA_REMAINING_HEADERS[2]),
+        ])
 }
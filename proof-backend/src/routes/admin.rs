@@ -0,0 +1,196 @@
+//! API key administration endpoints
+//!
+//! Create, rotate, re-scope and revoke API keys at runtime. Authenticated
+//! with `ADMIN_API_KEY` in the `X-Admin-Key` header instead of an API key;
+//! not served when it is unset.
+
+use axum::{
+    body::Body,
+    extract::{Path, Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post, put},
+    Json, Router,
+};
+use solana_sdk::hash::hash;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+use super::proofs::AppState;
+use crate::{
+    error::{ErrorResponse, Result},
+    types::{
+        ApiKeyInfo, ApiKeyListResponse, ApiKeySecretResponse, ApiKeySettings, RotateApiKeyRequest,
+    },
+};
+
+/// Prefix of the admin endpoints, which skip API key authentication
+pub const ADMIN_PATH_PREFIX: &str = "/v1/admin/";
+
+/// Header carrying the admin key
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// Create admin router; empty unless an admin key is configured
+pub fn admin_router(state: AppState) -> Router {
+    if state.config.admin_api_key.is_none() {
+        return Router::new();
+    }
+
+    Router::new()
+        .route("/v1/admin/keys", get(list_keys).post(create_key))
+        .route("/v1/admin/keys/{id}", put(update_key).delete(revoke_key))
+        .route("/v1/admin/keys/{id}/rotate", post(rotate_key))
+        .layer(middleware::from_fn_with_state(state.clone(), admin_auth))
+        .with_state(state)
+}
+
+/// Require the admin key, compared as a digest in constant time
+async fn admin_auth(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> std::result::Result<Response, StatusCode> {
+    let Some(admin_key) = &state.config.admin_api_key else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let presented = request
+        .headers()
+        .get(ADMIN_KEY_HEADER)
+        .map(|v| hash(v.as_bytes()))
+        .ok_or_else(|| {
+            warn!("Admin request missing admin key");
+            StatusCode::UNAUTHORIZED
+        })?;
+    let expected = hash(admin_key.as_bytes());
+    if !bool::from(expected.as_ref().ct_eq(presented.as_ref())) {
+        warn!("Invalid admin key provided");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// List managed API keys
+///
+/// GET /v1/admin/keys
+///
+/// Keys from the configuration are not listed, and key values never are.
+#[utoipa::path(
+    get,
+    path = "/v1/admin/keys",
+    responses(
+        (status = 200, body = ApiKeyListResponse),
+        (status = "4XX", body = ErrorResponse),
+    ),
+    security(("admin_key" = [])),
+    tag = "admin"
+)]
+async fn list_keys(State(state): State<AppState>) -> Json<ApiKeyListResponse> {
+    Json(ApiKeyListResponse {
+        keys: state.api_keys.list(),
+    })
+}
+
+/// Create an API key
+///
+/// POST /v1/admin/keys
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys",
+    request_body = ApiKeySettings,
+    responses(
+        (status = 201, body = ApiKeySecretResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    security(("admin_key" = [])),
+    tag = "admin"
+)]
+async fn create_key(
+    State(state): State<AppState>,
+    Json(settings): Json<ApiKeySettings>,
+) -> Result<(StatusCode, Json<ApiKeySecretResponse>)> {
+    let (info, key) = state.api_keys.create(settings).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiKeySecretResponse { info, key }),
+    ))
+}
+
+/// Replace the name, scopes, quota and priority of an API key
+///
+/// PUT /v1/admin/keys/{id}
+#[utoipa::path(
+    put,
+    path = "/v1/admin/keys/{id}",
+    params(("id" = String, Path, description = "Key id")),
+    request_body = ApiKeySettings,
+    responses(
+        (status = 200, body = ApiKeyInfo),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    security(("admin_key" = [])),
+    tag = "admin"
+)]
+async fn update_key(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(settings): Json<ApiKeySettings>,
+) -> Result<Json<ApiKeyInfo>> {
+    Ok(Json(state.api_keys.update(&id, settings).await?))
+}
+
+/// Issue a new value for an API key
+///
+/// POST /v1/admin/keys/{id}/rotate
+///
+/// The key keeps its id, settings and usage.
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys/{id}/rotate",
+    params(("id" = String, Path, description = "Key id")),
+    request_body = RotateApiKeyRequest,
+    responses(
+        (status = 200, body = ApiKeySecretResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    security(("admin_key" = [])),
+    tag = "admin"
+)]
+async fn rotate_key(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<RotateApiKeyRequest>,
+) -> Result<Json<ApiKeySecretResponse>> {
+    let grace = Duration::from_secs(req.grace_secs);
+    let (info, key) = state.api_keys.rotate(&id, grace).await?;
+
+    Ok(Json(ApiKeySecretResponse { info, key }))
+}
+
+/// Revoke an API key
+///
+/// DELETE /v1/admin/keys/{id}
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/keys/{id}",
+    params(("id" = String, Path, description = "Key id")),
+    responses(
+        (status = 204),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    security(("admin_key" = [])),
+    tag = "admin"
+)]
+async fn revoke_key(State(state): State<AppState>, Path(id): Path<String>) -> Result<StatusCode> {
+    state.api_keys.revoke(&id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
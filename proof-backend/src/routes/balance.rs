@@ -0,0 +1,326 @@
+//! Balance decryption and encryption endpoints
+
+use axum::{extract::State, routing::post, Json, Router};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::signature::Signature;
+use tracing::info;
+
+use super::proofs::{validate_request, AppState};
+use crate::{
+    error::{parse_fields, BackendError, ErrorResponse, Result},
+    services::{account_state::parse_required, ProofGenerator},
+    types::{
+        ApplyPendingRequest, ApplyPendingResponse, BalanceDecryptRequest, BalanceDecryptResponse,
+        BalanceEncryptRequest, BalanceEncryptResponse,
+    },
+};
+
+/// Create balance router
+pub fn balance_router(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/balance/decrypt", post(decrypt_balance))
+        .route("/v1/balance/encrypt", post(encrypt_balance))
+        .route("/v1/balance/apply-pending", post(apply_pending))
+        .with_state(state)
+}
+
+/// Decrypt available and pending balances
+///
+/// POST /v1/balance/decrypt
+#[utoipa::path(
+    post,
+    path = "/v1/balance/decrypt",
+    request_body = BalanceDecryptRequest,
+    responses(
+        (status = 200, body = BalanceDecryptResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "balance"
+)]
+async fn decrypt_balance(
+    State(state): State<AppState>,
+    Json(mut req): Json<BalanceDecryptRequest>,
+) -> Result<Json<BalanceDecryptResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        "Decrypting balance"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    let account_state = state.accounts.resolve(&mut req).await?;
+
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => state.sealing.open(&req.elgamal_signature),
+        available_balance = "available_balance"
+            => parse_required(&req.available_balance, ProofGenerator::parse_ciphertext),
+        pending_balance_lo = "pending_balance_lo"
+            => parse_required(&req.pending_balance_lo, ProofGenerator::parse_ciphertext),
+        pending_balance_hi = "pending_balance_hi"
+            => parse_required(&req.pending_balance_hi, ProofGenerator::parse_ciphertext),
+        decryptable_balance = "decryptable_available_balance" => req
+            .decryptable_available_balance
+            .as_deref()
+            .map(ProofGenerator::parse_ae_ciphertext)
+            .transpose(),
+        ae_signature = "ae_signature"
+            => req.ae_signature.as_ref().map(|s| state.sealing.open(s)).transpose(),
+    );
+
+    // Verify request signature
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+
+    // Derive ElGamal keypair
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+
+    // Prefer the AE ciphertext for the available balance: it is exact for any
+    // u64, while the ElGamal discrete log only recovers values below 2^32
+    let ae_available = match (decryptable_balance, ae_signature) {
+        (Some(decryptable_balance), Some(ae_signature)) => {
+            let ae_signature = Signature::from(*ae_signature);
+            ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, &ae_signature)?;
+            let ae_key = ProofGenerator::derive_ae_key(&ae_signature)?;
+            Some(ProofGenerator::decrypt_ae_balance(
+                &ae_key,
+                &decryptable_balance,
+            )?)
+        }
+        (None, None) => None,
+        _ => {
+            return Err(BackendError::BadRequest(
+                "decryptable_available_balance and ae_signature must be provided together"
+                    .to_string(),
+            ))
+        }
+    };
+
+    // Discrete logs are CPU-bound
+    let (available, pending) = state
+        .proof_limiter
+        .run(move || {
+            let available = match ae_available {
+                Some(available) => available,
+                None => {
+                    ProofGenerator::decrypt_elgamal_balance(&elgamal_keypair, &available_balance)?
+                }
+            };
+            let pending = ProofGenerator::decrypt_pending_balance(
+                &elgamal_keypair,
+                &pending_balance_lo,
+                &pending_balance_hi,
+            )?;
+            Ok((available, pending))
+        })
+        .await?;
+
+    info!("Decrypted balance");
+
+    Ok(Json(BalanceDecryptResponse {
+        available_balance: available.to_string(),
+        pending_balance: pending.to_string(),
+        pending_balance_credit_counter: account_state
+            .map(|account| account.pending_balance_credit_counter),
+    }))
+}
+
+/// Compute a decryptable (AE) balance
+///
+/// POST /v1/balance/encrypt
+#[utoipa::path(
+    post,
+    path = "/v1/balance/encrypt",
+    request_body = BalanceEncryptRequest,
+    responses(
+        (status = 200, body = BalanceEncryptResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "balance"
+)]
+async fn encrypt_balance(
+    State(state): State<AppState>,
+    Json(req): Json<BalanceEncryptRequest>,
+) -> Result<Json<BalanceEncryptResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        "Encrypting decryptable balance"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        ae_signature = "ae_signature" => state.sealing.open(&req.ae_signature),
+        balance = "balance" => ProofGenerator::parse_amount(&req.balance),
+    );
+    let ae_signature = Signature::from(*ae_signature);
+
+    // Verify request and AE key signatures
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+    ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, &ae_signature)?;
+
+    let ae_key = ProofGenerator::derive_ae_key(&ae_signature)?;
+    let decryptable_balance = ProofGenerator::encrypt_ae_balance(&ae_key, balance);
+
+    Ok(Json(BalanceEncryptResponse {
+        decryptable_balance: STANDARD.encode(decryptable_balance),
+    }))
+}
+
+/// Compute the arguments of `ApplyPendingBalance`
+///
+/// POST /v1/balance/apply-pending
+#[utoipa::path(
+    post,
+    path = "/v1/balance/apply-pending",
+    request_body = ApplyPendingRequest,
+    responses(
+        (status = 200, body = ApplyPendingResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "balance"
+)]
+async fn apply_pending(
+    State(state): State<AppState>,
+    Json(mut req): Json<ApplyPendingRequest>,
+) -> Result<Json<ApplyPendingResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        "Applying pending balance"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // The counter must describe the same snapshot as the pending ciphertexts,
+    // so they are either all sent or all read from the account
+    let pending_fields = [
+        req.pending_balance_lo.is_some(),
+        req.pending_balance_hi.is_some(),
+        req.pending_balance_credit_counter.is_some(),
+    ];
+    if pending_fields.contains(&true) && pending_fields.contains(&false) {
+        return Err(BackendError::BadRequest(
+            "pending_balance_lo, pending_balance_hi and pending_balance_credit_counter must be \
+             provided together"
+                .to_string(),
+        ));
+    }
+
+    // Read omitted balances from the token account
+    let account_state = state.accounts.resolve(&mut req).await?;
+    let expected_counter = req
+        .pending_balance_credit_counter
+        .or(account_state.map(|account| account.pending_balance_credit_counter))
+        .ok_or_else(|| BackendError::Internal("Account state was not resolved".to_string()))?;
+
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => state.sealing.open(&req.elgamal_signature),
+        ae_signature = "ae_signature" => state.sealing.open(&req.ae_signature),
+        pending_balance_lo = "pending_balance_lo"
+            => parse_required(&req.pending_balance_lo, ProofGenerator::parse_ciphertext),
+        pending_balance_hi = "pending_balance_hi"
+            => parse_required(&req.pending_balance_hi, ProofGenerator::parse_ciphertext),
+        decryptable_balance = "decryptable_available_balance" => parse_required(
+            &req.decryptable_available_balance,
+            ProofGenerator::parse_ae_ciphertext
+        ),
+    );
+    let ae_signature = Signature::from(*ae_signature);
+
+    // Verify request and AE key signatures
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+    ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, &ae_signature)?;
+
+    // Derive keys
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+    let ae_key = ProofGenerator::derive_ae_key(&ae_signature)?;
+
+    // Discrete logs are CPU-bound
+    let applied = state
+        .proof_limiter
+        .run(move || {
+            Ok(ProofGenerator::apply_pending_balance(
+                &elgamal_keypair,
+                &ae_key,
+                &decryptable_balance,
+                &pending_balance_lo,
+                &pending_balance_hi,
+            )?)
+        })
+        .await?;
+
+    info!(
+        expected_pending_balance_credit_counter = expected_counter,
+        "Computed pending balance application"
+    );
+
+    Ok(Json(ApplyPendingResponse {
+        available_balance: applied.available_balance.to_string(),
+        pending_balance: applied.pending_balance.to_string(),
+        new_available_balance: applied.new_available_balance.to_string(),
+        expected_pending_balance_credit_counter: expected_counter,
+        new_decryptable_available_balance: STANDARD
+            .encode(applied.new_decryptable_available_balance),
+    }))
+}
@@ -0,0 +1,131 @@
+//! OpenAPI document and Swagger UI
+
+use axum::Router;
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use super::{
+    admin, balance, estimate, health, jobs, keys, proofs, relay, sealing_key, service_key, tx,
+    usage,
+};
+
+/// Path of the generated OpenAPI document
+pub const OPENAPI_PATH: &str = "/v1/openapi.json";
+
+/// Path of the Swagger UI
+pub const SWAGGER_UI_PATH: &str = "/docs";
+
+/// OpenAPI description of the backend, generated from the handler annotations
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "SVS Proof Backend",
+        description = "ZK proof generation backend for SVS-2 Confidential Vaults"
+    ),
+    paths(
+        health::health_check,
+        health::readiness_check,
+        service_key::service_key,
+        sealing_key::sealing_key,
+        proofs::pubkey_validity,
+        proofs::pubkey_validity_batch,
+        proofs::equality_proof,
+        proofs::range_proof,
+        proofs::transfer_proof,
+        proofs::transfer_package,
+        proofs::zero_ciphertext_proof,
+        proofs::withdraw_bundle,
+        balance::decrypt_balance,
+        balance::encrypt_balance,
+        balance::apply_pending,
+        keys::elgamal_pubkey,
+        tx::withdraw_tx,
+        tx::context_state,
+        relay::relay_info,
+        relay::relay_submit,
+        estimate::estimate,
+        jobs::submit_job,
+        jobs::get_job,
+        jobs::job_events,
+        usage::get_usage,
+        admin::list_keys,
+        admin::create_key,
+        admin::update_key,
+        admin::rotate_key,
+        admin::revoke_key,
+    ),
+    modifiers(&ApiKeyAuth),
+    security(("api_key" = [])),
+    tags(
+        (name = "health", description = "Service status"),
+        (name = "proofs", description = "Token-2022 confidential transfer proof generation"),
+        (name = "balance", description = "Confidential balance decryption and encryption"),
+        (name = "keys", description = "Confidential transfer key derivation"),
+        (name = "tx", description = "SVS-2 transaction building"),
+        (name = "relay", description = "Gasless transaction submission"),
+        (name = "estimate", description = "Cost previews"),
+        (name = "jobs", description = "Asynchronous proof generation"),
+        (name = "usage", description = "API key metering"),
+        (name = "admin", description = "API key management"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `X-API-Key` and `X-Admin-Key` header schemes
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))),
+        );
+        components.add_security_scheme(
+            "admin_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(admin::ADMIN_KEY_HEADER))),
+        );
+    }
+}
+
+/// Create docs router serving the OpenAPI document and Swagger UI
+pub fn docs_router() -> Router {
+    SwaggerUi::new(SWAGGER_UI_PATH)
+        .url(OPENAPI_PATH, ApiDoc::openapi())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_covers_endpoints() {
+        let doc = ApiDoc::openapi();
+
+        for path in [
+            "/health",
+            "/health/ready",
+            "/v1/proofs/pubkey-validity/batch",
+            "/v1/proofs/transfer",
+            "/v1/proofs/withdraw-bundle",
+            "/v1/balance/decrypt",
+            "/v1/balance/apply-pending",
+            "/v1/tx/withdraw",
+            "/v1/relay/submit",
+            "/v1/estimate",
+            "/v1/jobs/{id}",
+            "/v1/admin/keys/{id}/rotate",
+        ] {
+            assert!(doc.paths.paths.contains_key(path), "missing {path}");
+        }
+
+        let schemas = doc.components.unwrap().schemas;
+        for schema in ["TransferProofRequest", "JobRequest", "ErrorResponse"] {
+            assert!(schemas.contains_key(schema), "missing {schema}");
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Cost estimate endpoint
+
+use axum::{routing::post, Json, Router};
+use solana_sdk::rent::Rent;
+
+use crate::{
+    error::{BackendError, ErrorResponse, Result},
+    services::TxBuilder,
+    types::{EstimateRequest, EstimateResponse, ProofCostEstimate},
+};
+
+/// Path of the estimate endpoint, open to every authenticated key and not
+/// metered
+pub const ESTIMATE_PATH: &str = "/v1/estimate";
+
+/// Most operations one estimate can describe
+const MAX_ESTIMATE_COUNT: u32 = 1_000;
+
+/// Base fee per transaction signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Create estimate router
+pub fn estimate_router() -> Router {
+    Router::new().route(ESTIMATE_PATH, post(estimate))
+}
+
+/// Estimate proof sizes, rent, transactions and compute units of an
+/// operation without generating any proofs
+///
+/// POST /v1/estimate
+#[utoipa::path(
+    post,
+    path = "/v1/estimate",
+    request_body = EstimateRequest,
+    responses(
+        (status = 200, body = EstimateResponse),
+        (status = "4XX", body = ErrorResponse),
+    ),
+    tag = "estimate"
+)]
+async fn estimate(Json(req): Json<EstimateRequest>) -> Result<Json<EstimateResponse>> {
+    if !(1..=MAX_ESTIMATE_COUNT).contains(&req.count) {
+        return Err(BackendError::invalid_format(
+            "INVALID_VALUE",
+            &format!("1 to {MAX_ESTIMATE_COUNT}"),
+            format!(
+                "expected 1 to {MAX_ESTIMATE_COUNT} operations, got {}",
+                req.count
+            ),
+        )
+        .for_field("count"));
+    }
+
+    Ok(Json(estimate_response(&req)))
+}
+
+fn estimate_response(req: &EstimateRequest) -> EstimateResponse {
+    let estimate = TxBuilder::estimate(req.operation);
+    let count = u64::from(req.count);
+    let rent = Rent::default();
+
+    let proofs: Vec<_> = estimate
+        .proofs
+        .iter()
+        .map(|proof| ProofCostEstimate {
+            proof_type: proof.proof_type,
+            proof_bytes: proof.proof_bytes as u64,
+            context_account_bytes: proof.context_account_bytes.map(|bytes| bytes as u64),
+            context_rent_lamports: proof
+                .context_account_bytes
+                .map(|bytes| rent.minimum_balance(bytes).to_string()),
+            compute_units: proof.compute_units,
+        })
+        .collect();
+    let context_rent: u64 = estimate
+        .proofs
+        .iter()
+        .filter_map(|proof| proof.context_account_bytes)
+        .map(|bytes| rent.minimum_balance(bytes))
+        .sum();
+    let signatures: u64 = estimate
+        .transactions
+        .iter()
+        .map(|tx| tx.signatures as u64)
+        .sum();
+    let compute_units: u64 = estimate
+        .transactions
+        .iter()
+        .map(|tx| tx.compute_units)
+        .sum();
+
+    EstimateResponse {
+        operation: req.operation,
+        count: req.count,
+        proofs,
+        transaction_count: estimate.transactions.len() as u64 * count,
+        signature_count: signatures * count,
+        signature_fee_lamports: (signatures * count * LAMPORTS_PER_SIGNATURE).to_string(),
+        context_rent_lamports: (context_rent * count).to_string(),
+        compute_units: compute_units * count,
+        max_transaction_compute_units: estimate
+            .transactions
+            .iter()
+            .map(|tx| tx.compute_units)
+            .max()
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EstimateOperation;
+
+    fn request(operation: EstimateOperation, count: u32) -> EstimateRequest {
+        EstimateRequest { operation, count }
+    }
+
+    #[test]
+    fn test_withdraw_estimate() {
+        let one = estimate_response(&request(EstimateOperation::Withdraw, 1));
+        assert_eq!(one.proofs.len(), 2);
+        assert_eq!(one.transaction_count, 3);
+        // The setup transaction is also signed by both context accounts
+        assert_eq!(one.signature_count, 5);
+        assert!(one.proofs.iter().all(|p| p.context_rent_lamports.is_some()));
+
+        let three = estimate_response(&request(EstimateOperation::Withdraw, 3));
+        assert_eq!(three.transaction_count, 9);
+        assert_eq!(three.compute_units, one.compute_units * 3);
+        assert_eq!(
+            three.context_rent_lamports.parse::<u64>().unwrap(),
+            one.context_rent_lamports.parse::<u64>().unwrap() * 3
+        );
+        assert_eq!(
+            three.max_transaction_compute_units,
+            one.max_transaction_compute_units
+        );
+    }
+
+    #[test]
+    fn test_configure_needs_no_context_account() {
+        let estimate = estimate_response(&request(EstimateOperation::Configure, 1));
+
+        assert_eq!(estimate.transaction_count, 1);
+        assert_eq!(estimate.context_rent_lamports, "0");
+        assert!(estimate.proofs[0].context_account_bytes.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_count_bounds() {
+        let err = estimate(Json(request(EstimateOperation::Transfer, 0)))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_error_response().fields[0].field, "count");
+    }
+}
@@ -1,20 +1,134 @@
-//! Health check endpoint
+//! Health check endpoints
+//!
+//! `/health` is a cheap liveness check. `/health/ready` also reports the proof
+//! self-test and job queue depth, so load balancers can stop routing to an
+//! instance whose proof generation is wedged or broken.
 
-use axum::{routing::get, Json, Router};
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
 use chrono::Utc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
-use crate::types::HealthResponse;
+use crate::{
+    services::{self_test::SelfTestStatus, JobQueue, SelfTest},
+    types::{HealthResponse, ReadinessResponse},
+};
+
+/// solana-zk-sdk version from Cargo.lock, set by build.rs
+const ZK_SDK_VERSION: &str = env!("ZK_SDK_VERSION");
+
+/// Whether the server still accepts new work
+///
+/// Flipped once on shutdown so load balancers stop routing here while
+/// in-flight requests drain.
+#[derive(Clone, Default)]
+pub struct Readiness {
+    draining: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    /// Mark the server as shutting down
+    pub fn start_draining(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether shutdown has started
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+}
+
+/// State shared by the health endpoints
+#[derive(Clone)]
+pub struct HealthState {
+    pub readiness: Readiness,
+    pub self_test: SelfTest,
+    pub job_queue: JobQueue,
+}
 
 /// Create health check router
-pub fn health_router() -> Router {
-    Router::new().route("/health", get(health_check))
+pub fn health_router(state: HealthState) -> Router {
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
+        .with_state(state)
 }
 
 /// Health check handler
-async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy".to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-        timestamp: Utc::now().timestamp(),
-    })
+///
+/// Returns 503 with status `draining` once shutdown has started.
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, body = HealthResponse),
+        (status = 503, description = "Server is draining for shutdown", body = HealthResponse),
+    ),
+    security(()),
+    tag = "health"
+)]
+async fn health_check(State(state): State<HealthState>) -> (StatusCode, Json<HealthResponse>) {
+    let (code, status) = if state.readiness.is_draining() {
+        (StatusCode::SERVICE_UNAVAILABLE, "draining")
+    } else {
+        (StatusCode::OK, "healthy")
+    };
+
+    (
+        code,
+        Json(HealthResponse {
+            status: status.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: Utc::now().timestamp(),
+        }),
+    )
+}
+
+/// Readiness check handler
+///
+/// Returns 503 unless the server is not draining and the latest proof
+/// self-test passed recently.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses(
+        (status = 200, body = ReadinessResponse),
+        (status = 503, description = "Draining, self-test failed or overdue, or no self-test has finished yet", body = ReadinessResponse),
+    ),
+    security(()),
+    tag = "health"
+)]
+async fn readiness_check(
+    State(state): State<HealthState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let now = Utc::now().timestamp();
+    let status = if state.readiness.is_draining() {
+        "draining"
+    } else {
+        match state.self_test.status(now) {
+            SelfTestStatus::Passed | SelfTestStatus::Disabled => "ready",
+            SelfTestStatus::Starting => "starting",
+            SelfTestStatus::Failed => "self-test-failed",
+            SelfTestStatus::Stale => "self-test-stale",
+        }
+    };
+    let code = if status == "ready" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        code,
+        Json(ReadinessResponse {
+            status: status.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            zk_sdk_version: ZK_SDK_VERSION.to_string(),
+            timestamp: now,
+            self_test: state.self_test.report(),
+            queue: state.job_queue.depth(),
+        }),
+    )
 }
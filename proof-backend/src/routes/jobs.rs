@@ -0,0 +1,382 @@
+//! Async proof job endpoints
+
+use axum::{
+    extract::{DefaultBodyLimit, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, time::Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::info;
+use zeroize::Zeroizing;
+
+use super::proofs::{
+    generate_equality, generate_pubkey_validity, generate_range, generate_transfer,
+    generate_withdraw_bundle_with_progress, generate_zero_ciphertext, validate_request, AppState,
+};
+use crate::{
+    auth::AuthenticatedKey,
+    error::{BackendError, ErrorResponse, Result},
+    services::{
+        job_queue::{JobFn, JobRecord, JobStatus, ProgressReporter},
+        IdempotencyStore, JobQueue,
+    },
+    types::{JobRequest, JobSubmitResponse},
+};
+
+/// Header naming a submission so retries return the same job
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Longest accepted idempotency key
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// A queued job as persisted by a durable queue
+#[derive(Serialize, Deserialize)]
+struct StoredJob {
+    /// The request, with its key-derivation signatures unsealed
+    request: JobRequest,
+    /// API key charged for the proof time
+    usage_key: Option<String>,
+}
+
+/// State shared by job handlers
+#[derive(Clone)]
+pub struct JobsState {
+    pub app: AppState,
+    pub queue: JobQueue,
+    pub idempotency: IdempotencyStore,
+}
+
+/// Create jobs router
+pub fn jobs_router(app: AppState, queue: JobQueue, idempotency: IdempotencyStore) -> Router {
+    // Jobs are how clients submit large batches
+    let batch_limit = DefaultBodyLimit::max(app.config.max_batch_body_bytes);
+    let state = JobsState {
+        app,
+        queue,
+        idempotency,
+    };
+
+    Router::new()
+        .route("/v1/jobs", post(submit_job).layer(batch_limit))
+        .route("/v1/jobs/{id}", get(get_job))
+        .route("/v1/jobs/{id}/events", get(job_events))
+        .with_state(state)
+}
+
+/// Queue a proof request and return its job id
+///
+/// POST /v1/jobs
+///
+/// A retry carrying the `Idempotency-Key` of an earlier submission returns
+/// that submission's job.
+#[utoipa::path(
+    post,
+    path = "/v1/jobs",
+    request_body = JobRequest,
+    params((
+        "Idempotency-Key" = Option<String>,
+        Header,
+        description = "Client-chosen key; retries with the same key return the same job"
+    )),
+    responses(
+        (status = 202, body = JobSubmitResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "jobs"
+)]
+async fn submit_job(
+    State(state): State<JobsState>,
+    api_key: Option<Extension<AuthenticatedKey>>,
+    headers: HeaderMap,
+    Json(mut req): Json<JobRequest>,
+) -> Result<(StatusCode, Json<JobSubmitResponse>)> {
+    info!(kind = req.kind(), "Submitting proof job");
+
+    // The middleware only checked the jobs scope; the job kind needs its own
+    if let Some(Extension(AuthenticatedKey(key))) = &api_key {
+        if !key.allows(req.scope()) {
+            return Err(BackendError::Forbidden(format!(
+                "API key not permitted for {} jobs",
+                req.kind()
+            )));
+        }
+    }
+
+    // A retry gets the job of its first attempt rather than a replay error
+    let idempotency_key = idempotency_key(&headers)?;
+    let client = api_key
+        .as_ref()
+        .map(|Extension(AuthenticatedKey(key))| key.key.clone())
+        .unwrap_or_default();
+    if let Some(key) = &idempotency_key {
+        if let Some(job_id) = state.idempotency.get(&client, key).await? {
+            info!(job_id, "Returning job of an earlier submission");
+            return Ok((StatusCode::ACCEPTED, Json(JobSubmitResponse { job_id })));
+        }
+    }
+
+    // Validate timestamp now so queueing delay cannot expire the request
+    validate_request(
+        &state.app.config,
+        &state.app.replay_cache,
+        req.timestamp(),
+        req.request_signature(),
+    )
+    .await?;
+
+    // Read omitted balances now; the job proves against the state at submission
+    let accounts = &state.app.accounts;
+    match &mut req {
+        JobRequest::Equality(req) => accounts.resolve(req).await?,
+        JobRequest::Transfer(req) => accounts.resolve(req).await?,
+        JobRequest::ZeroCiphertext(req) => accounts.resolve(req).await?,
+        JobRequest::WithdrawBundle(req) => accounts.resolve(req).await?,
+        JobRequest::PubkeyValidity(_) | JobRequest::Range(_) => None,
+    };
+
+    let kind = req.kind();
+    // Development mode has no key, so jobs run at the default tier
+    let priority = api_key
+        .as_ref()
+        .map(|Extension(AuthenticatedKey(key))| key.priority)
+        .unwrap_or_default();
+    let usage_key = api_key.map(|Extension(AuthenticatedKey(key))| key.key);
+
+    // The sealing key is per process, so persisted requests hold the
+    // signatures unsealed; the job store encrypts them
+    if state.queue.is_durable() {
+        for secret in req.secrets_mut() {
+            state.app.sealing.unseal_in_place(secret)?;
+        }
+    }
+    let stored = StoredJob {
+        request: req,
+        usage_key,
+    };
+
+    let job_id = if state.queue.is_durable() {
+        let payload = Zeroizing::new(serde_json::to_string(&stored).map_err(|e| {
+            BackendError::Internal(format!("Failed to serialize job request: {e}"))
+        })?);
+        let job = job_fn(state.app.clone(), stored);
+        state
+            .queue
+            .submit_durable(kind, priority, &payload, job)
+            .await?
+    } else {
+        let job = job_fn(state.app.clone(), stored);
+        state.queue.submit(kind, priority, job).await?
+    };
+
+    if let Some(key) = &idempotency_key {
+        state.idempotency.insert(&client, key, &job_id).await?;
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(JobSubmitResponse { job_id })))
+}
+
+/// The request's idempotency key, if it sent one
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .filter(|key| (1..=MAX_IDEMPOTENCY_KEY_LEN).contains(&key.len()))
+        .filter(|key| key.bytes().all(|b| b.is_ascii_graphic()))
+        .map(|key| Some(key.to_string()))
+        .ok_or_else(|| {
+            BackendError::BadRequest(format!(
+                "Idempotency-Key must be 1 to {MAX_IDEMPOTENCY_KEY_LEN} visible ASCII characters"
+            ))
+        })
+}
+
+/// Get job status and, once completed, its result
+///
+/// GET /v1/jobs/{id}
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, body = JobRecord),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "jobs"
+)]
+async fn get_job(
+    State(state): State<JobsState>,
+    Path(id): Path<String>,
+) -> Result<Json<JobRecord>> {
+    state
+        .queue
+        .get(&id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| BackendError::NotFound(format!("Job {id}")))
+}
+
+/// Stream job updates as server-sent events until the job finishes
+///
+/// GET /v1/jobs/{id}/events
+///
+/// Each event is named after the job status and carries the full job record.
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}/events",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (
+            status = 200,
+            description = "Server-sent events, each carrying a job record",
+            content_type = "text/event-stream",
+            body = JobRecord
+        ),
+        (status = "4XX", body = ErrorResponse),
+    ),
+    tag = "jobs"
+)]
+async fn job_events(
+    State(state): State<JobsState>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    // Subscribe before the snapshot so no update falls in between
+    let updates = state.queue.subscribe();
+    let current = state
+        .queue
+        .get(&id)
+        .await?
+        .ok_or_else(|| BackendError::NotFound(format!("Job {id}")))?;
+
+    let events = stream::unfold(
+        (Some(current), updates, state.queue, false),
+        move |(pending, mut updates, queue, done)| {
+            let id = id.clone();
+            async move {
+                if done {
+                    return None;
+                }
+
+                let record = match pending {
+                    Some(record) => record,
+                    None => loop {
+                        match updates.recv().await {
+                            Ok(record) if record.id == id => break record,
+                            Ok(_) => continue,
+                            // Missed updates: resend the latest state instead
+                            Err(RecvError::Lagged(_)) => {
+                                break queue.get(&id).await.ok().flatten()?
+                            }
+                            Err(RecvError::Closed) => return None,
+                        }
+                    },
+                };
+
+                let finished = record.status.is_finished();
+                Some((Ok(job_event(&record)), (None, updates, queue, finished)))
+            }
+        },
+    );
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Encode a job record as an SSE event named after its status
+fn job_event(record: &JobRecord) -> Event {
+    let event = Event::default().event(match record.status {
+        JobStatus::Queued => "queued",
+        JobStatus::Running => "running",
+        JobStatus::Completed => "completed",
+        JobStatus::Failed => "failed",
+    });
+
+    event
+        .json_data(record)
+        .unwrap_or_else(|_| Event::default().event("error"))
+}
+
+/// The work of a queued job, charging its proof time to the submitting key
+fn job_fn(app: AppState, job: StoredJob) -> JobFn {
+    Box::new(move |progress| {
+        let started = Instant::now();
+        let result = run_job(&app, job.request, progress);
+        if let Some(key) = job.usage_key {
+            app.usage.record_proof_time(&key, started.elapsed());
+        }
+        result
+    })
+}
+
+/// Rebuild persisted jobs left unfinished by a stopped replica
+pub fn spawn_job_recovery(queue: &JobQueue, app: AppState) {
+    // Their signatures were unsealed at submission
+    let app = AppState {
+        sealing: app.sealing.accepting_plain(),
+        ..app
+    };
+
+    queue.spawn_recovery(move |payload| {
+        let job: StoredJob = serde_json::from_str(payload)
+            .map_err(|e| BackendError::Internal(format!("Invalid stored job: {e}")))?;
+        Ok(job_fn(app.clone(), job))
+    });
+}
+
+/// Run a queued request through the synchronous endpoint logic
+///
+/// Multi-proof requests report progress per proof; single proofs report once
+/// generation starts.
+fn run_job(
+    app: &AppState,
+    req: JobRequest,
+    progress: ProgressReporter,
+) -> Result<serde_json::Value> {
+    let result = match req {
+        JobRequest::PubkeyValidity(req) => {
+            progress.report("pubkey-validity", 0, 1);
+            serde_json::to_value(generate_pubkey_validity(
+                req,
+                &app.pubkey_validity_cache,
+                &app.sealing,
+            )?)
+        }
+        JobRequest::Equality(req) => {
+            progress.report("equality", 0, 1);
+            serde_json::to_value(generate_equality(req, &app.sealing)?)
+        }
+        JobRequest::Range(req) => {
+            progress.report("range", 0, 1);
+            serde_json::to_value(generate_range(req)?)
+        }
+        JobRequest::Transfer(req) => {
+            // Generated in one library call, so only the start is observable
+            progress.report("transfer", 0, 3);
+            serde_json::to_value(generate_transfer(req, &app.sealing)?)
+        }
+        JobRequest::ZeroCiphertext(req) => {
+            progress.report("zero-ciphertext", 0, 1);
+            serde_json::to_value(generate_zero_ciphertext(req, &app.sealing)?)
+        }
+        JobRequest::WithdrawBundle(req) => {
+            progress.report("equality", 0, 2);
+            let mut completed = 0;
+            let response = generate_withdraw_bundle_with_progress(req, &app.sealing, |proof| {
+                completed += 1;
+                progress.report(proof, completed, 2);
+            })?;
+            serde_json::to_value(response)
+        }
+    };
+
+    result.map_err(|e| BackendError::Internal(format!("Failed to serialize job result: {e}")))
+}
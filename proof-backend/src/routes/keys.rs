@@ -0,0 +1,98 @@
+//! Key derivation endpoints
+
+use axum::{extract::State, routing::post, Json, Router};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::signature::Signature;
+use tracing::info;
+
+use super::proofs::{validate_request, AppState};
+use crate::{
+    error::{parse_fields, ErrorResponse, Result},
+    services::ProofGenerator,
+    types::{ElGamalPubkeyRequest, ElGamalPubkeyResponse},
+};
+
+/// Create keys router
+pub fn keys_router(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/keys/elgamal-pubkey", post(elgamal_pubkey))
+        .with_state(state)
+}
+
+/// Derive the ElGamal public key of a wallet/token account pair
+///
+/// POST /v1/keys/elgamal-pubkey
+///
+/// Returns only the public key (and optionally the AE key fingerprint), so
+/// frontends can display it or check registry entries without a proof.
+#[utoipa::path(
+    post,
+    path = "/v1/keys/elgamal-pubkey",
+    request_body = ElGamalPubkeyRequest,
+    responses(
+        (status = 200, body = ElGamalPubkeyResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "keys"
+)]
+async fn elgamal_pubkey(
+    State(state): State<AppState>,
+    Json(req): Json<ElGamalPubkeyRequest>,
+) -> Result<Json<ElGamalPubkeyResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        "Deriving ElGamal public key"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => state.sealing.open(&req.elgamal_signature),
+        ae_signature = "ae_signature" => req
+            .ae_signature
+            .as_ref()
+            .map(|signature| state.sealing.open(signature))
+            .transpose(),
+    );
+    let ae_signature = ae_signature.map(|signature| Signature::from(*signature));
+
+    // Verify request and AE key signatures
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+    if let Some(ae_signature) = &ae_signature {
+        ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, ae_signature)?;
+    }
+
+    // Derive keys
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+    let elgamal_pubkey: [u8; 32] = elgamal_keypair.pubkey().into();
+    let ae_key_fingerprint = ae_signature
+        .map(|signature| ProofGenerator::derive_ae_key(&signature))
+        .transpose()?
+        .map(|ae_key| STANDARD.encode(ProofGenerator::ae_key_fingerprint(&ae_key)));
+
+    Ok(Json(ElGamalPubkeyResponse {
+        elgamal_pubkey: STANDARD.encode(elgamal_pubkey),
+        ae_key_fingerprint,
+    }))
+}
@@ -1,7 +1,29 @@
 //! API Routes
 
+pub mod admin;
+pub mod balance;
+pub mod docs;
+pub mod estimate;
 pub mod health;
+pub mod jobs;
+pub mod keys;
 pub mod proofs;
+pub mod relay;
+pub mod sealing_key;
+pub mod service_key;
+pub mod tx;
+pub mod usage;
 
-pub use health::health_router;
+pub use admin::admin_router;
+pub use balance::balance_router;
+pub use docs::docs_router;
+pub use estimate::estimate_router;
+pub use health::{health_router, HealthState, Readiness};
+pub use jobs::jobs_router;
+pub use keys::keys_router;
 pub use proofs::proofs_router;
+pub use relay::relay_router;
+pub use sealing_key::sealing_key_router;
+pub use service_key::service_key_router;
+pub use tx::tx_router;
+pub use usage::usage_router;
@@ -1,21 +1,35 @@
 //! Proof generation endpoints
 
 use axum::{
-    extract::State,
+    extract::{DefaultBodyLimit, State},
     routing::post,
     Json, Router,
 };
 use base64::{engine::general_purpose::STANDARD, Engine};
 use chrono::Utc;
+use solana_sdk::signature::Signature;
 use std::sync::Arc;
+use svs_proof_core::{TransferProofs, MAX_RANGE_PROOF_AMOUNTS};
 use tracing::info;
 
+use super::tx::encode_transactions;
 use crate::{
-    error::{BackendError, Result},
-    services::ProofGenerator,
+    error::{max_items, parse_each, parse_fields, BackendError, ErrorResponse, Result},
+    jwt::JwtVerifier,
+    limits::ProofLimiter,
+    sealing::SealingKeys,
+    services::{
+        account_state::parse_required, tx_builder::TransferAccounts, AccountStateFetcher,
+        ApiKeyStore, ProofGenerator, PubkeyValidityCache, Relayer, ReplayCache, TxBuilder,
+        UsageTracker,
+    },
     types::{
-        Config, EqualityProofRequest, EqualityProofResponse, PubkeyValidityRequest,
-        PubkeyValidityResponse, RangeProofRequest, RangeProofResponse,
+        Config, EqualityProofRequest, EqualityProofResponse, PubkeyValidityBatchRequest,
+        PubkeyValidityBatchResponse, PubkeyValidityBatchResult, PubkeyValidityRequest,
+        PubkeyValidityResponse, RangeProofRequest, RangeProofResponse, TransferPackageRequest,
+        TransferPackageResponse, TransferProofRequest, TransferProofResponse,
+        WithdrawBundleRequest, WithdrawBundleResponse, ZeroCiphertextProofRequest,
+        ZeroCiphertextProofResponse, MAX_PUBKEY_VALIDITY_BATCH,
     },
 };
 
@@ -23,22 +37,51 @@ use crate::{
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<Config>,
+    pub replay_cache: ReplayCache,
+    pub pubkey_validity_cache: PubkeyValidityCache,
+    pub usage: UsageTracker,
+    pub sealing: SealingKeys,
+    pub accounts: AccountStateFetcher,
+    pub proof_limiter: ProofLimiter,
+    pub jwt: Option<JwtVerifier>,
+    pub api_keys: ApiKeyStore,
+    pub relayer: Option<Relayer>,
 }
 
 /// Create proofs router
-pub fn proofs_router(config: Arc<Config>) -> Router {
-    let state = AppState { config };
+pub fn proofs_router(state: AppState) -> Router {
+    // Batched proofs may exceed the default body limit
+    let batch_limit = DefaultBodyLimit::max(state.config.max_batch_body_bytes);
 
     Router::new()
-        .route("/api/proofs/pubkey-validity", post(pubkey_validity))
-        .route("/api/proofs/equality", post(equality_proof))
-        .route("/api/proofs/range", post(range_proof))
+        .route("/v1/proofs/pubkey-validity", post(pubkey_validity))
+        .route(
+            "/v1/proofs/pubkey-validity/batch",
+            post(pubkey_validity_batch).layer(batch_limit),
+        )
+        .route("/v1/proofs/equality", post(equality_proof))
+        .route("/v1/proofs/range", post(range_proof).layer(batch_limit))
+        .route("/v1/proofs/transfer", post(transfer_proof))
+        .route("/v1/proofs/transfer-package", post(transfer_package))
+        .route("/v1/proofs/zero-ciphertext", post(zero_ciphertext_proof))
+        .route("/v1/proofs/withdraw-bundle", post(withdraw_bundle))
         .with_state(state)
 }
 
 /// Generate PubkeyValidityProof
 ///
-/// POST /api/proofs/pubkey-validity
+/// POST /v1/proofs/pubkey-validity
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/pubkey-validity",
+    request_body = PubkeyValidityRequest,
+    responses(
+        (status = 200, body = PubkeyValidityResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
 async fn pubkey_validity(
     State(state): State<AppState>,
     Json(req): Json<PubkeyValidityRequest>,
@@ -49,26 +92,146 @@ async fn pubkey_validity(
         "Generating pubkey validity proof"
     );
 
-    // Validate timestamp
-    validate_timestamp(req.timestamp, state.config.timestamp_tolerance_secs)?;
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    let (cache, sealing) = (state.pubkey_validity_cache, state.sealing);
+    let response = state
+        .proof_limiter
+        .run(move || generate_pubkey_validity(req, &cache, &sealing))
+        .await?;
 
+    Ok(Json(response))
+}
+
+/// Generate PubkeyValidityProofs for several token accounts
+///
+/// POST /v1/proofs/pubkey-validity/batch
+///
+/// Entries are validated and proven independently; a failed entry is
+/// reported in its result instead of failing the batch.
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/pubkey-validity/batch",
+    request_body = PubkeyValidityBatchRequest,
+    responses(
+        (status = 200, body = PubkeyValidityBatchResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
+async fn pubkey_validity_batch(
+    State(state): State<AppState>,
+    Json(req): Json<PubkeyValidityBatchRequest>,
+) -> Result<Json<PubkeyValidityBatchResponse>> {
+    max_items(&req.requests, MAX_PUBKEY_VALIDITY_BATCH).map_err(|e| e.for_field("requests"))?;
+
+    info!(
+        batch_size = req.requests.len(),
+        "Generating pubkey validity proof batch"
+    );
+
+    // Validate each timestamp and reject replays, including within the batch
+    let mut entries = Vec::with_capacity(req.requests.len());
+    for request in req.requests {
+        let token_account = request.token_account.clone();
+        let validated = validate_request(
+            &state.config,
+            &state.replay_cache,
+            request.timestamp,
+            &request.request_signature,
+        )
+        .await
+        .map(|()| request);
+        entries.push((token_account, validated));
+    }
+
+    // One proof slot for the whole batch; each proof is cheap
+    let (cache, sealing) = (state.pubkey_validity_cache, state.sealing);
+    let results: Vec<_> = state
+        .proof_limiter
+        .run(move || {
+            Ok(entries
+                .into_iter()
+                .map(|(token_account, request)| {
+                    let proof =
+                        request.and_then(|req| generate_pubkey_validity(req, &cache, &sealing));
+                    match proof {
+                        Ok(proof) => PubkeyValidityBatchResult {
+                            token_account,
+                            proof_data: Some(proof.proof_data),
+                            elgamal_pubkey: Some(proof.elgamal_pubkey),
+                            error: None,
+                        },
+                        Err(e) => PubkeyValidityBatchResult {
+                            token_account,
+                            proof_data: None,
+                            elgamal_pubkey: None,
+                            error: Some(e.to_error_response()),
+                        },
+                    }
+                })
+                .collect())
+        })
+        .await?;
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    info!(failed, "Generated pubkey validity proof batch");
+
+    Ok(Json(PubkeyValidityBatchResponse {
+        succeeded: results.len() - failed,
+        failed,
+        results,
+    }))
+}
+
+/// Verify and generate a pubkey validity proof request (timestamp already validated)
+///
+/// Shared by the synchronous endpoint and the job queue.
+pub(crate) fn generate_pubkey_validity(
+    req: PubkeyValidityRequest,
+    cache: &PubkeyValidityCache,
+    sealing: &SealingKeys,
+) -> Result<PubkeyValidityResponse> {
     // Parse inputs
-    let wallet_pubkey = ProofGenerator::parse_pubkey(&req.wallet_pubkey)?;
-    let token_account = ProofGenerator::parse_pubkey(&req.token_account)?;
-    let request_signature = ProofGenerator::parse_signature(&req.request_signature)?;
-    let elgamal_signature = ProofGenerator::parse_signature(&req.elgamal_signature)?;
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => sealing.open(&req.elgamal_signature),
+    );
 
     // Verify request signature
     ProofGenerator::verify_request_signature(
         &wallet_pubkey,
         req.timestamp,
         &token_account,
+        req.nonce.as_deref(),
         &request_signature,
     )?;
 
     // Derive ElGamal keypair from the provided signature
-    let sig_bytes: [u8; 64] = elgamal_signature.into();
-    let elgamal_keypair = ProofGenerator::derive_elgamal_keypair(&sig_bytes, &token_account)?;
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+
+    // Reuse a proof for the same keypair if one is cached
+    let cached_pubkey: [u8; 32] = elgamal_keypair.pubkey().into();
+    if let Some(proof_data) = cache.get(&token_account, &cached_pubkey) {
+        info!("Returning cached pubkey validity proof");
+
+        return Ok(PubkeyValidityResponse {
+            proof_data: STANDARD.encode(&proof_data),
+            elgamal_pubkey: STANDARD.encode(cached_pubkey),
+        });
+    }
 
     // Generate the proof
     let (proof_data, elgamal_pubkey) =
@@ -79,18 +242,31 @@ async fn pubkey_validity(
         "Generated pubkey validity proof"
     );
 
-    Ok(Json(PubkeyValidityResponse {
+    cache.insert(&token_account, &elgamal_pubkey, proof_data.clone());
+
+    Ok(PubkeyValidityResponse {
         proof_data: STANDARD.encode(&proof_data),
         elgamal_pubkey: STANDARD.encode(elgamal_pubkey),
-    }))
+    })
 }
 
 /// Generate CiphertextCommitmentEqualityProof
 ///
-/// POST /api/proofs/equality
+/// POST /v1/proofs/equality
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/equality",
+    request_body = EqualityProofRequest,
+    responses(
+        (status = 200, body = EqualityProofResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
 async fn equality_proof(
     State(state): State<AppState>,
-    Json(req): Json<EqualityProofRequest>,
+    Json(mut req): Json<EqualityProofRequest>,
 ) -> Result<Json<EqualityProofResponse>> {
     info!(
         wallet = %req.wallet_pubkey,
@@ -99,31 +275,58 @@ async fn equality_proof(
         "Generating equality proof"
     );
 
-    // Validate timestamp
-    validate_timestamp(req.timestamp, state.config.timestamp_tolerance_secs)?;
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    state.accounts.resolve(&mut req).await?;
+
+    let sealing = state.sealing;
+    let response = state
+        .proof_limiter
+        .run(move || generate_equality(req, &sealing))
+        .await?;
 
+    Ok(Json(response))
+}
+
+/// Verify and generate an equality proof request (timestamp already validated)
+///
+/// Shared by the synchronous endpoint and the job queue.
+pub(crate) fn generate_equality(
+    req: EqualityProofRequest,
+    sealing: &SealingKeys,
+) -> Result<EqualityProofResponse> {
     // Parse inputs
-    let wallet_pubkey = ProofGenerator::parse_pubkey(&req.wallet_pubkey)?;
-    let token_account = ProofGenerator::parse_pubkey(&req.token_account)?;
-    let request_signature = ProofGenerator::parse_signature(&req.request_signature)?;
-    let elgamal_signature = ProofGenerator::parse_signature(&req.elgamal_signature)?;
-    let ciphertext = ProofGenerator::parse_ciphertext(&req.current_ciphertext)?;
-    let amount: u64 = req
-        .amount
-        .parse()
-        .map_err(|e| BackendError::BadRequest(format!("Invalid amount: {e}")))?;
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => sealing.open(&req.elgamal_signature),
+        ciphertext = "current_ciphertext"
+            => parse_required(&req.current_ciphertext, ProofGenerator::parse_ciphertext),
+        amount = "amount" => ProofGenerator::parse_amount(&req.amount),
+    );
 
     // Verify request signature
     ProofGenerator::verify_request_signature(
         &wallet_pubkey,
         req.timestamp,
         &token_account,
+        req.nonce.as_deref(),
         &request_signature,
     )?;
 
     // Derive ElGamal keypair
-    let sig_bytes: [u8; 64] = elgamal_signature.into();
-    let elgamal_keypair = ProofGenerator::derive_elgamal_keypair(&sig_bytes, &token_account)?;
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
 
     // Generate the proof
     let proof_data =
@@ -131,14 +334,25 @@ async fn equality_proof(
 
     info!(proof_size = proof_data.len(), "Generated equality proof");
 
-    Ok(Json(EqualityProofResponse {
+    Ok(EqualityProofResponse {
         proof_data: STANDARD.encode(&proof_data),
-    }))
+    })
 }
 
-/// Generate BatchedRangeProofU64
+/// Generate BatchedRangeProofU64, U128 or U256
 ///
-/// POST /api/proofs/range
+/// POST /v1/proofs/range
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/range",
+    request_body = RangeProofRequest,
+    responses(
+        (status = 200, body = RangeProofResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
 async fn range_proof(
     State(state): State<AppState>,
     Json(req): Json<RangeProofRequest>,
@@ -149,45 +363,515 @@ async fn range_proof(
         "Generating range proof"
     );
 
-    // Validate timestamp
-    validate_timestamp(req.timestamp, state.config.timestamp_tolerance_secs)?;
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    let response = state.proof_limiter.run(move || generate_range(req)).await?;
+
+    Ok(Json(response))
+}
 
+/// Verify and generate a range proof request (timestamp already validated)
+///
+/// Shared by the synchronous endpoint and the job queue.
+pub(crate) fn generate_range(req: RangeProofRequest) -> Result<RangeProofResponse> {
     // Parse inputs
-    let wallet_pubkey = ProofGenerator::parse_pubkey(&req.wallet_pubkey)?;
-    let request_signature = ProofGenerator::parse_signature(&req.request_signature)?;
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        amounts = "amounts"
+            => parse_each(&req.amounts, MAX_RANGE_PROOF_AMOUNTS, ProofGenerator::parse_amount),
+        openings = "commitment_blindings" => parse_each(
+            &req.commitment_blindings,
+            MAX_RANGE_PROOF_AMOUNTS,
+            ProofGenerator::parse_opening,
+        ),
+        bit_lengths = "bit_lengths" => req
+            .bit_lengths
+            .as_deref()
+            .map(|bit_lengths| max_items(bit_lengths, MAX_RANGE_PROOF_AMOUNTS))
+            .transpose(),
+    );
 
     // Verify request signature
     ProofGenerator::verify_range_request_signature(
         &wallet_pubkey,
         req.timestamp,
+        req.nonce.as_deref(),
         &request_signature,
     )?;
 
-    // Parse amounts
-    let amounts: Vec<u64> = req
-        .amounts
-        .iter()
-        .map(|s| {
-            s.parse()
-                .map_err(|e| BackendError::BadRequest(format!("Invalid amount: {e}")))
+    // Each amount is proven over 64 bits unless a split is given
+    let bit_lengths: Vec<usize> = match bit_lengths {
+        Some(bit_lengths) => bit_lengths.iter().map(|&bits| bits as usize).collect(),
+        None => vec![64; amounts.len()],
+    };
+
+    // Generate the proof
+    let (proof_data, proof_type) =
+        ProofGenerator::generate_range_proof(&amounts, &bit_lengths, &openings)?;
+
+    info!(
+        proof_size = proof_data.len(),
+        ?proof_type,
+        "Generated range proof"
+    );
+
+    Ok(RangeProofResponse {
+        proof_data: STANDARD.encode(&proof_data),
+        proof_type,
+    })
+}
+
+/// Generate all proofs for a confidential transfer
+///
+/// POST /v1/proofs/transfer
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/transfer",
+    request_body = TransferProofRequest,
+    responses(
+        (status = 200, body = TransferProofResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
+async fn transfer_proof(
+    State(state): State<AppState>,
+    Json(mut req): Json<TransferProofRequest>,
+) -> Result<Json<TransferProofResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        amount = %req.amount,
+        "Generating transfer proofs"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    state.accounts.resolve(&mut req).await?;
+
+    let sealing = state.sealing;
+    let response = state
+        .proof_limiter
+        .run(move || generate_transfer(req, &sealing))
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Verify and generate a transfer proof request (timestamp already validated)
+///
+/// Shared by the synchronous endpoint and the job queue.
+pub(crate) fn generate_transfer(
+    req: TransferProofRequest,
+    sealing: &SealingKeys,
+) -> Result<TransferProofResponse> {
+    Ok(transfer_response(&transfer_proofs(&req, sealing)?))
+}
+
+/// Verify a transfer request and generate its proofs
+fn transfer_proofs(req: &TransferProofRequest, sealing: &SealingKeys) -> Result<TransferProofs> {
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => sealing.open(&req.elgamal_signature),
+        ae_signature = "ae_signature" => sealing.open(&req.ae_signature),
+        ciphertext = "current_ciphertext"
+            => parse_required(&req.current_ciphertext, ProofGenerator::parse_ciphertext),
+        decryptable_balance = "current_decryptable_balance"
+            => parse_required(
+                &req.current_decryptable_balance,
+                ProofGenerator::parse_ae_ciphertext,
+            ),
+        destination_pubkey = "destination_elgamal_pubkey"
+            => ProofGenerator::parse_elgamal_pubkey(&req.destination_elgamal_pubkey),
+        auditor_pubkey = "auditor_elgamal_pubkey" => req
+            .auditor_elgamal_pubkey
+            .as_deref()
+            .map(ProofGenerator::parse_elgamal_pubkey)
+            .transpose(),
+        amount = "amount" => ProofGenerator::parse_amount(&req.amount),
+    );
+    let ae_signature = Signature::from(*ae_signature);
+
+    // Verify request and AE key signatures
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+    ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, &ae_signature)?;
+
+    // Derive keys
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+    let ae_key = ProofGenerator::derive_ae_key(&ae_signature)?;
+
+    // Generate the proofs
+    let proofs = ProofGenerator::generate_transfer_proof(
+        &elgamal_keypair,
+        &ae_key,
+        &ciphertext,
+        &decryptable_balance,
+        amount,
+        &destination_pubkey,
+        auditor_pubkey.as_ref(),
+    )?;
+
+    info!(
+        equality_size = proofs.equality_proof.len(),
+        validity_size = proofs.ciphertext_validity_proof.len(),
+        range_size = proofs.range_proof.len(),
+        "Generated transfer proofs"
+    );
+
+    Ok(proofs)
+}
+
+fn transfer_response(proofs: &TransferProofs) -> TransferProofResponse {
+    TransferProofResponse {
+        equality_proof_data: STANDARD.encode(&proofs.equality_proof),
+        ciphertext_validity_proof_data: STANDARD.encode(&proofs.ciphertext_validity_proof),
+        range_proof_data: STANDARD.encode(&proofs.range_proof),
+        auditor_ciphertext_lo: STANDARD.encode(proofs.auditor_ciphertext_lo),
+        auditor_ciphertext_hi: STANDARD.encode(proofs.auditor_ciphertext_hi),
+        new_decryptable_available_balance: STANDARD
+            .encode(proofs.new_decryptable_available_balance),
+    }
+}
+
+/// Generate a confidential transfer package: proofs and transactions
+///
+/// POST /v1/proofs/transfer-package
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/transfer-package",
+    request_body = TransferPackageRequest,
+    responses(
+        (status = 200, body = TransferPackageResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
+async fn transfer_package(
+    State(state): State<AppState>,
+    Json(mut req): Json<TransferPackageRequest>,
+) -> Result<Json<TransferPackageResponse>> {
+    info!(
+        wallet = %req.transfer.wallet_pubkey,
+        token_account = %req.transfer.token_account,
+        destination = %req.destination_token_account,
+        amount = %req.transfer.amount,
+        "Generating transfer package"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.transfer.timestamp,
+        &req.transfer.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    state
+        .accounts
+        .resolve(&mut req.transfer)
+        .await
+        .map_err(|e| e.for_field("transfer"))?;
+
+    parse_fields!(
+        authority = "transfer.wallet_pubkey"
+            => ProofGenerator::parse_pubkey(&req.transfer.wallet_pubkey),
+        source_token_account = "transfer.token_account"
+            => ProofGenerator::parse_pubkey(&req.transfer.token_account),
+        mint = "mint" => ProofGenerator::parse_pubkey(&req.mint),
+        destination_token_account = "destination_token_account"
+            => ProofGenerator::parse_pubkey(&req.destination_token_account),
+        recent_blockhash = "recent_blockhash"
+            => ProofGenerator::parse_blockhash(&req.recent_blockhash),
+    );
+    let accounts = TransferAccounts {
+        authority,
+        source_token_account,
+        mint,
+        destination_token_account,
+    };
+
+    let sealing = state.sealing;
+    let (proofs, built) = state
+        .proof_limiter
+        .run(move || {
+            let proofs =
+                transfer_proofs(&req.transfer, &sealing).map_err(|e| e.for_field("transfer"))?;
+            let built =
+                TxBuilder::build_transfer_transactions(&accounts, &proofs, recent_blockhash)?;
+            Ok((proofs, built))
         })
-        .collect::<Result<Vec<_>>>()?;
+        .await?;
+
+    info!(
+        transactions = built.transactions.len(),
+        equality_proof_context = %built.equality_proof_context,
+        ciphertext_validity_proof_context = %built.ciphertext_validity_proof_context,
+        range_proof_context = %built.range_proof_context,
+        "Built transfer transactions"
+    );
+
+    Ok(Json(TransferPackageResponse {
+        proofs: transfer_response(&proofs),
+        transactions: encode_transactions(&built.transactions)?,
+        equality_proof_context: built.equality_proof_context.to_string(),
+        ciphertext_validity_proof_context: built.ciphertext_validity_proof_context.to_string(),
+        range_proof_context: built.range_proof_context.to_string(),
+    }))
+}
+
+/// Generate ZeroCiphertextProof
+///
+/// POST /v1/proofs/zero-ciphertext
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/zero-ciphertext",
+    request_body = ZeroCiphertextProofRequest,
+    responses(
+        (status = 200, body = ZeroCiphertextProofResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
+async fn zero_ciphertext_proof(
+    State(state): State<AppState>,
+    Json(mut req): Json<ZeroCiphertextProofRequest>,
+) -> Result<Json<ZeroCiphertextProofResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        "Generating zero ciphertext proof"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    state.accounts.resolve(&mut req).await?;
+
+    let sealing = state.sealing;
+    let response = state
+        .proof_limiter
+        .run(move || generate_zero_ciphertext(req, &sealing))
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Verify and generate a zero ciphertext proof request (timestamp already validated)
+///
+/// Shared by the synchronous endpoint and the job queue.
+pub(crate) fn generate_zero_ciphertext(
+    req: ZeroCiphertextProofRequest,
+    sealing: &SealingKeys,
+) -> Result<ZeroCiphertextProofResponse> {
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => sealing.open(&req.elgamal_signature),
+        ciphertext = "ciphertext"
+            => parse_required(&req.ciphertext, ProofGenerator::parse_ciphertext),
+    );
+
+    // Verify request signature
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
 
-    // Parse openings
-    let openings: Vec<_> = req
-        .commitment_blindings
-        .iter()
-        .map(|s| ProofGenerator::parse_opening(s))
-        .collect::<Result<Vec<_>>>()?;
+    // Derive ElGamal keypair
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
 
     // Generate the proof
-    let proof_data = ProofGenerator::generate_range_proof(&amounts, &openings)?;
+    let proof_data = ProofGenerator::generate_zero_ciphertext_proof(&elgamal_keypair, &ciphertext)?;
 
-    info!(proof_size = proof_data.len(), "Generated range proof");
+    info!(
+        proof_size = proof_data.len(),
+        "Generated zero ciphertext proof"
+    );
 
-    Ok(Json(RangeProofResponse {
+    Ok(ZeroCiphertextProofResponse {
         proof_data: STANDARD.encode(&proof_data),
-    }))
+    })
+}
+
+/// Generate all proofs for a withdraw with a shared Pedersen opening
+///
+/// POST /v1/proofs/withdraw-bundle
+#[utoipa::path(
+    post,
+    path = "/v1/proofs/withdraw-bundle",
+    request_body = WithdrawBundleRequest,
+    responses(
+        (status = 200, body = WithdrawBundleResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "proofs"
+)]
+async fn withdraw_bundle(
+    State(state): State<AppState>,
+    Json(mut req): Json<WithdrawBundleRequest>,
+) -> Result<Json<WithdrawBundleResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        amount = %req.amount,
+        "Generating withdraw proof bundle"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    state.accounts.resolve(&mut req).await?;
+
+    let sealing = state.sealing;
+    let response = state
+        .proof_limiter
+        .run(move || generate_withdraw_bundle(req, &sealing))
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Verify and generate a withdraw bundle request (timestamp already validated)
+///
+/// Shared by the synchronous endpoint and the job queue.
+pub(crate) fn generate_withdraw_bundle(
+    req: WithdrawBundleRequest,
+    sealing: &SealingKeys,
+) -> Result<WithdrawBundleResponse> {
+    generate_withdraw_bundle_with_progress(req, sealing, |_| {})
+}
+
+/// [`generate_withdraw_bundle`], reporting each finished proof to `on_proof`
+pub(crate) fn generate_withdraw_bundle_with_progress(
+    req: WithdrawBundleRequest,
+    sealing: &SealingKeys,
+    on_proof: impl FnMut(&'static str),
+) -> Result<WithdrawBundleResponse> {
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => sealing.open(&req.elgamal_signature),
+        ae_signature = "ae_signature" => sealing.open(&req.ae_signature),
+        ciphertext = "current_ciphertext"
+            => parse_required(&req.current_ciphertext, ProofGenerator::parse_ciphertext),
+        decryptable_balance = "current_decryptable_balance"
+            => parse_required(
+                &req.current_decryptable_balance,
+                ProofGenerator::parse_ae_ciphertext,
+            ),
+        amount = "amount" => ProofGenerator::parse_amount(&req.amount),
+    );
+    let ae_signature = Signature::from(*ae_signature);
+
+    // Verify request and AE key signatures
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+    ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, &ae_signature)?;
+
+    // Derive keys
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+    let ae_key = ProofGenerator::derive_ae_key(&ae_signature)?;
+
+    // Generate the proofs
+    let proofs = ProofGenerator::generate_withdraw_proof_with_progress(
+        &elgamal_keypair,
+        &ae_key,
+        &ciphertext,
+        &decryptable_balance,
+        amount,
+        on_proof,
+    )?;
+
+    info!("Generated withdraw proof bundle");
+
+    Ok(WithdrawBundleResponse {
+        equality_proof_data: STANDARD.encode(bytemuck::bytes_of(&proofs.equality_proof_data)),
+        range_proof_data: STANDARD.encode(bytemuck::bytes_of(&proofs.range_proof_data)),
+        remaining_balance_commitment: STANDARD.encode(proofs.remaining_balance_commitment),
+        remaining_balance_opening: STANDARD.encode(proofs.remaining_balance_opening.to_bytes()),
+        new_decryptable_available_balance: STANDARD
+            .encode(proofs.new_decryptable_available_balance),
+    })
+}
+
+/// Validate the request timestamp and consume its signature
+///
+/// The signature itself is verified later, together with the request body.
+pub(crate) async fn validate_request(
+    config: &Config,
+    replay_cache: &ReplayCache,
+    timestamp: i64,
+    request_signature: &str,
+) -> Result<()> {
+    validate_timestamp(timestamp, config.timestamp_tolerance_secs)?;
+
+    let signature = ProofGenerator::parse_signature(request_signature)
+        .map_err(|e| BackendError::from(e).for_field("request_signature"))?;
+    replay_cache.check_and_record(&signature, timestamp).await
 }
 
 /// Validate that timestamp is within tolerance
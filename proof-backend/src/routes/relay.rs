@@ -0,0 +1,117 @@
+//! Transaction relayer endpoints
+//!
+//! Submit transactions with this server's relayer as fee payer, for wallets
+//! without SOL. Not served when no relayer is configured.
+
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use tracing::info;
+
+use super::proofs::AppState;
+use crate::{
+    auth::AuthenticatedKey,
+    error::{parse_each, parse_fields, BackendError, ErrorResponse, Result},
+    services::{relayer::decode_transaction, Relayer},
+    types::{RelayInfoResponse, RelaySubmitRequest, RelaySubmitResponse, MAX_RELAY_TRANSACTIONS},
+};
+
+/// Create relay router; empty unless a relayer is configured
+pub fn relay_router(state: AppState) -> Router {
+    if state.relayer.is_none() {
+        return Router::new();
+    }
+
+    Router::new()
+        .route("/v1/relay/info", get(relay_info))
+        .route("/v1/relay/submit", post(relay_submit))
+        .with_state(state)
+}
+
+fn relayer(state: &AppState) -> Result<&Relayer> {
+    state
+        .relayer
+        .as_ref()
+        .ok_or_else(|| BackendError::NotFound("Transaction relayer".to_string()))
+}
+
+/// Get the relayer's fee payer and limits
+///
+/// GET /v1/relay/info
+#[utoipa::path(
+    get,
+    path = "/v1/relay/info",
+    responses(
+        (status = 200, body = RelayInfoResponse),
+        (status = "4XX", body = ErrorResponse),
+    ),
+    tag = "relay"
+)]
+async fn relay_info(State(state): State<AppState>) -> Result<Json<RelayInfoResponse>> {
+    let relayer = relayer(&state)?;
+    let config = relayer.config();
+
+    Ok(Json(RelayInfoResponse {
+        fee_payer: relayer.pubkey().to_string(),
+        max_lamports_per_transaction: config.max_lamports_per_transaction.to_string(),
+        daily_lamports_per_key: config.daily_lamports_per_key.to_string(),
+        allowed_programs: relayer
+            .allowed_programs()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    }))
+}
+
+/// Sign and submit transactions as fee payer
+///
+/// POST /v1/relay/submit
+///
+/// Transactions are built with the relayer as fee payer, e.g. by
+/// `/v1/tx/withdraw` with `relay: true`, and signed by the wallet.
+#[utoipa::path(
+    post,
+    path = "/v1/relay/submit",
+    request_body = RelaySubmitRequest,
+    responses(
+        (status = 200, body = RelaySubmitResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "relay"
+)]
+async fn relay_submit(
+    State(state): State<AppState>,
+    api_key: Option<Extension<AuthenticatedKey>>,
+    Json(req): Json<RelaySubmitRequest>,
+) -> Result<Json<RelaySubmitResponse>> {
+    let relayer = relayer(&state)?;
+    // Requests pass unauthenticated while no key exists yet, e.g. with only
+    // the admin API configured; they must not spend the relayer's SOL
+    let Some(Extension(AuthenticatedKey(key))) = &api_key else {
+        return Err(BackendError::Forbidden(
+            "Relaying requires an API key or JWT".to_string(),
+        ));
+    };
+    let identity = key.key.as_str();
+
+    parse_fields!(
+        transactions = "transactions"
+            => parse_each(&req.transactions, MAX_RELAY_TRANSACTIONS, decode_transaction),
+    );
+    if transactions.is_empty() {
+        return Err(BackendError::BadRequest(
+            "At least one transaction is required".to_string(),
+        ));
+    }
+    info!(transactions = transactions.len(), "Relaying transactions");
+
+    let (signatures, lamports) = relayer.submit(identity, transactions).await?;
+
+    Ok(Json(RelaySubmitResponse {
+        signatures: signatures.iter().map(ToString::to_string).collect(),
+        lamports: lamports.to_string(),
+    }))
+}
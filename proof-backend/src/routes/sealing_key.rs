@@ -0,0 +1,34 @@
+//! Sealing key endpoint
+
+use axum::{extract::State, routing::get, Json, Router};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{sealing::SealingKeys, types::SealingKeyResponse};
+
+/// Path of the sealing key endpoint, open without an API key
+pub const SEALING_KEY_PATH: &str = "/v1/sealing-key";
+
+/// Create sealing key router
+pub fn sealing_key_router(keys: SealingKeys) -> Router {
+    Router::new()
+        .route(SEALING_KEY_PATH, get(sealing_key))
+        .with_state(keys)
+}
+
+/// Get the X25519 key that key-derivation signatures are sealed to
+///
+/// GET /v1/sealing-key
+#[utoipa::path(
+    get,
+    path = "/v1/sealing-key",
+    responses((status = 200, body = SealingKeyResponse)),
+    security(()),
+    tag = "health"
+)]
+async fn sealing_key(State(keys): State<SealingKeys>) -> Json<SealingKeyResponse> {
+    Json(SealingKeyResponse {
+        pubkey: STANDARD.encode(keys.pubkey()),
+        rotates_at: keys.rotates_at(),
+        sealed_required: keys.require_sealed(),
+    })
+}
@@ -0,0 +1,31 @@
+//! Service key endpoint
+
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::{signing::ResponseSigner, types::ServiceKeyResponse};
+
+/// Path of the service key endpoint, open without an API key
+pub const SERVICE_KEY_PATH: &str = "/v1/service-key";
+
+/// Create service key router
+pub fn service_key_router(signer: ResponseSigner) -> Router {
+    Router::new()
+        .route(SERVICE_KEY_PATH, get(service_key))
+        .with_state(signer)
+}
+
+/// Get the public key that signs API responses
+///
+/// GET /v1/service-key
+#[utoipa::path(
+    get,
+    path = "/v1/service-key",
+    responses((status = 200, body = ServiceKeyResponse)),
+    security(()),
+    tag = "health"
+)]
+async fn service_key(State(signer): State<ResponseSigner>) -> Json<ServiceKeyResponse> {
+    Json(ServiceKeyResponse {
+        pubkey: signer.pubkey().to_string(),
+    })
+}
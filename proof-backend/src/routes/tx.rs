@@ -0,0 +1,311 @@
+//! Transaction builder endpoints
+
+use axum::{extract::State, routing::post, Json, Router};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::str::FromStr;
+use tracing::info;
+
+use super::proofs::{validate_request, AppState};
+use crate::{
+    error::{parse_fields, BackendError, ErrorResponse, Result},
+    services::{
+        account_state::parse_required,
+        tx_builder::{VaultWithdrawal, WithdrawAccounts},
+        ProofGenerator, TxBuilder,
+    },
+    types::{
+        AccountMetaData, ContextStateRequest, ContextStateResponse, InstructionData,
+        WithdrawOperation, WithdrawTxRequest, WithdrawTxResponse,
+    },
+};
+
+/// Create transaction builder router
+pub fn tx_router(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/tx/withdraw", post(withdraw_tx))
+        .route("/v1/tx/context-state", post(context_state))
+        .with_state(state)
+}
+
+/// Build the full transaction set for an SVS-2 withdraw or redeem
+///
+/// POST /v1/tx/withdraw
+#[utoipa::path(
+    post,
+    path = "/v1/tx/withdraw",
+    request_body = WithdrawTxRequest,
+    responses(
+        (status = 200, body = WithdrawTxResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "tx"
+)]
+async fn withdraw_tx(
+    State(state): State<AppState>,
+    Json(mut req): Json<WithdrawTxRequest>,
+) -> Result<Json<WithdrawTxResponse>> {
+    info!(
+        wallet = %req.wallet_pubkey,
+        token_account = %req.token_account,
+        operation = ?req.operation,
+        shares = ?req.shares,
+        "Building withdraw transactions"
+    );
+
+    // Validate timestamp and reject replays
+    validate_request(
+        &state.config,
+        &state.replay_cache,
+        req.timestamp,
+        &req.request_signature,
+    )
+    .await?;
+
+    // Read omitted balances from the token account
+    state.accounts.resolve(&mut req).await?;
+
+    // Parse inputs
+    parse_fields!(
+        wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(&req.wallet_pubkey),
+        token_account = "token_account" => ProofGenerator::parse_pubkey(&req.token_account),
+        request_signature = "request_signature"
+            => ProofGenerator::parse_signature(&req.request_signature),
+        elgamal_signature = "elgamal_signature" => state.sealing.open(&req.elgamal_signature),
+        ae_signature = "ae_signature" => state.sealing.open(&req.ae_signature),
+        ciphertext = "current_ciphertext"
+            => parse_required(&req.current_ciphertext, ProofGenerator::parse_ciphertext),
+        decryptable_balance = "current_decryptable_balance"
+            => parse_required(
+                &req.current_decryptable_balance,
+                ProofGenerator::parse_ae_ciphertext,
+            ),
+        shares = "shares" => req.shares.as_deref().map(ProofGenerator::parse_amount).transpose(),
+        assets = "assets" => ProofGenerator::parse_amount(&req.assets),
+        memo_hash = "memo_hash"
+            => req.memo_hash.as_deref().map(ProofGenerator::parse_bytes::<32>).transpose(),
+        recent_blockhash = "recent_blockhash"
+            => ProofGenerator::parse_blockhash(&req.recent_blockhash),
+        vault = "vault" => ProofGenerator::parse_pubkey(&req.vault),
+        asset_mint = "asset_mint" => ProofGenerator::parse_pubkey(&req.asset_mint),
+        user_asset_account = "user_asset_account"
+            => ProofGenerator::parse_pubkey(&req.user_asset_account),
+        asset_vault = "asset_vault" => ProofGenerator::parse_pubkey(&req.asset_vault),
+        shares_mint = "shares_mint" => ProofGenerator::parse_pubkey(&req.shares_mint),
+        asset_token_program = "asset_token_program"
+            => ProofGenerator::parse_pubkey(&req.asset_token_program),
+    );
+    let ae_signature = Signature::from(*ae_signature);
+    let program_id = Pubkey::from_str(&state.config.svs2_program_id)
+        .map_err(|e| BackendError::Internal(format!("Invalid SVS2 program id: {e}")))?;
+
+    // `withdraw` burns the vault's preview for `assets`, `redeem` exactly `shares`
+    let shares = match (shares, req.operation) {
+        (Some(shares), _) => shares,
+        (None, WithdrawOperation::Withdraw) => {
+            state
+                .accounts
+                .preview_withdraw(&program_id, &vault, assets)
+                .await?
+        }
+        (None, WithdrawOperation::Redeem) => {
+            return Err(BackendError::BadRequest(
+                "shares is required for redeem".to_string(),
+            ))
+        }
+    };
+
+    let withdrawal = match req.operation {
+        WithdrawOperation::Withdraw => VaultWithdrawal::Withdraw {
+            assets,
+            // The proofs are bound to exactly `shares`, so any other burn fails anyway
+            max_shares_in: shares,
+            memo_hash,
+        },
+        WithdrawOperation::Redeem => {
+            if memo_hash.is_some() {
+                return Err(BackendError::BadRequest(
+                    "memo_hash is only supported for withdraw".to_string(),
+                ));
+            }
+            VaultWithdrawal::Redeem {
+                shares,
+                min_assets_out: assets,
+            }
+        }
+    };
+
+    // Relayed transactions are paid for by the relayer
+    let fee_payer = match (req.relay, &state.relayer) {
+        (false, _) => wallet_pubkey,
+        (true, Some(relayer)) => relayer.pubkey(),
+        (true, None) => {
+            return Err(BackendError::BadRequest(
+                "This server has no transaction relayer".to_string(),
+            ))
+        }
+    };
+
+    let accounts = WithdrawAccounts {
+        program_id,
+        user: wallet_pubkey,
+        vault,
+        asset_mint,
+        user_asset_account,
+        asset_vault,
+        shares_mint,
+        user_shares_account: token_account,
+        asset_token_program,
+    };
+
+    // Verify request and AE key signatures
+    ProofGenerator::verify_request_signature(
+        &wallet_pubkey,
+        req.timestamp,
+        &token_account,
+        req.nonce.as_deref(),
+        &request_signature,
+    )?;
+    ProofGenerator::verify_ae_signature(&wallet_pubkey, &token_account, &ae_signature)?;
+
+    // Derive keys
+    let elgamal_keypair =
+        ProofGenerator::derive_elgamal_keypair(&elgamal_signature, &token_account)?;
+    let ae_key = ProofGenerator::derive_ae_key(&ae_signature)?;
+
+    // Generate the proofs and assemble the transactions
+    let (proofs, built) = state
+        .proof_limiter
+        .run(move || {
+            let proofs = ProofGenerator::generate_withdraw_proof(
+                &elgamal_keypair,
+                &ae_key,
+                &ciphertext,
+                &decryptable_balance,
+                shares,
+            )?;
+            let built = TxBuilder::build_withdraw_transactions(
+                &accounts,
+                &withdrawal,
+                &proofs,
+                &fee_payer,
+                recent_blockhash,
+            )?;
+            Ok((proofs, built))
+        })
+        .await?;
+
+    let transactions = encode_transactions(&built.transactions)?;
+
+    info!(
+        transactions = transactions.len(),
+        relayed = req.relay,
+        equality_proof_context = %built.equality_proof_context,
+        range_proof_context = %built.range_proof_context,
+        "Built withdraw transactions"
+    );
+
+    Ok(Json(WithdrawTxResponse {
+        transactions,
+        fee_payer: fee_payer.to_string(),
+        equality_proof_context: built.equality_proof_context.to_string(),
+        range_proof_context: built.range_proof_context.to_string(),
+        new_decryptable_available_balance: STANDARD
+            .encode(proofs.new_decryptable_available_balance),
+    }))
+}
+
+/// Bincode-serialize unsigned transactions as base64
+pub(crate) fn encode_transactions(transactions: &[Transaction]) -> Result<Vec<String>> {
+    transactions
+        .iter()
+        .map(|tx| {
+            bincode::serialize(tx)
+                .map(|bytes| STANDARD.encode(bytes))
+                .map_err(|e| {
+                    BackendError::Internal(format!("Failed to serialize transaction: {e}"))
+                })
+        })
+        .collect()
+}
+
+/// Build create/verify/close instructions for one proof context account
+///
+/// POST /v1/tx/context-state
+#[utoipa::path(
+    post,
+    path = "/v1/tx/context-state",
+    request_body = ContextStateRequest,
+    responses(
+        (status = 200, body = ContextStateResponse),
+        (status = "4XX", body = ErrorResponse),
+        (status = "5XX", body = ErrorResponse),
+    ),
+    tag = "tx"
+)]
+async fn context_state(Json(req): Json<ContextStateRequest>) -> Result<Json<ContextStateResponse>> {
+    info!(
+        payer = %req.payer,
+        proof_type = ?req.proof_type,
+        "Building context state instructions"
+    );
+
+    // Parse inputs
+    parse_fields!(
+        payer = "payer" => ProofGenerator::parse_pubkey(&req.payer),
+        authority = "context_state_authority" => req
+            .context_state_authority
+            .as_deref()
+            .map(ProofGenerator::parse_pubkey)
+            .transpose(),
+        proof_data = "proof_data" => STANDARD.decode(&req.proof_data).map_err(|e| {
+            BackendError::invalid_format("INVALID_ENCODING", "base64", format!("invalid base64: {e}"))
+        }),
+    );
+    let authority = authority.unwrap_or(payer);
+    // A fresh pubkey prefix is a valid seed (base58, at most 32 chars)
+    let seed = req
+        .seed
+        .unwrap_or_else(|| Keypair::new().pubkey().to_string()[..32].to_string());
+
+    let bundle = TxBuilder::context_state_instructions(
+        &payer,
+        &authority,
+        &seed,
+        req.proof_type,
+        &proof_data,
+    )?;
+
+    Ok(Json(ContextStateResponse {
+        context_state_account: bundle.context_state_account.to_string(),
+        seed: bundle.seed,
+        space: bundle.space,
+        lamports: bundle.lamports.to_string(),
+        create_instruction: instruction_data(&bundle.create_instruction),
+        verify_instruction: instruction_data(&bundle.verify_instruction),
+        close_instruction: instruction_data(&bundle.close_instruction),
+    }))
+}
+
+/// Serialize an instruction for JSON responses
+fn instruction_data(instruction: &Instruction) -> InstructionData {
+    InstructionData {
+        program_id: instruction.program_id.to_string(),
+        accounts: instruction
+            .accounts
+            .iter()
+            .map(|meta| AccountMetaData {
+                pubkey: meta.pubkey.to_string(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: STANDARD.encode(&instruction.data),
+    }
+}
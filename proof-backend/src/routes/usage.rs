@@ -0,0 +1,57 @@
+//! API key usage endpoint
+
+use axum::{extract::State, routing::get, Extension, Json, Router};
+
+use super::proofs::AppState;
+use crate::{
+    auth::AuthenticatedKey,
+    error::{BackendError, ErrorResponse, Result},
+    types::UsageResponse,
+};
+
+/// Path of the usage endpoint, open to every authenticated key
+pub const USAGE_PATH: &str = "/v1/usage";
+
+/// Create usage router
+pub fn usage_router(state: AppState) -> Router {
+    Router::new()
+        .route(USAGE_PATH, get(get_usage))
+        .with_state(state)
+}
+
+/// Get request counts, proof time, quota and remaining quota of the calling
+/// API key
+///
+/// GET /v1/usage
+#[utoipa::path(
+    get,
+    path = "/v1/usage",
+    responses(
+        (status = 200, body = UsageResponse),
+        (status = "4XX", body = ErrorResponse),
+    ),
+    tag = "usage"
+)]
+async fn get_usage(
+    State(state): State<AppState>,
+    api_key: Option<Extension<AuthenticatedKey>>,
+) -> Result<Json<UsageResponse>> {
+    let Some(Extension(AuthenticatedKey(key))) = api_key else {
+        return Err(BackendError::NotFound(
+            "Usage is tracked per API key; none are configured".to_string(),
+        ));
+    };
+
+    let usage = state.usage.snapshot(&key.key).await?;
+    let remaining = key.quota.as_ref().map(|quota| usage.remaining(quota));
+
+    Ok(Json(UsageResponse {
+        api_key: key.label().to_string(),
+        day: usage.day,
+        month: usage.month,
+        daily: usage.daily,
+        monthly: usage.monthly,
+        quota: key.quota,
+        remaining,
+    }))
+}
@@ -0,0 +1,253 @@
+//! Sealed key-derivation signatures
+//!
+//! `elgamal_signature` and `ae_signature` are the seeds of the user's ElGamal
+//! and AE secret keys. Clients can seal them (libsodium sealed box: X25519 +
+//! XSalsa20-Poly1305) to an X25519 key that only ever exists in this process's
+//! memory, so the plaintext never passes through proxies, request buffers or
+//! job queues. The key rotates, and a payload sealed to the previous key is
+//! still accepted for one rotation period.
+//!
+//! Handlers follow these rules for request secrets:
+//! - open them with [`SealingKeys::open`], which returns a zeroizing buffer
+//! - never log or format them; [`SecretSignature`] redacts itself in `Debug`
+//! - derive keys through `ProofGenerator`, whose ElGamal and AE keys zeroize on drop
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use crypto_box::{aead::OsRng, SecretKey};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tracing::info;
+use zeroize::Zeroizing;
+
+use crate::{
+    error::{BackendError, Result},
+    services::ProofGenerator,
+    types::SecretSignature,
+};
+
+/// Current and previous sealing keys
+struct KeyRing {
+    current: SecretKey,
+    previous: Option<SecretKey>,
+    rotates_at: Option<i64>,
+}
+
+/// In-memory X25519 keys that request secrets are sealed to
+#[derive(Clone)]
+pub struct SealingKeys {
+    ring: Arc<RwLock<KeyRing>>,
+    rotation: Option<Duration>,
+    require_sealed: bool,
+}
+
+impl SealingKeys {
+    /// Generate a sealing key, replaced every `rotation_secs` (0 never rotates)
+    pub fn new(rotation_secs: u64, require_sealed: bool) -> Self {
+        let rotation = (rotation_secs > 0).then(|| Duration::from_secs(rotation_secs));
+        Self {
+            ring: Arc::new(RwLock::new(KeyRing {
+                current: SecretKey::generate(&mut OsRng),
+                previous: None,
+                rotates_at: rotation.map(next_rotation),
+            })),
+            rotation,
+            require_sealed,
+        }
+    }
+
+    /// Public key clients seal to
+    pub fn pubkey(&self) -> [u8; 32] {
+        self.read(|ring| ring.current.public_key().to_bytes())
+    }
+
+    /// Unix time of the next rotation, if the key rotates
+    pub fn rotates_at(&self) -> Option<i64> {
+        self.read(|ring| ring.rotates_at)
+    }
+
+    /// Whether plain signatures are rejected
+    pub fn require_sealed(&self) -> bool {
+        self.require_sealed
+    }
+
+    /// These keys, also accepting plain signatures; for requests unsealed by
+    /// [`SealingKeys::unseal_in_place`] before they were persisted
+    pub fn accepting_plain(&self) -> Self {
+        Self {
+            require_sealed: false,
+            ..self.clone()
+        }
+    }
+
+    /// Replace the current key; the old one stays valid until the next rotation
+    pub fn rotate(&self) {
+        let mut ring = self.ring.write().unwrap_or_else(|e| e.into_inner());
+        let current = std::mem::replace(&mut ring.current, SecretKey::generate(&mut OsRng));
+        ring.previous = Some(current);
+        ring.rotates_at = self.rotation.map(next_rotation);
+    }
+
+    /// Rotate the key on its interval for the life of the process
+    pub fn spawn_rotation(&self) {
+        let Some(rotation) = self.rotation else {
+            return;
+        };
+        let keys = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(rotation).await;
+                keys.rotate();
+                info!("Rotated sealing key");
+            }
+        });
+    }
+
+    /// Recover the 64 signature bytes of a plain or sealed signature
+    pub fn open(&self, secret: &SecretSignature) -> Result<Zeroizing<[u8; 64]>> {
+        match secret {
+            SecretSignature::Plain(_) if self.require_sealed => Err(BackendError::BadRequest(
+                "Key-derivation signatures must be sealed to the key from /v1/sealing-key"
+                    .to_string(),
+            )),
+            SecretSignature::Plain(s) => {
+                let signature = ProofGenerator::parse_signature(s)?;
+                Ok(Zeroizing::new(signature.into()))
+            }
+            SecretSignature::Sealed { sealed } => {
+                let ciphertext = STANDARD
+                    .decode(sealed)
+                    .map_err(|e| BackendError::InvalidSignature(format!("Invalid base64: {e}")))?;
+                let plaintext = Zeroizing::new(self.unseal(&ciphertext)?);
+                if plaintext.len() != 64 {
+                    return Err(BackendError::InvalidSignature(format!(
+                        "Sealed signature must be 64 bytes, got {}",
+                        plaintext.len()
+                    )));
+                }
+
+                let mut bytes = Zeroizing::new([0u8; 64]);
+                bytes.copy_from_slice(&plaintext);
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Replace a sealed signature with its plain form, which stays valid
+    /// after the sealing key rotates
+    pub fn unseal_in_place(&self, secret: &mut SecretSignature) -> Result<()> {
+        let signature = self.open(secret)?;
+        *secret = SecretSignature::Plain(STANDARD.encode(*signature));
+        Ok(())
+    }
+
+    fn unseal(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        self.read(|ring| {
+            ring.current
+                .unseal(ciphertext)
+                .or_else(|e| match &ring.previous {
+                    Some(previous) => previous.unseal(ciphertext),
+                    None => Err(e),
+                })
+                .map_err(|_| {
+                    BackendError::InvalidSignature(
+                        "Sealed signature could not be opened; seal it to the current key \
+                         from /v1/sealing-key"
+                            .to_string(),
+                    )
+                })
+        })
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&KeyRing) -> T) -> T {
+        // A poisoned lock only means a rotation panicked; the keys are intact
+        f(&self.ring.read().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+fn next_rotation(rotation: Duration) -> i64 {
+    Utc::now().timestamp() + rotation.as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_box::PublicKey;
+
+    fn seal(keys: &SealingKeys, plaintext: &[u8]) -> SecretSignature {
+        let sealed = PublicKey::from_bytes(keys.pubkey())
+            .seal(&mut OsRng, plaintext)
+            .unwrap();
+        SecretSignature::Sealed {
+            sealed: STANDARD.encode(sealed),
+        }
+    }
+
+    #[test]
+    fn test_open_sealed_signature() {
+        let keys = SealingKeys::new(0, false);
+
+        let opened = keys.open(&seal(&keys, &[7u8; 64])).unwrap();
+
+        assert_eq!(*opened, [7u8; 64]);
+        assert_eq!(keys.rotates_at(), None);
+    }
+
+    #[test]
+    fn test_open_plain_signature() {
+        let keys = SealingKeys::new(0, false);
+        let plain = SecretSignature::Plain(STANDARD.encode([3u8; 64]));
+
+        assert_eq!(*keys.open(&plain).unwrap(), [3u8; 64]);
+
+        let strict = SealingKeys::new(0, true);
+        assert!(matches!(
+            strict.open(&plain),
+            Err(BackendError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_previous_key_accepted_for_one_rotation() {
+        let keys = SealingKeys::new(60, false);
+        let sealed = seal(&keys, &[1u8; 64]);
+
+        keys.rotate();
+        assert!(keys.open(&sealed).is_ok());
+
+        keys.rotate();
+        assert!(matches!(
+            keys.open(&sealed),
+            Err(BackendError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_unsealed_signature_outlives_rotation() {
+        let keys = SealingKeys::new(60, true);
+        let mut secret = seal(&keys, &[5u8; 64]);
+
+        keys.unseal_in_place(&mut secret).unwrap();
+        keys.rotate();
+        keys.rotate();
+
+        assert!(keys.open(&secret).is_err());
+        assert_eq!(*keys.accepting_plain().open(&secret).unwrap(), [5u8; 64]);
+    }
+
+    #[test]
+    fn test_sealed_signature_wrong_length() {
+        let keys = SealingKeys::new(0, false);
+
+        assert!(keys.open(&seal(&keys, &[1u8; 32])).is_err());
+    }
+
+    #[test]
+    fn test_secret_signature_debug_redacted() {
+        let plain = SecretSignature::Plain("c2VjcmV0".to_string());
+
+        assert!(!format!("{plain:?}").contains("c2VjcmV0"));
+    }
+}
@@ -0,0 +1,599 @@
+//! Token Account and Vault State
+//!
+//! Reads the confidential transfer extension of a Token-2022 account from a
+//! Solana RPC node, so requests can omit balance ciphertexts and have the
+//! backend use the account's current state instead of a possibly stale copy.
+//! SVS-2 vault accounts are read the same way to preview the shares a
+//! `withdraw` burns, with the vault's own math (`svs-math`).
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::{
+    pubkey::Pubkey,
+    sysvar::{self, clock::Clock},
+};
+use std::time::Duration;
+use svs_math::Rounding;
+use tracing::{debug, instrument};
+
+use super::{tx_builder::TOKEN_2022_PROGRAM_ID, ProofGenerator};
+use crate::{
+    error::{parse_fields, BackendError, Result},
+    types::{
+        ApplyPendingRequest, BalanceDecryptRequest, EqualityProofRequest, TransferProofRequest,
+        WithdrawBundleRequest, WithdrawTxRequest, ZeroCiphertextProofRequest,
+    },
+};
+
+/// Size of the base SPL token account
+const BASE_ACCOUNT_LEN: usize = 165;
+
+/// `AccountType::Account`, stored right after the base account
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// `ExtensionType::ConfidentialTransferAccount`
+const CONFIDENTIAL_TRANSFER_ACCOUNT_EXTENSION: u16 = 5;
+
+/// Size of `ConfidentialTransferAccount`
+const CONFIDENTIAL_TRANSFER_ACCOUNT_LEN: usize = 295;
+
+/// Offset of `supply` in a mint, after the `COption<Pubkey>` mint authority
+const MINT_SUPPLY_OFFSET: usize = 36;
+
+/// Anchor account discriminator of SVS-2 `ConfidentialVault`
+const CONFIDENTIAL_VAULT_DISCRIMINATOR: [u8; 8] = [107, 161, 220, 30, 88, 176, 39, 252];
+
+/// Timeout for a single RPC call
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Confidential transfer state of a token account
+#[derive(Debug, Clone)]
+pub struct ConfidentialAccountState {
+    /// Wallet that owns the token account
+    pub owner: Pubkey,
+    pub pending_balance_lo: [u8; 64],
+    pub pending_balance_hi: [u8; 64],
+    pub available_balance: [u8; 64],
+    pub decryptable_available_balance: [u8; 36],
+    pub pending_balance_credit_counter: u64,
+}
+
+impl ConfidentialAccountState {
+    /// Decode a Token-2022 account with the confidential transfer extension
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let invalid = |reason: &str| BackendError::BadRequest(format!("Token account {reason}"));
+
+        if data.len() <= BASE_ACCOUNT_LEN || data[BASE_ACCOUNT_LEN] != ACCOUNT_TYPE_ACCOUNT {
+            return Err(invalid("has no extensions"));
+        }
+        let owner = Pubkey::try_from(&data[32..64]).map_err(|_| invalid("is malformed"))?;
+
+        // Extensions are (u16 type, u16 length, value) entries
+        let mut tlv = &data[BASE_ACCOUNT_LEN + 1..];
+        while tlv.len() >= 4 {
+            let extension = u16::from_le_bytes([tlv[0], tlv[1]]);
+            let len = u16::from_le_bytes([tlv[2], tlv[3]]) as usize;
+            let value = tlv.get(4..4 + len).ok_or_else(|| invalid("is malformed"))?;
+
+            if extension == CONFIDENTIAL_TRANSFER_ACCOUNT_EXTENSION {
+                if len != CONFIDENTIAL_TRANSFER_ACCOUNT_LEN {
+                    return Err(invalid("has a malformed confidential transfer extension"));
+                }
+                return Ok(Self::from_extension(owner, value));
+            }
+            tlv = &tlv[4 + len..];
+        }
+
+        Err(invalid("is not configured for confidential transfers"))
+    }
+
+    /// `value` is exactly `CONFIDENTIAL_TRANSFER_ACCOUNT_LEN` bytes
+    fn from_extension(owner: Pubkey, value: &[u8]) -> Self {
+        // approved (1) and the ElGamal key (32) precede the balances; the
+        // credit flags (2) follow them
+        let field = |offset: usize, len: usize| &value[offset..offset + len];
+        let counter = u64::from_le_bytes(field(263, 8).try_into().unwrap());
+
+        Self {
+            owner,
+            pending_balance_lo: field(33, 64).try_into().unwrap(),
+            pending_balance_hi: field(97, 64).try_into().unwrap(),
+            available_balance: field(161, 64).try_into().unwrap(),
+            decryptable_available_balance: field(225, 36).try_into().unwrap(),
+            pending_balance_credit_counter: counter,
+        }
+    }
+}
+
+/// Conversion inputs of an SVS-2 `ConfidentialVault` account; the share
+/// count is the supply of `shares_mint`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultState {
+    pub shares_mint: Pubkey,
+    pub total_assets: u64,
+    pub decimals_offset: u8,
+    pub paused: bool,
+    pub locked_profit: u64,
+    pub last_harvest_ts: i64,
+    pub profit_unlock_period: i64,
+}
+
+impl VaultState {
+    /// Decode the Borsh-encoded account, discriminator included
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let invalid = || BackendError::BadRequest("Vault account is malformed".to_string());
+
+        if data.get(..8) != Some(CONFIDENTIAL_VAULT_DISCRIMINATOR.as_slice()) {
+            return Err(BackendError::BadRequest(
+                "Account is not an SVS-2 vault".to_string(),
+            ));
+        }
+        let mut rest = &data[8..];
+        let mut take = |len: usize| -> Result<&[u8]> {
+            let (field, tail) = rest.split_at_checked(len).ok_or_else(invalid)?;
+            rest = tail;
+            Ok(field)
+        };
+        let u64_at = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+
+        // authority, asset_mint
+        take(2 * 32)?;
+        let shares_mint = Pubkey::new_from_array(take(32)?.try_into().unwrap());
+        // asset_vault
+        take(32)?;
+        let total_assets = u64_at(take(8)?);
+        let decimals_offset = take(1)?[0];
+        // bump
+        take(1)?;
+        let paused = take(1)?[0] != 0;
+        // vault_id, then the auditor key's Option tag and value
+        take(8)?;
+        if take(1)?[0] == 1 {
+            take(32)?;
+        }
+        // confidential_authority, guardian, swap_adapter, harvester
+        take(4 * 32)?;
+        let locked_profit = u64_at(take(8)?);
+        let last_harvest_ts = u64_at(take(8)?) as i64;
+        let profit_unlock_period = u64_at(take(8)?) as i64;
+
+        Ok(Self {
+            shares_mint,
+            total_assets,
+            decimals_offset,
+            paused,
+            locked_profit,
+            last_harvest_ts,
+            profit_unlock_period,
+        })
+    }
+
+    /// Shares `withdraw(assets)` burns at unix time `now` with `shares_supply`
+    /// shares minted: the vault's `preview_withdraw`, for assets without a
+    /// transfer fee
+    pub fn preview_withdraw(&self, assets: u64, shares_supply: u64, now: i64) -> Result<u64> {
+        if self.paused {
+            return Err(BackendError::BadRequest("Vault is paused".to_string()));
+        }
+        let math_error =
+            |e| BackendError::BadRequest(format!("Cannot preview withdrawing {assets}: {e}"));
+
+        let locked = svs_math::locked_profit(
+            self.locked_profit,
+            self.last_harvest_ts,
+            self.profit_unlock_period,
+            now,
+        )
+        .map_err(math_error)?;
+        svs_math::convert_to_shares(
+            assets,
+            self.total_assets.saturating_sub(locked),
+            shares_supply,
+            self.decimals_offset,
+            Rounding::Ceiling,
+        )
+        .map_err(math_error)
+    }
+}
+
+/// Requests whose balance fields can be read from their token account
+pub trait AccountBacked {
+    fn wallet_pubkey(&self) -> &str;
+    fn token_account(&self) -> &str;
+
+    /// Whether any balance field was omitted
+    fn needs_account_state(&self) -> bool;
+
+    /// Fill omitted balance fields from the account
+    fn fill_account_state(&mut self, state: &ConfidentialAccountState);
+}
+
+/// Fetches token accounts from the configured RPC node
+#[derive(Clone)]
+pub struct AccountStateFetcher {
+    client: reqwest::Client,
+    rpc_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<RpcResult>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct RpcResult {
+    value: Option<RpcAccount>,
+}
+
+#[derive(Deserialize)]
+struct RpcAccount {
+    /// `[data, encoding]`
+    data: (String, String),
+    owner: String,
+}
+
+/// An account with its data decoded
+struct Account {
+    owner: String,
+    data: Vec<u8>,
+}
+
+impl AccountStateFetcher {
+    /// Create a fetcher; without an RPC URL balances must be sent by clients
+    pub fn new(rpc_url: Option<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(RPC_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { client, rpc_url }
+    }
+
+    /// Fill a request's omitted balance fields from its token account
+    ///
+    /// Returns the account state if it was fetched.
+    pub async fn resolve<R: AccountBacked>(
+        &self,
+        req: &mut R,
+    ) -> Result<Option<ConfidentialAccountState>> {
+        if !req.needs_account_state() {
+            return Ok(None);
+        }
+
+        parse_fields!(
+            token_account = "token_account" => ProofGenerator::parse_pubkey(req.token_account()),
+            wallet_pubkey = "wallet_pubkey" => ProofGenerator::parse_pubkey(req.wallet_pubkey()),
+        );
+        let state = self.fetch(&token_account).await?;
+        if state.owner != wallet_pubkey {
+            return Err(BackendError::Forbidden(format!(
+                "Token account {token_account} is not owned by {wallet_pubkey}"
+            )));
+        }
+
+        req.fill_account_state(&state);
+        Ok(Some(state))
+    }
+
+    /// Fetch and decode a token account at `confirmed` commitment
+    #[instrument(skip(self))]
+    pub async fn fetch(&self, token_account: &Pubkey) -> Result<ConfidentialAccountState> {
+        let Some(rpc_url) = &self.rpc_url else {
+            return Err(BackendError::BadRequest(
+                "Balance ciphertexts are required; this server has no SOLANA_RPC_URL to read \
+                 them from the token account"
+                    .to_string(),
+            ));
+        };
+
+        let account = self
+            .get_account(rpc_url, token_account, "Token account")
+            .await?;
+        if account.owner != TOKEN_2022_PROGRAM_ID.to_string() {
+            return Err(BackendError::BadRequest(format!(
+                "{token_account} is not a Token-2022 account"
+            )));
+        }
+        debug!(len = account.data.len(), "Fetched token account");
+
+        ConfidentialAccountState::parse(&account.data)
+    }
+
+    /// Shares `withdraw(assets)` burns from the SVS-2 vault `vault` of
+    /// `program_id` at its current `confirmed` state
+    #[instrument(skip(self))]
+    pub async fn preview_withdraw(
+        &self,
+        program_id: &Pubkey,
+        vault: &Pubkey,
+        assets: u64,
+    ) -> Result<u64> {
+        let Some(rpc_url) = &self.rpc_url else {
+            return Err(BackendError::BadRequest(
+                "shares is required; this server has no SOLANA_RPC_URL to preview it from the \
+                 vault"
+                    .to_string(),
+            ));
+        };
+
+        let account = self.get_account(rpc_url, vault, "Vault").await?;
+        if account.owner != program_id.to_string() {
+            return Err(BackendError::BadRequest(format!(
+                "{vault} is not an SVS-2 vault"
+            )));
+        }
+        let state = VaultState::parse(&account.data)?;
+
+        let shares_mint = self
+            .get_account(rpc_url, &state.shares_mint, "Shares mint")
+            .await?;
+        let shares_supply = shares_mint
+            .data
+            .get(MINT_SUPPLY_OFFSET..MINT_SUPPLY_OFFSET + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| BackendError::Rpc("Shares mint is malformed".to_string()))?;
+
+        let clock = self
+            .get_account(rpc_url, &sysvar::clock::ID, "Clock sysvar")
+            .await?;
+        let clock: Clock = bincode::deserialize(&clock.data)
+            .map_err(|e| BackendError::Rpc(format!("Invalid clock sysvar: {e}")))?;
+
+        let shares = state.preview_withdraw(assets, shares_supply, clock.unix_timestamp)?;
+        debug!(shares, "Previewed withdraw");
+        Ok(shares)
+    }
+
+    /// `getAccountInfo` at `confirmed` commitment; `what` names the account
+    /// in errors
+    async fn get_account(&self, rpc_url: &str, address: &Pubkey, what: &str) -> Result<Account> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [
+                address.to_string(),
+                {"encoding": "base64", "commitment": "confirmed"},
+            ],
+        });
+        let response: RpcResponse = self
+            .client
+            .post(rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| BackendError::Rpc(format!("getAccountInfo failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| BackendError::Rpc(format!("Invalid getAccountInfo response: {e}")))?;
+
+        if let Some(error) = response.error {
+            return Err(BackendError::Rpc(format!("getAccountInfo failed: {error}")));
+        }
+        let account = response
+            .result
+            .and_then(|result| result.value)
+            .ok_or_else(|| BackendError::NotFound(format!("{what} {address}")))?;
+
+        let data = STANDARD
+            .decode(&account.data.0)
+            .map_err(|e| BackendError::Rpc(format!("Invalid account data: {e}")))?;
+
+        Ok(Account {
+            owner: account.owner,
+            data,
+        })
+    }
+}
+
+/// Parse a balance field, which is filled in by [`AccountStateFetcher::resolve`]
+/// when omitted
+pub fn parse_required<T, E: Into<BackendError>>(
+    field: &Option<String>,
+    parse: impl Fn(&str) -> std::result::Result<T, E>,
+) -> Result<T> {
+    let value = field.as_deref().ok_or_else(BackendError::missing)?;
+    parse(value).map_err(Into::into)
+}
+
+macro_rules! account_backed {
+    ($request:ty, |$state:ident| $($field:ident = $value:expr),+ $(,)?) => {
+        impl AccountBacked for $request {
+            fn wallet_pubkey(&self) -> &str {
+                &self.wallet_pubkey
+            }
+
+            fn token_account(&self) -> &str {
+                &self.token_account
+            }
+
+            fn needs_account_state(&self) -> bool {
+                false $(|| self.$field.is_none())+
+            }
+
+            fn fill_account_state(&mut self, $state: &ConfidentialAccountState) {
+                $(self.$field.get_or_insert_with(|| STANDARD.encode($value));)+
+            }
+        }
+    };
+}
+
+account_backed!(EqualityProofRequest, |state| current_ciphertext =
+    state.available_balance,);
+account_backed!(ZeroCiphertextProofRequest, |state| ciphertext =
+    state.available_balance,);
+account_backed!(
+    TransferProofRequest,
+    |state| current_ciphertext = state.available_balance,
+    current_decryptable_balance = state.decryptable_available_balance,
+);
+account_backed!(
+    WithdrawBundleRequest,
+    |state| current_ciphertext = state.available_balance,
+    current_decryptable_balance = state.decryptable_available_balance,
+);
+account_backed!(
+    WithdrawTxRequest,
+    |state| current_ciphertext = state.available_balance,
+    current_decryptable_balance = state.decryptable_available_balance,
+);
+account_backed!(
+    BalanceDecryptRequest,
+    |state| available_balance = state.available_balance,
+    pending_balance_lo = state.pending_balance_lo,
+    pending_balance_hi = state.pending_balance_hi,
+);
+account_backed!(
+    ApplyPendingRequest,
+    |state| pending_balance_lo = state.pending_balance_lo,
+    pending_balance_hi = state.pending_balance_hi,
+    decryptable_available_balance = state.decryptable_available_balance,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Token-2022 account with a memo-transfer extension before the
+    /// confidential transfer extension
+    fn account_data(owner: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; BASE_ACCOUNT_LEN];
+        data[32..64].copy_from_slice(owner.as_ref());
+        data.push(ACCOUNT_TYPE_ACCOUNT);
+
+        // MemoTransfer
+        data.extend_from_slice(&8u16.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.push(1);
+
+        let mut extension = vec![1u8];
+        extension.extend_from_slice(&[2u8; 32]);
+        extension.extend_from_slice(&[3u8; 64]);
+        extension.extend_from_slice(&[4u8; 64]);
+        extension.extend_from_slice(&[5u8; 64]);
+        extension.extend_from_slice(&[6u8; 36]);
+        extension.extend_from_slice(&[1, 1]);
+        for counter in [7u64, 65536, 7, 7] {
+            extension.extend_from_slice(&counter.to_le_bytes());
+        }
+        data.extend_from_slice(&CONFIDENTIAL_TRANSFER_ACCOUNT_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(extension.len() as u16).to_le_bytes());
+        data.extend_from_slice(&extension);
+        data
+    }
+
+    #[test]
+    fn test_parse_confidential_account() {
+        let owner = Pubkey::new_unique();
+
+        let state = ConfidentialAccountState::parse(&account_data(&owner)).unwrap();
+
+        assert_eq!(state.owner, owner);
+        assert_eq!(state.pending_balance_lo, [3u8; 64]);
+        assert_eq!(state.pending_balance_hi, [4u8; 64]);
+        assert_eq!(state.available_balance, [5u8; 64]);
+        assert_eq!(state.decryptable_available_balance, [6u8; 36]);
+        assert_eq!(state.pending_balance_credit_counter, 7);
+    }
+
+    #[test]
+    fn test_parse_rejects_plain_account() {
+        let mut data = account_data(&Pubkey::new_unique());
+
+        assert!(ConfidentialAccountState::parse(&data[..BASE_ACCOUNT_LEN]).is_err());
+
+        // Truncated extension
+        data.truncate(data.len() - 1);
+        assert!(ConfidentialAccountState::parse(&data).is_err());
+    }
+
+    /// `ConfidentialVault` with an auditor, 1000 profit locked at t=100 over 10s
+    fn vault_data(paused: bool) -> Vec<u8> {
+        let mut data = CONFIDENTIAL_VAULT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[1u8; 2 * 32]);
+        data.extend_from_slice(&[5u8; 32]);
+        data.extend_from_slice(&[1u8; 32]);
+        data.extend_from_slice(&11_000u64.to_le_bytes());
+        data.extend_from_slice(&[3, 255, paused as u8]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&[2u8; 32]);
+        data.extend_from_slice(&[4u8; 4 * 32]);
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&100i64.to_le_bytes());
+        data.extend_from_slice(&10i64.to_le_bytes());
+        data.extend_from_slice(&[0u8; 32]);
+        data
+    }
+
+    #[test]
+    fn test_parse_vault() {
+        let state = VaultState::parse(&vault_data(false)).unwrap();
+
+        assert_eq!(
+            state,
+            VaultState {
+                shares_mint: Pubkey::new_from_array([5; 32]),
+                total_assets: 11_000,
+                decimals_offset: 3,
+                paused: false,
+                locked_profit: 1_000,
+                last_harvest_ts: 100,
+                profit_unlock_period: 10,
+            }
+        );
+
+        let mut data = vault_data(false);
+        assert!(VaultState::parse(&data[..200]).is_err());
+        data[0] ^= 1;
+        assert!(VaultState::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_preview_withdraw_excludes_locked_profit() {
+        let state = VaultState::parse(&vault_data(false)).unwrap();
+
+        // All profit locked: 10_000 assets back 10_000_000 shares
+        assert_eq!(
+            state.preview_withdraw(1_000, 10_000_000, 100).unwrap(),
+            1_000_000
+        );
+        // Fully unlocked: the shares are worth more, so fewer are burned
+        assert_eq!(
+            state.preview_withdraw(1_000, 10_000_000, 110).unwrap(),
+            909_100
+        );
+
+        let paused = VaultState::parse(&vault_data(true)).unwrap();
+        assert!(matches!(
+            paused.preview_withdraw(1_000, 10_000_000, 110),
+            Err(BackendError::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_skips_complete_requests() {
+        let mut req: ZeroCiphertextProofRequest = serde_json::from_value(json!({
+            "wallet_pubkey": Pubkey::new_unique().to_string(),
+            "token_account": Pubkey::new_unique().to_string(),
+            "timestamp": 0,
+            "request_signature": "",
+            "elgamal_signature": "",
+            "ciphertext": STANDARD.encode([0u8; 64]),
+        }))
+        .unwrap();
+        let fetcher = AccountStateFetcher::new(None);
+
+        assert!(fetcher.resolve(&mut req).await.unwrap().is_none());
+
+        // Omitted balances need an RPC node
+        req.ciphertext = None;
+        assert!(matches!(
+            fetcher.resolve(&mut req).await,
+            Err(BackendError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,354 @@
+//! Managed API Keys
+//!
+//! API keys created, rotated, re-scoped and revoked through the admin API at
+//! runtime, alongside the keys from the configuration. Only SHA-256 digests
+//! of the keys are kept. With a shared Redis the keys are persisted there and
+//! every replica picks up changes; otherwise they live in memory and are lost
+//! on restart.
+
+use chrono::Utc;
+use crypto_box::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::hash;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use subtle::{Choice, ConstantTimeEq};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::{BackendError, Result},
+    services::RedisStore,
+    types::{ApiKeyConfig, ApiKeyInfo, ApiKeySettings},
+};
+
+/// Redis hash of managed keys, by id
+const KEYS_HASH: &str = "api-keys";
+
+/// Channel announcing that managed keys changed
+const KEY_EVENTS_CHANNEL: &str = "api-key-events";
+
+/// Interval of full reloads, which pick up changes announced while a
+/// replica was not subscribed
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Prefix of generated keys, so leaked keys are easy to recognize
+const KEY_PREFIX: &str = "svs_";
+
+/// A managed key as persisted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManagedKey {
+    id: String,
+    /// SHA-256 of the key (hex)
+    key_hash: String,
+    /// SHA-256 of the key replaced by the last rotation (hex)
+    #[serde(default)]
+    previous_key_hash: Option<String>,
+    /// Until when the replaced key is accepted (Unix timestamp)
+    #[serde(default)]
+    previous_key_expires_at: Option<i64>,
+    settings: ApiKeySettings,
+    created_at: i64,
+    #[serde(default)]
+    rotated_at: Option<i64>,
+}
+
+impl ManagedKey {
+    fn info(&self) -> ApiKeyInfo {
+        ApiKeyInfo {
+            id: self.id.clone(),
+            settings: self.settings.clone(),
+            created_at: self.created_at,
+            rotated_at: self.rotated_at,
+            previous_key_expires_at: self.previous_key_expires_at,
+        }
+    }
+
+    /// The key as used by authentication; usage is tracked by id, so it
+    /// carries over rotations
+    fn config(&self) -> ApiKeyConfig {
+        let name = self.settings.name.as_ref().unwrap_or(&self.id);
+        ApiKeyConfig {
+            key: format!("managed:{}", self.id),
+            name: Some(name.clone()),
+            scopes: self.settings.scopes.clone(),
+            quota: self.settings.quota.clone(),
+            priority: self.settings.priority,
+        }
+    }
+
+    /// Whether `digest` is the current key, or the replaced one within its
+    /// grace period, compared in constant time
+    fn matches(&self, digest: &str, now: i64) -> Choice {
+        let current = self.key_hash.as_bytes().ct_eq(digest.as_bytes());
+        let previous = match (&self.previous_key_hash, self.previous_key_expires_at) {
+            (Some(previous), Some(expires_at)) if expires_at > now => {
+                previous.as_bytes().ct_eq(digest.as_bytes())
+            }
+            _ => Choice::from(0),
+        };
+        current | previous
+    }
+}
+
+/// Hex SHA-256 of a key
+fn digest(key: &str) -> String {
+    hex::encode(hash(key.as_bytes()))
+}
+
+/// A new random key and its digest
+fn generate_key() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let key = format!("{KEY_PREFIX}{}", hex::encode(bytes));
+    let key_hash = digest(&key);
+    (key, key_hash)
+}
+
+/// API keys managed at runtime, by id
+#[derive(Clone, Default)]
+pub struct ApiKeyStore {
+    keys: Arc<RwLock<HashMap<String, ManagedKey>>>,
+    /// Keys shared by every replica; in-process when unset
+    shared: Option<RedisStore>,
+}
+
+impl ApiKeyStore {
+    /// Create a store, loading any keys persisted in `shared`
+    pub async fn load(shared: Option<RedisStore>) -> Result<Self> {
+        let store = Self {
+            keys: Arc::default(),
+            shared,
+        };
+        store.reload().await?;
+        Ok(store)
+    }
+
+    /// Keep the keys in sync with changes made through other replicas
+    pub fn spawn_sync(&self) {
+        let Some(shared) = &self.shared else {
+            return;
+        };
+
+        let store = self.clone();
+        shared.spawn_subscriber(KEY_EVENTS_CHANNEL, move |_| {
+            let store = store.clone();
+            tokio::spawn(async move { store.reload_or_warn().await });
+        });
+
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                store.reload_or_warn().await;
+            }
+        });
+    }
+
+    async fn reload_or_warn(&self) {
+        if let Err(e) = self.reload().await {
+            warn!(error = %e, "Failed to reload API keys");
+        }
+    }
+
+    /// Replace the in-memory keys with the persisted ones
+    async fn reload(&self) -> Result<()> {
+        let Some(shared) = &self.shared else {
+            return Ok(());
+        };
+
+        let mut keys = HashMap::new();
+        for value in shared.hash_values(KEYS_HASH).await? {
+            match serde_json::from_str::<ManagedKey>(&value) {
+                Ok(key) => {
+                    keys.insert(key.id.clone(), key);
+                }
+                Err(e) => warn!(error = %e, "Invalid persisted API key"),
+            }
+        }
+        *self.keys.write().unwrap() = keys;
+        Ok(())
+    }
+
+    /// Whether no keys have been created
+    pub fn is_empty(&self) -> bool {
+        self.keys.read().unwrap().is_empty()
+    }
+
+    /// The managed key equal to `api_key`
+    ///
+    /// Like configured keys, every key is compared as a digest in constant
+    /// time.
+    pub fn find(&self, api_key: &str) -> Option<ApiKeyConfig> {
+        let presented = digest(api_key);
+        let now = Utc::now().timestamp();
+        let keys = self.keys.read().unwrap();
+
+        keys.values()
+            .fold(None, |found, key| {
+                if bool::from(key.matches(&presented, now)) {
+                    Some(key)
+                } else {
+                    found
+                }
+            })
+            .map(ManagedKey::config)
+    }
+
+    /// Every managed key, oldest first
+    pub fn list(&self) -> Vec<ApiKeyInfo> {
+        let keys = self.keys.read().unwrap();
+        let mut infos: Vec<_> = keys.values().map(ManagedKey::info).collect();
+        infos.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        infos
+    }
+
+    /// Create a key; returns it with its value, which is not kept
+    pub async fn create(&self, settings: ApiKeySettings) -> Result<(ApiKeyInfo, String)> {
+        let (key, key_hash) = generate_key();
+        let managed = ManagedKey {
+            id: Uuid::new_v4().to_string(),
+            key_hash,
+            previous_key_hash: None,
+            previous_key_expires_at: None,
+            settings,
+            created_at: Utc::now().timestamp(),
+            rotated_at: None,
+        };
+
+        self.save(&managed).await?;
+        info!(key_id = %managed.id, "Created API key");
+        Ok((managed.info(), key))
+    }
+
+    /// Replace a key's name, scopes, quota and priority
+    pub async fn update(&self, id: &str, settings: ApiKeySettings) -> Result<ApiKeyInfo> {
+        let mut managed = self.get(id)?;
+        managed.settings = settings;
+
+        self.save(&managed).await?;
+        info!(key_id = %id, "Updated API key");
+        Ok(managed.info())
+    }
+
+    /// Issue a new value for a key; the old value stays valid for `grace`
+    pub async fn rotate(&self, id: &str, grace: Duration) -> Result<(ApiKeyInfo, String)> {
+        let mut managed = self.get(id)?;
+        let (key, key_hash) = generate_key();
+        let now = Utc::now().timestamp();
+
+        let previous_key_hash = std::mem::replace(&mut managed.key_hash, key_hash);
+        (managed.previous_key_hash, managed.previous_key_expires_at) = match grace.as_secs() {
+            0 => (None, None),
+            secs => (
+                Some(previous_key_hash),
+                Some(now.saturating_add_unsigned(secs)),
+            ),
+        };
+        managed.rotated_at = Some(now);
+
+        self.save(&managed).await?;
+        info!(key_id = %id, grace_secs = grace.as_secs(), "Rotated API key");
+        Ok((managed.info(), key))
+    }
+
+    /// Delete a key; it is rejected from then on
+    pub async fn revoke(&self, id: &str) -> Result<()> {
+        self.get(id)?;
+
+        if let Some(shared) = &self.shared {
+            shared.hash_delete(KEYS_HASH, id).await?;
+            self.announce(shared).await;
+        }
+        self.keys.write().unwrap().remove(id);
+
+        info!(key_id = %id, "Revoked API key");
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Result<ManagedKey> {
+        self.keys
+            .read()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| BackendError::NotFound(format!("API key {id}")))
+    }
+
+    /// Persist a key, then apply it locally
+    async fn save(&self, managed: &ManagedKey) -> Result<()> {
+        if let Some(shared) = &self.shared {
+            let value = serde_json::to_string(managed)
+                .map_err(|e| BackendError::Internal(format!("Failed to encode API key: {e}")))?;
+            shared.hash_set(KEYS_HASH, &managed.id, &value).await?;
+            self.announce(shared).await;
+        }
+        self.keys
+            .write()
+            .unwrap()
+            .insert(managed.id.clone(), managed.clone());
+        Ok(())
+    }
+
+    /// Tell other replicas to reload; they also reload periodically, so a
+    /// lost announcement only delays the change
+    async fn announce(&self, shared: &RedisStore) {
+        if let Err(e) = shared.publish(KEY_EVENTS_CHANNEL, "changed").await {
+            warn!(error = %e, "Failed to announce API key change");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ApiScope;
+
+    #[tokio::test]
+    async fn test_key_lifecycle() {
+        let store = ApiKeyStore::default();
+        let settings = ApiKeySettings {
+            name: Some("custodian".to_string()),
+            scopes: Some(vec![ApiScope::PubkeyValidity]),
+            ..Default::default()
+        };
+
+        let (info, key) = store.create(settings).await.unwrap();
+        assert!(key.starts_with(KEY_PREFIX));
+        let found = store.find(&key).unwrap();
+        assert_eq!(found.key, format!("managed:{}", info.id));
+        assert_eq!(found.label(), "custodian");
+        assert!(store.find("svs_unknown").is_none());
+
+        // Scopes apply from the next request
+        let info = store
+            .update(&info.id, ApiKeySettings::default())
+            .await
+            .unwrap();
+        assert!(store.find(&key).unwrap().allows(ApiScope::Tx));
+
+        // The replaced key is accepted during the grace period only
+        let (_, rotated) = store
+            .rotate(&info.id, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(store.find(&rotated).is_some());
+        assert!(store.find(&key).is_some());
+        let (_, rotated_again) = store.rotate(&info.id, Duration::ZERO).await.unwrap();
+        assert!(store.find(&rotated).is_none());
+        assert!(store.find(&rotated_again).is_some());
+
+        store.revoke(&info.id).await.unwrap();
+        assert!(store.find(&rotated_again).is_none());
+        assert!(store.is_empty());
+        assert!(matches!(
+            store.revoke(&info.id).await,
+            Err(BackendError::NotFound(_))
+        ));
+    }
+}
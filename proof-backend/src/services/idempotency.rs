@@ -0,0 +1,87 @@
+//! Idempotent Job Submission
+//!
+//! A client retrying `POST /v1/jobs` with the same `Idempotency-Key` gets the
+//! job its first attempt created instead of a replay error. Keys are scoped
+//! to the API key and remembered as long as job results, in memory or in the
+//! shared Redis.
+
+use moka::sync::Cache;
+use std::time::Duration;
+
+use crate::{error::Result, services::RedisStore};
+
+/// Most idempotency keys remembered in memory
+const MAX_KEYS: u64 = 100_000;
+
+/// Job ids by API key and idempotency key
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    jobs: Cache<String, String>,
+    ttl: Duration,
+    /// Keys shared by every replica; in-process when unset
+    shared: Option<RedisStore>,
+}
+
+impl IdempotencyStore {
+    /// Create a store remembering each key for `ttl`
+    pub fn new(ttl: Duration, shared: Option<RedisStore>) -> Self {
+        Self {
+            jobs: Cache::builder()
+                .max_capacity(MAX_KEYS)
+                .time_to_live(ttl)
+                .build(),
+            ttl,
+            shared,
+        }
+    }
+
+    /// Job created for `key` by `client`, if any
+    pub async fn get(&self, client: &str, key: &str) -> Result<Option<String>> {
+        let name = entry_name(client, key);
+        match &self.shared {
+            Some(shared) => shared.get(&name).await,
+            None => Ok(self.jobs.get(&name)),
+        }
+    }
+
+    /// Remember `job_id` as the job created for `key` by `client`; a job
+    /// already recorded for the key is kept
+    pub async fn insert(&self, client: &str, key: &str, job_id: &str) -> Result<()> {
+        let name = entry_name(client, key);
+        match &self.shared {
+            Some(shared) => {
+                shared.set_if_absent(&name, job_id, self.ttl).await?;
+            }
+            None => {
+                self.jobs.entry(name).or_insert(job_id.to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entry name; the API key is hashed so it never leaves the process
+fn entry_name(client: &str, key: &str) -> String {
+    let client_id = solana_sdk::hash::hash(client.as_bytes());
+    format!("idempotency:{client_id}:{key}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_first_job_wins_per_client() {
+        let store = IdempotencyStore::new(Duration::from_secs(60), None);
+
+        store.insert("a", "retry-1", "job-1").await.unwrap();
+        store.insert("a", "retry-1", "job-2").await.unwrap();
+
+        assert_eq!(
+            store.get("a", "retry-1").await.unwrap().as_deref(),
+            Some("job-1")
+        );
+        assert_eq!(store.get("b", "retry-1").await.unwrap(), None);
+        assert_eq!(store.get("a", "retry-2").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,738 @@
+//! Async Proof Job Queue
+//!
+//! Runs proof generation on the blocking thread pool with bounded concurrency
+//! and keeps results until they expire. Every state change is broadcast so
+//! clients can follow jobs without polling.
+//!
+//! When every worker is busy, queued jobs start in priority tier order, oldest
+//! first within a tier. A job that has waited longer than the maximum wait
+//! starts ahead of every tier, so batch traffic is delayed but never starved.
+//!
+//! With a shared Redis, job records and their updates go through Redis so
+//! any replica can report on any job. A job still runs on the replica that
+//! accepted it, and each replica schedules its own jobs. With a durable store
+//! the job requests are persisted too: jobs left unfinished by a stopped
+//! replica are claimed by a running one, or by the replica once it restarts,
+//! and run again from the start.
+
+use crate::error::{BackendError, ErrorResponse, Result};
+use crate::services::{job_store::DurableJobStore, usage::proof_ms, RedisStore};
+use crate::types::{JobQueueDepth, PriorityTier, UsageCounts};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tracing::{info, info_span, warn, Instrument, Span};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Lifecycle of a queued job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    /// Whether the job will not change again
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed)
+    }
+}
+
+/// Progress of a running job, in proofs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobProgress {
+    /// Proof currently being generated, or the one just finished
+    pub stage: String,
+    pub completed: u32,
+    pub total: u32,
+}
+
+/// Stored state of a job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobRecord {
+    pub id: String,
+    pub kind: String,
+    pub priority: PriorityTier,
+    pub status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<JobProgress>,
+    /// Response body of the matching synchronous endpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+    /// Proof generation time charged to the submitting key, once finished;
+    /// the submission itself was charged as one request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageCounts>,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+}
+
+/// Capacity of the job update channel; slow subscribers skip to the latest state
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
+
+/// How often [`JobQueue::wait_idle`] re-checks for unfinished jobs
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Redis channel carrying job updates between replicas
+const JOB_EVENTS_CHANNEL: &str = "job-events";
+
+/// How often leases of held jobs are renewed and unleased jobs claimed;
+/// well within the lease TTL
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Work of a job, run on the blocking thread pool
+pub type JobFn = Box<dyn FnOnce(ProgressReporter) -> Result<serde_json::Value> + Send>;
+
+/// A job waiting for a worker
+struct PendingJob {
+    record: JobRecord,
+    queued_at: Instant,
+    job: JobFn,
+    span: Span,
+}
+
+/// Jobs waiting for a worker, and the number of busy workers
+#[derive(Default)]
+struct Scheduler {
+    pending: Vec<PendingJob>,
+    running: usize,
+}
+
+impl Scheduler {
+    /// Index of the job to start next: the oldest overdue job, else the
+    /// oldest job of the highest tier
+    fn next(&self, max_wait: Duration) -> Option<usize> {
+        let now = Instant::now();
+        let overdue = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| now.duration_since(job.queued_at) >= max_wait)
+            .min_by_key(|(_, job)| job.queued_at);
+        overdue
+            .or_else(|| {
+                self.pending
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, job)| (job.record.priority, job.queued_at))
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+/// Job queue with a fixed number of concurrent workers
+#[derive(Clone)]
+pub struct JobQueue {
+    jobs: Arc<RwLock<HashMap<String, JobRecord>>>,
+    /// Job records and updates shared by every replica; in-process when unset
+    shared: Option<RedisStore>,
+    scheduler: Arc<Mutex<Scheduler>>,
+    workers: usize,
+    max_wait: Duration,
+    updates: broadcast::Sender<JobRecord>,
+    ttl: Duration,
+    /// Requests of unfinished jobs, persisted so other replicas can take
+    /// them over; jobs are lost with the process when unset
+    durable: Option<DurableJobStore>,
+    /// Persisted jobs leased to this replica
+    held: Arc<Mutex<HashSet<String>>>,
+}
+
+/// Handle given to a running job to publish its progress
+pub struct ProgressReporter {
+    progress: mpsc::UnboundedSender<JobProgress>,
+}
+
+impl ProgressReporter {
+    /// Record progress; called from the blocking thread running the job
+    pub fn report(&self, stage: &str, completed: u32, total: u32) {
+        // The job's task stores and broadcasts it
+        let _ = self.progress.send(JobProgress {
+            stage: stage.to_string(),
+            completed,
+            total,
+        });
+    }
+}
+
+impl JobQueue {
+    /// Create a queue running at most `workers` jobs at once; jobs queued
+    /// for `max_wait` start ahead of higher tiers, and finished jobs are
+    /// dropped `ttl` after completion (shared records `ttl` after their last
+    /// update)
+    pub fn new(
+        workers: usize,
+        max_wait: Duration,
+        ttl: Duration,
+        shared: Option<RedisStore>,
+    ) -> Self {
+        let updates = broadcast::channel(UPDATE_CHANNEL_CAPACITY).0;
+
+        // Updates from every replica, this one included, arrive through Redis
+        if let Some(store) = &shared {
+            let updates = updates.clone();
+            store.spawn_subscriber(
+                JOB_EVENTS_CHANNEL,
+                move |message| match serde_json::from_str(message) {
+                    Ok(record) => {
+                        let _ = updates.send(record);
+                    }
+                    Err(e) => warn!(error = %e, "Invalid job update"),
+                },
+            );
+        }
+
+        Self {
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            shared,
+            scheduler: Arc::default(),
+            workers: workers.max(1),
+            max_wait,
+            updates,
+            ttl,
+            durable: None,
+            held: Arc::default(),
+        }
+    }
+
+    /// Persist job requests in `store` (see [`JobQueue::submit_durable`])
+    pub fn with_durable_store(mut self, store: DurableJobStore) -> Self {
+        self.durable = Some(store);
+        self
+    }
+
+    /// Whether job requests are persisted
+    pub fn is_durable(&self) -> bool {
+        self.durable.is_some()
+    }
+
+    /// Queue `job` at `priority` and return its id immediately
+    pub async fn submit<F>(&self, kind: &str, priority: PriorityTier, job: F) -> Result<String>
+    where
+        F: FnOnce(ProgressReporter) -> Result<serde_json::Value> + Send + 'static,
+    {
+        self.enqueue(kind, priority, None, Box::new(job)).await
+    }
+
+    /// Queue `job` like [`JobQueue::submit`], persisting `payload` first so
+    /// the job can be rebuilt from it by the loader given to
+    /// [`JobQueue::spawn_recovery`]
+    pub async fn submit_durable<F>(
+        &self,
+        kind: &str,
+        priority: PriorityTier,
+        payload: &str,
+        job: F,
+    ) -> Result<String>
+    where
+        F: FnOnce(ProgressReporter) -> Result<serde_json::Value> + Send + 'static,
+    {
+        self.enqueue(kind, priority, Some(payload), Box::new(job))
+            .await
+    }
+
+    async fn enqueue(
+        &self,
+        kind: &str,
+        priority: PriorityTier,
+        payload: Option<&str>,
+        job: JobFn,
+    ) -> Result<String> {
+        self.purge_expired().await;
+
+        let id = Uuid::new_v4().to_string();
+        let record = JobRecord {
+            id: id.clone(),
+            kind: kind.to_string(),
+            priority,
+            status: JobStatus::Queued,
+            progress: None,
+            result: None,
+            error: None,
+            usage: None,
+            created_at: Utc::now().timestamp(),
+            finished_at: None,
+        };
+        self.store(&record).await?;
+        if let (Some(durable), Some(payload)) = (&self.durable, payload) {
+            durable.persist(&id, payload).await?;
+            self.lock_held().insert(id.clone());
+        }
+
+        // Jobs run under the submitting request's span so traces cover the proof work
+        self.lock_scheduler().pending.push(PendingJob {
+            record,
+            queued_at: Instant::now(),
+            job,
+            span: Span::current(),
+        });
+        info!(job_id = %id, kind, ?priority, "Queued proof job");
+        self.dispatch();
+
+        Ok(id)
+    }
+
+    /// Start queued jobs while workers are free
+    fn dispatch(&self) {
+        loop {
+            let pending = {
+                let mut scheduler = self.lock_scheduler();
+                if scheduler.running >= self.workers {
+                    return;
+                }
+                let Some(index) = scheduler.next(self.max_wait) else {
+                    return;
+                };
+                scheduler.running += 1;
+                scheduler.pending.remove(index)
+            };
+            self.start(pending);
+        }
+    }
+
+    fn start(&self, pending: PendingJob) {
+        let PendingJob {
+            mut record,
+            job,
+            span,
+            ..
+        } = pending;
+        let queue = self.clone();
+        tokio::spawn(
+            async move {
+                record.status = JobStatus::Running;
+                queue.update(&record).await;
+
+                let (progress, mut reports) = mpsc::unbounded_channel();
+                let reporter = ProgressReporter { progress };
+                let span = Span::current();
+                let started = Instant::now();
+                let mut handle =
+                    tokio::task::spawn_blocking(move || span.in_scope(|| job(reporter)));
+
+                // Progress sent before the job returned is published first
+                let outcome = loop {
+                    tokio::select! {
+                        biased;
+                        Some(progress) = reports.recv() => {
+                            record.progress = Some(progress);
+                            queue.update(&record).await;
+                        }
+                        outcome = &mut handle => break outcome.unwrap_or_else(|e| {
+                            Err(BackendError::Internal(format!("Job panicked: {e}")))
+                        }),
+                    }
+                };
+                record.usage = Some(UsageCounts {
+                    requests: 0,
+                    proof_ms: proof_ms(started.elapsed()),
+                });
+                queue.finish(&mut record, outcome).await;
+                queue.release(&record.id).await;
+
+                queue.lock_scheduler().running -= 1;
+                queue.dispatch();
+            }
+            .instrument(span),
+        );
+    }
+
+    fn lock_scheduler(&self) -> std::sync::MutexGuard<'_, Scheduler> {
+        self.scheduler.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_held(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.held.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drop the persisted request of a finished job
+    async fn release(&self, id: &str) {
+        let Some(durable) = &self.durable else {
+            return;
+        };
+        if self.lock_held().remove(id) {
+            durable.release(id).await;
+        }
+    }
+
+    /// Keep the leases of this replica's persisted jobs, and take over jobs
+    /// whose replica stopped; `load` rebuilds a job from its payload
+    pub fn spawn_recovery<L>(&self, load: L)
+    where
+        L: Fn(&str) -> Result<JobFn> + Send + Sync + 'static,
+    {
+        let Some(durable) = self.durable.clone() else {
+            return;
+        };
+
+        let queue = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RECOVERY_INTERVAL);
+            loop {
+                interval.tick().await;
+                queue.renew_leases(&durable).await;
+                if let Err(e) = queue.recover(&durable, &load).await {
+                    warn!(error = %e, "Failed to recover proof jobs");
+                }
+            }
+        });
+    }
+
+    async fn renew_leases(&self, durable: &DurableJobStore) {
+        let held: Vec<String> = self.lock_held().iter().cloned().collect();
+        for id in held {
+            match durable.renew(&id).await {
+                Ok(true) => {}
+                // Claimed elsewhere while this replica could not renew; the
+                // job may run twice, but both runs produce a valid result
+                Ok(false) => warn!(job_id = %id, "Lost lease of proof job"),
+                Err(e) => warn!(job_id = %id, error = %e, "Failed to renew job lease"),
+            }
+        }
+    }
+
+    /// Queue every unleased persisted job again
+    async fn recover<L>(&self, durable: &DurableJobStore, load: &L) -> Result<()>
+    where
+        L: Fn(&str) -> Result<JobFn>,
+    {
+        for (id, payload) in durable.claim_orphaned().await? {
+            // A lease that expired under this replica is simply held again
+            if self.lock_held().contains(&id) {
+                continue;
+            }
+
+            let mut record = match self.get(&id).await? {
+                Some(record) if !record.status.is_finished() => record,
+                // Finished, or its record expired
+                _ => {
+                    durable.release(&id).await;
+                    continue;
+                }
+            };
+
+            let job = durable.decrypt(&payload).and_then(|payload| load(&payload));
+            let job = match job {
+                Ok(job) => job,
+                Err(e) => {
+                    self.finish(&mut record, Err(e)).await;
+                    durable.release(&id).await;
+                    continue;
+                }
+            };
+
+            self.lock_held().insert(id.clone());
+            record.status = JobStatus::Queued;
+            record.progress = None;
+            self.update(&record).await;
+
+            info!(job_id = %id, kind = %record.kind, "Recovered proof job");
+            let span = info_span!("recovered_job", job_id = %id);
+            self.lock_scheduler().pending.push(PendingJob {
+                record,
+                queued_at: Instant::now(),
+                job,
+                span,
+            });
+            self.dispatch();
+        }
+        Ok(())
+    }
+
+    /// Look up a job by id
+    pub async fn get(&self, id: &str) -> Result<Option<JobRecord>> {
+        let stored = match (&self.shared, &self.durable) {
+            (Some(shared), _) => shared.get(&job_key(id)).await?,
+            (None, durable) => {
+                if let Some(record) = self.jobs.read().await.get(id) {
+                    return Ok(Some(record.clone()));
+                }
+                // Jobs of other replicas, or from before a restart
+                match durable {
+                    Some(durable) => durable.record(id).await?,
+                    None => None,
+                }
+            }
+        };
+
+        stored
+            .map(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| BackendError::Internal(format!("Invalid stored job: {e}")))
+            })
+            .transpose()
+    }
+
+    /// Count jobs waiting for a worker and jobs running on this replica
+    pub fn depth(&self) -> JobQueueDepth {
+        let scheduler = self.lock_scheduler();
+        JobQueueDepth {
+            queued: scheduler.pending.len(),
+            running: scheduler.running,
+        }
+    }
+
+    /// Wait until no job is queued or running on this replica
+    pub async fn wait_idle(&self) {
+        loop {
+            let depth = self.depth();
+            if depth.queued == 0 && depth.running == 0 {
+                return;
+            }
+            tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Subscribe to updates of all jobs
+    ///
+    /// Subscribe before reading the current state with [`JobQueue::get`] so no
+    /// update can be missed in between.
+    pub fn subscribe(&self) -> broadcast::Receiver<JobRecord> {
+        self.updates.subscribe()
+    }
+
+    /// Store `record` without broadcasting it
+    async fn store(&self, record: &JobRecord) -> Result<()> {
+        let Some(shared) = &self.shared else {
+            self.jobs
+                .write()
+                .await
+                .insert(record.id.clone(), record.clone());
+            return match &self.durable {
+                Some(durable) => {
+                    durable
+                        .store_record(&record.id, &encode(record)?, self.ttl)
+                        .await
+                }
+                None => Ok(()),
+            };
+        };
+
+        shared
+            .set(&job_key(&record.id), &encode(record)?, self.ttl)
+            .await
+    }
+
+    /// Store `record` and broadcast it to subscribers on every replica
+    async fn update(&self, record: &JobRecord) {
+        let result = async {
+            self.store(record).await?;
+            match &self.shared {
+                Some(shared) => shared.publish(JOB_EVENTS_CHANNEL, &encode(record)?).await,
+                None => {
+                    let _ = self.updates.send(record.clone());
+                    Ok(())
+                }
+            }
+        };
+
+        if let Err(e) = result.await {
+            warn!(job_id = %record.id, error = %e, "Failed to store job update");
+        }
+    }
+
+    async fn finish(&self, record: &mut JobRecord, outcome: Result<serde_json::Value>) {
+        match outcome {
+            Ok(result) => {
+                record.status = JobStatus::Completed;
+                record.result = Some(result);
+            }
+            Err(err) => {
+                warn!(job_id = %record.id, error = %err, "Proof job failed");
+                record.status = JobStatus::Failed;
+                record.error = Some(err.to_error_response());
+            }
+        }
+        record.finished_at = Some(Utc::now().timestamp());
+        self.update(record).await;
+    }
+
+    /// Drop finished jobs older than the TTL; shared records expire in Redis
+    async fn purge_expired(&self) {
+        let cutoff = Utc::now().timestamp() - self.ttl.as_secs() as i64;
+        self.jobs
+            .write()
+            .await
+            .retain(|_, record| record.finished_at.is_none_or(|finished| finished > cutoff));
+    }
+}
+
+/// Name of a shared job record
+fn job_key(id: &str) -> String {
+    format!("job:{id}")
+}
+
+fn encode(record: &JobRecord) -> Result<String> {
+    serde_json::to_string(record)
+        .map_err(|e| BackendError::Internal(format!("Failed to serialize job: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_for(queue: &JobQueue, id: &str) -> JobRecord {
+        for _ in 0..100 {
+            let record = queue.get(id).await.unwrap().unwrap();
+            if record.status.is_finished() {
+                return record;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {id} did not finish");
+    }
+
+    #[tokio::test]
+    async fn test_job_completes() {
+        let queue = JobQueue::new(2, Duration::from_secs(30), Duration::from_secs(60), None);
+
+        let id = queue
+            .submit("test", PriorityTier::Standard, |_| {
+                Ok(serde_json::json!({ "proof_data": "abc" }))
+            })
+            .await
+            .unwrap();
+        let record = wait_for(&queue, &id).await;
+
+        assert_eq!(record.status, JobStatus::Completed);
+        assert_eq!(record.result.unwrap()["proof_data"], "abc");
+        assert_eq!(record.usage.unwrap().requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_job_failure_is_recorded() {
+        let queue = JobQueue::new(1, Duration::from_secs(30), Duration::from_secs(60), None);
+
+        let id = queue
+            .submit("test", PriorityTier::Standard, |_| {
+                Err(BackendError::BadRequest("nope".to_string()))
+            })
+            .await
+            .unwrap();
+        let record = wait_for(&queue, &id).await;
+
+        assert_eq!(record.status, JobStatus::Failed);
+        assert_eq!(record.error.unwrap().code, "BAD_REQUEST");
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle() {
+        let queue = JobQueue::new(1, Duration::from_secs(30), Duration::from_secs(60), None);
+
+        let id = queue
+            .submit("test", PriorityTier::Standard, |_| {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(serde_json::json!({}))
+            })
+            .await
+            .unwrap();
+        queue.wait_idle().await;
+
+        assert!(queue.get(&id).await.unwrap().unwrap().status.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_broadcast() {
+        let queue = JobQueue::new(1, Duration::from_secs(30), Duration::from_secs(60), None);
+        let mut updates = queue.subscribe();
+
+        let id = queue
+            .submit("test", PriorityTier::Standard, |progress| {
+                progress.report("equality", 1, 2);
+                Ok(serde_json::json!({}))
+            })
+            .await
+            .unwrap();
+
+        let mut seen = Vec::new();
+        while let Ok(record) = updates.recv().await {
+            assert_eq!(record.id, id);
+            seen.push((record.status, record.progress.map(|p| p.completed)));
+            if record.status.is_finished() {
+                break;
+            }
+        }
+
+        assert_eq!(
+            seen,
+            vec![
+                (JobStatus::Running, None),
+                (JobStatus::Running, Some(1)),
+                (JobStatus::Completed, Some(1)),
+            ]
+        );
+    }
+
+    /// Submit jobs behind a blocker on a single worker and return the order
+    /// they ran in
+    async fn run_order(max_wait: Duration, tiers: &[PriorityTier]) -> Vec<usize> {
+        let queue = JobQueue::new(1, max_wait, Duration::from_secs(60), None);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+        let blocker = queue
+            .submit("test", PriorityTier::Interactive, move |_| {
+                release_rx.recv().unwrap();
+                Ok(serde_json::json!({}))
+            })
+            .await
+            .unwrap();
+        for (index, tier) in tiers.iter().enumerate() {
+            let order = order.clone();
+            queue
+                .submit("test", *tier, move |_| {
+                    order.lock().unwrap().push(index);
+                    Ok(serde_json::json!({}))
+                })
+                .await
+                .unwrap();
+        }
+
+        release_tx.send(()).unwrap();
+        wait_for(&queue, &blocker).await;
+        queue.wait_idle().await;
+
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[tokio::test]
+    async fn test_higher_tiers_run_first() {
+        let order = run_order(
+            Duration::from_secs(30),
+            &[
+                PriorityTier::Batch,
+                PriorityTier::Standard,
+                PriorityTier::Interactive,
+                PriorityTier::Batch,
+            ],
+        )
+        .await;
+
+        assert_eq!(order, vec![2, 1, 0, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_overdue_jobs_are_not_starved() {
+        // Every job is overdue at once, so they run in submission order
+        let order = run_order(
+            Duration::ZERO,
+            &[PriorityTier::Batch, PriorityTier::Interactive],
+        )
+        .await;
+
+        assert_eq!(order, vec![0, 1]);
+    }
+}
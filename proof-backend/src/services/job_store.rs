@@ -0,0 +1,254 @@
+//! Durable Job Store
+//!
+//! Persists the requests of async proof jobs in Postgres or the shared Redis,
+//! so queued and running jobs outlive the replica that accepted them. Each
+//! job is leased to one replica, which keeps renewing the lease while it
+//! holds the job. When a replica stops, its leases expire and any replica,
+//! the restarted one included, claims the jobs and runs them again.
+//!
+//! Postgres also keeps the job records, results included, so they survive
+//! restarts without Redis. With Redis the records already live in the shared
+//! store (see `JobQueue`).
+//!
+//! Job requests carry key-derivation signatures, so they are encrypted with
+//! `JOB_STORE_KEY` before they leave the process.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crypto_box::{
+    aead::{Aead, AeadCore, OsRng},
+    Nonce, SalsaBox, SecretKey,
+};
+use std::{sync::Arc, time::Duration};
+use tracing::warn;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::{
+    error::{BackendError, Result},
+    services::{PostgresStore, RedisStore},
+};
+
+/// Redis hash of encrypted job requests, by job id
+const PAYLOADS_HASH: &str = "job-payloads";
+
+/// How long a lease lasts without renewal
+pub const LEASE_TTL: Duration = Duration::from_secs(30);
+
+/// Size of the XSalsa20 nonce prefixed to each payload
+const NONCE_LEN: usize = 24;
+
+/// Name of a job's lease, holding the id of the replica running it
+fn lease_key(id: &str) -> String {
+    format!("job-lease:{id}")
+}
+
+/// Authenticated encryption of job payloads under `JOB_STORE_KEY`
+#[derive(Clone)]
+pub struct PayloadCipher {
+    cipher: Arc<SalsaBox>,
+}
+
+impl PayloadCipher {
+    /// Create a cipher from a 32-byte hex key; every replica needs the same
+    pub fn new(key_hex: &str) -> anyhow::Result<Self> {
+        let key = Zeroizing::new(
+            hex::decode(key_hex.trim())
+                .map_err(|e| anyhow::anyhow!("JOB_STORE_KEY is not valid hex: {e}"))?,
+        );
+        let key: [u8; 32] = key
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("JOB_STORE_KEY must be 32 bytes"))?;
+        let secret = SecretKey::from(key);
+
+        Ok(Self {
+            cipher: Arc::new(SalsaBox::new(&secret.public_key(), &secret)),
+        })
+    }
+
+    /// `nonce || ciphertext`, base64 encoded
+    pub fn encrypt(&self, payload: &str) -> Result<String> {
+        let nonce = SalsaBox::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, payload.as_bytes())
+            .map_err(|e| BackendError::Internal(format!("Failed to encrypt job: {e}")))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    pub fn decrypt(&self, sealed: &str) -> Result<Zeroizing<String>> {
+        let invalid = || BackendError::Internal("Stored job cannot be decrypted".to_string());
+
+        let sealed = STANDARD.decode(sealed).map_err(|_| invalid())?;
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| invalid())?;
+
+        String::from_utf8(plaintext)
+            .map(Zeroizing::new)
+            .map_err(|_| invalid())
+    }
+}
+
+/// Where persisted jobs are kept
+#[derive(Clone)]
+enum Backend {
+    Redis(RedisStore),
+    Postgres(PostgresStore),
+}
+
+/// Job requests persisted in Postgres or Redis and leased to replicas
+#[derive(Clone)]
+pub struct DurableJobStore {
+    backend: Backend,
+    cipher: PayloadCipher,
+    /// Lease holder id of this process
+    replica: String,
+}
+
+impl DurableJobStore {
+    /// Persist jobs in the shared Redis
+    pub fn new(shared: RedisStore, cipher: PayloadCipher) -> Self {
+        Self::with_backend(Backend::Redis(shared), cipher)
+    }
+
+    /// Persist jobs and their records in Postgres
+    pub fn postgres(store: PostgresStore, cipher: PayloadCipher) -> Self {
+        Self::with_backend(Backend::Postgres(store), cipher)
+    }
+
+    fn with_backend(backend: Backend, cipher: PayloadCipher) -> Self {
+        Self {
+            backend,
+            cipher,
+            replica: Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Persist a job's request, leased to this replica
+    pub async fn persist(&self, id: &str, payload: &str) -> Result<()> {
+        let payload = self.cipher.encrypt(payload)?;
+        match &self.backend {
+            Backend::Redis(shared) => {
+                shared.set(&lease_key(id), &self.replica, LEASE_TTL).await?;
+                shared.hash_set(PAYLOADS_HASH, id, &payload).await
+            }
+            Backend::Postgres(store) => store.persist(id, &payload, &self.replica, LEASE_TTL).await,
+        }
+    }
+
+    /// Extend this replica's lease on a job; `false` when it has lost it
+    pub async fn renew(&self, id: &str) -> Result<bool> {
+        match &self.backend {
+            // Compare and extend in one step, so a lease taken over by
+            // another replica in between is never extended
+            Backend::Redis(shared) => {
+                shared
+                    .expire_if_equal(&lease_key(id), &self.replica, LEASE_TTL)
+                    .await
+            }
+            Backend::Postgres(store) => store.renew(id, &self.replica, LEASE_TTL).await,
+        }
+    }
+
+    /// Lease every persisted job that no replica holds; returns their ids
+    /// and encrypted requests
+    pub async fn claim_orphaned(&self) -> Result<Vec<(String, String)>> {
+        let shared = match &self.backend {
+            Backend::Redis(shared) => shared,
+            Backend::Postgres(store) => {
+                if let Err(e) = store.purge_expired().await {
+                    warn!(error = %e, "Failed to purge expired jobs");
+                }
+                return store.claim_orphaned(&self.replica, LEASE_TTL).await;
+            }
+        };
+
+        let mut claimed = Vec::new();
+        for (id, payload) in shared.hash_entries(PAYLOADS_HASH).await? {
+            if shared
+                .set_if_absent(&lease_key(&id), &self.replica, LEASE_TTL)
+                .await?
+            {
+                claimed.push((id, payload));
+            }
+        }
+        Ok(claimed)
+    }
+
+    pub fn decrypt(&self, payload: &str) -> Result<Zeroizing<String>> {
+        self.cipher.decrypt(payload)
+    }
+
+    /// Forget a finished job's request; a job record kept by Postgres stays
+    /// until it expires
+    pub async fn release(&self, id: &str) {
+        let result = async {
+            match &self.backend {
+                Backend::Redis(shared) => {
+                    shared.hash_delete(PAYLOADS_HASH, id).await?;
+                    shared.delete(&lease_key(id)).await
+                }
+                Backend::Postgres(store) => store.release(id).await,
+            }
+        };
+
+        if let Err(e) = result.await {
+            warn!(job_id = %id, error = %e, "Failed to release stored job");
+        }
+    }
+
+    /// Keep a job record for `ttl` after this update, when this store holds
+    /// records (Postgres); Redis-backed queues share them through Redis
+    pub async fn store_record(&self, id: &str, record: &str, ttl: Duration) -> Result<()> {
+        match &self.backend {
+            Backend::Redis(_) => Ok(()),
+            Backend::Postgres(store) => store.store_record(id, record, ttl).await,
+        }
+    }
+
+    /// A job record kept by [`DurableJobStore::store_record`]
+    pub async fn record(&self, id: &str) -> Result<Option<String>> {
+        match &self.backend {
+            Backend::Redis(_) => Ok(None),
+            Backend::Postgres(store) => store.record(id).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn test_payload_round_trip() {
+        let cipher = PayloadCipher::new(KEY).unwrap();
+
+        let sealed = cipher.encrypt(r#"{"kind":"range"}"#).unwrap();
+
+        assert!(!sealed.contains("range"));
+        assert_eq!(*cipher.decrypt(&sealed).unwrap(), r#"{"kind":"range"}"#);
+        // Fresh nonce per payload
+        assert_ne!(cipher.encrypt(r#"{"kind":"range"}"#).unwrap(), sealed);
+    }
+
+    #[test]
+    fn test_payload_needs_the_same_key() {
+        let sealed = PayloadCipher::new(KEY).unwrap().encrypt("{}").unwrap();
+        let other = PayloadCipher::new(&"ff".repeat(32)).unwrap();
+
+        assert!(other.decrypt(&sealed).is_err());
+        assert!(PayloadCipher::new("abcd").is_err());
+        assert!(PayloadCipher::new("not hex").is_err());
+    }
+}
@@ -1,5 +1,30 @@
 //! Backend services
 
-pub mod proof_generator;
+pub mod account_state;
+pub mod api_keys;
+pub mod idempotency;
+pub mod job_queue;
+pub mod job_store;
+pub mod postgres_store;
+pub mod proof_cache;
+pub mod redis_store;
+pub mod relayer;
+pub mod replay_cache;
+pub mod self_test;
+pub mod tx_builder;
+pub mod usage;
 
-pub use proof_generator::ProofGenerator;
+pub use account_state::AccountStateFetcher;
+pub use api_keys::ApiKeyStore;
+pub use idempotency::IdempotencyStore;
+pub use job_queue::JobQueue;
+pub use job_store::DurableJobStore;
+pub use postgres_store::PostgresStore;
+pub use proof_cache::PubkeyValidityCache;
+pub use redis_store::RedisStore;
+pub use relayer::Relayer;
+pub use replay_cache::ReplayCache;
+pub use self_test::SelfTest;
+pub use svs_proof_core::ProofGenerator;
+pub use tx_builder::TxBuilder;
+pub use usage::UsageTracker;
@@ -0,0 +1,236 @@
+//! Durable Jobs in Postgres
+//!
+//! One row per job holds its encrypted request while it is unfinished, the
+//! lease of the replica running it, and its latest record, result included,
+//! until the record expires. Replicas race for orphaned jobs with single
+//! `UPDATE` statements, so a lease is only ever taken or extended by one.
+
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+use tokio_postgres::{Client, NoTls};
+use tracing::warn;
+
+use crate::error::{BackendError, Result};
+
+/// Created on connect when missing
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS proof_jobs (
+    id TEXT PRIMARY KEY,
+    -- Encrypted request; NULL once the job finished
+    payload TEXT,
+    lease_holder TEXT,
+    lease_expires_at TIMESTAMPTZ,
+    -- Latest JobRecord as JSON
+    record TEXT,
+    record_expires_at TIMESTAMPTZ
+);
+CREATE INDEX IF NOT EXISTS proof_jobs_unfinished ON proof_jobs (lease_expires_at)
+    WHERE payload IS NOT NULL;
+";
+
+/// Connection to the job database, reopened when it drops
+#[derive(Clone)]
+pub struct PostgresStore {
+    url: Arc<str>,
+    client: Arc<Mutex<Arc<Client>>>,
+}
+
+impl PostgresStore {
+    /// Connect to `url` and create the jobs table, failing if Postgres is
+    /// unreachable
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let client = open(url).await?;
+        client.batch_execute(SCHEMA).await?;
+
+        Ok(Self {
+            url: url.into(),
+            client: Arc::new(Mutex::new(Arc::new(client))),
+        })
+    }
+
+    async fn client(&self) -> Result<Arc<Client>> {
+        let mut client = self.client.lock().await;
+        if client.is_closed() {
+            *client = Arc::new(open(&self.url).await.map_err(store_error)?);
+        }
+        Ok(client.clone())
+    }
+
+    /// Store the request of job `id`, leased to `holder`
+    pub async fn persist(
+        &self,
+        id: &str,
+        payload: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.client()
+            .await?
+            .execute(
+                "INSERT INTO proof_jobs (id, payload, lease_holder, lease_expires_at)
+                 VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+                 ON CONFLICT (id) DO UPDATE SET payload = $2, lease_holder = $3,
+                     lease_expires_at = now() + make_interval(secs => $4)",
+                &[&id, &payload, &holder, &ttl.as_secs_f64()],
+            )
+            .await
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Extend `holder`'s lease on job `id`; `false` when it no longer holds it
+    pub async fn renew(&self, id: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let renewed = self
+            .client()
+            .await?
+            .execute(
+                "UPDATE proof_jobs SET lease_expires_at = now() + make_interval(secs => $3)
+                 WHERE id = $1 AND lease_holder = $2 AND payload IS NOT NULL",
+                &[&id, &holder, &ttl.as_secs_f64()],
+            )
+            .await
+            .map_err(store_error)?;
+        Ok(renewed == 1)
+    }
+
+    /// Lease every unfinished job whose lease expired to `holder`; returns
+    /// their ids and encrypted requests
+    pub async fn claim_orphaned(
+        &self,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<(String, String)>> {
+        let rows = self
+            .client()
+            .await?
+            .query(
+                "UPDATE proof_jobs
+                 SET lease_holder = $1, lease_expires_at = now() + make_interval(secs => $2)
+                 WHERE payload IS NOT NULL
+                     AND (lease_expires_at IS NULL OR lease_expires_at < now())
+                 RETURNING id, payload",
+                &[&holder, &ttl.as_secs_f64()],
+            )
+            .await
+            .map_err(store_error)?;
+
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    /// Drop the request and lease of a finished job; its record stays until
+    /// it expires
+    pub async fn release(&self, id: &str) -> Result<()> {
+        self.client()
+            .await?
+            .execute(
+                "UPDATE proof_jobs SET payload = NULL, lease_holder = NULL, lease_expires_at = NULL
+                 WHERE id = $1",
+                &[&id],
+            )
+            .await
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Store the record of job `id`, kept for `ttl` after this update
+    pub async fn store_record(&self, id: &str, record: &str, ttl: Duration) -> Result<()> {
+        self.client()
+            .await?
+            .execute(
+                "INSERT INTO proof_jobs (id, record, record_expires_at)
+                 VALUES ($1, $2, now() + make_interval(secs => $3))
+                 ON CONFLICT (id) DO UPDATE SET record = $2,
+                     record_expires_at = now() + make_interval(secs => $3)",
+                &[&id, &record, &ttl.as_secs_f64()],
+            )
+            .await
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Record of job `id`, unless it expired
+    pub async fn record(&self, id: &str) -> Result<Option<String>> {
+        let row = self
+            .client()
+            .await?
+            .query_opt(
+                "SELECT record FROM proof_jobs WHERE id = $1 AND record_expires_at > now()",
+                &[&id],
+            )
+            .await
+            .map_err(store_error)?;
+
+        Ok(row.and_then(|row| row.get(0)))
+    }
+
+    /// Delete finished jobs whose record expired
+    pub async fn purge_expired(&self) -> Result<u64> {
+        self.client()
+            .await?
+            .execute(
+                "DELETE FROM proof_jobs
+                 WHERE payload IS NULL AND (record_expires_at IS NULL OR record_expires_at < now())",
+                &[],
+            )
+            .await
+            .map_err(store_error)
+    }
+}
+
+/// Open a connection, driven by a background task until it closes
+async fn open(url: &str) -> std::result::Result<Client, tokio_postgres::Error> {
+    let (client, connection) = tokio_postgres::connect(url, NoTls).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            warn!(error = %e, "Job database connection lost");
+        }
+    });
+    Ok(client)
+}
+
+fn store_error(err: tokio_postgres::Error) -> BackendError {
+    BackendError::StateStore(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Needs a scratch database: `JOB_DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_leases_and_records() {
+        let url = std::env::var("JOB_DATABASE_URL").expect("JOB_DATABASE_URL");
+        let store = PostgresStore::connect(&url).await.unwrap();
+        let id = uuid::Uuid::new_v4().to_string();
+        let ttl = Duration::from_secs(30);
+
+        store.store_record(&id, "{}", ttl).await.unwrap();
+        store.persist(&id, "sealed", "a", ttl).await.unwrap();
+        assert_eq!(store.record(&id).await.unwrap().as_deref(), Some("{}"));
+
+        // Only the holder renews, and a live lease is not claimed
+        assert!(store.renew(&id, "a", ttl).await.unwrap());
+        assert!(!store.renew(&id, "b", ttl).await.unwrap());
+        let claimed = store.claim_orphaned("b", ttl).await.unwrap();
+        assert!(!claimed.iter().any(|(claimed, _)| *claimed == id));
+
+        // An expired lease goes to the first claimant
+        store
+            .persist(&id, "sealed", "a", Duration::ZERO)
+            .await
+            .unwrap();
+        let claimed = store.claim_orphaned("b", ttl).await.unwrap();
+        assert!(claimed.contains(&(id.clone(), "sealed".to_string())));
+        assert!(!store.renew(&id, "a", ttl).await.unwrap());
+        assert!(store.renew(&id, "b", ttl).await.unwrap());
+
+        // Released jobs keep their record but are never claimed again
+        store.release(&id).await.unwrap();
+        assert!(!store.renew(&id, "b", ttl).await.unwrap());
+        assert_eq!(store.record(&id).await.unwrap().as_deref(), Some("{}"));
+        store.store_record(&id, "{}", Duration::ZERO).await.unwrap();
+        store.purge_expired().await.unwrap();
+        assert_eq!(store.record(&id).await.unwrap(), None);
+    }
+}
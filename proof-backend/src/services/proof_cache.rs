@@ -0,0 +1,76 @@
+//! Pubkey Validity Proof Cache
+//!
+//! A pubkey validity proof only attests to the ElGamal keypair, so any valid
+//! proof for a keypair can be reused. Onboarding retries and multi-device
+//! flows derive the same keypair for a token account, and get the cached proof
+//! instead of a fresh one.
+
+use moka::sync::Cache;
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+
+/// Cache key: token account and the ElGamal public key derived for it
+type CacheKey = (Pubkey, [u8; 32]);
+
+/// Optional TTL cache of pubkey validity proof bytes
+#[derive(Clone)]
+pub struct PubkeyValidityCache {
+    /// `None` when caching is disabled
+    proofs: Option<Cache<CacheKey, Vec<u8>>>,
+}
+
+impl PubkeyValidityCache {
+    /// Create a cache holding up to `capacity` proofs for `ttl` each; a zero
+    /// TTL or capacity disables caching
+    pub fn new(ttl: Duration, capacity: u64) -> Self {
+        let proofs = (!ttl.is_zero() && capacity > 0).then(|| {
+            Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ttl)
+                .build()
+        });
+
+        Self { proofs }
+    }
+
+    /// Cached proof for the keypair, if any
+    pub fn get(&self, token_account: &Pubkey, elgamal_pubkey: &[u8; 32]) -> Option<Vec<u8>> {
+        self.proofs
+            .as_ref()?
+            .get(&(*token_account, *elgamal_pubkey))
+    }
+
+    /// Store a freshly generated proof
+    pub fn insert(&self, token_account: &Pubkey, elgamal_pubkey: &[u8; 32], proof_data: Vec<u8>) {
+        if let Some(proofs) = &self.proofs {
+            proofs.insert((*token_account, *elgamal_pubkey), proof_data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hit() {
+        let cache = PubkeyValidityCache::new(Duration::from_secs(60), 10);
+        let token_account = Pubkey::new_unique();
+
+        cache.insert(&token_account, &[1; 32], vec![7; 4]);
+
+        assert_eq!(cache.get(&token_account, &[1; 32]), Some(vec![7; 4]));
+        assert_eq!(cache.get(&token_account, &[2; 32]), None);
+        assert_eq!(cache.get(&Pubkey::new_unique(), &[1; 32]), None);
+    }
+
+    #[test]
+    fn test_disabled_cache() {
+        let cache = PubkeyValidityCache::new(Duration::ZERO, 10);
+        let token_account = Pubkey::new_unique();
+
+        cache.insert(&token_account, &[1; 32], vec![7; 4]);
+
+        assert_eq!(cache.get(&token_account, &[1; 32]), None);
+    }
+}
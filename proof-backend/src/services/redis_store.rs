@@ -0,0 +1,252 @@
+//! Shared State in Redis
+//!
+//! Replicas behind a load balancer keep replay protection, usage counters,
+//! idempotency keys, job records and requests, managed API keys and relayer
+//! spend in one Redis, so a request behaves the same whichever replica serves
+//! it. Without a Redis URL each process keeps this state in memory.
+
+use futures::StreamExt;
+use redis::{aio::ConnectionManager, AsyncCommands, Client};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::error::{BackendError, Result};
+
+/// Wait before resubscribing after the pub/sub connection drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Extends `KEYS[1]` to `ARGV[2]` milliseconds only while it holds `ARGV[1]`,
+/// in one step so no other client can change it in between
+const EXPIRE_IF_EQUAL_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+/// Connection to the shared Redis; every key is namespaced by a prefix
+#[derive(Clone)]
+pub struct RedisStore {
+    client: Client,
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisStore {
+    /// Connect to `url`, failing if Redis is unreachable
+    pub async fn connect(url: &str, prefix: &str) -> anyhow::Result<Self> {
+        let client = Client::open(url)?;
+        let conn = client.get_connection_manager().await?;
+
+        Ok(Self {
+            client,
+            conn,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}:{name}", self.prefix)
+    }
+
+    /// Set `name` unless it already exists; `true` when it was set
+    pub async fn set_if_absent(&self, name: &str, value: &str, ttl: Duration) -> Result<bool> {
+        let set: Option<String> = redis::cmd("SET")
+            .arg(self.key(name))
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(store_error)?;
+
+        Ok(set.is_some())
+    }
+
+    /// Set `name`, replacing any previous value
+    pub async fn set(&self, name: &str, value: &str, ttl: Duration) -> Result<()> {
+        self.conn
+            .clone()
+            .set_ex(self.key(name), value, ttl.as_secs().max(1))
+            .await
+            .map_err(store_error)
+    }
+
+    /// Reset the expiry of `name` to `ttl` if it still holds `value`; `true`
+    /// when it did
+    pub async fn expire_if_equal(&self, name: &str, value: &str, ttl: Duration) -> Result<bool> {
+        let extended: u64 = redis::cmd("EVAL")
+            .arg(EXPIRE_IF_EQUAL_SCRIPT)
+            .arg(1)
+            .arg(self.key(name))
+            .arg(value)
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(store_error)?;
+
+        Ok(extended == 1)
+    }
+
+    pub async fn get(&self, name: &str) -> Result<Option<String>> {
+        self.conn
+            .clone()
+            .get(self.key(name))
+            .await
+            .map_err(store_error)
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        self.conn
+            .clone()
+            .del(self.key(name))
+            .await
+            .map_err(store_error)
+    }
+
+    /// Set `field` of the hash `name`, which never expires
+    pub async fn hash_set(&self, name: &str, field: &str, value: &str) -> Result<()> {
+        self.conn
+            .clone()
+            .hset(self.key(name), field, value)
+            .await
+            .map_err(store_error)
+    }
+
+    /// Delete `field` of the hash `name`; `true` when it existed
+    pub async fn hash_delete(&self, name: &str, field: &str) -> Result<bool> {
+        let deleted: u64 = self
+            .conn
+            .clone()
+            .hdel(self.key(name), field)
+            .await
+            .map_err(store_error)?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Every field and value of the hash `name`
+    pub async fn hash_entries(&self, name: &str) -> Result<Vec<(String, String)>> {
+        self.conn
+            .clone()
+            .hgetall(self.key(name))
+            .await
+            .map_err(store_error)
+    }
+
+    /// Every value of the hash `name`
+    pub async fn hash_values(&self, name: &str) -> Result<Vec<String>> {
+        self.conn
+            .clone()
+            .hvals(self.key(name))
+            .await
+            .map_err(store_error)
+    }
+
+    /// Add `by` to the counter `name`; the counter expires `ttl` after its
+    /// last update
+    pub async fn increment(&self, name: &str, by: u64, ttl: Duration) -> Result<u64> {
+        let key = self.key(name);
+        let (value,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(&key, by)
+            .expire(&key, ttl.as_secs().max(1) as i64)
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(store_error)?;
+
+        Ok(value)
+    }
+
+    /// Subtract `by` from the counter `name`
+    pub async fn decrement(&self, name: &str, by: u64) -> Result<()> {
+        let _: i64 = self
+            .conn
+            .clone()
+            .decr(self.key(name), by)
+            .await
+            .map_err(store_error)?;
+
+        Ok(())
+    }
+
+    /// Current values of several counters; missing counters are zero
+    pub async fn counters(&self, names: &[String]) -> Result<Vec<u64>> {
+        let keys: Vec<_> = names.iter().map(|name| self.key(name)).collect();
+        let values: Vec<Option<u64>> = redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(store_error)?;
+
+        Ok(values.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
+    /// Publish `message` to every replica subscribed to `channel`
+    pub async fn publish(&self, channel: &str, message: &str) -> Result<()> {
+        self.conn
+            .clone()
+            .publish(self.key(channel), message)
+            .await
+            .map_err(store_error)
+    }
+
+    /// Call `on_message` for every message published to `channel`,
+    /// resubscribing whenever the connection drops
+    ///
+    /// Messages published while the subscription is down are lost.
+    pub fn spawn_subscriber(&self, channel: &str, on_message: impl Fn(&str) + Send + 'static) {
+        let client = self.client.clone();
+        let channel = self.key(channel);
+
+        tokio::spawn(async move {
+            loop {
+                match client.get_async_pubsub().await {
+                    Ok(mut pubsub) => match pubsub.subscribe(&channel).await {
+                        Ok(()) => {
+                            info!(channel, "Subscribed to shared events");
+                            let mut messages = pubsub.into_on_message();
+                            while let Some(message) = messages.next().await {
+                                match message.get_payload::<String>() {
+                                    Ok(payload) => on_message(&payload),
+                                    Err(e) => warn!(channel, error = %e, "Invalid shared event"),
+                                }
+                            }
+                            warn!(channel, "Shared event subscription lost");
+                        }
+                        Err(e) => warn!(channel, error = %e, "Failed to subscribe"),
+                    },
+                    Err(e) => warn!(channel, error = %e, "Failed to connect for events"),
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        });
+    }
+}
+
+fn store_error(err: redis::RedisError) -> BackendError {
+    BackendError::StateStore(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Needs a scratch Redis: `REDIS_URL=redis://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_expire_if_equal() {
+        let url = std::env::var("REDIS_URL").expect("REDIS_URL");
+        let prefix = format!("test-{}", uuid::Uuid::new_v4());
+        let store = RedisStore::connect(&url, &prefix).await.unwrap();
+        let ttl = Duration::from_secs(30);
+
+        assert!(!store.expire_if_equal("lease", "a", ttl).await.unwrap());
+        store.set("lease", "a", ttl).await.unwrap();
+        assert!(store.expire_if_equal("lease", "a", ttl).await.unwrap());
+        assert!(!store.expire_if_equal("lease", "b", ttl).await.unwrap());
+        store.delete("lease").await.unwrap();
+    }
+}
@@ -0,0 +1,659 @@
+//! Transaction Relayer
+//!
+//! Signs and submits transactions as fee payer for wallets that opt in, so a
+//! confidential withdrawal needs no SOL in the user's wallet. Transactions
+//! may only call allowlisted programs, and the relayer's account may only
+//! fund and receive back proof context state accounts. What the relayer pays
+//! in fees and rent is capped per transaction, per API key per UTC day and
+//! in total per UTC day. Daily spend lives in memory, or in a shared Redis.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::{
+    compute_budget,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::{instruction::SystemInstruction, program as system_program};
+use solana_zk_sdk::zk_elgamal_proof_program::{self, instruction::ProofInstruction};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, instrument, warn};
+
+use super::tx_builder::TOKEN_2022_PROGRAM_ID;
+use crate::{
+    error::{BackendError, Result},
+    services::RedisStore,
+    types::{Config, RelayerConfig},
+};
+
+/// Base fee per signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Compute unit limit of an instruction when the transaction sets none
+const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u64 = 200_000;
+
+/// Maximum compute unit limit of a transaction
+const MAX_TRANSACTION_COMPUTE_UNITS: u64 = 1_400_000;
+
+/// `ComputeBudgetInstruction::SetComputeUnitLimit`
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+
+/// `ComputeBudgetInstruction::SetComputeUnitPrice`
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Timeout for a single RPC call
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a submitted transaction may take to be confirmed
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between signature status polls
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Shared daily spend counters outlive their day by this much
+const SPEND_COUNTER_TTL: Duration = Duration::from_secs(2 * 24 * 3600);
+
+/// Decode a bincode-serialized transaction (base64)
+pub fn decode_transaction(s: &str) -> Result<Transaction> {
+    let bytes = STANDARD.decode(s).map_err(|e| {
+        BackendError::invalid_format("INVALID_ENCODING", "base64", format!("invalid base64: {e}"))
+    })?;
+    bincode::deserialize(&bytes).map_err(|e| {
+        BackendError::invalid_format(
+            "INVALID_VALUE",
+            "bincode-serialized transaction",
+            format!("invalid transaction: {e}"),
+        )
+    })
+}
+
+/// Lamports the relayer pays for `tx`: signature and priority fees, plus
+/// context state accounts it funds
+///
+/// Rejects transactions whose fee payer is not `relayer`, that call a
+/// program outside `allowed_programs`, or that use the relayer's account for
+/// anything but funding a context state account or receiving its rent back.
+pub fn relay_cost(tx: &Transaction, relayer: &Pubkey, allowed_programs: &[Pubkey]) -> Result<u64> {
+    let message = &tx.message;
+    if message.account_keys.first() != Some(relayer) {
+        return Err(BackendError::Forbidden(format!(
+            "Fee payer must be the relayer {relayer}"
+        )));
+    }
+
+    let malformed = || BackendError::BadRequest("Transaction is malformed".to_string());
+    let mut funded = 0u64;
+    let mut compute_unit_limit = None;
+    let mut compute_unit_price = 0u64;
+    let mut other_instructions = 0u64;
+
+    for (i, ix) in message.instructions.iter().enumerate() {
+        let program = message
+            .account_keys
+            .get(ix.program_id_index as usize)
+            .ok_or_else(malformed)?;
+        if !allowed_programs.contains(program) {
+            return Err(BackendError::Forbidden(format!(
+                "Instruction {i} calls {program}, which the relayer does not allow"
+            )));
+        }
+        let relayer_position = ix
+            .accounts
+            .iter()
+            .position(|&index| message.account_keys.get(index as usize) == Some(relayer));
+
+        if compute_budget::check_id(program) {
+            let value = |len: usize| ix.data.get(1..1 + len).ok_or_else(malformed);
+            match ix.data.first() {
+                Some(&SET_COMPUTE_UNIT_LIMIT) => {
+                    let limit = u32::from_le_bytes(value(4)?.try_into().unwrap());
+                    compute_unit_limit = Some(u64::from(limit));
+                }
+                Some(&SET_COMPUTE_UNIT_PRICE) => {
+                    compute_unit_price = u64::from_le_bytes(value(8)?.try_into().unwrap());
+                }
+                _ => {}
+            }
+            continue;
+        }
+        other_instructions += 1;
+
+        let allowed_use = if *program == system_program::ID {
+            // Creating a proof context account, funded by the relayer
+            match bincode::deserialize::<SystemInstruction>(&ix.data) {
+                Ok(SystemInstruction::CreateAccount {
+                    lamports, owner, ..
+                }) if owner == zk_elgamal_proof_program::id() && relayer_position == Some(0) => {
+                    funded = funded.checked_add(lamports).ok_or_else(malformed)?;
+                    true
+                }
+                _ => relayer_position.is_none(),
+            }
+        } else if *program == zk_elgamal_proof_program::id()
+            && ProofInstruction::instruction_type(&ix.data)
+                == Some(ProofInstruction::CloseContextState)
+        {
+            // The relayer may receive the rent of a closed context account
+            matches!(relayer_position, None | Some(1))
+        } else {
+            relayer_position.is_none()
+        };
+        if !allowed_use {
+            return Err(BackendError::Forbidden(format!(
+                "Instruction {i} uses the relayer account"
+            )));
+        }
+    }
+
+    let compute_units = compute_unit_limit.unwrap_or_else(|| {
+        (other_instructions * DEFAULT_INSTRUCTION_COMPUTE_UNITS).min(MAX_TRANSACTION_COMPUTE_UNITS)
+    });
+    // The price is in micro-lamports per compute unit, rounded up
+    let priority_fee = (u128::from(compute_units) * u128::from(compute_unit_price))
+        .div_ceil(1_000_000)
+        .try_into()
+        .map_err(|_| malformed())?;
+    let signature_fee = u64::from(message.header.num_required_signatures) * LAMPORTS_PER_SIGNATURE;
+
+    signature_fee
+        .checked_add(priority_fee)
+        .and_then(|fee| fee.checked_add(funded))
+        .ok_or_else(malformed)
+}
+
+/// Programs relayed transactions may call when none are configured: those of
+/// the SVS-2 withdraw flow
+fn default_allowed_programs(svs2_program_id: &str) -> anyhow::Result<Vec<Pubkey>> {
+    let svs2 = Pubkey::from_str(svs2_program_id)
+        .map_err(|e| anyhow::anyhow!("Invalid SVS2 program id: {e}"))?;
+
+    Ok(vec![
+        svs2,
+        zk_elgamal_proof_program::id(),
+        TOKEN_2022_PROGRAM_ID,
+        system_program::ID,
+        compute_budget::id(),
+    ])
+}
+
+/// Lamports reserved from the daily budgets for one transaction
+struct Reservation {
+    day: String,
+    identity: String,
+    lamports: u64,
+}
+
+/// Daily spend, in total and per API key
+#[derive(Clone, Default)]
+struct SpendTracker {
+    /// `(day, identity)` to lamports; the empty identity is the total
+    spent: Arc<Mutex<HashMap<(String, String), u64>>>,
+    /// Counters shared by every replica; in-process when unset
+    shared: Option<RedisStore>,
+}
+
+/// Name of a shared spend counter; the identity is hashed so an API key
+/// never leaves the process
+fn spend_counter_name(day: &str, identity: &str) -> String {
+    if identity.is_empty() {
+        return format!("relay:{day}:total");
+    }
+    let identity_id = solana_sdk::hash::hash(identity.as_bytes());
+    format!("relay:{day}:{identity_id}")
+}
+
+impl SpendTracker {
+    /// Reserve `lamports` for `identity`, unless that exceeds its daily limit
+    /// or the daily total
+    async fn reserve(
+        &self,
+        identity: &str,
+        lamports: u64,
+        config: &RelayerConfig,
+    ) -> Result<Reservation> {
+        let reservation = Reservation {
+            day: Utc::now().format("%Y-%m-%d").to_string(),
+            identity: identity.to_string(),
+            lamports,
+        };
+        let within_limits = |total: u64, own: u64| {
+            total <= config.daily_lamports && own <= config.daily_lamports_per_key
+        };
+
+        let reserved = match &self.shared {
+            Some(shared) => {
+                let total_name = spend_counter_name(&reservation.day, "");
+                let own_name = spend_counter_name(&reservation.day, identity);
+                let total = shared
+                    .increment(&total_name, lamports, SPEND_COUNTER_TTL)
+                    .await?;
+                let own = shared
+                    .increment(&own_name, lamports, SPEND_COUNTER_TTL)
+                    .await?;
+                let reserved = within_limits(total, own);
+                if !reserved {
+                    self.release(&reservation).await;
+                }
+                reserved
+            }
+            None => {
+                let mut spent = self.spent.lock().unwrap();
+                // Only today's counters are needed
+                spent.retain(|(day, _), _| *day == reservation.day);
+                let total = spent
+                    .get(&(reservation.day.clone(), String::new()))
+                    .copied()
+                    .unwrap_or_default()
+                    .saturating_add(lamports);
+                let own = spent
+                    .get(&(reservation.day.clone(), identity.to_string()))
+                    .copied()
+                    .unwrap_or_default()
+                    .saturating_add(lamports);
+                let reserved = within_limits(total, own);
+                if reserved {
+                    spent.insert((reservation.day.clone(), String::new()), total);
+                    spent.insert((reservation.day.clone(), identity.to_string()), own);
+                }
+                reserved
+            }
+        };
+
+        if !reserved {
+            return Err(BackendError::QuotaExceeded(
+                "Relayer daily spend limit reached".to_string(),
+            ));
+        }
+        Ok(reservation)
+    }
+
+    /// Return a reservation for a transaction that was not sent
+    async fn release(&self, reservation: &Reservation) {
+        let Some(shared) = &self.shared else {
+            let mut spent = self.spent.lock().unwrap();
+            for identity in ["", reservation.identity.as_str()] {
+                if let Some(value) = spent.get_mut(&(reservation.day.clone(), identity.to_string()))
+                {
+                    *value = value.saturating_sub(reservation.lamports);
+                }
+            }
+            return;
+        };
+
+        for identity in ["", reservation.identity.as_str()] {
+            let name = spend_counter_name(&reservation.day, identity);
+            if let Err(e) = shared.decrement(&name, reservation.lamports).await {
+                warn!(error = %e, "Failed to release relayer spend");
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct SignatureStatuses {
+    value: Vec<Option<SignatureStatus>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignatureStatus {
+    err: Option<serde_json::Value>,
+    confirmation_status: Option<String>,
+}
+
+/// Fee payer that signs and submits relayed transactions
+#[derive(Clone)]
+pub struct Relayer {
+    keypair: Arc<Keypair>,
+    config: RelayerConfig,
+    allowed_programs: Vec<Pubkey>,
+    spend: SpendTracker,
+    client: reqwest::Client,
+    rpc_url: String,
+}
+
+impl Relayer {
+    /// Load the relayer from the configuration; `None` when it is disabled
+    pub fn load(config: &Config, shared: Option<RedisStore>) -> anyhow::Result<Option<Self>> {
+        let Some(relayer) = &config.relayer else {
+            return Ok(None);
+        };
+        // Without credentials every request is anonymous, and anyone could
+        // spend the fee payer's SOL
+        anyhow::ensure!(
+            !config.api_keys.is_empty() || config.admin_api_key.is_some() || config.jwt.is_some(),
+            "The relayer needs API_KEYS, ADMIN_API_KEY or JWT authentication"
+        );
+
+        let path = relayer
+            .keypair_path
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("The relayer needs RELAYER_KEYPAIR_PATH"))?;
+        let keypair = read_keypair_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read relayer keypair {path}: {e}"))?;
+        let rpc_url = config
+            .rpc_url
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("The relayer needs SOLANA_RPC_URL"))?;
+        let allowed_programs = match &relayer.allowed_programs {
+            Some(programs) => programs
+                .iter()
+                .map(|program| {
+                    Pubkey::from_str(program)
+                        .map_err(|e| anyhow::anyhow!("Invalid relayer program {program}: {e}"))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            None => default_allowed_programs(&config.svs2_program_id)?,
+        };
+        let client = reqwest::Client::builder()
+            .timeout(RPC_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Ok(Some(Self {
+            keypair: Arc::new(keypair),
+            config: relayer.clone(),
+            allowed_programs,
+            spend: SpendTracker {
+                spent: Arc::default(),
+                shared,
+            },
+            client,
+            rpc_url,
+        }))
+    }
+
+    /// Fee payer of relayed transactions
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn config(&self) -> &RelayerConfig {
+        &self.config
+    }
+
+    pub fn allowed_programs(&self) -> &[Pubkey] {
+        &self.allowed_programs
+    }
+
+    /// Sign and submit transactions in order, each once the previous one is
+    /// confirmed; `identity` is charged for what the relayer pays
+    ///
+    /// Every transaction is checked before the first is sent. Returns the
+    /// signatures of the submitted transactions and the lamports spent.
+    #[instrument(skip_all, fields(transactions = transactions.len()))]
+    pub async fn submit(
+        &self,
+        identity: &str,
+        transactions: Vec<Transaction>,
+    ) -> Result<(Vec<Signature>, u64)> {
+        let relayer = self.pubkey();
+        let mut signed = Vec::with_capacity(transactions.len());
+        for (i, mut tx) in transactions.into_iter().enumerate() {
+            let cost = relay_cost(&tx, &relayer, &self.allowed_programs)?;
+            if cost > self.config.max_lamports_per_transaction {
+                return Err(BackendError::Forbidden(format!(
+                    "Transaction {i} costs the relayer {cost} lamports, more than {}",
+                    self.config.max_lamports_per_transaction
+                )));
+            }
+
+            let blockhash = tx.message.recent_blockhash;
+            tx.try_partial_sign(&[self.keypair.as_ref()], blockhash)
+                .map_err(|e| BackendError::BadRequest(format!("Transaction {i}: {e}")))?;
+            tx.verify().map_err(|_| {
+                BackendError::InvalidSignature(format!(
+                    "Transaction {i} is missing a signature other than the relayer's"
+                ))
+            })?;
+            signed.push((tx, cost));
+        }
+
+        let mut signatures = Vec::with_capacity(signed.len());
+        let mut spent = 0u64;
+        for (i, (tx, cost)) in signed.into_iter().enumerate() {
+            let reservation = self.spend.reserve(identity, cost, &self.config).await?;
+            let signature = match self.send(&tx).await {
+                Ok(signature) => signature,
+                Err(e) => {
+                    // Rejected before landing, so nothing was paid
+                    self.spend.release(&reservation).await;
+                    return Err(e);
+                }
+            };
+            spent += cost;
+            info!(%signature, lamports = cost, "Relayed transaction {i}");
+
+            self.confirm(&signature).await.map_err(|e| {
+                BackendError::Rpc(format!(
+                    "Transaction {i} ({signature}) failed: {e}; earlier transactions landed"
+                ))
+            })?;
+            signatures.push(signature);
+        }
+
+        Ok((signatures, spent))
+    }
+
+    /// Submit a signed transaction, with preflight simulation
+    async fn send(&self, tx: &Transaction) -> Result<Signature> {
+        let bytes = bincode::serialize(tx)
+            .map_err(|e| BackendError::Internal(format!("Failed to serialize transaction: {e}")))?;
+        let response: RpcResponse<String> = self
+            .call(
+                "sendTransaction",
+                json!([
+                    STANDARD.encode(bytes),
+                    {"encoding": "base64", "preflightCommitment": "confirmed"},
+                ]),
+            )
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(BackendError::BadRequest(format!(
+                "Transaction rejected: {error}"
+            )));
+        }
+        let signature = response
+            .result
+            .ok_or_else(|| BackendError::Rpc("sendTransaction returned no signature".into()))?;
+        Signature::from_str(&signature)
+            .map_err(|e| BackendError::Rpc(format!("Invalid transaction signature: {e}")))
+    }
+
+    /// Wait until a transaction is confirmed; fails if it errored on chain
+    async fn confirm(&self, signature: &Signature) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + CONFIRM_TIMEOUT;
+        loop {
+            let response: RpcResponse<SignatureStatuses> = self
+                .call("getSignatureStatuses", json!([[signature.to_string()]]))
+                .await?;
+            if let Some(error) = response.error {
+                return Err(BackendError::Rpc(format!(
+                    "getSignatureStatuses failed: {error}"
+                )));
+            }
+
+            let status = response
+                .result
+                .and_then(|result| result.value.into_iter().next().flatten());
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    return Err(BackendError::Rpc(format!("transaction error: {err}")));
+                }
+                if matches!(
+                    status.confirmation_status.as_deref(),
+                    Some("confirmed" | "finalized")
+                ) {
+                    return Ok(());
+                }
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(BackendError::Rpc("not confirmed in time".to_string()));
+            }
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<RpcResponse<T>> {
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        self.client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| BackendError::Rpc(format!("{method} failed: {e}")))?
+            .json()
+            .await
+            .map_err(|e| BackendError::Rpc(format!("Invalid {method} response: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, instruction::Instruction};
+    use solana_system_interface::instruction as system_instruction;
+    use solana_zk_sdk::zk_elgamal_proof_program::instruction::{
+        close_context_state, ContextStateInfo,
+    };
+
+    fn allowed() -> Vec<Pubkey> {
+        default_allowed_programs(crate::types::DEFAULT_SVS2_PROGRAM_ID).unwrap()
+    }
+
+    fn transaction(instructions: &[Instruction], payer: &Pubkey) -> Transaction {
+        let mut tx = Transaction::new_with_payer(instructions, Some(payer));
+        tx.message.recent_blockhash = Hash::new_unique();
+        tx
+    }
+
+    #[test]
+    fn test_load_requires_authentication() {
+        let mut config = Config {
+            relayer: Some(RelayerConfig::default()),
+            ..Config::default()
+        };
+        let Err(err) = Relayer::load(&config, None) else {
+            panic!("relayer loaded without authentication");
+        };
+        assert!(err.to_string().contains("authentication"));
+
+        // Past the check, the missing keypair is what fails
+        config.admin_api_key = Some("admin".to_string());
+        let Err(err) = Relayer::load(&config, None) else {
+            panic!("relayer loaded without a keypair");
+        };
+        assert!(err.to_string().contains("RELAYER_KEYPAIR_PATH"));
+    }
+
+    #[test]
+    fn test_relay_cost_counts_fees_and_rent() {
+        let relayer = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let context = Pubkey::new_unique();
+        let tx = transaction(
+            &[
+                compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(100_000),
+                compute_budget::ComputeBudgetInstruction::set_compute_unit_price(10),
+                system_instruction::create_account(
+                    &relayer,
+                    &context,
+                    1_000_000,
+                    100,
+                    &zk_elgamal_proof_program::id(),
+                ),
+                close_context_state(
+                    ContextStateInfo {
+                        context_state_account: &context,
+                        context_state_authority: &user,
+                    },
+                    &relayer,
+                ),
+            ],
+            &relayer,
+        );
+
+        // Three signers (relayer, context account, user), 1 lamport of
+        // priority fee and the context account's rent
+        let cost = relay_cost(&tx, &relayer, &allowed()).unwrap();
+        assert_eq!(cost, 3 * LAMPORTS_PER_SIGNATURE + 1 + 1_000_000);
+    }
+
+    #[test]
+    fn test_relay_cost_rejects_misuse() {
+        let relayer = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let forbidden = |tx: &Transaction| {
+            matches!(
+                relay_cost(tx, &relayer, &allowed()),
+                Err(BackendError::Forbidden(_))
+            )
+        };
+
+        // Another fee payer
+        let transfer = system_instruction::transfer(&user, &relayer, 1);
+        assert!(forbidden(&transaction(&[transfer], &user)));
+
+        // Draining the relayer
+        let transfer = system_instruction::transfer(&relayer, &user, 1);
+        assert!(forbidden(&transaction(&[transfer], &relayer)));
+
+        // Creating an account the relayer cannot get back
+        let create = system_instruction::create_account(
+            &relayer,
+            &Pubkey::new_unique(),
+            1,
+            0,
+            &Pubkey::new_unique(),
+        );
+        assert!(forbidden(&transaction(&[create], &relayer)));
+
+        // Programs outside the allowlist
+        let other = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        assert!(forbidden(&transaction(&[other], &relayer)));
+    }
+
+    #[tokio::test]
+    async fn test_spend_limits() {
+        let config = RelayerConfig {
+            daily_lamports: 150,
+            daily_lamports_per_key: 100,
+            ..Default::default()
+        };
+        let spend = SpendTracker::default();
+
+        let first = spend.reserve("a", 60, &config).await.unwrap();
+        assert!(matches!(
+            spend.reserve("a", 60, &config).await,
+            Err(BackendError::QuotaExceeded(_))
+        ));
+        spend.reserve("b", 60, &config).await.unwrap();
+        // The total is exhausted too
+        assert!(spend.reserve("c", 60, &config).await.is_err());
+
+        // Unsent transactions give their reservation back
+        spend.release(&first).await;
+        spend.reserve("c", 60, &config).await.unwrap();
+    }
+}
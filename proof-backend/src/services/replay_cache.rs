@@ -0,0 +1,128 @@
+//! Replay Protection
+//!
+//! Remembers request signatures until their timestamp leaves the tolerance
+//! window, so a captured request cannot be submitted twice. Entries older than
+//! the window need no tracking: the timestamp check already rejects them.
+//!
+//! With a shared Redis the signatures are recorded there, so a request
+//! accepted by one replica is rejected by all of them.
+
+use crate::error::{BackendError, Result};
+use crate::services::RedisStore;
+use chrono::Utc;
+use solana_sdk::signature::Signature;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Store of consumed request signatures
+#[derive(Clone)]
+pub struct ReplayCache {
+    /// Signature bytes -> unix time after which the entry can be dropped
+    seen: Arc<Mutex<HashMap<[u8; 64], i64>>>,
+    tolerance_secs: i64,
+    capacity: usize,
+    /// Signatures shared by every replica; in-process when unset
+    shared: Option<RedisStore>,
+}
+
+impl ReplayCache {
+    /// Create a cache holding at most `capacity` signatures for requests
+    /// accepted within `tolerance_secs` of their timestamp
+    pub fn new(tolerance_secs: i64, capacity: usize, shared: Option<RedisStore>) -> Self {
+        Self {
+            seen: Arc::new(Mutex::new(HashMap::new())),
+            tolerance_secs,
+            capacity: capacity.max(1),
+            shared,
+        }
+    }
+
+    /// Record `signature`, failing if it was already used
+    ///
+    /// Fails closed when the cache is full of live entries rather than evicting
+    /// one, since an evicted signature could be replayed, and when the shared
+    /// store is unreachable.
+    pub async fn check_and_record(&self, signature: &Signature, timestamp: i64) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let Some(shared) = &self.shared else {
+            return self.check_and_record_at(signature, timestamp, now);
+        };
+
+        let ttl = (timestamp + self.tolerance_secs - now).max(1) as u64;
+        let name = format!("replay:{signature}");
+        if shared
+            .set_if_absent(&name, "1", Duration::from_secs(ttl))
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(BackendError::ReplayedRequest)
+        }
+    }
+
+    fn check_and_record_at(&self, signature: &Signature, timestamp: i64, now: i64) -> Result<()> {
+        let mut seen = self
+            .seen
+            .lock()
+            .map_err(|_| BackendError::Internal("Replay cache poisoned".to_string()))?;
+
+        let key: [u8; 64] = (*signature).into();
+        if seen.get(&key).is_some_and(|&expires_at| expires_at >= now) {
+            return Err(BackendError::ReplayedRequest);
+        }
+
+        if seen.len() >= self.capacity {
+            seen.retain(|_, expires_at| *expires_at >= now);
+            if seen.len() >= self.capacity {
+                return Err(BackendError::Internal("Replay cache full".to_string()));
+            }
+        }
+
+        seen.insert(key, timestamp + self.tolerance_secs);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(byte: u8) -> Signature {
+        Signature::from([byte; 64])
+    }
+
+    #[test]
+    fn test_duplicate_rejected() {
+        let cache = ReplayCache::new(300, 10, None);
+
+        cache
+            .check_and_record_at(&signature(1), 1000, 1000)
+            .unwrap();
+        let result = cache.check_and_record_at(&signature(1), 1000, 1010);
+
+        assert!(matches!(result, Err(BackendError::ReplayedRequest)));
+        cache
+            .check_and_record_at(&signature(2), 1000, 1010)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_expired_entries_are_pruned() {
+        let cache = ReplayCache::new(300, 1, None);
+
+        cache
+            .check_and_record_at(&signature(1), 1000, 1000)
+            .unwrap();
+        assert!(cache
+            .check_and_record_at(&signature(2), 1000, 1200)
+            .is_err());
+
+        // Past the window the first entry makes room for the next
+        cache
+            .check_and_record_at(&signature(2), 1301, 1301)
+            .unwrap();
+    }
+}
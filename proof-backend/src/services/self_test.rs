@@ -0,0 +1,156 @@
+//! Proof Self-Test
+//!
+//! Periodically generates and verifies a pubkey validity proof for a random
+//! keypair on the blocking pool, so the readiness check catches an instance
+//! whose proof generation is wedged or producing invalid proofs.
+
+use chrono::Utc;
+use solana_zk_sdk::{
+    encryption::elgamal::ElGamalKeypair,
+    zk_elgamal_proof_program::proof_data::{PubkeyValidityProofData, ZkProofData},
+};
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tracing::{debug, error};
+
+use crate::{
+    error::{BackendError, Result},
+    services::ProofGenerator,
+    types::SelfTestReport,
+};
+
+/// A test is overdue once this many intervals pass without a result
+const STALE_AFTER_INTERVALS: u32 = 3;
+
+/// Health of the self-test as seen by the readiness check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestStatus {
+    /// The self-test is disabled
+    Disabled,
+    /// No test has finished yet
+    Starting,
+    Passed,
+    Failed,
+    /// The last result is older than the staleness limit
+    Stale,
+}
+
+/// Latest self-test result, refreshed in the background
+#[derive(Clone)]
+pub struct SelfTest {
+    last: Arc<RwLock<Option<SelfTestReport>>>,
+    interval: Option<Duration>,
+}
+
+impl SelfTest {
+    /// Create a self-test running every `interval_secs` (0 disables it)
+    pub fn new(interval_secs: u64) -> Self {
+        Self {
+            last: Arc::new(RwLock::new(None)),
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+        }
+    }
+
+    /// Run the test on its interval for the life of the process
+    pub fn spawn(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let self_test = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let started = Instant::now();
+                let outcome = tokio::task::spawn_blocking(Self::run_once)
+                    .await
+                    .unwrap_or_else(|e| {
+                        Err(BackendError::Internal(format!("Self-test panicked: {e}")))
+                    });
+
+                match &outcome {
+                    Ok(()) => debug!("Proof self-test passed"),
+                    Err(e) => error!(error = %e, "Proof self-test failed"),
+                }
+                self_test.record(outcome, started.elapsed());
+
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    /// Generate and verify one pubkey validity proof
+    pub fn run_once() -> Result<()> {
+        let keypair = ElGamalKeypair::new_rand();
+        let (proof_bytes, _) = ProofGenerator::generate_pubkey_validity_proof(&keypair)?;
+
+        let proof = bytemuck::try_from_bytes::<PubkeyValidityProofData>(&proof_bytes)
+            .map_err(|e| BackendError::Internal(format!("Malformed proof data: {e}")))?;
+        proof
+            .verify_proof()
+            .map_err(|e| BackendError::Internal(format!("Proof failed verification: {e}")))
+    }
+
+    /// Most recent result, if any test has finished
+    pub fn report(&self) -> Option<SelfTestReport> {
+        self.last.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Classify the most recent result at unix time `now`
+    pub fn status(&self, now: i64) -> SelfTestStatus {
+        let Some(interval) = self.interval else {
+            return SelfTestStatus::Disabled;
+        };
+        let Some(report) = self.report() else {
+            return SelfTestStatus::Starting;
+        };
+
+        let stale_after = (interval * STALE_AFTER_INTERVALS).as_secs() as i64;
+        if now - report.checked_at > stale_after {
+            SelfTestStatus::Stale
+        } else if report.ok {
+            SelfTestStatus::Passed
+        } else {
+            SelfTestStatus::Failed
+        }
+    }
+
+    fn record(&self, outcome: Result<()>, elapsed: Duration) {
+        let report = SelfTestReport {
+            ok: outcome.is_ok(),
+            checked_at: Utc::now().timestamp(),
+            duration_ms: elapsed.as_millis() as u64,
+            error: outcome.err().map(|e| e.to_string()),
+        };
+        *self.last.write().unwrap_or_else(|e| e.into_inner()) = Some(report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        assert!(SelfTest::run_once().is_ok());
+    }
+
+    #[test]
+    fn test_self_test_status() {
+        let self_test = SelfTest::new(10);
+        assert_eq!(self_test.status(0), SelfTestStatus::Starting);
+
+        self_test.record(Ok(()), Duration::from_millis(5));
+        let checked_at = self_test.report().unwrap().checked_at;
+        assert_eq!(self_test.status(checked_at), SelfTestStatus::Passed);
+        assert_eq!(self_test.status(checked_at + 31), SelfTestStatus::Stale);
+
+        self_test.record(
+            Err(BackendError::Internal("broken".to_string())),
+            Duration::from_millis(5),
+        );
+        assert_eq!(self_test.status(checked_at), SelfTestStatus::Failed);
+
+        assert_eq!(SelfTest::new(0).status(0), SelfTestStatus::Disabled);
+    }
+}
@@ -0,0 +1,896 @@
+//! Transaction Builder Service
+//!
+//! Assembles the unsigned transactions for SVS-2 flows that need ZK proof
+//! context state accounts.
+
+use crate::error::{BackendError, Result};
+use crate::types::{ContextProofType, EstimateOperation};
+use bytemuck::Pod;
+use solana_sdk::{
+    hash::{hashv, Hash},
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    instruction::{close_context_state, ContextStateInfo, ProofInstruction},
+    proof_data::{
+        BatchedGroupedCiphertext3HandlesValidityProofContext,
+        BatchedGroupedCiphertext3HandlesValidityProofData, BatchedRangeProofContext,
+        BatchedRangeProofU128Data, BatchedRangeProofU256Data, BatchedRangeProofU64Data,
+        CiphertextCommitmentEqualityProofContext, CiphertextCommitmentEqualityProofData,
+        PubkeyValidityProofContext, PubkeyValidityProofData, ZeroCiphertextProofData, ZkProofData,
+    },
+    state::ProofContextState,
+};
+use svs_proof_core::{TransferProofs, WithdrawProofs};
+
+/// Token-2022 program ID
+pub(crate) const TOKEN_2022_PROGRAM_ID: Pubkey =
+    pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// `TokenInstruction::ConfidentialTransferExtension`
+const CONFIDENTIAL_TRANSFER_EXTENSION: u8 = 27;
+
+/// `ConfidentialTransferInstruction::Transfer`
+const CONFIDENTIAL_TRANSFER: u8 = 7;
+
+// Approximate compute units, for cost estimates. Proof verification costs are
+// the fixed amounts charged by the ZK ElGamal proof program; program
+// instructions are rough upper bounds.
+const CREATE_ACCOUNT_COMPUTE_UNITS: u64 = 150;
+const CLOSE_CONTEXT_STATE_COMPUTE_UNITS: u64 = 3_300;
+const VERIFY_PUBKEY_VALIDITY_COMPUTE_UNITS: u64 = 2_600;
+const VERIFY_EQUALITY_COMPUTE_UNITS: u64 = 6_400;
+const VERIFY_CIPHERTEXT_VALIDITY_COMPUTE_UNITS: u64 = 16_400;
+const VERIFY_RANGE_U64_COMPUTE_UNITS: u64 = 111_000;
+const VERIFY_RANGE_U128_COMPUTE_UNITS: u64 = 200_000;
+const CONFIGURE_ACCOUNT_COMPUTE_UNITS: u64 = 40_000;
+const VAULT_WITHDRAW_COMPUTE_UNITS: u64 = 80_000;
+const CONFIDENTIAL_TRANSFER_COMPUTE_UNITS: u64 = 40_000;
+
+/// Transaction builder service
+pub struct TxBuilder;
+
+/// Accounts of an SVS-2 withdraw or redeem instruction
+pub struct WithdrawAccounts {
+    pub program_id: Pubkey,
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub asset_mint: Pubkey,
+    pub user_asset_account: Pubkey,
+    pub asset_vault: Pubkey,
+    pub shares_mint: Pubkey,
+    pub user_shares_account: Pubkey,
+    pub asset_token_program: Pubkey,
+}
+
+/// Vault instruction that burns the proven shares
+pub enum VaultWithdrawal {
+    /// `withdraw(assets, max_shares_in, new_decryptable_available_balance, memo_hash)`
+    Withdraw {
+        assets: u64,
+        max_shares_in: u64,
+        memo_hash: Option<[u8; 32]>,
+    },
+    /// `redeem(shares, min_assets_out, new_decryptable_available_balance)`
+    Redeem { shares: u64, min_assets_out: u64 },
+}
+
+/// Unsigned transactions for a confidential withdrawal, in submission order
+pub struct WithdrawTransactions {
+    /// 1. Create both context accounts and verify the equality proof
+    /// 2. Verify the range proof
+    /// 3. Vault withdraw/redeem, then close both context accounts
+    pub transactions: Vec<Transaction>,
+    pub equality_proof_context: Pubkey,
+    pub range_proof_context: Pubkey,
+}
+
+/// Accounts of a Token-2022 confidential transfer
+pub struct TransferAccounts {
+    /// Source token account owner; pays fees and owns the context accounts
+    pub authority: Pubkey,
+    pub source_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub destination_token_account: Pubkey,
+}
+
+/// Unsigned transactions for a confidential transfer, in submission order
+pub struct TransferTransactions {
+    /// 1. Create all three context accounts and verify the equality proof
+    /// 2. Verify the ciphertext validity proof
+    /// 3. Verify the range proof
+    /// 4. Token-2022 confidential transfer, then close the context accounts
+    pub transactions: Vec<Transaction>,
+    pub equality_proof_context: Pubkey,
+    pub ciphertext_validity_proof_context: Pubkey,
+    pub range_proof_context: Pubkey,
+}
+
+/// A proof an operation verifies, estimated without generating it
+pub struct ProofEstimate {
+    pub proof_type: ContextProofType,
+    pub proof_bytes: usize,
+    /// Size of its context state account; `None` when verified in the same
+    /// transaction as the instruction using it
+    pub context_account_bytes: Option<usize>,
+    pub compute_units: u64,
+}
+
+/// A transaction of an operation, estimated without building it
+pub struct TransactionEstimate {
+    pub signatures: usize,
+    pub compute_units: u64,
+}
+
+/// Proofs and transactions of one operation, in submission order
+pub struct OperationEstimate {
+    pub proofs: Vec<ProofEstimate>,
+    pub transactions: Vec<TransactionEstimate>,
+}
+
+/// Instructions to verify one proof into a context state account
+pub struct ContextStateBundle {
+    /// Context state account, derived with `create_with_seed(payer, seed, zk_program)`
+    pub context_state_account: Pubkey,
+    pub seed: String,
+    pub space: u64,
+    pub lamports: u64,
+    /// `SystemProgram::create_account_with_seed` (payer is base and funder)
+    pub create_instruction: Instruction,
+    /// ZK ElGamal proof program `VerifyProof` writing into the context account
+    pub verify_instruction: Instruction,
+    /// `CloseContextState` returning rent to the payer (signed by the authority)
+    pub close_instruction: Instruction,
+}
+
+impl TxBuilder {
+    /// Build the create/verify/close instructions for a context state account
+    ///
+    /// The account address is derived from the payer and `seed`, so no extra
+    /// keypair has to sign the create instruction.
+    pub fn context_state_instructions(
+        payer: &Pubkey,
+        context_state_authority: &Pubkey,
+        seed: &str,
+        proof_type: ContextProofType,
+        proof_data: &[u8],
+    ) -> Result<ContextStateBundle> {
+        let program_id = zk_elgamal_proof_program::id();
+        let context_state_account = Pubkey::create_with_seed(payer, seed, &program_id)
+            .map_err(|e| BackendError::BadRequest(format!("Invalid seed: {e}")))?;
+        let info = ContextStateInfo {
+            context_state_account: &context_state_account,
+            context_state_authority,
+        };
+
+        let (verify_instruction, space) = match proof_type {
+            ContextProofType::PubkeyValidity => {
+                Self::verify_proof_instruction::<PubkeyValidityProofData, _>(
+                    ProofInstruction::VerifyPubkeyValidity,
+                    info,
+                    proof_data,
+                )?
+            }
+            ContextProofType::ZeroCiphertext => {
+                Self::verify_proof_instruction::<ZeroCiphertextProofData, _>(
+                    ProofInstruction::VerifyZeroCiphertext,
+                    info,
+                    proof_data,
+                )?
+            }
+            ContextProofType::Equality => {
+                Self::verify_proof_instruction::<CiphertextCommitmentEqualityProofData, _>(
+                    ProofInstruction::VerifyCiphertextCommitmentEquality,
+                    info,
+                    proof_data,
+                )?
+            }
+            ContextProofType::RangeU64 => {
+                Self::verify_proof_instruction::<BatchedRangeProofU64Data, _>(
+                    ProofInstruction::VerifyBatchedRangeProofU64,
+                    info,
+                    proof_data,
+                )?
+            }
+            ContextProofType::RangeU128 => {
+                Self::verify_proof_instruction::<BatchedRangeProofU128Data, _>(
+                    ProofInstruction::VerifyBatchedRangeProofU128,
+                    info,
+                    proof_data,
+                )?
+            }
+            ContextProofType::RangeU256 => {
+                Self::verify_proof_instruction::<BatchedRangeProofU256Data, _>(
+                    ProofInstruction::VerifyBatchedRangeProofU256,
+                    info,
+                    proof_data,
+                )?
+            }
+            ContextProofType::CiphertextValidity => Self::verify_proof_instruction::<
+                BatchedGroupedCiphertext3HandlesValidityProofData,
+                _,
+            >(
+                ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+                info,
+                proof_data,
+            )?,
+        };
+
+        let lamports = Rent::default().minimum_balance(space);
+        let create_instruction = system_instruction::create_account_with_seed(
+            payer,
+            &context_state_account,
+            payer,
+            seed,
+            lamports,
+            space as u64,
+            &program_id,
+        );
+
+        Ok(ContextStateBundle {
+            context_state_account,
+            seed: seed.to_string(),
+            space: space as u64,
+            lamports,
+            create_instruction,
+            verify_instruction,
+            close_instruction: close_context_state(info, payer),
+        })
+    }
+
+    /// Encode a `VerifyProof` instruction from raw proof bytes, returning it
+    /// with the size of the context state account it writes
+    fn verify_proof_instruction<T, U>(
+        instruction: ProofInstruction,
+        info: ContextStateInfo,
+        proof_data: &[u8],
+    ) -> Result<(Instruction, usize)>
+    where
+        T: Pod + ZkProofData<U>,
+        U: Pod,
+    {
+        let proof = bytemuck::try_from_bytes::<T>(proof_data).map_err(|_| {
+            BackendError::BadRequest(format!(
+                "Invalid proof data length: expected {} bytes, got {}",
+                std::mem::size_of::<T>(),
+                proof_data.len()
+            ))
+        })?;
+
+        Ok((
+            instruction.encode_verify_proof(Some(info), proof),
+            std::mem::size_of::<ProofContextState<U>>(),
+        ))
+    }
+
+    /// Estimate the proofs and transactions of an operation
+    ///
+    /// Follows the layouts of the transaction builders: configure verifies its
+    /// proof inline, withdraw and transfer use one context account per proof.
+    pub fn estimate(operation: EstimateOperation) -> OperationEstimate {
+        match operation {
+            EstimateOperation::Configure => OperationEstimate {
+                proofs: vec![Self::proof_estimate::<
+                    PubkeyValidityProofData,
+                    PubkeyValidityProofContext,
+                >(
+                    ContextProofType::PubkeyValidity,
+                    false,
+                    VERIFY_PUBKEY_VALIDITY_COMPUTE_UNITS,
+                )],
+                transactions: vec![TransactionEstimate {
+                    signatures: 1,
+                    compute_units: VERIFY_PUBKEY_VALIDITY_COMPUTE_UNITS
+                        + CONFIGURE_ACCOUNT_COMPUTE_UNITS,
+                }],
+            },
+            EstimateOperation::Withdraw => {
+                let proofs = vec![
+                    Self::proof_estimate::<
+                        CiphertextCommitmentEqualityProofData,
+                        CiphertextCommitmentEqualityProofContext,
+                    >(
+                        ContextProofType::Equality,
+                        true,
+                        VERIFY_EQUALITY_COMPUTE_UNITS,
+                    ),
+                    Self::proof_estimate::<BatchedRangeProofU64Data, BatchedRangeProofContext>(
+                        ContextProofType::RangeU64,
+                        true,
+                        VERIFY_RANGE_U64_COMPUTE_UNITS,
+                    ),
+                ];
+                Self::context_account_transactions(proofs, VAULT_WITHDRAW_COMPUTE_UNITS)
+            }
+            EstimateOperation::Transfer => {
+                let proofs = vec![
+                    Self::proof_estimate::<
+                        CiphertextCommitmentEqualityProofData,
+                        CiphertextCommitmentEqualityProofContext,
+                    >(
+                        ContextProofType::Equality,
+                        true,
+                        VERIFY_EQUALITY_COMPUTE_UNITS,
+                    ),
+                    Self::proof_estimate::<
+                        BatchedGroupedCiphertext3HandlesValidityProofData,
+                        BatchedGroupedCiphertext3HandlesValidityProofContext,
+                    >(
+                        ContextProofType::CiphertextValidity,
+                        true,
+                        VERIFY_CIPHERTEXT_VALIDITY_COMPUTE_UNITS,
+                    ),
+                    Self::proof_estimate::<BatchedRangeProofU128Data, BatchedRangeProofContext>(
+                        ContextProofType::RangeU128,
+                        true,
+                        VERIFY_RANGE_U128_COMPUTE_UNITS,
+                    ),
+                ];
+                Self::context_account_transactions(proofs, CONFIDENTIAL_TRANSFER_COMPUTE_UNITS)
+            }
+        }
+    }
+
+    fn proof_estimate<T: ZkProofData<U>, U: Pod>(
+        proof_type: ContextProofType,
+        in_context_account: bool,
+        compute_units: u64,
+    ) -> ProofEstimate {
+        ProofEstimate {
+            proof_type,
+            proof_bytes: std::mem::size_of::<T>(),
+            context_account_bytes: in_context_account
+                .then(std::mem::size_of::<ProofContextState<U>>),
+            compute_units,
+        }
+    }
+
+    /// Transactions of a context account flow: create every account and
+    /// verify the first proof, verify each other proof, then execute and close
+    fn context_account_transactions(
+        proofs: Vec<ProofEstimate>,
+        execute_compute_units: u64,
+    ) -> OperationEstimate {
+        let accounts = proofs.len();
+        let mut transactions = vec![TransactionEstimate {
+            // The payer and every context account keypair
+            signatures: 1 + accounts,
+            compute_units: CREATE_ACCOUNT_COMPUTE_UNITS * accounts as u64 + proofs[0].compute_units,
+        }];
+        transactions.extend(proofs[1..].iter().map(|proof| TransactionEstimate {
+            signatures: 1,
+            compute_units: proof.compute_units,
+        }));
+        transactions.push(TransactionEstimate {
+            signatures: 1,
+            compute_units: execute_compute_units
+                + CLOSE_CONTEXT_STATE_COMPUTE_UNITS * accounts as u64,
+        });
+
+        OperationEstimate {
+            proofs,
+            transactions,
+        }
+    }
+
+    /// Build the withdraw/redeem transaction set
+    ///
+    /// Context accounts are fresh keypairs owned by the ZK ElGamal proof program
+    /// with the user as context authority. The first transaction is partially
+    /// signed with those keypairs, so only the user's signature is missing.
+    ///
+    /// `fee_payer` pays the fees and the context accounts' rent, which is
+    /// returned to it when they are closed; the user, or a relayer.
+    pub fn build_withdraw_transactions(
+        accounts: &WithdrawAccounts,
+        withdrawal: &VaultWithdrawal,
+        proofs: &WithdrawProofs,
+        fee_payer: &Pubkey,
+        recent_blockhash: Hash,
+    ) -> Result<WithdrawTransactions> {
+        let user = &accounts.user;
+        let equality_context = Keypair::new();
+        let range_context = Keypair::new();
+        let equality_info = ContextStateInfo {
+            context_state_account: &equality_context.pubkey(),
+            context_state_authority: user,
+        };
+        let range_info = ContextStateInfo {
+            context_state_account: &range_context.pubkey(),
+            context_state_authority: user,
+        };
+
+        let mut setup = Transaction::new_with_payer(
+            &[
+                Self::create_context_account(
+                    fee_payer,
+                    &equality_context.pubkey(),
+                    std::mem::size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(
+                    ),
+                ),
+                Self::create_context_account(
+                    fee_payer,
+                    &range_context.pubkey(),
+                    std::mem::size_of::<ProofContextState<BatchedRangeProofContext>>(),
+                ),
+                ProofInstruction::VerifyCiphertextCommitmentEquality
+                    .encode_verify_proof(Some(equality_info), &proofs.equality_proof_data),
+            ],
+            Some(fee_payer),
+        );
+        setup
+            .try_partial_sign(&[&equality_context, &range_context], recent_blockhash)
+            .map_err(|e| {
+                BackendError::Internal(format!("Failed to sign setup transaction: {e}"))
+            })?;
+
+        let mut verify_range = Transaction::new_with_payer(
+            &[ProofInstruction::VerifyBatchedRangeProofU64
+                .encode_verify_proof(Some(range_info), &proofs.range_proof_data)],
+            Some(fee_payer),
+        );
+        verify_range.message.recent_blockhash = recent_blockhash;
+
+        let mut execute = Transaction::new_with_payer(
+            &[
+                Self::vault_withdraw_instruction(
+                    accounts,
+                    withdrawal,
+                    &equality_context.pubkey(),
+                    &range_context.pubkey(),
+                    &proofs.new_decryptable_available_balance,
+                ),
+                close_context_state(equality_info, fee_payer),
+                close_context_state(range_info, fee_payer),
+            ],
+            Some(fee_payer),
+        );
+        execute.message.recent_blockhash = recent_blockhash;
+
+        Ok(WithdrawTransactions {
+            transactions: vec![setup, verify_range, execute],
+            equality_proof_context: equality_context.pubkey(),
+            range_proof_context: range_context.pubkey(),
+        })
+    }
+
+    /// Build the confidential transfer transaction set
+    ///
+    /// Same layout as the withdraw set: fresh context account keypairs with the
+    /// source owner as authority, partially signed into the first transaction.
+    pub fn build_transfer_transactions(
+        accounts: &TransferAccounts,
+        proofs: &TransferProofs,
+        recent_blockhash: Hash,
+    ) -> Result<TransferTransactions> {
+        let user = &accounts.authority;
+        let equality_context = Keypair::new();
+        let validity_context = Keypair::new();
+        let range_context = Keypair::new();
+        let equality_info = ContextStateInfo {
+            context_state_account: &equality_context.pubkey(),
+            context_state_authority: user,
+        };
+        let validity_info = ContextStateInfo {
+            context_state_account: &validity_context.pubkey(),
+            context_state_authority: user,
+        };
+        let range_info = ContextStateInfo {
+            context_state_account: &range_context.pubkey(),
+            context_state_authority: user,
+        };
+
+        let (verify_equality, equality_space) =
+            Self::verify_proof_instruction::<CiphertextCommitmentEqualityProofData, _>(
+                ProofInstruction::VerifyCiphertextCommitmentEquality,
+                equality_info,
+                &proofs.equality_proof,
+            )?;
+        let (verify_validity, validity_space) =
+            Self::verify_proof_instruction::<BatchedGroupedCiphertext3HandlesValidityProofData, _>(
+                ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+                validity_info,
+                &proofs.ciphertext_validity_proof,
+            )?;
+        let (verify_range, range_space) =
+            Self::verify_proof_instruction::<BatchedRangeProofU128Data, _>(
+                ProofInstruction::VerifyBatchedRangeProofU128,
+                range_info,
+                &proofs.range_proof,
+            )?;
+
+        let mut setup = Transaction::new_with_payer(
+            &[
+                Self::create_context_account(user, &equality_context.pubkey(), equality_space),
+                Self::create_context_account(user, &validity_context.pubkey(), validity_space),
+                Self::create_context_account(user, &range_context.pubkey(), range_space),
+                verify_equality,
+            ],
+            Some(user),
+        );
+        setup
+            .try_partial_sign(
+                &[&equality_context, &validity_context, &range_context],
+                recent_blockhash,
+            )
+            .map_err(|e| {
+                BackendError::Internal(format!("Failed to sign setup transaction: {e}"))
+            })?;
+
+        let mut verify_validity = Transaction::new_with_payer(&[verify_validity], Some(user));
+        verify_validity.message.recent_blockhash = recent_blockhash;
+
+        let mut verify_range = Transaction::new_with_payer(&[verify_range], Some(user));
+        verify_range.message.recent_blockhash = recent_blockhash;
+
+        let mut execute = Transaction::new_with_payer(
+            &[
+                Self::confidential_transfer_instruction(
+                    accounts,
+                    &equality_context.pubkey(),
+                    &validity_context.pubkey(),
+                    &range_context.pubkey(),
+                    proofs,
+                ),
+                close_context_state(equality_info, user),
+                close_context_state(validity_info, user),
+                close_context_state(range_info, user),
+            ],
+            Some(user),
+        );
+        execute.message.recent_blockhash = recent_blockhash;
+
+        Ok(TransferTransactions {
+            transactions: vec![setup, verify_validity, verify_range, execute],
+            equality_proof_context: equality_context.pubkey(),
+            ciphertext_validity_proof_context: validity_context.pubkey(),
+            range_proof_context: range_context.pubkey(),
+        })
+    }
+
+    /// Build the Token-2022 confidential `Transfer` instruction reading all
+    /// three proofs from context state accounts
+    fn confidential_transfer_instruction(
+        accounts: &TransferAccounts,
+        equality_proof_context: &Pubkey,
+        ciphertext_validity_proof_context: &Pubkey,
+        range_proof_context: &Pubkey,
+        proofs: &TransferProofs,
+    ) -> Instruction {
+        // TransferInstructionData
+        let mut data = vec![CONFIDENTIAL_TRANSFER_EXTENSION, CONFIDENTIAL_TRANSFER];
+        data.extend_from_slice(&proofs.new_decryptable_available_balance);
+        data.extend_from_slice(&proofs.auditor_ciphertext_lo);
+        data.extend_from_slice(&proofs.auditor_ciphertext_hi);
+        // Proof instruction offsets; zero reads each proof from its context account
+        data.extend_from_slice(&[0, 0, 0]);
+
+        Instruction {
+            program_id: TOKEN_2022_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(accounts.source_token_account, false),
+                AccountMeta::new_readonly(accounts.mint, false),
+                AccountMeta::new(accounts.destination_token_account, false),
+                AccountMeta::new_readonly(*equality_proof_context, false),
+                AccountMeta::new_readonly(*ciphertext_validity_proof_context, false),
+                AccountMeta::new_readonly(*range_proof_context, false),
+                AccountMeta::new_readonly(accounts.authority, true),
+            ],
+            data,
+        }
+    }
+
+    /// Allocate a rent-exempt context state account owned by the proof program
+    fn create_context_account(payer: &Pubkey, account: &Pubkey, space: usize) -> Instruction {
+        system_instruction::create_account(
+            payer,
+            account,
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &zk_elgamal_proof_program::id(),
+        )
+    }
+
+    /// Build the SVS-2 `withdraw` or `redeem` instruction
+    fn vault_withdraw_instruction(
+        accounts: &WithdrawAccounts,
+        withdrawal: &VaultWithdrawal,
+        equality_proof_context: &Pubkey,
+        range_proof_context: &Pubkey,
+        new_decryptable_available_balance: &[u8; 36],
+    ) -> Instruction {
+        let data = match withdrawal {
+            VaultWithdrawal::Withdraw {
+                assets,
+                max_shares_in,
+                memo_hash,
+            } => {
+                let mut data = Self::anchor_discriminator("withdraw").to_vec();
+                data.extend_from_slice(&assets.to_le_bytes());
+                data.extend_from_slice(&max_shares_in.to_le_bytes());
+                data.extend_from_slice(new_decryptable_available_balance);
+                match memo_hash {
+                    Some(hash) => {
+                        data.push(1);
+                        data.extend_from_slice(hash);
+                    }
+                    None => data.push(0),
+                }
+                data
+            }
+            VaultWithdrawal::Redeem {
+                shares,
+                min_assets_out,
+            } => {
+                let mut data = Self::anchor_discriminator("redeem").to_vec();
+                data.extend_from_slice(&shares.to_le_bytes());
+                data.extend_from_slice(&min_assets_out.to_le_bytes());
+                data.extend_from_slice(new_decryptable_available_balance);
+                data
+            }
+        };
+
+        // Account order matches the Withdraw/Redeem account structs in svs-2
+        Instruction {
+            program_id: accounts.program_id,
+            accounts: vec![
+                AccountMeta::new(accounts.user, true),
+                AccountMeta::new(accounts.vault, false),
+                AccountMeta::new_readonly(accounts.asset_mint, false),
+                AccountMeta::new(accounts.user_asset_account, false),
+                AccountMeta::new(accounts.asset_vault, false),
+                AccountMeta::new(accounts.shares_mint, false),
+                AccountMeta::new(accounts.user_shares_account, false),
+                AccountMeta::new_readonly(*equality_proof_context, false),
+                AccountMeta::new_readonly(*range_proof_context, false),
+                AccountMeta::new_readonly(accounts.asset_token_program, false),
+                AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    /// Anchor instruction discriminator: sha256("global:<name>")[..8]
+    fn anchor_discriminator(name: &str) -> [u8; 8] {
+        let hash = hashv(&[b"global:", name.as_bytes()]);
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+        discriminator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{relayer::relay_cost, ProofGenerator};
+    use solana_sdk::packet::PACKET_DATA_SIZE;
+    use solana_zk_sdk::encryption::{auth_encryption::AeKey, elgamal::ElGamalKeypair};
+
+    fn test_accounts() -> WithdrawAccounts {
+        WithdrawAccounts {
+            program_id: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            asset_mint: Pubkey::new_unique(),
+            user_asset_account: Pubkey::new_unique(),
+            asset_vault: Pubkey::new_unique(),
+            shares_mint: Pubkey::new_unique(),
+            user_shares_account: Pubkey::new_unique(),
+            asset_token_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn test_proofs() -> WithdrawProofs {
+        let keypair = ElGamalKeypair::new_rand();
+        let ae_key = AeKey::new_rand();
+        let available = keypair.pubkey().encrypt(1_000u64);
+        let decryptable = ae_key.encrypt(1_000u64);
+
+        ProofGenerator::generate_withdraw_proof(&keypair, &ae_key, &available, &decryptable, 400)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_context_state_instructions() {
+        let payer = Pubkey::new_unique();
+        let proofs = test_proofs();
+
+        let bundle = TxBuilder::context_state_instructions(
+            &payer,
+            &payer,
+            "svs-equality",
+            ContextProofType::Equality,
+            bytemuck::bytes_of(&proofs.equality_proof_data),
+        )
+        .unwrap();
+
+        assert_eq!(
+            bundle.space as usize,
+            std::mem::size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>()
+        );
+        assert_eq!(
+            bundle.verify_instruction.accounts[0].pubkey,
+            bundle.context_state_account
+        );
+        assert_eq!(
+            bundle.close_instruction.accounts[0].pubkey,
+            bundle.context_state_account
+        );
+    }
+
+    #[test]
+    fn test_context_state_rejects_wrong_length() {
+        let payer = Pubkey::new_unique();
+        let proofs = test_proofs();
+
+        // Equality proof bytes submitted as a range proof
+        let result = TxBuilder::context_state_instructions(
+            &payer,
+            &payer,
+            "svs-range",
+            ContextProofType::RangeU64,
+            bytemuck::bytes_of(&proofs.equality_proof_data),
+        );
+
+        assert!(matches!(result, Err(BackendError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_anchor_discriminator() {
+        // sha256("global:withdraw")[..8], as in the svs-2 IDL
+        assert_eq!(
+            TxBuilder::anchor_discriminator("withdraw"),
+            [183, 18, 70, 156, 148, 109, 161, 34]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_transactions_fit_packet() {
+        let accounts = test_accounts();
+        let withdrawal = VaultWithdrawal::Withdraw {
+            assets: 100,
+            max_shares_in: 400,
+            memo_hash: Some([7u8; 32]),
+        };
+
+        let result = TxBuilder::build_withdraw_transactions(
+            &accounts,
+            &withdrawal,
+            &test_proofs(),
+            &accounts.user,
+            Hash::new_unique(),
+        )
+        .unwrap();
+
+        assert_eq!(result.transactions.len(), 3);
+        for tx in &result.transactions {
+            let size = bincode::serialized_size(tx).unwrap() as usize;
+            assert!(size <= PACKET_DATA_SIZE, "transaction too large: {size}");
+            assert_eq!(tx.message.account_keys[0], accounts.user);
+        }
+
+        // Only the user's signature is missing from the setup transaction
+        let setup = &result.transactions[0];
+        assert_eq!(setup.signatures.len(), 3);
+        assert_eq!(setup.signatures[0], Default::default());
+        assert!(setup.signatures[1..]
+            .iter()
+            .all(|sig| *sig != Default::default()));
+    }
+
+    #[test]
+    fn test_relayed_withdraw_transactions() {
+        let accounts = test_accounts();
+        let relayer = Pubkey::new_unique();
+
+        let result = TxBuilder::build_withdraw_transactions(
+            &accounts,
+            &VaultWithdrawal::Redeem {
+                shares: 400,
+                min_assets_out: 0,
+            },
+            &test_proofs(),
+            &relayer,
+            Hash::new_unique(),
+        )
+        .unwrap();
+
+        // Only the execute transaction needs the user's signature
+        for tx in &result.transactions {
+            assert_eq!(tx.message.account_keys[0], relayer);
+        }
+        let signers = |tx: &Transaction| {
+            tx.message.account_keys[..tx.message.header.num_required_signatures as usize].to_vec()
+        };
+        assert!(!signers(&result.transactions[0]).contains(&accounts.user));
+        assert_eq!(signers(&result.transactions[2]), [relayer, accounts.user]);
+
+        // and the relayer accepts every transaction
+        let allowed = [
+            accounts.program_id,
+            zk_elgamal_proof_program::id(),
+            solana_system_interface::program::ID,
+        ];
+        for tx in &result.transactions {
+            relay_cost(tx, &relayer, &allowed).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_withdraw_estimate_matches_transactions() {
+        let proofs = test_proofs();
+        let accounts = test_accounts();
+        let result = TxBuilder::build_withdraw_transactions(
+            &accounts,
+            &VaultWithdrawal::Redeem {
+                shares: 400,
+                min_assets_out: 0,
+            },
+            &proofs,
+            &accounts.user,
+            Hash::new_unique(),
+        )
+        .unwrap();
+
+        let estimate = TxBuilder::estimate(EstimateOperation::Withdraw);
+        assert_eq!(estimate.transactions.len(), result.transactions.len());
+        for (estimated, tx) in estimate.transactions.iter().zip(&result.transactions) {
+            assert_eq!(estimated.signatures, tx.signatures.len());
+        }
+        assert_eq!(
+            estimate.proofs[0].proof_bytes,
+            std::mem::size_of_val(&proofs.equality_proof_data)
+        );
+        assert_eq!(
+            estimate.proofs[1].proof_bytes,
+            std::mem::size_of_val(&proofs.range_proof_data)
+        );
+    }
+
+    #[test]
+    fn test_transfer_transactions_fit_packet() {
+        let keypair = ElGamalKeypair::new_rand();
+        let destination = ElGamalKeypair::new_rand();
+        let ae_key = AeKey::new_rand();
+        let proofs = ProofGenerator::generate_transfer_proof(
+            &keypair,
+            &ae_key,
+            &keypair.pubkey().encrypt(1_000u64),
+            &ae_key.encrypt(1_000u64),
+            400,
+            destination.pubkey(),
+            None,
+        )
+        .unwrap();
+        let accounts = TransferAccounts {
+            authority: Pubkey::new_unique(),
+            source_token_account: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            destination_token_account: Pubkey::new_unique(),
+        };
+
+        let result =
+            TxBuilder::build_transfer_transactions(&accounts, &proofs, Hash::new_unique()).unwrap();
+
+        assert_eq!(result.transactions.len(), 4);
+        for tx in &result.transactions {
+            let size = bincode::serialized_size(tx).unwrap() as usize;
+            assert!(size <= PACKET_DATA_SIZE, "transaction too large: {size}");
+            assert_eq!(tx.message.account_keys[0], accounts.authority);
+        }
+
+        // 2-byte tag + decryptable balance + two auditor ciphertexts + 3 offsets
+        let execute = &result.transactions[3];
+        assert_eq!(
+            execute.message.instructions[0].data.len(),
+            2 + 36 + 64 + 64 + 3
+        );
+    }
+}
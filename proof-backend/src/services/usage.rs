@@ -0,0 +1,324 @@
+//! Usage Accounting
+//!
+//! Counts billable requests and proof generation time per API key, for the
+//! current UTC day and month, and checks them against the key's quota.
+//! Counters live in memory and restart from zero with the process, unless
+//! they are kept in a shared Redis.
+
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::warn;
+
+use crate::{
+    error::Result,
+    services::RedisStore,
+    types::{QuotaConfig, QuotaRemaining, UsageCounts},
+};
+
+/// Shared daily counters outlive their day by this much
+const DAILY_COUNTER_TTL: Duration = Duration::from_secs(2 * 24 * 3600);
+
+/// Shared monthly counters outlive their month by this much
+const MONTHLY_COUNTER_TTL: Duration = Duration::from_secs(62 * 24 * 3600);
+
+/// Counters for one API key
+#[derive(Debug, Default, Clone)]
+struct KeyUsage {
+    day: String,
+    month: String,
+    daily: UsageCounts,
+    monthly: UsageCounts,
+}
+
+/// UTC day and month containing `now`
+fn periods(now: DateTime<Utc>) -> (String, String) {
+    (
+        now.format("%Y-%m-%d").to_string(),
+        now.format("%Y-%m").to_string(),
+    )
+}
+
+/// Proof time as counted, in milliseconds
+pub fn proof_ms(elapsed: Duration) -> u64 {
+    elapsed.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Name of a shared counter; the API key is hashed so it never leaves the
+/// process
+fn counter_name(key: &str, period: &str, counter: &str) -> String {
+    let key_id = solana_sdk::hash::hash(key.as_bytes());
+    format!("usage:{key_id}:{period}:{counter}")
+}
+
+impl KeyUsage {
+    /// Reset counters whose period has ended
+    fn roll_over(&mut self, now: DateTime<Utc>) {
+        let (day, month) = periods(now);
+        if self.day != day {
+            self.day = day;
+            self.daily = UsageCounts::default();
+        }
+        if self.month != month {
+            self.month = month;
+            self.monthly = UsageCounts::default();
+        }
+    }
+}
+
+/// Point-in-time usage of one API key
+#[derive(Debug, Clone)]
+pub struct UsageSnapshot {
+    pub day: String,
+    pub month: String,
+    pub daily: UsageCounts,
+    pub monthly: UsageCounts,
+}
+
+impl UsageSnapshot {
+    /// Name of the first quota limit the key has used up, if any
+    pub fn exhausted_limit(&self, quota: &QuotaConfig) -> Option<&'static str> {
+        let reached = |limit: Option<u64>, used: u64| limit.is_some_and(|limit| used >= limit);
+
+        if reached(quota.daily_requests, self.daily.requests) {
+            Some("daily_requests")
+        } else if reached(quota.monthly_requests, self.monthly.requests) {
+            Some("monthly_requests")
+        } else if reached(quota.monthly_proof_secs, self.monthly.proof_ms / 1000) {
+            Some("monthly_proof_secs")
+        } else {
+            None
+        }
+    }
+
+    /// Add the cost of a request that is not counted yet
+    pub fn charge(&mut self, cost: &UsageCounts) {
+        for counts in [&mut self.daily, &mut self.monthly] {
+            counts.requests += cost.requests;
+            counts.proof_ms += cost.proof_ms;
+        }
+    }
+
+    /// What is left of each configured quota limit
+    pub fn remaining(&self, quota: &QuotaConfig) -> QuotaRemaining {
+        let left = |limit: Option<u64>, used: u64| limit.map(|limit| limit.saturating_sub(used));
+
+        QuotaRemaining {
+            daily_requests: left(quota.daily_requests, self.daily.requests),
+            monthly_requests: left(quota.monthly_requests, self.monthly.requests),
+            monthly_proof_secs: left(quota.monthly_proof_secs, self.monthly.proof_ms / 1000),
+        }
+    }
+}
+
+/// Per-key usage counters, keyed by the API key value
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    keys: Arc<Mutex<HashMap<String, KeyUsage>>>,
+    /// Counters shared by every replica; in-process when unset
+    shared: Option<RedisStore>,
+}
+
+impl UsageTracker {
+    pub fn new(shared: Option<RedisStore>) -> Self {
+        Self {
+            keys: Arc::default(),
+            shared,
+        }
+    }
+
+    /// Count one billable request
+    ///
+    /// Shared counters are updated in the background, so callers never wait
+    /// on Redis.
+    pub fn record_request(&self, key: &str) {
+        if self.shared.is_some() {
+            return self.add_shared(key, "requests", 1);
+        }
+        self.update(key, Utc::now(), |usage| {
+            usage.daily.requests += 1;
+            usage.monthly.requests += 1;
+        });
+    }
+
+    /// Add time spent generating proofs for a request
+    pub fn record_proof_time(&self, key: &str, elapsed: Duration) {
+        let ms = proof_ms(elapsed);
+        if self.shared.is_some() {
+            return self.add_shared(key, "proof_ms", ms);
+        }
+        self.update(key, Utc::now(), |usage| {
+            usage.daily.proof_ms += ms;
+            usage.monthly.proof_ms += ms;
+        });
+    }
+
+    /// Current counters for a key
+    pub async fn snapshot(&self, key: &str) -> Result<UsageSnapshot> {
+        let Some(shared) = &self.shared else {
+            let usage = self.update(key, Utc::now(), |_| {});
+            return Ok(UsageSnapshot {
+                day: usage.day,
+                month: usage.month,
+                daily: usage.daily,
+                monthly: usage.monthly,
+            });
+        };
+
+        let (day, month) = periods(Utc::now());
+        let names: Vec<_> = [&day, &month]
+            .into_iter()
+            .flat_map(|period| {
+                ["requests", "proof_ms"].map(|counter| counter_name(key, period, counter))
+            })
+            .collect();
+        let values = shared.counters(&names).await?;
+
+        Ok(UsageSnapshot {
+            day,
+            month,
+            daily: UsageCounts {
+                requests: values[0],
+                proof_ms: values[1],
+            },
+            monthly: UsageCounts {
+                requests: values[2],
+                proof_ms: values[3],
+            },
+        })
+    }
+
+    /// Add to a key's shared daily and monthly `counter`
+    fn add_shared(&self, key: &str, counter: &'static str, by: u64) {
+        let Some(shared) = self.shared.clone() else {
+            return;
+        };
+        let (day, month) = periods(Utc::now());
+        let daily = counter_name(key, &day, counter);
+        let monthly = counter_name(key, &month, counter);
+
+        tokio::spawn(async move {
+            let result = async {
+                shared.increment(&daily, by, DAILY_COUNTER_TTL).await?;
+                shared.increment(&monthly, by, MONTHLY_COUNTER_TTL).await
+            };
+            if let Err(e) = result.await {
+                warn!(counter, error = %e, "Failed to record usage");
+            }
+        });
+    }
+
+    fn update(&self, key: &str, now: DateTime<Utc>, f: impl FnOnce(&mut KeyUsage)) -> KeyUsage {
+        // A poisoned lock only means another request panicked mid-update
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        let usage = keys.entry(key.to_string()).or_default();
+        usage.roll_over(now);
+        f(usage);
+        usage.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::QuotaAction;
+    use chrono::TimeZone;
+
+    async fn exhausted(
+        tracker: &UsageTracker,
+        key: &str,
+        quota: &QuotaConfig,
+    ) -> Option<&'static str> {
+        tracker.snapshot(key).await.unwrap().exhausted_limit(quota)
+    }
+
+    fn quota(daily_requests: Option<u64>, monthly_proof_secs: Option<u64>) -> QuotaConfig {
+        QuotaConfig {
+            daily_requests,
+            monthly_requests: None,
+            monthly_proof_secs,
+            on_exhausted: QuotaAction::Reject,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daily_quota() {
+        let tracker = UsageTracker::default();
+        let quota = quota(Some(2), None);
+
+        tracker.record_request("k");
+        assert_eq!(exhausted(&tracker, "k", &quota).await, None);
+        tracker.record_request("k");
+        assert_eq!(
+            exhausted(&tracker, "k", &quota).await,
+            Some("daily_requests")
+        );
+
+        // Other keys are counted separately
+        assert_eq!(exhausted(&tracker, "other", &quota).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_proof_time_quota() {
+        let tracker = UsageTracker::default();
+
+        tracker.record_proof_time("k", Duration::from_millis(1500));
+
+        assert_eq!(tracker.snapshot("k").await.unwrap().monthly.proof_ms, 1500);
+        assert_eq!(exhausted(&tracker, "k", &quota(None, Some(2))).await, None);
+        assert_eq!(
+            exhausted(&tracker, "k", &quota(None, Some(1))).await,
+            Some("monthly_proof_secs")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remaining_quota() {
+        let tracker = UsageTracker::default();
+        tracker.record_request("k");
+        let mut usage = tracker.snapshot("k").await.unwrap();
+
+        usage.charge(&UsageCounts {
+            requests: 1,
+            proof_ms: 2500,
+        });
+
+        assert_eq!(
+            usage.remaining(&quota(Some(10), Some(60))),
+            QuotaRemaining {
+                daily_requests: Some(8),
+                monthly_requests: None,
+                monthly_proof_secs: Some(58),
+            }
+        );
+        assert_eq!(
+            usage.remaining(&quota(Some(1), None)).daily_requests,
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_day_roll_over_keeps_month() {
+        let mut usage = KeyUsage::default();
+        usage.roll_over(Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap());
+        usage.daily.requests = 5;
+        usage.monthly.requests = 5;
+
+        usage.roll_over(Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 1).unwrap());
+
+        assert_eq!(usage.daily.requests, 0);
+        assert_eq!(usage.monthly.requests, 5);
+    }
+
+    #[test]
+    fn test_shared_counter_names_hide_key() {
+        let name = counter_name("secret-key", "2026-03", "requests");
+
+        assert!(name.starts_with("usage:") && name.ends_with(":2026-03:requests"));
+        assert!(!name.contains("secret-key"));
+        assert_ne!(name, counter_name("other-key", "2026-03", "requests"));
+    }
+}
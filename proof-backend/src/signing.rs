@@ -0,0 +1,154 @@
+//! Signed responses
+//!
+//! Every successful API response carries an ed25519 signature by the service
+//! key over the request and response bodies, so clients and relayers can check
+//! that a proof came from this backend unmodified:
+//!
+//! `sign("SVS_PROOF_RESPONSE" || sha256(request_body) || sha256(response_body))`
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::{
+    hash::hash,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Response header carrying the base58 service signature
+pub const SIGNATURE_HEADER: &str = "x-svs-signature";
+
+/// Response header carrying the base64 sha256 of the request body that was signed
+pub const REQUEST_HASH_HEADER: &str = "x-svs-request-hash";
+
+/// Domain separator for response signatures
+const RESPONSE_MESSAGE_PREFIX: &[u8] = b"SVS_PROOF_RESPONSE";
+
+/// Signs API responses with the service key
+#[derive(Clone)]
+pub struct ResponseSigner {
+    keypair: Arc<Keypair>,
+}
+
+impl ResponseSigner {
+    /// Load the service keypair (Solana CLI JSON format), or generate an
+    /// ephemeral one when no path is configured
+    pub fn load(path: Option<&str>) -> anyhow::Result<Self> {
+        let keypair = match path {
+            Some(path) => read_keypair_file(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read service keypair {path}: {e}"))?,
+            None => {
+                warn!("No SERVICE_KEYPAIR_PATH set - signing responses with an ephemeral key");
+                Keypair::new()
+            }
+        };
+
+        Ok(Self {
+            keypair: Arc::new(keypair),
+        })
+    }
+
+    /// Public key clients verify response signatures against
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// Sign a request/response body pair
+    pub fn sign(&self, request_body: &[u8], response_body: &[u8]) -> Signature {
+        self.keypair
+            .sign_message(&response_message(request_body, response_body))
+    }
+}
+
+/// Message signed for a request/response body pair
+pub fn response_message(request_body: &[u8], response_body: &[u8]) -> Vec<u8> {
+    let mut message = RESPONSE_MESSAGE_PREFIX.to_vec();
+    message.extend_from_slice(hash(request_body).as_ref());
+    message.extend_from_slice(hash(response_body).as_ref());
+    message
+}
+
+/// Sign successful responses
+///
+/// Buffers both bodies; event streams are passed through unsigned.
+pub async fn sign_response_middleware(
+    State(signer): State<ResponseSigner>,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let (parts, body) = request.into_parts();
+    // The body size limit layer runs first, so this is bounded
+    let request_body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(request_body.clone())))
+        .await;
+
+    let is_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"text/event-stream"));
+    if !response.status().is_success() || is_stream {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let response_body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let signature = signer.sign(&request_body, &response_body);
+    let request_hash = hash(&request_body);
+    parts.headers.insert(
+        SIGNATURE_HEADER,
+        HeaderValue::from_str(&signature.to_string()).expect("base58 is a valid header value"),
+    );
+    parts.headers.insert(
+        REQUEST_HASH_HEADER,
+        HeaderValue::from_str(&STANDARD.encode(request_hash))
+            .expect("base64 is a valid header value"),
+    );
+
+    Ok(Response::from_parts(parts, Body::from(response_body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_signature_verifies() {
+        let signer = ResponseSigner::load(None).unwrap();
+        let request = br#"{"timestamp":1706500000}"#;
+        let response = br#"{"proof_data":"abc"}"#;
+
+        let signature = signer.sign(request, response);
+
+        assert!(signature.verify(
+            signer.pubkey().as_ref(),
+            &response_message(request, response)
+        ));
+        // A modified response no longer verifies
+        assert!(!signature.verify(
+            signer.pubkey().as_ref(),
+            &response_message(request, br#"{"proof_data":"abd"}"#)
+        ));
+    }
+
+    #[test]
+    fn test_response_message_layout() {
+        let message = response_message(b"a", b"b");
+
+        assert_eq!(message.len(), 18 + 32 + 32); // prefix + two sha256 hashes
+        assert!(message.starts_with(b"SVS_PROOF_RESPONSE"));
+    }
+}
@@ -0,0 +1,118 @@
+//! Tracing setup and OpenTelemetry export
+//!
+//! Logs always go to stdout. When `OTEL_EXPORTER_OTLP_ENDPOINT` is set, spans
+//! are also exported over OTLP/HTTP, and each request span continues the trace
+//! from the caller's W3C `traceparent` header.
+
+use axum::{body::Body, extract::Request, http::HeaderMap};
+use opentelemetry::{global, propagation::Extractor, trace::TracerProvider as _};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing::{info_span, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::types::Config;
+
+/// Service name reported to the trace collector
+const SERVICE_NAME: &str = "svs-proof-backend";
+
+/// Install the global tracing subscriber
+///
+/// Returns the tracer provider when OTLP export is enabled; call
+/// [`SdkTracerProvider::shutdown`] on it before exit to flush pending spans.
+pub fn init(config: &Config) -> Option<SdkTracerProvider> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let registry = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "svs_proof_backend=info,tower_http=debug".into()),
+        )
+        .with(tracing_subscriber::fmt::layer());
+
+    let Some(endpoint) = &config.otlp_endpoint else {
+        registry.init();
+        return None;
+    };
+
+    let exporter = match SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            registry.init();
+            tracing::error!(error = %e, "Failed to build OTLP exporter, trace export disabled");
+            return None;
+        }
+    };
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+
+    registry
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)))
+        .init();
+
+    Some(provider)
+}
+
+/// Root span for an HTTP request, parented to the incoming trace context
+pub fn make_request_span(request: &Request<Body>) -> Span {
+    let span = info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+    );
+
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    // Fails only when no OpenTelemetry layer is installed
+    let _ = span.set_parent(parent);
+
+    span
+}
+
+/// Reads trace context propagation headers from a request
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use opentelemetry::{propagation::TextMapPropagator, trace::TraceContextExt};
+
+    #[test]
+    fn test_extracts_traceparent() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        );
+
+        let cx = TraceContextPropagator::new().extract(&HeaderExtractor(&headers));
+        let span_context = cx.span().span_context().clone();
+
+        assert!(span_context.is_remote());
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! TLS termination
+//!
+//! Requests carry key-derivation signatures, so the server only speaks plain
+//! HTTP when `allow_plaintext` is set for a deployment behind a TLS-terminating
+//! proxy. With a client CA configured, every client must present a
+//! certificate issued by it (mutual TLS).
+
+use anyhow::Context;
+use axum::serve::Listener;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io,
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
+};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        server::WebPkiClientVerifier,
+        RootCertStore, ServerConfig,
+    },
+    server::TlsStream,
+    TlsAcceptor,
+};
+use tokio_util::either::Either;
+use tracing::debug;
+
+use crate::types::Config;
+
+/// Time a client has to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Build the TLS acceptor from the config, or `None` for plain HTTP
+pub fn load_acceptor(config: &Config) -> anyhow::Result<Option<TlsAcceptor>> {
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) if config.tls_client_ca_path.is_some() => {
+            anyhow::bail!("TLS_CLIENT_CA_PATH needs TLS_CERT_PATH and TLS_KEY_PATH")
+        }
+        (None, None) if config.allow_plaintext => return Ok(None),
+        (None, None) => anyhow::bail!(
+            "Refusing to serve plain HTTP: set TLS_CERT_PATH and TLS_KEY_PATH, or \
+             ALLOW_PLAINTEXT=true behind a TLS-terminating proxy"
+        ),
+        _ => anyhow::bail!("TLS_CERT_PATH and TLS_KEY_PATH must be set together"),
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read TLS certificates from {cert_path}"))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .with_context(|| format!("Failed to read TLS private key from {key_path}"))?;
+
+    let provider = Arc::new(ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match &config.tls_client_ca_path {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca_path)
+                .with_context(|| format!("Failed to read client CAs from {ca_path}"))?
+            {
+                roots.add(cert?)?;
+            }
+            let verifier =
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .context("Invalid TLS certificate or key")?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(Some(TlsAcceptor::from(Arc::new(server_config))))
+}
+
+/// Listener serving TLS when an acceptor is given, plain TCP otherwise
+///
+/// Handshakes run concurrently, so a slow client cannot hold up others.
+pub struct ServerListener {
+    tcp: TcpListener,
+    acceptor: Option<TlsAcceptor>,
+    handshakes: JoinSet<(io::Result<TlsStream<TcpStream>>, SocketAddr)>,
+}
+
+impl ServerListener {
+    pub fn new(tcp: TcpListener, acceptor: Option<TlsAcceptor>) -> Self {
+        Self {
+            tcp,
+            acceptor,
+            handshakes: JoinSet::new(),
+        }
+    }
+}
+
+impl Listener for ServerListener {
+    type Io = Either<TcpStream, TlsStream<TcpStream>>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        let Self {
+            tcp,
+            acceptor,
+            handshakes,
+        } = self;
+        let Some(acceptor) = acceptor else {
+            let (stream, addr) = Listener::accept(tcp).await;
+            return (Either::Left(stream), addr);
+        };
+
+        loop {
+            tokio::select! {
+                // Retries accept errors itself
+                (stream, addr) = Listener::accept(tcp) => {
+                    let acceptor = acceptor.clone();
+                    handshakes.spawn(async move {
+                        let handshake = acceptor.accept(stream);
+                        let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+                            .await
+                            .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()));
+                        (stream, addr)
+                    });
+                }
+                Some(Ok((stream, addr))) = handshakes.join_next() => match stream {
+                    Ok(stream) => return (Either::Right(stream), addr),
+                    Err(e) => debug!(%addr, error = %e, "TLS handshake failed"),
+                },
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.tcp.local_addr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plaintext_must_be_allowed() {
+        let config = Config::default();
+        assert!(load_acceptor(&config).is_err());
+
+        let config = Config {
+            allow_plaintext: true,
+            ..Config::default()
+        };
+        assert!(load_acceptor(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_incomplete_tls_config_rejected() {
+        let config = Config {
+            tls_cert_path: Some("cert.pem".to_string()),
+            allow_plaintext: true,
+            ..Config::default()
+        };
+        assert!(load_acceptor(&config).is_err());
+
+        let config = Config {
+            tls_client_ca_path: Some("ca.pem".to_string()),
+            allow_plaintext: true,
+            ..Config::default()
+        };
+        assert!(load_acceptor(&config).is_err());
+    }
+}
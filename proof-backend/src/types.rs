@@ -1,11 +1,45 @@
 //! Request and response types for the SVS Proof Backend
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::ErrorResponse;
+
+pub use svs_proof_core::ContextProofType;
+
+/// Deployed SVS-2 program ID
+pub(crate) const DEFAULT_SVS2_PROGRAM_ID: &str = "3UrYrxh1HmVgq7WPygZ5x1gNEaWFwqTMs7geNqMnsrtD";
+
+/// A key-derivation signature (`elgamal_signature`, `ae_signature`)
+///
+/// Either the plain base64 signature, or `{"sealed": "..."}`: a sealed box
+/// (base64) of the 64 signature bytes encrypted to the key from
+/// `GET /v1/sealing-key`. Never printed by `Debug`.
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum SecretSignature {
+    /// Base64 signature
+    Plain(String),
+    /// Signature sealed to the backend's current sealing key
+    Sealed {
+        /// Sealed box ciphertext (base64 encoded)
+        sealed: String,
+    },
+}
+
+impl std::fmt::Debug for SecretSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plain(_) => f.write_str("SecretSignature::Plain(<redacted>)"),
+            Self::Sealed { .. } => f.write_str("SecretSignature::Sealed(<redacted>)"),
+        }
+    }
+}
 
 /// Request for PubkeyValidity proof generation
 ///
 /// Used for ConfigureAccount instruction to prove ownership of ElGamal keypair.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PubkeyValidityRequest {
     /// Wallet public key (base58)
     pub wallet_pubkey: String,
@@ -16,17 +50,23 @@ pub struct PubkeyValidityRequest {
     /// Unix timestamp (must be within 5 minutes)
     pub timestamp: i64,
 
-    /// Signature of: "SVS_PROOF_REQUEST" || timestamp || token_account
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
     /// This proves the wallet owner authorized this proof request
     pub request_signature: String,
 
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
     /// Signature used for ElGamal key derivation
     /// Signature of: "ElGamalSecretKey" || token_account
-    pub elgamal_signature: String,
+    pub elgamal_signature: SecretSignature,
 }
 
 /// Response for PubkeyValidity proof
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PubkeyValidityResponse {
     /// The generated proof data (64 bytes, base64 encoded)
     pub proof_data: String,
@@ -35,10 +75,57 @@ pub struct PubkeyValidityResponse {
     pub elgamal_pubkey: String,
 }
 
+/// Maximum number of requests in a pubkey validity batch
+pub const MAX_PUBKEY_VALIDITY_BATCH: usize = 100;
+
+/// Request for PubkeyValidity proofs of several token accounts
+///
+/// Each entry is a complete pubkey validity request, signed by the wallet
+/// that owns its token account.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PubkeyValidityBatchRequest {
+    /// At most 100 requests
+    pub requests: Vec<PubkeyValidityRequest>,
+}
+
+/// Outcome of one pubkey validity batch entry
+///
+/// Either the proof fields or `error` are set.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PubkeyValidityBatchResult {
+    /// Token account of the request, as sent
+    pub token_account: String,
+
+    /// The generated proof data (64 bytes, base64 encoded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_data: Option<String>,
+
+    /// The derived ElGamal public key (32 bytes, base64 encoded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elgamal_pubkey: Option<String>,
+
+    /// Why this entry failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+/// Response for a pubkey validity batch
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PubkeyValidityBatchResponse {
+    /// One result per request, in request order
+    pub results: Vec<PubkeyValidityBatchResult>,
+
+    /// Number of entries with a proof
+    pub succeeded: usize,
+
+    /// Number of entries with an error
+    pub failed: usize,
+}
+
 /// Request for CiphertextCommitmentEquality proof generation
 ///
 /// Used for Withdraw/Redeem to prove ciphertext encrypts a specific amount.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EqualityProofRequest {
     /// Wallet public key (base58)
     pub wallet_pubkey: String,
@@ -49,21 +136,29 @@ pub struct EqualityProofRequest {
     /// Unix timestamp (must be within 5 minutes)
     pub timestamp: i64,
 
-    /// Signature of: "SVS_PROOF_REQUEST" || timestamp || token_account
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
     pub request_signature: String,
 
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
     /// Signature used for ElGamal key derivation
-    pub elgamal_signature: String,
+    pub elgamal_signature: SecretSignature,
 
     /// Current encrypted balance ciphertext (base64 encoded)
-    pub current_ciphertext: String,
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_ciphertext: Option<String>,
 
     /// Amount to prove (as string to handle u64)
     pub amount: String,
 }
 
 /// Response for Equality proof
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EqualityProofResponse {
     /// The generated proof data (192 bytes, base64 encoded)
     pub proof_data: String,
@@ -72,7 +167,7 @@ pub struct EqualityProofResponse {
 /// Request for BatchedRangeProofU64 generation
 ///
 /// Used for Withdraw/Redeem to prove amounts are in valid range.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RangeProofRequest {
     /// Wallet public key (base58)
     pub wallet_pubkey: String,
@@ -80,86 +175,1752 @@ pub struct RangeProofRequest {
     /// Unix timestamp (must be within 5 minutes)
     pub timestamp: i64,
 
-    /// Signature of: "SVS_PROOF_REQUEST" || timestamp || "range"
+    /// Signature of: "SVS range proof request: timestamp <timestamp>[, nonce
+    /// <nonce>]", plain or as an off-chain message
     pub request_signature: String,
 
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
     /// Amounts to prove (as strings to handle u64)
     pub amounts: Vec<String>,
 
     /// Commitment blindings (base64 encoded, one per amount)
     pub commitment_blindings: Vec<String>,
+
+    /// Bit length proven for each amount (1-64), 64 each when omitted
+    ///
+    /// They must sum to 64, 128 or 256, which selects the U64, U128 or U256
+    /// proof. A Token-2022 transfer proves `[64, 16, 32, 16]` over the new
+    /// balance, the lo and hi transfer amount, and a zero padding amount.
+    #[serde(default)]
+    pub bit_lengths: Option<Vec<u8>>,
 }
 
 /// Response for Range proof
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RangeProofResponse {
-    /// The generated proof data (672+ bytes depending on batch size, base64 encoded)
+    /// The generated proof data (size depends on the proof variant, base64 encoded)
     pub proof_data: String,
+
+    /// Proof variant, as accepted by `/v1/tx/context-state`
+    pub proof_type: ContextProofType,
 }
 
-/// Health check response
-#[derive(Debug, Serialize)]
-pub struct HealthResponse {
-    pub status: String,
-    pub version: String,
+/// Request for all proofs of a confidential withdraw in one call
+///
+/// Used for Withdraw/Redeem so the equality and range proofs share one opening.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WithdrawBundleRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
     pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Signature used for AE key derivation
+    /// Signature of: "AeKey" || token_account
+    pub ae_signature: SecretSignature,
+
+    /// Current available balance ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_ciphertext: Option<String>,
+
+    /// Current decryptable available balance (base64 encoded, 36 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_decryptable_balance: Option<String>,
+
+    /// Amount withdrawn from the confidential balance (as string to handle u64)
+    pub amount: String,
 }
 
-/// Configuration for the backend server
-#[derive(Debug, Clone)]
-pub struct Config {
-    /// Server port
-    pub port: u16,
+/// Response for a withdraw proof bundle
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WithdrawBundleResponse {
+    /// CiphertextCommitmentEquality proof data (base64 encoded)
+    pub equality_proof_data: String,
 
-    /// CORS allowed origins
-    pub cors_origins: Vec<String>,
+    /// BatchedRangeProofU64 proof data (base64 encoded)
+    pub range_proof_data: String,
 
-    /// API keys for authentication
-    pub api_keys: Vec<String>,
+    /// Pedersen commitment to the remaining balance (base64 encoded, 32 bytes)
+    pub remaining_balance_commitment: String,
 
-    /// Request timestamp tolerance in seconds
-    pub timestamp_tolerance_secs: i64,
+    /// Opening of the remaining balance commitment (base64 encoded, 32 bytes)
+    pub remaining_balance_opening: String,
+
+    /// New decryptable available balance (base64 encoded, 36 bytes)
+    pub new_decryptable_available_balance: String,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            port: 3001,
-            cors_origins: vec!["http://localhost:3000".to_string()],
-            api_keys: vec![],
-            timestamp_tolerance_secs: 300, // 5 minutes
-        }
-    }
+/// Request for ZeroCiphertext proof generation
+///
+/// Used for EmptyAccount to prove a balance ciphertext encrypts zero.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ZeroCiphertextProofRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Balance ciphertext that must encrypt zero (base64 encoded, 64 bytes)
+    /// For EmptyAccount this is the available balance
+    /// Omit to read the available balance from the token account (needs
+    /// `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub ciphertext: Option<String>,
 }
 
-impl Config {
-    /// Load configuration from environment variables
-    pub fn from_env() -> Self {
-        let port = std::env::var("PORT")
-            .ok()
-            .and_then(|p| p.parse().ok())
-            .unwrap_or(3001);
-
-        let cors_origins = std::env::var("CORS_ORIGINS")
-            .ok()
-            .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
-            .unwrap_or_else(|| vec!["http://localhost:3000".to_string()]);
-
-        let api_keys = std::env::var("API_KEYS")
-            .ok()
-            .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
-            .unwrap_or_default();
-
-        let timestamp_tolerance_secs = std::env::var("TIMESTAMP_TOLERANCE_SECS")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(300);
+/// Response for ZeroCiphertext proof
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ZeroCiphertextProofResponse {
+    /// The generated proof data (base64 encoded)
+    pub proof_data: String,
+}
 
-        Self {
-            port,
-            cors_origins,
-            api_keys,
-            timestamp_tolerance_secs,
-        }
+/// Request for confidential transfer proof generation
+///
+/// Used for Token-2022 confidential Transfer of vault shares.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TransferProofRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Source token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Signature used for AE key derivation
+    /// Signature of: "AeKey" || token_account
+    pub ae_signature: SecretSignature,
+
+    /// Source available balance ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_ciphertext: Option<String>,
+
+    /// Source decryptable available balance (base64 encoded, 36 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_decryptable_balance: Option<String>,
+
+    /// Amount to transfer (as string to handle u64)
+    pub amount: String,
+
+    /// Destination account ElGamal public key (base64 encoded, 32 bytes)
+    pub destination_elgamal_pubkey: String,
+
+    /// Mint auditor ElGamal public key (base64 encoded, 32 bytes), if any
+    #[serde(default)]
+    pub auditor_elgamal_pubkey: Option<String>,
+}
+
+/// Response for confidential transfer proofs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransferProofResponse {
+    /// CiphertextCommitmentEquality proof data (base64 encoded)
+    pub equality_proof_data: String,
+
+    /// BatchedGroupedCiphertext3HandlesValidity proof data (base64 encoded)
+    pub ciphertext_validity_proof_data: String,
+
+    /// BatchedRangeProofU128 proof data (base64 encoded)
+    pub range_proof_data: String,
+
+    /// Transfer amount lo ciphertext under the auditor key (base64 encoded)
+    pub auditor_ciphertext_lo: String,
+
+    /// Transfer amount hi ciphertext under the auditor key (base64 encoded)
+    pub auditor_ciphertext_hi: String,
+
+    /// New decryptable available balance for the source (base64 encoded, 36 bytes)
+    pub new_decryptable_available_balance: String,
+}
+
+/// Request for everything needed to execute a confidential transfer
+///
+/// The transfer proof fields plus the accounts of the Token-2022 `Transfer`
+/// instruction.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransferPackageRequest {
+    #[serde(flatten)]
+    pub transfer: TransferProofRequest,
+
+    /// Mint of the transferred token, e.g. the vault shares mint (base58)
+    pub mint: String,
+
+    /// Destination confidential token account (base58)
+    pub destination_token_account: String,
+
+    /// Recent blockhash for the transactions (base58)
+    pub recent_blockhash: String,
+}
+
+/// Response for a confidential transfer package
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransferPackageResponse {
+    #[serde(flatten)]
+    pub proofs: TransferProofResponse,
+
+    /// Bincode-serialized legacy transactions (base64), to be signed by the
+    /// wallet and submitted in order
+    pub transactions: Vec<String>,
+
+    /// Equality proof context state account (base58)
+    pub equality_proof_context: String,
+
+    /// Ciphertext validity proof context state account (base58)
+    pub ciphertext_validity_proof_context: String,
+
+    /// Range proof context state account (base58)
+    pub range_proof_context: String,
+}
+
+/// Request for balance decryption
+///
+/// Ciphertexts are read by the client from the token account's
+/// ConfidentialTransferAccount extension.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BalanceDecryptRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Available balance ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub available_balance: Option<String>,
+
+    /// Pending balance lo ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub pending_balance_lo: Option<String>,
+
+    /// Pending balance hi ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub pending_balance_hi: Option<String>,
+
+    /// Decryptable available balance (base64 encoded, 36 bytes)
+    /// When provided with `ae_signature`, the available balance is decrypted
+    /// from this instead of the ElGamal ciphertext (no 2^32 limit)
+    #[serde(default)]
+    pub decryptable_available_balance: Option<String>,
+
+    /// Signature used for AE key derivation
+    /// Signature of: "AeKey" || token_account
+    #[serde(default)]
+    pub ae_signature: Option<SecretSignature>,
+}
+
+/// Response for balance decryption
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BalanceDecryptResponse {
+    /// Available balance (as string to handle u64)
+    pub available_balance: String,
+
+    /// Pending balance (as string to handle u64)
+    pub pending_balance: String,
+
+    /// Pending balance credit counter, when balances were read from the
+    /// token account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_balance_credit_counter: Option<u64>,
+}
+
+/// Request for decryptable (AE) balance computation
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BalanceEncryptRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for AE key derivation
+    /// Signature of: "AeKey" || token_account
+    pub ae_signature: SecretSignature,
+
+    /// Balance to encrypt (as string to handle u64)
+    pub balance: String,
+}
+
+/// Response for decryptable balance computation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BalanceEncryptResponse {
+    /// Decryptable balance (PodAeCiphertext, base64 encoded, 36 bytes)
+    pub decryptable_balance: String,
+}
+
+/// Request for applying the pending balance
+///
+/// Computes the arguments of the Token-2022 `ApplyPendingBalance`
+/// instruction, which moves the pending balance into the available balance.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApplyPendingRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Signature used for AE key derivation
+    /// Signature of: "AeKey" || token_account
+    pub ae_signature: SecretSignature,
+
+    /// Pending balance lo ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub pending_balance_lo: Option<String>,
+
+    /// Pending balance hi ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub pending_balance_hi: Option<String>,
+
+    /// Pending balance credit counter the pending ciphertexts were read at
+    /// Required with, and only with, the pending balance ciphertexts
+    #[serde(default)]
+    pub pending_balance_credit_counter: Option<u64>,
+
+    /// Decryptable available balance (base64 encoded, 36 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub decryptable_available_balance: Option<String>,
+}
+
+/// Response for applying the pending balance
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApplyPendingResponse {
+    /// Available balance before applying (as string to handle u64)
+    pub available_balance: String,
+
+    /// Pending balance being applied (as string to handle u64)
+    pub pending_balance: String,
+
+    /// Available balance after applying (as string to handle u64)
+    pub new_available_balance: String,
+
+    /// `expected_pending_balance_credit_counter` instruction argument
+    pub expected_pending_balance_credit_counter: u64,
+
+    /// `new_decryptable_available_balance` instruction argument (base64
+    /// encoded, 36 bytes)
+    pub new_decryptable_available_balance: String,
+}
+
+/// Request for ElGamal public key derivation
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ElGamalPubkeyRequest {
+    /// Wallet public key (base58)
+    pub wallet_pubkey: String,
+
+    /// Token account public key (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Signature used for AE key derivation, to also return its fingerprint
+    /// Signature of: "AeKey" || token_account
+    #[serde(default)]
+    pub ae_signature: Option<SecretSignature>,
+}
+
+/// Response for ElGamal public key derivation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ElGamalPubkeyResponse {
+    /// ElGamal public key (base64 encoded, 32 bytes)
+    pub elgamal_pubkey: String,
+
+    /// sha256("SVS_AE_KEY_FINGERPRINT" || AE key) (base64 encoded, 32 bytes),
+    /// when `ae_signature` was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ae_key_fingerprint: Option<String>,
+}
+
+/// Vault instruction used to leave a confidential position
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WithdrawOperation {
+    /// Exact assets out (`withdraw`)
+    Withdraw,
+    /// Exact shares in (`redeem`)
+    Redeem,
+}
+
+/// Request for a full SVS-2 withdraw/redeem transaction set
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WithdrawTxRequest {
+    /// Wallet public key (base58), also the fee payer unless relayed
+    pub wallet_pubkey: String,
+
+    /// User's confidential shares token account (base58)
+    pub token_account: String,
+
+    /// Unix timestamp (must be within 5 minutes)
+    pub timestamp: i64,
+
+    /// Signature of: "SVS proof request: token account <token_account>,
+    /// timestamp <timestamp>[, nonce <nonce>]", plain or as an off-chain message
+    pub request_signature: String,
+
+    /// Optional client nonce appended to the signed message, so several
+    /// requests can share a timestamp
+    #[serde(default)]
+    pub nonce: Option<String>,
+
+    /// Signature used for ElGamal key derivation
+    pub elgamal_signature: SecretSignature,
+
+    /// Signature used for AE key derivation
+    /// Signature of: "AeKey" || token_account
+    pub ae_signature: SecretSignature,
+
+    /// Current available balance ciphertext (base64 encoded, 64 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_ciphertext: Option<String>,
+
+    /// Current decryptable available balance (base64 encoded, 36 bytes)
+    /// Omit to read it from the token account (needs `SOLANA_RPC_URL`)
+    #[serde(default)]
+    pub current_decryptable_balance: Option<String>,
+
+    /// `withdraw` or `redeem`
+    pub operation: WithdrawOperation,
+
+    /// Shares burned (as string to handle u64)
+    /// For `withdraw` this must equal the vault's preview for `assets`; omit
+    /// it to preview from the vault account (needs `SOLANA_RPC_URL`, and
+    /// assets without a transfer fee)
+    #[serde(default)]
+    pub shares: Option<String>,
+
+    /// Exact assets for `withdraw`, minimum assets out for `redeem` (as string)
+    pub assets: String,
+
+    /// Compliance memo hash for `withdraw` (base64 encoded, 32 bytes)
+    #[serde(default)]
+    pub memo_hash: Option<String>,
+
+    /// Vault account (base58)
+    pub vault: String,
+
+    /// Vault asset mint (base58)
+    pub asset_mint: String,
+
+    /// Vault asset token account (base58)
+    pub asset_vault: String,
+
+    /// Vault shares mint (base58)
+    pub shares_mint: String,
+
+    /// User's asset token account receiving the assets (base58)
+    pub user_asset_account: String,
+
+    /// Token program of the asset mint (base58)
+    pub asset_token_program: String,
+
+    /// Recent blockhash for the transactions (base58)
+    pub recent_blockhash: String,
+
+    /// Make this server's relayer the fee payer, funding the context
+    /// accounts and getting their rent back; submit the signed transactions
+    /// through `/v1/relay/submit`
+    #[serde(default)]
+    pub relay: bool,
+}
+
+/// Response for a withdraw/redeem transaction set
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WithdrawTxResponse {
+    /// Bincode-serialized legacy transactions (base64), to be signed by the
+    /// wallet and submitted in order
+    pub transactions: Vec<String>,
+
+    /// Fee payer of the transactions: the wallet, or the relayer (base58)
+    pub fee_payer: String,
+
+    /// Equality proof context state account (base58)
+    pub equality_proof_context: String,
+
+    /// Range proof context state account (base58)
+    pub range_proof_context: String,
+
+    /// New decryptable available balance (base64 encoded, 36 bytes)
+    pub new_decryptable_available_balance: String,
+}
+
+/// Maximum transactions relayed per request
+pub const MAX_RELAY_TRANSACTIONS: usize = 8;
+
+/// Request to submit transactions through the relayer
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RelaySubmitRequest {
+    /// Bincode-serialized transactions (base64) with the relayer as fee
+    /// payer, signed by every signer but the relayer; submitted in order, each
+    /// once the previous one is confirmed
+    pub transactions: Vec<String>,
+}
+
+/// Submitted transactions
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelaySubmitResponse {
+    /// Transaction signatures (base58), in submission order
+    pub signatures: Vec<String>,
+
+    /// Lamports the relayer paid in fees and rent (as string)
+    pub lamports: String,
+}
+
+/// Relayer fee payer and limits
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelayInfoResponse {
+    /// Fee payer of relayed transactions (base58)
+    pub fee_payer: String,
+
+    /// Most lamports paid for one transaction (as string)
+    pub max_lamports_per_transaction: String,
+
+    /// Most lamports paid per API key per UTC day (as string)
+    pub daily_lamports_per_key: String,
+
+    /// Programs relayed transactions may call (base58)
+    pub allowed_programs: Vec<String>,
+}
+
+/// Request for a context state account instruction bundle
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ContextStateRequest {
+    /// Proof type of `proof_data`
+    pub proof_type: ContextProofType,
+
+    /// Proof data as returned by the proof endpoints (base64 encoded)
+    pub proof_data: String,
+
+    /// Payer and seed base for the context account (base58)
+    pub payer: String,
+
+    /// Context state authority allowed to close the account (base58)
+    /// Defaults to the payer
+    #[serde(default)]
+    pub context_state_authority: Option<String>,
+
+    /// Seed for the context account address (max 32 chars)
+    /// A random seed is generated if omitted
+    #[serde(default)]
+    pub seed: Option<String>,
+}
+
+/// Account meta of a serialized instruction
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountMetaData {
+    /// Account public key (base58)
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Serialized instruction
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InstructionData {
+    /// Program ID (base58)
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaData>,
+    /// Instruction data (base64 encoded)
+    pub data: String,
+}
+
+/// Response for a context state account instruction bundle
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContextStateResponse {
+    /// Context state account (base58)
+    pub context_state_account: String,
+
+    /// Seed used to derive the context state account from the payer
+    pub seed: String,
+
+    /// Account size in bytes
+    pub space: u64,
+
+    /// Rent-exempt balance (as string to handle u64)
+    pub lamports: String,
+
+    /// SystemProgram::CreateAccountWithSeed
+    pub create_instruction: InstructionData,
+
+    /// ZK ElGamal proof program VerifyProof into the context account
+    pub verify_instruction: InstructionData,
+
+    /// CloseContextState, returning rent to the payer
+    pub close_instruction: InstructionData,
+}
+
+/// Operation a cost estimate describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum EstimateOperation {
+    /// Configure a token account for confidential transfers
+    Configure,
+    /// Confidential vault withdraw or redeem
+    Withdraw,
+    /// Token-2022 confidential transfer
+    Transfer,
+}
+
+/// Request for a cost estimate
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EstimateRequest {
+    pub operation: EstimateOperation,
+
+    /// Number of operations, e.g. withdrawals of N amounts (default 1)
+    #[serde(default = "default_estimate_count")]
+    pub count: u32,
+}
+
+fn default_estimate_count() -> u32 {
+    1
+}
+
+/// Estimated size and cost of one proof of an operation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProofCostEstimate {
+    pub proof_type: ContextProofType,
+
+    /// Proof data size in bytes
+    pub proof_bytes: u64,
+
+    /// Context state account size in bytes; absent when the proof is
+    /// verified in the same transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_account_bytes: Option<u64>,
+
+    /// Rent-exempt balance of the context state account (as string to handle u64)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_rent_lamports: Option<String>,
+
+    /// Approximate compute units to verify the proof
+    pub compute_units: u64,
+}
+
+/// Cost estimate for `count` operations, computed without generating proofs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EstimateResponse {
+    pub operation: EstimateOperation,
+    pub count: u32,
+
+    /// Proofs of one operation
+    pub proofs: Vec<ProofCostEstimate>,
+
+    /// Transactions to submit, in total
+    pub transaction_count: u64,
+
+    /// Transaction signatures, in total
+    pub signature_count: u64,
+
+    /// Base signature fees, without priority fees (as string to handle u64)
+    pub signature_fee_lamports: String,
+
+    /// Rent locked in context state accounts, refunded when they are
+    /// closed (as string to handle u64)
+    pub context_rent_lamports: String,
+
+    /// Approximate compute units, in total
+    pub compute_units: u64,
+
+    /// Approximate compute units of the most expensive transaction, for
+    /// sizing a compute budget instruction
+    pub max_transaction_compute_units: u64,
+}
+
+/// Proof request submitted to the async job queue
+///
+/// `request` is the body of the matching synchronous `/v1/proofs/*` endpoint.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", content = "request", rename_all = "kebab-case")]
+pub enum JobRequest {
+    PubkeyValidity(PubkeyValidityRequest),
+    Equality(EqualityProofRequest),
+    Range(RangeProofRequest),
+    Transfer(TransferProofRequest),
+    ZeroCiphertext(ZeroCiphertextProofRequest),
+    WithdrawBundle(WithdrawBundleRequest),
+}
+
+impl JobRequest {
+    /// Request timestamp, validated at submission time
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            JobRequest::PubkeyValidity(req) => req.timestamp,
+            JobRequest::Equality(req) => req.timestamp,
+            JobRequest::Range(req) => req.timestamp,
+            JobRequest::Transfer(req) => req.timestamp,
+            JobRequest::ZeroCiphertext(req) => req.timestamp,
+            JobRequest::WithdrawBundle(req) => req.timestamp,
+        }
+    }
+
+    /// Request signature, recorded at submission time against replays
+    pub fn request_signature(&self) -> &str {
+        match self {
+            JobRequest::PubkeyValidity(req) => &req.request_signature,
+            JobRequest::Equality(req) => &req.request_signature,
+            JobRequest::Range(req) => &req.request_signature,
+            JobRequest::Transfer(req) => &req.request_signature,
+            JobRequest::ZeroCiphertext(req) => &req.request_signature,
+            JobRequest::WithdrawBundle(req) => &req.request_signature,
+        }
+    }
+
+    /// Key-derivation signatures carried by the request
+    pub fn secrets_mut(&mut self) -> Vec<&mut SecretSignature> {
+        match self {
+            JobRequest::PubkeyValidity(req) => vec![&mut req.elgamal_signature],
+            JobRequest::Equality(req) => vec![&mut req.elgamal_signature],
+            JobRequest::Range(_) => vec![],
+            JobRequest::Transfer(req) => vec![&mut req.elgamal_signature, &mut req.ae_signature],
+            JobRequest::ZeroCiphertext(req) => vec![&mut req.elgamal_signature],
+            JobRequest::WithdrawBundle(req) => {
+                vec![&mut req.elgamal_signature, &mut req.ae_signature]
+            }
+        }
+    }
+
+    /// Scope an API key needs to submit this job
+    pub fn scope(&self) -> ApiScope {
+        match self {
+            JobRequest::PubkeyValidity(_) => ApiScope::PubkeyValidity,
+            JobRequest::Equality(_) => ApiScope::Equality,
+            JobRequest::Range(_) => ApiScope::Range,
+            JobRequest::Transfer(_) => ApiScope::Transfer,
+            JobRequest::ZeroCiphertext(_) => ApiScope::ZeroCiphertext,
+            JobRequest::WithdrawBundle(_) => ApiScope::WithdrawBundle,
+        }
+    }
+
+    /// Job kind as used in the request body
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobRequest::PubkeyValidity(_) => "pubkey-validity",
+            JobRequest::Equality(_) => "equality",
+            JobRequest::Range(_) => "range",
+            JobRequest::Transfer(_) => "transfer",
+            JobRequest::ZeroCiphertext(_) => "zero-ciphertext",
+            JobRequest::WithdrawBundle(_) => "withdraw-bundle",
+        }
+    }
+}
+
+/// Request count and proof generation time for one period, or charged for
+/// one request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct UsageCounts {
+    /// Billable (POST) requests
+    pub requests: u64,
+
+    /// Time spent generating proofs, in milliseconds
+    pub proof_ms: u64,
+}
+
+/// What is left of each configured quota limit in the current period
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct QuotaRemaining {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_requests: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_requests: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_proof_secs: Option<u64>,
+}
+
+/// Usage of the calling API key
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageResponse {
+    /// Key name from config (never the key itself)
+    pub api_key: String,
+
+    /// Current UTC day (YYYY-MM-DD)
+    pub day: String,
+
+    /// Current UTC month (YYYY-MM)
+    pub month: String,
+
+    pub daily: UsageCounts,
+
+    pub monthly: UsageCounts,
+
+    /// Configured limits, if any
+    pub quota: Option<QuotaConfig>,
+
+    /// What is left of the configured limits, if any
+    pub remaining: Option<QuotaRemaining>,
+}
+
+/// Public key that signs API responses
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServiceKeyResponse {
+    /// ed25519 public key (base58)
+    pub pubkey: String,
+}
+
+/// Public X25519 key that clients seal key-derivation signatures to
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SealingKeyResponse {
+    /// X25519 public key (32 bytes, base64 encoded)
+    pub pubkey: String,
+
+    /// Unix time after which a new key is issued; sealed payloads for this
+    /// key are accepted for one more rotation period. Absent when the key
+    /// lives for the whole process.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotates_at: Option<i64>,
+
+    /// Whether plain (unsealed) key-derivation signatures are rejected
+    pub sealed_required: bool,
+}
+
+/// Response for job submission
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobSubmitResponse {
+    /// Job id to poll at `GET /v1/jobs/{id}`
+    pub job_id: String,
+}
+
+/// Health check response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+    pub timestamp: i64,
+}
+
+/// Outcome of the most recent proof self-test
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SelfTestReport {
+    /// Whether a pubkey validity proof was generated and verified
+    pub ok: bool,
+
+    /// Unix timestamp the test finished
+    pub checked_at: i64,
+
+    pub duration_ms: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Number of unfinished proof jobs
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct JobQueueDepth {
+    pub queued: usize,
+    pub running: usize,
+}
+
+/// Readiness check response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready`, `starting` (no self-test finished yet), `self-test-failed`,
+    /// `self-test-stale` (the last test is overdue) or `draining`
+    pub status: String,
+    pub version: String,
+
+    /// solana-zk-sdk version the binary was built with
+    pub zk_sdk_version: String,
+    pub timestamp: i64,
+
+    /// Absent when the self-test is disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_test: Option<SelfTestReport>,
+    pub queue: JobQueueDepth,
+}
+
+/// Configuration for the backend server
+///
+/// Loaded from an optional TOML file (`CONFIG_FILE`), then overridden by any
+/// environment variables that are set. Missing settings keep their defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Server port
+    pub port: u16,
+
+    /// CORS allowed origins
+    pub cors_origins: Vec<String>,
+
+    /// API keys for authentication
+    pub api_keys: Vec<ApiKeyConfig>,
+
+    /// Key for the `/v1/admin/*` API key management endpoints, sent in the
+    /// `X-Admin-Key` header; the admin API is disabled when unset
+    pub admin_api_key: Option<String>,
+
+    /// JWT bearer authentication, accepted alongside API keys; disabled
+    /// when unset
+    pub jwt: Option<JwtConfig>,
+
+    /// Request timestamp tolerance in seconds
+    pub timestamp_tolerance_secs: i64,
+
+    /// Maximum number of request signatures remembered for replay protection
+    pub replay_cache_capacity: usize,
+
+    /// Lifetime of cached pubkey validity proofs in seconds (0 disables the cache)
+    pub pubkey_proof_cache_ttl_secs: u64,
+
+    /// Maximum number of cached pubkey validity proofs
+    pub pubkey_proof_cache_capacity: u64,
+
+    /// OTLP/HTTP collector base URL; trace export is disabled when unset
+    pub otlp_endpoint: Option<String>,
+
+    /// SVS-2 program ID used by the transaction builder (base58)
+    pub svs2_program_id: String,
+
+    /// Maximum number of proof jobs generated concurrently
+    pub job_workers: usize,
+
+    /// How long finished job results are kept, in seconds
+    pub job_ttl_secs: u64,
+
+    /// How long a queued job may wait before it starts ahead of higher
+    /// priority tiers, in seconds
+    pub job_max_wait_secs: u64,
+
+    /// Persist queued and running jobs, so they survive restarts and are
+    /// picked up by another replica (needs `job_database_url` or `redis_url`,
+    /// and `job_store_key`)
+    pub durable_jobs: bool,
+
+    /// 32-byte hex key encrypting persisted job requests, the same on every
+    /// replica
+    pub job_store_key: Option<String>,
+
+    /// Postgres keeping durable jobs and their results; takes precedence
+    /// over `redis_url` for them
+    pub job_database_url: Option<String>,
+
+    /// How long shutdown waits for in-flight requests and jobs, in seconds
+    pub shutdown_grace_secs: u64,
+
+    /// Solana keypair file used to sign responses; ephemeral when unset
+    pub service_keypair_path: Option<String>,
+
+    /// How often the in-memory sealing key is replaced, in seconds (0 keeps
+    /// one key for the life of the process)
+    pub sealing_key_rotation_secs: u64,
+
+    /// Reject plain key-derivation signatures; clients must seal them
+    pub require_sealed_secrets: bool,
+
+    /// How often the readiness self-test generates and verifies a proof, in
+    /// seconds (0 disables it)
+    pub self_test_interval_secs: u64,
+
+    /// Serve the unversioned `/api/*` paths as deprecated aliases of `/v1/*`
+    pub legacy_api_enabled: bool,
+
+    /// RFC 3339 time after which the `/api/*` aliases may be removed,
+    /// announced in the `Sunset` header
+    pub legacy_api_sunset: Option<String>,
+
+    /// Solana RPC endpoint used to read token accounts when a request omits
+    /// its balance ciphertexts
+    pub rpc_url: Option<String>,
+
+    /// Maximum API requests handled at once; further requests get 503 (0
+    /// disables the limit)
+    pub max_in_flight_requests: usize,
+
+    /// Maximum synchronous proofs generated at once; further proof requests
+    /// get 503
+    pub max_concurrent_proofs: usize,
+
+    /// Timeout for proof, balance and transaction endpoints, in seconds
+    pub proof_timeout_secs: u64,
+
+    /// Timeout for other API endpoints, in seconds
+    pub request_timeout_secs: u64,
+
+    /// Maximum request body size, in bytes
+    pub max_body_bytes: usize,
+
+    /// Maximum request body size of batch endpoints (range proofs, pubkey
+    /// validity batches and job submission), in bytes
+    pub max_batch_body_bytes: usize,
+
+    /// PEM certificate chain served over TLS
+    pub tls_cert_path: Option<String>,
+
+    /// PEM private key of the TLS certificate
+    pub tls_key_path: Option<String>,
+
+    /// PEM CA certificates that client certificates must chain to; enables
+    /// mutual TLS when set
+    pub tls_client_ca_path: Option<String>,
+
+    /// Serve plain HTTP when no certificate is configured, for deployments
+    /// behind a TLS-terminating proxy
+    pub allow_plaintext: bool,
+
+    /// Redis shared by every replica for replay protection, usage counters,
+    /// idempotency keys and job records; state is per process when unset
+    pub redis_url: Option<String>,
+
+    /// Prefix of every Redis key, so several deployments can share a Redis
+    pub redis_key_prefix: String,
+
+    /// Fee payer that submits transactions for wallets that opt in; disabled
+    /// when unset
+    pub relayer: Option<RelayerConfig>,
+}
+
+/// An API key and the metadata used to identify its holder
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Key value sent in the `X-API-Key` header
+    pub key: String,
+
+    /// Label for logs, e.g. the integrating team
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Endpoints this key may call; unrestricted when unset
+    #[serde(default)]
+    pub scopes: Option<Vec<ApiScope>>,
+
+    /// Usage limits; unlimited when unset
+    #[serde(default)]
+    pub quota: Option<QuotaConfig>,
+
+    /// Scheduling tier of the key's async proof jobs
+    #[serde(default)]
+    pub priority: PriorityTier,
+}
+
+/// Settings of an API key managed through the admin API
+#[derive(Debug, Clone, Default, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeySettings {
+    /// Label for logs, e.g. the integrating team
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Endpoints this key may call; unrestricted when unset
+    #[serde(default)]
+    pub scopes: Option<Vec<ApiScope>>,
+
+    /// Usage limits; unlimited when unset
+    #[serde(default)]
+    pub quota: Option<QuotaConfig>,
+
+    /// Scheduling tier of the key's async proof jobs
+    #[serde(default)]
+    pub priority: PriorityTier,
+}
+
+/// An API key managed through the admin API, without its value
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyInfo {
+    /// Key id, used in admin API paths
+    pub id: String,
+
+    #[serde(flatten)]
+    pub settings: ApiKeySettings,
+
+    /// Unix timestamp of creation
+    pub created_at: i64,
+
+    /// Unix timestamp of the last rotation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<i64>,
+
+    /// Until when the key replaced by the last rotation is still accepted
+    /// (Unix timestamp)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_key_expires_at: Option<i64>,
+}
+
+/// A newly created or rotated API key
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeySecretResponse {
+    #[serde(flatten)]
+    pub info: ApiKeyInfo,
+
+    /// The key to send in `X-API-Key`; it is only ever returned here
+    pub key: String,
+}
+
+/// Managed API keys
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyListResponse {
+    pub keys: Vec<ApiKeyInfo>,
+}
+
+/// Request to rotate an API key
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RotateApiKeyRequest {
+    /// How long the replaced key stays valid, in seconds, so clients can
+    /// switch over; 0 revokes it immediately
+    #[serde(default)]
+    pub grace_secs: u64,
+}
+
+/// JWT bearer token verification
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    /// Shared HMAC secret (HS256/384/512), at least 32 bytes
+    pub secret: Option<String>,
+
+    /// URL of the identity provider's JWKS; used instead of `secret`
+    pub jwks_url: Option<String>,
+
+    /// How long fetched JWKS keys are used before refetching, in seconds
+    pub jwks_refresh_secs: u64,
+
+    /// Required `iss` claim
+    pub issuer: Option<String>,
+
+    /// Required `aud` claim
+    pub audience: Option<String>,
+
+    /// Claim listing the token's scopes, as a space-separated string or an
+    /// array
+    pub scope_claim: String,
+
+    /// Prefix of the scope values meant for this service, e.g. `svs:`
+    pub scope_prefix: String,
+
+    /// Usage limits applied to each token subject; unlimited when unset
+    pub quota: Option<QuotaConfig>,
+
+    /// Scheduling tier of each token subject's async proof jobs
+    pub priority: PriorityTier,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            jwks_url: None,
+            jwks_refresh_secs: 300,
+            issuer: None,
+            audience: None,
+            scope_claim: "scope".to_string(),
+            scope_prefix: String::new(),
+            quota: None,
+            priority: PriorityTier::default(),
+        }
+    }
+}
+
+/// Transaction relayer paying fees and context account rent
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RelayerConfig {
+    /// Solana keypair file of the fee payer
+    pub keypair_path: Option<String>,
+
+    /// Most lamports the relayer pays for one transaction, in fees and rent
+    pub max_lamports_per_transaction: u64,
+
+    /// Most lamports the relayer pays per API key per UTC day
+    pub daily_lamports_per_key: u64,
+
+    /// Most lamports the relayer pays per UTC day in total
+    pub daily_lamports: u64,
+
+    /// Programs relayed transactions may call (base58); defaults to those of
+    /// the SVS-2 withdraw flow
+    pub allowed_programs: Option<Vec<String>>,
+}
+
+impl Default for RelayerConfig {
+    fn default() -> Self {
+        Self {
+            keypair_path: None,
+            max_lamports_per_transaction: 10_000_000, // 0.01 SOL
+            daily_lamports_per_key: 100_000_000,      // 0.1 SOL
+            daily_lamports: 1_000_000_000,            // 1 SOL
+            allowed_programs: None,
+        }
+    }
+}
+
+/// Usage limits for an API key, per UTC day and month
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaConfig {
+    /// Billable (POST) requests per day
+    #[serde(default)]
+    pub daily_requests: Option<u64>,
+
+    /// Billable (POST) requests per month
+    #[serde(default)]
+    pub monthly_requests: Option<u64>,
+
+    /// Seconds of proof generation per month
+    #[serde(default)]
+    pub monthly_proof_secs: Option<u64>,
+
+    /// What happens once a limit is reached
+    #[serde(default)]
+    pub on_exhausted: QuotaAction,
+}
+
+/// Behavior when an API key has used up its quota
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaAction {
+    /// Reject requests with 429 until the period ends
+    #[default]
+    Reject,
+    /// Serve requests and log a warning
+    Log,
+}
+
+/// Scheduling tier of an API key's async proof jobs
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PriorityTier {
+    /// Wallet-interactive traffic; runs first
+    Interactive,
+    #[default]
+    Standard,
+    /// Bulk and backfill traffic; runs when nothing else is waiting
+    Batch,
+}
+
+impl ApiKeyConfig {
+    /// Label for logs, never the key itself
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or("unnamed")
+    }
+
+    /// Whether the key may use endpoints in `scope`
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&scope))
+    }
+}
+
+/// Group of endpoints an API key can be granted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiScope {
+    /// POST /v1/proofs/pubkey-validity
+    PubkeyValidity,
+    /// POST /v1/proofs/equality
+    Equality,
+    /// POST /v1/proofs/range
+    Range,
+    /// POST /v1/proofs/transfer
+    Transfer,
+    /// POST /v1/proofs/zero-ciphertext
+    ZeroCiphertext,
+    /// POST /v1/proofs/withdraw-bundle
+    WithdrawBundle,
+    /// /v1/balance/*
+    Balance,
+    /// /v1/tx/* (transaction builder)
+    Tx,
+    /// /v1/keys/* (key derivation)
+    Keys,
+    /// /v1/jobs/*; submitting also needs the scope of the job kind
+    Jobs,
+    /// /v1/relay/* (transaction relayer)
+    Relay,
+}
+
+impl ApiScope {
+    /// Whether the scope is a synchronous proof endpoint
+    pub fn is_proof(self) -> bool {
+        !matches!(
+            self,
+            Self::Balance | Self::Tx | Self::Keys | Self::Jobs | Self::Relay
+        )
+    }
+
+    /// Scope covering a request path, if any
+    pub fn for_path(path: &str) -> Option<Self> {
+        if let Some(proof) = path.strip_prefix("/v1/proofs/") {
+            return match proof {
+                "pubkey-validity" | "pubkey-validity/batch" => Some(Self::PubkeyValidity),
+                "equality" => Some(Self::Equality),
+                "range" => Some(Self::Range),
+                "transfer" | "transfer-package" => Some(Self::Transfer),
+                "zero-ciphertext" => Some(Self::ZeroCiphertext),
+                "withdraw-bundle" => Some(Self::WithdrawBundle),
+                _ => None,
+            };
+        }
+
+        if path.starts_with("/v1/balance/") {
+            Some(Self::Balance)
+        } else if path.starts_with("/v1/tx/") {
+            Some(Self::Tx)
+        } else if path.starts_with("/v1/keys/") {
+            Some(Self::Keys)
+        } else if path == "/v1/jobs" || path.starts_with("/v1/jobs/") {
+            Some(Self::Jobs)
+        } else if path.starts_with("/v1/relay/") {
+            Some(Self::Relay)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: 3001,
+            cors_origins: vec!["http://localhost:3000".to_string()],
+            api_keys: vec![],
+            admin_api_key: None,
+            jwt: None,
+            timestamp_tolerance_secs: 300, // 5 minutes
+            replay_cache_capacity: 100_000,
+            pubkey_proof_cache_ttl_secs: 0,
+            pubkey_proof_cache_capacity: 10_000,
+            otlp_endpoint: None,
+            svs2_program_id: DEFAULT_SVS2_PROGRAM_ID.to_string(),
+            job_workers: 4,
+            job_ttl_secs: 600, // 10 minutes
+            job_max_wait_secs: 30,
+            durable_jobs: false,
+            job_store_key: None,
+            job_database_url: None,
+            shutdown_grace_secs: 30,
+            service_keypair_path: None,
+            sealing_key_rotation_secs: 3600, // 1 hour
+            require_sealed_secrets: false,
+            self_test_interval_secs: 30,
+            legacy_api_enabled: true,
+            legacy_api_sunset: None,
+            rpc_url: None,
+            max_in_flight_requests: 256,
+            max_concurrent_proofs: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+            proof_timeout_secs: 60,
+            request_timeout_secs: 10,
+            max_body_bytes: 64 * 1024,
+            max_batch_body_bytes: 1024 * 1024,
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_client_ca_path: None,
+            allow_plaintext: false,
+            redis_url: None,
+            redis_key_prefix: "svs-proof-backend".to_string(),
+            relayer: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from `CONFIG_FILE` (if set) and environment variables
+    pub fn load() -> anyhow::Result<Self> {
+        let mut config = match std::env::var("CONFIG_FILE") {
+            Ok(path) => {
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read config file {path}: {e}"))?;
+                Self::from_toml(&contents)
+                    .map_err(|e| anyhow::anyhow!("Invalid config file {path}: {e}"))?
+            }
+            Err(_) => Self::default(),
+        };
+        config.apply_env();
+
+        Ok(config)
+    }
+
+    /// Parse a TOML config file; omitted settings take their defaults
+    pub fn from_toml(contents: &str) -> std::result::Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    /// Override settings with any environment variables that are set
+    fn apply_env(&mut self) {
+        if let Some(port) = env_parse("PORT") {
+            self.port = port;
+        }
+
+        if let Ok(s) = std::env::var("CORS_ORIGINS") {
+            self.cors_origins = s.split(',').map(|s| s.trim().to_string()).collect();
+        }
+
+        if let Ok(s) = std::env::var("API_KEYS") {
+            self.api_keys = s
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|key| ApiKeyConfig {
+                    key: key.to_string(),
+                    name: None,
+                    scopes: None,
+                    quota: None,
+                    priority: PriorityTier::default(),
+                })
+                .collect();
+        }
+
+        if let Ok(key) = std::env::var("ADMIN_API_KEY") {
+            self.admin_api_key = Some(key).filter(|s| !s.is_empty());
+        }
+
+        if let Some(secs) = env_parse("TIMESTAMP_TOLERANCE_SECS") {
+            self.timestamp_tolerance_secs = secs;
+        }
+
+        if let Some(capacity) = env_parse("REPLAY_CACHE_CAPACITY") {
+            self.replay_cache_capacity = capacity;
+        }
+
+        if let Some(secs) = env_parse("PUBKEY_PROOF_CACHE_TTL_SECS") {
+            self.pubkey_proof_cache_ttl_secs = secs;
+        }
+
+        if let Some(capacity) = env_parse("PUBKEY_PROOF_CACHE_CAPACITY") {
+            self.pubkey_proof_cache_capacity = capacity;
+        }
+
+        if let Ok(endpoint) = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+            self.otlp_endpoint = Some(endpoint).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(program_id) = std::env::var("SVS2_PROGRAM_ID") {
+            self.svs2_program_id = program_id;
+        }
+
+        if let Some(workers) = env_parse("JOB_WORKERS") {
+            self.job_workers = workers;
+        }
+
+        if let Some(secs) = env_parse("JOB_TTL_SECS") {
+            self.job_ttl_secs = secs;
+        }
+
+        if let Some(secs) = env_parse("JOB_MAX_WAIT_SECS") {
+            self.job_max_wait_secs = secs;
+        }
+
+        if let Some(durable) = env_parse("DURABLE_JOBS") {
+            self.durable_jobs = durable;
+        }
+
+        if let Ok(key) = std::env::var("JOB_STORE_KEY") {
+            self.job_store_key = Some(key).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(url) = std::env::var("JOB_DATABASE_URL") {
+            self.job_database_url = Some(url).filter(|s| !s.is_empty());
+        }
+
+        if let Some(secs) = env_parse("SHUTDOWN_GRACE_SECS") {
+            self.shutdown_grace_secs = secs;
+        }
+
+        if let Ok(path) = std::env::var("SERVICE_KEYPAIR_PATH") {
+            self.service_keypair_path = Some(path).filter(|s| !s.is_empty());
+        }
+
+        if let Some(secs) = env_parse("SEALING_KEY_ROTATION_SECS") {
+            self.sealing_key_rotation_secs = secs;
+        }
+
+        if let Some(required) = env_parse("REQUIRE_SEALED_SECRETS") {
+            self.require_sealed_secrets = required;
+        }
+
+        if let Some(secs) = env_parse("SELF_TEST_INTERVAL_SECS") {
+            self.self_test_interval_secs = secs;
+        }
+
+        if let Some(enabled) = env_parse("LEGACY_API_ENABLED") {
+            self.legacy_api_enabled = enabled;
+        }
+
+        if let Ok(sunset) = std::env::var("LEGACY_API_SUNSET") {
+            self.legacy_api_sunset = Some(sunset).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(url) = std::env::var("SOLANA_RPC_URL") {
+            self.rpc_url = Some(url).filter(|s| !s.is_empty());
+        }
+
+        if let Some(max) = env_parse("MAX_IN_FLIGHT_REQUESTS") {
+            self.max_in_flight_requests = max;
+        }
+
+        if let Some(max) = env_parse("MAX_CONCURRENT_PROOFS") {
+            self.max_concurrent_proofs = max;
+        }
+
+        if let Some(secs) = env_parse("PROOF_TIMEOUT_SECS") {
+            self.proof_timeout_secs = secs;
+        }
+
+        if let Some(secs) = env_parse("REQUEST_TIMEOUT_SECS") {
+            self.request_timeout_secs = secs;
+        }
+
+        if let Some(bytes) = env_parse("MAX_BODY_BYTES") {
+            self.max_body_bytes = bytes;
+        }
+
+        if let Some(bytes) = env_parse("MAX_BATCH_BODY_BYTES") {
+            self.max_batch_body_bytes = bytes;
+        }
+
+        if let Ok(path) = std::env::var("TLS_CERT_PATH") {
+            self.tls_cert_path = Some(path).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(path) = std::env::var("TLS_KEY_PATH") {
+            self.tls_key_path = Some(path).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(path) = std::env::var("TLS_CLIENT_CA_PATH") {
+            self.tls_client_ca_path = Some(path).filter(|s| !s.is_empty());
+        }
+
+        if let Some(allow) = env_parse("ALLOW_PLAINTEXT") {
+            self.allow_plaintext = allow;
+        }
+
+        if let Ok(url) = std::env::var("REDIS_URL") {
+            self.redis_url = Some(url).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(prefix) = std::env::var("REDIS_KEY_PREFIX") {
+            self.redis_key_prefix = prefix;
+        }
+
+        // Any JWT variable enables JWT authentication
+        if let Ok(secret) = std::env::var("JWT_SECRET") {
+            self.jwt.get_or_insert_with(JwtConfig::default).secret =
+                Some(secret).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(url) = std::env::var("JWT_JWKS_URL") {
+            self.jwt.get_or_insert_with(JwtConfig::default).jwks_url =
+                Some(url).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(issuer) = std::env::var("JWT_ISSUER") {
+            self.jwt.get_or_insert_with(JwtConfig::default).issuer =
+                Some(issuer).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(audience) = std::env::var("JWT_AUDIENCE") {
+            self.jwt.get_or_insert_with(JwtConfig::default).audience =
+                Some(audience).filter(|s| !s.is_empty());
+        }
+
+        if let Ok(claim) = std::env::var("JWT_SCOPE_CLAIM") {
+            self.jwt.get_or_insert_with(JwtConfig::default).scope_claim = claim;
+        }
+
+        if let Ok(prefix) = std::env::var("JWT_SCOPE_PREFIX") {
+            self.jwt.get_or_insert_with(JwtConfig::default).scope_prefix = prefix;
+        }
+
+        // Any relayer variable enables the relayer
+        if let Ok(path) = std::env::var("RELAYER_KEYPAIR_PATH") {
+            self.relayer
+                .get_or_insert_with(RelayerConfig::default)
+                .keypair_path = Some(path).filter(|s| !s.is_empty());
+        }
+
+        if let Some(lamports) = env_parse("RELAYER_MAX_LAMPORTS_PER_TX") {
+            self.relayer
+                .get_or_insert_with(RelayerConfig::default)
+                .max_lamports_per_transaction = lamports;
+        }
+
+        if let Some(lamports) = env_parse("RELAYER_DAILY_LAMPORTS_PER_KEY") {
+            self.relayer
+                .get_or_insert_with(RelayerConfig::default)
+                .daily_lamports_per_key = lamports;
+        }
+
+        if let Some(lamports) = env_parse("RELAYER_DAILY_LAMPORTS") {
+            self.relayer
+                .get_or_insert_with(RelayerConfig::default)
+                .daily_lamports = lamports;
+        }
+
+        if let Ok(s) = std::env::var("RELAYER_ALLOWED_PROGRAMS") {
+            let programs = s
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            self.relayer
+                .get_or_insert_with(RelayerConfig::default)
+                .allowed_programs = Some(programs);
+        }
+    }
+}
+
+/// Parse an environment variable, ignoring it if unset or malformed
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_partial() {
+        let config = Config::from_toml(
+            r#"
+            port = 8080
+            job_workers = 2
+
+            [[api_keys]]
+            key = "abc"
+            name = "wallet-team"
+            scopes = ["pubkey-validity", "jobs"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.job_workers, 2);
+        assert_eq!(config.api_keys[0].label(), "wallet-team");
+        assert!(config.api_keys[0].allows(ApiScope::Jobs));
+        assert!(!config.api_keys[0].allows(ApiScope::Tx));
+        // Unset values keep their defaults
+        assert_eq!(config.timestamp_tolerance_secs, 300);
+        assert_eq!(config.svs2_program_id, DEFAULT_SVS2_PROGRAM_ID);
+    }
+
+    #[test]
+    fn test_example_config_file_parses() {
+        let config = Config::from_toml(include_str!("../config.example.toml")).unwrap();
+
+        assert_eq!(config.port, 3001);
+        assert!(config.api_keys.is_empty());
+    }
+
+    #[test]
+    fn test_config_file_rejects_unknown_keys() {
+        assert!(Config::from_toml("prot = 8080").is_err());
     }
 }
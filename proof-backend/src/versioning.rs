@@ -0,0 +1,242 @@
+//! API versioning
+//!
+//! Endpoints live under `/v{n}/`, so request and response schemas can change
+//! in a new version while existing integrations keep the one they were
+//! written against. Every versioned response carries an `API-Version` header.
+//!
+//! The unversioned `/api/*` paths from before versioning are aliases of
+//! `/v1/*`. Their responses are marked deprecated (`Deprecation`, a `Link` to
+//! the successor path, and `Sunset` when configured) so clients can migrate.
+//!
+//! Clients may send `API-Version` to pin a version: a legacy path is served
+//! by that version, and a versioned path that disagrees with it is rejected.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{uri::PathAndQuery, HeaderMap, HeaderValue, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::DateTime;
+
+use crate::error::{BackendError, Result};
+
+/// Request and response header naming the API version
+pub const API_VERSION_HEADER: &str = "api-version";
+
+/// Versions this server implements
+pub const SUPPORTED_VERSIONS: &[u32] = &[1];
+
+/// Prefix of the unversioned paths served before `/v1`
+const LEGACY_PREFIX: &str = "/api/";
+
+/// Version the legacy paths map to when the client does not pin one
+const LEGACY_VERSION: u32 = 1;
+
+/// How legacy paths are handled
+#[derive(Clone)]
+pub struct VersionPolicy {
+    legacy_enabled: bool,
+    sunset: Option<HeaderValue>,
+}
+
+impl VersionPolicy {
+    /// Serve legacy paths if `legacy_enabled`, announcing the RFC 3339
+    /// `sunset` time if given
+    pub fn new(legacy_enabled: bool, sunset: Option<&str>) -> anyhow::Result<Self> {
+        let sunset = sunset
+            .map(|s| {
+                let time = DateTime::parse_from_rfc3339(s)
+                    .map_err(|e| anyhow::anyhow!("Invalid legacy API sunset {s}: {e}"))?;
+                let http_date = time.to_utc().format("%a, %d %b %Y %H:%M:%S GMT");
+                Ok::<_, anyhow::Error>(HeaderValue::from_str(&http_date.to_string())?)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            legacy_enabled,
+            sunset,
+        })
+    }
+}
+
+/// Route legacy paths to their versioned equivalent and label responses
+///
+/// Runs outside the router so rewritten paths are routed, authenticated and
+/// scoped exactly like the versioned ones.
+pub async fn version_middleware(
+    State(policy): State<VersionPolicy>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let legacy_path = path
+        .strip_prefix(LEGACY_PREFIX)
+        .filter(|_| policy.legacy_enabled)
+        .map(str::to_string);
+    let path_version = path_version(path);
+    if legacy_path.is_none() && path_version.is_none() {
+        // Health checks and docs are unversioned
+        return next.run(request).await;
+    }
+
+    let requested = match requested_version(request.headers()) {
+        Ok(requested) => requested,
+        Err(e) => return e.into_response(),
+    };
+    let (version, successor) = match (path_version, legacy_path) {
+        (Some(version), _) => {
+            if let Some(requested) = requested.filter(|r| *r != version) {
+                return BackendError::BadRequest(format!(
+                    "API-Version {requested} does not match the v{version} path"
+                ))
+                .into_response();
+            }
+            (version, None)
+        }
+        (None, rest) => {
+            let version = requested.unwrap_or(LEGACY_VERSION);
+            let successor = format!("/v{version}/{}", rest.unwrap_or_default());
+            *request.uri_mut() = with_path(request.uri(), &successor);
+            (version, Some(successor))
+        }
+    };
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(API_VERSION_HEADER, HeaderValue::from(version));
+    if let Some(successor) = successor {
+        headers.insert("deprecation", HeaderValue::from_static("true"));
+        if let Ok(link) =
+            HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\""))
+        {
+            headers.insert("link", link);
+        }
+        if let Some(sunset) = &policy.sunset {
+            headers.insert("sunset", sunset.clone());
+        }
+    }
+
+    response
+}
+
+/// Version pinned by the `API-Version` request header (`1` or `v1`)
+fn requested_version(headers: &HeaderMap) -> Result<Option<u32>> {
+    let Some(value) = headers.get(API_VERSION_HEADER) else {
+        return Ok(None);
+    };
+
+    let value = value.to_str().unwrap_or_default().trim();
+    value
+        .trim_start_matches('v')
+        .parse()
+        .ok()
+        .filter(|version| SUPPORTED_VERSIONS.contains(version))
+        .map(Some)
+        .ok_or_else(|| {
+            BackendError::BadRequest(format!(
+                "Unsupported API-Version {value:?}; supported: {SUPPORTED_VERSIONS:?}"
+            ))
+        })
+}
+
+/// Version of a `/v{n}/...` path
+fn path_version(path: &str) -> Option<u32> {
+    let (version, _) = path.strip_prefix("/v")?.split_once('/')?;
+    version.parse().ok()
+}
+
+/// `uri` with its path replaced, keeping the query
+fn with_path(uri: &Uri, path: &str) -> Uri {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = PathAndQuery::try_from(path_and_query).ok();
+    Uri::from_parts(parts).unwrap_or_else(|_| uri.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, middleware, routing::get, Router};
+    use tower::{Layer, ServiceExt};
+
+    async fn send(policy: VersionPolicy, request: Request<Body>) -> Response {
+        let router = Router::new()
+            .route("/v1/usage", get(|| async { "usage" }))
+            .route("/health", get(|| async { "healthy" }));
+        middleware::from_fn_with_state(policy, version_middleware)
+            .layer(router)
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    async fn get_with(path: &str, version: Option<&str>) -> Response {
+        let policy = VersionPolicy::new(true, Some("2027-06-30T00:00:00Z")).unwrap();
+        let mut request = Request::get(path);
+        if let Some(version) = version {
+            request = request.header(API_VERSION_HEADER, version);
+        }
+        send(policy, request.body(Body::empty()).unwrap()).await
+    }
+
+    #[tokio::test]
+    async fn test_versioned_path() {
+        let response = get_with("/v1/usage", None).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[API_VERSION_HEADER], "1");
+        assert!(response.headers().get("deprecation").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_path_deprecated() {
+        let response = get_with("/api/usage?window=1", None).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(
+            response.headers()["link"],
+            "</v1/usage>; rel=\"successor-version\""
+        );
+        assert_eq!(
+            response.headers()["sunset"],
+            "Wed, 30 Jun 2027 00:00:00 GMT"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_legacy_path_disabled() {
+        let policy = VersionPolicy::new(false, None).unwrap();
+        let request = Request::get("/api/usage").body(Body::empty()).unwrap();
+
+        let response = send(policy, request).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_version_negotiation() {
+        assert_eq!(
+            get_with("/v1/usage", Some("v1")).await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            get_with("/v1/usage", Some("2")).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        // Unversioned endpoints are untouched
+        let health = get_with("/health", Some("v9")).await;
+        assert_eq!(health.status(), StatusCode::OK);
+        assert!(health.headers().get(API_VERSION_HEADER).is_none());
+    }
+
+    #[test]
+    fn test_invalid_sunset_rejected() {
+        assert!(VersionPolicy::new(true, Some("next year")).is_err());
+    }
+}
@@ -27,6 +27,7 @@ import {
   setupTest,
   getVaultPDA,
   getSharesMintPDA,
+  getConfigPDA,
   explorerUrl,
   accountUrl,
   ASSET_DECIMALS,
@@ -74,6 +75,7 @@ async function main() {
 
   const vaultId = new BN(Date.now());
   const [vault] = getVaultPDA(programId, assetMint, vaultId);
+  const [config] = getConfigPDA(programId);
   const [sharesMint] = getSharesMintPDA(programId, vault);
   const assetVault = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault });
   const userSharesAccount = getAssociatedTokenAddressSync(
@@ -89,10 +91,10 @@ async function main() {
   console.log("-".repeat(70));
 
   const initTx = await program.methods
-    .initialize(vaultId, "SVS-1 Test Vault", "svVAULT", "https://arweave.net/vault-metadata")
+    .initialize(vaultId, "SVS-1 Test Vault", "svVAULT", "https://arweave.net/vault-metadata", false)
     .accountsStrict({
       authority: payer.publicKey,
-      vault, assetMint, sharesMint, assetVault,
+      vault, config, assetMint, sharesMint, assetVault,
       assetTokenProgram: TOKEN_PROGRAM_ID,
       token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -119,6 +121,7 @@ async function main() {
       token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
+      config,
     })
     .rpc();
 
@@ -141,6 +144,7 @@ async function main() {
       vault, assetMint, userAssetAccount, assetVault, sharesMint, userSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID,
       token2022Program: TOKEN_2022_PROGRAM_ID,
+      config,
     })
     .rpc();
 
@@ -162,6 +166,7 @@ async function main() {
         user: payer.publicKey, vault, assetMint, userAssetAccount, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       }).rpc();
     console.log("  ERROR: Deposit should have failed when paused!");
   } catch (err: any) {
@@ -27,7 +27,7 @@ import {
 } from "@solana/spl-token";
 import { Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
 import { Svs1 } from "../../target/types/svs_1";
-import { setupTest, getVaultPDA, getSharesMintPDA, fundAccount, ASSET_DECIMALS } from "./helpers";
+import { setupTest, getVaultPDA, getSharesMintPDA, getConfigPDA, fundAccount, ASSET_DECIMALS } from "./helpers";
 import * as fs from "fs";
 import * as path from "path";
 
@@ -67,6 +67,7 @@ async function main() {
 
   const vaultId = new BN(Date.now());
   const [vault] = getVaultPDA(programId, assetMint, vaultId);
+  const [config] = getConfigPDA(programId);
   const [sharesMint] = getSharesMintPDA(programId, vault);
   const assetVault = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault });
   const userSharesAccount = getAssociatedTokenAddressSync(
@@ -74,9 +75,9 @@ async function main() {
   );
 
   await program.methods
-    .initialize(vaultId, "Edge Case Test Vault", "EDGE", "https://test.com")
+    .initialize(vaultId, "Edge Case Test Vault", "EDGE", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault, assetMint, sharesMint, assetVault,
+      authority: payer.publicKey, vault, config, assetMint, sharesMint, assetVault,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -91,6 +92,7 @@ async function main() {
       assetVault, sharesMint, userSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .rpc();
 
@@ -108,6 +110,7 @@ async function main() {
         assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       }).rpc();
     console.log("  ❌ FAILED: Should have rejected"); results.push({ name: "Zero amount", passed: false });
   } catch {
@@ -148,6 +151,7 @@ async function main() {
         assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       }).rpc();
     console.log("  ❌ FAILED: Should have rejected"); results.push({ name: "Deposit when paused", passed: false });
   } catch (err: any) {
@@ -173,6 +177,7 @@ async function main() {
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address,
         assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+        config,
       }).rpc();
     console.log("  ❌ FAILED: Should have rejected"); results.push({ name: "Excess redeem", passed: false });
   } catch {
@@ -213,9 +218,9 @@ async function main() {
   const assetVault2 = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault2 });
 
   await program.methods
-    .initialize(vaultId2, "Second Vault", "VAULT2", "https://test.com")
+    .initialize(vaultId2, "Second Vault", "VAULT2", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault: vault2, assetMint, sharesMint: sharesMint2, assetVault: assetVault2,
+      authority: payer.publicKey, vault: vault2, config, assetMint, sharesMint: sharesMint2, assetVault: assetVault2,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -233,6 +238,7 @@ async function main() {
       assetVault: assetVault2, sharesMint: sharesMint2, userSharesAccount: userSharesAccount2,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .rpc();
 
@@ -33,6 +33,10 @@ export function getSharesMintPDA(programId: PublicKey, vault: PublicKey): [Publi
   );
 }
 
+export function getConfigPDA(programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([Buffer.from("config")], programId);
+}
+
 export function explorerUrl(signature: string): string {
   return `https://explorer.solana.com/tx/${signature}?cluster=devnet`;
 }
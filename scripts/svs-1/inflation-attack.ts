@@ -23,7 +23,7 @@ import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { setupTest, getVaultPDA, getSharesMintPDA, fundAccounts, ASSET_DECIMALS } from "./helpers";
+import { setupTest, getVaultPDA, getSharesMintPDA, getConfigPDA, fundAccounts, ASSET_DECIMALS } from "./helpers";
 
 async function main() {
   const { connection, payer, program, programId } = await setupTest("Inflation/Donation Attack");
@@ -71,13 +71,14 @@ async function main() {
   console.log("\n--- Initializing vault ---");
   const vaultId = new BN(Date.now());
   const [vault] = getVaultPDA(programId, assetMint, vaultId);
+  const [config] = getConfigPDA(programId);
   const [sharesMint] = getSharesMintPDA(programId, vault);
   const assetVault = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault });
 
   await program.methods
-    .initialize(vaultId, "Inflation Test Vault", "INFLAT", "https://test.com")
+    .initialize(vaultId, "Inflation Test Vault", "INFLAT", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault, assetMint, sharesMint, assetVault,
+      authority: payer.publicKey, vault, config, assetMint, sharesMint, assetVault,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -104,6 +105,7 @@ async function main() {
       userSharesAccount: attackerSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .signers([attacker])
     .rpc();
@@ -142,6 +144,7 @@ async function main() {
       userSharesAccount: victimSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .signers([victim])
     .rpc();
@@ -196,6 +199,7 @@ async function main() {
       userAssetAccount: victimAta.address, assetVault, sharesMint,
       userSharesAccount: victimSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+      config,
     })
     .signers([victim])
     .rpc();
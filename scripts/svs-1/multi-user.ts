@@ -23,7 +23,7 @@ import {
   getMint,
 } from "@solana/spl-token";
 import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { setupTest, getVaultPDA, getSharesMintPDA, fundAccounts, ASSET_DECIMALS, SHARE_DECIMALS } from "./helpers";
+import { setupTest, getVaultPDA, getSharesMintPDA, getConfigPDA, fundAccounts, ASSET_DECIMALS, SHARE_DECIMALS } from "./helpers";
 
 interface UserState {
   name: string;
@@ -82,13 +82,14 @@ async function main() {
   console.log("\n--- Initializing vault ---");
   const vaultId = new BN(Date.now());
   const [vault] = getVaultPDA(programId, assetMint, vaultId);
+  const [config] = getConfigPDA(programId);
   const [sharesMint] = getSharesMintPDA(programId, vault);
   const assetVault = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault });
 
   await program.methods
-    .initialize(vaultId, "Multi-User Test Vault", "MULTI", "https://test.com")
+    .initialize(vaultId, "Multi-User Test Vault", "MULTI", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault, assetMint, sharesMint, assetVault,
+      authority: payer.publicKey, vault, config, assetMint, sharesMint, assetVault,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -118,6 +119,7 @@ async function main() {
         userSharesAccount: user.sharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       })
       .signers([user.keypair])
       .rpc();
@@ -162,6 +164,7 @@ async function main() {
         userAssetAccount: user.assetAccount, assetVault, sharesMint,
         userSharesAccount: user.sharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+        config,
       })
       .signers([user.keypair])
       .rpc();
@@ -23,7 +23,7 @@ import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { setupTest, getVaultPDA, getSharesMintPDA, ASSET_DECIMALS } from "./helpers";
+import { setupTest, getVaultPDA, getSharesMintPDA, getConfigPDA, ASSET_DECIMALS } from "./helpers";
 
 async function main() {
   const { connection, payer, program, programId } = await setupTest("Slippage Protection");
@@ -43,6 +43,7 @@ async function main() {
 
   const vaultId = new BN(Date.now());
   const [vault] = getVaultPDA(programId, assetMint, vaultId);
+  const [config] = getConfigPDA(programId);
   const [sharesMint] = getSharesMintPDA(programId, vault);
   const assetVault = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault });
   const userSharesAccount = getAssociatedTokenAddressSync(
@@ -50,9 +51,9 @@ async function main() {
   );
 
   await program.methods
-    .initialize(vaultId, "Slippage Test Vault", "SLIP", "https://test.com")
+    .initialize(vaultId, "Slippage Test Vault", "SLIP", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault, assetMint, sharesMint, assetVault,
+      authority: payer.publicKey, vault, config, assetMint, sharesMint, assetVault,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -66,6 +67,7 @@ async function main() {
       user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .rpc();
 
@@ -86,6 +88,7 @@ async function main() {
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       })
       .rpc();
     console.log("  ❌ FAILED: Should have reverted"); failed++;
@@ -109,6 +112,7 @@ async function main() {
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       })
       .rpc();
     console.log("  ✅ PASSED: Deposit succeeded"); passed++;
@@ -128,6 +132,7 @@ async function main() {
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       })
       .rpc();
     console.log("  ❌ FAILED: Should have reverted"); failed++;
@@ -146,6 +151,7 @@ async function main() {
       .accountsStrict({
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+        config,
       })
       .rpc();
     console.log("  ❌ FAILED: Should have reverted"); failed++;
@@ -167,6 +173,7 @@ async function main() {
       .accountsStrict({
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+        config,
       })
       .rpc();
     console.log("  ❌ FAILED: Should have reverted"); failed++;
@@ -185,6 +192,7 @@ async function main() {
       .accountsStrict({
         user: payer.publicKey, vault, assetMint, userAssetAccount: userAta.address, assetVault, sharesMint, userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+        config,
       })
       .rpc();
     console.log("  ✅ PASSED: Redeem succeeded"); passed++;
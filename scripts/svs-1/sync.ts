@@ -26,7 +26,7 @@ import {
   getMint,
 } from "@solana/spl-token";
 import { Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { setupTest, getVaultPDA, getSharesMintPDA, fundAccounts, ASSET_DECIMALS, SHARE_DECIMALS } from "./helpers";
+import { setupTest, getVaultPDA, getSharesMintPDA, getConfigPDA, fundAccounts, ASSET_DECIMALS, SHARE_DECIMALS } from "./helpers";
 
 async function main() {
   const { connection, payer, program, programId } = await setupTest("Sync Function Analysis");
@@ -49,6 +49,7 @@ async function main() {
 
   const vaultId = new BN(Date.now());
   const [vault] = getVaultPDA(programId, assetMint, vaultId);
+  const [config] = getConfigPDA(programId);
   const [sharesMint] = getSharesMintPDA(programId, vault);
   const assetVault = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault });
   const userSharesAccount = getAssociatedTokenAddressSync(
@@ -56,9 +57,9 @@ async function main() {
   );
 
   await program.methods
-    .initialize(vaultId, "Sync Test Vault", "SYNC", "https://test.com")
+    .initialize(vaultId, "Sync Test Vault", "SYNC", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault, assetMint, sharesMint, assetVault,
+      authority: payer.publicKey, vault, config, assetMint, sharesMint, assetVault,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -73,6 +74,7 @@ async function main() {
       assetVault, sharesMint, userSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .rpc();
 
@@ -154,9 +156,9 @@ async function main() {
   const assetVault2 = anchor.utils.token.associatedAddress({ mint: assetMint, owner: vault2 });
 
   await program.methods
-    .initialize(vaultId2, "Attack Test Vault", "ATTACK", "https://test.com")
+    .initialize(vaultId2, "Attack Test Vault", "ATTACK", "https://test.com", false)
     .accountsStrict({
-      authority: payer.publicKey, vault: vault2, assetMint, sharesMint: sharesMint2, assetVault: assetVault2,
+      authority: payer.publicKey, vault: vault2, config, assetMint, sharesMint: sharesMint2, assetVault: assetVault2,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
@@ -179,6 +181,7 @@ async function main() {
       userSharesAccount: attackerSharesAccount,
       assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      config,
     })
     .signers([attacker])
     .rpc();
@@ -233,6 +236,7 @@ async function main() {
         userSharesAccount: victimSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        config,
       })
       .signers([victim])
       .rpc();
@@ -274,6 +278,7 @@ async function main() {
         userAssetAccount: victimAta.address, assetVault: assetVault2, sharesMint: sharesMint2,
         userSharesAccount: victimSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID, token2022Program: TOKEN_2022_PROGRAM_ID,
+        config,
       })
       .signers([victim])
       .rpc();
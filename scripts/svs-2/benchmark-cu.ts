@@ -0,0 +1,115 @@
+/**
+ * SVS-2 Compute Unit Benchmark
+ *
+ * Measures compute units per instruction path from transactions that actually
+ * landed on a cluster (e.g. after `anchor test` against a local validator, or a
+ * devnet run of the proof-backend flows). Transactions are classified by the
+ * Anchor discriminator of their SVS-2 instruction; CU is the transaction total
+ * as reported in `meta.computeUnitsConsumed`.
+ *
+ * Run:
+ *   RPC_URL=http://127.0.0.1:8899 npx ts-node scripts/svs-2/benchmark-cu.ts [limit]
+ *
+ * Output is a markdown table suitable for pasting into docs/PRIVACY.md.
+ */
+
+import { Connection, PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
+import * as fs from "fs";
+import * as path from "path";
+
+const RPC_URL = process.env.RPC_URL || "http://127.0.0.1:8899";
+const IDL_PATH = path.join(__dirname, "../../target/idl/svs_2.json");
+const DEFAULT_LIMIT = 500;
+
+interface PathStats {
+  samples: number[];
+  withProofIx: number;
+}
+
+function discriminator(name: string): string {
+  return createHash("sha256")
+    .update(`global:${name}`)
+    .digest()
+    .subarray(0, 8)
+    .toString("hex");
+}
+
+function loadIdl(): { programId: PublicKey; names: Map<string, string> } {
+  const idl = JSON.parse(fs.readFileSync(IDL_PATH, "utf-8"));
+  const names = new Map<string, string>();
+  for (const ix of idl.instructions) {
+    names.set(discriminator(ix.name), ix.name);
+  }
+  return { programId: new PublicKey(idl.address), names };
+}
+
+function percentile(sorted: number[], p: number): number {
+  const idx = Math.min(sorted.length - 1, Math.floor((p / 100) * sorted.length));
+  return sorted[idx];
+}
+
+async function main() {
+  const limit = Number(process.argv[2] || DEFAULT_LIMIT);
+  const { programId, names } = loadIdl();
+  const connection = new Connection(RPC_URL, "confirmed");
+
+  console.log(`Scanning last ${limit} transactions for ${programId.toBase58()} on ${RPC_URL}`);
+
+  const signatures = await connection.getSignaturesForAddress(programId, { limit });
+  const stats = new Map<string, PathStats>();
+
+  for (const { signature, err } of signatures) {
+    if (err) continue;
+
+    const tx = await connection.getTransaction(signature, {
+      maxSupportedTransactionVersion: 0,
+      commitment: "confirmed",
+    });
+    const units = tx?.meta?.computeUnitsConsumed;
+    if (!tx || units === undefined) continue;
+
+    const keys = tx.transaction.message.getAccountKeys();
+    const instructions = tx.transaction.message.compiledInstructions;
+    let hasProofIx = false;
+    let pathName: string | undefined;
+
+    for (const ix of instructions) {
+      const ixProgram = keys.get(ix.programIdIndex);
+      if (!ixProgram) continue;
+      if (ixProgram.equals(programId)) {
+        const disc = Buffer.from(ix.data).subarray(0, 8).toString("hex");
+        pathName = pathName ?? names.get(disc);
+      } else if (ixProgram.toBase58().startsWith("ZkE1Gama1Proof")) {
+        hasProofIx = true;
+      }
+    }
+    if (!pathName) continue;
+
+    const entry = stats.get(pathName) ?? { samples: [], withProofIx: 0 };
+    entry.samples.push(units);
+    if (hasProofIx) entry.withProofIx += 1;
+    stats.set(pathName, entry);
+  }
+
+  if (stats.size === 0) {
+    console.log("No successful SVS-2 transactions found. Run the test suite first.");
+    return;
+  }
+
+  console.log("\n| Instruction | Samples | Min CU | p50 CU | Max CU | Inline proof txs |");
+  console.log("|-------------|---------|--------|--------|--------|------------------|");
+  for (const [name, { samples, withProofIx }] of [...stats.entries()].sort()) {
+    const sorted = [...samples].sort((a, b) => a - b);
+    console.log(
+      `| ${name} | ${sorted.length} | ${sorted[0].toLocaleString()} | ` +
+        `${percentile(sorted, 50).toLocaleString()} | ` +
+        `${sorted[sorted.length - 1].toLocaleString()} | ${withProofIx} |`
+    );
+  }
+}
+
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
@@ -3,6 +3,7 @@ import { BN } from "@coral-xyz/anchor";
 
 export const VAULT_SEED = Buffer.from("vault");
 export const SHARES_MINT_SEED = Buffer.from("shares");
+export const CONFIG_SEED = Buffer.from("config");
 
 /**
  * Derive the vault PDA address
@@ -32,6 +33,13 @@ export function getSharesMintAddress(
   );
 }
 
+/**
+ * Derive the protocol config PDA address of a program
+ */
+export function getConfigAddress(programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([CONFIG_SEED], programId);
+}
+
 /**
  * Derive all vault-related addresses at once
  */
@@ -13,7 +13,7 @@ import {
   getMint,
 } from "@solana/spl-token";
 
-import { deriveVaultAddresses } from "./pda";
+import { deriveVaultAddresses, getConfigAddress } from "./pda";
 import * as math from "./math";
 
 /**
@@ -70,6 +70,8 @@ export interface CreateVaultParams {
   name: string;
   symbol: string;
   uri: string;
+  /** Soulbound shares that holders cannot transfer (default false) */
+  nonTransferable?: boolean;
 }
 
 export interface DepositParams {
@@ -202,10 +204,17 @@ export class SolanaVault {
     );
 
     await program.methods
-      .initialize(id, params.name, params.symbol, params.uri)
+      .initialize(
+        id,
+        params.name,
+        params.symbol,
+        params.uri,
+        params.nonTransferable ?? false,
+      )
       .accountsStrict({
         authority: provider.wallet.publicKey,
         vault: addresses.vault,
+        config: getConfigAddress(program.programId)[0],
         assetMint: params.assetMint,
         sharesMint: addresses.sharesMint,
         assetVault: assetVault,
@@ -292,6 +301,7 @@ export class SolanaVault {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigAddress(this.program.programId)[0],
       })
       .rpc();
   }
@@ -317,6 +327,7 @@ export class SolanaVault {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigAddress(this.program.programId)[0],
       })
       .rpc();
   }
@@ -340,6 +351,7 @@ export class SolanaVault {
         userSharesAccount,
         assetTokenProgram: this.assetTokenProgram,
         token2022Program: TOKEN_2022_PROGRAM_ID,
+        config: getConfigAddress(this.program.programId)[0],
       })
       .rpc();
   }
@@ -363,6 +375,7 @@ export class SolanaVault {
         userSharesAccount,
         assetTokenProgram: this.assetTokenProgram,
         token2022Program: TOKEN_2022_PROGRAM_ID,
+        config: getConfigAddress(this.program.programId)[0],
       })
       .rpc();
   }
@@ -0,0 +1,331 @@
+/* C ABI of the SVS client, see sdk/ffi/README.md */
+
+#ifndef SVS_H
+#define SVS_H
+
+/* Generated by cbindgen from sdk/ffi/src, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Outcome of a call
+typedef enum SvsStatus {
+  SVS_STATUS_OK = 0,
+  // A required pointer was NULL
+  SVS_STATUS_NULL_POINTER = 1,
+  // An argument was out of range or malformed
+  SVS_STATUS_INVALID_ARGUMENT = 2,
+  // Account data could not be decoded as the expected account
+  SVS_STATUS_INVALID_ACCOUNT = 3,
+  // Vault math overflowed or the vault is paused
+  SVS_STATUS_MATH = 4,
+  // Key derivation, encryption or decryption failed
+  SVS_STATUS_CRYPTO = 5,
+  // The library panicked; please report it
+  SVS_STATUS_PANIC = 6,
+} SvsStatus;
+
+// Encryption keys of one confidential token account, owned by the caller
+typedef struct SvsConfidentialKeys SvsConfidentialKeys;
+
+// An encoded instruction, owned by the caller
+typedef struct SvsInstruction SvsInstruction;
+
+// A 32-byte Solana public key
+typedef struct SvsPubkey {
+  uint8_t bytes[32];
+} SvsPubkey;
+
+// Bytes allocated by the library, e.g. a message to sign
+typedef struct SvsBuffer {
+  uint8_t *data;
+  size_t len;
+} SvsBuffer;
+
+// A 64-byte Ed25519 signature
+typedef struct SvsSignature {
+  uint8_t bytes[64];
+} SvsSignature;
+
+// An ElGamal public key
+typedef struct SvsElGamalPubkey {
+  uint8_t bytes[32];
+} SvsElGamalPubkey;
+
+// AE ciphertext of a decryptable balance
+typedef struct SvsDecryptableBalance {
+  uint8_t bytes[36];
+} SvsDecryptableBalance;
+
+// Arguments of `svs2_apply_pending`
+typedef struct SvsPendingApplication {
+  uint64_t new_available_balance;
+  SvsDecryptableBalance new_decryptable_available_balance;
+  // Credits included in the applied balance; later credits stay pending
+  uint64_t expected_pending_balance_credit_counter;
+} SvsPendingApplication;
+
+// One account of an instruction
+typedef struct SvsAccountMeta {
+  SvsPubkey pubkey;
+  bool is_signer;
+  bool is_writable;
+} SvsAccountMeta;
+
+// Accounts every instruction of one vault refers to, see `VaultKeys`
+typedef struct SvsVaultKeys {
+  // SVS-1 or SVS-2 program
+  SvsPubkey program_id;
+  SvsPubkey vault;
+  SvsPubkey asset_mint;
+  SvsPubkey shares_mint;
+  SvsPubkey asset_vault;
+  // SPL Token or Token-2022, owner of the asset mint
+  SvsPubkey asset_token_program;
+} SvsVaultKeys;
+
+// A 32-byte hash, e.g. of an off-chain memo
+typedef struct SvsHash {
+  uint8_t bytes[32];
+} SvsHash;
+
+// Vault state the views are computed from, see `VaultSnapshot`
+typedef struct SvsVaultSnapshot {
+  // Assets backing shares (SVS-2 excludes still-locked harvest profit)
+  uint64_t total_assets;
+  uint64_t total_shares;
+  uint8_t decimals_offset;
+  bool paused;
+} SvsVaultSnapshot;
+
+#ifdef __cplusplus
+extern "C" {
+#endif  // __cplusplus
+
+// Release a buffer returned by the library
+void svs_buffer_free(SvsBuffer buffer);
+
+// Message of the last failed call on this thread, or NULL
+//
+// The string is owned by the library and valid until the next call on the
+// same thread.
+const char *svs_last_error(void);
+
+// Message the wallet signs to derive the ElGamal keypair of
+// `token_account`; free it with `svs_buffer_free`
+SvsStatus svs_elgamal_key_message(const SvsPubkey *token_account, SvsBuffer *out);
+
+// Message the wallet signs to derive the AE key of `token_account`; free it
+// with `svs_buffer_free`
+SvsStatus svs_ae_key_message(const SvsPubkey *token_account, SvsBuffer *out);
+
+// Derive the keys of `token_account` from the wallet's signatures of the
+// two derivation messages
+SvsStatus svs_confidential_keys_from_signatures(const SvsSignature *elgamal_signature,
+                                                const SvsSignature *ae_signature,
+                                                const SvsPubkey *token_account,
+                                                SvsConfidentialKeys **out);
+
+// Release keys; NULL is ignored
+void svs_confidential_keys_free(SvsConfidentialKeys *keys);
+
+// ElGamal public key, to configure the token account with
+SvsStatus svs_confidential_keys_elgamal_pubkey(const SvsConfidentialKeys *keys,
+                                               SvsElGamalPubkey *out);
+
+// Decryptable balance of `balance`, e.g. 0 for `svs2_configure_account`
+SvsStatus svs_confidential_keys_encrypt_balance(const SvsConfidentialKeys *keys,
+                                                uint64_t balance,
+                                                SvsDecryptableBalance *out);
+
+// Decrypt a decryptable balance
+SvsStatus svs_confidential_keys_decrypt_balance(const SvsConfidentialKeys *keys,
+                                                const SvsDecryptableBalance *balance,
+                                                uint64_t *out);
+
+// Available balance of a shares account, from its account data
+SvsStatus svs_confidential_keys_available_balance(const SvsConfidentialKeys *keys,
+                                                  const uint8_t *token_account_data,
+                                                  size_t token_account_data_len,
+                                                  uint64_t *out);
+
+// Pending balance of a shares account, from its account data
+SvsStatus svs_confidential_keys_pending_balance(const SvsConfidentialKeys *keys,
+                                                const uint8_t *token_account_data,
+                                                size_t token_account_data_len,
+                                                uint64_t *out);
+
+// Arguments of `svs2_apply_pending` for a shares account, from its account
+// data
+SvsStatus svs_confidential_keys_apply_pending(const SvsConfidentialKeys *keys,
+                                              const uint8_t *token_account_data,
+                                              size_t token_account_data_len,
+                                              SvsPendingApplication *out);
+
+// Program the instruction invokes
+SvsStatus svs_instruction_program_id(const SvsInstruction *instruction, SvsPubkey *out);
+
+// Number of accounts of the instruction, 0 if it is NULL
+size_t svs_instruction_accounts_len(const SvsInstruction *instruction);
+
+// Account `index` of the instruction
+SvsStatus svs_instruction_account(const SvsInstruction *instruction,
+                                  size_t index,
+                                  SvsAccountMeta *out);
+
+// Data of the instruction, borrowed until it is freed
+SvsStatus svs_instruction_data(const SvsInstruction *instruction,
+                               const uint8_t **out_data,
+                               size_t *out_len);
+
+// Release an instruction; NULL is ignored
+void svs_instruction_free(SvsInstruction *instruction);
+
+// SVS-1 `deposit` of exactly `assets` for at least `min_shares_out` shares
+SvsStatus svs1_deposit(const SvsVaultKeys *keys,
+                       const SvsPubkey *user,
+                       uint64_t assets,
+                       uint64_t min_shares_out,
+                       SvsInstruction **out);
+
+// SVS-1 `mint` of exactly `shares` for at most `max_assets_in` assets
+SvsStatus svs1_mint(const SvsVaultKeys *keys,
+                    const SvsPubkey *user,
+                    uint64_t shares,
+                    uint64_t max_assets_in,
+                    SvsInstruction **out);
+
+// SVS-1 `withdraw` of exactly `assets` for at most `max_shares_in` shares
+SvsStatus svs1_withdraw(const SvsVaultKeys *keys,
+                        const SvsPubkey *user,
+                        uint64_t assets,
+                        uint64_t max_shares_in,
+                        SvsInstruction **out);
+
+// SVS-1 `redeem` of exactly `shares` for at least `min_assets_out` assets
+SvsStatus svs1_redeem(const SvsVaultKeys *keys,
+                      const SvsPubkey *user,
+                      uint64_t shares,
+                      uint64_t min_assets_out,
+                      SvsInstruction **out);
+
+// SVS-2 `configure_account` verifying the pubkey validity proof
+// `proof_instruction_offset` instructions away in the same transaction
+SvsStatus svs2_configure_account(const SvsVaultKeys *keys,
+                                 const SvsPubkey *user,
+                                 const SvsDecryptableBalance *decryptable_zero_balance,
+                                 int8_t proof_instruction_offset,
+                                 SvsInstruction **out);
+
+// SVS-2 `deposit`; `memo_hash` is optional
+SvsStatus svs2_deposit(const SvsVaultKeys *keys,
+                       const SvsPubkey *user,
+                       uint64_t assets,
+                       uint64_t min_shares_out,
+                       const SvsHash *memo_hash,
+                       SvsInstruction **out);
+
+// SVS-2 `apply_pending`, with the values of
+// `svs_confidential_keys_apply_pending`
+SvsStatus svs2_apply_pending(const SvsVaultKeys *keys,
+                             const SvsPubkey *user,
+                             const SvsDecryptableBalance *new_decryptable_available_balance,
+                             uint64_t expected_pending_balance_credit_counter,
+                             SvsInstruction **out);
+
+// SVS-2 `withdraw` proven by the equality and range proof context accounts
+SvsStatus svs2_withdraw(const SvsVaultKeys *keys,
+                        const SvsPubkey *user,
+                        const SvsPubkey *equality_proof_context,
+                        const SvsPubkey *range_proof_context,
+                        uint64_t assets,
+                        uint64_t max_shares_in,
+                        const SvsDecryptableBalance *new_decryptable_available_balance,
+                        SvsInstruction **out);
+
+// SVS-2 `redeem` proven by the equality and range proof context accounts
+SvsStatus svs2_redeem(const SvsVaultKeys *keys,
+                      const SvsPubkey *user,
+                      const SvsPubkey *equality_proof_context,
+                      const SvsPubkey *range_proof_context,
+                      uint64_t shares,
+                      uint64_t min_assets_out,
+                      const SvsDecryptableBalance *new_decryptable_available_balance,
+                      SvsInstruction **out);
+
+// Derive the accounts of vault `vault_id` for `asset_mint`, e.g. before
+// initializing it
+SvsStatus svs_vault_keys_derive(const SvsPubkey *program_id,
+                                const SvsPubkey *asset_mint,
+                                uint64_t vault_id,
+                                const SvsPubkey *asset_token_program,
+                                SvsVaultKeys *out);
+
+// Accounts recorded in the data of an SVS-1 or SVS-2 vault account
+SvsStatus svs_vault_keys_from_account(const SvsPubkey *program_id,
+                                      const SvsPubkey *vault,
+                                      const uint8_t *data,
+                                      size_t data_len,
+                                      const SvsPubkey *asset_token_program,
+                                      SvsVaultKeys *out);
+
+// Vault PDA of `asset_mint` and `vault_id`, and its bump
+SvsStatus svs_find_vault_address(const SvsPubkey *program_id,
+                                 const SvsPubkey *asset_mint,
+                                 uint64_t vault_id,
+                                 SvsPubkey *out_address,
+                                 uint8_t *out_bump);
+
+// Shares mint PDA of `vault`, and its bump
+SvsStatus svs_find_shares_mint_address(const SvsPubkey *program_id,
+                                       const SvsPubkey *vault,
+                                       SvsPubkey *out_address,
+                                       uint8_t *out_bump);
+
+// Shares account of `owner`: their Token-2022 associated token account
+SvsStatus svs_shares_account(const SvsVaultKeys *keys, const SvsPubkey *owner, SvsPubkey *out);
+
+// Asset token account of `owner`: their associated token account
+SvsStatus svs_asset_account(const SvsVaultKeys *keys, const SvsPubkey *owner, SvsPubkey *out);
+
+// Snapshot of an SVS-1 vault account; its share count is the shares mint
+// supply
+SvsStatus svs_vault_snapshot_from_vault(const uint8_t *data,
+                                        size_t data_len,
+                                        uint64_t shares_supply,
+                                        SvsVaultSnapshot *out);
+
+// Snapshot of an SVS-2 vault account at unix time `now`; its share count
+// is the shares mint supply
+SvsStatus svs_vault_snapshot_from_confidential_vault(const uint8_t *data,
+                                                     size_t data_len,
+                                                     uint64_t shares_supply,
+                                                     int64_t now,
+                                                     SvsVaultSnapshot *out);
+
+// Shares minted for depositing `assets`
+SvsStatus svs_preview_deposit(const SvsVaultSnapshot *snapshot, uint64_t assets, uint64_t *out);
+
+// Assets needed to mint `shares`
+SvsStatus svs_preview_mint(const SvsVaultSnapshot *snapshot, uint64_t shares, uint64_t *out);
+
+// Shares burned for withdrawing `assets`
+SvsStatus svs_preview_withdraw(const SvsVaultSnapshot *snapshot, uint64_t assets, uint64_t *out);
+
+// Assets received for redeeming `shares`
+SvsStatus svs_preview_redeem(const SvsVaultSnapshot *snapshot, uint64_t shares, uint64_t *out);
+
+// Assets withdrawable by an owner of `owner_shares`
+SvsStatus svs_max_withdraw(const SvsVaultSnapshot *snapshot, uint64_t owner_shares, uint64_t *out);
+
+// Price of one whole share in whole assets, with `scale` as 1.0
+SvsStatus svs_share_price(const SvsVaultSnapshot *snapshot, uint64_t scale, uint64_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SVS_H */
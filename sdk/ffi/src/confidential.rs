@@ -0,0 +1,276 @@
+//! SVS-2 encryption keys and confidential balances
+//!
+//! The wallet signs the two derivation messages of a shares account with its
+//! own key, so the secret key never crosses the ABI; the signatures are
+//! secret key material and should be dropped once the keys are derived.
+
+use anchor_lang::prelude::Pubkey;
+use svs_client::confidential::{self, ConfidentialAccountState, ConfidentialKeys};
+
+use crate::{
+    arg, bytes, call, instruction::SvsDecryptableBalance, write, Error, Result, SvsBuffer,
+    SvsPubkey, SvsStatus,
+};
+
+/// Encryption keys of one confidential token account, owned by the caller
+pub struct SvsConfidentialKeys(ConfidentialKeys);
+
+/// A 64-byte Ed25519 signature
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvsSignature {
+    pub bytes: [u8; 64],
+}
+
+/// An ElGamal public key
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SvsElGamalPubkey {
+    pub bytes: [u8; 32],
+}
+
+/// Arguments of `svs2_apply_pending`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvsPendingApplication {
+    pub new_available_balance: u64,
+    pub new_decryptable_available_balance: SvsDecryptableBalance,
+    /// Credits included in the applied balance; later credits stay pending
+    pub expected_pending_balance_credit_counter: u64,
+}
+
+/// Message the wallet signs to derive the ElGamal keypair of
+/// `token_account`; free it with `svs_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn svs_elgamal_key_message(
+    token_account: *const SvsPubkey,
+    out: *mut SvsBuffer,
+) -> SvsStatus {
+    call(|| {
+        let token_account = (*arg(token_account, "token_account")?).into();
+        message(out, confidential::elgamal_key_message(&token_account))
+    })
+}
+
+/// Message the wallet signs to derive the AE key of `token_account`; free it
+/// with `svs_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn svs_ae_key_message(
+    token_account: *const SvsPubkey,
+    out: *mut SvsBuffer,
+) -> SvsStatus {
+    call(|| {
+        let token_account = (*arg(token_account, "token_account")?).into();
+        message(out, confidential::ae_key_message(&token_account))
+    })
+}
+
+/// Hand `message` to the caller, allocating only when it can be returned
+unsafe fn message(out: *mut SvsBuffer, message: Vec<u8>) -> Result<()> {
+    if out.is_null() {
+        return Err(Error::new(SvsStatus::NullPointer, "out is NULL"));
+    }
+    out.write(SvsBuffer::new(message));
+    Ok(())
+}
+
+/// Derive the keys of `token_account` from the wallet's signatures of the
+/// two derivation messages
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_from_signatures(
+    elgamal_signature: *const SvsSignature,
+    ae_signature: *const SvsSignature,
+    token_account: *const SvsPubkey,
+    out: *mut *mut SvsConfidentialKeys,
+) -> SvsStatus {
+    call(|| {
+        let keys = ConfidentialKeys::from_signatures(
+            &arg(elgamal_signature, "elgamal_signature")?.bytes.into(),
+            &arg(ae_signature, "ae_signature")?.bytes.into(),
+            &(*arg(token_account, "token_account")?).into(),
+        )?;
+        let keys = Box::into_raw(Box::new(SvsConfidentialKeys(keys)));
+        write(out, "out", keys).inspect_err(|_| drop(Box::from_raw(keys)))
+    })
+}
+
+/// Release keys; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_free(keys: *mut SvsConfidentialKeys) {
+    if !keys.is_null() {
+        drop(Box::from_raw(keys));
+    }
+}
+
+/// ElGamal public key, to configure the token account with
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_elgamal_pubkey(
+    keys: *const SvsConfidentialKeys,
+    out: *mut SvsElGamalPubkey,
+) -> SvsStatus {
+    call(|| {
+        let pubkey = arg(keys, "keys")?.0.elgamal.pubkey();
+        write(
+            out,
+            "out",
+            SvsElGamalPubkey {
+                bytes: pubkey.into(),
+            },
+        )
+    })
+}
+
+/// Decryptable balance of `balance`, e.g. 0 for `svs2_configure_account`
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_encrypt_balance(
+    keys: *const SvsConfidentialKeys,
+    balance: u64,
+    out: *mut SvsDecryptableBalance,
+) -> SvsStatus {
+    call(|| {
+        let bytes = arg(keys, "keys")?.0.encrypt_balance(balance);
+        write(out, "out", SvsDecryptableBalance { bytes })
+    })
+}
+
+/// Decrypt a decryptable balance
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_decrypt_balance(
+    keys: *const SvsConfidentialKeys,
+    balance: *const SvsDecryptableBalance,
+    out: *mut u64,
+) -> SvsStatus {
+    call(|| {
+        let keys = &arg(keys, "keys")?.0;
+        let balance = keys.decrypt_balance(&arg(balance, "balance")?.bytes)?;
+        write(out, "out", balance)
+    })
+}
+
+/// Available balance of a shares account, from its account data
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_available_balance(
+    keys: *const SvsConfidentialKeys,
+    token_account_data: *const u8,
+    token_account_data_len: usize,
+    out: *mut u64,
+) -> SvsStatus {
+    call(|| {
+        let keys = &arg(keys, "keys")?.0;
+        let state = state(token_account_data, token_account_data_len)?;
+        write(out, "out", keys.available_balance(&state)?)
+    })
+}
+
+/// Pending balance of a shares account, from its account data
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_pending_balance(
+    keys: *const SvsConfidentialKeys,
+    token_account_data: *const u8,
+    token_account_data_len: usize,
+    out: *mut u64,
+) -> SvsStatus {
+    call(|| {
+        let keys = &arg(keys, "keys")?.0;
+        let state = state(token_account_data, token_account_data_len)?;
+        write(out, "out", keys.pending_balance(&state)?)
+    })
+}
+
+/// Arguments of `svs2_apply_pending` for a shares account, from its account
+/// data
+#[no_mangle]
+pub unsafe extern "C" fn svs_confidential_keys_apply_pending(
+    keys: *const SvsConfidentialKeys,
+    token_account_data: *const u8,
+    token_account_data_len: usize,
+    out: *mut SvsPendingApplication,
+) -> SvsStatus {
+    call(|| {
+        let keys = &arg(keys, "keys")?.0;
+        let applied = keys.apply_pending(&state(token_account_data, token_account_data_len)?)?;
+        write(
+            out,
+            "out",
+            SvsPendingApplication {
+                new_available_balance: applied.new_available_balance,
+                new_decryptable_available_balance: SvsDecryptableBalance {
+                    bytes: applied.new_decryptable_available_balance,
+                },
+                expected_pending_balance_credit_counter: applied
+                    .expected_pending_balance_credit_counter,
+            },
+        )
+    })
+}
+
+unsafe fn state(data: *const u8, len: usize) -> Result<ConfidentialAccountState> {
+    let data = bytes(data, len, "token_account_data")?;
+    Ok(ConfidentialAccountState::parse(&Pubkey::default(), data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ptr, slice};
+
+    use super::*;
+    use crate::svs_buffer_free;
+
+    fn keys(seed: u8) -> *mut SvsConfidentialKeys {
+        let token_account = SvsPubkey::from(Pubkey::new_unique());
+        let (elgamal_signature, ae_signature) = (
+            SvsSignature { bytes: [seed; 64] },
+            SvsSignature {
+                bytes: [seed + 1; 64],
+            },
+        );
+        let mut keys = ptr::null_mut();
+        let status = unsafe {
+            svs_confidential_keys_from_signatures(
+                &elgamal_signature,
+                &ae_signature,
+                &token_account,
+                &mut keys,
+            )
+        };
+        assert_eq!(status, SvsStatus::Ok);
+        keys
+    }
+
+    #[test]
+    fn test_balance_round_trip() {
+        let (keys, other) = (keys(1), keys(3));
+        let mut balance = SvsDecryptableBalance { bytes: [0; 36] };
+        let status = unsafe { svs_confidential_keys_encrypt_balance(keys, 1_500, &mut balance) };
+        assert_eq!(status, SvsStatus::Ok);
+
+        let mut decrypted = 0;
+        let status =
+            unsafe { svs_confidential_keys_decrypt_balance(keys, &balance, &mut decrypted) };
+        assert_eq!(status, SvsStatus::Ok);
+        assert_eq!(decrypted, 1_500);
+
+        let status =
+            unsafe { svs_confidential_keys_decrypt_balance(other, &balance, &mut decrypted) };
+        assert_eq!(status, SvsStatus::Crypto);
+
+        unsafe {
+            svs_confidential_keys_free(keys);
+            svs_confidential_keys_free(other);
+        }
+    }
+
+    #[test]
+    fn test_key_messages() {
+        let token_account = Pubkey::new_unique();
+        let mut buffer = SvsBuffer::new(Vec::new());
+        let status = unsafe { svs_elgamal_key_message(&token_account.into(), &mut buffer) };
+        assert_eq!(status, SvsStatus::Ok);
+        let message = unsafe { slice::from_raw_parts(buffer.data, buffer.len) };
+        assert_eq!(message, confidential::elgamal_key_message(&token_account));
+        unsafe { svs_buffer_free(buffer) };
+
+        let status = unsafe { svs_ae_key_message(&token_account.into(), ptr::null_mut()) };
+        assert_eq!(status, SvsStatus::NullPointer);
+    }
+}
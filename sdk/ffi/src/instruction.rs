@@ -0,0 +1,413 @@
+//! SVS-1 and SVS-2 instruction builders
+//!
+//! Builders return an opaque [`SvsInstruction`]; read its program id,
+//! accounts and data with the accessors to assemble a transaction in the
+//! host language, then release it with [`svs_instruction_free`].
+
+use anchor_lang::solana_program::instruction::Instruction;
+use svs_client::{
+    svs1, svs2,
+    svs2::{PubkeyValidityProof, WithdrawProofContexts},
+    VaultKeys,
+};
+
+use crate::{arg, call, keys::SvsVaultKeys, optional, write, Error, Result, SvsPubkey, SvsStatus};
+
+/// An encoded instruction, owned by the caller
+pub struct SvsInstruction(Instruction);
+
+/// One account of an instruction
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SvsAccountMeta {
+    pub pubkey: SvsPubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// AE ciphertext of a decryptable balance
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvsDecryptableBalance {
+    pub bytes: [u8; 36],
+}
+
+/// A 32-byte hash, e.g. of an off-chain memo
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvsHash {
+    pub bytes: [u8; 32],
+}
+
+/// Hand `instruction` to the caller
+unsafe fn build(out: *mut *mut SvsInstruction, instruction: Instruction) -> Result<()> {
+    let instruction = Box::into_raw(Box::new(SvsInstruction(instruction)));
+    write(out, "out", instruction).inspect_err(|_| drop(Box::from_raw(instruction)))
+}
+
+/// Program the instruction invokes
+#[no_mangle]
+pub unsafe extern "C" fn svs_instruction_program_id(
+    instruction: *const SvsInstruction,
+    out: *mut SvsPubkey,
+) -> SvsStatus {
+    call(|| {
+        let instruction = &arg(instruction, "instruction")?.0;
+        write(out, "out", instruction.program_id.into())
+    })
+}
+
+/// Number of accounts of the instruction, 0 if it is NULL
+#[no_mangle]
+pub unsafe extern "C" fn svs_instruction_accounts_len(instruction: *const SvsInstruction) -> usize {
+    optional(instruction).map_or(0, |instruction| instruction.0.accounts.len())
+}
+
+/// Account `index` of the instruction
+#[no_mangle]
+pub unsafe extern "C" fn svs_instruction_account(
+    instruction: *const SvsInstruction,
+    index: usize,
+    out: *mut SvsAccountMeta,
+) -> SvsStatus {
+    call(|| {
+        let accounts = &arg(instruction, "instruction")?.0.accounts;
+        let meta = accounts.get(index).ok_or_else(|| {
+            Error::new(
+                SvsStatus::InvalidArgument,
+                format!("account {index} of {}", accounts.len()),
+            )
+        })?;
+        write(
+            out,
+            "out",
+            SvsAccountMeta {
+                pubkey: meta.pubkey.into(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            },
+        )
+    })
+}
+
+/// Data of the instruction, borrowed until it is freed
+#[no_mangle]
+pub unsafe extern "C" fn svs_instruction_data(
+    instruction: *const SvsInstruction,
+    out_data: *mut *const u8,
+    out_len: *mut usize,
+) -> SvsStatus {
+    call(|| {
+        let data = &arg(instruction, "instruction")?.0.data;
+        write(out_len, "out_len", data.len())?;
+        write(out_data, "out_data", data.as_ptr())
+    })
+}
+
+/// Release an instruction; NULL is ignored
+#[no_mangle]
+pub unsafe extern "C" fn svs_instruction_free(instruction: *mut SvsInstruction) {
+    if !instruction.is_null() {
+        drop(Box::from_raw(instruction));
+    }
+}
+
+/// SVS-1 `deposit` of exactly `assets` for at least `min_shares_out` shares
+#[no_mangle]
+pub unsafe extern "C" fn svs1_deposit(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    assets: u64,
+    min_shares_out: u64,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        build(out, svs1::deposit(&keys, &user, assets, min_shares_out))
+    })
+}
+
+/// SVS-1 `mint` of exactly `shares` for at most `max_assets_in` assets
+#[no_mangle]
+pub unsafe extern "C" fn svs1_mint(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    shares: u64,
+    max_assets_in: u64,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        build(out, svs1::mint(&keys, &user, shares, max_assets_in))
+    })
+}
+
+/// SVS-1 `withdraw` of exactly `assets` for at most `max_shares_in` shares
+#[no_mangle]
+pub unsafe extern "C" fn svs1_withdraw(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    assets: u64,
+    max_shares_in: u64,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        build(out, svs1::withdraw(&keys, &user, assets, max_shares_in))
+    })
+}
+
+/// SVS-1 `redeem` of exactly `shares` for at least `min_assets_out` assets
+#[no_mangle]
+pub unsafe extern "C" fn svs1_redeem(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    shares: u64,
+    min_assets_out: u64,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        build(out, svs1::redeem(&keys, &user, shares, min_assets_out))
+    })
+}
+
+/// SVS-2 `configure_account` verifying the pubkey validity proof
+/// `proof_instruction_offset` instructions away in the same transaction
+#[no_mangle]
+pub unsafe extern "C" fn svs2_configure_account(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    decryptable_zero_balance: *const SvsDecryptableBalance,
+    proof_instruction_offset: i8,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        let zero_balance = arg(decryptable_zero_balance, "decryptable_zero_balance")?.bytes;
+        build(
+            out,
+            svs2::configure_account(
+                &keys,
+                &user,
+                None,
+                zero_balance,
+                PubkeyValidityProof::InstructionOffset(proof_instruction_offset),
+            ),
+        )
+    })
+}
+
+/// SVS-2 `deposit`; `memo_hash` is optional
+#[no_mangle]
+pub unsafe extern "C" fn svs2_deposit(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    assets: u64,
+    min_shares_out: u64,
+    memo_hash: *const SvsHash,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        let memo_hash = optional(memo_hash).map(|hash| hash.bytes);
+        build(
+            out,
+            svs2::deposit(&keys, &user, assets, min_shares_out, memo_hash),
+        )
+    })
+}
+
+/// SVS-2 `apply_pending`, with the values of
+/// `svs_confidential_keys_apply_pending`
+#[no_mangle]
+pub unsafe extern "C" fn svs2_apply_pending(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    new_decryptable_available_balance: *const SvsDecryptableBalance,
+    expected_pending_balance_credit_counter: u64,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        let new_balance = arg(
+            new_decryptable_available_balance,
+            "new_decryptable_available_balance",
+        )?;
+        build(
+            out,
+            svs2::apply_pending(
+                &keys,
+                &user,
+                new_balance.bytes,
+                expected_pending_balance_credit_counter,
+            ),
+        )
+    })
+}
+
+/// SVS-2 `withdraw` proven by the equality and range proof context accounts
+#[no_mangle]
+pub unsafe extern "C" fn svs2_withdraw(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    equality_proof_context: *const SvsPubkey,
+    range_proof_context: *const SvsPubkey,
+    assets: u64,
+    max_shares_in: u64,
+    new_decryptable_available_balance: *const SvsDecryptableBalance,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        let proofs = proof_contexts(equality_proof_context, range_proof_context)?;
+        let new_balance = arg(
+            new_decryptable_available_balance,
+            "new_decryptable_available_balance",
+        )?;
+        build(
+            out,
+            svs2::withdraw(
+                &keys,
+                &user,
+                &proofs,
+                assets,
+                max_shares_in,
+                new_balance.bytes,
+                None,
+            ),
+        )
+    })
+}
+
+/// SVS-2 `redeem` proven by the equality and range proof context accounts
+#[no_mangle]
+pub unsafe extern "C" fn svs2_redeem(
+    keys: *const SvsVaultKeys,
+    user: *const SvsPubkey,
+    equality_proof_context: *const SvsPubkey,
+    range_proof_context: *const SvsPubkey,
+    shares: u64,
+    min_assets_out: u64,
+    new_decryptable_available_balance: *const SvsDecryptableBalance,
+    out: *mut *mut SvsInstruction,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let user = (*arg(user, "user")?).into();
+        let proofs = proof_contexts(equality_proof_context, range_proof_context)?;
+        let new_balance = arg(
+            new_decryptable_available_balance,
+            "new_decryptable_available_balance",
+        )?;
+        build(
+            out,
+            svs2::redeem(
+                &keys,
+                &user,
+                &proofs,
+                shares,
+                min_assets_out,
+                new_balance.bytes,
+            ),
+        )
+    })
+}
+
+unsafe fn proof_contexts(
+    equality: *const SvsPubkey,
+    range: *const SvsPubkey,
+) -> Result<WithdrawProofContexts> {
+    Ok(WithdrawProofContexts {
+        equality: (*arg(equality, "equality_proof_context")?).into(),
+        range: (*arg(range, "range_proof_context")?).into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ptr, slice};
+
+    use anchor_lang::prelude::Pubkey;
+
+    use super::*;
+
+    #[test]
+    fn test_instruction_matches_client() {
+        let keys = VaultKeys::derive(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            0,
+            &Pubkey::new_unique(),
+        );
+        let user = Pubkey::new_unique();
+        let expected = svs1::deposit(&keys, &user, 1_000, 990);
+
+        let mut instruction = ptr::null_mut();
+        let status = unsafe {
+            svs1_deposit(
+                &SvsVaultKeys::from(keys),
+                &user.into(),
+                1_000,
+                990,
+                &mut instruction,
+            )
+        };
+        assert_eq!(status, SvsStatus::Ok);
+
+        let mut program_id = SvsPubkey::default();
+        unsafe { svs_instruction_program_id(instruction, &mut program_id) };
+        assert_eq!(Pubkey::from(program_id), expected.program_id);
+
+        let len = unsafe { svs_instruction_accounts_len(instruction) };
+        assert_eq!(len, expected.accounts.len());
+        for (index, expected) in expected.accounts.iter().enumerate() {
+            let mut meta = SvsAccountMeta::default();
+            let status = unsafe { svs_instruction_account(instruction, index, &mut meta) };
+            assert_eq!(status, SvsStatus::Ok);
+            assert_eq!(Pubkey::from(meta.pubkey), expected.pubkey);
+            assert_eq!(meta.is_signer, expected.is_signer);
+            assert_eq!(meta.is_writable, expected.is_writable);
+        }
+        let mut meta = SvsAccountMeta::default();
+        let status = unsafe { svs_instruction_account(instruction, len, &mut meta) };
+        assert_eq!(status, SvsStatus::InvalidArgument);
+
+        let (mut data, mut data_len) = (ptr::null(), 0);
+        unsafe { svs_instruction_data(instruction, &mut data, &mut data_len) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(data, data_len) },
+            expected.data
+        );
+
+        unsafe { svs_instruction_free(instruction) };
+    }
+
+    #[test]
+    fn test_optional_memo_hash() {
+        let keys = SvsVaultKeys::from(VaultKeys::derive(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            0,
+            &Pubkey::new_unique(),
+        ));
+        let user = SvsPubkey::from(Pubkey::new_unique());
+        let memo_hash = SvsHash { bytes: [7; 32] };
+
+        let (mut without, mut with) = (ptr::null_mut(), ptr::null_mut());
+        unsafe {
+            svs2_deposit(&keys, &user, 1_000, 990, ptr::null(), &mut without);
+            svs2_deposit(&keys, &user, 1_000, 990, &memo_hash, &mut with);
+        }
+        let (without, with) = unsafe { (Box::from_raw(without), Box::from_raw(with)) };
+        assert_eq!(with.0.data.len(), without.0.data.len() + 32);
+    }
+}
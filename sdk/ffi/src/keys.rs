@@ -0,0 +1,221 @@
+//! Vault accounts and PDA derivation
+
+use svs_client::{decode_confidential_vault, decode_vault, pda, VaultKeys};
+
+use crate::{arg, bytes, call, write, SvsPubkey, SvsStatus};
+
+/// Accounts every instruction of one vault refers to, see `VaultKeys`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SvsVaultKeys {
+    /// SVS-1 or SVS-2 program
+    pub program_id: SvsPubkey,
+    pub vault: SvsPubkey,
+    pub asset_mint: SvsPubkey,
+    pub shares_mint: SvsPubkey,
+    pub asset_vault: SvsPubkey,
+    /// SPL Token or Token-2022, owner of the asset mint
+    pub asset_token_program: SvsPubkey,
+}
+
+impl From<VaultKeys> for SvsVaultKeys {
+    fn from(keys: VaultKeys) -> Self {
+        Self {
+            program_id: keys.program_id.into(),
+            vault: keys.vault.into(),
+            asset_mint: keys.asset_mint.into(),
+            shares_mint: keys.shares_mint.into(),
+            asset_vault: keys.asset_vault.into(),
+            asset_token_program: keys.asset_token_program.into(),
+        }
+    }
+}
+
+impl From<&SvsVaultKeys> for VaultKeys {
+    fn from(keys: &SvsVaultKeys) -> Self {
+        Self {
+            program_id: keys.program_id.into(),
+            vault: keys.vault.into(),
+            asset_mint: keys.asset_mint.into(),
+            shares_mint: keys.shares_mint.into(),
+            asset_vault: keys.asset_vault.into(),
+            asset_token_program: keys.asset_token_program.into(),
+        }
+    }
+}
+
+/// Derive the accounts of vault `vault_id` for `asset_mint`, e.g. before
+/// initializing it
+#[no_mangle]
+pub unsafe extern "C" fn svs_vault_keys_derive(
+    program_id: *const SvsPubkey,
+    asset_mint: *const SvsPubkey,
+    vault_id: u64,
+    asset_token_program: *const SvsPubkey,
+    out: *mut SvsVaultKeys,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::derive(
+            &(*arg(program_id, "program_id")?).into(),
+            &(*arg(asset_mint, "asset_mint")?).into(),
+            vault_id,
+            &(*arg(asset_token_program, "asset_token_program")?).into(),
+        );
+        write(out, "out", keys.into())
+    })
+}
+
+/// Accounts recorded in the data of an SVS-1 or SVS-2 vault account
+#[no_mangle]
+pub unsafe extern "C" fn svs_vault_keys_from_account(
+    program_id: *const SvsPubkey,
+    vault: *const SvsPubkey,
+    data: *const u8,
+    data_len: usize,
+    asset_token_program: *const SvsPubkey,
+    out: *mut SvsVaultKeys,
+) -> SvsStatus {
+    call(|| {
+        let program_id = (*arg(program_id, "program_id")?).into();
+        let vault = (*arg(vault, "vault")?).into();
+        let data = bytes(data, data_len, "data")?;
+        let asset_token_program = (*arg(asset_token_program, "asset_token_program")?).into();
+
+        let keys = match decode_vault(&vault, data) {
+            Ok(state) => VaultKeys::from_vault(&program_id, &vault, &state, &asset_token_program),
+            Err(_) => VaultKeys::from_confidential_vault(
+                &program_id,
+                &vault,
+                &decode_confidential_vault(&vault, data)?,
+                &asset_token_program,
+            ),
+        };
+        write(out, "out", keys.into())
+    })
+}
+
+/// Vault PDA of `asset_mint` and `vault_id`, and its bump
+#[no_mangle]
+pub unsafe extern "C" fn svs_find_vault_address(
+    program_id: *const SvsPubkey,
+    asset_mint: *const SvsPubkey,
+    vault_id: u64,
+    out_address: *mut SvsPubkey,
+    out_bump: *mut u8,
+) -> SvsStatus {
+    call(|| {
+        let (address, bump) = pda::find_vault_address(
+            &(*arg(program_id, "program_id")?).into(),
+            &(*arg(asset_mint, "asset_mint")?).into(),
+            vault_id,
+        );
+        write(out_bump, "out_bump", bump)?;
+        write(out_address, "out_address", address.into())
+    })
+}
+
+/// Shares mint PDA of `vault`, and its bump
+#[no_mangle]
+pub unsafe extern "C" fn svs_find_shares_mint_address(
+    program_id: *const SvsPubkey,
+    vault: *const SvsPubkey,
+    out_address: *mut SvsPubkey,
+    out_bump: *mut u8,
+) -> SvsStatus {
+    call(|| {
+        let (address, bump) = pda::find_shares_mint_address(
+            &(*arg(program_id, "program_id")?).into(),
+            &(*arg(vault, "vault")?).into(),
+        );
+        write(out_bump, "out_bump", bump)?;
+        write(out_address, "out_address", address.into())
+    })
+}
+
+/// Shares account of `owner`: their Token-2022 associated token account
+#[no_mangle]
+pub unsafe extern "C" fn svs_shares_account(
+    keys: *const SvsVaultKeys,
+    owner: *const SvsPubkey,
+    out: *mut SvsPubkey,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let address = keys.shares_account(&(*arg(owner, "owner")?).into());
+        write(out, "out", address.into())
+    })
+}
+
+/// Asset token account of `owner`: their associated token account
+#[no_mangle]
+pub unsafe extern "C" fn svs_asset_account(
+    keys: *const SvsVaultKeys,
+    owner: *const SvsPubkey,
+    out: *mut SvsPubkey,
+) -> SvsStatus {
+    call(|| {
+        let keys = VaultKeys::from(arg(keys, "keys")?);
+        let address = keys.asset_account(&(*arg(owner, "owner")?).into());
+        write(out, "out", address.into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use anchor_lang::prelude::Pubkey;
+
+    use super::*;
+
+    fn unique() -> SvsPubkey {
+        Pubkey::new_unique().into()
+    }
+
+    #[test]
+    fn test_derive_matches_client() {
+        let (program_id, asset_mint, token_program) = (unique(), unique(), unique());
+        let mut keys = SvsVaultKeys::default();
+        let status = unsafe {
+            svs_vault_keys_derive(&program_id, &asset_mint, 7, &token_program, &mut keys)
+        };
+        assert_eq!(status, SvsStatus::Ok);
+        let expected = VaultKeys::derive(
+            &program_id.into(),
+            &asset_mint.into(),
+            7,
+            &token_program.into(),
+        );
+        assert_eq!(VaultKeys::from(&keys), expected);
+
+        let (mut vault, mut bump) = (SvsPubkey::default(), 0);
+        let status =
+            unsafe { svs_find_vault_address(&program_id, &asset_mint, 7, &mut vault, &mut bump) };
+        assert_eq!(status, SvsStatus::Ok);
+        assert_eq!(vault, keys.vault);
+
+        let owner = unique();
+        let mut shares_account = SvsPubkey::default();
+        let status = unsafe { svs_shares_account(&keys, &owner, &mut shares_account) };
+        assert_eq!(status, SvsStatus::Ok);
+        assert_eq!(
+            Pubkey::from(shares_account),
+            expected.shares_account(&owner.into())
+        );
+
+        let status = unsafe { svs_shares_account(&keys, ptr::null(), &mut shares_account) };
+        assert_eq!(status, SvsStatus::NullPointer);
+    }
+
+    #[test]
+    fn test_from_account_rejects_other_data() {
+        let key = unique();
+        let mut keys = SvsVaultKeys::default();
+        let data = [0u8; 64];
+        let status = unsafe {
+            svs_vault_keys_from_account(&key, &key, data.as_ptr(), data.len(), &key, &mut keys)
+        };
+        assert_eq!(status, SvsStatus::InvalidAccount);
+        assert_eq!(keys, SvsVaultKeys::default());
+    }
+}
@@ -0,0 +1,253 @@
+//! C ABI of the SVS client
+//!
+//! Exposes PDA derivation, instruction encoding, view math and the SVS-2
+//! balance helpers of `svs-client` to C, so mobile wallets (Swift, Kotlin
+//! through JNI) and other non-Rust stacks embed the canonical logic instead
+//! of re-implementing the confidential math. `include/svs.h` is generated
+//! from this crate by cbindgen on every build.
+//!
+//! Conventions, for every function:
+//!
+//! - The return value is an [`SvsStatus`]; results are written through out
+//!   pointers, which are left untouched on failure. [`svs_last_error`]
+//!   describes the last failure on the calling thread.
+//! - Pointer arguments must be valid for reads (or writes, for out pointers)
+//!   of their type for the duration of the call. Byte arrays are passed as a
+//!   pointer and a length. NULL is rejected unless documented as optional.
+//! - Objects returned as pointers ([`SvsInstruction`],
+//!   [`SvsConfidentialKeys`]) and [`SvsBuffer`]s are owned by the caller and
+//!   released with their `_free` function.
+//! - Panics are caught and reported as [`SvsStatus::Panic`].
+
+// Pointer requirements are the same for every function, see above
+#![allow(clippy::missing_safety_doc)]
+
+pub mod confidential;
+pub mod instruction;
+pub mod keys;
+pub mod view;
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use anchor_lang::prelude::Pubkey;
+use svs_client::ClientError;
+
+/// Outcome of a call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvsStatus {
+    Ok = 0,
+    /// A required pointer was NULL
+    NullPointer = 1,
+    /// An argument was out of range or malformed
+    InvalidArgument = 2,
+    /// Account data could not be decoded as the expected account
+    InvalidAccount = 3,
+    /// Vault math overflowed or the vault is paused
+    Math = 4,
+    /// Key derivation, encryption or decryption failed
+    Crypto = 5,
+    /// The library panicked; please report it
+    Panic = 6,
+}
+
+/// A 32-byte Solana public key
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SvsPubkey {
+    pub bytes: [u8; 32],
+}
+
+impl From<Pubkey> for SvsPubkey {
+    fn from(pubkey: Pubkey) -> Self {
+        Self {
+            bytes: pubkey.to_bytes(),
+        }
+    }
+}
+
+impl From<SvsPubkey> for Pubkey {
+    fn from(pubkey: SvsPubkey) -> Self {
+        Pubkey::new_from_array(pubkey.bytes)
+    }
+}
+
+/// Bytes allocated by the library, e.g. a message to sign
+#[repr(C)]
+#[derive(Debug)]
+pub struct SvsBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl SvsBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let bytes = Box::into_raw(bytes.into_boxed_slice());
+        Self {
+            data: bytes.cast(),
+            len: bytes.len(),
+        }
+    }
+}
+
+/// Release a buffer returned by the library
+#[no_mangle]
+pub unsafe extern "C" fn svs_buffer_free(buffer: SvsBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Message of the last failed call on this thread, or NULL
+///
+/// The string is owned by the library and valid until the next call on the
+/// same thread.
+#[no_mangle]
+pub extern "C" fn svs_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// A failed call: its status and message
+#[derive(Debug)]
+pub(crate) struct Error {
+    status: SvsStatus,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(status: SvsStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ClientError> for Error {
+    fn from(error: ClientError) -> Self {
+        let status = match &error {
+            ClientError::AccountNotFound(_) | ClientError::InvalidAccount { .. } => {
+                SvsStatus::InvalidAccount
+            }
+            ClientError::Math(_) => SvsStatus::Math,
+            ClientError::Signer(_) | ClientError::Proof(_) => SvsStatus::Crypto,
+            _ => SvsStatus::InvalidArgument,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+/// Run the body of an exported function, recording its error
+pub(crate) fn call(body: impl FnOnce() -> Result<()>) -> SvsStatus {
+    let result = panic::catch_unwind(AssertUnwindSafe(body))
+        .unwrap_or_else(|_| Err(Error::new(SvsStatus::Panic, "svs-ffi panicked")));
+    let (status, message) = match result {
+        Ok(()) => (SvsStatus::Ok, None),
+        // Messages never contain NUL bytes
+        Err(error) => (error.status, CString::new(error.message).ok()),
+    };
+    LAST_ERROR.with(|error| *error.borrow_mut() = message);
+    status
+}
+
+/// Dereference a required argument
+pub(crate) unsafe fn arg<'a, T>(pointer: *const T, name: &str) -> Result<&'a T> {
+    pointer
+        .as_ref()
+        .ok_or_else(|| Error::new(SvsStatus::NullPointer, format!("{name} is NULL")))
+}
+
+/// Dereference an optional argument
+pub(crate) unsafe fn optional<'a, T>(pointer: *const T) -> Option<&'a T> {
+    pointer.as_ref()
+}
+
+/// `len` bytes at `data`; NULL is allowed for no bytes
+pub(crate) unsafe fn bytes<'a>(data: *const u8, len: usize, name: &str) -> Result<&'a [u8]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(Error::new(
+            SvsStatus::NullPointer,
+            format!("{name} is NULL"),
+        ));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Write a result through a required out pointer
+pub(crate) unsafe fn write<T>(out: *mut T, name: &str, value: T) -> Result<()> {
+    if out.is_null() {
+        return Err(Error::new(
+            SvsStatus::NullPointer,
+            format!("{name} is NULL"),
+        ));
+    }
+    out.write(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    #[test]
+    fn test_errors_are_recorded_per_call() {
+        let status = call(|| Err(Error::new(SvsStatus::InvalidArgument, "bad amount")));
+        assert_eq!(status, SvsStatus::InvalidArgument);
+        let message = unsafe { CStr::from_ptr(svs_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "bad amount");
+
+        assert_eq!(call(|| Ok(())), SvsStatus::Ok);
+        assert!(svs_last_error().is_null());
+
+        assert_eq!(call(|| panic!("bug")), SvsStatus::Panic);
+        assert!(!svs_last_error().is_null());
+    }
+
+    #[test]
+    fn test_null_arguments() {
+        let status = call(|| unsafe { arg::<SvsPubkey>(ptr::null(), "owner").map(|_| ()) });
+        assert_eq!(status, SvsStatus::NullPointer);
+        assert!(unsafe { bytes(ptr::null(), 0, "data") }.unwrap().is_empty());
+        assert!(unsafe { bytes(ptr::null(), 1, "data") }.is_err());
+    }
+
+    #[test]
+    fn test_buffer_round_trip() {
+        let buffer = SvsBuffer::new(b"ElGamalSecretKey".to_vec());
+        assert_eq!(
+            unsafe { slice::from_raw_parts(buffer.data, buffer.len) },
+            b"ElGamalSecretKey"
+        );
+        unsafe { svs_buffer_free(buffer) };
+        unsafe {
+            svs_buffer_free(SvsBuffer {
+                data: ptr::null_mut(),
+                len: 0,
+            })
+        };
+    }
+}
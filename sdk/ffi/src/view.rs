@@ -0,0 +1,183 @@
+//! Vault views computed locally, with the programs' own rounding
+
+use anchor_lang::prelude::Pubkey;
+use svs_client::{decode_confidential_vault, decode_vault, view::VaultSnapshot};
+
+use crate::{arg, bytes, call, write, SvsStatus};
+
+/// Vault state the views are computed from, see `VaultSnapshot`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SvsVaultSnapshot {
+    /// Assets backing shares (SVS-2 excludes still-locked harvest profit)
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub decimals_offset: u8,
+    pub paused: bool,
+}
+
+impl From<VaultSnapshot> for SvsVaultSnapshot {
+    fn from(snapshot: VaultSnapshot) -> Self {
+        Self {
+            total_assets: snapshot.total_assets,
+            total_shares: snapshot.total_shares,
+            decimals_offset: snapshot.decimals_offset,
+            paused: snapshot.paused,
+        }
+    }
+}
+
+impl From<&SvsVaultSnapshot> for VaultSnapshot {
+    fn from(snapshot: &SvsVaultSnapshot) -> Self {
+        Self {
+            total_assets: snapshot.total_assets,
+            total_shares: snapshot.total_shares,
+            decimals_offset: snapshot.decimals_offset,
+            paused: snapshot.paused,
+        }
+    }
+}
+
+/// Snapshot of an SVS-1 vault account; its share count is the shares mint
+/// supply
+#[no_mangle]
+pub unsafe extern "C" fn svs_vault_snapshot_from_vault(
+    data: *const u8,
+    data_len: usize,
+    shares_supply: u64,
+    out: *mut SvsVaultSnapshot,
+) -> SvsStatus {
+    call(|| {
+        let vault = decode_vault(&Pubkey::default(), bytes(data, data_len, "data")?)?;
+        let snapshot = VaultSnapshot::from_vault(&vault, shares_supply);
+        write(out, "out", snapshot.into())
+    })
+}
+
+/// Snapshot of an SVS-2 vault account at unix time `now`; its share count
+/// is the shares mint supply
+#[no_mangle]
+pub unsafe extern "C" fn svs_vault_snapshot_from_confidential_vault(
+    data: *const u8,
+    data_len: usize,
+    shares_supply: u64,
+    now: i64,
+    out: *mut SvsVaultSnapshot,
+) -> SvsStatus {
+    call(|| {
+        let vault = decode_confidential_vault(&Pubkey::default(), bytes(data, data_len, "data")?)?;
+        let snapshot = VaultSnapshot::from_confidential_vault(&vault, shares_supply, now)?;
+        write(out, "out", snapshot.into())
+    })
+}
+
+/// Evaluate one view of `snapshot`
+unsafe fn view(
+    snapshot: *const SvsVaultSnapshot,
+    out: *mut u64,
+    view: impl FnOnce(&VaultSnapshot) -> svs_client::Result<u64>,
+) -> SvsStatus {
+    call(|| {
+        let snapshot = VaultSnapshot::from(arg(snapshot, "snapshot")?);
+        write(out, "out", view(&snapshot)?)
+    })
+}
+
+/// Shares minted for depositing `assets`
+#[no_mangle]
+pub unsafe extern "C" fn svs_preview_deposit(
+    snapshot: *const SvsVaultSnapshot,
+    assets: u64,
+    out: *mut u64,
+) -> SvsStatus {
+    view(snapshot, out, |snapshot| snapshot.preview_deposit(assets))
+}
+
+/// Assets needed to mint `shares`
+#[no_mangle]
+pub unsafe extern "C" fn svs_preview_mint(
+    snapshot: *const SvsVaultSnapshot,
+    shares: u64,
+    out: *mut u64,
+) -> SvsStatus {
+    view(snapshot, out, |snapshot| snapshot.preview_mint(shares))
+}
+
+/// Shares burned for withdrawing `assets`
+#[no_mangle]
+pub unsafe extern "C" fn svs_preview_withdraw(
+    snapshot: *const SvsVaultSnapshot,
+    assets: u64,
+    out: *mut u64,
+) -> SvsStatus {
+    view(snapshot, out, |snapshot| snapshot.preview_withdraw(assets))
+}
+
+/// Assets received for redeeming `shares`
+#[no_mangle]
+pub unsafe extern "C" fn svs_preview_redeem(
+    snapshot: *const SvsVaultSnapshot,
+    shares: u64,
+    out: *mut u64,
+) -> SvsStatus {
+    view(snapshot, out, |snapshot| snapshot.preview_redeem(shares))
+}
+
+/// Assets withdrawable by an owner of `owner_shares`
+#[no_mangle]
+pub unsafe extern "C" fn svs_max_withdraw(
+    snapshot: *const SvsVaultSnapshot,
+    owner_shares: u64,
+    out: *mut u64,
+) -> SvsStatus {
+    view(snapshot, out, |snapshot| {
+        snapshot.max_withdraw(owner_shares)
+    })
+}
+
+/// Price of one whole share in whole assets, with `scale` as 1.0
+#[no_mangle]
+pub unsafe extern "C" fn svs_share_price(
+    snapshot: *const SvsVaultSnapshot,
+    scale: u64,
+    out: *mut u64,
+) -> SvsStatus {
+    view(snapshot, out, |snapshot| snapshot.share_price(scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previews_match_client() {
+        let snapshot = SvsVaultSnapshot {
+            total_assets: 1_000_000,
+            total_shares: 900_000_000,
+            decimals_offset: 3,
+            paused: false,
+        };
+        let expected = VaultSnapshot::from(&snapshot);
+
+        let mut shares = 0;
+        let status = unsafe { svs_preview_deposit(&snapshot, 12_345, &mut shares) };
+        assert_eq!(status, SvsStatus::Ok);
+        assert_eq!(shares, expected.preview_deposit(12_345).unwrap());
+
+        let mut assets = 0;
+        let status = unsafe { svs_preview_mint(&snapshot, shares, &mut assets) };
+        assert_eq!(status, SvsStatus::Ok);
+        assert!(assets >= 12_345);
+
+        let status = unsafe { svs_preview_deposit(&snapshot, u64::MAX, &mut shares) };
+        assert_eq!(status, SvsStatus::Math);
+
+        let paused = SvsVaultSnapshot {
+            paused: true,
+            ..snapshot
+        };
+        let status = unsafe { svs_max_withdraw(&paused, shares, &mut assets) };
+        assert_eq!(status, SvsStatus::Ok);
+        assert_eq!(assets, 0);
+    }
+}
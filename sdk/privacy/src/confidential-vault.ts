@@ -184,6 +184,7 @@ export class ConfidentialSolanaVault {
       )
       .accounts({
         user: userPubkey,
+        payer: null, // user pays reallocation rent
         vault: params.vault,
         sharesMint: vault.sharesMint,
         userSharesAccount,
@@ -237,7 +238,11 @@ export class ConfidentialSolanaVault {
     );
 
     const signature = await this.program.methods
-      .deposit(params.assets, params.minSharesOut)
+      .deposit(
+        params.assets,
+        params.minSharesOut,
+        params.memoHash ? Array.from(params.memoHash) : null,
+      )
       .accounts({
         user: userPubkey,
         vault: params.vault,
@@ -338,6 +343,7 @@ export class ConfidentialSolanaVault {
         params.assets,
         params.maxSharesIn,
         Array.from(params.newDecryptableBalance.ciphertext),
+        params.memoHash ? Array.from(params.memoHash) : null,
       )
       .accounts({
         user: userPubkey,
@@ -609,7 +609,7 @@ export async function createPubkeyValidityProofViaBackend(
   const elgamalSignature = nacl.sign.detached(elgamalMessage, wallet.secretKey);
 
   const response = await fetch(
-    `${PROOF_BACKEND_URL}/api/proofs/pubkey-validity`,
+    `${PROOF_BACKEND_URL}/v1/proofs/pubkey-validity`,
     {
       method: "POST",
       headers: buildHeaders(),
@@ -661,7 +661,7 @@ export async function createEqualityProofViaBackend(
   const elgamalMessage = buildElGamalDerivationMessage(tokenAccount);
   const elgamalSignature = nacl.sign.detached(elgamalMessage, wallet.secretKey);
 
-  const response = await fetch(`${PROOF_BACKEND_URL}/api/proofs/equality`, {
+  const response = await fetch(`${PROOF_BACKEND_URL}/v1/proofs/equality`, {
     method: "POST",
     headers: buildHeaders(),
     body: JSON.stringify({
@@ -703,7 +703,7 @@ export async function createRangeProofViaBackend(
   const requestMessage = buildRangeRequestMessage(timestamp);
   const requestSignature = nacl.sign.detached(requestMessage, wallet.secretKey);
 
-  const response = await fetch(`${PROOF_BACKEND_URL}/api/proofs/range`, {
+  const response = await fetch(`${PROOF_BACKEND_URL}/v1/proofs/range`, {
     method: "POST",
     headers: buildHeaders(),
     body: JSON.stringify({
@@ -762,29 +762,24 @@ function buildHeaders(): Record<string, string> {
 
 /**
  * Build the request message that must be signed by the wallet
+ *
+ * Plain ASCII text, so wallets display it; hardware wallets sign it wrapped
+ * in the Solana off-chain message format, which the backend also accepts.
  */
 function buildRequestMessage(
   timestamp: number,
   tokenAccount: PublicKey,
 ): Uint8Array {
-  const prefix = Buffer.from("SVS_PROOF_REQUEST");
-  const timestampBytes = Buffer.alloc(8);
-  timestampBytes.writeBigInt64LE(BigInt(timestamp));
-  const accountBytes = tokenAccount.toBuffer();
-
-  return Buffer.concat([prefix, timestampBytes, accountBytes]);
+  return Buffer.from(
+    `SVS proof request: token account ${tokenAccount.toBase58()}, timestamp ${timestamp}`,
+  );
 }
 
 /**
  * Build the message for range proof request signature
  */
 function buildRangeRequestMessage(timestamp: number): Uint8Array {
-  const prefix = Buffer.from("SVS_PROOF_REQUEST");
-  const timestampBytes = Buffer.alloc(8);
-  timestampBytes.writeBigInt64LE(BigInt(timestamp));
-  const suffix = Buffer.from("range");
-
-  return Buffer.concat([prefix, timestampBytes, suffix]);
+  return Buffer.from(`SVS range proof request: timestamp ${timestamp}`);
 }
 
 /**
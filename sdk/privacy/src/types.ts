@@ -168,6 +168,8 @@ export interface ConfidentialDepositParams {
   vault: PublicKey;
   assets: BN;
   minSharesOut: BN;
+  /** Optional 32-byte hash of an off-chain compliance record */
+  memoHash?: Uint8Array;
 }
 
 /**
@@ -180,6 +182,8 @@ export interface ConfidentialWithdrawParams {
   newDecryptableBalance: DecryptableBalance;
   equalityProofContext: PublicKey;
   rangeProofContext: PublicKey;
+  /** Optional 32-byte hash of an off-chain compliance record */
+  memoHash?: Uint8Array;
 }
 
 /**
@@ -0,0 +1,157 @@
+//! Vault accounts and views over RPC
+//!
+//! Calls block on the RPC node with the GIL released, so other Python
+//! threads keep running.
+
+use std::{str::FromStr, sync::Arc};
+
+use anchor_lang::prelude::Pubkey;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use svs_client::rpc;
+
+use crate::{
+    error,
+    keys::VaultKeys,
+    pubkey,
+    state::{ConfidentialVault, Vault},
+};
+
+/// Blocking RPC client reading at one commitment
+#[pyclass(module = "svs", frozen)]
+pub struct Client {
+    rpc: Arc<RpcClient>,
+}
+
+#[pymethods]
+impl Client {
+    /// Client of the RPC node at `url`; `commitment` is `processed`,
+    /// `confirmed` or `finalized`
+    #[new]
+    #[pyo3(signature = (url, commitment = "confirmed"))]
+    fn new(url: String, commitment: &str) -> PyResult<Self> {
+        let commitment = CommitmentConfig::from_str(commitment)
+            .map_err(|_| PyValueError::new_err(format!("Invalid commitment {commitment}")))?;
+        Ok(Self {
+            rpc: Arc::new(RpcClient::new_with_commitment(url, commitment)),
+        })
+    }
+
+    /// Fetch an SVS-1 vault
+    fn fetch_vault(&self, py: Python<'_>, address: &str) -> PyResult<Vault> {
+        let address = pubkey(address)?;
+        let vault = py.allow_threads(|| rpc::fetch_vault(&self.rpc, &address));
+        Ok(vault.map_err(error)?.into())
+    }
+
+    /// Fetch an SVS-2 confidential vault
+    fn fetch_confidential_vault(
+        &self,
+        py: Python<'_>,
+        address: &str,
+    ) -> PyResult<ConfidentialVault> {
+        let address = pubkey(address)?;
+        let vault = py.allow_threads(|| rpc::fetch_confidential_vault(&self.rpc, &address));
+        Ok(vault.map_err(error)?.into())
+    }
+
+    /// Fetch the keys of an existing SVS-1 or SVS-2 vault
+    fn fetch_vault_keys(&self, py: Python<'_>, vault: &str) -> PyResult<VaultKeys> {
+        let vault = pubkey(vault)?;
+        let keys = py.allow_threads(|| rpc::fetch_vault_keys(&self.rpc, &vault));
+        Ok(VaultKeys(keys.map_err(error)?))
+    }
+
+    /// Views of the vault described by `keys`; `payer` is any existing
+    /// account that could pay a fee, e.g. the user's wallet
+    fn views(&self, keys: &VaultKeys, payer: &str) -> PyResult<Views> {
+        Ok(Views {
+            rpc: self.rpc.clone(),
+            keys: keys.0,
+            payer: pubkey(payer)?,
+        })
+    }
+}
+
+/// View functions of one vault, simulated against the cluster or else
+/// computed locally from the fetched accounts
+#[pyclass(module = "svs", frozen)]
+pub struct Views {
+    rpc: Arc<RpcClient>,
+    keys: svs_client::VaultKeys,
+    payer: Pubkey,
+}
+
+impl Views {
+    fn get(
+        &self,
+        py: Python<'_>,
+        view: impl FnOnce(&rpc::Views<'_>) -> svs_client::Result<u64> + Send,
+    ) -> PyResult<u64> {
+        py.allow_threads(|| view(&rpc::Views::new(&self.rpc, self.keys, self.payer)))
+            .map_err(error)
+    }
+}
+
+#[pymethods]
+impl Views {
+    /// Shares minted for depositing `assets`
+    fn preview_deposit(&self, py: Python<'_>, assets: u64) -> PyResult<u64> {
+        self.get(py, |views| views.preview_deposit(assets))
+    }
+
+    /// Assets required to mint `shares`
+    fn preview_mint(&self, py: Python<'_>, shares: u64) -> PyResult<u64> {
+        self.get(py, |views| views.preview_mint(shares))
+    }
+
+    /// Shares burned to withdraw `assets`
+    fn preview_withdraw(&self, py: Python<'_>, assets: u64) -> PyResult<u64> {
+        self.get(py, |views| views.preview_withdraw(assets))
+    }
+
+    /// Assets received for redeeming `shares`
+    fn preview_redeem(&self, py: Python<'_>, shares: u64) -> PyResult<u64> {
+        self.get(py, |views| views.preview_redeem(shares))
+    }
+
+    /// `assets` in shares, rounded down
+    fn convert_to_shares(&self, py: Python<'_>, assets: u64) -> PyResult<u64> {
+        self.get(py, |views| views.convert_to_shares(assets))
+    }
+
+    /// `shares` in assets, rounded down
+    fn convert_to_assets(&self, py: Python<'_>, shares: u64) -> PyResult<u64> {
+        self.get(py, |views| views.convert_to_assets(shares))
+    }
+
+    /// Assets per share, in `SHARE_PRICE_SCALE` fixed point
+    fn share_price(&self, py: Python<'_>) -> PyResult<u64> {
+        self.get(py, |views| views.share_price())
+    }
+
+    fn total_assets(&self, py: Python<'_>) -> PyResult<u64> {
+        self.get(py, |views| views.total_assets())
+    }
+
+    fn max_deposit(&self, py: Python<'_>) -> PyResult<u64> {
+        self.get(py, |views| views.max_deposit())
+    }
+
+    fn max_mint(&self, py: Python<'_>) -> PyResult<u64> {
+        self.get(py, |views| views.max_mint())
+    }
+
+    /// Assets `owner` can withdraw
+    fn max_withdraw(&self, py: Python<'_>, owner: &str) -> PyResult<u64> {
+        let owner = pubkey(owner)?;
+        self.get(py, |views| views.max_withdraw(owner))
+    }
+
+    /// Shares `owner` can redeem
+    fn max_redeem(&self, py: Python<'_>, owner: &str) -> PyResult<u64> {
+        let owner = pubkey(owner)?;
+        self.get(py, |views| views.max_redeem(owner))
+    }
+}
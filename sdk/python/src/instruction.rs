@@ -0,0 +1,70 @@
+//! Encoded instructions
+
+use anchor_lang::solana_program::instruction;
+use pyo3::{prelude::*, types::PyBytes};
+
+/// One account of an instruction
+#[pyclass(module = "svs", frozen, get_all)]
+#[derive(Clone)]
+pub struct AccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[pymethods]
+impl AccountMeta {
+    fn __repr__(&self) -> String {
+        format!(
+            "AccountMeta(pubkey={}, is_signer={}, is_writable={})",
+            self.pubkey,
+            if self.is_signer { "True" } else { "False" },
+            if self.is_writable { "True" } else { "False" },
+        )
+    }
+}
+
+/// An encoded instruction, e.g. for `solders.instruction.Instruction`
+#[pyclass(module = "svs", frozen)]
+pub struct Instruction(pub(crate) instruction::Instruction);
+
+#[pymethods]
+impl Instruction {
+    #[getter]
+    fn program_id(&self) -> String {
+        self.0.program_id.to_string()
+    }
+
+    #[getter]
+    fn accounts(&self) -> Vec<AccountMeta> {
+        self.0
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: meta.pubkey.to_string(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.data)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Instruction(program_id={}, accounts={}, data={} bytes)",
+            self.0.program_id,
+            self.0.accounts.len(),
+            self.0.data.len(),
+        )
+    }
+}
+
+impl From<instruction::Instruction> for Instruction {
+    fn from(instruction: instruction::Instruction) -> Self {
+        Self(instruction)
+    }
+}
@@ -0,0 +1,228 @@
+//! Vault accounts and instruction builders
+
+use pyo3::prelude::*;
+use svs_client::{
+    decode_confidential_vault, decode_vault, svs1, svs2,
+    svs2::{PubkeyValidityProof, WithdrawProofContexts},
+};
+
+use crate::{bytes, error, instruction::Instruction, pubkey};
+
+/// Accounts of one vault; instruction builders are its methods
+#[pyclass(module = "svs", frozen)]
+#[derive(Clone)]
+pub struct VaultKeys(pub(crate) svs_client::VaultKeys);
+
+#[pymethods]
+impl VaultKeys {
+    /// Derive the accounts of vault `vault_id` for `asset_mint`
+    #[staticmethod]
+    fn derive(
+        program_id: &str,
+        asset_mint: &str,
+        vault_id: u64,
+        asset_token_program: &str,
+    ) -> PyResult<Self> {
+        Ok(Self(svs_client::VaultKeys::derive(
+            &pubkey(program_id)?,
+            &pubkey(asset_mint)?,
+            vault_id,
+            &pubkey(asset_token_program)?,
+        )))
+    }
+
+    /// Accounts recorded in a vault account (SVS-1 or SVS-2)
+    #[staticmethod]
+    fn from_account(
+        program_id: &str,
+        vault: &str,
+        data: &[u8],
+        asset_token_program: &str,
+    ) -> PyResult<Self> {
+        let (program_id, vault) = (pubkey(program_id)?, pubkey(vault)?);
+        let asset_token_program = pubkey(asset_token_program)?;
+
+        let keys = match decode_vault(&vault, data) {
+            Ok(state) => {
+                svs_client::VaultKeys::from_vault(&program_id, &vault, &state, &asset_token_program)
+            }
+            Err(_) => svs_client::VaultKeys::from_confidential_vault(
+                &program_id,
+                &vault,
+                &decode_confidential_vault(&vault, data).map_err(error)?,
+                &asset_token_program,
+            ),
+        };
+        Ok(Self(keys))
+    }
+
+    #[getter]
+    fn program_id(&self) -> String {
+        self.0.program_id.to_string()
+    }
+
+    #[getter]
+    fn vault(&self) -> String {
+        self.0.vault.to_string()
+    }
+
+    #[getter]
+    fn asset_mint(&self) -> String {
+        self.0.asset_mint.to_string()
+    }
+
+    #[getter]
+    fn shares_mint(&self) -> String {
+        self.0.shares_mint.to_string()
+    }
+
+    #[getter]
+    fn asset_vault(&self) -> String {
+        self.0.asset_vault.to_string()
+    }
+
+    #[getter]
+    fn asset_token_program(&self) -> String {
+        self.0.asset_token_program.to_string()
+    }
+
+    /// Shares account of `owner`
+    fn shares_account(&self, owner: &str) -> PyResult<String> {
+        Ok(self.0.shares_account(&pubkey(owner)?).to_string())
+    }
+
+    /// Asset token account of `owner`
+    fn asset_account(&self, owner: &str) -> PyResult<String> {
+        Ok(self.0.asset_account(&pubkey(owner)?).to_string())
+    }
+
+    fn svs1_deposit(&self, user: &str, assets: u64, min_shares_out: u64) -> PyResult<Instruction> {
+        let user = pubkey(user)?;
+        Ok(svs1::deposit(&self.0, &user, assets, min_shares_out).into())
+    }
+
+    fn svs1_mint(&self, user: &str, shares: u64, max_assets_in: u64) -> PyResult<Instruction> {
+        let user = pubkey(user)?;
+        Ok(svs1::mint(&self.0, &user, shares, max_assets_in).into())
+    }
+
+    fn svs1_withdraw(&self, user: &str, assets: u64, max_shares_in: u64) -> PyResult<Instruction> {
+        let user = pubkey(user)?;
+        Ok(svs1::withdraw(&self.0, &user, assets, max_shares_in).into())
+    }
+
+    fn svs1_redeem(&self, user: &str, shares: u64, min_assets_out: u64) -> PyResult<Instruction> {
+        let user = pubkey(user)?;
+        Ok(svs1::redeem(&self.0, &user, shares, min_assets_out).into())
+    }
+
+    /// `configure_account` verifying the proof `proof_instruction_offset`
+    /// instructions away in the same transaction
+    fn svs2_configure_account(
+        &self,
+        user: &str,
+        decryptable_zero_balance: &[u8],
+        proof_instruction_offset: i8,
+    ) -> PyResult<Instruction> {
+        Ok(svs2::configure_account(
+            &self.0,
+            &pubkey(user)?,
+            None,
+            bytes("decryptable_zero_balance", decryptable_zero_balance)?,
+            PubkeyValidityProof::InstructionOffset(proof_instruction_offset),
+        )
+        .into())
+    }
+
+    #[pyo3(signature = (user, assets, min_shares_out, memo_hash = None))]
+    fn svs2_deposit(
+        &self,
+        user: &str,
+        assets: u64,
+        min_shares_out: u64,
+        memo_hash: Option<&[u8]>,
+    ) -> PyResult<Instruction> {
+        let memo_hash = memo_hash.map(|hash| bytes("memo_hash", hash)).transpose()?;
+        Ok(svs2::deposit(&self.0, &pubkey(user)?, assets, min_shares_out, memo_hash).into())
+    }
+
+    fn svs2_apply_pending(
+        &self,
+        user: &str,
+        new_decryptable_available_balance: &[u8],
+        expected_pending_balance_credit_counter: u64,
+    ) -> PyResult<Instruction> {
+        Ok(svs2::apply_pending(
+            &self.0,
+            &pubkey(user)?,
+            bytes(
+                "new_decryptable_available_balance",
+                new_decryptable_available_balance,
+            )?,
+            expected_pending_balance_credit_counter,
+        )
+        .into())
+    }
+
+    fn svs2_withdraw(
+        &self,
+        user: &str,
+        equality_proof_context: &str,
+        range_proof_context: &str,
+        assets: u64,
+        max_shares_in: u64,
+        new_decryptable_available_balance: &[u8],
+    ) -> PyResult<Instruction> {
+        let proofs = WithdrawProofContexts {
+            equality: pubkey(equality_proof_context)?,
+            range: pubkey(range_proof_context)?,
+        };
+        Ok(svs2::withdraw(
+            &self.0,
+            &pubkey(user)?,
+            &proofs,
+            assets,
+            max_shares_in,
+            bytes(
+                "new_decryptable_available_balance",
+                new_decryptable_available_balance,
+            )?,
+            None,
+        )
+        .into())
+    }
+
+    fn svs2_redeem(
+        &self,
+        user: &str,
+        equality_proof_context: &str,
+        range_proof_context: &str,
+        shares: u64,
+        min_assets_out: u64,
+        new_decryptable_available_balance: &[u8],
+    ) -> PyResult<Instruction> {
+        let proofs = WithdrawProofContexts {
+            equality: pubkey(equality_proof_context)?,
+            range: pubkey(range_proof_context)?,
+        };
+        Ok(svs2::redeem(
+            &self.0,
+            &pubkey(user)?,
+            &proofs,
+            shares,
+            min_assets_out,
+            bytes(
+                "new_decryptable_available_balance",
+                new_decryptable_available_balance,
+            )?,
+        )
+        .into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "VaultKeys(program_id={}, vault={})",
+            self.0.program_id, self.0.vault
+        )
+    }
+}
@@ -0,0 +1,56 @@
+//! Python bindings of the SVS client
+//!
+//! Exposes PDA derivation, instruction encoding, account decoding, view math
+//! and RPC reads of `svs-client` as the `svs` Python module, so operations and
+//! accounting can be scripted from notebooks. Build with maturin
+//! (`maturin develop` in `sdk/python`).
+//!
+//! Public keys are base58 strings, amounts are Python ints and instruction
+//! data is `bytes`. Client errors are raised as `svs.SvsError`, malformed
+//! arguments as `ValueError`.
+
+mod client;
+mod instruction;
+mod keys;
+mod state;
+mod view;
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use pyo3::{create_exception, exceptions::PyException, exceptions::PyValueError, prelude::*};
+use svs_client::ClientError;
+
+create_exception!(svs, SvsError, PyException, "A vault client call failed");
+
+fn pubkey(value: &str) -> PyResult<Pubkey> {
+    Pubkey::from_str(value)
+        .map_err(|e| PyValueError::new_err(format!("Invalid public key {value}: {e}")))
+}
+
+fn bytes<const N: usize>(name: &str, value: &[u8]) -> PyResult<[u8; N]> {
+    value.try_into().map_err(|_| {
+        PyValueError::new_err(format!("{name} must be {N} bytes, got {}", value.len()))
+    })
+}
+
+fn error(error: ClientError) -> PyErr {
+    SvsError::new_err(error.to_string())
+}
+
+#[pymodule]
+fn svs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("SvsError", m.py().get_type::<SvsError>())?;
+    m.add_class::<client::Client>()?;
+    m.add_class::<client::Views>()?;
+    m.add_class::<instruction::AccountMeta>()?;
+    m.add_class::<instruction::Instruction>()?;
+    m.add_class::<keys::VaultKeys>()?;
+    m.add_class::<state::Vault>()?;
+    m.add_class::<state::ConfidentialVault>()?;
+    m.add_class::<view::VaultSnapshot>()?;
+    m.add("SVS_1_PROGRAM_ID", svs_1::ID.to_string())?;
+    m.add("SVS_2_PROGRAM_ID", svs_2::ID.to_string())?;
+    m.add("SHARE_PRICE_SCALE", svs_client::view::SHARE_PRICE_SCALE)?;
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! Decoded vault accounts
+
+use anchor_lang::prelude::Pubkey;
+use pyo3::{prelude::*, types::PyBytes};
+use svs_client::{decode_confidential_vault, decode_vault, view::VaultSnapshot};
+
+use crate::{error, view};
+
+/// State of an SVS-1 vault
+#[pyclass(module = "svs", frozen)]
+pub struct Vault {
+    #[pyo3(get)]
+    pub authority: String,
+    #[pyo3(get)]
+    pub asset_mint: String,
+    #[pyo3(get)]
+    pub shares_mint: String,
+    #[pyo3(get)]
+    pub asset_vault: String,
+    #[pyo3(get)]
+    pub total_assets: u64,
+    #[pyo3(get)]
+    pub decimals_offset: u8,
+    #[pyo3(get)]
+    pub paused: bool,
+    #[pyo3(get)]
+    pub vault_id: u64,
+    state: svs_client::Vault,
+}
+
+impl From<svs_client::Vault> for Vault {
+    fn from(vault: svs_client::Vault) -> Self {
+        Self {
+            authority: vault.authority.to_string(),
+            asset_mint: vault.asset_mint.to_string(),
+            shares_mint: vault.shares_mint.to_string(),
+            asset_vault: vault.asset_vault.to_string(),
+            total_assets: vault.total_assets,
+            decimals_offset: vault.decimals_offset,
+            paused: vault.paused,
+            vault_id: vault.vault_id,
+            state: vault,
+        }
+    }
+}
+
+#[pymethods]
+impl Vault {
+    /// Decode the data of an SVS-1 vault account
+    #[staticmethod]
+    fn from_account(data: &[u8]) -> PyResult<Self> {
+        let vault = decode_vault(&Pubkey::default(), data).map_err(error)?;
+        Ok(vault.into())
+    }
+
+    /// Snapshot for computing views; the share count is the shares mint
+    /// supply
+    fn snapshot(&self, shares_supply: u64) -> view::VaultSnapshot {
+        view::VaultSnapshot(VaultSnapshot::from_vault(&self.state, shares_supply))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Vault(asset_mint={}, total_assets={}, paused={})",
+            self.asset_mint,
+            self.total_assets,
+            if self.paused { "True" } else { "False" },
+        )
+    }
+}
+
+/// State of an SVS-2 confidential vault; unset optional keys are `None`
+#[pyclass(module = "svs", frozen)]
+pub struct ConfidentialVault {
+    #[pyo3(get)]
+    pub authority: String,
+    #[pyo3(get)]
+    pub asset_mint: String,
+    #[pyo3(get)]
+    pub shares_mint: String,
+    #[pyo3(get)]
+    pub asset_vault: String,
+    #[pyo3(get)]
+    pub total_assets: u64,
+    #[pyo3(get)]
+    pub decimals_offset: u8,
+    #[pyo3(get)]
+    pub paused: bool,
+    #[pyo3(get)]
+    pub vault_id: u64,
+    #[pyo3(get)]
+    pub confidential_authority: String,
+    #[pyo3(get)]
+    pub guardian: Option<String>,
+    #[pyo3(get)]
+    pub swap_adapter: Option<String>,
+    #[pyo3(get)]
+    pub harvester: Option<String>,
+    #[pyo3(get)]
+    pub locked_profit: u64,
+    #[pyo3(get)]
+    pub last_harvest_ts: i64,
+    #[pyo3(get)]
+    pub profit_unlock_period: i64,
+    state: svs_client::ConfidentialVault,
+}
+
+fn optional(key: &Pubkey) -> Option<String> {
+    (*key != Pubkey::default()).then(|| key.to_string())
+}
+
+impl From<svs_client::ConfidentialVault> for ConfidentialVault {
+    fn from(vault: svs_client::ConfidentialVault) -> Self {
+        Self {
+            authority: vault.authority.to_string(),
+            asset_mint: vault.asset_mint.to_string(),
+            shares_mint: vault.shares_mint.to_string(),
+            asset_vault: vault.asset_vault.to_string(),
+            total_assets: vault.total_assets,
+            decimals_offset: vault.decimals_offset,
+            paused: vault.paused,
+            vault_id: vault.vault_id,
+            confidential_authority: vault.confidential_authority.to_string(),
+            guardian: optional(&vault.guardian),
+            swap_adapter: optional(&vault.swap_adapter),
+            harvester: optional(&vault.harvester),
+            locked_profit: vault.locked_profit,
+            last_harvest_ts: vault.last_harvest_ts,
+            profit_unlock_period: vault.profit_unlock_period,
+            state: vault,
+        }
+    }
+}
+
+#[pymethods]
+impl ConfidentialVault {
+    /// Decode the data of an SVS-2 vault account
+    #[staticmethod]
+    fn from_account(data: &[u8]) -> PyResult<Self> {
+        let vault = decode_confidential_vault(&Pubkey::default(), data).map_err(error)?;
+        Ok(vault.into())
+    }
+
+    /// Auditor ElGamal public key (32 bytes), if the vault has one
+    #[getter]
+    fn auditor_elgamal_pubkey<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        self.state
+            .auditor_elgamal_pubkey
+            .map(|pubkey| PyBytes::new(py, &pubkey))
+    }
+
+    /// Snapshot for computing views at unix time `now`, excluding profit
+    /// still unlocking; the share count is the shares mint supply
+    fn snapshot(&self, shares_supply: u64, now: i64) -> PyResult<view::VaultSnapshot> {
+        let snapshot = VaultSnapshot::from_confidential_vault(&self.state, shares_supply, now)
+            .map_err(error)?;
+        Ok(view::VaultSnapshot(snapshot))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConfidentialVault(asset_mint={}, total_assets={}, paused={})",
+            self.asset_mint,
+            self.total_assets,
+            if self.paused { "True" } else { "False" },
+        )
+    }
+}
@@ -0,0 +1,93 @@
+//! Vault views computed locally
+
+use pyo3::prelude::*;
+
+use crate::error;
+
+/// Vault state for computing views locally, with the programs' own rounding
+#[pyclass(module = "svs", frozen)]
+#[derive(Clone)]
+pub struct VaultSnapshot(pub(crate) svs_client::view::VaultSnapshot);
+
+#[pymethods]
+impl VaultSnapshot {
+    #[new]
+    #[pyo3(signature = (total_assets, total_shares, decimals_offset, paused = false))]
+    fn new(total_assets: u64, total_shares: u64, decimals_offset: u8, paused: bool) -> Self {
+        Self(svs_client::view::VaultSnapshot {
+            total_assets,
+            total_shares,
+            decimals_offset,
+            paused,
+        })
+    }
+
+    #[getter]
+    fn total_assets(&self) -> u64 {
+        self.0.total_assets
+    }
+
+    #[getter]
+    fn total_shares(&self) -> u64 {
+        self.0.total_shares
+    }
+
+    #[getter]
+    fn decimals_offset(&self) -> u8 {
+        self.0.decimals_offset
+    }
+
+    #[getter]
+    fn paused(&self) -> bool {
+        self.0.paused
+    }
+
+    /// Shares minted for depositing `assets`
+    fn preview_deposit(&self, assets: u64) -> PyResult<u64> {
+        self.0.preview_deposit(assets).map_err(error)
+    }
+
+    /// Assets required to mint `shares`
+    fn preview_mint(&self, shares: u64) -> PyResult<u64> {
+        self.0.preview_mint(shares).map_err(error)
+    }
+
+    /// Shares burned to withdraw `assets`
+    fn preview_withdraw(&self, assets: u64) -> PyResult<u64> {
+        self.0.preview_withdraw(assets).map_err(error)
+    }
+
+    /// Assets received for redeeming `shares`
+    fn preview_redeem(&self, shares: u64) -> PyResult<u64> {
+        self.0.preview_redeem(shares).map_err(error)
+    }
+
+    /// Price of one whole share in whole assets, with `scale` as 1.0
+    #[pyo3(signature = (scale = svs_client::view::SHARE_PRICE_SCALE))]
+    fn share_price(&self, scale: u64) -> PyResult<u64> {
+        self.0.share_price(scale).map_err(error)
+    }
+
+    fn max_deposit(&self) -> u64 {
+        self.0.max_deposit()
+    }
+
+    /// Assets redeemable for `owner_shares`
+    fn max_withdraw(&self, owner_shares: u64) -> PyResult<u64> {
+        self.0.max_withdraw(owner_shares).map_err(error)
+    }
+
+    fn max_redeem(&self, owner_shares: u64) -> u64 {
+        self.0.max_redeem(owner_shares)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "VaultSnapshot(total_assets={}, total_shares={}, decimals_offset={}, paused={})",
+            self.0.total_assets,
+            self.0.total_shares,
+            self.0.decimals_offset,
+            if self.0.paused { "True" } else { "False" },
+        )
+    }
+}
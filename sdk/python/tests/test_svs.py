@@ -0,0 +1,62 @@
+import pytest
+
+import svs
+
+TOKEN_PROGRAM = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+ASSET_MINT = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+USER = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"
+
+
+@pytest.fixture
+def keys():
+    return svs.VaultKeys.derive(svs.SVS_1_PROGRAM_ID, ASSET_MINT, 0, TOKEN_PROGRAM)
+
+
+def test_derive(keys):
+    assert keys.program_id == svs.SVS_1_PROGRAM_ID
+    assert keys.asset_mint == ASSET_MINT
+    assert keys.shares_account(USER) != keys.asset_account(USER)
+    again = svs.VaultKeys.derive(svs.SVS_1_PROGRAM_ID, ASSET_MINT, 1, TOKEN_PROGRAM)
+    assert again.vault != keys.vault
+
+
+def test_svs1_deposit(keys):
+    ix = keys.svs1_deposit(USER, 1_000_000, 990_000)
+    assert ix.program_id == svs.SVS_1_PROGRAM_ID
+    assert ix.accounts[0].pubkey == USER
+    assert ix.accounts[0].is_signer
+    assert isinstance(ix.data, bytes)
+    # Discriminator, assets, min_shares_out
+    assert len(ix.data) == 8 + 8 + 8
+    assert int.from_bytes(ix.data[8:16], "little") == 1_000_000
+
+
+def test_svs2_deposit_memo(keys):
+    without = keys.svs2_deposit(USER, 1_000, 990)
+    with_memo = keys.svs2_deposit(USER, 1_000, 990, memo_hash=bytes(32))
+    assert len(with_memo.data) == len(without.data) + 32
+    with pytest.raises(ValueError):
+        keys.svs2_deposit(USER, 1_000, 990, memo_hash=bytes(31))
+
+
+def test_snapshot_previews():
+    snapshot = svs.VaultSnapshot(1_000_000, 1_000_000_000, 3)
+    shares = snapshot.preview_deposit(1_000)
+    assert shares > 0
+    assert snapshot.preview_mint(shares) >= 1_000
+    assert snapshot.preview_redeem(shares) <= 1_000
+    assert snapshot.share_price() == svs.SHARE_PRICE_SCALE
+    assert snapshot.share_price(10**18) // 10**6 == snapshot.share_price()
+
+    paused = svs.VaultSnapshot(1_000_000, 1_000_000_000, 3, paused=True)
+    assert paused.max_withdraw(shares) == 0
+    assert paused.max_deposit() == 0
+
+
+def test_errors():
+    with pytest.raises(ValueError):
+        svs.VaultKeys.derive("not a key", ASSET_MINT, 0, TOKEN_PROGRAM)
+    with pytest.raises(svs.SvsError):
+        svs.Vault.from_account(bytes(64))
+    with pytest.raises(svs.SvsError):
+        svs.VaultSnapshot(1_000_000, 1_000_000_000, 3).preview_deposit(2**64 - 1)
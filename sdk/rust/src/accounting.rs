@@ -0,0 +1,706 @@
+//! Share accounting and PnL of one wallet in one vault
+//!
+//! [`Ledger`] replays the vault's event history (e.g. parsed with
+//! `svs-events`) in order. Every deposit and withdrawal moves the vault's
+//! totals, harvests add profit that unlocks over the SVS-2 unlock period, and
+//! syncs reset the assets, so the share price is known at every event without
+//! fetching historical account state. The wallet's own deposits open cost
+//! lots and its withdrawals close them, realizing the difference.
+//!
+//! ```ignore
+//! let mut ledger = Ledger::new(keys.vault, user, vault.decimals_offset, CostBasis::Fifo);
+//! for parsed in events {
+//!     ledger.apply(&HistoryEntry::new(block_time, parsed.context.signature, parsed.event))?;
+//! }
+//! let report = ledger.report(now)?;
+//! ledger.write_csv(File::create("pnl.csv")?)?;
+//! ```
+//!
+//! The history has to start at the vault's initialization for the
+//! reconstructed totals to be exact; otherwise value the position against a
+//! fetched snapshot with [`Ledger::report_with`].
+
+use std::{collections::VecDeque, fmt, io};
+
+use anchor_lang::prelude::Pubkey;
+use svs_interface::events::VaultEvent;
+use svs_math::{locked_profit, mul_div, MathError, Rounding};
+
+use crate::{view::VaultSnapshot, ClientError, Result};
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// How withdrawn shares are matched with the deposits that minted them
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CostBasis {
+    /// Oldest shares first
+    #[default]
+    Fifo,
+    /// Every share at the average cost of the position
+    Average,
+}
+
+/// A vault event and when it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Block time of the transaction
+    pub timestamp: i64,
+    /// Where the event came from, e.g. the transaction signature
+    pub reference: String,
+    pub event: VaultEvent,
+}
+
+impl HistoryEntry {
+    pub fn new(timestamp: i64, reference: impl fmt::Display, event: VaultEvent) -> Self {
+        Self {
+            timestamp,
+            reference: reference.to_string(),
+            event,
+        }
+    }
+}
+
+/// Change of the wallet's position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Deposit,
+    Withdraw,
+    EmergencyRedeem,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Deposit => "deposit",
+            Operation::Withdraw => "withdraw",
+            Operation::EmergencyRedeem => "emergency_redeem",
+        })
+    }
+}
+
+/// One change of the wallet's position, a row of the CSV export
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub timestamp: i64,
+    pub reference: String,
+    pub operation: Operation,
+    /// Assets paid in or received
+    pub assets: u64,
+    /// Shares minted or burned
+    pub shares: u64,
+    /// Cost basis of the burned shares, 0 for deposits
+    pub cost: u64,
+    /// `assets - cost` of a withdrawal, 0 for deposits
+    pub realized_pnl: i128,
+    /// Shares held after the operation
+    pub position_shares: u64,
+    /// Cost basis of the shares held after the operation
+    pub position_cost: u64,
+    /// Assets per share after the operation, excluding locked profit
+    pub share_price: f64,
+}
+
+/// The wallet's position valued at one point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    pub shares: u64,
+    pub cost_basis: u64,
+    /// Assets the shares redeem for
+    pub value: u64,
+    /// `value - cost_basis`
+    pub unrealized_pnl: i128,
+    /// Sum over all withdrawals of proceeds minus cost basis
+    pub realized_pnl: i128,
+    /// Assets deposited over the whole history
+    pub deposited: u128,
+    /// Assets withdrawn over the whole history
+    pub withdrawn: u128,
+    pub share_price: f64,
+    /// Annualized share price growth since the wallet's first deposit. Being
+    /// time-weighted, it does not depend on when or how much the wallet
+    /// deposited. `None` before the first deposit or a second later.
+    pub apy: Option<f64>,
+}
+
+/// Shares minted together and what they cost
+#[derive(Debug, Clone, Copy)]
+struct Lot {
+    shares: u64,
+    cost: u64,
+}
+
+/// Vault and wallet state rebuilt from the event history
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    vault: Pubkey,
+    wallet: Pubkey,
+    method: CostBasis,
+    decimals_offset: u8,
+    total_assets: u64,
+    total_shares: u64,
+    locked_profit: u64,
+    last_harvest_ts: i64,
+    profit_unlock_period: i64,
+    paused: bool,
+    last_timestamp: i64,
+    lots: VecDeque<Lot>,
+    realized_pnl: i128,
+    deposited: u128,
+    withdrawn: u128,
+    /// Time and share price of the wallet's first deposit
+    entry: Option<(i64, f64)>,
+    records: Vec<Record>,
+}
+
+impl Ledger {
+    /// Empty ledger of `wallet` in `vault`, whose decimals offset is
+    /// `decimals_offset`
+    pub fn new(vault: Pubkey, wallet: Pubkey, decimals_offset: u8, method: CostBasis) -> Self {
+        Self {
+            vault,
+            wallet,
+            method,
+            decimals_offset,
+            total_assets: 0,
+            total_shares: 0,
+            locked_profit: 0,
+            last_harvest_ts: 0,
+            profit_unlock_period: 0,
+            paused: false,
+            last_timestamp: i64::MIN,
+            lots: VecDeque::new(),
+            realized_pnl: 0,
+            deposited: 0,
+            withdrawn: 0,
+            entry: None,
+            records: Vec::new(),
+        }
+    }
+
+    /// Replay the next event; events of other vaults are ignored
+    pub fn apply(&mut self, entry: &HistoryEntry) -> Result<()> {
+        if entry.timestamp < self.last_timestamp {
+            return Err(ClientError::History(format!(
+                "event {} at {} follows one at {}",
+                entry.reference, entry.timestamp, self.last_timestamp
+            )));
+        }
+
+        let now = entry.timestamp;
+        match &entry.event {
+            VaultEvent::Deposit(deposit) if deposit.vault == self.vault => {
+                self.total_assets = add(self.total_assets, deposit.assets)?;
+                self.total_shares = add(self.total_shares, deposit.shares)?;
+                if deposit.owner == self.wallet {
+                    self.deposit(entry, deposit.assets, deposit.shares)?;
+                }
+            }
+            VaultEvent::Withdraw(withdraw) if withdraw.vault == self.vault => {
+                self.total_assets = sub(self.total_assets, withdraw.assets, entry)?;
+                self.total_shares = sub(self.total_shares, withdraw.shares, entry)?;
+                if withdraw.owner == self.wallet {
+                    let (assets, shares) = (withdraw.assets, withdraw.shares);
+                    self.withdraw(entry, Operation::Withdraw, assets, shares)?;
+                }
+            }
+            VaultEvent::EmergencyRedeem(redeem) if redeem.vault == self.vault => {
+                // Mirrors the program, which saturates here
+                self.total_assets = self.total_assets.saturating_sub(redeem.assets);
+                self.total_shares = self.total_shares.saturating_sub(redeem.shares);
+                if redeem.owner == self.wallet {
+                    let (assets, shares) = (redeem.assets, redeem.shares);
+                    self.withdraw(entry, Operation::EmergencyRedeem, assets, shares)?;
+                }
+            }
+            VaultEvent::VaultSynced(synced) if synced.vault == self.vault => {
+                self.total_assets = synced.new_total;
+            }
+            VaultEvent::Harvested(harvested) if harvested.vault == self.vault => {
+                self.total_assets = add(self.total_assets, harvested.assets_added)?;
+                self.locked_profit = harvested.locked_profit;
+                self.last_harvest_ts = now;
+            }
+            VaultEvent::HarvestConfigUpdated(config) if config.vault == self.vault => {
+                self.profit_unlock_period = config.profit_unlock_period;
+            }
+            VaultEvent::VaultStatusChanged(status) if status.vault == self.vault => {
+                self.paused = status.paused;
+            }
+            _ => {}
+        }
+
+        self.last_timestamp = now;
+        Ok(())
+    }
+
+    /// Replay `entries` in order
+    pub fn apply_all<'a>(
+        &mut self,
+        entries: impl IntoIterator<Item = &'a HistoryEntry>,
+    ) -> Result<()> {
+        entries.into_iter().try_for_each(|entry| self.apply(entry))
+    }
+
+    fn deposit(&mut self, entry: &HistoryEntry, assets: u64, shares: u64) -> Result<()> {
+        let lot = Lot {
+            shares,
+            cost: assets,
+        };
+        match (self.method, self.lots.front_mut()) {
+            (CostBasis::Average, Some(position)) => {
+                position.shares = add(position.shares, shares)?;
+                position.cost = add(position.cost, assets)?;
+            }
+            _ => self.lots.push_back(lot),
+        }
+        self.deposited += u128::from(assets);
+
+        let share_price = self.share_price_at(entry.timestamp)?;
+        self.entry.get_or_insert((entry.timestamp, share_price));
+        self.record(entry, Operation::Deposit, assets, shares, 0, share_price);
+        Ok(())
+    }
+
+    fn withdraw(
+        &mut self,
+        entry: &HistoryEntry,
+        operation: Operation,
+        assets: u64,
+        shares: u64,
+    ) -> Result<()> {
+        let held = self.position().0;
+        if shares > held {
+            return Err(ClientError::History(format!(
+                "{} burns {shares} shares of {} holding {held}",
+                entry.reference, self.wallet
+            )));
+        }
+
+        let mut cost = 0u64;
+        let mut remaining = shares;
+        while remaining > 0 {
+            let lot = self.lots.front_mut().expect("shares are held");
+            let taken = remaining.min(lot.shares);
+            // Partial lots keep the rounding remainder of their cost
+            let taken_cost = if taken == lot.shares {
+                lot.cost
+            } else {
+                mul_div(lot.cost, taken, lot.shares, Rounding::Floor).map_err(ClientError::Math)?
+            };
+            lot.shares -= taken;
+            lot.cost -= taken_cost;
+            cost += taken_cost;
+            remaining -= taken;
+            if lot.shares == 0 {
+                self.lots.pop_front();
+            }
+        }
+
+        let realized = i128::from(assets) - i128::from(cost);
+        self.realized_pnl += realized;
+        self.withdrawn += u128::from(assets);
+
+        let share_price = self.share_price_at(entry.timestamp)?;
+        self.record(entry, operation, assets, shares, cost, share_price);
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        entry: &HistoryEntry,
+        operation: Operation,
+        assets: u64,
+        shares: u64,
+        cost: u64,
+        share_price: f64,
+    ) {
+        let (position_shares, position_cost) = self.position();
+        let realized_pnl = match operation {
+            Operation::Deposit => 0,
+            _ => i128::from(assets) - i128::from(cost),
+        };
+        self.records.push(Record {
+            timestamp: entry.timestamp,
+            reference: entry.reference.clone(),
+            operation,
+            assets,
+            shares,
+            cost,
+            realized_pnl,
+            position_shares,
+            position_cost,
+            share_price,
+        });
+    }
+
+    /// Shares held and their cost basis
+    pub fn position(&self) -> (u64, u64) {
+        self.lots.iter().fold((0, 0), |(shares, cost), lot| {
+            (shares + lot.shares, cost + lot.cost)
+        })
+    }
+
+    /// Changes of the wallet's position so far
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Reconstructed vault state at unix time `now`
+    pub fn snapshot(&self, now: i64) -> Result<VaultSnapshot> {
+        let locked = locked_profit(
+            self.locked_profit,
+            self.last_harvest_ts,
+            self.profit_unlock_period,
+            now,
+        )
+        .map_err(ClientError::Math)?;
+
+        Ok(VaultSnapshot {
+            total_assets: self.total_assets.saturating_sub(locked),
+            total_shares: self.total_shares,
+            decimals_offset: self.decimals_offset,
+            paused: self.paused,
+        })
+    }
+
+    fn share_price_at(&self, now: i64) -> Result<f64> {
+        Ok(share_price(&self.snapshot(now)?))
+    }
+
+    /// Position at unix time `now`, valued with the reconstructed vault state
+    pub fn report(&self, now: i64) -> Result<Report> {
+        self.report_with(&self.snapshot(now)?, now)
+    }
+
+    /// Position at unix time `now`, valued with `snapshot`, e.g. fetched
+    /// from the chain when the history is incomplete
+    pub fn report_with(&self, snapshot: &VaultSnapshot, now: i64) -> Result<Report> {
+        let (shares, cost_basis) = self.position();
+        let value = snapshot.preview_redeem(shares)?;
+        let share_price = share_price(snapshot);
+
+        let apy = self.entry.and_then(|(since, entry_price)| {
+            let elapsed = now.checked_sub(since).filter(|elapsed| *elapsed > 0)?;
+            let growth = share_price / entry_price;
+            Some(growth.powf(SECONDS_PER_YEAR / elapsed as f64) - 1.0)
+        });
+
+        Ok(Report {
+            shares,
+            cost_basis,
+            value,
+            unrealized_pnl: i128::from(value) - i128::from(cost_basis),
+            realized_pnl: self.realized_pnl,
+            deposited: self.deposited,
+            withdrawn: self.withdrawn,
+            share_price,
+            apy,
+        })
+    }
+
+    /// Write the wallet's records as CSV, with a header row
+    pub fn write_csv(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "timestamp,reference,operation,assets,shares,cost,realized_pnl,\
+             position_shares,position_cost,share_price"
+        )?;
+        for record in &self.records {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{}",
+                record.timestamp,
+                csv_field(&record.reference),
+                record.operation,
+                record.assets,
+                record.shares,
+                record.cost,
+                record.realized_pnl,
+                record.position_shares,
+                record.position_cost,
+                record.share_price,
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+/// Assets per share, with the same virtual offset as the conversions
+pub fn share_price(snapshot: &VaultSnapshot) -> f64 {
+    let virtual_assets = snapshot.total_assets as f64 + 1.0;
+    let virtual_shares = snapshot.total_shares as f64 + 10f64.powi(snapshot.decimals_offset.into());
+    virtual_assets / virtual_shares
+}
+
+fn add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b)
+        .ok_or(ClientError::Math(MathError::Overflow))
+}
+
+fn sub(a: u64, b: u64, entry: &HistoryEntry) -> Result<u64> {
+    a.checked_sub(b).ok_or_else(|| {
+        ClientError::History(format!(
+            "{} withdraws more than the vault holds",
+            entry.reference
+        ))
+    })
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use svs_interface::events::{Deposit, HarvestConfigUpdated, Harvested, VaultSynced, Withdraw};
+
+    use super::*;
+
+    struct History {
+        vault: Pubkey,
+        wallet: Pubkey,
+        entries: Vec<HistoryEntry>,
+    }
+
+    impl History {
+        fn new() -> Self {
+            Self {
+                vault: Pubkey::new_unique(),
+                wallet: Pubkey::new_unique(),
+                entries: Vec::new(),
+            }
+        }
+
+        fn next_sequence(&self) -> u64 {
+            self.entries.len() as u64 + 1
+        }
+
+        fn push(&mut self, timestamp: i64, event: VaultEvent) -> &mut Self {
+            let reference = format!("tx{}", self.entries.len());
+            self.entries
+                .push(HistoryEntry::new(timestamp, reference, event));
+            self
+        }
+
+        fn deposit(
+            &mut self,
+            timestamp: i64,
+            owner: Pubkey,
+            assets: u64,
+            shares: u64,
+        ) -> &mut Self {
+            let event = VaultEvent::Deposit(Deposit {
+                vault: self.vault,
+                caller: owner,
+                owner,
+                assets,
+                shares,
+                op_counter: self.next_sequence(),
+                sequence: self.next_sequence(),
+                timestamp,
+            });
+            self.push(timestamp, event)
+        }
+
+        fn withdraw(
+            &mut self,
+            timestamp: i64,
+            owner: Pubkey,
+            assets: u64,
+            shares: u64,
+        ) -> &mut Self {
+            let event = VaultEvent::Withdraw(Withdraw {
+                vault: self.vault,
+                caller: owner,
+                receiver: owner,
+                owner,
+                assets,
+                shares,
+                op_counter: self.next_sequence(),
+                sequence: self.next_sequence(),
+                timestamp,
+            });
+            self.push(timestamp, event)
+        }
+
+        fn sync(&mut self, timestamp: i64, previous_total: u64, new_total: u64) -> &mut Self {
+            let event = VaultEvent::VaultSynced(VaultSynced {
+                vault: self.vault,
+                previous_total,
+                new_total,
+                op_counter: self.next_sequence(),
+                sequence: self.next_sequence(),
+                timestamp,
+            });
+            self.push(timestamp, event)
+        }
+
+        fn ledger(&self, method: CostBasis) -> Result<Ledger> {
+            let mut ledger = Ledger::new(self.vault, self.wallet, 0, method);
+            ledger.apply_all(&self.entries)?;
+            Ok(ledger)
+        }
+    }
+
+    /// Deposit, yield of 10%, deposit again and withdraw the first lot's
+    /// worth of shares
+    fn two_deposits() -> History {
+        let mut history = History::new();
+        let wallet = history.wallet;
+        history
+            .deposit(0, wallet, 1_000, 1_000)
+            .sync(100, 1_000, 1_100)
+            .deposit(100, wallet, 1_100, 1_000)
+            .withdraw(200, wallet, 1_100, 1_000);
+        history
+    }
+
+    #[test]
+    fn test_fifo_and_average_cost() {
+        let history = two_deposits();
+
+        let fifo = history
+            .ledger(CostBasis::Fifo)
+            .unwrap()
+            .report(200)
+            .unwrap();
+        assert_eq!((fifo.shares, fifo.cost_basis), (1_000, 1_100));
+        assert_eq!(fifo.realized_pnl, 100);
+        // 1_000 * 1_101 / 1_001, rounded down
+        assert_eq!(fifo.value, 1_099);
+        assert_eq!(fifo.unrealized_pnl, -1);
+
+        let average = history
+            .ledger(CostBasis::Average)
+            .unwrap()
+            .report(200)
+            .unwrap();
+        assert_eq!((average.shares, average.cost_basis), (1_000, 1_050));
+        assert_eq!(average.realized_pnl, 50);
+        assert_eq!(average.unrealized_pnl, 49);
+
+        // Either way the total PnL is the same
+        assert_eq!(
+            fifo.realized_pnl + fifo.unrealized_pnl,
+            average.realized_pnl + average.unrealized_pnl
+        );
+        assert_eq!((fifo.deposited, fifo.withdrawn), (2_100, 1_100));
+    }
+
+    #[test]
+    fn test_other_wallets_and_vaults() {
+        let mut history = two_deposits();
+        let other = Pubkey::new_unique();
+        history.deposit(300, other, 2_200, 2_000);
+        history.push(
+            300,
+            VaultEvent::Deposit(Deposit {
+                vault: Pubkey::new_unique(),
+                caller: history.wallet,
+                owner: history.wallet,
+                assets: 5_000,
+                shares: 5_000,
+                op_counter: 1,
+                sequence: 1,
+                timestamp: 300,
+            }),
+        );
+
+        let ledger = history.ledger(CostBasis::Fifo).unwrap();
+        assert_eq!(ledger.position(), (1_000, 1_100));
+        assert_eq!(ledger.records().len(), 3);
+        let snapshot = ledger.snapshot(300).unwrap();
+        assert_eq!(
+            (snapshot.total_assets, snapshot.total_shares),
+            (3_300, 3_000)
+        );
+    }
+
+    #[test]
+    fn test_incomplete_history() {
+        let mut history = History::new();
+        let wallet = history.wallet;
+        history
+            .deposit(0, wallet, 1_000, 1_000)
+            .withdraw(10, wallet, 2_000, 2_000);
+        assert!(matches!(
+            history.ledger(CostBasis::Fifo),
+            Err(ClientError::History(_))
+        ));
+
+        let mut history = History::new();
+        let wallet = history.wallet;
+        history
+            .deposit(10, wallet, 1_000, 1_000)
+            .deposit(0, wallet, 1_000, 1_000);
+        assert!(matches!(
+            history.ledger(CostBasis::Fifo),
+            Err(ClientError::History(_))
+        ));
+    }
+
+    #[test]
+    fn test_locked_profit_and_apy() {
+        let mut history = History::new();
+        let (vault, wallet) = (history.vault, history.wallet);
+        history
+            .push(
+                0,
+                VaultEvent::HarvestConfigUpdated(HarvestConfigUpdated {
+                    vault,
+                    swap_adapter: Pubkey::new_unique(),
+                    harvester: Pubkey::new_unique(),
+                    profit_unlock_period: 1_000,
+                    op_counter: 1,
+                    sequence: 1,
+                    timestamp: 0,
+                }),
+            )
+            .deposit(0, wallet, 1_000_000, 1_000_000)
+            .push(
+                0,
+                VaultEvent::Harvested(Harvested {
+                    vault,
+                    reward_mint: Pubkey::new_unique(),
+                    reward_amount: 7,
+                    assets_added: 100_000,
+                    locked_profit: 100_000,
+                    op_counter: 3,
+                    sequence: 3,
+                    timestamp: 0,
+                }),
+            );
+        let ledger = history.ledger(CostBasis::Fifo).unwrap();
+
+        assert_eq!(ledger.snapshot(0).unwrap().total_assets, 1_000_000);
+        assert_eq!(ledger.snapshot(500).unwrap().total_assets, 1_050_000);
+        assert_eq!(ledger.snapshot(1_000).unwrap().total_assets, 1_100_000);
+        assert_eq!(ledger.report(0).unwrap().apy, None);
+
+        // 10% over half a year compounds to 21% a year
+        let half_year = (SECONDS_PER_YEAR / 2.0) as i64;
+        let apy = ledger.report(half_year).unwrap().apy.unwrap();
+        assert!((apy - 0.21).abs() < 1e-4, "{apy}");
+    }
+
+    #[test]
+    fn test_csv() {
+        let history = two_deposits();
+        let ledger = history.ledger(CostBasis::Fifo).unwrap();
+
+        let mut csv = Vec::new();
+        ledger.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("timestamp,reference,operation,"));
+        assert!(lines[3].starts_with("200,tx3,withdraw,1100,1000,1000,100,1000,1100,"));
+
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,82 @@
+//! Decoding of vault accounts
+//!
+//! Account data is checked against the Anchor discriminator of the expected
+//! type before it is deserialized.
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize};
+
+use svs_1::state::ProtocolConfig;
+
+use crate::{ClientError, ConfidentialVault, Result, Vault};
+
+/// Decode an account as `T`; `address` is only used in errors
+pub fn decode<T: AccountDeserialize>(
+    address: &Pubkey,
+    expected: &'static str,
+    data: &[u8],
+) -> Result<T> {
+    T::try_deserialize(&mut &data[..]).map_err(|e| ClientError::InvalidAccount {
+        address: *address,
+        expected,
+        reason: e.to_string(),
+    })
+}
+
+/// Decode an SVS-1 vault account
+pub fn decode_vault(address: &Pubkey, data: &[u8]) -> Result<Vault> {
+    decode(address, "SVS-1 vault", data)
+}
+
+/// Decode an SVS-2 confidential vault account
+pub fn decode_confidential_vault(address: &Pubkey, data: &[u8]) -> Result<ConfidentialVault> {
+    decode(address, "SVS-2 vault", data)
+}
+
+/// Decode a protocol config account; both programs share its layout
+pub fn decode_protocol_config(address: &Pubkey, data: &[u8]) -> Result<ProtocolConfig> {
+    decode(address, "protocol config", data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    fn vault() -> Vault {
+        Vault {
+            authority: Pubkey::new_unique(),
+            asset_mint: Pubkey::new_unique(),
+            shares_mint: Pubkey::new_unique(),
+            asset_vault: Pubkey::new_unique(),
+            total_assets: 1_000,
+            decimals_offset: 3,
+            bump: 255,
+            paused: false,
+            vault_id: 1,
+            event_sequence: 0,
+            min_deposit_amount: 0,
+            metadata_hash: [0; 32],
+            op_counter: 0,
+            non_transferable: false,
+            _reserved: [0; 7],
+        }
+    }
+
+    #[test]
+    fn test_decode_vault() {
+        let expected = vault();
+        let mut data = Vec::new();
+        expected.try_serialize(&mut data).unwrap();
+
+        let decoded = decode_vault(&Pubkey::new_unique(), &data).unwrap();
+        assert_eq!(decoded.asset_mint, expected.asset_mint);
+        assert_eq!(decoded.total_assets, 1_000);
+
+        // Another account type's discriminator is rejected
+        assert!(matches!(
+            decode_confidential_vault(&Pubkey::new_unique(), &data),
+            Err(ClientError::InvalidAccount { .. })
+        ));
+        assert!(decode_vault(&Pubkey::new_unique(), &data[..8]).is_err());
+    }
+}
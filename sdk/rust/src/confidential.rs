@@ -0,0 +1,237 @@
+//! Confidential shares accounts (SVS-2)
+//!
+//! Keys are derived from wallet signatures the same way as by the proof
+//! backend and the spl-token CLI, so every client arrives at the same keys
+//! for a token account. Derivation signatures are secret key material.
+//!
+//! Balances are decrypted and re-encrypted with the proof core, so the
+//! decryptable balances produced here are exactly the 36-byte AE ciphertexts
+//! the program and Token-2022 expect.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    state::Account,
+};
+use solana_sdk::{signature::Signature, signer::Signer};
+use solana_zk_sdk::encryption::{
+    auth_encryption::{AeCiphertext, AeKey},
+    elgamal::{ElGamalCiphertext, ElGamalKeypair},
+};
+use svs_proof_core::ProofGenerator;
+
+use crate::{svs2::DecryptableBalance, ClientError, Result};
+
+/// Message signed to derive the ElGamal keypair of `token_account`
+pub fn elgamal_key_message(token_account: &Pubkey) -> Vec<u8> {
+    let mut message = b"ElGamalSecretKey".to_vec();
+    message.extend_from_slice(token_account.as_ref());
+    message
+}
+
+/// Message signed to derive the AE key of `token_account`
+pub fn ae_key_message(token_account: &Pubkey) -> Vec<u8> {
+    ProofGenerator::construct_ae_key_message(token_account)
+}
+
+/// Encryption keys of one confidential token account
+pub struct ConfidentialKeys {
+    pub elgamal: ElGamalKeypair,
+    pub ae: AeKey,
+}
+
+impl ConfidentialKeys {
+    /// Derive the keys by having `owner` sign both derivation messages
+    pub fn derive(owner: &impl Signer, token_account: &Pubkey) -> Result<Self> {
+        let elgamal_signature = owner.try_sign_message(&elgamal_key_message(token_account))?;
+        let ae_signature = owner.try_sign_message(&ae_key_message(token_account))?;
+        Self::from_signatures(&elgamal_signature, &ae_signature, token_account)
+    }
+
+    /// Derive the keys from signatures of the derivation messages
+    pub fn from_signatures(
+        elgamal_signature: &Signature,
+        ae_signature: &Signature,
+        token_account: &Pubkey,
+    ) -> Result<Self> {
+        let elgamal_signature: [u8; 64] = (*elgamal_signature).into();
+
+        Ok(Self {
+            elgamal: ProofGenerator::derive_elgamal_keypair(&elgamal_signature, token_account)?,
+            ae: ProofGenerator::derive_ae_key(ae_signature)?,
+        })
+    }
+
+    /// Encrypt `balance` as a decryptable balance
+    pub fn encrypt_balance(&self, balance: u64) -> DecryptableBalance {
+        ProofGenerator::encrypt_ae_balance(&self.ae, balance)
+    }
+
+    /// Decrypt a decryptable balance
+    pub fn decrypt_balance(&self, balance: &DecryptableBalance) -> Result<u64> {
+        let ciphertext = AeCiphertext::from_bytes(balance).ok_or_else(|| {
+            svs_proof_core::ProofError::InvalidInput("Malformed decryptable balance".to_string())
+        })?;
+        Ok(ProofGenerator::decrypt_ae_balance(&self.ae, &ciphertext)?)
+    }
+
+    /// Available balance of `state`, from its decryptable balance
+    pub fn available_balance(&self, state: &ConfidentialAccountState) -> Result<u64> {
+        Ok(ProofGenerator::decrypt_ae_balance(
+            &self.ae,
+            &state.decryptable_available_balance,
+        )?)
+    }
+
+    /// Pending balance of `state`; each part must be below 2^32
+    pub fn pending_balance(&self, state: &ConfidentialAccountState) -> Result<u64> {
+        Ok(ProofGenerator::decrypt_pending_balance(
+            &self.elgamal,
+            &state.pending_balance_lo,
+            &state.pending_balance_hi,
+        )?)
+    }
+
+    /// Arguments of `apply_pending` for `state`
+    pub fn apply_pending(&self, state: &ConfidentialAccountState) -> Result<PendingApplication> {
+        let applied = ProofGenerator::apply_pending_balance(
+            &self.elgamal,
+            &self.ae,
+            &state.decryptable_available_balance,
+            &state.pending_balance_lo,
+            &state.pending_balance_hi,
+        )?;
+
+        Ok(PendingApplication {
+            new_available_balance: applied.new_available_balance,
+            new_decryptable_available_balance: applied.new_decryptable_available_balance,
+            expected_pending_balance_credit_counter: state.pending_balance_credit_counter,
+        })
+    }
+}
+
+/// Result of applying the pending balance, see [`crate::svs2::apply_pending`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingApplication {
+    pub new_available_balance: u64,
+    pub new_decryptable_available_balance: DecryptableBalance,
+    /// Credits included in the applied balance; later credits stay pending
+    pub expected_pending_balance_credit_counter: u64,
+}
+
+/// Confidential transfer state of a Token-2022 account
+#[derive(Debug, Clone)]
+pub struct ConfidentialAccountState {
+    /// Wallet that owns the token account
+    pub owner: Pubkey,
+    pub pending_balance_lo: ElGamalCiphertext,
+    pub pending_balance_hi: ElGamalCiphertext,
+    pub available_balance: ElGamalCiphertext,
+    pub decryptable_available_balance: AeCiphertext,
+    pub pending_balance_credit_counter: u64,
+}
+
+impl ConfidentialAccountState {
+    /// Decode a token account configured for confidential transfers
+    pub fn parse(address: &Pubkey, data: &[u8]) -> Result<Self> {
+        let invalid = |reason: String| ClientError::InvalidAccount {
+            address: *address,
+            expected: "confidential token account",
+            reason,
+        };
+
+        let account =
+            StateWithExtensions::<Account>::unpack(data).map_err(|e| invalid(e.to_string()))?;
+        let extension = account
+            .get_extension::<ConfidentialTransferAccount>()
+            .map_err(|e| invalid(e.to_string()))?;
+
+        let elgamal = |pod: &_| {
+            ElGamalCiphertext::from_bytes(bytemuck::bytes_of(pod))
+                .ok_or_else(|| invalid("malformed balance ciphertext".to_string()))
+        };
+
+        Ok(Self {
+            owner: account.base.owner,
+            pending_balance_lo: elgamal(&extension.pending_balance_lo)?,
+            pending_balance_hi: elgamal(&extension.pending_balance_hi)?,
+            available_balance: elgamal(&extension.available_balance)?,
+            decryptable_available_balance: AeCiphertext::from_bytes(bytemuck::bytes_of(
+                &extension.decryptable_available_balance,
+            ))
+            .ok_or_else(|| invalid("malformed decryptable balance".to_string()))?,
+            pending_balance_credit_counter: extension.pending_balance_credit_counter.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+
+    #[test]
+    fn test_derived_keys_match_signatures() {
+        let owner = Keypair::new();
+        let token_account = Pubkey::new_unique();
+
+        let derived = ConfidentialKeys::derive(&owner, &token_account).unwrap();
+        let from_signatures = ConfidentialKeys::from_signatures(
+            &owner.sign_message(&elgamal_key_message(&token_account)),
+            &owner.sign_message(&ae_key_message(&token_account)),
+            &token_account,
+        )
+        .unwrap();
+
+        assert_eq!(derived.elgamal.pubkey(), from_signatures.elgamal.pubkey());
+        let ciphertext = derived.ae.encrypt(42);
+        assert_eq!(ciphertext.decrypt(&from_signatures.ae), Some(42));
+
+        // Keys are bound to the token account
+        let other = ConfidentialKeys::derive(&owner, &Pubkey::new_unique()).unwrap();
+        assert_ne!(derived.elgamal.pubkey(), other.elgamal.pubkey());
+    }
+
+    #[test]
+    fn test_balances() {
+        let owner = Keypair::new();
+        let keys = ConfidentialKeys::derive(&owner, &Pubkey::new_unique()).unwrap();
+        let pubkey = keys.elgamal.pubkey();
+
+        // 70_000 = 1 << 16 | 4_464, split like a Token-2022 pending balance
+        let state = ConfidentialAccountState {
+            owner: owner.pubkey(),
+            pending_balance_lo: pubkey.encrypt(4_464u64),
+            pending_balance_hi: pubkey.encrypt(1u64),
+            available_balance: pubkey.encrypt(1_000u64),
+            decryptable_available_balance: keys.ae.encrypt(1_000),
+            pending_balance_credit_counter: 3,
+        };
+
+        assert_eq!(keys.available_balance(&state).unwrap(), 1_000);
+        assert_eq!(keys.pending_balance(&state).unwrap(), 70_000);
+
+        let applied = keys.apply_pending(&state).unwrap();
+        assert_eq!(applied.new_available_balance, 71_000);
+        assert_eq!(applied.expected_pending_balance_credit_counter, 3);
+        assert_eq!(
+            keys.decrypt_balance(&applied.new_decryptable_available_balance)
+                .unwrap(),
+            71_000
+        );
+
+        assert_eq!(keys.decrypt_balance(&keys.encrypt_balance(5)).unwrap(), 5);
+        assert!(keys.decrypt_balance(&[0; 36]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unconfigured_account() {
+        assert!(matches!(
+            ConfidentialAccountState::parse(&Pubkey::new_unique(), &[0; 165]),
+            Err(ClientError::InvalidAccount { .. })
+        ));
+    }
+}
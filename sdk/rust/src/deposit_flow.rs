@@ -0,0 +1,259 @@
+//! One-shot SVS-2 deposits
+//!
+//! An SVS-2 deposit credits the new shares to the pending balance of the
+//! user's confidential shares account, where they can't be withdrawn or
+//! transferred until `apply_pending` moves them to the available balance.
+//! `apply_pending` needs the new decryptable balance, which is computed from
+//! the account once the deposit has landed, so the two can't share a
+//! transaction. [`ConfidentialDepositFlow`] sends the deposit, waits for its
+//! credit to show on the shares account, then sends `apply_pending`.
+//!
+//! The shares account must already be configured for confidential transfers,
+//! see [`crate::svs2::configure_account`].
+//!
+//! ```ignore
+//! let flow = ConfidentialDepositFlow::new(&client, keys, SendConfig::default());
+//! let outcome = flow.run(&user, &user, 1_000_000, min_shares_out, None).await?;
+//! println!("{} shares available", outcome.applied.available_shares);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{message::AddressLookupTableAccount, signature::Signature, signer::Signer};
+
+use crate::{
+    confidential::{ConfidentialAccountState, ConfidentialKeys, PendingApplication},
+    send::{nonblocking::TransactionSender, SendConfig},
+    svs2, ClientError, Result, VaultKeys,
+};
+
+/// A deposit whose shares were made available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositOutcome {
+    pub deposit: Signature,
+    pub applied: AppliedPending,
+}
+
+/// A confirmed `apply_pending`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppliedPending {
+    pub signature: Signature,
+    /// Shares moved to the available balance, including credits from before
+    /// the deposit
+    pub shares: u64,
+    pub available_shares: u64,
+}
+
+/// Confidential deposits into one SVS-2 vault
+pub struct ConfidentialDepositFlow<'a> {
+    client: &'a RpcClient,
+    keys: VaultKeys,
+    sender: TransactionSender<'a>,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl<'a> ConfidentialDepositFlow<'a> {
+    /// Send both transactions with `config`, whose timeout and poll interval
+    /// also bound the wait for the deposit's credit
+    pub fn new(client: &'a RpcClient, keys: VaultKeys, config: SendConfig) -> Self {
+        Self {
+            client,
+            keys,
+            timeout: config.timeout,
+            poll_interval: config.poll_interval,
+            sender: TransactionSender::new(client, config),
+        }
+    }
+
+    /// Compile v0 transactions looking up accounts in `tables`, e.g. the
+    /// vault's table from [`crate::lookup_table`]
+    pub fn with_lookup_tables(mut self, tables: Vec<AddressLookupTableAccount>) -> Self {
+        self.sender = self.sender.with_lookup_tables(tables);
+        self
+    }
+
+    /// Deposit `assets` from `user` for at least `min_shares_out` shares, and
+    /// apply them once the deposit is confirmed
+    ///
+    /// `fee_payer` pays both transactions; pass the user to pay them
+    /// yourself. When the deposit lands but applying fails, the error is
+    /// [`ClientError::ApplyPendingFailed`] and the shares stay pending until
+    /// [`Self::apply_pending`].
+    pub async fn run<U, P>(
+        &self,
+        user: &U,
+        fee_payer: &P,
+        assets: u64,
+        min_shares_out: u64,
+        memo_hash: Option<[u8; 32]>,
+    ) -> Result<DepositOutcome>
+    where
+        U: Signer,
+        P: Signer,
+    {
+        let user_pubkey = user.pubkey();
+        let shares_account = self.keys.shares_account(&user_pubkey);
+        // Both fail before anything is sent, e.g. for an unconfigured account
+        let before = self.shares_state(&shares_account).await?;
+        let keys = ConfidentialKeys::derive(user, &shares_account)?;
+
+        let deposit = svs2::deposit(&self.keys, &user_pubkey, assets, min_shares_out, memo_hash);
+        let deposit = self
+            .sender
+            .send(
+                &fee_payer.pubkey(),
+                &[deposit],
+                &[fee_payer as &dyn Signer, user],
+            )
+            .await?;
+
+        let applied = async {
+            let state = self
+                .credited_state(
+                    &shares_account,
+                    before.pending_balance_credit_counter,
+                    deposit,
+                )
+                .await?;
+            self.apply(user, fee_payer, &keys, &state).await
+        }
+        .await;
+        match applied {
+            Ok(applied) => Ok(DepositOutcome { deposit, applied }),
+            Err(error) => Err(ClientError::ApplyPendingFailed {
+                deposit,
+                error: Box::new(error),
+            }),
+        }
+    }
+
+    /// Apply `user`'s pending shares, e.g. after
+    /// [`ClientError::ApplyPendingFailed`]
+    pub async fn apply_pending<U, P>(&self, user: &U, fee_payer: &P) -> Result<AppliedPending>
+    where
+        U: Signer,
+        P: Signer,
+    {
+        let shares_account = self.keys.shares_account(&user.pubkey());
+        let state = self.shares_state(&shares_account).await?;
+        let keys = ConfidentialKeys::derive(user, &shares_account)?;
+        self.apply(user, fee_payer, &keys, &state).await
+    }
+
+    async fn apply(
+        &self,
+        user: &impl Signer,
+        fee_payer: &impl Signer,
+        keys: &ConfidentialKeys,
+        state: &ConfidentialAccountState,
+    ) -> Result<AppliedPending> {
+        let (application, shares) = application(keys, state)?;
+        let instruction = svs2::apply_pending(
+            &self.keys,
+            &user.pubkey(),
+            application.new_decryptable_available_balance,
+            application.expected_pending_balance_credit_counter,
+        );
+        let signature = self
+            .sender
+            .send(
+                &fee_payer.pubkey(),
+                &[instruction],
+                &[fee_payer as &dyn Signer, user],
+            )
+            .await?;
+
+        Ok(AppliedPending {
+            signature,
+            shares,
+            available_shares: application.new_available_balance,
+        })
+    }
+
+    async fn shares_state(&self, shares_account: &Pubkey) -> Result<ConfidentialAccountState> {
+        let data = self
+            .client
+            .get_account_with_commitment(shares_account, self.client.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(*shares_account))?
+            .data;
+        ConfidentialAccountState::parse(shares_account, &data)
+    }
+
+    /// The shares account once it counts more credits than `credits_before`;
+    /// the node read from can lag the one that confirmed `deposit`
+    async fn credited_state(
+        &self,
+        shares_account: &Pubkey,
+        credits_before: u64,
+        deposit: Signature,
+    ) -> Result<ConfidentialAccountState> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let state = self.shares_state(shares_account).await?;
+            if state.pending_balance_credit_counter > credits_before {
+                return Ok(state);
+            }
+            if Instant::now() >= deadline {
+                return Err(ClientError::CreditNotVisible(deposit));
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// Arguments of `apply_pending` for `state`, and the shares it moves
+fn application(
+    keys: &ConfidentialKeys,
+    state: &ConfidentialAccountState,
+) -> Result<(PendingApplication, u64)> {
+    let available = keys.available_balance(state)?;
+    let application = keys.apply_pending(state)?;
+    let shares = application.new_available_balance.saturating_sub(available);
+    Ok((application, shares))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+
+    #[test]
+    fn test_application() {
+        let owner = Keypair::new();
+        let keys = ConfidentialKeys::derive(&owner, &Pubkey::new_unique()).unwrap();
+        let pubkey = keys.elgamal.pubkey();
+
+        // 500 shares left pending by an earlier deposit, then 2_000 more
+        let state = ConfidentialAccountState {
+            owner: owner.pubkey(),
+            pending_balance_lo: pubkey.encrypt(2_500u64),
+            pending_balance_hi: pubkey.encrypt(0u64),
+            available_balance: pubkey.encrypt(1_000u64),
+            decryptable_available_balance: keys.ae.encrypt(1_000),
+            pending_balance_credit_counter: 2,
+        };
+
+        let (pending, shares) = application(&keys, &state).unwrap();
+        assert_eq!(shares, 2_500);
+        assert_eq!(pending.new_available_balance, 3_500);
+        assert_eq!(pending.expected_pending_balance_credit_counter, 2);
+        assert_eq!(
+            keys.decrypt_balance(&pending.new_decryptable_available_balance)
+                .unwrap(),
+            3_500
+        );
+
+        // Nothing pending
+        let empty = ConfidentialAccountState {
+            pending_balance_lo: pubkey.encrypt(0u64),
+            ..state
+        };
+        assert_eq!(application(&keys, &empty).unwrap().1, 0);
+    }
+}
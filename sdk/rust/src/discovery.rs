@@ -0,0 +1,512 @@
+//! Finding vaults
+//!
+//! Lists the vaults of the SVS programs with `getProgramAccounts`, filtered
+//! on the node by asset mint, authority and paused state, and prices each one
+//! from its shares mint and the clock. There is no on-chain registry of
+//! vaults, so custom deployments are listed by program id with
+//! [`find_program_vaults`].
+//!
+//! Both vault accounts start with the same fields, so the filters are the
+//! same memcmp offsets for SVS-1 and SVS-2.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use anchor_spl::token_interface::Mint;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_sdk::{
+    account::{from_account, Account},
+    sysvar::clock::{self, Clock},
+};
+
+use crate::{
+    accounting::share_price, accounts, view::VaultSnapshot, ClientError, ConfidentialVault, Result,
+    Vault, VaultKeys,
+};
+
+/// Offsets of the filtered fields, after the 8-byte discriminator
+const AUTHORITY_OFFSET: usize = 8;
+const ASSET_MINT_OFFSET: usize = 40;
+const PAUSED_OFFSET: usize = 146;
+
+/// Which program's vault account type to list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultKind {
+    /// SVS-1 `Vault`
+    Public,
+    /// SVS-2 `ConfidentialVault`
+    Confidential,
+}
+
+impl VaultKind {
+    fn discriminator(self) -> &'static [u8] {
+        match self {
+            VaultKind::Public => Vault::DISCRIMINATOR,
+            VaultKind::Confidential => ConfidentialVault::DISCRIMINATOR,
+        }
+    }
+}
+
+/// Vaults to list; unset fields match every vault
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VaultFilter {
+    pub asset_mint: Option<Pubkey>,
+    pub authority: Option<Pubkey>,
+    pub paused: Option<bool>,
+}
+
+impl VaultFilter {
+    /// `getProgramAccounts` filters selecting `kind` vaults that match
+    pub fn rpc_filters(&self, kind: VaultKind) -> Vec<RpcFilterType> {
+        let memcmp = |offset, bytes: &[u8]| {
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, bytes.to_vec()))
+        };
+        let mut filters = vec![memcmp(0, kind.discriminator())];
+        if let Some(asset_mint) = &self.asset_mint {
+            filters.push(memcmp(ASSET_MINT_OFFSET, asset_mint.as_ref()));
+        }
+        if let Some(authority) = &self.authority {
+            filters.push(memcmp(AUTHORITY_OFFSET, authority.as_ref()));
+        }
+        if let Some(paused) = self.paused {
+            filters.push(memcmp(PAUSED_OFFSET, &[paused as u8]));
+        }
+        filters
+    }
+}
+
+/// A vault with its current share price
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultSummary {
+    pub keys: VaultKeys,
+    pub kind: VaultKind,
+    pub authority: Pubkey,
+    pub vault_id: u64,
+    pub paused: bool,
+    /// Totals the views use; SVS-2 excludes still-locked harvest profit
+    pub snapshot: VaultSnapshot,
+    /// Assets per share, see [`share_price`]
+    pub share_price: f64,
+}
+
+/// A vault account returned by `getProgramAccounts`, before pricing
+struct Found {
+    program_id: Pubkey,
+    address: Pubkey,
+    state: State,
+}
+
+enum State {
+    Public(Box<Vault>),
+    Confidential(Box<ConfidentialVault>),
+}
+
+impl Found {
+    fn decode(program_id: &Pubkey, kind: VaultKind, address: Pubkey, data: &[u8]) -> Result<Self> {
+        let state = match kind {
+            VaultKind::Public => State::Public(Box::new(accounts::decode_vault(&address, data)?)),
+            VaultKind::Confidential => State::Confidential(Box::new(
+                accounts::decode_confidential_vault(&address, data)?,
+            )),
+        };
+        Ok(Self {
+            program_id: *program_id,
+            address,
+            state,
+        })
+    }
+
+    fn asset_mint(&self) -> Pubkey {
+        match &self.state {
+            State::Public(vault) => vault.asset_mint,
+            State::Confidential(vault) => vault.asset_mint,
+        }
+    }
+
+    fn shares_mint(&self) -> Pubkey {
+        match &self.state {
+            State::Public(vault) => vault.shares_mint,
+            State::Confidential(vault) => vault.shares_mint,
+        }
+    }
+
+    /// Price the vault; `account` returns the accounts listed by
+    /// `pricing_accounts`
+    fn summarize<'a>(
+        self,
+        account: impl Fn(&Pubkey) -> Result<&'a Account>,
+        now: i64,
+    ) -> Result<VaultSummary> {
+        let asset_token_program = account(&self.asset_mint())?.owner;
+        let shares_mint = self.shares_mint();
+        let mint: Mint =
+            accounts::decode(&shares_mint, "shares mint", &account(&shares_mint)?.data)?;
+        let (keys, kind, snapshot, authority, vault_id, paused) = match &self.state {
+            State::Public(vault) => (
+                VaultKeys::from_vault(&self.program_id, &self.address, vault, &asset_token_program),
+                VaultKind::Public,
+                VaultSnapshot::from_vault(vault, mint.supply),
+                vault.authority,
+                vault.vault_id,
+                vault.paused,
+            ),
+            State::Confidential(vault) => (
+                VaultKeys::from_confidential_vault(
+                    &self.program_id,
+                    &self.address,
+                    vault,
+                    &asset_token_program,
+                ),
+                VaultKind::Confidential,
+                VaultSnapshot::from_confidential_vault(vault, mint.supply, now)?,
+                vault.authority,
+                vault.vault_id,
+                vault.paused,
+            ),
+        };
+
+        Ok(VaultSummary {
+            keys,
+            kind,
+            authority,
+            vault_id,
+            paused,
+            share_price: share_price(&snapshot),
+            snapshot,
+        })
+    }
+}
+
+fn program_accounts_config(kind: VaultKind, filter: &VaultFilter) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(filter.rpc_filters(kind)),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Accounts needed to price `found`: the asset mints for their token
+/// program, the SVS-1 shares mints for their supply, and the clock
+fn pricing_accounts(found: &[Found]) -> Vec<Pubkey> {
+    let mut addresses = BTreeSet::from([clock::ID]);
+    for vault in found {
+        addresses.insert(vault.asset_mint());
+        addresses.insert(vault.shares_mint());
+    }
+    addresses.into_iter().collect()
+}
+
+/// Price `found` with the accounts fetched from `pricing_accounts`
+fn summarize(found: Vec<Found>, accounts: &HashMap<Pubkey, Account>) -> Result<Vec<VaultSummary>> {
+    let account = |address: &Pubkey| {
+        accounts
+            .get(address)
+            .ok_or(ClientError::AccountNotFound(*address))
+    };
+    let clock: Clock = from_account(account(&clock::ID)?).ok_or(ClientError::InvalidAccount {
+        address: clock::ID,
+        expected: "clock sysvar",
+        reason: "undecodable".to_string(),
+    })?;
+
+    let mut summaries = found
+        .into_iter()
+        .map(|found| found.summarize(account, clock.unix_timestamp))
+        .collect::<Result<Vec<_>>>()?;
+    summaries.sort_by_key(|summary| summary.keys.vault);
+    Ok(summaries)
+}
+
+fn fetched_map(addresses: &[Pubkey], fetched: Vec<Option<Account>>) -> HashMap<Pubkey, Account> {
+    addresses
+        .iter()
+        .zip(fetched)
+        .filter_map(|(address, account)| Some((*address, account?)))
+        .collect()
+}
+
+fn found_vaults(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    kind: VaultKind,
+    filter: &VaultFilter,
+) -> Result<Vec<Found>> {
+    let mut config = program_accounts_config(kind, filter);
+    config.account_config.commitment = Some(client.commitment());
+    client
+        .get_program_accounts_with_config(program_id, config)?
+        .into_iter()
+        .map(|(address, account)| Found::decode(program_id, kind, address, &account.data))
+        .collect()
+}
+
+fn price(client: &RpcClient, found: Vec<Found>) -> Result<Vec<VaultSummary>> {
+    let addresses = pricing_accounts(&found);
+    let mut fetched = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        fetched.extend(
+            client
+                .get_multiple_accounts_with_commitment(chunk, client.commitment())?
+                .value,
+        );
+    }
+    summarize(found, &fetched_map(&addresses, fetched))
+}
+
+/// Vaults of the canonical SVS-1 and SVS-2 programs matching `filter`,
+/// ordered by address
+pub fn find_vaults(client: &RpcClient, filter: &VaultFilter) -> Result<Vec<VaultSummary>> {
+    let mut found = found_vaults(client, &svs_1::ID, VaultKind::Public, filter)?;
+    found.extend(found_vaults(
+        client,
+        &svs_2::ID,
+        VaultKind::Confidential,
+        filter,
+    )?);
+    price(client, found)
+}
+
+/// Vaults of one deployment of SVS-1 (`Public`) or SVS-2 (`Confidential`)
+/// matching `filter`, ordered by address
+pub fn find_program_vaults(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    kind: VaultKind,
+    filter: &VaultFilter,
+) -> Result<Vec<VaultSummary>> {
+    let found = found_vaults(client, program_id, kind, filter)?;
+    price(client, found)
+}
+
+/// Async equivalents of the blocking functions
+pub mod nonblocking {
+    use anchor_lang::prelude::Pubkey;
+    use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::MAX_MULTIPLE_ACCOUNTS};
+
+    use super::{
+        fetched_map, pricing_accounts, program_accounts_config, summarize, Found, VaultFilter,
+        VaultKind, VaultSummary,
+    };
+    use crate::Result;
+
+    async fn found_vaults(
+        client: &RpcClient,
+        program_id: &Pubkey,
+        kind: VaultKind,
+        filter: &VaultFilter,
+    ) -> Result<Vec<Found>> {
+        let mut config = program_accounts_config(kind, filter);
+        config.account_config.commitment = Some(client.commitment());
+        client
+            .get_program_accounts_with_config(program_id, config)
+            .await?
+            .into_iter()
+            .map(|(address, account)| Found::decode(program_id, kind, address, &account.data))
+            .collect()
+    }
+
+    async fn price(client: &RpcClient, found: Vec<Found>) -> Result<Vec<VaultSummary>> {
+        let addresses = pricing_accounts(&found);
+        let mut fetched = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            fetched.extend(
+                client
+                    .get_multiple_accounts_with_commitment(chunk, client.commitment())
+                    .await?
+                    .value,
+            );
+        }
+        summarize(found, &fetched_map(&addresses, fetched))
+    }
+
+    /// Vaults of the canonical SVS-1 and SVS-2 programs matching `filter`
+    pub async fn find_vaults(
+        client: &RpcClient,
+        filter: &VaultFilter,
+    ) -> Result<Vec<VaultSummary>> {
+        let mut found = found_vaults(client, &svs_1::ID, VaultKind::Public, filter).await?;
+        found.extend(found_vaults(client, &svs_2::ID, VaultKind::Confidential, filter).await?);
+        price(client, found).await
+    }
+
+    /// Vaults of one deployment of SVS-1 or SVS-2 matching `filter`
+    pub async fn find_program_vaults(
+        client: &RpcClient,
+        program_id: &Pubkey,
+        kind: VaultKind,
+        filter: &VaultFilter,
+    ) -> Result<Vec<VaultSummary>> {
+        let found = found_vaults(client, program_id, kind, filter).await?;
+        price(client, found).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+    use anchor_spl::token_2022::spl_token_2022::{
+        self, solana_program::program_pack::Pack, state::Mint,
+    };
+    use solana_sdk::account::create_account_for_test;
+
+    use super::*;
+
+    fn confidential_vault() -> ConfidentialVault {
+        ConfidentialVault {
+            authority: Pubkey::new_unique(),
+            asset_mint: Pubkey::new_unique(),
+            shares_mint: Pubkey::new_unique(),
+            asset_vault: Pubkey::new_unique(),
+            total_assets: 2_000_000,
+            decimals_offset: 3,
+            bump: 255,
+            paused: true,
+            vault_id: 4,
+            auditor_elgamal_pubkey: None,
+            confidential_authority: Pubkey::new_unique(),
+            guardian: Pubkey::default(),
+            swap_adapter: Pubkey::default(),
+            harvester: Pubkey::default(),
+            locked_profit: 0,
+            last_harvest_ts: 0,
+            profit_unlock_period: 0,
+            event_sequence: 0,
+            min_deposit_amount: 0,
+            metadata_hash: [0; 32],
+            op_counter: 0,
+            _reserved: [0; 8],
+        }
+    }
+
+    fn serialize(account: &impl AccountSerialize) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Whether `filters` select an account with `data`
+    fn selects(filters: &[RpcFilterType], data: &[u8]) -> bool {
+        filters.iter().all(|filter| match filter {
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+            _ => unreachable!(),
+        })
+    }
+
+    #[test]
+    fn test_filter_offsets() {
+        let vault = confidential_vault();
+        let data = serialize(&vault);
+        let filter = VaultFilter {
+            asset_mint: Some(vault.asset_mint),
+            authority: Some(vault.authority),
+            paused: Some(true),
+        };
+        assert!(selects(&filter.rpc_filters(VaultKind::Confidential), &data));
+        assert!(!selects(&filter.rpc_filters(VaultKind::Public), &data));
+
+        let mismatches = [
+            VaultFilter {
+                asset_mint: Some(vault.shares_mint),
+                ..filter
+            },
+            VaultFilter {
+                authority: Some(vault.confidential_authority),
+                ..filter
+            },
+            VaultFilter {
+                paused: Some(false),
+                ..filter
+            },
+        ];
+        for mismatch in mismatches {
+            assert!(!selects(
+                &mismatch.rpc_filters(VaultKind::Confidential),
+                &data
+            ));
+        }
+
+        // SVS-1 vaults share the layout
+        let data = serialize(&Vault {
+            authority: vault.authority,
+            asset_mint: vault.asset_mint,
+            shares_mint: vault.shares_mint,
+            asset_vault: vault.asset_vault,
+            total_assets: 0,
+            decimals_offset: 3,
+            bump: 255,
+            paused: true,
+            vault_id: 4,
+            event_sequence: 0,
+            min_deposit_amount: 0,
+            metadata_hash: [0; 32],
+            op_counter: 0,
+            non_transferable: false,
+            _reserved: [0; 7],
+        });
+        assert!(selects(&filter.rpc_filters(VaultKind::Public), &data));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let vault = confidential_vault();
+        let address = Pubkey::new_unique();
+        let data = serialize(&vault);
+        let found = || Found::decode(&svs_2::ID, VaultKind::Confidential, address, &data).unwrap();
+
+        let addresses = pricing_accounts(&[found()]);
+        assert_eq!(addresses.len(), 3);
+        let asset_token_program = Pubkey::new_unique();
+        let clock = create_account_for_test(&Clock {
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        });
+        let asset_mint = Account {
+            owner: asset_token_program,
+            ..Account::default()
+        };
+        let mut shares_mint = vec![0; Mint::LEN];
+        Mint {
+            supply: 1_000_000_000,
+            decimals: 9,
+            is_initialized: true,
+            ..Mint::default()
+        }
+        .pack_into_slice(&mut shares_mint);
+        let shares_mint = Account {
+            data: shares_mint,
+            owner: spl_token_2022::ID,
+            ..Account::default()
+        };
+        let accounts = HashMap::from([
+            (clock::ID, clock),
+            (vault.asset_mint, asset_mint),
+            (vault.shares_mint, shares_mint),
+        ]);
+
+        let summaries = summarize(vec![found()], &accounts).unwrap();
+        let summary = &summaries[0];
+        assert_eq!(summary.keys.vault, address);
+        assert_eq!(summary.keys.asset_token_program, asset_token_program);
+        assert_eq!(summary.kind, VaultKind::Confidential);
+        assert_eq!((summary.vault_id, summary.paused), (4, true));
+        // 2 assets per 1000 shares, with the virtual offset
+        let expected = 2_000_001.0 / 1_000_001_000.0;
+        assert!((summary.share_price - expected).abs() < 1e-12);
+
+        let mut accounts = accounts;
+        accounts.remove(&vault.asset_mint);
+        assert!(matches!(
+            summarize(vec![found()], &accounts),
+            Err(ClientError::AccountNotFound(mint)) if mint == vault.asset_mint
+        ));
+    }
+}
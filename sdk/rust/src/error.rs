@@ -0,0 +1,85 @@
+use anchor_lang::prelude::Pubkey;
+use thiserror::Error;
+
+/// Errors returned by the client
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account {address} is not a valid {expected}: {reason}")]
+    InvalidAccount {
+        address: Pubkey,
+        expected: &'static str,
+        reason: String,
+    },
+
+    #[error("Vault math error: {0}")]
+    Math(svs_math::MathError),
+
+    #[error("Simulation failed: {0}")]
+    Simulation(String),
+
+    #[error("Incomplete event history: {0}")]
+    History(String),
+
+    #[cfg(any(feature = "rpc", feature = "confidential"))]
+    #[error("Signing failed: {0}")]
+    Signer(#[from] solana_sdk::signer::SignerError),
+
+    #[cfg(feature = "confidential")]
+    #[error("Proof error: {0}")]
+    Proof(#[from] svs_proof_core::ProofError),
+
+    #[cfg(feature = "withdraw-flow")]
+    #[error("Proof backend error: {0}")]
+    Backend(String),
+
+    #[cfg(feature = "rpc")]
+    #[error("Transaction compilation failed: {0}")]
+    Compile(#[from] solana_sdk::message::CompileError),
+
+    #[cfg(feature = "rpc")]
+    #[error("Instructions set their own compute budget")]
+    ComputeBudgetSet,
+
+    #[cfg(feature = "rpc")]
+    #[error("Transaction {signature} failed: {error}")]
+    TransactionFailed {
+        signature: solana_sdk::signature::Signature,
+        error: String,
+    },
+
+    #[cfg(feature = "rpc")]
+    #[error("Transaction {0} was not confirmed before the timeout")]
+    Timeout(solana_sdk::signature::Signature),
+
+    #[cfg(feature = "rpc")]
+    #[error("Transaction expired unconfirmed {0} times")]
+    Expired(u32),
+
+    #[cfg(all(feature = "rpc", feature = "confidential"))]
+    #[error("Shares credited by {0} did not show on the shares account before the timeout")]
+    CreditNotVisible(solana_sdk::signature::Signature),
+
+    #[cfg(all(feature = "rpc", feature = "confidential"))]
+    #[error("Deposit {deposit} landed, but applying its pending shares failed: {error}")]
+    ApplyPendingFailed {
+        deposit: solana_sdk::signature::Signature,
+        error: Box<ClientError>,
+    },
+
+    #[cfg(feature = "rpc")]
+    #[error("RPC error: {0}")]
+    Rpc(Box<solana_client::client_error::ClientError>),
+}
+
+// Boxed, as the RPC error alone is several hundred bytes
+#[cfg(feature = "rpc")]
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(error: solana_client::client_error::ClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
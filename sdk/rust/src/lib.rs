@@ -0,0 +1,75 @@
+//! Rust client for the Solana Vault Standard programs
+//!
+//! Instruction builders, PDA derivation and account decoding for SVS-1
+//! (public) and SVS-2 (confidential) vaults, and local evaluation of their
+//! view functions, as well as a wallet's cost basis and PnL replayed from the
+//! vault's events ([`accounting`]). With the default `rpc` feature, vault
+//! accounts can also be fetched and views simulated over blocking or async
+//! RPC, vaults listed by asset mint, authority or paused state
+//! ([`discovery`]), transactions built as v0 with the vault's address lookup
+//! table, and sent with priority fees, retries and confirmation. The `confidential` and
+//! `withdraw-flow` features add SVS-2 key derivation and the full
+//! confidential withdrawal flow; with both `rpc` and `confidential`, deposits
+//! apply their pending shares in one call ([`deposit_flow`]).
+//!
+//! Everything outside `rpc` and `withdraw-flow` is pure and builds for
+//! `wasm32-unknown-unknown`; the `wasm` feature exports it to JavaScript (see
+//! [`wasm`]).
+//!
+//! Instructions are encoded with the programs' own Anchor types, so they
+//! always match the program they are built against.
+//!
+//! ```ignore
+//! let keys = VaultKeys::derive(&svs_1::ID, &asset_mint, 0, &spl_token::ID);
+//! let ix = svs1::deposit(&keys, &user, 1_000_000, min_shares_out);
+//! ```
+
+pub mod accounting;
+pub mod accounts;
+#[cfg(feature = "confidential")]
+pub mod confidential;
+#[cfg(all(feature = "rpc", feature = "confidential"))]
+pub mod deposit_flow;
+#[cfg(feature = "rpc")]
+pub mod discovery;
+pub mod error;
+#[cfg(feature = "rpc")]
+pub mod lookup_table;
+pub mod pda;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "rpc")]
+pub mod send;
+pub mod svs1;
+pub mod svs2;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "withdraw-flow")]
+pub mod withdraw_flow;
+
+#[cfg(all(target_arch = "wasm32", feature = "rpc"))]
+compile_error!("the `rpc` feature does not build for wasm32, use `--no-default-features`");
+
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::Instruction, InstructionData, ToAccountMetas,
+};
+
+pub use accounts::{decode_confidential_vault, decode_vault};
+pub use error::{ClientError, Result};
+pub use pda::VaultKeys;
+pub use svs_1::{metadata::metadata_hash, state::Vault};
+pub use svs_2::state::ConfidentialVault;
+
+/// Instruction of `program_id` with Anchor-encoded accounts and arguments
+pub(crate) fn instruction(
+    program_id: Pubkey,
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
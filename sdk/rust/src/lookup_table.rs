@@ -0,0 +1,317 @@
+//! Address lookup tables and versioned transactions
+//!
+//! Every vault instruction references the vault, both mints, the asset vault,
+//! the token programs, the protocol config and often a sysvar. SVS-2
+//! withdrawals add two proof context accounts, a close of each and possibly a
+//! relayer and a priority fee, which brings them close to the 1232-byte
+//! packet limit. A lookup table
+//! holding the vault's accounts lets a v0 transaction refer to each of them
+//! with a one-byte index instead of its 32-byte key.
+//!
+//! One table per vault is enough; anyone can create it:
+//!
+//! ```ignore
+//! let slot = client.get_slot_with_commitment(CommitmentConfig::finalized())?;
+//! let (instructions, table) = create_vault_lookup_table(&keys, &authority, &payer, slot);
+//! let create = versioned_transaction(&payer, &instructions, &[], blockhash, &[&payer])?;
+//!
+//! // From the next slot on
+//! let tables = rpc::fetch_lookup_tables(&client, &[table])?;
+//! let deposit = svs1::deposit(&keys, &user.pubkey(), 1_000_000, min_shares_out);
+//! let tx = versioned_transaction(&user.pubkey(), &[deposit], &tables, blockhash, &[&user])?;
+//! ```
+//!
+//! A table can be used from the slot after it was last extended. Program ids
+//! invoked by an instruction are never looked up, so the table only holds
+//! accounts passed to the instructions.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, sysvar},
+    system_program,
+};
+use anchor_spl::{associated_token, token_2022};
+use solana_address_lookup_table_interface::{
+    instruction::{create_lookup_table, extend_lookup_table},
+    state::{AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES},
+};
+use solana_sdk::{
+    hash::Hash,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+
+use crate::{pda::find_config_address, ClientError, Result, VaultKeys};
+
+/// Addresses added by one extend instruction, so that it fits in a legacy
+/// transaction next to the create instruction
+pub const MAX_EXTEND_ADDRESSES: usize = 20;
+
+/// Accounts shared by the instructions of all users of a vault
+pub fn vault_addresses(keys: &VaultKeys) -> Vec<Pubkey> {
+    let mut addresses = Vec::new();
+    for address in [
+        keys.vault,
+        keys.asset_mint,
+        keys.shares_mint,
+        keys.asset_vault,
+        find_config_address(&keys.program_id).0,
+        keys.asset_token_program,
+        token_2022::ID,
+        associated_token::ID,
+        system_program::ID,
+        sysvar::rent::ID,
+        sysvar::instructions::ID,
+    ] {
+        // The asset token program may be Token-2022
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    addresses
+}
+
+/// Instructions creating a lookup table of `authority` filled with the
+/// vault's accounts, and the table's address
+///
+/// `recent_slot` seeds the table address and must be a recent rooted slot,
+/// e.g. the current slot at `finalized` commitment. `payer` funds the table's
+/// rent; both instructions fit in one transaction.
+pub fn create_vault_lookup_table(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    recent_slot: u64,
+) -> (Vec<Instruction>, Pubkey) {
+    let (create, table) = create_lookup_table(*authority, *payer, recent_slot);
+    let extend = extend_lookup_table(table, *authority, Some(*payer), vault_addresses(keys));
+    (vec![create, extend], table)
+}
+
+/// Instructions adding the `addresses` missing from `table`, e.g. from
+/// [`vault_addresses`] after the vault's asset token program changed
+///
+/// Each instruction adds at most [`MAX_EXTEND_ADDRESSES`]; send them in
+/// separate transactions. None are needed when the table is complete.
+pub fn extend_instructions(
+    table: &AddressLookupTableAccount,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    addresses: &[Pubkey],
+) -> Result<Vec<Instruction>> {
+    let mut missing: Vec<Pubkey> = Vec::new();
+    for address in addresses {
+        if !table.addresses.contains(address) && !missing.contains(address) {
+            missing.push(*address);
+        }
+    }
+    if table.addresses.len() + missing.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+        return Err(invalid_table(
+            &table.key,
+            format!("no room for {} more addresses", missing.len()),
+        ));
+    }
+
+    Ok(missing
+        .chunks(MAX_EXTEND_ADDRESSES)
+        .map(|chunk| extend_lookup_table(table.key, *authority, Some(*payer), chunk.to_vec()))
+        .collect())
+}
+
+/// Decode the lookup table at `address`; deactivated tables are rejected, as
+/// transactions can't use them
+pub fn decode_lookup_table(address: &Pubkey, data: &[u8]) -> Result<AddressLookupTableAccount> {
+    let table =
+        AddressLookupTable::deserialize(data).map_err(|e| invalid_table(address, e.to_string()))?;
+    if table.meta.deactivation_slot != u64::MAX {
+        return Err(invalid_table(
+            address,
+            format!("deactivated in slot {}", table.meta.deactivation_slot),
+        ));
+    }
+
+    Ok(AddressLookupTableAccount {
+        key: *address,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+/// Compile `instructions` into a v0 message looking up accounts in `tables`,
+/// or a legacy message when there are none
+pub fn versioned_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage> {
+    if tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &recent_blockhash,
+        )));
+    }
+    Ok(VersionedMessage::V0(v0::Message::try_compile(
+        payer,
+        instructions,
+        tables,
+        recent_blockhash,
+    )?))
+}
+
+/// Sign [`versioned_message`]
+///
+/// `payer` must be among `signers`, which may repeat, e.g. for a user paying
+/// their own fees.
+pub fn versioned_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+    signers: &[&dyn Signer],
+) -> Result<VersionedTransaction> {
+    let message = versioned_message(payer, instructions, tables, recent_blockhash)?;
+
+    // Versioned transactions take exactly one keypair per signature
+    let mut unique: Vec<&dyn Signer> = Vec::with_capacity(signers.len());
+    for signer in signers {
+        if !unique.iter().any(|other| other.pubkey() == signer.pubkey()) {
+            unique.push(*signer);
+        }
+    }
+    Ok(VersionedTransaction::try_new(message, unique.as_slice())?)
+}
+
+fn invalid_table(address: &Pubkey, reason: String) -> ClientError {
+    ClientError::InvalidAccount {
+        address: *address,
+        expected: "address lookup table",
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token;
+    use solana_address_lookup_table_interface::state::LookupTableMeta;
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+    use crate::svs1;
+
+    fn table_data(addresses: &[Pubkey], deactivation_slot: u64) -> Vec<u8> {
+        AddressLookupTable {
+            meta: LookupTableMeta {
+                deactivation_slot,
+                ..LookupTableMeta::new(Pubkey::new_unique())
+            },
+            addresses: addresses.into(),
+        }
+        .serialize_for_tests()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_vault_addresses() {
+        let keys = VaultKeys::derive(&svs_1::ID, &Pubkey::new_unique(), 0, &token::ID);
+        let addresses = vault_addresses(&keys);
+        assert_eq!(addresses.len(), 11);
+        assert!(addresses.contains(&keys.shares_mint));
+        assert!(!addresses.contains(&keys.program_id));
+
+        let keys = VaultKeys::derive(&svs_1::ID, &Pubkey::new_unique(), 0, &token_2022::ID);
+        assert_eq!(vault_addresses(&keys).len(), 10);
+    }
+
+    #[test]
+    fn test_extend_instructions() {
+        let keys = VaultKeys::derive(&svs_1::ID, &Pubkey::new_unique(), 0, &token::ID);
+        let authority = Pubkey::new_unique();
+        let addresses = vault_addresses(&keys);
+        let mut table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: addresses[..4].to_vec(),
+        };
+
+        assert_eq!(
+            extend_instructions(&table, &authority, &authority, &addresses)
+                .unwrap()
+                .len(),
+            1
+        );
+        table.addresses = addresses.clone();
+        assert!(
+            extend_instructions(&table, &authority, &authority, &addresses)
+                .unwrap()
+                .is_empty()
+        );
+
+        let many: Vec<_> = (0..45).map(|_| Pubkey::new_unique()).collect();
+        assert_eq!(
+            extend_instructions(&table, &authority, &authority, &many)
+                .unwrap()
+                .len(),
+            3
+        );
+        table.addresses = (0..LOOKUP_TABLE_MAX_ADDRESSES - addresses.len())
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        assert!(extend_instructions(&table, &authority, &authority, &addresses).is_ok());
+        assert!(extend_instructions(&table, &authority, &authority, &many).is_err());
+    }
+
+    #[test]
+    fn test_decode_lookup_table() {
+        let address = Pubkey::new_unique();
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let table = decode_lookup_table(&address, &table_data(&addresses, u64::MAX)).unwrap();
+        assert_eq!(table.key, address);
+        assert_eq!(table.addresses, addresses);
+
+        assert!(decode_lookup_table(&address, &table_data(&addresses, 100)).is_err());
+        assert!(decode_lookup_table(&address, &[0; 8]).is_err());
+    }
+
+    #[test]
+    fn test_versioned_transaction_looks_up_vault_accounts() {
+        let keys = VaultKeys::derive(&svs_1::ID, &Pubkey::new_unique(), 0, &token::ID);
+        let user = Keypair::new();
+        let deposit = svs1::deposit(&keys, &user.pubkey(), 1_000, 1);
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vault_addresses(&keys),
+        };
+
+        // The user pays their own fees
+        let signers: [&dyn Signer; 2] = [&user, &user];
+        let legacy = versioned_transaction(
+            &user.pubkey(),
+            std::slice::from_ref(&deposit),
+            &[],
+            Hash::new_unique(),
+            &signers,
+        )
+        .unwrap();
+        let v0 = versioned_transaction(
+            &user.pubkey(),
+            &[deposit],
+            std::slice::from_ref(&table),
+            Hash::new_unique(),
+            &signers,
+        )
+        .unwrap();
+        assert!(matches!(legacy.message, VersionedMessage::Legacy(_)));
+        v0.verify_and_hash_message().unwrap();
+
+        let VersionedMessage::V0(message) = &v0.message else {
+            panic!("not a v0 message");
+        };
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].account_key, table.key);
+        assert!(!message.account_keys.contains(&keys.vault));
+        assert!(message.account_keys.contains(&keys.program_id));
+        assert!(v0.message.serialize().len() < legacy.message.serialize().len());
+    }
+}
@@ -0,0 +1,207 @@
+//! Program-derived addresses and token accounts of a vault
+//!
+//! SVS-1 and SVS-2 derive their accounts with the same seeds, so every
+//! function takes the program id of the vault's program.
+
+use anchor_lang::{prelude::Pubkey, solana_program::bpf_loader_upgradeable};
+use anchor_spl::{associated_token::get_associated_token_address_with_program_id, token_2022};
+use svs_1::constants::{CONFIG_SEED, SHARES_MINT_SEED, VAULT_SEED};
+use svs_2::constants::{HARVEST_AUTHORITY_SEED, PERMIT_DELEGATE_SEED, PERMIT_SEED};
+
+use crate::{ConfidentialVault, Vault};
+
+/// Vault PDA: `["vault", asset_mint, vault_id (u64 LE)]`
+pub fn find_vault_address(program_id: &Pubkey, asset_mint: &Pubkey, vault_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_SEED, asset_mint.as_ref(), &vault_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Shares mint PDA: `["shares", vault]`
+pub fn find_shares_mint_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SHARES_MINT_SEED, vault.as_ref()], program_id)
+}
+
+/// Account holding the vault's assets: the vault's associated token account
+pub fn asset_vault_address(
+    vault: &Pubkey,
+    asset_mint: &Pubkey,
+    asset_token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(vault, asset_mint, asset_token_program)
+}
+
+/// Shares account of `owner`: their Token-2022 associated token account
+pub fn shares_account_address(owner: &Pubkey, shares_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, shares_mint, &token_2022::ID)
+}
+
+/// SVS-2 permit receipt PDA marking `nonce` of `owner` as used:
+/// `["permit", vault, owner, nonce (u64 LE)]`
+pub fn find_permit_receipt_address(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    owner: &Pubkey,
+    nonce: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PERMIT_SEED,
+            vault.as_ref(),
+            owner.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// SVS-2 permit delegate PDA, which owners approve as token delegate of the
+/// asset account `deposit_with_permit` pulls from: `["permit_delegate", vault]`
+pub fn find_permit_delegate_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PERMIT_DELEGATE_SEED, vault.as_ref()], program_id)
+}
+
+/// SVS-2 harvest authority PDA, the owner of a vault's reward token
+/// accounts: `["harvest_authority", vault]`
+pub fn find_harvest_authority_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[HARVEST_AUTHORITY_SEED, vault.as_ref()], program_id)
+}
+
+/// Protocol config PDA of a program: `["config"]`
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// ProgramData account of an upgradeable program, holding its upgrade
+/// authority
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Accounts every instruction of one vault refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultKeys {
+    /// Program the vault belongs to (SVS-1 or SVS-2)
+    pub program_id: Pubkey,
+    pub vault: Pubkey,
+    pub asset_mint: Pubkey,
+    pub shares_mint: Pubkey,
+    pub asset_vault: Pubkey,
+    /// Token program owning the asset mint (SPL Token or Token-2022)
+    pub asset_token_program: Pubkey,
+}
+
+impl VaultKeys {
+    /// Derive the accounts of vault `vault_id` for `asset_mint`, e.g. before
+    /// initializing it
+    pub fn derive(
+        program_id: &Pubkey,
+        asset_mint: &Pubkey,
+        vault_id: u64,
+        asset_token_program: &Pubkey,
+    ) -> Self {
+        let (vault, _) = find_vault_address(program_id, asset_mint, vault_id);
+        let (shares_mint, _) = find_shares_mint_address(program_id, &vault);
+
+        Self {
+            program_id: *program_id,
+            vault,
+            asset_mint: *asset_mint,
+            shares_mint,
+            asset_vault: asset_vault_address(&vault, asset_mint, asset_token_program),
+            asset_token_program: *asset_token_program,
+        }
+    }
+
+    /// Accounts recorded in a fetched SVS-1 vault
+    pub fn from_vault(
+        program_id: &Pubkey,
+        vault: &Pubkey,
+        state: &Vault,
+        asset_token_program: &Pubkey,
+    ) -> Self {
+        Self {
+            program_id: *program_id,
+            vault: *vault,
+            asset_mint: state.asset_mint,
+            shares_mint: state.shares_mint,
+            asset_vault: state.asset_vault,
+            asset_token_program: *asset_token_program,
+        }
+    }
+
+    /// Accounts recorded in a fetched SVS-2 vault
+    pub fn from_confidential_vault(
+        program_id: &Pubkey,
+        vault: &Pubkey,
+        state: &ConfidentialVault,
+        asset_token_program: &Pubkey,
+    ) -> Self {
+        Self {
+            program_id: *program_id,
+            vault: *vault,
+            asset_mint: state.asset_mint,
+            shares_mint: state.shares_mint,
+            asset_vault: state.asset_vault,
+            asset_token_program: *asset_token_program,
+        }
+    }
+
+    /// Asset token account of `owner` (associated token account)
+    pub fn asset_account(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(
+            owner,
+            &self.asset_mint,
+            &self.asset_token_program,
+        )
+    }
+
+    /// Shares account of `owner`
+    pub fn shares_account(&self, owner: &Pubkey) -> Pubkey {
+        shares_account_address(owner, &self.shares_mint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token;
+
+    #[test]
+    fn test_programs_share_seeds() {
+        assert_eq!(VAULT_SEED, svs_2::constants::VAULT_SEED);
+        assert_eq!(SHARES_MINT_SEED, svs_2::constants::SHARES_MINT_SEED);
+        assert_eq!(CONFIG_SEED, svs_2::constants::CONFIG_SEED);
+    }
+
+    #[test]
+    fn test_derived_keys() {
+        let asset_mint = Pubkey::new_unique();
+        let keys = VaultKeys::derive(&svs_1::ID, &asset_mint, 7, &token::ID);
+
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"vault", asset_mint.as_ref(), &7u64.to_le_bytes()],
+            &svs_1::ID,
+        );
+        assert_eq!(keys.vault, vault);
+        assert_eq!(
+            keys.shares_mint,
+            find_shares_mint_address(&svs_1::ID, &vault).0
+        );
+        assert_eq!(
+            keys.asset_vault,
+            get_associated_token_address_with_program_id(&vault, &asset_mint, &token::ID)
+        );
+
+        // Vault ids and programs give distinct vaults
+        assert_ne!(
+            VaultKeys::derive(&svs_1::ID, &asset_mint, 8, &token::ID).vault,
+            vault
+        );
+        assert_ne!(
+            VaultKeys::derive(&svs_2::ID, &asset_mint, 7, &token::ID).vault,
+            vault
+        );
+    }
+}
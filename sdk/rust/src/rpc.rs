@@ -0,0 +1,438 @@
+//! Fetching vault accounts and calling views over RPC
+//!
+//! Blocking functions take a `solana_client::rpc_client::RpcClient`; the
+//! same functions in [`nonblocking`] take the async client. Accounts are read
+//! and views simulated at the client's commitment.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_client::{
+    rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    account::{from_account, Account},
+    commitment_config::CommitmentConfig,
+    message::{AddressLookupTableAccount, Message},
+    sysvar::clock::{self, Clock},
+    transaction::Transaction,
+};
+
+use crate::{
+    accounts, lookup_table,
+    view::{self, VaultSnapshot, View},
+    ClientError, ConfidentialVault, Result, Vault, VaultKeys,
+};
+
+/// Owner and data of an account
+type RawAccount = (Pubkey, Vec<u8>);
+
+fn get_account(client: &RpcClient, address: &Pubkey) -> Result<RawAccount> {
+    client
+        .get_account_with_commitment(address, client.commitment())?
+        .value
+        .map(|account| (account.owner, account.data))
+        .ok_or(ClientError::AccountNotFound(*address))
+}
+
+/// Keys recorded in an SVS-1 or SVS-2 vault, minus the asset token program
+fn recorded_keys(vault: &Pubkey, (program_id, data): &RawAccount) -> Result<VaultKeys> {
+    let unknown = Pubkey::default();
+    match accounts::decode_vault(vault, data) {
+        Ok(state) => Ok(VaultKeys::from_vault(program_id, vault, &state, &unknown)),
+        Err(_) => accounts::decode_confidential_vault(vault, data)
+            .map(|state| VaultKeys::from_confidential_vault(program_id, vault, &state, &unknown)),
+    }
+}
+
+/// Lookup tables decoded from the accounts fetched at `addresses`
+fn lookup_tables(
+    addresses: &[Pubkey],
+    fetched: &[Option<Account>],
+) -> Result<Vec<AddressLookupTableAccount>> {
+    addresses
+        .iter()
+        .zip(fetched)
+        .map(|(address, account)| {
+            let account = account
+                .as_ref()
+                .ok_or(ClientError::AccountNotFound(*address))?;
+            lookup_table::decode_lookup_table(address, &account.data)
+        })
+        .collect()
+}
+
+/// Unsigned transaction calling `view`; simulation skips signature checks
+fn view_transaction(keys: &VaultKeys, view: &View, payer: &Pubkey) -> Transaction {
+    Transaction::new_unsigned(Message::new(&[view::instruction(keys, view)], Some(payer)))
+}
+
+pub(crate) fn simulation_config(commitment: CommitmentConfig) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(commitment),
+        ..Default::default()
+    }
+}
+
+/// Fail with the logs of a failed simulation
+pub(crate) fn check_simulation(result: &RpcSimulateTransactionResult) -> Result<()> {
+    match &result.err {
+        Some(err) => {
+            let logs = result.logs.as_deref().unwrap_or_default().join("\n");
+            Err(ClientError::Simulation(format!("{err}\n{logs}")))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Return value of a simulated view
+fn simulated_value(keys: &VaultKeys, result: RpcSimulateTransactionResult) -> Result<u64> {
+    check_simulation(&result)?;
+
+    // A zero return value has no bytes left once trailing zeros are stripped
+    let Some(return_data) = result.return_data else {
+        return Ok(0);
+    };
+    if return_data.program_id != keys.program_id.to_string() {
+        return Err(ClientError::Simulation(format!(
+            "return data set by {} instead of the vault program",
+            return_data.program_id
+        )));
+    }
+
+    let data = BASE64
+        .decode(&return_data.data.0)
+        .map_err(|e| ClientError::Simulation(e.to_string()))?;
+    view::decode_return_data(&data)
+}
+
+/// Accounts read to evaluate `view` locally
+fn view_accounts(keys: &VaultKeys, view: &View) -> Vec<Pubkey> {
+    let mut addresses = vec![keys.vault, keys.shares_mint, clock::ID];
+    if let Some(owner) = view.owner() {
+        addresses.push(keys.shares_account(owner));
+    }
+    addresses
+}
+
+/// Value of `view` computed from the accounts listed by `view_accounts`
+fn local_value(keys: &VaultKeys, view: &View, fetched: &[Option<Account>]) -> Result<u64> {
+    let addresses = view_accounts(keys, view);
+    let account = |i: usize| {
+        fetched
+            .get(i)
+            .and_then(Option::as_ref)
+            .ok_or(ClientError::AccountNotFound(addresses[i]))
+    };
+
+    let vault = account(0)?;
+    let mint: Mint = accounts::decode(&keys.shares_mint, "shares mint", &account(1)?.data)?;
+    let snapshot = match accounts::decode_vault(&keys.vault, &vault.data) {
+        Ok(state) => VaultSnapshot::from_vault(&state, mint.supply),
+        Err(_) => {
+            let state = accounts::decode_confidential_vault(&keys.vault, &vault.data)?;
+            let clock: Clock = from_account(account(2)?).ok_or(ClientError::InvalidAccount {
+                address: clock::ID,
+                expected: "clock sysvar",
+                reason: "undecodable".to_string(),
+            })?;
+            VaultSnapshot::from_confidential_vault(&state, mint.supply, clock.unix_timestamp)?
+        }
+    };
+
+    // An owner without a shares account has no shares
+    let owner_shares = match fetched.get(3).and_then(Option::as_ref) {
+        Some(shares_account) => {
+            accounts::decode::<TokenAccount>(&addresses[3], "shares account", &shares_account.data)?
+                .amount
+        }
+        None => 0,
+    };
+
+    snapshot.evaluate(view, owner_shares)
+}
+
+/// Fetch an SVS-1 vault
+pub fn fetch_vault(client: &RpcClient, address: &Pubkey) -> Result<Vault> {
+    accounts::decode_vault(address, &get_account(client, address)?.1)
+}
+
+/// Fetch an SVS-2 confidential vault
+pub fn fetch_confidential_vault(client: &RpcClient, address: &Pubkey) -> Result<ConfidentialVault> {
+    accounts::decode_confidential_vault(address, &get_account(client, address)?.1)
+}
+
+/// Fetch the keys of an existing SVS-1 or SVS-2 vault; the program is the
+/// vault's owner and the asset token program the asset mint's owner
+pub fn fetch_vault_keys(client: &RpcClient, vault: &Pubkey) -> Result<VaultKeys> {
+    let mut keys = recorded_keys(vault, &get_account(client, vault)?)?;
+    keys.asset_token_program = get_account(client, &keys.asset_mint)?.0;
+    Ok(keys)
+}
+
+/// Fetch address lookup tables to compile v0 transactions with, see
+/// [`lookup_table`]
+pub fn fetch_lookup_tables(
+    client: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>> {
+    let fetched = client
+        .get_multiple_accounts_with_commitment(addresses, client.commitment())?
+        .value;
+    lookup_tables(addresses, &fetched)
+}
+
+/// View functions of one vault, simulated against the cluster
+///
+/// Nothing is signed: `payer` only has to be an existing account that could
+/// pay the fee, e.g. the user's wallet. When the RPC node can't simulate,
+/// views are computed locally from the fetched accounts.
+pub struct Views<'a> {
+    client: &'a RpcClient,
+    keys: VaultKeys,
+    payer: Pubkey,
+}
+
+impl<'a> Views<'a> {
+    pub fn new(client: &'a RpcClient, keys: VaultKeys, payer: Pubkey) -> Self {
+        Self {
+            client,
+            keys,
+            payer,
+        }
+    }
+
+    /// Value of `view`, simulated or else computed locally
+    pub fn get(&self, view: View) -> Result<u64> {
+        match self.simulate(&view) {
+            Err(ClientError::Rpc(_)) => self.compute_locally(&view),
+            value => value,
+        }
+    }
+
+    /// Value of `view` returned by a simulated view instruction
+    pub fn simulate(&self, view: &View) -> Result<u64> {
+        let transaction = view_transaction(&self.keys, view, &self.payer);
+        let config = simulation_config(self.client.commitment());
+        let result = self
+            .client
+            .simulate_transaction_with_config(&transaction, config)?;
+        simulated_value(&self.keys, result.value)
+    }
+
+    /// Value of `view` computed from the fetched vault accounts
+    pub fn compute_locally(&self, view: &View) -> Result<u64> {
+        let fetched = self
+            .client
+            .get_multiple_accounts_with_commitment(
+                &view_accounts(&self.keys, view),
+                self.client.commitment(),
+            )?
+            .value;
+        local_value(&self.keys, view, &fetched)
+    }
+
+    pub fn preview_deposit(&self, assets: u64) -> Result<u64> {
+        self.get(View::PreviewDeposit(assets))
+    }
+
+    pub fn preview_mint(&self, shares: u64) -> Result<u64> {
+        self.get(View::PreviewMint(shares))
+    }
+
+    pub fn preview_withdraw(&self, assets: u64) -> Result<u64> {
+        self.get(View::PreviewWithdraw(assets))
+    }
+
+    pub fn preview_redeem(&self, shares: u64) -> Result<u64> {
+        self.get(View::PreviewRedeem(shares))
+    }
+
+    pub fn convert_to_shares(&self, assets: u64) -> Result<u64> {
+        self.get(View::ConvertToShares(assets))
+    }
+
+    pub fn convert_to_assets(&self, shares: u64) -> Result<u64> {
+        self.get(View::ConvertToAssets(shares))
+    }
+
+    /// Assets per share, in [`SHARE_PRICE_SCALE`](crate::view::SHARE_PRICE_SCALE) fixed point
+    pub fn share_price(&self) -> Result<u64> {
+        self.get(View::SharePrice)
+    }
+
+    pub fn total_assets(&self) -> Result<u64> {
+        self.get(View::TotalAssets)
+    }
+
+    pub fn max_deposit(&self) -> Result<u64> {
+        self.get(View::MaxDeposit)
+    }
+
+    pub fn max_mint(&self) -> Result<u64> {
+        self.get(View::MaxMint)
+    }
+
+    pub fn max_withdraw(&self, owner: Pubkey) -> Result<u64> {
+        self.get(View::MaxWithdraw(owner))
+    }
+
+    pub fn max_redeem(&self, owner: Pubkey) -> Result<u64> {
+        self.get(View::MaxRedeem(owner))
+    }
+}
+
+/// Async equivalents of the blocking functions
+pub mod nonblocking {
+    use anchor_lang::prelude::Pubkey;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::message::AddressLookupTableAccount;
+
+    use super::{
+        local_value, lookup_tables, recorded_keys, simulated_value, simulation_config,
+        view_accounts, view_transaction, RawAccount,
+    };
+    use crate::{accounts, view::View, ClientError, ConfidentialVault, Result, Vault, VaultKeys};
+
+    async fn get_account(client: &RpcClient, address: &Pubkey) -> Result<RawAccount> {
+        client
+            .get_account_with_commitment(address, client.commitment())
+            .await?
+            .value
+            .map(|account| (account.owner, account.data))
+            .ok_or(ClientError::AccountNotFound(*address))
+    }
+
+    /// Fetch an SVS-1 vault
+    pub async fn fetch_vault(client: &RpcClient, address: &Pubkey) -> Result<Vault> {
+        accounts::decode_vault(address, &get_account(client, address).await?.1)
+    }
+
+    /// Fetch an SVS-2 confidential vault
+    pub async fn fetch_confidential_vault(
+        client: &RpcClient,
+        address: &Pubkey,
+    ) -> Result<ConfidentialVault> {
+        accounts::decode_confidential_vault(address, &get_account(client, address).await?.1)
+    }
+
+    /// Fetch the keys of an existing SVS-1 or SVS-2 vault
+    pub async fn fetch_vault_keys(client: &RpcClient, vault: &Pubkey) -> Result<VaultKeys> {
+        let mut keys = recorded_keys(vault, &get_account(client, vault).await?)?;
+        keys.asset_token_program = get_account(client, &keys.asset_mint).await?.0;
+        Ok(keys)
+    }
+
+    /// Fetch address lookup tables to compile v0 transactions with
+    pub async fn fetch_lookup_tables(
+        client: &RpcClient,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>> {
+        let fetched = client
+            .get_multiple_accounts_with_commitment(addresses, client.commitment())
+            .await?
+            .value;
+        lookup_tables(addresses, &fetched)
+    }
+
+    /// Async [`super::Views`]
+    pub struct Views<'a> {
+        client: &'a RpcClient,
+        keys: VaultKeys,
+        payer: Pubkey,
+    }
+
+    impl<'a> Views<'a> {
+        pub fn new(client: &'a RpcClient, keys: VaultKeys, payer: Pubkey) -> Self {
+            Self {
+                client,
+                keys,
+                payer,
+            }
+        }
+
+        /// Value of `view`, simulated or else computed locally
+        pub async fn get(&self, view: View) -> Result<u64> {
+            match self.simulate(&view).await {
+                Err(ClientError::Rpc(_)) => self.compute_locally(&view).await,
+                value => value,
+            }
+        }
+
+        /// Value of `view` returned by a simulated view instruction
+        pub async fn simulate(&self, view: &View) -> Result<u64> {
+            let transaction = view_transaction(&self.keys, view, &self.payer);
+            let config = simulation_config(self.client.commitment());
+            let result = self
+                .client
+                .simulate_transaction_with_config(&transaction, config)
+                .await?;
+            simulated_value(&self.keys, result.value)
+        }
+
+        /// Value of `view` computed from the fetched vault accounts
+        pub async fn compute_locally(&self, view: &View) -> Result<u64> {
+            let fetched = self
+                .client
+                .get_multiple_accounts_with_commitment(
+                    &view_accounts(&self.keys, view),
+                    self.client.commitment(),
+                )
+                .await?
+                .value;
+            local_value(&self.keys, view, &fetched)
+        }
+
+        pub async fn preview_deposit(&self, assets: u64) -> Result<u64> {
+            self.get(View::PreviewDeposit(assets)).await
+        }
+
+        pub async fn preview_mint(&self, shares: u64) -> Result<u64> {
+            self.get(View::PreviewMint(shares)).await
+        }
+
+        pub async fn preview_withdraw(&self, assets: u64) -> Result<u64> {
+            self.get(View::PreviewWithdraw(assets)).await
+        }
+
+        pub async fn preview_redeem(&self, shares: u64) -> Result<u64> {
+            self.get(View::PreviewRedeem(shares)).await
+        }
+
+        pub async fn convert_to_shares(&self, assets: u64) -> Result<u64> {
+            self.get(View::ConvertToShares(assets)).await
+        }
+
+        pub async fn convert_to_assets(&self, shares: u64) -> Result<u64> {
+            self.get(View::ConvertToAssets(shares)).await
+        }
+
+        /// Assets per share, in [`SHARE_PRICE_SCALE`](crate::view::SHARE_PRICE_SCALE) fixed point
+        pub async fn share_price(&self) -> Result<u64> {
+            self.get(View::SharePrice).await
+        }
+
+        pub async fn total_assets(&self) -> Result<u64> {
+            self.get(View::TotalAssets).await
+        }
+
+        pub async fn max_deposit(&self) -> Result<u64> {
+            self.get(View::MaxDeposit).await
+        }
+
+        pub async fn max_mint(&self) -> Result<u64> {
+            self.get(View::MaxMint).await
+        }
+
+        pub async fn max_withdraw(&self, owner: Pubkey) -> Result<u64> {
+            self.get(View::MaxWithdraw(owner)).await
+        }
+
+        pub async fn max_redeem(&self, owner: Pubkey) -> Result<u64> {
+            self.get(View::MaxRedeem(owner)).await
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! Landing transactions: priority fees, compute limits, retries and
+//! confirmation
+//!
+//! [`TransactionSender`] prices a transaction's compute units from the recent
+//! prioritization fees of the accounts it writes, sizes its compute unit
+//! limit from a simulation, then sends it and polls its status until it
+//! reaches the client's commitment, rebroadcasting it in between. A
+//! transaction whose blockhash expired unconfirmed can no longer land, so it
+//! is signed again with a fresh blockhash, up to [`SendConfig::max_attempts`]
+//! times. [`nonblocking::TransactionSender`] does the same with the async
+//! client.
+//!
+//! ```ignore
+//! let sender = TransactionSender::new(&client, SendConfig::default());
+//! let ix = svs1::deposit(&keys, &user.pubkey(), 1_000_000, min_shares_out);
+//! let signature = sender.send(&user.pubkey(), &[ix], &[&user])?;
+//! ```
+//!
+//! The sender adds the compute budget instructions, so the instructions
+//! passed must not set a compute unit price or limit themselves.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use anchor_lang::prelude::Pubkey;
+use solana_client::{
+    rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::{self, ComputeBudgetInstruction},
+    hash::Hash,
+    instruction::Instruction,
+    message::AddressLookupTableAccount,
+    signature::Signature,
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+
+use crate::{
+    lookup_table::{versioned_message, versioned_transaction},
+    rpc::{check_simulation, simulation_config},
+    ClientError, Result,
+};
+
+/// Highest compute unit limit a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Accounts `getRecentPrioritizationFees` accepts at most
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// Compute unit price paid on top of the base fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFee {
+    None,
+    /// Micro-lamports per compute unit
+    Fixed(u64),
+    /// `percentile` (0-100) of the fees paid in recent slots by transactions
+    /// writing the same accounts, capped at `max_micro_lamports`
+    Estimate {
+        percentile: u8,
+        max_micro_lamports: u64,
+    },
+}
+
+/// Compute units a transaction may consume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeUnitLimit {
+    /// The runtime's default of 200k per instruction
+    Default,
+    Fixed(u32),
+    /// Units consumed in a simulation plus `margin_percent`
+    Simulate {
+        margin_percent: u32,
+    },
+}
+
+/// How [`TransactionSender`] prices, sends and confirms transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendConfig {
+    pub priority_fee: PriorityFee,
+    pub compute_unit_limit: ComputeUnitLimit,
+    /// Blockhashes to try before giving up
+    pub max_attempts: u32,
+    /// Time to wait for confirmation, over all attempts
+    pub timeout: Duration,
+    /// Delay between status polls, after each of which an unconfirmed
+    /// transaction is rebroadcast
+    pub poll_interval: Duration,
+    /// Send without simulating first; failures then only show once landed
+    pub skip_preflight: bool,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            priority_fee: PriorityFee::Estimate {
+                percentile: 75,
+                max_micro_lamports: 1_000_000,
+            },
+            compute_unit_limit: ComputeUnitLimit::Simulate { margin_percent: 10 },
+            max_attempts: 3,
+            timeout: Duration::from_secs(90),
+            poll_interval: Duration::from_secs(2),
+            skip_preflight: false,
+        }
+    }
+}
+
+/// `percentile` of recent prioritization fees
+fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    fees[(fees.len() - 1) * usize::from(percentile.min(100)) / 100]
+}
+
+/// Accounts written by `instructions`, whose write locks set the fee market
+fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts.truncate(MAX_FEE_ACCOUNTS);
+    accounts
+}
+
+/// `units` consumed in simulation plus `margin_percent`
+fn with_margin(units: u64, margin_percent: u32) -> u32 {
+    let units = units.saturating_mul(100 + u64::from(margin_percent)) / 100;
+    units.min(u64::from(MAX_COMPUTE_UNIT_LIMIT)) as u32
+}
+
+/// `instructions` preceded by the compute budget instructions
+fn with_compute_budget(
+    instructions: &[Instruction],
+    price: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<Instruction> {
+    limit
+        .map(ComputeBudgetInstruction::set_compute_unit_limit)
+        .into_iter()
+        .chain(price.map(ComputeBudgetInstruction::set_compute_unit_price))
+        .chain(instructions.iter().cloned())
+        .collect()
+}
+
+fn check_instructions(instructions: &[Instruction]) -> Result<()> {
+    if instructions
+        .iter()
+        .any(|ix| ix.program_id == compute_budget::ID)
+    {
+        return Err(ClientError::ComputeBudgetSet);
+    }
+    Ok(())
+}
+
+/// Unsigned transaction simulating `instructions` at the maximum limit
+fn simulation_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    price: Option<u64>,
+    tables: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction> {
+    let instructions = with_compute_budget(instructions, price, Some(MAX_COMPUTE_UNIT_LIMIT));
+    // The blockhash is replaced and signatures are not verified
+    let message = versioned_message(payer, &instructions, tables, Hash::default())?;
+    Ok(VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures.into()],
+        message,
+    })
+}
+
+fn simulated_units(result: &RpcSimulateTransactionResult) -> Result<u64> {
+    check_simulation(result)?;
+    result
+        .units_consumed
+        .ok_or_else(|| ClientError::Simulation("no compute units reported".to_string()))
+}
+
+fn send_config(commitment: CommitmentConfig, skip_preflight: bool) -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight,
+        preflight_commitment: Some(commitment.commitment),
+        // Rebroadcast by the sender instead
+        max_retries: Some(0),
+        ..Default::default()
+    }
+}
+
+/// Sends transactions until they are confirmed, see the [module](self) docs
+pub struct TransactionSender<'a> {
+    client: &'a RpcClient,
+    config: SendConfig,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+}
+
+impl<'a> TransactionSender<'a> {
+    pub fn new(client: &'a RpcClient, config: SendConfig) -> Self {
+        Self {
+            client,
+            config,
+            lookup_tables: Vec::new(),
+        }
+    }
+
+    /// Send v0 transactions looking up accounts in `tables`, e.g. from
+    /// [`crate::rpc::fetch_lookup_tables`]
+    pub fn with_lookup_tables(mut self, tables: Vec<AddressLookupTableAccount>) -> Self {
+        self.lookup_tables = tables;
+        self
+    }
+
+    /// Compute unit price of `instructions` in micro-lamports, if any
+    pub fn compute_unit_price(&self, instructions: &[Instruction]) -> Result<Option<u64>> {
+        match self.config.priority_fee {
+            PriorityFee::None => Ok(None),
+            PriorityFee::Fixed(price) => Ok(Some(price)),
+            PriorityFee::Estimate {
+                percentile,
+                max_micro_lamports,
+            } => {
+                let fees = self
+                    .client
+                    .get_recent_prioritization_fees(&writable_accounts(instructions))?;
+                let fees = fees.iter().map(|fee| fee.prioritization_fee).collect();
+                Ok(Some(
+                    fee_percentile(fees, percentile).min(max_micro_lamports),
+                ))
+            }
+        }
+    }
+
+    /// Compute unit limit of `instructions` paid by `payer`, unless the
+    /// default applies
+    pub fn compute_unit_limit(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        price: Option<u64>,
+    ) -> Result<Option<u32>> {
+        match self.config.compute_unit_limit {
+            ComputeUnitLimit::Default => Ok(None),
+            ComputeUnitLimit::Fixed(limit) => Ok(Some(limit)),
+            ComputeUnitLimit::Simulate { margin_percent } => {
+                let transaction =
+                    simulation_transaction(payer, instructions, price, &self.lookup_tables)?;
+                let result = self.client.simulate_transaction_with_config(
+                    &transaction,
+                    simulation_config(self.client.commitment()),
+                )?;
+                Ok(Some(with_margin(
+                    simulated_units(&result.value)?,
+                    margin_percent,
+                )))
+            }
+        }
+    }
+
+    /// Sign `instructions` and send them until confirmed at the client's
+    /// commitment; `payer` must be among `signers`
+    pub fn send(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Signature> {
+        check_instructions(instructions)?;
+        let price = self.compute_unit_price(instructions)?;
+        let limit = self.compute_unit_limit(payer, instructions, price)?;
+        let instructions = with_compute_budget(instructions, price, limit);
+        let commitment = self.client.commitment();
+        let deadline = Instant::now() + self.config.timeout;
+
+        for _ in 0..self.config.max_attempts {
+            let (blockhash, last_valid_block_height) = self
+                .client
+                .get_latest_blockhash_with_commitment(commitment)?;
+            let transaction = versioned_transaction(
+                payer,
+                &instructions,
+                &self.lookup_tables,
+                blockhash,
+                signers,
+            )?;
+            let signature = self.client.send_transaction_with_config(
+                &transaction,
+                send_config(commitment, self.config.skip_preflight),
+            )?;
+
+            loop {
+                thread::sleep(self.config.poll_interval);
+                // Read before the status: once past it, an unknown
+                // transaction can't land anymore
+                let block_height = self.client.get_block_height_with_commitment(commitment)?;
+                let status = self
+                    .client
+                    .get_signature_statuses(&[signature])?
+                    .value
+                    .pop()
+                    .flatten();
+                match status {
+                    Some(status) => {
+                        if let Some(error) = status.err {
+                            return Err(ClientError::TransactionFailed {
+                                signature,
+                                error: error.to_string(),
+                            });
+                        }
+                        if status.satisfies_commitment(commitment) {
+                            return Ok(signature);
+                        }
+                    }
+                    None if block_height > last_valid_block_height => break,
+                    None => {
+                        // A failed rebroadcast is retried at the next poll
+                        let _ = self.client.send_transaction_with_config(
+                            &transaction,
+                            send_config(commitment, true),
+                        );
+                    }
+                }
+                if Instant::now() >= deadline {
+                    return Err(ClientError::Timeout(signature));
+                }
+            }
+        }
+        Err(ClientError::Expired(self.config.max_attempts))
+    }
+}
+
+/// Async [`TransactionSender`]
+pub mod nonblocking {
+    use std::time::Instant;
+
+    use anchor_lang::prelude::Pubkey;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::{
+        instruction::Instruction, message::AddressLookupTableAccount, signature::Signature,
+        signer::Signer,
+    };
+
+    use super::{
+        check_instructions, fee_percentile, send_config, simulated_units, simulation_transaction,
+        with_compute_budget, with_margin, writable_accounts, ComputeUnitLimit, PriorityFee,
+        SendConfig,
+    };
+    use crate::{lookup_table::versioned_transaction, rpc::simulation_config, ClientError, Result};
+
+    /// Async [`super::TransactionSender`]
+    pub struct TransactionSender<'a> {
+        client: &'a RpcClient,
+        config: SendConfig,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+    }
+
+    impl<'a> TransactionSender<'a> {
+        pub fn new(client: &'a RpcClient, config: SendConfig) -> Self {
+            Self {
+                client,
+                config,
+                lookup_tables: Vec::new(),
+            }
+        }
+
+        pub fn with_lookup_tables(mut self, tables: Vec<AddressLookupTableAccount>) -> Self {
+            self.lookup_tables = tables;
+            self
+        }
+
+        pub async fn compute_unit_price(
+            &self,
+            instructions: &[Instruction],
+        ) -> Result<Option<u64>> {
+            match self.config.priority_fee {
+                PriorityFee::None => Ok(None),
+                PriorityFee::Fixed(price) => Ok(Some(price)),
+                PriorityFee::Estimate {
+                    percentile,
+                    max_micro_lamports,
+                } => {
+                    let fees = self
+                        .client
+                        .get_recent_prioritization_fees(&writable_accounts(instructions))
+                        .await?;
+                    let fees = fees.iter().map(|fee| fee.prioritization_fee).collect();
+                    Ok(Some(
+                        fee_percentile(fees, percentile).min(max_micro_lamports),
+                    ))
+                }
+            }
+        }
+
+        pub async fn compute_unit_limit(
+            &self,
+            payer: &Pubkey,
+            instructions: &[Instruction],
+            price: Option<u64>,
+        ) -> Result<Option<u32>> {
+            match self.config.compute_unit_limit {
+                ComputeUnitLimit::Default => Ok(None),
+                ComputeUnitLimit::Fixed(limit) => Ok(Some(limit)),
+                ComputeUnitLimit::Simulate { margin_percent } => {
+                    let transaction =
+                        simulation_transaction(payer, instructions, price, &self.lookup_tables)?;
+                    let result = self
+                        .client
+                        .simulate_transaction_with_config(
+                            &transaction,
+                            simulation_config(self.client.commitment()),
+                        )
+                        .await?;
+                    Ok(Some(with_margin(
+                        simulated_units(&result.value)?,
+                        margin_percent,
+                    )))
+                }
+            }
+        }
+
+        pub async fn send(
+            &self,
+            payer: &Pubkey,
+            instructions: &[Instruction],
+            signers: &[&dyn Signer],
+        ) -> Result<Signature> {
+            check_instructions(instructions)?;
+            let price = self.compute_unit_price(instructions).await?;
+            let limit = self.compute_unit_limit(payer, instructions, price).await?;
+            let instructions = with_compute_budget(instructions, price, limit);
+            let commitment = self.client.commitment();
+            let deadline = Instant::now() + self.config.timeout;
+
+            for _ in 0..self.config.max_attempts {
+                let (blockhash, last_valid_block_height) = self
+                    .client
+                    .get_latest_blockhash_with_commitment(commitment)
+                    .await?;
+                let transaction = versioned_transaction(
+                    payer,
+                    &instructions,
+                    &self.lookup_tables,
+                    blockhash,
+                    signers,
+                )?;
+                let signature = self
+                    .client
+                    .send_transaction_with_config(
+                        &transaction,
+                        send_config(commitment, self.config.skip_preflight),
+                    )
+                    .await?;
+
+                loop {
+                    tokio::time::sleep(self.config.poll_interval).await;
+                    let block_height = self
+                        .client
+                        .get_block_height_with_commitment(commitment)
+                        .await?;
+                    let status = self
+                        .client
+                        .get_signature_statuses(&[signature])
+                        .await?
+                        .value
+                        .pop()
+                        .flatten();
+                    match status {
+                        Some(status) => {
+                            if let Some(error) = status.err {
+                                return Err(ClientError::TransactionFailed {
+                                    signature,
+                                    error: error.to_string(),
+                                });
+                            }
+                            if status.satisfies_commitment(commitment) {
+                                return Ok(signature);
+                            }
+                        }
+                        None if block_height > last_valid_block_height => break,
+                        None => {
+                            let _ = self
+                                .client
+                                .send_transaction_with_config(
+                                    &transaction,
+                                    send_config(commitment, true),
+                                )
+                                .await;
+                        }
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(ClientError::Timeout(signature));
+                    }
+                }
+            }
+            Err(ClientError::Expired(self.config.max_attempts))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token;
+
+    use super::*;
+    use crate::{svs1, VaultKeys};
+
+    #[test]
+    fn test_fee_percentile() {
+        assert_eq!(fee_percentile(Vec::new(), 75), 0);
+        let fees = vec![50, 0, 10, 0, 1_000, 20, 0, 30, 40, 0, 5];
+        assert_eq!(fee_percentile(fees.clone(), 0), 0);
+        assert_eq!(fee_percentile(fees.clone(), 50), 10);
+        assert_eq!(fee_percentile(fees.clone(), 75), 30);
+        assert_eq!(fee_percentile(fees.clone(), 100), 1_000);
+        assert_eq!(fee_percentile(fees, 200), 1_000);
+    }
+
+    #[test]
+    fn test_with_margin() {
+        assert_eq!(with_margin(100_000, 10), 110_000);
+        assert_eq!(with_margin(100_000, 0), 100_000);
+        assert_eq!(with_margin(1_300_000, 20), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(with_margin(u64::MAX, 10), MAX_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn test_compute_budget_and_fee_accounts() {
+        let keys = VaultKeys::derive(&svs_1::ID, &Pubkey::new_unique(), 0, &token::ID);
+        let user = Pubkey::new_unique();
+        let deposit = svs1::deposit(&keys, &user, 1_000, 1);
+        let instructions = std::slice::from_ref(&deposit);
+
+        let accounts = writable_accounts(instructions);
+        assert!(accounts.contains(&keys.vault));
+        assert!(!accounts.contains(&keys.asset_mint));
+        assert!(!accounts.contains(&keys.asset_token_program));
+
+        let budgeted = with_compute_budget(instructions, Some(5), Some(60_000));
+        assert_eq!(budgeted.len(), 3);
+        assert!(budgeted[..2]
+            .iter()
+            .all(|ix| ix.program_id == compute_budget::ID));
+        assert_eq!(budgeted[2], deposit);
+        assert_eq!(with_compute_budget(instructions, None, None), instructions);
+        assert!(check_instructions(&budgeted).is_err());
+        assert!(check_instructions(instructions).is_ok());
+
+        let transaction = simulation_transaction(&user, instructions, None, &[]).unwrap();
+        assert_eq!(transaction.signatures.len(), 1);
+        assert_eq!(transaction.message.instructions().len(), 2);
+    }
+}
@@ -0,0 +1,368 @@
+//! SVS-1 instruction builders
+//!
+//! `keys.program_id` must be the SVS-1 program. Token accounts of the user
+//! are their associated token accounts; the shares account of a depositor is
+//! created if needed.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, sysvar},
+    system_program,
+};
+use anchor_spl::{associated_token, token_2022};
+use svs_1::{accounts, instruction};
+
+use crate::{
+    pda::{find_config_address, program_data_address},
+    VaultKeys,
+};
+
+/// Create the vault described by `keys` (see [`VaultKeys::derive`]);
+/// `non_transferable` creates soulbound shares
+pub fn initialize(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    vault_id: u64,
+    name: String,
+    symbol: String,
+    uri: String,
+    non_transferable: bool,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Initialize {
+            authority: *authority,
+            vault: keys.vault,
+            config: find_config_address(&keys.program_id).0,
+            asset_mint: keys.asset_mint,
+            shares_mint: keys.shares_mint,
+            asset_vault: keys.asset_vault,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::Initialize {
+            vault_id,
+            name,
+            symbol,
+            uri,
+            non_transferable,
+        },
+    )
+}
+
+/// Deposit `assets` and receive at least `min_shares_out` shares
+pub fn deposit(keys: &VaultKeys, user: &Pubkey, assets: u64, min_shares_out: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Deposit {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Deposit {
+            assets,
+            min_shares_out,
+        },
+    )
+}
+
+/// Mint exactly `shares` for at most `max_assets_in` assets
+pub fn mint(keys: &VaultKeys, user: &Pubkey, shares: u64, max_assets_in: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::MintShares {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Mint {
+            shares,
+            max_assets_in,
+        },
+    )
+}
+
+/// Withdraw exactly `assets`, burning at most `max_shares_in` shares
+pub fn withdraw(keys: &VaultKeys, user: &Pubkey, assets: u64, max_shares_in: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Withdraw {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Withdraw {
+            assets,
+            max_shares_in,
+        },
+    )
+}
+
+/// Redeem `shares` for at least `min_assets_out` assets
+pub fn redeem(keys: &VaultKeys, user: &Pubkey, shares: u64, min_assets_out: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Redeem {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Redeem {
+            shares,
+            min_assets_out,
+        },
+    )
+}
+
+fn admin(keys: &VaultKeys, authority: &Pubkey) -> accounts::Admin {
+    accounts::Admin {
+        authority: *authority,
+        vault: keys.vault,
+    }
+}
+
+/// Pause every vault operation
+pub fn pause(keys: &VaultKeys, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::Pause {},
+    )
+}
+
+/// Resume vault operations
+pub fn unpause(keys: &VaultKeys, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::Unpause {},
+    )
+}
+
+/// Hand the vault over to `new_authority`
+pub fn transfer_authority(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::TransferAuthority { new_authority },
+    )
+}
+
+/// Set `total_assets` to the asset vault balance
+pub fn sync(keys: &VaultKeys, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Sync {
+            authority: *authority,
+            vault: keys.vault,
+            asset_vault: keys.asset_vault,
+        },
+        instruction::Sync {},
+    )
+}
+
+/// Raise or lower the vault's minimum deposit, no lower than the protocol
+/// minimum
+pub fn set_min_deposit(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    min_deposit_amount: u64,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::SetMinDeposit {
+            authority: *authority,
+            vault: keys.vault,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::SetMinDeposit { min_deposit_amount },
+    )
+}
+
+/// Create the protocol config of `program_id`; `authority` must be the
+/// program's upgrade authority
+pub fn initialize_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    min_deposit_amount: u64,
+    max_decimals: u8,
+    shares_decimals: u8,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        accounts::InitializeConfig {
+            authority: *authority,
+            config: find_config_address(program_id).0,
+            program: *program_id,
+            program_data: program_data_address(program_id),
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig {
+            min_deposit_amount,
+            max_decimals,
+            shares_decimals,
+        },
+    )
+}
+
+fn update_config_accounts(program_id: &Pubkey, authority: &Pubkey) -> accounts::UpdateConfig {
+    accounts::UpdateConfig {
+        authority: *authority,
+        config: find_config_address(program_id).0,
+    }
+}
+
+/// Change the protocol config applied to vaults created from now on
+pub fn update_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    min_deposit_amount: u64,
+    max_decimals: u8,
+    shares_decimals: u8,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        update_config_accounts(program_id, authority),
+        instruction::UpdateConfig {
+            min_deposit_amount,
+            max_decimals,
+            shares_decimals,
+        },
+    )
+}
+
+/// Hand the protocol config over to `new_authority`
+pub fn transfer_config_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        update_config_accounts(program_id, authority),
+        instruction::TransferConfigAuthority { new_authority },
+    )
+}
+
+/// Set or clear (`Pubkey::default()`) the protocol guardian
+pub fn set_protocol_guardian(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_guardian: Pubkey,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        update_config_accounts(program_id, authority),
+        instruction::SetProtocolGuardian { new_guardian },
+    )
+}
+
+fn protocol_guardian(program_id: &Pubkey, authority: &Pubkey) -> accounts::ProtocolGuardian {
+    accounts::ProtocolGuardian {
+        authority: *authority,
+        config: find_config_address(program_id).0,
+    }
+}
+
+/// Pause every vault of `program_id`; `authority` is the config authority or
+/// the protocol guardian
+pub fn pause_protocol(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        *program_id,
+        protocol_guardian(program_id, authority),
+        instruction::PauseProtocol {},
+    )
+}
+
+/// Lift the protocol-wide pause of `program_id`
+pub fn unpause_protocol(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        *program_id,
+        protocol_guardian(program_id, authority),
+        instruction::UnpauseProtocol {},
+    )
+}
+
+/// Fail unless the vault is internally consistent; the program must be
+/// built with its `testing` feature
+#[cfg(feature = "testing")]
+pub fn assert_invariants(keys: &VaultKeys) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::AssertInvariants {
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            shares_mint: keys.shares_mint,
+            asset_vault: keys.asset_vault,
+        },
+        instruction::AssertInvariants {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    use anchor_spl::token;
+
+    #[test]
+    fn test_deposit_instruction() {
+        let keys = VaultKeys::derive(&svs_1::ID, &Pubkey::new_unique(), 0, &token::ID);
+        let user = Pubkey::new_unique();
+
+        let ix = deposit(&keys, &user, 5_000, 4_000);
+
+        assert_eq!(ix.program_id, svs_1::ID);
+        assert_eq!(&ix.data[..8], instruction::Deposit::DISCRIMINATOR);
+        assert_eq!(&ix.data[8..16], &5_000u64.to_le_bytes());
+        assert_eq!(&ix.data[16..], &4_000u64.to_le_bytes());
+
+        assert_eq!(ix.accounts.len(), 12);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[0].pubkey, user);
+        assert_eq!(ix.accounts[3].pubkey, keys.asset_account(&user));
+        assert_eq!(ix.accounts[6].pubkey, keys.shares_account(&user));
+        // The protocol config comes last, so existing account indices hold
+        assert_eq!(ix.accounts[11].pubkey, find_config_address(&svs_1::ID).0);
+        assert!(!ix.accounts[11].is_writable);
+    }
+}
@@ -0,0 +1,780 @@
+//! SVS-2 instruction builders
+//!
+//! `keys.program_id` must be the SVS-2 program. Shares are confidential, so
+//! the ZK proofs and AE ciphertexts these instructions take are generated
+//! off-chain, e.g. by the proof backend. A user's shares account must exist
+//! and be configured with [`configure_account`] before their first deposit.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        ed25519_program,
+        instruction::{AccountMeta, Instruction},
+        sysvar,
+    },
+    system_program,
+};
+use anchor_spl::{associated_token, token_2022};
+use svs_2::{accounts, instruction};
+
+pub use svs_2::permit::deposit_permit_message;
+
+use crate::{
+    pda::{
+        find_config_address, find_harvest_authority_address, find_permit_delegate_address,
+        find_permit_receipt_address, program_data_address,
+    },
+    VaultKeys,
+};
+
+/// AE ciphertext of a decryptable balance (36 bytes)
+pub type DecryptableBalance = [u8; 36];
+
+/// Where `configure_account` finds its pubkey validity proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PubkeyValidityProof {
+    /// A VerifyPubkeyValidity instruction in the same transaction, at this
+    /// offset from `configure_account` (-1 if directly before it)
+    InstructionOffset(i8),
+    /// A context state account verified in an earlier transaction
+    ContextAccount(Pubkey),
+}
+
+/// Context state accounts holding the proofs of a withdraw or redeem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawProofContexts {
+    /// Ciphertext-commitment equality proof
+    pub equality: Pubkey,
+    /// Batched range proof
+    pub range: Pubkey,
+}
+
+/// Create the vault described by `keys` (see [`VaultKeys::derive`])
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    keys: &VaultKeys,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    vault_id: u64,
+    name: String,
+    symbol: String,
+    uri: String,
+    auditor_elgamal_pubkey: Option<[u8; 32]>,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Initialize {
+            payer: *payer,
+            authority: *authority,
+            vault: keys.vault,
+            config: find_config_address(&keys.program_id).0,
+            asset_mint: keys.asset_mint,
+            shares_mint: keys.shares_mint,
+            asset_vault: keys.asset_vault,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::Initialize {
+            vault_id,
+            name,
+            symbol,
+            uri,
+            auditor_elgamal_pubkey,
+        },
+    )
+}
+
+/// Create the vault described by `keys` around the existing Token-2022
+/// `keys.shares_mint`
+///
+/// The mint must have the protocol's shares decimals (9 by default), no supply and no freeze authority, the
+/// vault PDA as mint authority, and `ConfidentialTransferMint` with the vault
+/// PDA as authority and auto-approval. Its auditor key becomes the vault's.
+pub fn initialize_with_shares_mint(
+    keys: &VaultKeys,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    vault_id: u64,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::InitializeWithMint {
+            payer: *payer,
+            authority: *authority,
+            vault: keys.vault,
+            config: find_config_address(&keys.program_id).0,
+            asset_mint: keys.asset_mint,
+            shares_mint: keys.shares_mint,
+            asset_vault: keys.asset_vault,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeWithSharesMint {
+            vault_id,
+            name,
+            symbol,
+            uri,
+        },
+    )
+}
+
+/// Enable confidential transfers on `user`'s shares account; `payer` funds
+/// the account's growth, the user when `None`
+pub fn configure_account(
+    keys: &VaultKeys,
+    user: &Pubkey,
+    payer: Option<&Pubkey>,
+    decryptable_zero_balance: DecryptableBalance,
+    proof: PubkeyValidityProof,
+) -> Instruction {
+    let (proof_context_account, proof_instruction_offset) = match proof {
+        PubkeyValidityProof::InstructionOffset(offset) => (None, offset),
+        PubkeyValidityProof::ContextAccount(account) => (Some(account), 0),
+    };
+
+    crate::instruction(
+        keys.program_id,
+        accounts::ConfigureAccount {
+            user: *user,
+            payer: payer.copied(),
+            vault: keys.vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            proof_context_account,
+            instructions_sysvar: sysvar::instructions::ID,
+            token_2022_program: token_2022::ID,
+            system_program: system_program::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::ConfigureAccount {
+            decryptable_zero_balance,
+            proof_instruction_offset,
+        },
+    )
+}
+
+/// Deposit `assets` for at least `min_shares_out` shares, credited to the
+/// pending balance; `memo_hash` links an off-chain compliance record
+pub fn deposit(
+    keys: &VaultKeys,
+    user: &Pubkey,
+    assets: u64,
+    min_shares_out: u64,
+    memo_hash: Option<[u8; 32]>,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Deposit {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Deposit {
+            assets,
+            min_shares_out,
+            memo_hash,
+        },
+    )
+}
+
+/// Ed25519 program instruction verifying `owner`'s `signature` of a
+/// [`deposit_permit_message`]; it must directly precede
+/// [`deposit_with_permit`]
+pub fn permit_signature_verification(
+    owner: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Instruction {
+    // One signature, padding, then the offsets of the public key, signature
+    // and message, all in this instruction (index u16::MAX)
+    const PUBKEY_OFFSET: u16 = 16;
+    const SIGNATURE_OFFSET: u16 = PUBKEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+    let message_len = u16::try_from(message.len()).expect("permit message fits in a u16");
+
+    let mut data = vec![1, 0];
+    for field in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBKEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message_len,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Deposit `assets` of `owner`, who signed the permit, with `relayer` paying
+/// the fees and the receipt that consumes `nonce`
+///
+/// The vault's permit delegate, see [`find_permit_delegate_address`], pulls
+/// the assets as `owner`'s token delegate, and the shares
+/// are minted to the public balance of `owner`'s shares account. Precede it
+/// with [`permit_signature_verification`].
+pub fn deposit_with_permit(
+    keys: &VaultKeys,
+    relayer: &Pubkey,
+    owner: &Pubkey,
+    assets: u64,
+    min_shares_out: u64,
+    nonce: u64,
+    expires_at: i64,
+) -> Instruction {
+    let (permit_receipt, _) =
+        find_permit_receipt_address(&keys.program_id, &keys.vault, owner, nonce);
+
+    crate::instruction(
+        keys.program_id,
+        accounts::DepositWithPermit {
+            relayer: *relayer,
+            owner: *owner,
+            vault: keys.vault,
+            permit_delegate: find_permit_delegate_address(&keys.program_id, &keys.vault).0,
+            asset_mint: keys.asset_mint,
+            owner_asset_account: keys.asset_account(owner),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            owner_shares_account: keys.shares_account(owner),
+            permit_receipt,
+            instructions_sysvar: sysvar::instructions::ID,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            system_program: system_program::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::DepositWithPermit {
+            assets,
+            min_shares_out,
+            nonce,
+            expires_at,
+        },
+    )
+}
+
+/// Mint exactly `shares` for at most `max_assets_in` assets, credited to the
+/// pending balance
+pub fn mint(keys: &VaultKeys, user: &Pubkey, shares: u64, max_assets_in: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::MintShares {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Mint {
+            shares,
+            max_assets_in,
+        },
+    )
+}
+
+/// Move the pending balance to the available balance
+pub fn apply_pending(
+    keys: &VaultKeys,
+    user: &Pubkey,
+    new_decryptable_available_balance: DecryptableBalance,
+    expected_pending_balance_credit_counter: u64,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::ApplyPending {
+            user: *user,
+            vault: keys.vault,
+            user_shares_account: keys.shares_account(user),
+            token_2022_program: token_2022::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::ApplyPending {
+            new_decryptable_available_balance,
+            expected_pending_balance_credit_counter,
+        },
+    )
+}
+
+/// Move `shares` of the user's public balance, e.g. from a permit deposit,
+/// into the pending balance
+pub fn resume_confidential_deposit(keys: &VaultKeys, user: &Pubkey, shares: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::ResumeConfidentialDeposit {
+            user: *user,
+            vault: keys.vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            token_2022_program: token_2022::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::ResumeConfidentialDeposit { shares },
+    )
+}
+
+/// Withdraw exactly `assets`, burning at most `max_shares_in` shares proven
+/// by the `proofs` context accounts
+pub fn withdraw(
+    keys: &VaultKeys,
+    user: &Pubkey,
+    proofs: &WithdrawProofContexts,
+    assets: u64,
+    max_shares_in: u64,
+    new_decryptable_available_balance: DecryptableBalance,
+    memo_hash: Option<[u8; 32]>,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Withdraw {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            equality_proof_context: proofs.equality,
+            range_proof_context: proofs.range,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Withdraw {
+            assets,
+            max_shares_in,
+            new_decryptable_available_balance,
+            memo_hash,
+        },
+    )
+}
+
+/// Redeem `shares` proven by the `proofs` context accounts for at least
+/// `min_assets_out` assets
+pub fn redeem(
+    keys: &VaultKeys,
+    user: &Pubkey,
+    proofs: &WithdrawProofContexts,
+    shares: u64,
+    min_assets_out: u64,
+    new_decryptable_available_balance: DecryptableBalance,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Redeem {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            equality_proof_context: proofs.equality,
+            range_proof_context: proofs.range,
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Redeem {
+            shares,
+            min_assets_out,
+            new_decryptable_available_balance,
+        },
+    )
+}
+
+/// Redeem `shares` from the public balance of `user`'s shares account pro
+/// rata against the asset vault balance; only while the vault is paused
+pub fn emergency_redeem(keys: &VaultKeys, user: &Pubkey, shares: u64) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::EmergencyRedeem {
+            user: *user,
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            user_asset_account: keys.asset_account(user),
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            user_shares_account: keys.shares_account(user),
+            asset_token_program: keys.asset_token_program,
+            token_2022_program: token_2022::ID,
+        },
+        instruction::EmergencyRedeem { shares },
+    )
+}
+
+/// Move the rewards in `reward_token_account` into the vault
+///
+/// The reward account must be owned by the vault's harvest authority, see
+/// [`find_harvest_authority_address`]. `caller` is the authority or the
+/// harvester. Rewards in another mint are swapped by `swap_adapter`, the
+/// vault's whitelisted adapter, which gets `adapter_accounts` after its fixed
+/// accounts; their signer flags are not passed on to the adapter.
+pub fn harvest(
+    keys: &VaultKeys,
+    caller: &Pubkey,
+    reward_token_account: &Pubkey,
+    swap_adapter: &Pubkey,
+    min_assets_out: u64,
+    adapter_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut ix = crate::instruction(
+        keys.program_id,
+        accounts::Harvest {
+            caller: *caller,
+            vault: keys.vault,
+            harvest_authority: find_harvest_authority_address(&keys.program_id, &keys.vault).0,
+            asset_mint: keys.asset_mint,
+            asset_vault: keys.asset_vault,
+            shares_mint: keys.shares_mint,
+            reward_token_account: *reward_token_account,
+            swap_adapter: *swap_adapter,
+            asset_token_program: keys.asset_token_program,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::Harvest { min_assets_out },
+    );
+    ix.accounts.extend_from_slice(adapter_accounts);
+    ix
+}
+
+/// Pause every vault operation; `authority` is the vault authority or its
+/// guardian
+pub fn pause(keys: &VaultKeys, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Pause {
+            authority: *authority,
+            vault: keys.vault,
+        },
+        instruction::Pause {},
+    )
+}
+
+fn admin(keys: &VaultKeys, authority: &Pubkey) -> accounts::Admin {
+    accounts::Admin {
+        authority: *authority,
+        vault: keys.vault,
+    }
+}
+
+/// Resume vault operations
+pub fn unpause(keys: &VaultKeys, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::Unpause {},
+    )
+}
+
+/// Hand the vault over to `new_authority`
+pub fn transfer_authority(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::TransferAuthority { new_authority },
+    )
+}
+
+/// Set the pause-only guardian (`Pubkey::default()` removes it)
+pub fn set_guardian(keys: &VaultKeys, authority: &Pubkey, new_guardian: Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::SetGuardian { new_guardian },
+    )
+}
+
+/// Set the swap adapter, the harvester and the period over which harvested
+/// profit unlocks
+pub fn set_harvest_config(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    swap_adapter: Pubkey,
+    harvester: Pubkey,
+    profit_unlock_period: i64,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        admin(keys, authority),
+        instruction::SetHarvestConfig {
+            swap_adapter,
+            harvester,
+            profit_unlock_period,
+        },
+    )
+}
+
+/// Set `total_assets` to the asset vault balance
+pub fn sync(keys: &VaultKeys, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::Sync {
+            authority: *authority,
+            vault: keys.vault,
+            asset_vault: keys.asset_vault,
+        },
+        instruction::Sync {},
+    )
+}
+
+/// Raise or lower the vault's minimum deposit, no lower than the protocol
+/// minimum
+pub fn set_min_deposit(
+    keys: &VaultKeys,
+    authority: &Pubkey,
+    min_deposit_amount: u64,
+) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::SetMinDeposit {
+            authority: *authority,
+            vault: keys.vault,
+            config: find_config_address(&keys.program_id).0,
+        },
+        instruction::SetMinDeposit { min_deposit_amount },
+    )
+}
+
+/// Grow a vault created with an older account layout to the current one,
+/// with `payer` funding the extra rent
+pub fn migrate_vault(keys: &VaultKeys, payer: &Pubkey) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::MigrateVault {
+            payer: *payer,
+            vault: keys.vault,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateVault {},
+    )
+}
+
+/// Create the protocol config of `program_id`; `authority` must be the
+/// program's upgrade authority
+pub fn initialize_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    min_deposit_amount: u64,
+    max_decimals: u8,
+    shares_decimals: u8,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        accounts::InitializeConfig {
+            authority: *authority,
+            config: find_config_address(program_id).0,
+            program: *program_id,
+            program_data: program_data_address(program_id),
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig {
+            min_deposit_amount,
+            max_decimals,
+            shares_decimals,
+        },
+    )
+}
+
+fn update_config_accounts(program_id: &Pubkey, authority: &Pubkey) -> accounts::UpdateConfig {
+    accounts::UpdateConfig {
+        authority: *authority,
+        config: find_config_address(program_id).0,
+    }
+}
+
+/// Change the protocol config applied to vaults created from now on
+pub fn update_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    min_deposit_amount: u64,
+    max_decimals: u8,
+    shares_decimals: u8,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        update_config_accounts(program_id, authority),
+        instruction::UpdateConfig {
+            min_deposit_amount,
+            max_decimals,
+            shares_decimals,
+        },
+    )
+}
+
+/// Hand the protocol config over to `new_authority`
+pub fn transfer_config_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        update_config_accounts(program_id, authority),
+        instruction::TransferConfigAuthority { new_authority },
+    )
+}
+
+/// Set or clear (`Pubkey::default()`) the protocol guardian
+pub fn set_protocol_guardian(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_guardian: Pubkey,
+) -> Instruction {
+    crate::instruction(
+        *program_id,
+        update_config_accounts(program_id, authority),
+        instruction::SetProtocolGuardian { new_guardian },
+    )
+}
+
+fn protocol_guardian(program_id: &Pubkey, authority: &Pubkey) -> accounts::ProtocolGuardian {
+    accounts::ProtocolGuardian {
+        authority: *authority,
+        config: find_config_address(program_id).0,
+    }
+}
+
+/// Pause every vault of `program_id`; `authority` is the config authority or
+/// the protocol guardian
+pub fn pause_protocol(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        *program_id,
+        protocol_guardian(program_id, authority),
+        instruction::PauseProtocol {},
+    )
+}
+
+/// Lift the protocol-wide pause of `program_id`
+pub fn unpause_protocol(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    crate::instruction(
+        *program_id,
+        protocol_guardian(program_id, authority),
+        instruction::UnpauseProtocol {},
+    )
+}
+
+/// Fail unless the vault is internally consistent; the program must be
+/// built with its `testing` feature
+#[cfg(feature = "testing")]
+pub fn assert_invariants(keys: &VaultKeys) -> Instruction {
+    crate::instruction(
+        keys.program_id,
+        accounts::AssertInvariants {
+            vault: keys.vault,
+            asset_mint: keys.asset_mint,
+            shares_mint: keys.shares_mint,
+            asset_vault: keys.asset_vault,
+        },
+        instruction::AssertInvariants {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token;
+
+    fn keys() -> VaultKeys {
+        VaultKeys::derive(&svs_2::ID, &Pubkey::new_unique(), 0, &token::ID)
+    }
+
+    #[test]
+    fn test_configure_account_proof_location() {
+        let keys = keys();
+        let user = Pubkey::new_unique();
+        let context = Pubkey::new_unique();
+
+        let inline = configure_account(
+            &keys,
+            &user,
+            None,
+            [0; 36],
+            PubkeyValidityProof::InstructionOffset(-1),
+        );
+        let verified = configure_account(
+            &keys,
+            &user,
+            None,
+            [0; 36],
+            PubkeyValidityProof::ContextAccount(context),
+        );
+
+        // Omitted optional accounts are passed as the program id
+        assert_eq!(inline.accounts[1].pubkey, svs_2::ID);
+        assert_eq!(inline.accounts[5].pubkey, svs_2::ID);
+        assert_eq!(*inline.data.last().unwrap(), -1i8 as u8);
+        assert_eq!(verified.accounts[5].pubkey, context);
+        assert_eq!(*verified.data.last().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_permit_signature_verification() {
+        let owner = Pubkey::new_unique();
+        let message = deposit_permit_message(&Pubkey::new_unique(), &owner, 1_000, 1, 0, 60);
+
+        let ix = permit_signature_verification(&owner, &[9; 64], &message);
+
+        // The offsets the program reads the key and message from
+        let read = |at: usize| u16::from_le_bytes([ix.data[at], ix.data[at + 1]]) as usize;
+        assert_eq!(&ix.data[read(6)..read(6) + 32], owner.as_ref());
+        assert_eq!(&ix.data[read(2)..read(2) + 64], &[9; 64]);
+        assert_eq!(&ix.data[read(10)..read(10) + read(12)], message.as_slice());
+        assert_eq!([read(4), read(8), read(14)], [u16::MAX as usize; 3]);
+    }
+
+    #[test]
+    fn test_withdraw_instruction() {
+        let keys = keys();
+        let user = Pubkey::new_unique();
+        let proofs = WithdrawProofContexts {
+            equality: Pubkey::new_unique(),
+            range: Pubkey::new_unique(),
+        };
+
+        let ix = withdraw(&keys, &user, &proofs, 100, 120, [7; 36], None);
+
+        assert_eq!(ix.accounts.len(), 12);
+        assert_eq!(ix.accounts[7].pubkey, proofs.equality);
+        assert_eq!(ix.accounts[8].pubkey, proofs.range);
+        assert!(!ix.accounts[7].is_writable);
+        // Discriminator, two amounts, the balance and a `None` memo hash
+        assert_eq!(ix.data.len(), 8 + 8 + 8 + 36 + 1);
+    }
+}
@@ -0,0 +1,406 @@
+//! Vault view functions
+//!
+//! The programs' view instructions (`preview_deposit`, `max_withdraw`, ...)
+//! return a `u64` through return data, so clients call them by simulating a
+//! transaction (see `rpc::Views`). [`VaultSnapshot`] evaluates the same views
+//! locally from fetched account state, with the programs' own math (`svs-math`).
+//!
+//! SVS-1 and SVS-2 name their view instructions and accounts identically, so
+//! [`instruction`] encodes them once for either program.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::instruction::{AccountMeta, Instruction},
+};
+use svs_1::{accounts, instruction as ix};
+use svs_math::{
+    convert_to_assets, convert_to_shares, locked_profit, share_price, MathError, Rounding,
+};
+
+pub use svs_math::SHARE_PRICE_SCALE;
+
+use crate::{pda::find_config_address, ClientError, ConfidentialVault, Result, Vault, VaultKeys};
+
+/// A view instruction and its argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Shares minted for depositing `assets`
+    PreviewDeposit(u64),
+    /// Assets required to mint `shares`
+    PreviewMint(u64),
+    /// Shares burned to withdraw `assets`
+    PreviewWithdraw(u64),
+    /// Assets received for redeeming `shares`
+    PreviewRedeem(u64),
+    /// `PreviewDeposit`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewDeposit(u64),
+    /// `PreviewMint`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewMint(u64),
+    /// `PreviewWithdraw`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewWithdraw(u64),
+    /// `PreviewRedeem`, `u64::MAX` instead of an overflow error
+    SaturatingPreviewRedeem(u64),
+    /// `assets` in shares, rounded down
+    ConvertToShares(u64),
+    /// `shares` in assets, rounded down
+    ConvertToAssets(u64),
+    /// Assets per share, in [`SHARE_PRICE_SCALE`] fixed point, rounded down
+    SharePrice,
+    TotalAssets,
+    MaxDeposit,
+    MaxMint,
+    /// Assets the owner of this wallet can withdraw
+    MaxWithdraw(Pubkey),
+    /// Shares the owner of this wallet can redeem
+    MaxRedeem(Pubkey),
+}
+
+impl View {
+    /// Owner whose shares account the view reads, if any
+    pub fn owner(&self) -> Option<&Pubkey> {
+        match self {
+            View::MaxWithdraw(owner) | View::MaxRedeem(owner) => Some(owner),
+            _ => None,
+        }
+    }
+}
+
+/// Instruction calling `view` on the vault described by `keys`
+///
+/// Every view reads the protocol config for the pause of the `max_*` views.
+/// The asset mint is appended as the last account: SVS-2 reads its transfer
+/// fee for the previews and `MaxWithdraw`, SVS-1 ignores it.
+pub fn instruction(keys: &VaultKeys, view: &View) -> Instruction {
+    let mut instruction = view_instruction(keys, view);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(keys.asset_mint, false));
+    instruction
+}
+
+fn view_instruction(keys: &VaultKeys, view: &View) -> Instruction {
+    let config = find_config_address(&keys.program_id).0;
+    let vault_view = accounts::VaultView {
+        vault: keys.vault,
+        shares_mint: keys.shares_mint,
+        config,
+    };
+
+    match *view {
+        View::PreviewDeposit(assets) => {
+            crate::instruction(keys.program_id, vault_view, ix::PreviewDeposit { assets })
+        }
+        View::PreviewMint(shares) => {
+            crate::instruction(keys.program_id, vault_view, ix::PreviewMint { shares })
+        }
+        View::PreviewWithdraw(assets) => {
+            crate::instruction(keys.program_id, vault_view, ix::PreviewWithdraw { assets })
+        }
+        View::PreviewRedeem(shares) => {
+            crate::instruction(keys.program_id, vault_view, ix::PreviewRedeem { shares })
+        }
+        View::SaturatingPreviewDeposit(assets) => crate::instruction(
+            keys.program_id,
+            vault_view,
+            ix::SaturatingPreviewDeposit { assets },
+        ),
+        View::SaturatingPreviewMint(shares) => crate::instruction(
+            keys.program_id,
+            vault_view,
+            ix::SaturatingPreviewMint { shares },
+        ),
+        View::SaturatingPreviewWithdraw(assets) => crate::instruction(
+            keys.program_id,
+            vault_view,
+            ix::SaturatingPreviewWithdraw { assets },
+        ),
+        View::SaturatingPreviewRedeem(shares) => crate::instruction(
+            keys.program_id,
+            vault_view,
+            ix::SaturatingPreviewRedeem { shares },
+        ),
+        View::ConvertToShares(assets) => {
+            crate::instruction(keys.program_id, vault_view, ix::ConvertToShares { assets })
+        }
+        View::ConvertToAssets(shares) => {
+            crate::instruction(keys.program_id, vault_view, ix::ConvertToAssets { shares })
+        }
+        View::SharePrice => crate::instruction(keys.program_id, vault_view, ix::SharePrice {}),
+        View::TotalAssets => crate::instruction(keys.program_id, vault_view, ix::TotalAssets {}),
+        View::MaxDeposit => crate::instruction(keys.program_id, vault_view, ix::MaxDeposit {}),
+        View::MaxMint => crate::instruction(keys.program_id, vault_view, ix::MaxMint {}),
+        View::MaxWithdraw(owner) | View::MaxRedeem(owner) => {
+            let with_owner = accounts::VaultViewWithOwner {
+                vault: keys.vault,
+                shares_mint: keys.shares_mint,
+                owner_shares_account: keys.shares_account(&owner),
+                config,
+            };
+            match view {
+                View::MaxWithdraw(_) => {
+                    crate::instruction(keys.program_id, with_owner, ix::MaxWithdraw {})
+                }
+                _ => crate::instruction(keys.program_id, with_owner, ix::MaxRedeem {}),
+            }
+        }
+    }
+}
+
+/// Decode the return data of a view; the runtime strips trailing zero bytes
+pub fn decode_return_data(data: &[u8]) -> Result<u64> {
+    if data.len() > 8 {
+        return Err(ClientError::Simulation(format!(
+            "expected a u64 return value, got {} bytes",
+            data.len()
+        )));
+    }
+
+    let mut bytes = [0u8; 8];
+    bytes[..data.len()].copy_from_slice(data);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Vault state the views are computed from
+///
+/// The local previews ignore transfer fees; for an SVS-2 vault whose asset
+/// charges one, simulate the view instead (see `rpc::Views`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultSnapshot {
+    /// Assets backing shares (SVS-2 excludes still-locked harvest profit)
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub decimals_offset: u8,
+    /// Whether the vault is paused. The programs' `max_*` views also return
+    /// 0 while the protocol is paused; set this when the config says so.
+    pub paused: bool,
+}
+
+impl VaultSnapshot {
+    /// Snapshot of an SVS-1 vault; its share count is the shares mint supply
+    pub fn from_vault(vault: &Vault, shares_supply: u64) -> Self {
+        Self {
+            total_assets: vault.total_assets,
+            total_shares: shares_supply,
+            decimals_offset: vault.decimals_offset,
+            paused: vault.paused,
+        }
+    }
+
+    /// Snapshot of an SVS-2 vault at unix time `now`; its share count is
+    /// also the shares mint supply, confidential balances included
+    pub fn from_confidential_vault(
+        vault: &ConfidentialVault,
+        shares_supply: u64,
+        now: i64,
+    ) -> Result<Self> {
+        let locked = locked_profit(
+            vault.locked_profit,
+            vault.last_harvest_ts,
+            vault.profit_unlock_period,
+            now,
+        )
+        .map_err(ClientError::Math)?;
+
+        Ok(Self {
+            total_assets: vault.total_assets.saturating_sub(locked),
+            total_shares: shares_supply,
+            decimals_offset: vault.decimals_offset,
+            paused: vault.paused,
+        })
+    }
+
+    fn shares_for(&self, assets: u64, rounding: Rounding) -> Result<u64> {
+        convert_to_shares(
+            assets,
+            self.total_assets,
+            self.total_shares,
+            self.decimals_offset,
+            rounding,
+        )
+        .map_err(ClientError::Math)
+    }
+
+    fn assets_for(&self, shares: u64, rounding: Rounding) -> Result<u64> {
+        convert_to_assets(
+            shares,
+            self.total_assets,
+            self.total_shares,
+            self.decimals_offset,
+            rounding,
+        )
+        .map_err(ClientError::Math)
+    }
+
+    pub fn preview_deposit(&self, assets: u64) -> Result<u64> {
+        self.shares_for(assets, Rounding::Floor)
+    }
+
+    pub fn preview_mint(&self, shares: u64) -> Result<u64> {
+        self.assets_for(shares, Rounding::Ceiling)
+    }
+
+    pub fn preview_withdraw(&self, assets: u64) -> Result<u64> {
+        self.shares_for(assets, Rounding::Ceiling)
+    }
+
+    pub fn preview_redeem(&self, shares: u64) -> Result<u64> {
+        self.assets_for(shares, Rounding::Floor)
+    }
+
+    /// Price of one whole share in whole assets, scaled by `scale` (e.g.
+    /// [`SHARE_PRICE_SCALE`] like the program, or `10^18`)
+    pub fn share_price(&self, scale: u64) -> Result<u64> {
+        share_price(
+            self.total_assets,
+            self.total_shares,
+            self.decimals_offset,
+            scale,
+            Rounding::Floor,
+        )
+        .map_err(ClientError::Math)
+    }
+
+    pub fn max_deposit(&self) -> u64 {
+        if self.paused {
+            0
+        } else {
+            u64::MAX
+        }
+    }
+
+    /// Assets redeemable for `owner_shares` (the owner's public balance)
+    pub fn max_withdraw(&self, owner_shares: u64) -> Result<u64> {
+        if self.paused {
+            return Ok(0);
+        }
+        Ok(self
+            .assets_for(owner_shares, Rounding::Floor)?
+            .min(self.total_assets))
+    }
+
+    pub fn max_redeem(&self, owner_shares: u64) -> u64 {
+        if self.paused {
+            0
+        } else {
+            owner_shares
+        }
+    }
+
+    /// Evaluate `view`; `owner_shares` is only read by the owner views
+    pub fn evaluate(&self, view: &View, owner_shares: u64) -> Result<u64> {
+        match *view {
+            View::PreviewDeposit(assets) => self.preview_deposit(assets),
+            View::PreviewMint(shares) => self.preview_mint(shares),
+            View::PreviewWithdraw(assets) => self.preview_withdraw(assets),
+            View::PreviewRedeem(shares) => self.preview_redeem(shares),
+            View::SaturatingPreviewDeposit(assets) => saturate(self.preview_deposit(assets)),
+            View::SaturatingPreviewMint(shares) => saturate(self.preview_mint(shares)),
+            View::SaturatingPreviewWithdraw(assets) => saturate(self.preview_withdraw(assets)),
+            View::SaturatingPreviewRedeem(shares) => saturate(self.preview_redeem(shares)),
+            View::ConvertToShares(assets) => self.shares_for(assets, Rounding::Floor),
+            View::ConvertToAssets(shares) => self.assets_for(shares, Rounding::Floor),
+            View::SharePrice => self.share_price(SHARE_PRICE_SCALE),
+            View::TotalAssets => Ok(self.total_assets),
+            View::MaxDeposit | View::MaxMint => Ok(self.max_deposit()),
+            View::MaxWithdraw(_) => self.max_withdraw(owner_shares),
+            View::MaxRedeem(_) => Ok(self.max_redeem(owner_shares)),
+        }
+    }
+}
+
+/// `value`, or `u64::MAX` if computing it overflowed, like the saturating views
+fn saturate(value: Result<u64>) -> Result<u64> {
+    match value {
+        Err(ClientError::Math(MathError::Overflow)) => Ok(u64::MAX),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{Discriminator, ToAccountMetas};
+    use anchor_spl::token;
+
+    #[test]
+    fn test_programs_share_view_instructions() {
+        assert_eq!(
+            ix::PreviewDeposit::DISCRIMINATOR,
+            svs_2::instruction::PreviewDeposit::DISCRIMINATOR
+        );
+        assert_eq!(
+            ix::MaxWithdraw::DISCRIMINATOR,
+            svs_2::instruction::MaxWithdraw::DISCRIMINATOR
+        );
+
+        let keys = VaultKeys::derive(&svs_2::ID, &Pubkey::new_unique(), 0, &token::ID);
+        let owner = Pubkey::new_unique();
+        let max = instruction(&keys, &View::MaxWithdraw(owner));
+        assert_eq!(max.program_id, svs_2::ID);
+        assert_eq!(
+            max.accounts,
+            svs_2::accounts::VaultViewWithOwner {
+                vault: keys.vault,
+                shares_mint: keys.shares_mint,
+                owner_shares_account: keys.shares_account(&owner),
+                config: find_config_address(&svs_2::ID).0,
+                asset_mint: keys.asset_mint,
+            }
+            .to_account_metas(None)
+        );
+    }
+
+    #[test]
+    fn test_decode_return_data() {
+        assert_eq!(decode_return_data(&1_000u64.to_le_bytes()).unwrap(), 1_000);
+        // Trailing zeros are stripped by the runtime
+        assert_eq!(decode_return_data(&[0xe8, 0x03]).unwrap(), 1_000);
+        assert_eq!(decode_return_data(&[]).unwrap(), 0);
+        assert!(decode_return_data(&[1; 9]).is_err());
+    }
+
+    #[test]
+    fn test_local_views() {
+        let vault = VaultSnapshot {
+            total_assets: 1_000,
+            total_shares: 1_000_000,
+            decimals_offset: 3,
+            paused: false,
+        };
+
+        assert_eq!(vault.preview_deposit(100).unwrap(), 100_000);
+        assert_eq!(vault.preview_mint(100_000).unwrap(), 100);
+        assert!(vault.preview_withdraw(100).unwrap() >= vault.preview_deposit(100).unwrap());
+        assert_eq!(vault.max_withdraw(u64::MAX).unwrap(), 1_000);
+        assert!(vault.preview_deposit(u64::MAX).is_err());
+        assert_eq!(
+            vault
+                .evaluate(&View::SaturatingPreviewDeposit(u64::MAX), 0)
+                .unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            vault
+                .evaluate(&View::SaturatingPreviewMint(100_000), 0)
+                .unwrap(),
+            100
+        );
+        assert_eq!(
+            vault.evaluate(&View::SharePrice, 0).unwrap(),
+            vault.share_price(SHARE_PRICE_SCALE).unwrap()
+        );
+
+        let paused = VaultSnapshot {
+            paused: true,
+            ..vault
+        };
+        assert_eq!(paused.evaluate(&View::MaxDeposit, 0).unwrap(), 0);
+        assert_eq!(
+            paused
+                .evaluate(&View::MaxRedeem(Pubkey::default()), 50)
+                .unwrap(),
+            0
+        );
+        assert_eq!(paused.evaluate(&View::TotalAssets, 0).unwrap(), 1_000);
+    }
+}
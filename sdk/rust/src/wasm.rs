@@ -0,0 +1,478 @@
+//! WebAssembly bindings
+//!
+//! Exposes the pure parts of the client to JavaScript through wasm-bindgen:
+//! PDA derivation, instruction encoding, view math and the SVS-2 balance
+//! helpers, so browser wallets don't re-implement them. Build with
+//! `--no-default-features --features wasm` for `wasm32-unknown-unknown`.
+//!
+//! Public keys are base58 strings, amounts are `bigint`s. Instructions are
+//! returned as `{ programId, keys: [{ pubkey, isSigner, isWritable }], data }`,
+//! the shape of a web3.js `TransactionInstruction` with string keys.
+
+use std::str::FromStr;
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    confidential::{self, ConfidentialAccountState, ConfidentialKeys},
+    svs1, svs2,
+    svs2::{PubkeyValidityProof, WithdrawProofContexts},
+    view::VaultSnapshot,
+    ClientError,
+};
+
+fn pubkey(value: &str) -> Result<Pubkey, JsError> {
+    Pubkey::from_str(value).map_err(|e| JsError::new(&format!("Invalid public key {value}: {e}")))
+}
+
+fn bytes<const N: usize>(name: &str, value: &[u8]) -> Result<[u8; N], JsError> {
+    value
+        .try_into()
+        .map_err(|_| JsError::new(&format!("{name} must be {N} bytes, got {}", value.len())))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsInstruction {
+    program_id: String,
+    keys: Vec<JsAccountMeta>,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+/// Serialize with `u64`s as `bigint`s
+fn serialize(value: &impl Serialize) -> Result<JsValue, JsError> {
+    let serializer =
+        serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    Ok(value.serialize(&serializer)?)
+}
+
+fn to_js(instruction: Instruction) -> Result<JsValue, JsError> {
+    serialize(&JsInstruction {
+        program_id: instruction.program_id.to_string(),
+        keys: instruction
+            .accounts
+            .into_iter()
+            .map(|meta| JsAccountMeta {
+                pubkey: meta.pubkey.to_string(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: instruction.data,
+    })
+}
+
+/// Accounts of one vault; instruction builders are its methods
+#[wasm_bindgen]
+pub struct VaultKeys(crate::VaultKeys);
+
+#[wasm_bindgen]
+impl VaultKeys {
+    /// Derive the accounts of vault `vault_id` for `asset_mint`
+    pub fn derive(
+        program_id: &str,
+        asset_mint: &str,
+        vault_id: u64,
+        asset_token_program: &str,
+    ) -> Result<VaultKeys, JsError> {
+        Ok(Self(crate::VaultKeys::derive(
+            &pubkey(program_id)?,
+            &pubkey(asset_mint)?,
+            vault_id,
+            &pubkey(asset_token_program)?,
+        )))
+    }
+
+    /// Accounts recorded in a vault account (SVS-1 or SVS-2)
+    #[wasm_bindgen(js_name = fromAccount)]
+    pub fn from_account(
+        program_id: &str,
+        vault: &str,
+        data: &[u8],
+        asset_token_program: &str,
+    ) -> Result<VaultKeys, JsError> {
+        let (program_id, vault) = (pubkey(program_id)?, pubkey(vault)?);
+        let asset_token_program = pubkey(asset_token_program)?;
+
+        let keys = match crate::decode_vault(&vault, data) {
+            Ok(state) => {
+                crate::VaultKeys::from_vault(&program_id, &vault, &state, &asset_token_program)
+            }
+            Err(_) => crate::VaultKeys::from_confidential_vault(
+                &program_id,
+                &vault,
+                &crate::decode_confidential_vault(&vault, data)?,
+                &asset_token_program,
+            ),
+        };
+        Ok(Self(keys))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn vault(&self) -> String {
+        self.0.vault.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = sharesMint)]
+    pub fn shares_mint(&self) -> String {
+        self.0.shares_mint.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = assetVault)]
+    pub fn asset_vault(&self) -> String {
+        self.0.asset_vault.to_string()
+    }
+
+    #[wasm_bindgen(js_name = sharesAccount)]
+    pub fn shares_account(&self, owner: &str) -> Result<String, JsError> {
+        Ok(self.0.shares_account(&pubkey(owner)?).to_string())
+    }
+
+    #[wasm_bindgen(js_name = assetAccount)]
+    pub fn asset_account(&self, owner: &str) -> Result<String, JsError> {
+        Ok(self.0.asset_account(&pubkey(owner)?).to_string())
+    }
+
+    #[wasm_bindgen(js_name = svs1Deposit)]
+    pub fn svs1_deposit(
+        &self,
+        user: &str,
+        assets: u64,
+        min_shares_out: u64,
+    ) -> Result<JsValue, JsError> {
+        to_js(svs1::deposit(
+            &self.0,
+            &pubkey(user)?,
+            assets,
+            min_shares_out,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = svs1Mint)]
+    pub fn svs1_mint(
+        &self,
+        user: &str,
+        shares: u64,
+        max_assets_in: u64,
+    ) -> Result<JsValue, JsError> {
+        to_js(svs1::mint(&self.0, &pubkey(user)?, shares, max_assets_in))
+    }
+
+    #[wasm_bindgen(js_name = svs1Withdraw)]
+    pub fn svs1_withdraw(
+        &self,
+        user: &str,
+        assets: u64,
+        max_shares_in: u64,
+    ) -> Result<JsValue, JsError> {
+        to_js(svs1::withdraw(
+            &self.0,
+            &pubkey(user)?,
+            assets,
+            max_shares_in,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = svs1Redeem)]
+    pub fn svs1_redeem(
+        &self,
+        user: &str,
+        shares: u64,
+        min_assets_out: u64,
+    ) -> Result<JsValue, JsError> {
+        to_js(svs1::redeem(
+            &self.0,
+            &pubkey(user)?,
+            shares,
+            min_assets_out,
+        ))
+    }
+
+    /// `configure_account` verifying the proof `proof_instruction_offset`
+    /// instructions away in the same transaction
+    #[wasm_bindgen(js_name = svs2ConfigureAccount)]
+    pub fn svs2_configure_account(
+        &self,
+        user: &str,
+        decryptable_zero_balance: &[u8],
+        proof_instruction_offset: i8,
+    ) -> Result<JsValue, JsError> {
+        to_js(svs2::configure_account(
+            &self.0,
+            &pubkey(user)?,
+            None,
+            bytes("decryptable_zero_balance", decryptable_zero_balance)?,
+            PubkeyValidityProof::InstructionOffset(proof_instruction_offset),
+        ))
+    }
+
+    #[wasm_bindgen(js_name = svs2Deposit)]
+    pub fn svs2_deposit(
+        &self,
+        user: &str,
+        assets: u64,
+        min_shares_out: u64,
+        memo_hash: Option<Vec<u8>>,
+    ) -> Result<JsValue, JsError> {
+        let memo_hash = memo_hash
+            .map(|hash| bytes("memo_hash", &hash))
+            .transpose()?;
+        to_js(svs2::deposit(
+            &self.0,
+            &pubkey(user)?,
+            assets,
+            min_shares_out,
+            memo_hash,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = svs2ApplyPending)]
+    pub fn svs2_apply_pending(
+        &self,
+        user: &str,
+        new_decryptable_available_balance: &[u8],
+        expected_pending_balance_credit_counter: u64,
+    ) -> Result<JsValue, JsError> {
+        to_js(svs2::apply_pending(
+            &self.0,
+            &pubkey(user)?,
+            bytes(
+                "new_decryptable_available_balance",
+                new_decryptable_available_balance,
+            )?,
+            expected_pending_balance_credit_counter,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = svs2Withdraw)]
+    pub fn svs2_withdraw(
+        &self,
+        user: &str,
+        equality_proof_context: &str,
+        range_proof_context: &str,
+        assets: u64,
+        max_shares_in: u64,
+        new_decryptable_available_balance: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let proofs = WithdrawProofContexts {
+            equality: pubkey(equality_proof_context)?,
+            range: pubkey(range_proof_context)?,
+        };
+        to_js(svs2::withdraw(
+            &self.0,
+            &pubkey(user)?,
+            &proofs,
+            assets,
+            max_shares_in,
+            bytes(
+                "new_decryptable_available_balance",
+                new_decryptable_available_balance,
+            )?,
+            None,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = svs2Redeem)]
+    pub fn svs2_redeem(
+        &self,
+        user: &str,
+        equality_proof_context: &str,
+        range_proof_context: &str,
+        shares: u64,
+        min_assets_out: u64,
+        new_decryptable_available_balance: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let proofs = WithdrawProofContexts {
+            equality: pubkey(equality_proof_context)?,
+            range: pubkey(range_proof_context)?,
+        };
+        to_js(svs2::redeem(
+            &self.0,
+            &pubkey(user)?,
+            &proofs,
+            shares,
+            min_assets_out,
+            bytes(
+                "new_decryptable_available_balance",
+                new_decryptable_available_balance,
+            )?,
+        ))
+    }
+}
+
+/// Vault state for computing views locally
+#[wasm_bindgen(js_name = VaultSnapshot)]
+pub struct JsVaultSnapshot(VaultSnapshot);
+
+#[wasm_bindgen(js_class = VaultSnapshot)]
+impl JsVaultSnapshot {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        total_assets: u64,
+        total_shares: u64,
+        decimals_offset: u8,
+        paused: bool,
+    ) -> JsVaultSnapshot {
+        Self(VaultSnapshot {
+            total_assets,
+            total_shares,
+            decimals_offset,
+            paused,
+        })
+    }
+
+    /// Snapshot of an SVS-1 vault account
+    #[wasm_bindgen(js_name = fromVault)]
+    pub fn from_vault(data: &[u8], shares_supply: u64) -> Result<JsVaultSnapshot, JsError> {
+        let vault = crate::decode_vault(&Pubkey::default(), data)?;
+        Ok(Self(VaultSnapshot::from_vault(&vault, shares_supply)))
+    }
+
+    /// Snapshot of an SVS-2 vault account at unix time `now`; its share
+    /// count is the shares mint supply
+    #[wasm_bindgen(js_name = fromConfidentialVault)]
+    pub fn from_confidential_vault(
+        data: &[u8],
+        shares_supply: u64,
+        now: i64,
+    ) -> Result<JsVaultSnapshot, JsError> {
+        let vault = crate::decode_confidential_vault(&Pubkey::default(), data)?;
+        Ok(Self(VaultSnapshot::from_confidential_vault(
+            &vault,
+            shares_supply,
+            now,
+        )?))
+    }
+
+    #[wasm_bindgen(js_name = previewDeposit)]
+    pub fn preview_deposit(&self, assets: u64) -> Result<u64, JsError> {
+        Ok(self.0.preview_deposit(assets)?)
+    }
+
+    #[wasm_bindgen(js_name = previewMint)]
+    pub fn preview_mint(&self, shares: u64) -> Result<u64, JsError> {
+        Ok(self.0.preview_mint(shares)?)
+    }
+
+    #[wasm_bindgen(js_name = previewWithdraw)]
+    pub fn preview_withdraw(&self, assets: u64) -> Result<u64, JsError> {
+        Ok(self.0.preview_withdraw(assets)?)
+    }
+
+    #[wasm_bindgen(js_name = previewRedeem)]
+    pub fn preview_redeem(&self, shares: u64) -> Result<u64, JsError> {
+        Ok(self.0.preview_redeem(shares)?)
+    }
+
+    #[wasm_bindgen(js_name = maxWithdraw)]
+    pub fn max_withdraw(&self, owner_shares: u64) -> Result<u64, JsError> {
+        Ok(self.0.max_withdraw(owner_shares)?)
+    }
+}
+
+/// Message a wallet signs to derive the ElGamal keypair of `token_account`
+#[wasm_bindgen(js_name = elgamalKeyMessage)]
+pub fn elgamal_key_message(token_account: &str) -> Result<Vec<u8>, JsError> {
+    Ok(confidential::elgamal_key_message(&pubkey(token_account)?))
+}
+
+/// Message a wallet signs to derive the AE key of `token_account`
+#[wasm_bindgen(js_name = aeKeyMessage)]
+pub fn ae_key_message(token_account: &str) -> Result<Vec<u8>, JsError> {
+    Ok(confidential::ae_key_message(&pubkey(token_account)?))
+}
+
+/// Encryption keys of a confidential token account
+#[wasm_bindgen(js_name = ConfidentialKeys)]
+pub struct JsConfidentialKeys(ConfidentialKeys);
+
+#[wasm_bindgen(js_class = ConfidentialKeys)]
+impl JsConfidentialKeys {
+    /// Derive the keys from the wallet's 64-byte signatures of the
+    /// derivation messages
+    #[wasm_bindgen(js_name = fromSignatures)]
+    pub fn from_signatures(
+        elgamal_signature: &[u8],
+        ae_signature: &[u8],
+        token_account: &str,
+    ) -> Result<JsConfidentialKeys, JsError> {
+        let elgamal_signature = bytes::<64>("elgamal_signature", elgamal_signature)?;
+        let ae_signature = bytes::<64>("ae_signature", ae_signature)?;
+
+        Ok(Self(ConfidentialKeys::from_signatures(
+            &elgamal_signature.into(),
+            &ae_signature.into(),
+            &pubkey(token_account)?,
+        )?))
+    }
+
+    /// ElGamal public key (32 bytes)
+    #[wasm_bindgen(js_name = elgamalPubkey)]
+    pub fn elgamal_pubkey(&self) -> Vec<u8> {
+        <[u8; 32]>::from(self.0.elgamal.pubkey()).to_vec()
+    }
+
+    /// Decryptable balance (36 bytes) of `balance`
+    #[wasm_bindgen(js_name = encryptBalance)]
+    pub fn encrypt_balance(&self, balance: u64) -> Vec<u8> {
+        self.0.encrypt_balance(balance).to_vec()
+    }
+
+    #[wasm_bindgen(js_name = decryptBalance)]
+    pub fn decrypt_balance(&self, balance: &[u8]) -> Result<u64, JsError> {
+        Ok(self
+            .0
+            .decrypt_balance(&bytes("decryptable balance", balance)?)?)
+    }
+
+    /// Available balance of a token account's data
+    #[wasm_bindgen(js_name = availableBalance)]
+    pub fn available_balance(&self, token_account_data: &[u8]) -> Result<u64, JsError> {
+        Ok(self
+            .0
+            .available_balance(&parse_state(token_account_data)?)?)
+    }
+
+    /// Pending balance of a token account's data
+    #[wasm_bindgen(js_name = pendingBalance)]
+    pub fn pending_balance(&self, token_account_data: &[u8]) -> Result<u64, JsError> {
+        Ok(self.0.pending_balance(&parse_state(token_account_data)?)?)
+    }
+
+    /// `{ newAvailableBalance, newDecryptableAvailableBalance,
+    /// expectedPendingBalanceCreditCounter }` for `apply_pending`
+    #[wasm_bindgen(js_name = applyPending)]
+    pub fn apply_pending(&self, token_account_data: &[u8]) -> Result<JsValue, JsError> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Applied {
+            new_available_balance: u64,
+            #[serde(with = "serde_bytes")]
+            new_decryptable_available_balance: Vec<u8>,
+            expected_pending_balance_credit_counter: u64,
+        }
+
+        let applied = self.0.apply_pending(&parse_state(token_account_data)?)?;
+        serialize(&Applied {
+            new_available_balance: applied.new_available_balance,
+            new_decryptable_available_balance: applied.new_decryptable_available_balance.to_vec(),
+            expected_pending_balance_credit_counter: applied
+                .expected_pending_balance_credit_counter,
+        })
+    }
+}
+
+fn parse_state(data: &[u8]) -> Result<ConfidentialAccountState, ClientError> {
+    ConfidentialAccountState::parse(&Pubkey::default(), data)
+}
@@ -0,0 +1,527 @@
+//! Orchestrated SVS-2 withdrawals
+//!
+//! A confidential withdraw or redeem is verified from two proof context
+//! accounts, which do not fit in one transaction with the vault instruction.
+//! [`ConfidentialWithdrawFlow`] reads the user's shares account, generates the
+//! proofs in-process or with the proof backend, and returns the signed
+//! transactions. They must be confirmed one after another:
+//!
+//! 1. Create both context accounts and verify the equality proof
+//! 2. Verify the range proof
+//! 3. `withdraw`/`redeem`, then close both context accounts, returning their
+//!    rent to the fee payer
+//!
+//! With [`ConfidentialWithdrawFlow::with_lookup_tables`], e.g. the vault's
+//! table from [`crate::lookup_table`], the transactions are compiled as v0
+//! and look up the vault's accounts.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bytemuck::Pod;
+use serde::Deserialize;
+use serde_json::json;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, hash::Hash, instruction::Instruction,
+    message::AddressLookupTableAccount, signature::Keypair, signer::Signer,
+    transaction::VersionedTransaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    instruction::{close_context_state, ContextStateInfo, ProofInstruction},
+    proof_data::{
+        BatchedRangeProofContext, BatchedRangeProofU64Data,
+        CiphertextCommitmentEqualityProofContext, CiphertextCommitmentEqualityProofData,
+    },
+    state::ProofContextState,
+};
+use svs_proof_core::{ProofError, ProofGenerator};
+
+use crate::{
+    confidential::{
+        ae_key_message, elgamal_key_message, ConfidentialAccountState, ConfidentialKeys,
+    },
+    lookup_table::versioned_transaction,
+    rpc::nonblocking::{fetch_lookup_tables, Views},
+    svs2::{self, WithdrawProofContexts},
+    ClientError, Result, VaultKeys,
+};
+
+/// Proof backend endpoint generating both withdraw proofs
+const WITHDRAW_BUNDLE_PATH: &str = "/v1/proofs/withdraw-bundle";
+
+/// Sizes of the equality and range proof context accounts
+const CONTEXT_SPACE: [usize; 2] = [
+    std::mem::size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+    std::mem::size_of::<ProofContextState<BatchedRangeProofContext>>(),
+];
+
+/// Where the flow gets its proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofSource {
+    /// Generate them in this process, on a blocking thread
+    InProcess,
+    /// Request them from a proof backend, e.g. `https://proofs.example.com`
+    ///
+    /// The key derivation signatures are sent unsealed, so only use a
+    /// backend you trust, over TLS.
+    Backend {
+        url: String,
+        api_key: Option<String>,
+    },
+}
+
+/// Vault instruction burning the proven shares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Withdrawal {
+    /// Withdraw exactly `assets`; the shares to burn are previewed when the
+    /// flow runs, so the withdraw fails if the share price moves before it
+    /// executes
+    Withdraw {
+        assets: u64,
+        memo_hash: Option<[u8; 32]>,
+    },
+    /// Redeem `shares` for at least `min_assets_out` assets
+    Redeem { shares: u64, min_assets_out: u64 },
+}
+
+/// Signed transactions of a confidential withdrawal, in submission order
+pub struct WithdrawTransactions {
+    /// v0 transactions when the flow has lookup tables, legacy otherwise
+    pub transactions: Vec<VersionedTransaction>,
+    pub proof_contexts: WithdrawProofContexts,
+    /// Shares burned by the withdrawal
+    pub shares: u64,
+}
+
+/// Proofs of one withdrawal, however they were generated
+struct Proofs {
+    equality: CiphertextCommitmentEqualityProofData,
+    range: BatchedRangeProofU64Data,
+    new_decryptable_available_balance: [u8; 36],
+}
+
+#[derive(Deserialize)]
+struct WithdrawBundleResponse {
+    equality_proof_data: String,
+    range_proof_data: String,
+    new_decryptable_available_balance: String,
+}
+
+/// Confidential withdrawals from one SVS-2 vault
+pub struct ConfidentialWithdrawFlow<'a> {
+    client: &'a RpcClient,
+    keys: VaultKeys,
+    proofs: ProofSource,
+    compute_unit_price: Option<u64>,
+    lookup_tables: Vec<Pubkey>,
+    http: reqwest::Client,
+}
+
+impl<'a> ConfidentialWithdrawFlow<'a> {
+    pub fn new(client: &'a RpcClient, keys: VaultKeys, proofs: ProofSource) -> Self {
+        Self {
+            client,
+            keys,
+            proofs,
+            compute_unit_price: None,
+            lookup_tables: Vec::new(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Pay a priority fee of `micro_lamports` per compute unit on every
+    /// transaction
+    pub fn with_compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// Compile v0 transactions looking up accounts in `tables`, which are
+    /// fetched on every run
+    pub fn with_lookup_tables(mut self, tables: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.lookup_tables.extend(tables);
+        self
+    }
+
+    /// Build and sign the transactions of `withdrawal` from `user`'s shares
+    ///
+    /// `fee_payer` pays the fees and the context accounts' rent; pass the user
+    /// to pay them yourself. Pending shares must be applied beforehand.
+    pub async fn run<U, P>(
+        &self,
+        user: &U,
+        fee_payer: &P,
+        withdrawal: Withdrawal,
+    ) -> Result<WithdrawTransactions>
+    where
+        U: Signer,
+        P: Signer,
+    {
+        let user_pubkey = user.pubkey();
+        let shares_account = self.keys.shares_account(&user_pubkey);
+        let data = self
+            .client
+            .get_account_with_commitment(&shares_account, self.client.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(shares_account))?
+            .data;
+        let state = ConfidentialAccountState::parse(&shares_account, &data)?;
+
+        let shares = match withdrawal {
+            Withdrawal::Withdraw { assets, .. } => {
+                Views::new(self.client, self.keys, fee_payer.pubkey())
+                    .preview_withdraw(assets)
+                    .await?
+            }
+            Withdrawal::Redeem { shares, .. } => shares,
+        };
+
+        let proofs = match &self.proofs {
+            ProofSource::InProcess => {
+                let keys = ConfidentialKeys::derive(user, &shares_account)?;
+                tokio::task::spawn_blocking(move || {
+                    ProofGenerator::generate_withdraw_proof(
+                        &keys.elgamal,
+                        &keys.ae,
+                        &state.available_balance,
+                        &state.decryptable_available_balance,
+                        shares,
+                    )
+                })
+                .await
+                .map_err(|e| ProofError::ProofGeneration(format!("Proof task failed: {e}")))??
+                .into()
+            }
+            ProofSource::Backend { url, api_key } => {
+                self.backend_proofs(
+                    url,
+                    api_key.as_deref(),
+                    user,
+                    &shares_account,
+                    &state,
+                    shares,
+                )
+                .await?
+            }
+        };
+
+        let mut rent = [0; 2];
+        for (lamports, space) in rent.iter_mut().zip(CONTEXT_SPACE) {
+            *lamports = self
+                .client
+                .get_minimum_balance_for_rent_exemption(space)
+                .await?;
+        }
+        let lookup_tables = match self.lookup_tables.as_slice() {
+            [] => Vec::new(),
+            tables => fetch_lookup_tables(self.client, tables).await?,
+        };
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+
+        self.sign_transactions(
+            user,
+            fee_payer,
+            withdrawal,
+            shares,
+            &proofs,
+            rent,
+            &lookup_tables,
+            recent_blockhash,
+        )
+    }
+
+    /// Have the proof backend generate both proofs
+    async fn backend_proofs(
+        &self,
+        url: &str,
+        api_key: Option<&str>,
+        user: &impl Signer,
+        shares_account: &Pubkey,
+        state: &ConfidentialAccountState,
+        shares: u64,
+    ) -> Result<Proofs> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| ClientError::Backend(e.to_string()))?
+            .as_secs() as i64;
+        let request_message =
+            ProofGenerator::construct_request_message(timestamp, shares_account, None);
+        let sign = |message: &[u8]| -> Result<String> {
+            Ok(BASE64.encode(user.try_sign_message(message)?))
+        };
+
+        let body = json!({
+            "wallet_pubkey": user.pubkey().to_string(),
+            "token_account": shares_account.to_string(),
+            "timestamp": timestamp,
+            "request_signature": sign(request_message.as_bytes())?,
+            "elgamal_signature": sign(&elgamal_key_message(shares_account))?,
+            "ae_signature": sign(&ae_key_message(shares_account))?,
+            "current_ciphertext": BASE64.encode(state.available_balance.to_bytes()),
+            "current_decryptable_balance":
+                BASE64.encode(state.decryptable_available_balance.to_bytes()),
+            "amount": shares.to_string(),
+        });
+
+        let mut request = self
+            .http
+            .post(format!(
+                "{}{WITHDRAW_BUNDLE_PATH}",
+                url.trim_end_matches('/')
+            ))
+            .json(&body);
+        if let Some(api_key) = api_key {
+            request = request.header("x-api-key", api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ClientError::Backend(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(ClientError::Backend(format!("{status}: {text}")));
+        }
+        let bundle: WithdrawBundleResponse = response
+            .json()
+            .await
+            .map_err(|e| ClientError::Backend(e.to_string()))?;
+
+        Ok(Proofs {
+            equality: decode_pod(&bundle.equality_proof_data)?,
+            range: decode_pod(&bundle.range_proof_data)?,
+            new_decryptable_available_balance: decode_pod(
+                &bundle.new_decryptable_available_balance,
+            )?,
+        })
+    }
+
+    /// Assemble and sign the transactions; `rent` funds the equality and
+    /// range context accounts
+    #[allow(clippy::too_many_arguments)]
+    fn sign_transactions(
+        &self,
+        user: &impl Signer,
+        fee_payer: &impl Signer,
+        withdrawal: Withdrawal,
+        shares: u64,
+        proofs: &Proofs,
+        rent: [u64; 2],
+        lookup_tables: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<WithdrawTransactions> {
+        let payer = fee_payer.pubkey();
+        let user_pubkey = user.pubkey();
+        let equality_context = Keypair::new();
+        let range_context = Keypair::new();
+        let equality_info = ContextStateInfo {
+            context_state_account: &equality_context.pubkey(),
+            context_state_authority: &user_pubkey,
+        };
+        let range_info = ContextStateInfo {
+            context_state_account: &range_context.pubkey(),
+            context_state_authority: &user_pubkey,
+        };
+        let create = |account: &Pubkey, index: usize| {
+            system_instruction::create_account(
+                &payer,
+                account,
+                rent[index],
+                CONTEXT_SPACE[index] as u64,
+                &zk_elgamal_proof_program::id(),
+            )
+        };
+
+        let setup = [
+            create(&equality_context.pubkey(), 0),
+            create(&range_context.pubkey(), 1),
+            ProofInstruction::VerifyCiphertextCommitmentEquality
+                .encode_verify_proof(Some(equality_info), &proofs.equality),
+        ];
+        let verify_range = [ProofInstruction::VerifyBatchedRangeProofU64
+            .encode_verify_proof(Some(range_info), &proofs.range)];
+
+        let proof_contexts = WithdrawProofContexts {
+            equality: equality_context.pubkey(),
+            range: range_context.pubkey(),
+        };
+        let vault_instruction = match withdrawal {
+            Withdrawal::Withdraw { assets, memo_hash } => svs2::withdraw(
+                &self.keys,
+                &user_pubkey,
+                &proof_contexts,
+                assets,
+                // The proofs are bound to exactly `shares`, so any other burn fails anyway
+                shares,
+                proofs.new_decryptable_available_balance,
+                memo_hash,
+            ),
+            Withdrawal::Redeem { min_assets_out, .. } => svs2::redeem(
+                &self.keys,
+                &user_pubkey,
+                &proof_contexts,
+                shares,
+                min_assets_out,
+                proofs.new_decryptable_available_balance,
+            ),
+        };
+        let execute = [
+            vault_instruction,
+            close_context_state(equality_info, &payer),
+            close_context_state(range_info, &payer),
+        ];
+
+        let priority_fee = self
+            .compute_unit_price
+            .map(ComputeBudgetInstruction::set_compute_unit_price);
+        let sign = |instructions: &[Instruction], signers: &[&dyn Signer]| -> Result<_> {
+            let instructions: Vec<_> = priority_fee.iter().chain(instructions).cloned().collect();
+            versioned_transaction(
+                &payer,
+                &instructions,
+                lookup_tables,
+                recent_blockhash,
+                signers,
+            )
+        };
+
+        Ok(WithdrawTransactions {
+            transactions: vec![
+                sign(
+                    &setup,
+                    &[fee_payer as &dyn Signer, &equality_context, &range_context],
+                )?,
+                sign(&verify_range, &[fee_payer as &dyn Signer])?,
+                sign(&execute, &[fee_payer as &dyn Signer, user])?,
+            ],
+            proof_contexts,
+            shares,
+        })
+    }
+}
+
+impl From<svs_proof_core::WithdrawProofs> for Proofs {
+    fn from(proofs: svs_proof_core::WithdrawProofs) -> Self {
+        Self {
+            equality: proofs.equality_proof_data,
+            range: proofs.range_proof_data,
+            new_decryptable_available_balance: proofs.new_decryptable_available_balance,
+        }
+    }
+}
+
+/// Decode a base64 field of a backend response into `T`
+fn decode_pod<T: Pod>(value: &str) -> Result<T> {
+    let bytes = BASE64
+        .decode(value)
+        .map_err(|e| ClientError::Backend(format!("Invalid proof data: {e}")))?;
+    bytemuck::try_pod_read_unaligned(&bytes)
+        .map_err(|e| ClientError::Backend(format!("Invalid proof data: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lookup_table::vault_addresses;
+    use anchor_spl::token;
+    use solana_sdk::message::VersionedMessage;
+
+    fn vault() -> VaultKeys {
+        VaultKeys::derive(&svs_2::ID, &Pubkey::new_unique(), 0, &token::ID)
+    }
+
+    /// Redeem of 400 shares by a new user, relayed by a new fee payer
+    fn signed_transactions(
+        vault: VaultKeys,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> (Keypair, Keypair, WithdrawTransactions) {
+        let client = RpcClient::new("http://localhost:8899".to_string());
+        let flow = ConfidentialWithdrawFlow::new(&client, vault, ProofSource::InProcess);
+        let user = Keypair::new();
+        let relayer = Keypair::new();
+
+        let keys = ConfidentialKeys::derive(&user, &vault.shares_account(&user.pubkey())).unwrap();
+        let proofs = ProofGenerator::generate_withdraw_proof(
+            &keys.elgamal,
+            &keys.ae,
+            &keys.elgamal.pubkey().encrypt(1_000u64),
+            &keys.ae.encrypt(1_000),
+            400,
+        )
+        .unwrap()
+        .into();
+
+        let built = flow
+            .sign_transactions(
+                &user,
+                &relayer,
+                Withdrawal::Redeem {
+                    shares: 400,
+                    min_assets_out: 1,
+                },
+                400,
+                &proofs,
+                [1_000_000; 2],
+                lookup_tables,
+                Hash::new_unique(),
+            )
+            .unwrap();
+        (user, relayer, built)
+    }
+
+    #[test]
+    fn test_signed_transactions() {
+        let (user, relayer, built) = signed_transactions(vault(), &[]);
+
+        assert_eq!(built.transactions.len(), 3);
+        for transaction in &built.transactions {
+            assert!(matches!(transaction.message, VersionedMessage::Legacy(_)));
+            assert_eq!(
+                transaction.message.static_account_keys()[0],
+                relayer.pubkey()
+            );
+            transaction.verify_and_hash_message().unwrap();
+        }
+
+        // The vault instruction reads both context accounts, which are then closed
+        let execute = &built.transactions[2];
+        let account_keys = execute.message.static_account_keys();
+        assert_eq!(execute.message.instructions().len(), 3);
+        assert!(account_keys.contains(&built.proof_contexts.equality));
+        assert!(account_keys.contains(&built.proof_contexts.range));
+        assert!(account_keys.contains(&user.pubkey()));
+    }
+
+    #[test]
+    fn test_signed_transactions_with_lookup_table() {
+        let vault = vault();
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vault_addresses(&vault),
+        };
+        let (_, _, legacy) = signed_transactions(vault, &[]);
+        let (user, relayer, built) = signed_transactions(vault, std::slice::from_ref(&table));
+
+        for transaction in &built.transactions {
+            transaction.verify_and_hash_message().unwrap();
+        }
+        let execute = &built.transactions[2];
+        let VersionedMessage::V0(message) = &execute.message else {
+            panic!("not a v0 message");
+        };
+        assert_eq!(message.address_table_lookups[0].account_key, table.key);
+        assert!(!message.account_keys.contains(&vault.shares_mint));
+        // Signers and per-user accounts stay static
+        assert!(message.account_keys.contains(&user.pubkey()));
+        assert_eq!(message.account_keys[0], relayer.pubkey());
+        assert!(
+            execute.message.serialize().len() < legacy.transactions[2].message.serialize().len()
+        );
+    }
+}
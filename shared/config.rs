@@ -0,0 +1,158 @@
+//! Protocol config instructions, included by both vault programs
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::CONFIG_SEED,
+    error::VaultError,
+    events::{ProtocolConfigUpdated, ProtocolStatusChanged},
+    state::ProtocolConfig,
+    VaultProgram,
+};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// Upgrade authority of the program, becomes the config authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ProtocolConfig::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, VaultProgram>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ VaultError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        constraint = authority.key() == config.authority @ VaultError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+/// Pause and resume are the only config changes the guardian may make
+#[derive(Accounts)]
+pub struct ProtocolGuardian<'info> {
+    #[account(
+        constraint = authority.key() == config.authority
+            || authority.key() == config.guardian @ VaultError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+/// Create the protocol config, replacing the built-in defaults for new vaults
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    min_deposit_amount: u64,
+    max_decimals: u8,
+    shares_decimals: u8,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.authority = ctx.accounts.authority.key();
+    config.bump = ctx.bumps.config;
+    config.guardian = Pubkey::default();
+    config.paused = false;
+    config.event_sequence = 0;
+    config._reserved = [0u8; 23];
+    config.set_params(min_deposit_amount, max_decimals, shares_decimals)?;
+    emit_config_updated(config)
+}
+
+/// Change the parameters of vaults created from now on
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    min_deposit_amount: u64,
+    max_decimals: u8,
+    shares_decimals: u8,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.set_params(min_deposit_amount, max_decimals, shares_decimals)?;
+    emit_config_updated(config)
+}
+
+/// Hand the protocol config to a new authority
+pub fn transfer_config_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.authority = new_authority;
+    emit_config_updated(config)
+}
+
+/// Set or clear (Pubkey::default()) the protocol guardian
+pub fn set_protocol_guardian(ctx: Context<UpdateConfig>, new_guardian: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.guardian = new_guardian;
+    emit_config_updated(config)
+}
+
+/// Halt the user-facing instructions of every vault of the program
+pub fn pause_protocol(ctx: Context<ProtocolGuardian>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(!config.paused, VaultError::AlreadyPaused);
+    config.paused = true;
+    emit_status_changed(config, ctx.accounts.authority.key())
+}
+
+/// Lift the protocol-wide pause; vaults paused individually stay paused
+pub fn unpause_protocol(ctx: Context<ProtocolGuardian>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(config.paused, VaultError::ProtocolNotPaused);
+    config.paused = false;
+    emit_status_changed(config, ctx.accounts.authority.key())
+}
+
+/// Publish the config as it now stands
+fn emit_config_updated(config: &mut Account<ProtocolConfig>) -> Result<()> {
+    let (sequence, timestamp) = config.stamp_event()?;
+    emit!(ProtocolConfigUpdated {
+        config: config.key(),
+        authority: config.authority,
+        guardian: config.guardian,
+        min_deposit_amount: config.min_deposit_amount,
+        max_decimals: config.max_decimals,
+        shares_decimals: config.shares_decimals,
+        sequence,
+        timestamp,
+    });
+    Ok(())
+}
+
+/// Publish a protocol pause or resume by `caller`
+fn emit_status_changed(config: &mut Account<ProtocolConfig>, caller: Pubkey) -> Result<()> {
+    let (sequence, timestamp) = config.stamp_event()?;
+    emit!(ProtocolStatusChanged {
+        config: config.key(),
+        caller,
+        paused: config.paused,
+        sequence,
+        timestamp,
+    });
+    Ok(())
+}
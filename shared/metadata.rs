@@ -0,0 +1,49 @@
+//! Vault metadata checks, included by both vault programs
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{
+    constants::{MAX_NAME_LEN, MAX_SYMBOL_LEN, MAX_URI_LEN},
+    error::VaultError,
+};
+
+/// Require that the vault metadata given at initialize is within bounds
+///
+/// Strings that are not UTF-8 already fail instruction deserialization. On
+/// top of that the name must be non-empty and free of control characters,
+/// the symbol ASCII alphanumeric, and the uri free of whitespace and control
+/// characters (it may be empty).
+pub fn validate_metadata(name: &str, symbol: &str, uri: &str) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_NAME_LEN && !name.chars().any(char::is_control),
+        VaultError::InvalidVaultName
+    );
+    require!(
+        !symbol.is_empty()
+            && symbol.len() <= MAX_SYMBOL_LEN
+            && symbol.bytes().all(|b| b.is_ascii_alphanumeric()),
+        VaultError::InvalidVaultSymbol
+    );
+    require!(
+        uri.len() <= MAX_URI_LEN && !uri.chars().any(|c| c.is_control() || c.is_whitespace()),
+        VaultError::InvalidVaultUri
+    );
+    Ok(())
+}
+
+/// Hash stored in the vault, against which off-chain metadata is verified
+///
+/// sha256 of the Borsh encoding of (name, symbol, uri): each string is
+/// prefixed with its u32 little-endian byte length.
+pub fn metadata_hash(name: &str, symbol: &str, uri: &str) -> [u8; 32] {
+    hashv(&[
+        &(name.len() as u32).to_le_bytes(),
+        name.as_bytes(),
+        &(symbol.len() as u32).to_le_bytes(),
+        symbol.as_bytes(),
+        &(uri.len() as u32).to_le_bytes(),
+        uri.as_bytes(),
+    ])
+    .to_bytes()
+}
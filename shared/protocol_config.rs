@@ -0,0 +1,114 @@
+//! Protocol config account, included by both vault programs through
+//! their `state` modules
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{
+        CONFIG_SEED, MAX_DECIMALS, MAX_SHARES_DECIMALS, MIN_DEPOSIT_AMOUNT, SHARES_DECIMALS,
+    },
+    error::VaultError,
+};
+
+/// Protocol-wide parameters of new vaults, one per program
+#[account]
+pub struct ProtocolConfig {
+    /// Key allowed to update the config; the upgrade authority at creation
+    pub authority: Pubkey,
+    /// Minimum deposit new vaults start with, and the floor of their overrides
+    pub min_deposit_amount: u64,
+    /// Largest asset decimals a new vault accepts
+    pub max_decimals: u8,
+    /// Decimals of new shares mints; the decimals offset is
+    /// `shares_decimals - asset_decimals`
+    pub shares_decimals: u8,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Key that may pause and resume the whole protocol besides the
+    /// authority, e.g. a multisig; Pubkey::default() if none
+    pub guardian: Pubkey,
+    /// Protocol-wide pause: user-facing instructions of every vault fail
+    pub paused: bool,
+    /// Sequence number of the last emitted config event (0 before the first)
+    pub event_sequence: u64,
+    /// Reserved for future upgrades
+    pub _reserved: [u8; 23],
+}
+
+impl ProtocolConfig {
+    pub const LEN: usize = 8 +  // discriminator
+        32 +  // authority
+        8 +   // min_deposit_amount
+        1 +   // max_decimals
+        1 +   // shares_decimals
+        1 +   // bump
+        32 +  // guardian
+        1 +   // paused
+        8 +   // event_sequence
+        23; // _reserved
+
+    pub const SEED_PREFIX: &'static [u8] = CONFIG_SEED;
+
+    /// Built-in parameters, in effect while no config account exists
+    pub fn defaults() -> Self {
+        Self {
+            authority: Pubkey::default(),
+            min_deposit_amount: MIN_DEPOSIT_AMOUNT,
+            max_decimals: MAX_DECIMALS,
+            shares_decimals: SHARES_DECIMALS,
+            bump: 0,
+            guardian: Pubkey::default(),
+            paused: false,
+            event_sequence: 0,
+            _reserved: [0u8; 23],
+        }
+    }
+
+    /// The config stored in `account`, the config PDA, or the defaults if it
+    /// has not been created
+    pub fn load_or_default(account: &AccountInfo) -> Result<Self> {
+        if account.data_is_empty() {
+            return Ok(Self::defaults());
+        }
+        require_keys_eq!(*account.owner, crate::ID, VaultError::InvalidConfig);
+        Self::try_deserialize(&mut &account.try_borrow_data()?[..])
+    }
+
+    /// Fail with `ProtocolPaused` if `account`, the config PDA, has the
+    /// protocol paused
+    pub fn require_not_paused(account: &AccountInfo) -> Result<()> {
+        require!(
+            !Self::load_or_default(account)?.paused,
+            VaultError::ProtocolPaused
+        );
+        Ok(())
+    }
+
+    /// Sequence number and timestamp for the next emitted config event
+    pub fn stamp_event(&mut self) -> Result<(u64, i64)> {
+        self.event_sequence = self
+            .event_sequence
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok((self.event_sequence, Clock::get()?.unix_timestamp))
+    }
+
+    /// Set the parameters, rejecting combinations no vault could use
+    pub fn set_params(
+        &mut self,
+        min_deposit_amount: u64,
+        max_decimals: u8,
+        shares_decimals: u8,
+    ) -> Result<()> {
+        require!(
+            min_deposit_amount > 0
+                && max_decimals <= shares_decimals
+                && shares_decimals <= MAX_SHARES_DECIMALS,
+            VaultError::InvalidConfig
+        );
+        self.min_deposit_amount = min_deposit_amount;
+        self.max_decimals = max_decimals;
+        self.shares_decimals = shares_decimals;
+        Ok(())
+    }
+}
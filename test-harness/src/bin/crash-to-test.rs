@@ -0,0 +1,78 @@
+//! crash-to-test
+//!
+//! Turn a Trident crash artifact from `trident-tests/crashes/` into a
+//! regression test at `tests/regression_<name>.rs` of this crate. See
+//! [`svs_test_harness::crash`].
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use svs_test_harness::crash::{test_name, CrashArtifact};
+
+const USAGE: &str = "\
+Usage: crash-to-test <artifact> [--name <name>]
+
+Writes tests/regression_<name>.rs replaying the crash against SVS-1. The name
+defaults to the artifact's file name, e.g. 1700000000_42 for
+1700000000-42.crash.
+";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print!("{USAGE}");
+        return;
+    }
+
+    match run(&args) {
+        Ok(path) => println!("Wrote {}", path.display()),
+        Err(e) => {
+            eprintln!("Error: {e}\n\n{USAGE}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<PathBuf, String> {
+    let (artifact_path, name) = match args {
+        [path] => (Path::new(path), None),
+        [path, flag, name] if flag == "--name" => (Path::new(path), Some(name.as_str())),
+        _ => return Err("unexpected arguments".to_string()),
+    };
+
+    let artifact: CrashArtifact = fs::read_to_string(artifact_path)
+        .map_err(|e| format!("reading {}: {e}", artifact_path.display()))?
+        .parse()
+        .map_err(|e| format!("{}: {e}", artifact_path.display()))?;
+    let file_name = artifact_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = artifact_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = test_name(name.unwrap_or(&stem));
+    if name.is_empty() {
+        return Err("the test needs a name, pass --name".to_string());
+    }
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(format!("regression_{name}.rs"));
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    fs::write(&path, artifact.render_test(&name, &file_name))
+        .map_err(|e| format!("writing {}: {e}", path.display()))?;
+
+    // Long amounts overflow rustfmt's call width; formatting is best effort
+    let _ = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .arg(&path)
+        .status();
+    Ok(path)
+}
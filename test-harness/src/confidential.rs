@@ -0,0 +1,280 @@
+//! SVS-2 users with confidential shares accounts
+//!
+//! [`Harness::confidential_user`] goes through the same steps as a wallet:
+//! create the shares ATA, derive the encryption keys from signatures, and
+//! call `configure_account` after an inline pubkey validity proof. Withdraw
+//! and redeem proofs are verified into context accounts one transaction per
+//! proof, as in `svs_client::withdraw_flow`.
+
+use anchor_spl::token_2022;
+use litesvm::types::TransactionResult;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+    signature::Keypair, signer::Signer,
+};
+use solana_system_interface::instruction as system_instruction;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    instruction::{close_context_state, ContextStateInfo, ProofInstruction},
+    proof_data::{
+        BatchedRangeProofContext, CiphertextCommitmentEqualityProofContext, PubkeyValidityProofData,
+    },
+    state::ProofContextState,
+};
+use svs_client::{
+    confidential::{ConfidentialAccountState, ConfidentialKeys},
+    svs2::{self, DecryptableBalance, PubkeyValidityProof, WithdrawProofContexts},
+    VaultKeys,
+};
+use svs_proof_core::ProofGenerator;
+
+use crate::Harness;
+
+/// Compute limit of transactions verifying proofs or running confidential
+/// Token-2022 instructions
+pub const CONFIDENTIAL_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Sizes of the equality and range proof context accounts
+const CONTEXT_SPACE: [usize; 2] = [
+    std::mem::size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+    std::mem::size_of::<ProofContextState<BatchedRangeProofContext>>(),
+];
+
+/// A wallet with a configured confidential shares account of one vault
+pub struct ConfidentialUser {
+    pub wallet: Keypair,
+    pub keys: ConfidentialKeys,
+    /// The wallet's shares ATA
+    pub shares_account: Pubkey,
+}
+
+/// Verified proofs of a withdraw or redeem of a fixed number of shares
+pub struct ProvenWithdraw {
+    pub contexts: WithdrawProofContexts,
+    pub new_decryptable_available_balance: DecryptableBalance,
+}
+
+impl ProvenWithdraw {
+    /// Close both context accounts, returning their rent to `destination`;
+    /// signed by the user
+    pub fn close_instructions(&self, user: &Pubkey, destination: &Pubkey) -> [Instruction; 2] {
+        [self.contexts.equality, self.contexts.range]
+            .map(|context| close_context_state(context_info(&context, user), destination))
+    }
+}
+
+impl Harness {
+    /// Depositor holding `assets` whose shares account is configured for
+    /// confidential transfers
+    pub fn confidential_user(&mut self, keys: &VaultKeys, assets: u64) -> ConfidentialUser {
+        let wallet = self.depositor(keys, assets);
+        let shares_account = self.create_ata(&wallet.pubkey(), &keys.shares_mint, &token_2022::ID);
+        let confidential_keys =
+            ConfidentialKeys::derive(&wallet, &shares_account).expect("derive confidential keys");
+
+        let proof = PubkeyValidityProofData::new(&confidential_keys.elgamal)
+            .expect("pubkey validity proof");
+        let instructions = [
+            ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof),
+            svs2::configure_account(
+                keys,
+                &wallet.pubkey(),
+                None,
+                confidential_keys.encrypt_balance(0),
+                PubkeyValidityProof::InstructionOffset(-1),
+            ),
+        ];
+        self.send_ok(&instructions, &[&wallet]);
+
+        ConfidentialUser {
+            wallet,
+            keys: confidential_keys,
+            shares_account,
+        }
+    }
+}
+
+impl ConfidentialUser {
+    pub fn pubkey(&self) -> Pubkey {
+        self.wallet.pubkey()
+    }
+
+    pub fn state(&self, h: &Harness) -> ConfidentialAccountState {
+        let data = h.account_data(&self.shares_account);
+        ConfidentialAccountState::parse(&self.shares_account, &data).expect("confidential account")
+    }
+
+    /// Decrypted available shares
+    pub fn available_shares(&self, h: &Harness) -> u64 {
+        self.keys
+            .available_balance(&self.state(h))
+            .expect("decrypt available balance")
+    }
+
+    /// Decrypted pending shares
+    pub fn pending_shares(&self, h: &Harness) -> u64 {
+        self.keys
+            .pending_balance(&self.state(h))
+            .expect("decrypt pending balance")
+    }
+
+    /// Confidential deposit of `assets`, with the shares left pending
+    pub fn deposit(
+        &self,
+        h: &mut Harness,
+        keys: &VaultKeys,
+        assets: u64,
+        min_shares_out: u64,
+    ) -> TransactionResult {
+        let deposit = svs2::deposit(keys, &self.pubkey(), assets, min_shares_out, None);
+        h.send(&[compute_limit(), deposit], &[&self.wallet])
+    }
+
+    /// Confidential mint of exactly `shares`, left pending
+    pub fn mint(
+        &self,
+        h: &mut Harness,
+        keys: &VaultKeys,
+        shares: u64,
+        max_assets_in: u64,
+    ) -> TransactionResult {
+        let mint = svs2::mint(keys, &self.pubkey(), shares, max_assets_in);
+        h.send(&[compute_limit(), mint], &[&self.wallet])
+    }
+
+    /// Move the pending shares into the available balance
+    pub fn apply_pending(&self, h: &mut Harness, keys: &VaultKeys) {
+        let application = self
+            .keys
+            .apply_pending(&self.state(h))
+            .expect("apply pending balance");
+        let instruction = svs2::apply_pending(
+            keys,
+            &self.pubkey(),
+            application.new_decryptable_available_balance,
+            application.expected_pending_balance_credit_counter,
+        );
+        h.send_ok(&[compute_limit(), instruction], &[&self.wallet]);
+    }
+
+    /// Generate and verify the proofs for burning `shares` from the available
+    /// balance
+    pub fn prove_withdraw(&self, h: &mut Harness, shares: u64) -> ProvenWithdraw {
+        let state = self.state(h);
+        let proofs = ProofGenerator::generate_withdraw_proof(
+            &self.keys.elgamal,
+            &self.keys.ae,
+            &state.available_balance,
+            &state.decryptable_available_balance,
+            shares,
+        )
+        .expect("withdraw proofs");
+
+        let user = self.pubkey();
+        let payer = h.payer.pubkey();
+        let rent = CONTEXT_SPACE.map(|space| h.svm.minimum_balance_for_rent_exemption(space));
+        let equality = Keypair::new();
+        let range = Keypair::new();
+        let (equality_account, range_account) = (equality.pubkey(), range.pubkey());
+        let create = |account: &Pubkey, index: usize| {
+            system_instruction::create_account(
+                &payer,
+                account,
+                rent[index],
+                CONTEXT_SPACE[index] as u64,
+                &zk_elgamal_proof_program::id(),
+            )
+        };
+
+        let verify_equality = [
+            compute_limit(),
+            create(&equality_account, 0),
+            create(&range_account, 1),
+            ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                Some(context_info(&equality_account, &user)),
+                &proofs.equality_proof_data,
+            ),
+        ];
+        h.send_ok(&verify_equality, &[&equality, &range]);
+
+        let verify_range = [
+            compute_limit(),
+            ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(
+                Some(context_info(&range_account, &user)),
+                &proofs.range_proof_data,
+            ),
+        ];
+        h.send_ok(&verify_range, &[]);
+
+        ProvenWithdraw {
+            contexts: WithdrawProofContexts {
+                equality: equality_account,
+                range: range_account,
+            },
+            new_decryptable_available_balance: proofs.new_decryptable_available_balance,
+        }
+    }
+
+    /// Withdraw `assets` burning exactly `shares`, then close the proofs
+    pub fn withdraw(
+        &self,
+        h: &mut Harness,
+        keys: &VaultKeys,
+        assets: u64,
+        shares: u64,
+    ) -> TransactionResult {
+        let proven = self.prove_withdraw(h, shares);
+        let withdraw = svs2::withdraw(
+            keys,
+            &self.pubkey(),
+            &proven.contexts,
+            assets,
+            shares,
+            proven.new_decryptable_available_balance,
+            None,
+        );
+        self.send_with_proofs(h, withdraw, &proven)
+    }
+
+    /// Redeem `shares` for at least `min_assets_out`, then close the proofs
+    pub fn redeem(
+        &self,
+        h: &mut Harness,
+        keys: &VaultKeys,
+        shares: u64,
+        min_assets_out: u64,
+    ) -> TransactionResult {
+        let proven = self.prove_withdraw(h, shares);
+        let redeem = svs2::redeem(
+            keys,
+            &self.pubkey(),
+            &proven.contexts,
+            shares,
+            min_assets_out,
+            proven.new_decryptable_available_balance,
+        );
+        self.send_with_proofs(h, redeem, &proven)
+    }
+
+    fn send_with_proofs(
+        &self,
+        h: &mut Harness,
+        instruction: Instruction,
+        proven: &ProvenWithdraw,
+    ) -> TransactionResult {
+        let mut instructions = vec![compute_limit(), instruction];
+        instructions.extend(proven.close_instructions(&self.pubkey(), &h.payer.pubkey()));
+        h.send(&instructions, &[&self.wallet])
+    }
+}
+
+fn context_info<'a>(account: &'a Pubkey, authority: &'a Pubkey) -> ContextStateInfo<'a> {
+    ContextStateInfo {
+        context_state_account: account,
+        context_state_authority: authority,
+    }
+}
+
+fn compute_limit() -> Instruction {
+    ComputeBudgetInstruction::set_compute_unit_limit(CONFIDENTIAL_COMPUTE_UNITS)
+}
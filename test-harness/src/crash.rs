@@ -0,0 +1,431 @@
+//! Regression tests from fuzz crashes
+//!
+//! The Trident fuzzer in `trident-tests` writes the flows of a failing
+//! iteration to a crash artifact: `#` comments with the panic message, then
+//! `decimals_offset <n>` and one `<flow> <amount>` line per step, where
+//! `pause`, `unpause` and `sync` take no amount.
+//! [`CrashArtifact::render_test`] turns it into an integration test that
+//! replays the flows against SVS-1 and checks the fuzzer's invariants on the
+//! program's state after every step. The `crash-to-test` binary writes that
+//! test under `tests/`.
+
+use std::str::FromStr;
+
+use svs_client::{view::View, VaultKeys};
+use svs_math::{convert_to_assets, Rounding};
+
+use crate::Harness;
+
+/// Decimals of the shares mint; the asset has `9 - decimals_offset`
+const SHARES_DECIMALS: u8 = 9;
+
+/// Slack the fuzzer allows above the theoretical shares maximum
+const MAX_SHARES_TOLERANCE: u64 = 1_000;
+
+/// Assets the fuzzer funds its depositor with; mint costs depend on the
+/// replayed state, so the replay funds the same
+const DEPOSITOR_ASSETS: u64 = u64::MAX / 2;
+
+/// A fuzz flow with its fuzzed amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Deposit assets
+    Deposit(u64),
+    /// Redeem shares
+    Redeem(u64),
+    /// Withdraw assets
+    Withdraw(u64),
+    /// Mint shares
+    Mint(u64),
+    Pause,
+    Unpause,
+    /// Mint assets straight to the asset vault
+    Donate(u64),
+    /// Sync the total assets with the asset vault balance
+    Sync,
+    /// Check the assets round trip through shares
+    ConversionCheck(u64),
+}
+
+/// Parsed crash artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashArtifact {
+    /// Comment lines, without the `#`; the fuzzer writes the panic message
+    pub comments: Vec<String>,
+    pub decimals_offset: u8,
+    pub steps: Vec<Step>,
+}
+
+impl FromStr for CrashArtifact {
+    type Err = String;
+
+    fn from_str(artifact: &str) -> Result<Self, String> {
+        let mut comments = Vec::new();
+        let mut decimals_offset = None;
+        let mut steps = Vec::new();
+
+        for (index, line) in artifact.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                comments.push(comment.trim().to_string());
+                continue;
+            }
+
+            let (key, value) = match line.split_once(char::is_whitespace) {
+                Some((key, value)) => {
+                    let value: u64 = value
+                        .trim()
+                        .parse()
+                        .map_err(|e| format!("line {line_number}: {e}"))?;
+                    (key, Some(value))
+                }
+                None => (line, None),
+            };
+            if let Some(step) = match key {
+                "pause" => Some(Step::Pause),
+                "unpause" => Some(Step::Unpause),
+                "sync" => Some(Step::Sync),
+                _ => None,
+            } {
+                if value.is_some() {
+                    return Err(format!("line {line_number}: `{key}` takes no amount"));
+                }
+                steps.push(step);
+                continue;
+            }
+
+            let value =
+                value.ok_or_else(|| format!("line {line_number}: expected `<flow> <amount>`"))?;
+            match key {
+                "decimals_offset" => {
+                    let offset = u8::try_from(value)
+                        .ok()
+                        .filter(|offset| *offset <= SHARES_DECIMALS)
+                        .ok_or_else(|| {
+                            format!("line {line_number}: decimals offset {value} above 9")
+                        })?;
+                    decimals_offset = Some(offset);
+                }
+                "deposit" => steps.push(Step::Deposit(value)),
+                "redeem" => steps.push(Step::Redeem(value)),
+                "withdraw" => steps.push(Step::Withdraw(value)),
+                "mint" => steps.push(Step::Mint(value)),
+                "donate" => steps.push(Step::Donate(value)),
+                "conversion_check" => steps.push(Step::ConversionCheck(value)),
+                _ => return Err(format!("line {line_number}: unknown flow `{key}`")),
+            }
+        }
+
+        Ok(Self {
+            comments,
+            decimals_offset: decimals_offset.ok_or("missing `decimals_offset`")?,
+            steps,
+        })
+    }
+}
+
+impl CrashArtifact {
+    /// Source of an integration test `test_<name>` replaying the steps;
+    /// `artifact` names the crash file in the module docs
+    pub fn render_test(&self, name: &str, artifact: &str) -> String {
+        let mut test = String::new();
+        let mut line = |text: &str| {
+            test.push_str(text);
+            test.push('\n');
+        };
+
+        line(&format!(
+            "//! Regression test for the fuzz crash `{artifact}`"
+        ));
+        line("//!");
+        line("//! Generated by `crash-to-test`: replays the fuzzer's flows against SVS-1");
+        line("//! and checks its invariants after every step.");
+        if !self.comments.is_empty() {
+            line("//!");
+            line("//! ```text");
+            for comment in &self.comments {
+                line(format!("//! {comment}").trim_end());
+            }
+            line("//! ```");
+        }
+        line("");
+        line("use anchor_spl::token;");
+        line("use solana_sdk::signer::Signer;");
+        line("use svs_client::svs1;");
+        line("use svs_test_harness::{crash, Harness};");
+        line("");
+        line("#[test]");
+        line(&format!("fn test_{name}() {{"));
+        line("    let mut h = Harness::new();");
+        line(&format!(
+            "    let asset_mint = h.create_mint(&token::ID, {});",
+            SHARES_DECIMALS - self.decimals_offset
+        ));
+        line("    let vault = h.create_svs1_vault(&asset_mint, 1);");
+        line("    let keys = &vault.keys;");
+        if self
+            .steps
+            .iter()
+            .any(|step| matches!(step, Step::Pause | Step::Unpause | Step::Sync))
+        {
+            line("    let authority = &vault.authority;");
+        }
+        line(&format!(
+            "    let user = h.depositor(keys, {DEPOSITOR_ASSETS});"
+        ));
+        line("    crash::assert_invariants(&h, keys, 0);");
+
+        // Like the fuzzer, expect the user flows to fail while paused and
+        // pause or unpause to fail when they don't change the state
+        let mut paused = false;
+        let mut unsynced = 0u64;
+        for step in &self.steps {
+            line("");
+            match step {
+                Step::Deposit(assets) => line(&send(
+                    paused,
+                    &format!("svs1::deposit(keys, &user.pubkey(), {assets}, 0)"),
+                    "&user",
+                )),
+                Step::Redeem(shares) => line(&send(
+                    paused,
+                    &format!("svs1::redeem(keys, &user.pubkey(), {shares}, 0)"),
+                    "&user",
+                )),
+                Step::Withdraw(assets) => line(&send(
+                    paused,
+                    &format!("svs1::withdraw(keys, &user.pubkey(), {assets}, u64::MAX)"),
+                    "&user",
+                )),
+                Step::Mint(shares) => line(&send(
+                    paused,
+                    &format!("svs1::mint(keys, &user.pubkey(), {shares}, u64::MAX)"),
+                    "&user",
+                )),
+                Step::Pause => {
+                    line(&send(
+                        paused,
+                        "svs1::pause(keys, &authority.pubkey())",
+                        "authority",
+                    ));
+                    paused = true;
+                }
+                Step::Unpause => {
+                    line(&send(
+                        !paused,
+                        "svs1::unpause(keys, &authority.pubkey())",
+                        "authority",
+                    ));
+                    paused = false;
+                }
+                Step::Donate(assets) => {
+                    line(&format!(
+                        "    h.mint_to(&asset_mint, &keys.asset_vault, {assets});"
+                    ));
+                    unsynced = unsynced.saturating_add(*assets);
+                }
+                Step::Sync => {
+                    line(&send(
+                        false,
+                        "svs1::sync(keys, &authority.pubkey())",
+                        "authority",
+                    ));
+                    unsynced = 0;
+                }
+                Step::ConversionCheck(assets) => line(&format!(
+                    "    crash::assert_round_trip(&h, keys, {assets});"
+                )),
+            }
+            line(&format!(
+                "    crash::assert_invariants(&h, keys, {unsynced});"
+            ));
+        }
+        line("}");
+        test
+    }
+}
+
+/// Statement sending `instruction` signed by `signer`, asserting that it
+/// fails when `rejected`
+fn send(rejected: bool, instruction: &str, signer: &str) -> String {
+    if rejected {
+        format!("    assert!(h.send(&[{instruction}], &[{signer}]).is_err());")
+    } else {
+        format!("    h.send_ok(&[{instruction}], &[{signer}]);")
+    }
+}
+
+/// Identifier for a test named after a crash file, e.g. `1700000000_42`
+/// for `1700000000-42.crash`
+pub fn test_name(stem: &str) -> String {
+    stem.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The fuzzer's vault invariants on the program's state: the asset vault
+/// holds the recorded total assets plus the `unsynced` donations, and the
+/// shares supply stays within `(total_assets + 1) * 10^offset` plus the
+/// fuzzer's tolerance
+pub fn assert_invariants(h: &Harness, keys: &VaultKeys, unsynced: u64) {
+    let vault = h.vault(keys);
+    assert_eq!(
+        h.balance(&keys.asset_vault),
+        vault.total_assets + unsynced,
+        "Invariant: asset vault balance matches total assets plus unsynced donations"
+    );
+
+    let offset = 10u64.pow(u32::from(vault.decimals_offset));
+    let max_shares = vault
+        .total_assets
+        .saturating_mul(offset)
+        .saturating_add(offset)
+        .saturating_add(MAX_SHARES_TOLERANCE);
+    assert!(
+        h.supply(&keys.shares_mint) <= max_shares,
+        "Invariant: shares exceed theoretical maximum"
+    );
+}
+
+/// Depositing `assets` and redeeming the minted shares right after would not
+/// return more than `assets`
+pub fn assert_round_trip(h: &Harness, keys: &VaultKeys, assets: u64) {
+    let shares = h.view(keys, &View::PreviewDeposit(assets));
+    if shares == 0 {
+        return;
+    }
+
+    let vault = h.vault(keys);
+    let assets_back = convert_to_assets(
+        shares,
+        vault.total_assets.saturating_add(assets),
+        h.supply(&keys.shares_mint).saturating_add(shares),
+        vault.decimals_offset,
+        Rounding::Floor,
+    )
+    .expect("round trip conversion");
+    assert!(
+        assets_back <= assets,
+        "Invariant: round-trip should not create free assets"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTIFACT: &str = "\
+# panicked at fuzz_0/test_fuzz.rs:104:9:
+# Invariant: cannot redeem more assets than available
+decimals_offset 3
+deposit 1001
+redeem 5
+conversion_check 77
+deposit 250000
+";
+
+    const ADMIN_ARTIFACT: &str = "\
+decimals_offset 0
+deposit 5000
+pause
+withdraw 10
+unpause
+donate 700
+mint 3
+sync
+";
+
+    #[test]
+    fn test_parse() {
+        let artifact: CrashArtifact = ARTIFACT.parse().unwrap();
+        assert_eq!(artifact.decimals_offset, 3);
+        assert_eq!(
+            artifact.comments[1],
+            "Invariant: cannot redeem more assets than available"
+        );
+        assert_eq!(
+            artifact.steps,
+            [
+                Step::Deposit(1001),
+                Step::Redeem(5),
+                Step::ConversionCheck(77),
+                Step::Deposit(250_000),
+            ]
+        );
+
+        let artifact: CrashArtifact = ADMIN_ARTIFACT.parse().unwrap();
+        assert_eq!(
+            artifact.steps,
+            [
+                Step::Deposit(5000),
+                Step::Pause,
+                Step::Withdraw(10),
+                Step::Unpause,
+                Step::Donate(700),
+                Step::Mint(3),
+                Step::Sync,
+            ]
+        );
+
+        let invalid = |artifact: &str| artifact.parse::<CrashArtifact>().is_err();
+        assert!(invalid("deposit 5"));
+        assert!(invalid("decimals_offset 10"));
+        assert!(invalid("decimals_offset 3\ntransfer 5"));
+        assert!(invalid("decimals_offset 3\ndeposit -5"));
+        assert!(invalid("decimals_offset 3\nwithdraw"));
+        assert!(invalid("decimals_offset 3\npause 1"));
+    }
+
+    #[test]
+    fn test_render() {
+        let artifact: CrashArtifact = ARTIFACT.parse().unwrap();
+        let name = test_name("1700000000-42");
+        assert_eq!(name, "1700000000_42");
+
+        let test = artifact.render_test(&name, "1700000000-42.crash");
+        assert!(test.starts_with("//! Regression test for the fuzz crash `1700000000-42.crash`"));
+        assert!(test.contains("//! Invariant: cannot redeem more assets than available\n"));
+        assert!(test.contains("fn test_1700000000_42() {"));
+        assert!(test.contains("h.create_mint(&token::ID, 6);"));
+        assert!(test.contains("h.depositor(keys, 9223372036854775807);"));
+        assert!(test.contains("svs1::redeem(keys, &user.pubkey(), 5, 0)"));
+        assert!(test.contains("crash::assert_round_trip(&h, keys, 77);"));
+        assert_eq!(test.matches("crash::assert_invariants").count(), 5);
+        assert!(!test.contains("let authority"));
+    }
+
+    #[test]
+    fn test_render_admin_flows() {
+        let artifact: CrashArtifact = ADMIN_ARTIFACT.parse().unwrap();
+        let test = artifact.render_test("admin", "admin.crash");
+        assert!(test.contains("let authority = &vault.authority;"));
+        // Rejected while paused, accepted again once unpaused
+        assert!(test.contains(
+            "assert!(h.send(&[svs1::withdraw(keys, &user.pubkey(), 10, u64::MAX)], \
+             &[&user]).is_err());"
+        ));
+        assert!(
+            test.contains("h.send_ok(&[svs1::mint(keys, &user.pubkey(), 3, u64::MAX)], &[&user]);")
+        );
+        assert!(
+            test.contains("h.send_ok(&[svs1::unpause(keys, &authority.pubkey())], &[authority]);")
+        );
+        assert!(test.contains("h.mint_to(&asset_mint, &keys.asset_vault, 700);"));
+        // The donation stays unsynced until the sync
+        assert!(test.contains("crash::assert_invariants(&h, keys, 700);"));
+        assert!(test.ends_with(
+            "h.send_ok(&[svs1::sync(keys, &authority.pubkey())], &[authority]);\n    \
+             crash::assert_invariants(&h, keys, 0);\n}\n"
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+//! LiteSVM test harness for the SVS programs
+//!
+//! [`Harness`] runs the compiled `svs_1.so` and `svs_2.so`, the
+//! `mock_swap_adapter.so` harvest tests swap with and the
+//! `mock_governance.so` admin tests sign with, in an in-process LiteSVM
+//! bank next to its bundled SPL Token, Token-2022, Associated Token,
+//! Ed25519 and ZK ElGamal proof programs, and adds fixtures for the pieces
+//! every end-to-end test needs: mints and token accounts ([`token`]), vaults
+//! ([`vault`]) and SVS-2 users with configured confidential shares accounts
+//! ([`confidential`]). [`crash`] turns Trident fuzz crashes into
+//! regression tests.
+//!
+//! Instructions are built with `svs-client`, so the tests cover the SDK
+//! builders together with the programs. Build the programs with
+//! `anchor build` first, or point `SBF_OUT_DIR` at the directory with the
+//! `.so` files.
+
+// Senders return LiteSVM's own `TransactionResult`, whose error is large
+#![allow(clippy::result_large_err)]
+
+pub mod confidential;
+pub mod crash;
+pub mod token;
+pub mod vault;
+
+use std::path::PathBuf;
+
+use litesvm::{
+    types::{FailedTransactionMetadata, TransactionMetadata, TransactionResult},
+    LiteSVM,
+};
+use solana_loader_v3_interface::state::UpgradeableLoaderState;
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use solana_zk_sdk::zk_elgamal_proof_program;
+use svs_client::{view::View, ConfidentialVault, Vault, VaultKeys};
+
+pub use confidential::{ConfidentialUser, ProvenWithdraw};
+pub use vault::TestVault;
+
+/// Unix timestamp the bank's clock starts at
+pub const START_TIMESTAMP: i64 = 1_700_000_000;
+
+/// Lamports given to every funded wallet
+pub const USER_LAMPORTS: u64 = 100 * LAMPORTS_PER_SOL;
+
+/// In-process bank with both vault programs deployed
+pub struct Harness {
+    pub svm: LiteSVM,
+    /// Fee payer of every transaction, mint authority of test mints and
+    /// upgrade authority of both programs
+    pub payer: Keypair,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let payer = Keypair::new();
+
+        let programs = program_dir();
+        for (program_id, name) in [
+            (svs_1::ID, "svs_1"),
+            (svs_2::ID, "svs_2"),
+            (mock_swap_adapter::ID, "mock_swap_adapter"),
+            (mock_governance::ID, "mock_governance"),
+        ] {
+            let path = programs.join(format!("{name}.so"));
+            let elf = std::fs::read(&path).unwrap_or_else(|e| {
+                panic!("loading {} ({e}); run `anchor build` first", path.display())
+            });
+            deploy_upgradeable(&mut svm, program_id, &elf, &payer.pubkey());
+        }
+        assert!(
+            svm.get_account(&zk_elgamal_proof_program::id())
+                .is_some_and(|account| account.executable),
+            "LiteSVM build without the ZK ElGamal proof program"
+        );
+
+        svm.airdrop(&payer.pubkey(), 1_000 * LAMPORTS_PER_SOL)
+            .expect("airdrop to payer");
+
+        let mut harness = Self { svm, payer };
+        harness.set_unix_timestamp(START_TIMESTAMP);
+        harness
+    }
+
+    /// New wallet holding [`USER_LAMPORTS`]
+    pub fn funded_user(&mut self) -> Keypair {
+        let user = Keypair::new();
+        self.svm
+            .airdrop(&user.pubkey(), USER_LAMPORTS)
+            .expect("airdrop to user");
+        user
+    }
+
+    /// Send `instructions` paid by the harness payer and signed by `signers`
+    ///
+    /// The blockhash is expired afterwards, so sending the same instructions
+    /// again is a new transaction rather than a duplicate.
+    pub fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> TransactionResult {
+        let transaction = self.transaction(instructions, signers);
+        let result = self.svm.send_transaction(transaction);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// [`Self::send`], panicking with the program logs if it fails
+    pub fn send_ok(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> TransactionMetadata {
+        match self.send(instructions, signers) {
+            Ok(meta) => meta,
+            Err(failed) => panic!(
+                "transaction failed: {:?}\n{}",
+                failed.err,
+                failed.meta.pretty_logs()
+            ),
+        }
+    }
+
+    /// Call a view instruction of the vault described by `keys`
+    pub fn view(&self, keys: &VaultKeys, view: &View) -> u64 {
+        let transaction = self.transaction(&[svs_client::view::instruction(keys, view)], &[]);
+        let simulated = self
+            .svm
+            .simulate_transaction(transaction)
+            .unwrap_or_else(|failed| {
+                panic!(
+                    "view {view:?} failed: {:?}\n{}",
+                    failed.err,
+                    failed.meta.pretty_logs()
+                )
+            });
+        svs_client::view::decode_return_data(&simulated.meta.return_data.data)
+            .expect("view return data")
+    }
+
+    /// Current state of an SVS-1 vault
+    pub fn vault(&self, keys: &VaultKeys) -> Vault {
+        let data = self.account_data(&keys.vault);
+        svs_client::decode_vault(&keys.vault, &data).expect("SVS-1 vault")
+    }
+
+    /// Current state of an SVS-2 vault
+    pub fn confidential_vault(&self, keys: &VaultKeys) -> ConfidentialVault {
+        let data = self.account_data(&keys.vault);
+        svs_client::decode_confidential_vault(&keys.vault, &data).expect("SVS-2 vault")
+    }
+
+    /// Data of an existing account
+    pub fn account_data(&self, address: &Pubkey) -> Vec<u8> {
+        self.svm
+            .get_account(address)
+            .unwrap_or_else(|| panic!("account {address} does not exist"))
+            .data
+    }
+
+    pub fn unix_timestamp(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// Move the clock `seconds` forward
+    pub fn warp(&mut self, seconds: i64) {
+        self.set_unix_timestamp(self.unix_timestamp() + seconds);
+    }
+
+    fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend(
+            signers
+                .iter()
+                .filter(|signer| signer.pubkey() != self.payer.pubkey()),
+        );
+        Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        )
+    }
+}
+
+/// Assert that `result` failed with the Anchor/program error `code`, e.g.
+/// `u32::from(svs_2::error::VaultError::VaultPaused)`
+pub fn assert_error(result: TransactionResult, code: u32) {
+    match result {
+        Ok(meta) => panic!(
+            "expected error {code}, transaction succeeded\n{}",
+            meta.pretty_logs()
+        ),
+        Err(FailedTransactionMetadata { err, meta }) => match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(actual))
+                if actual == code => {}
+            err => panic!("expected error {code}, got {err:?}\n{}", meta.pretty_logs()),
+        },
+    }
+}
+
+/// Assert that `result` failed with `expected`, an error of the runtime
+/// rather than of a program
+pub fn assert_instruction_error(result: TransactionResult, expected: InstructionError) {
+    match result {
+        Ok(meta) => panic!(
+            "expected {expected:?}, transaction succeeded\n{}",
+            meta.pretty_logs()
+        ),
+        Err(FailedTransactionMetadata { err, meta }) => match err {
+            TransactionError::InstructionError(_, actual) if actual == expected => {}
+            err => panic!("expected {expected:?}, got {err:?}\n{}", meta.pretty_logs()),
+        },
+    }
+}
+
+/// Deploy `elf` with the upgradeable loader, as `solana program deploy`
+/// does, so the program has a ProgramData account naming its upgrade
+/// authority
+fn deploy_upgradeable(
+    svm: &mut LiteSVM,
+    program_id: Pubkey,
+    elf: &[u8],
+    upgrade_authority: &Pubkey,
+) {
+    let program_data_address = svs_client::pda::program_data_address(&program_id);
+
+    let mut program_data = Account::new_data(
+        0,
+        &UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(*upgrade_authority),
+        },
+        &bpf_loader_upgradeable::ID,
+    )
+    .expect("serialize ProgramData");
+    program_data.data.extend_from_slice(elf);
+    program_data.lamports = svm.minimum_balance_for_rent_exemption(program_data.data.len());
+
+    let mut program = Account::new_data(
+        svm.minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program()),
+        &UpgradeableLoaderState::Program {
+            programdata_address: program_data_address,
+        },
+        &bpf_loader_upgradeable::ID,
+    )
+    .expect("serialize Program");
+    program.executable = true;
+
+    // The program is loaded from its ProgramData when it is set
+    svm.set_account(program_data_address, program_data)
+        .expect("set ProgramData account");
+    svm.set_account(program_id, program)
+        .unwrap_or_else(|e| panic!("loading program {program_id}: {e}"));
+}
+
+/// Directory holding the programs' `.so` files
+fn program_dir() -> PathBuf {
+    std::env::var_os("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/deploy"))
+}
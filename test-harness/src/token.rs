@@ -0,0 +1,301 @@
+//! Mints and token accounts for either token program
+//!
+//! Test mints use the harness payer as mint authority, so tests can mint
+//! assets to anyone without extra signers.
+
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address_with_program_id,
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    },
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            confidential_transfer, cpi_guard, transfer_fee, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
+        state::{Account, Mint},
+    },
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer};
+use solana_system_interface::instruction as system_instruction;
+
+use crate::Harness;
+
+impl Harness {
+    /// Mint of `token_program` with `decimals` and no extensions
+    pub fn create_mint(&mut self, token_program: &Pubkey, decimals: u8) -> Pubkey {
+        self.create_mint_with(token_program, decimals, &[], |_| vec![])
+    }
+
+    /// Token-2022 mint charging `basis_points` (at most `maximum_fee`) on
+    /// every transfer
+    pub fn create_transfer_fee_mint(
+        &mut self,
+        decimals: u8,
+        basis_points: u16,
+        maximum_fee: u64,
+    ) -> Pubkey {
+        let authority = self.payer.pubkey();
+        self.create_mint_with(
+            &spl_token_2022::ID,
+            decimals,
+            &[ExtensionType::TransferFeeConfig],
+            |mint| {
+                vec![transfer_fee::instruction::initialize_transfer_fee_config(
+                    &spl_token_2022::ID,
+                    mint,
+                    Some(&authority),
+                    Some(&authority),
+                    basis_points,
+                    maximum_fee,
+                )
+                .expect("initialize_transfer_fee_config")]
+            },
+        )
+    }
+
+    /// Token-2022 mint meeting the SVS-2 shares mint requirements, for
+    /// `initialize_with_shares_mint` of the vault `vault`
+    pub fn create_shares_mint(
+        &mut self,
+        vault: &Pubkey,
+        auditor_elgamal_pubkey: Option<[u8; 32]>,
+    ) -> Pubkey {
+        self.create_shares_mint_with(vault, auditor_elgamal_pubkey, &[], |_| vec![])
+    }
+
+    /// [`create_shares_mint`](Self::create_shares_mint) with the extra
+    /// `extensions`, initialized by `init_extensions` before the mint itself
+    pub fn create_shares_mint_with(
+        &mut self,
+        vault: &Pubkey,
+        auditor_elgamal_pubkey: Option<[u8; 32]>,
+        extensions: &[ExtensionType],
+        init_extensions: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+    ) -> Pubkey {
+        let keypair = Keypair::new();
+        let mint = keypair.pubkey();
+        let mut extension_types = vec![ExtensionType::ConfidentialTransferMint];
+        extension_types.extend_from_slice(extensions);
+        let space =
+            ExtensionType::try_calculate_account_len::<Mint>(&extension_types).expect("mint size");
+
+        let mut instructions = vec![
+            self.create_account_instruction(&mint, space, &spl_token_2022::ID),
+            confidential_transfer::instruction::initialize_mint(
+                &spl_token_2022::ID,
+                &mint,
+                Some(*vault),
+                true,
+                auditor_elgamal_pubkey.map(Into::into),
+            )
+            .expect("initialize confidential transfer mint"),
+        ];
+        instructions.extend(init_extensions(&mint));
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                &mint,
+                vault,
+                None,
+                svs_2::constants::SHARES_DECIMALS,
+            )
+            .expect("initialize_mint2"),
+        );
+        self.send_ok(&instructions, &[&keypair]);
+        mint
+    }
+
+    /// Associated token account of `owner`, created if needed
+    pub fn create_ata(&mut self, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        let instruction = create_associated_token_account_idempotent(
+            &self.payer.pubkey(),
+            owner,
+            mint,
+            token_program,
+        );
+        self.send_ok(&[instruction], &[]);
+        get_associated_token_address_with_program_id(owner, mint, token_program)
+    }
+
+    /// Token account of `owner` at a fresh address (not their ATA)
+    pub fn create_token_account(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        let keypair = Keypair::new();
+        let account = keypair.pubkey();
+        let mint_data = self.account_data(mint);
+        let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data).expect("mint");
+        let extensions = ExtensionType::get_required_init_account_extensions(
+            &mint_state.get_extension_types().expect("mint extensions"),
+        );
+        let space =
+            ExtensionType::try_calculate_account_len::<Account>(&extensions).expect("account size");
+
+        let instructions = [
+            self.create_account_instruction(&account, space, token_program),
+            spl_token_2022::instruction::initialize_account3(token_program, &account, mint, owner)
+                .expect("initialize_account3"),
+        ];
+        self.send_ok(&instructions, &[&keypair]);
+        account
+    }
+
+    /// Mint `amount` of a test mint into `account`
+    pub fn mint_to(&mut self, mint: &Pubkey, account: &Pubkey, amount: u64) {
+        let token_program = self.owner_program(mint);
+        let instruction = spl_token_2022::instruction::mint_to(
+            &token_program,
+            mint,
+            account,
+            &self.payer.pubkey(),
+            &[],
+            amount,
+        )
+        .expect("mint_to");
+        self.send_ok(&[instruction], &[]);
+    }
+
+    /// Let `delegate` move up to `amount` out of `owner`'s `account`
+    pub fn approve(&mut self, owner: &Keypair, account: &Pubkey, delegate: &Pubkey, amount: u64) {
+        let token_program = self.owner_program(account);
+        let instruction = spl_token_2022::instruction::approve(
+            &token_program,
+            account,
+            delegate,
+            &owner.pubkey(),
+            &[],
+            amount,
+        )
+        .expect("approve");
+        self.send_ok(&[instruction], &[owner]);
+    }
+
+    /// Enable CPI Guard on `owner`'s Token-2022 `account`, first making room
+    /// for the extension
+    pub fn enable_cpi_guard(&mut self, owner: &Keypair, account: &Pubkey) {
+        let instructions = [
+            spl_token_2022::instruction::reallocate(
+                &spl_token_2022::ID,
+                account,
+                &self.payer.pubkey(),
+                &owner.pubkey(),
+                &[],
+                &[ExtensionType::CpiGuard],
+            )
+            .expect("reallocate"),
+            cpi_guard::instruction::enable_cpi_guard(
+                &spl_token_2022::ID,
+                account,
+                &owner.pubkey(),
+                &[],
+            )
+            .expect("enable_cpi_guard"),
+        ];
+        self.send_ok(&instructions, &[owner]);
+    }
+
+    /// Move `amount` of `mint` from `owner`'s `source` to `destination`
+    pub fn transfer(
+        &mut self,
+        owner: &Keypair,
+        source: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) {
+        let token_program = self.owner_program(mint);
+        let instruction = spl_token_2022::instruction::transfer_checked(
+            &token_program,
+            source,
+            mint,
+            destination,
+            &owner.pubkey(),
+            &[],
+            amount,
+            self.decimals(mint),
+        )
+        .expect("transfer_checked");
+        self.send_ok(&[instruction], &[owner]);
+    }
+
+    /// Public balance of a token account of either program
+    pub fn balance(&self, account: &Pubkey) -> u64 {
+        let data = self.account_data(account);
+        StateWithExtensions::<Account>::unpack(&data)
+            .expect("token account")
+            .base
+            .amount
+    }
+
+    pub fn supply(&self, mint: &Pubkey) -> u64 {
+        let data = self.account_data(mint);
+        StateWithExtensions::<Mint>::unpack(&data)
+            .expect("mint")
+            .base
+            .supply
+    }
+
+    pub fn decimals(&self, mint: &Pubkey) -> u8 {
+        let data = self.account_data(mint);
+        StateWithExtensions::<Mint>::unpack(&data)
+            .expect("mint")
+            .base
+            .decimals
+    }
+
+    /// Token program owning `address`
+    pub fn owner_program(&self, address: &Pubkey) -> Pubkey {
+        self.svm
+            .get_account(address)
+            .unwrap_or_else(|| panic!("account {address} does not exist"))
+            .owner
+    }
+
+    fn create_mint_with(
+        &mut self,
+        token_program: &Pubkey,
+        decimals: u8,
+        extensions: &[ExtensionType],
+        init_extensions: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+    ) -> Pubkey {
+        let keypair = Keypair::new();
+        let mint = keypair.pubkey();
+        let space =
+            ExtensionType::try_calculate_account_len::<Mint>(extensions).expect("mint size");
+
+        let mut instructions = vec![self.create_account_instruction(&mint, space, token_program)];
+        instructions.extend(init_extensions(&mint));
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(
+                token_program,
+                &mint,
+                &self.payer.pubkey(),
+                None,
+                decimals,
+            )
+            .expect("initialize_mint2"),
+        );
+        self.send_ok(&instructions, &[&keypair]);
+        mint
+    }
+
+    fn create_account_instruction(
+        &self,
+        address: &Pubkey,
+        space: usize,
+        owner: &Pubkey,
+    ) -> Instruction {
+        system_instruction::create_account(
+            &self.payer.pubkey(),
+            address,
+            self.svm.minimum_balance_for_rent_exemption(space),
+            space as u64,
+            owner,
+        )
+    }
+}
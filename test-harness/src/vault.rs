@@ -0,0 +1,81 @@
+//! Initialized vaults and depositors
+
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use svs_client::{svs1, svs2, VaultKeys};
+
+use crate::Harness;
+
+/// A vault created by the harness
+pub struct TestVault {
+    pub keys: VaultKeys,
+    /// Signs the admin instructions; may also harvest SVS-2 vaults
+    pub authority: Keypair,
+}
+
+impl Harness {
+    /// Initialize an SVS-1 vault over `asset_mint`
+    pub fn create_svs1_vault(&mut self, asset_mint: &Pubkey, vault_id: u64) -> TestVault {
+        let keys = VaultKeys::derive(
+            &svs_1::ID,
+            asset_mint,
+            vault_id,
+            &self.owner_program(asset_mint),
+        );
+        let authority = self.funded_user();
+        self.send_ok(
+            &[svs1::initialize(
+                &keys,
+                &authority.pubkey(),
+                vault_id,
+                "SVS-1 Test Vault".to_string(),
+                "svTEST".to_string(),
+                "https://example.com/svs-1.json".to_string(),
+                false,
+            )],
+            &[&authority],
+        );
+        TestVault { keys, authority }
+    }
+
+    /// Initialize an SVS-2 vault over `asset_mint`, with the program-created
+    /// shares mint
+    pub fn create_svs2_vault(
+        &mut self,
+        asset_mint: &Pubkey,
+        vault_id: u64,
+        auditor_elgamal_pubkey: Option<[u8; 32]>,
+    ) -> TestVault {
+        let keys = VaultKeys::derive(
+            &svs_2::ID,
+            asset_mint,
+            vault_id,
+            &self.owner_program(asset_mint),
+        );
+        let authority = self.funded_user();
+        let payer = self.payer.pubkey();
+        self.send_ok(
+            &[svs2::initialize(
+                &keys,
+                &payer,
+                &authority.pubkey(),
+                vault_id,
+                "SVS-2 Test Vault".to_string(),
+                "csTEST".to_string(),
+                "https://example.com/svs-2.json".to_string(),
+                auditor_elgamal_pubkey,
+            )],
+            &[&authority],
+        );
+        TestVault { keys, authority }
+    }
+
+    /// Funded wallet holding `assets` of the vault's asset in its ATA
+    pub fn depositor(&mut self, keys: &VaultKeys, assets: u64) -> Keypair {
+        let user = self.funded_user();
+        let account = self.create_ata(&user.pubkey(), &keys.asset_mint, &keys.asset_token_program);
+        if assets > 0 {
+            self.mint_to(&keys.asset_mint, &account, assets);
+        }
+        user
+    }
+}
@@ -0,0 +1,800 @@
+//! End-to-end tests of every SVS-1 instruction
+//!
+//! Expected amounts are golden values of the vault math for a 6-decimal
+//! asset (decimals offset 3), so any change in rounding shows up here.
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::{
+    token,
+    token_2022::{
+        self,
+        spl_token_2022::{self, extension::cpi_guard},
+    },
+};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use svs_1::{error::VaultError, state::ProtocolConfig};
+use svs_client::{
+    metadata_hash,
+    pda::find_config_address,
+    svs1,
+    view::{decode_return_data, View},
+    VaultKeys,
+};
+use svs_events::{logs::decode_logs, VaultEvent};
+use svs_test_harness::{assert_error, Harness, TestVault};
+
+fn setup() -> (Harness, TestVault) {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let vault = h.create_svs1_vault(&asset_mint, 1);
+    (h, vault)
+}
+
+#[test]
+fn test_initialize() {
+    let (h, vault) = setup();
+    let keys = &vault.keys;
+
+    let state = h.vault(keys);
+    assert_eq!(state.authority, vault.authority.pubkey());
+    assert_eq!(state.asset_mint, keys.asset_mint);
+    assert_eq!(state.shares_mint, keys.shares_mint);
+    assert_eq!(state.asset_vault, keys.asset_vault);
+    assert_eq!(state.total_assets, 0);
+    assert_eq!(state.decimals_offset, 3);
+    assert_eq!(state.vault_id, 1);
+    assert!(!state.paused);
+    assert!(!state.non_transferable);
+    assert_eq!(
+        state.metadata_hash,
+        metadata_hash(
+            "SVS-1 Test Vault",
+            "svTEST",
+            "https://example.com/svs-1.json"
+        )
+    );
+
+    assert_eq!(h.owner_program(&keys.shares_mint), token_2022::ID);
+    assert_eq!(h.decimals(&keys.shares_mint), 9);
+    assert_eq!(h.supply(&keys.shares_mint), 0);
+    assert_eq!(h.balance(&keys.asset_vault), 0);
+}
+
+#[test]
+fn test_initialize_rejects_invalid_metadata() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let keys = VaultKeys::derive(&svs_1::ID, &asset_mint, 1, &token::ID);
+    let authority = h.funded_user();
+    let uri = "https://example.com/svs-1.json";
+    let long_name = "n".repeat(33);
+    let long_uri = "u".repeat(201);
+
+    for (name, symbol, uri, error) in [
+        ("", "svTEST", uri, VaultError::InvalidVaultName),
+        (
+            long_name.as_str(),
+            "svTEST",
+            uri,
+            VaultError::InvalidVaultName,
+        ),
+        ("Vault\n", "svTEST", uri, VaultError::InvalidVaultName),
+        ("Vault", "", uri, VaultError::InvalidVaultSymbol),
+        ("Vault", "svTESTVAULT", uri, VaultError::InvalidVaultSymbol),
+        ("Vault", "sv-TEST", uri, VaultError::InvalidVaultSymbol),
+        (
+            "Vault",
+            "svTEST",
+            long_uri.as_str(),
+            VaultError::InvalidVaultUri,
+        ),
+        (
+            "Vault",
+            "svTEST",
+            "https://example.com/a b",
+            VaultError::InvalidVaultUri,
+        ),
+    ] {
+        let initialize = svs1::initialize(
+            &keys,
+            &authority.pubkey(),
+            1,
+            name.to_string(),
+            symbol.to_string(),
+            uri.to_string(),
+            false,
+        );
+        let result = h.send(&[initialize], &[&authority]);
+        assert_error(result, error.into());
+    }
+
+    // Bounds are inclusive, and an empty uri is allowed
+    let name = "n".repeat(32);
+    h.send_ok(
+        &[svs1::initialize(
+            &keys,
+            &authority.pubkey(),
+            1,
+            name.clone(),
+            "SVTEST1234".to_string(),
+            String::new(),
+            false,
+        )],
+        &[&authority],
+    );
+    assert_eq!(
+        h.vault(&keys).metadata_hash,
+        metadata_hash(&name, "SVTEST1234", "")
+    );
+}
+
+#[test]
+fn test_non_transferable_shares() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let keys = &VaultKeys::derive(&svs_1::ID, &asset_mint, 1, &token::ID);
+    let authority = h.funded_user();
+    let initialize = h.send_ok(
+        &[svs1::initialize(
+            keys,
+            &authority.pubkey(),
+            1,
+            "Vesting Vault".to_string(),
+            "svVEST".to_string(),
+            String::new(),
+            true,
+        )],
+        &[&authority],
+    );
+    assert!(h.vault(keys).non_transferable);
+    let events = decode_logs(&initialize.logs, &[svs_1::ID]);
+    assert!(matches!(
+        &events[..],
+        [(_, VaultEvent::VaultInitialized(event))] if event.non_transferable
+    ));
+
+    let alice = h.depositor(keys, 1_000_000);
+    h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+    let alice_shares = keys.shares_account(&alice.pubkey());
+    assert_eq!(h.balance(&alice_shares), 1_000_000_000);
+
+    // Shares cannot change hands...
+    let bob = h.funded_user();
+    let bob_shares = h.create_ata(&bob.pubkey(), &keys.shares_mint, &token_2022::ID);
+    let transfer = spl_token_2022::instruction::transfer_checked(
+        &token_2022::ID,
+        &alice_shares,
+        &keys.shares_mint,
+        &bob_shares,
+        &alice.pubkey(),
+        &[],
+        1_000,
+        9,
+    )
+    .unwrap();
+    let result = h.send(&[transfer], &[&alice]);
+    assert_error(
+        result,
+        spl_token_2022::error::TokenError::NonTransferable as u32,
+    );
+
+    // ...but the holder still redeems them
+    h.send_ok(
+        &[svs1::redeem(
+            keys,
+            &alice.pubkey(),
+            1_000_000_000,
+            1_000_000,
+        )],
+        &[&alice],
+    );
+    assert_eq!(h.balance(&alice_shares), 0);
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 1_000_000);
+    assert_eq!(h.supply(&keys.shares_mint), 0);
+}
+
+#[test]
+fn test_deposit_mint_redeem_withdraw() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.depositor(keys, 1_000_000);
+    let bob = h.depositor(keys, 1_000_000);
+
+    // Empty vault: 10^3 virtual shares per virtual asset
+    h.send_ok(
+        &[svs1::deposit(
+            keys,
+            &alice.pubkey(),
+            1_000_000,
+            1_000_000_000,
+        )],
+        &[&alice],
+    );
+    assert_eq!(
+        h.balance(&keys.shares_account(&alice.pubkey())),
+        1_000_000_000
+    );
+
+    h.send_ok(
+        &[svs1::mint(keys, &bob.pubkey(), 500_000_000, 500_000)],
+        &[&bob],
+    );
+    assert_eq!(h.balance(&keys.shares_account(&bob.pubkey())), 500_000_000);
+    assert_eq!(h.balance(&keys.asset_account(&bob.pubkey())), 500_000);
+    assert_eq!(h.vault(keys).total_assets, 1_500_000);
+
+    // 300_000 of yield lands in the asset vault and is picked up by sync
+    let donor = h.depositor(keys, 300_000);
+    h.transfer(
+        &donor,
+        &keys.asset_account(&donor.pubkey()),
+        &keys.asset_mint,
+        &keys.asset_vault,
+        300_000,
+    );
+    h.send_ok(
+        &[svs1::sync(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+    assert_eq!(h.vault(keys).total_assets, 1_800_000);
+
+    // Redeem rounds the assets down
+    h.send_ok(
+        &[svs1::redeem(keys, &alice.pubkey(), 400_000_000, 479_999)],
+        &[&alice],
+    );
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 479_999);
+
+    // Withdraw rounds the burned shares up
+    h.send_ok(
+        &[svs1::withdraw(keys, &alice.pubkey(), 100_000, 83_333_283)],
+        &[&alice],
+    );
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 579_999);
+    assert_eq!(
+        h.balance(&keys.shares_account(&alice.pubkey())),
+        516_666_717
+    );
+
+    assert_eq!(h.vault(keys).total_assets, 1_220_001);
+    assert_eq!(h.balance(&keys.asset_vault), 1_220_001);
+    assert_eq!(h.supply(&keys.shares_mint), 1_016_666_717);
+}
+
+#[test]
+fn test_slippage_and_limits() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.depositor(keys, 1_000_000);
+
+    let result = h.send(
+        &[svs1::deposit(
+            keys,
+            &alice.pubkey(),
+            1_000_000,
+            1_000_000_001,
+        )],
+        &[&alice],
+    );
+    assert_error(result, VaultError::SlippageExceeded.into());
+
+    let result = h.send(&[svs1::deposit(keys, &alice.pubkey(), 999, 0)], &[&alice]);
+    assert_error(result, VaultError::DepositTooSmall.into());
+
+    h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+
+    let result = h.send(
+        &[svs1::redeem(keys, &alice.pubkey(), 1_000_000_001, 0)],
+        &[&alice],
+    );
+    assert_error(result, VaultError::InsufficientShares.into());
+
+    let result = h.send(
+        &[svs1::withdraw(
+            keys,
+            &alice.pubkey(),
+            1_000_000,
+            999_999_999,
+        )],
+        &[&alice],
+    );
+    assert_error(result, VaultError::SlippageExceeded.into());
+}
+
+#[test]
+fn test_views() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.depositor(keys, 1_000_000);
+    let alice_key = alice.pubkey();
+    h.send_ok(&[svs1::deposit(keys, &alice_key, 1_000_000, 0)], &[&alice]);
+    let donor = h.depositor(keys, 250_000);
+    h.transfer(
+        &donor,
+        &keys.asset_account(&donor.pubkey()),
+        &keys.asset_mint,
+        &keys.asset_vault,
+        250_000,
+    );
+    h.send_ok(
+        &[svs1::sync(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+
+    // 1_250_000 assets backing 10^9 shares
+    for (view, expected) in [
+        (View::PreviewDeposit(1_000_000), 800_000_159),
+        (View::PreviewMint(1_000_000_000), 1_250_000),
+        (View::PreviewWithdraw(1_000_000), 800_000_160),
+        (View::PreviewRedeem(1_000_000_000), 1_249_999),
+        (View::SaturatingPreviewDeposit(u64::MAX), u64::MAX),
+        (View::SaturatingPreviewRedeem(1_000_000_000), 1_249_999),
+        (View::ConvertToShares(1_000_000), 800_000_159),
+        (View::ConvertToAssets(1_000_000_000), 1_249_999),
+        (View::SharePrice, 1_249_999_750_000),
+        (View::TotalAssets, 1_250_000),
+        (View::MaxDeposit, u64::MAX),
+        (View::MaxMint, u64::MAX),
+        (View::MaxWithdraw(alice_key), 1_249_999),
+        (View::MaxRedeem(alice_key), 1_000_000_000),
+    ] {
+        assert_eq!(h.view(keys, &view), expected, "{view:?}");
+    }
+
+    h.send_ok(
+        &[svs1::pause(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+    for view in [
+        View::MaxDeposit,
+        View::MaxMint,
+        View::MaxWithdraw(alice_key),
+        View::MaxRedeem(alice_key),
+    ] {
+        assert_eq!(h.view(keys, &view), 0, "{view:?} while paused");
+    }
+}
+
+#[test]
+fn test_pause_unpause() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+    let alice = h.depositor(keys, 2_000_000);
+
+    let stranger = h.funded_user();
+    let result = h.send(&[svs1::pause(keys, &stranger.pubkey())], &[&stranger]);
+    assert_error(result, VaultError::Unauthorized.into());
+
+    h.send_ok(&[svs1::pause(keys, &authority)], &[&vault.authority]);
+    assert!(h.vault(keys).paused);
+
+    let result = h.send(&[svs1::pause(keys, &authority)], &[&vault.authority]);
+    assert_error(result, VaultError::AlreadyPaused.into());
+
+    let result = h.send(
+        &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+    assert_error(result, VaultError::VaultPaused.into());
+
+    h.send_ok(&[svs1::unpause(keys, &authority)], &[&vault.authority]);
+    assert!(!h.vault(keys).paused);
+    let result = h.send(&[svs1::unpause(keys, &authority)], &[&vault.authority]);
+    assert_error(result, VaultError::VaultNotPaused.into());
+
+    h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+}
+
+#[test]
+fn test_event_sequence() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+    let alice = h.depositor(keys, 1_000_000);
+    // VaultInitialized was the first event
+    assert_eq!(h.vault(keys).event_sequence, 1);
+
+    let deposit = h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+    let pause = h.send_ok(
+        &[
+            svs1::pause(keys, &authority),
+            svs1::unpause(keys, &authority),
+        ],
+        &[&vault.authority],
+    );
+    let events: Vec<_> = [&deposit, &pause]
+        .iter()
+        .flat_map(|meta| decode_logs(&meta.logs, &[svs_1::ID]))
+        .collect();
+
+    let sequences: Vec<_> = events.iter().map(|(_, e)| e.sequence()).collect();
+    assert_eq!(sequences, [2, 3, 4]);
+    assert!(matches!(events[0].1, VaultEvent::Deposit(_)));
+    assert!(events
+        .iter()
+        .all(|(_, e)| e.timestamp() == h.unix_timestamp()));
+    assert_eq!(h.vault(keys).event_sequence, 4);
+
+    // One operation per instruction, also returned as return data
+    let op_counters: Vec<_> = events.iter().map(|(_, e)| e.op_counter()).collect();
+    assert_eq!(op_counters, [2, 3, 4]);
+    assert_eq!(decode_return_data(&deposit.return_data.data).unwrap(), 2);
+    assert_eq!(decode_return_data(&pause.return_data.data).unwrap(), 4);
+
+    // Parameter changes are sequenced like every other change
+    let previous = h.vault(keys).min_deposit_amount;
+    let updated = h.send_ok(
+        &[svs1::set_min_deposit(keys, &authority, 2_000)],
+        &[&vault.authority],
+    );
+    let events = decode_logs(&updated.logs, &[svs_1::ID]);
+    assert!(matches!(
+        &events[..],
+        [(_, VaultEvent::MinDepositUpdated(event))]
+            if (event.previous, event.new) == (previous, 2_000)
+                && (event.op_counter, event.sequence) == (5, 5)
+    ));
+    let state = h.vault(keys);
+    assert_eq!((state.op_counter, state.event_sequence), (5, 5));
+}
+
+#[test]
+fn test_protocol_config() {
+    let (mut h, old_vault) = setup();
+    let upgrade_authority = h.payer.insecure_clone();
+    let stranger = h.funded_user();
+
+    let result = h.send(
+        &[svs1::initialize_config(
+            &svs_1::ID,
+            &stranger.pubkey(),
+            10_000,
+            6,
+            9,
+        )],
+        &[&stranger],
+    );
+    assert_error(result, VaultError::Unauthorized.into());
+    let result = h.send(
+        &[svs1::initialize_config(
+            &svs_1::ID,
+            &upgrade_authority.pubkey(),
+            10_000,
+            10,
+            9,
+        )],
+        &[],
+    );
+    assert_error(result, VaultError::InvalidConfig.into());
+
+    h.send_ok(
+        &[svs1::initialize_config(
+            &svs_1::ID,
+            &upgrade_authority.pubkey(),
+            10_000,
+            6,
+            9,
+        )],
+        &[],
+    );
+    let (config_address, _) = find_config_address(&svs_1::ID);
+    let config =
+        ProtocolConfig::try_deserialize(&mut &h.account_data(&config_address)[..]).unwrap();
+    assert_eq!(config.authority, upgrade_authority.pubkey());
+    assert_eq!(config.min_deposit_amount, 10_000);
+
+    // New vaults take the configured limits, existing ones keep theirs
+    let vault = h.create_svs1_vault(&old_vault.keys.asset_mint, 2);
+    let keys = &vault.keys;
+    assert_eq!(h.vault(keys).min_deposit_amount, 10_000);
+    assert_eq!(h.vault(&old_vault.keys).min_deposit(), 1_000);
+    let wide_mint = h.create_mint(&token::ID, 8);
+    let wide_keys = VaultKeys::derive(&svs_1::ID, &wide_mint, 1, &token::ID);
+    let result = h.send(
+        &[svs1::initialize(
+            &wide_keys,
+            &stranger.pubkey(),
+            1,
+            "Vault".to_string(),
+            "svV".to_string(),
+            String::new(),
+            false,
+        )],
+        &[&stranger],
+    );
+    assert_error(result, VaultError::InvalidAssetDecimals.into());
+
+    let alice = h.depositor(keys, 100_000);
+    let result = h.send(&[svs1::deposit(keys, &alice.pubkey(), 5_000, 0)], &[&alice]);
+    assert_error(result, VaultError::DepositTooSmall.into());
+
+    // The vault authority may raise its minimum, but not below the protocol's
+    let authority = vault.authority.pubkey();
+    let result = h.send(
+        &[svs1::set_min_deposit(keys, &authority, 9_999)],
+        &[&vault.authority],
+    );
+    assert_error(result, VaultError::MinDepositTooLow.into());
+    h.send_ok(
+        &[svs1::set_min_deposit(keys, &authority, 50_000)],
+        &[&vault.authority],
+    );
+    let result = h.send(
+        &[svs1::deposit(keys, &alice.pubkey(), 20_000, 0)],
+        &[&alice],
+    );
+    assert_error(result, VaultError::DepositTooSmall.into());
+    h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 50_000, 0)],
+        &[&alice],
+    );
+
+    // Only the config authority updates or hands over the config
+    let result = h.send(
+        &[svs1::update_config(&svs_1::ID, &stranger.pubkey(), 1, 9, 9)],
+        &[&stranger],
+    );
+    assert_error(result, VaultError::Unauthorized.into());
+    h.send_ok(
+        &[svs1::transfer_config_authority(
+            &svs_1::ID,
+            &upgrade_authority.pubkey(),
+            stranger.pubkey(),
+        )],
+        &[],
+    );
+    h.send_ok(
+        &[svs1::update_config(&svs_1::ID, &stranger.pubkey(), 1, 9, 9)],
+        &[&stranger],
+    );
+    h.send_ok(
+        &[svs1::initialize(
+            &wide_keys,
+            &stranger.pubkey(),
+            1,
+            "Vault".to_string(),
+            "svV".to_string(),
+            String::new(),
+            false,
+        )],
+        &[&stranger],
+    );
+    assert_eq!(h.vault(&wide_keys).decimals_offset, 1);
+}
+
+#[test]
+fn test_protocol_pause() {
+    let (mut h, vault) = setup();
+    let other = h.create_svs1_vault(&vault.keys.asset_mint, 2);
+    let config_authority = h.payer.pubkey();
+    let guardian = h.funded_user();
+    let stranger = h.funded_user();
+    let alice = h.depositor(&vault.keys, 3_000_000);
+    h.send_ok(
+        &[svs1::deposit(&vault.keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+
+    let configured = h.send_ok(
+        &[
+            svs1::initialize_config(&svs_1::ID, &config_authority, 1_000, 9, 9),
+            svs1::set_protocol_guardian(&svs_1::ID, &config_authority, guardian.pubkey()),
+        ],
+        &[],
+    );
+    let (config, _) = find_config_address(&svs_1::ID);
+    let events = decode_logs(&configured.logs, &[svs_1::ID]);
+    assert!(matches!(
+        &events[..],
+        [
+            (_, VaultEvent::ProtocolConfigUpdated(first)),
+            (_, VaultEvent::ProtocolConfigUpdated(second)),
+        ] if first.config == config
+            && first.guardian == Pubkey::default()
+            && second.guardian == guardian.pubkey()
+            && (first.sequence, second.sequence) == (1, 2)
+    ));
+    let result = h.send(
+        &[svs1::pause_protocol(&svs_1::ID, &stranger.pubkey())],
+        &[&stranger],
+    );
+    assert_error(result, VaultError::Unauthorized.into());
+
+    let paused = h.send_ok(
+        &[svs1::pause_protocol(&svs_1::ID, &guardian.pubkey())],
+        &[&guardian],
+    );
+    let events = decode_logs(&paused.logs, &[svs_1::ID]);
+    assert!(matches!(
+        &events[..],
+        [(_, VaultEvent::ProtocolStatusChanged(event))]
+            if event.paused && event.caller == guardian.pubkey() && event.sequence == 3
+    ));
+    let result = h.send(&[svs1::pause_protocol(&svs_1::ID, &config_authority)], &[]);
+    assert_error(result, VaultError::AlreadyPaused.into());
+
+    // Every vault halts, without touching their own pause flags
+    for keys in [&vault.keys, &other.keys] {
+        let result = h.send(
+            &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+            &[&alice],
+        );
+        assert_error(result, VaultError::ProtocolPaused.into());
+        assert!(!h.vault(keys).paused);
+    }
+    let result = h.send(
+        &[svs1::redeem(&vault.keys, &alice.pubkey(), 1_000, 0)],
+        &[&alice],
+    );
+    assert_error(result, VaultError::ProtocolPaused.into());
+
+    // The max views report the halt, while the other views keep working
+    let alice_key = alice.pubkey();
+    for view in [
+        View::MaxDeposit,
+        View::MaxMint,
+        View::MaxWithdraw(alice_key),
+        View::MaxRedeem(alice_key),
+    ] {
+        assert_eq!(h.view(&vault.keys, &view), 0, "{view:?}");
+    }
+    assert!(h.view(&vault.keys, &View::PreviewDeposit(1_000)) > 0);
+
+    h.send_ok(
+        &[svs1::unpause_protocol(&svs_1::ID, &guardian.pubkey())],
+        &[&guardian],
+    );
+    let result = h.send(
+        &[svs1::unpause_protocol(&svs_1::ID, &guardian.pubkey())],
+        &[&guardian],
+    );
+    assert_error(result, VaultError::ProtocolNotPaused.into());
+    assert_eq!(h.view(&vault.keys, &View::MaxDeposit), u64::MAX);
+    h.send_ok(
+        &[svs1::deposit(&other.keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+    h.send_ok(
+        &[svs1::redeem(&vault.keys, &alice.pubkey(), 1_000, 0)],
+        &[&alice],
+    );
+}
+
+#[test]
+fn test_transfer_authority() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let new_authority = h.funded_user();
+
+    h.send_ok(
+        &[svs1::transfer_authority(
+            keys,
+            &vault.authority.pubkey(),
+            new_authority.pubkey(),
+        )],
+        &[&vault.authority],
+    );
+    assert_eq!(h.vault(keys).authority, new_authority.pubkey());
+
+    let result = h.send(
+        &[svs1::pause(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+    assert_error(result, VaultError::Unauthorized.into());
+
+    h.send_ok(
+        &[svs1::pause(keys, &new_authority.pubkey())],
+        &[&new_authority],
+    );
+}
+
+#[test]
+fn test_token_2022_asset() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token_2022::ID, 9);
+    let vault = h.create_svs1_vault(&asset_mint, 7);
+    let keys = &vault.keys;
+    assert_eq!(keys.asset_token_program, token_2022::ID);
+    assert_eq!(h.vault(keys).decimals_offset, 0);
+
+    let alice = h.depositor(keys, 5_000_000);
+    h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 5_000_000, 5_000_000)],
+        &[&alice],
+    );
+    h.send_ok(
+        &[svs1::redeem(keys, &alice.pubkey(), 5_000_000, 5_000_000)],
+        &[&alice],
+    );
+
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 5_000_000);
+    assert_eq!(h.supply(&keys.shares_mint), 0);
+    assert_eq!(h.vault(keys).total_assets, 0);
+}
+
+#[test]
+fn test_cpi_guard() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token_2022::ID, 6);
+    let vault = h.create_svs1_vault(&asset_mint, 1);
+    let keys = &vault.keys;
+    let alice = h.depositor(keys, 1_000_000);
+    let alice_assets = keys.asset_account(&alice.pubkey());
+    h.enable_cpi_guard(&alice, &alice_assets);
+
+    let deposit = svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0);
+    let result = h.send(std::slice::from_ref(&deposit), &[&alice]);
+    assert_error(result, VaultError::CpiGuardEnabled.into());
+
+    // Lifting the guard around the deposit lets it through
+    let guard = |enable: bool| {
+        let toggle = if enable {
+            cpi_guard::instruction::enable_cpi_guard
+        } else {
+            cpi_guard::instruction::disable_cpi_guard
+        };
+        toggle(&token_2022::ID, &alice_assets, &alice.pubkey(), &[]).unwrap()
+    };
+    h.send_ok(&[guard(false), deposit, guard(true)], &[&alice]);
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 0);
+    assert_eq!(h.vault(keys).total_assets, 1_000_000);
+}
+
+#[test]
+fn test_vaults_per_asset_are_independent() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let first = h.create_svs1_vault(&asset_mint, 1);
+    let second = h.create_svs1_vault(&asset_mint, 2);
+    assert_ne!(first.keys.vault, second.keys.vault);
+    assert_ne!(first.keys.shares_mint, second.keys.shares_mint);
+
+    let alice = h.depositor(&first.keys, 1_000_000);
+    h.send_ok(
+        &[svs1::deposit(&first.keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+    assert_eq!(h.vault(&first.keys).total_assets, 1_000_000);
+    assert_eq!(h.vault(&second.keys).total_assets, 0);
+    assert_eq!(h.supply(&second.keys.shares_mint), 0);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_assert_invariants() {
+    use anchor_lang::AccountSerialize;
+
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.depositor(keys, 1_000_000);
+    h.send_ok(&[svs1::assert_invariants(keys)], &[]);
+    h.send_ok(
+        &[svs1::deposit(keys, &alice.pubkey(), 1_000_000, 0)],
+        &[&alice],
+    );
+    h.send_ok(&[svs1::assert_invariants(keys)], &[]);
+
+    // Record an asset the asset vault doesn't hold
+    let mut state = h.vault(keys);
+    state.total_assets += 1;
+    let mut account = h.svm.get_account(&keys.vault).unwrap();
+    account.data.clear();
+    state.try_serialize(&mut account.data).unwrap();
+    h.svm.set_account(keys.vault, account).unwrap();
+
+    let result = h.send(&[svs1::assert_invariants(keys)], &[]);
+    assert_error(result, VaultError::InvariantViolated.into());
+}
@@ -0,0 +1,1164 @@
+//! End-to-end tests of every SVS-2 instruction
+//!
+//! Expected amounts are golden values of the vault math for a 6-decimal
+//! asset (decimals offset 3). Confidential balances are checked by
+//! decrypting the users' shares accounts.
+
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::{
+    token,
+    token_2022::{
+        self,
+        spl_token_2022::{
+            self,
+            error::TokenError,
+            extension::{
+                default_account_state, interest_bearing_mint, metadata_pointer, transfer_fee,
+                transfer_hook, ExtensionType,
+            },
+            instruction as token_instruction,
+            state::AccountState,
+        },
+    },
+};
+use mock_governance::GOVERNANCE_SEED;
+use mock_swap_adapter::POOL_SEED;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    instruction::ProofInstruction, proof_data::PubkeyValidityProofData,
+};
+use svs_2::{
+    constants::{DEFAULT_PROFIT_UNLOCK_PERIOD, WITHDRAW_MIN_COMPUTE_UNITS},
+    error::VaultError,
+    state::{ConfidentialVault, PermitReceipt},
+};
+use svs_client::{
+    confidential::ConfidentialKeys,
+    metadata_hash,
+    pda::{
+        find_harvest_authority_address, find_permit_delegate_address, find_permit_receipt_address,
+    },
+    svs2::{
+        self, deposit_permit_message, permit_signature_verification, PubkeyValidityProof,
+        WithdrawProofContexts,
+    },
+    view::View,
+    VaultKeys,
+};
+use svs_events::{logs::decode_logs, VaultEvent};
+use svs_test_harness::{
+    assert_error, assert_instruction_error, confidential::CONFIDENTIAL_COMPUTE_UNITS, Harness,
+    TestVault, START_TIMESTAMP,
+};
+
+const AUDITOR: [u8; 32] = [7; 32];
+
+/// Instructions initializing one extension on the shares mint at the given address
+type ExtensionInit<'a> = Box<dyn FnOnce(&Pubkey) -> Vec<Instruction> + 'a>;
+
+fn setup() -> (Harness, TestVault) {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let vault = h.create_svs2_vault(&asset_mint, 1, None);
+    (h, vault)
+}
+
+/// Permit signed by `owner` and the instructions relaying it
+fn permit(
+    keys: &VaultKeys,
+    relayer: &Pubkey,
+    owner: &Keypair,
+    assets: u64,
+    min_shares_out: u64,
+    nonce: u64,
+    expires_at: i64,
+) -> [Instruction; 2] {
+    let message = deposit_permit_message(
+        &keys.vault,
+        &owner.pubkey(),
+        assets,
+        min_shares_out,
+        nonce,
+        expires_at,
+    );
+    let signature: [u8; 64] = owner.sign_message(&message).into();
+    [
+        permit_signature_verification(&owner.pubkey(), &signature, &message),
+        svs2::deposit_with_permit(
+            keys,
+            relayer,
+            &owner.pubkey(),
+            assets,
+            min_shares_out,
+            nonce,
+            expires_at,
+        ),
+    ]
+}
+
+/// Owner holding `assets` who approved the permit delegate and has a public
+/// shares account, ready for permit deposits
+fn permit_owner(h: &mut Harness, keys: &VaultKeys, assets: u64) -> Keypair {
+    let owner = h.depositor(keys, assets);
+    h.create_ata(&owner.pubkey(), &keys.shares_mint, &token_2022::ID);
+    let (permit_delegate, _) = find_permit_delegate_address(&svs_2::ID, &keys.vault);
+    h.approve(
+        &owner,
+        &keys.asset_account(&owner.pubkey()),
+        &permit_delegate,
+        assets,
+    );
+    owner
+}
+
+#[test]
+fn test_initialize() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let vault = h.create_svs2_vault(&asset_mint, 1, Some(AUDITOR));
+    let keys = &vault.keys;
+
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.authority, vault.authority.pubkey());
+    assert_eq!(state.asset_mint, asset_mint);
+    assert_eq!(state.shares_mint, keys.shares_mint);
+    assert_eq!(state.asset_vault, keys.asset_vault);
+    assert_eq!(state.decimals_offset, 3);
+    assert_eq!(state.auditor_elgamal_pubkey, Some(AUDITOR));
+    assert_eq!(state.confidential_authority, keys.vault);
+    assert_eq!(state.guardian, Pubkey::default());
+    assert_eq!(state.swap_adapter, Pubkey::default());
+    assert_eq!(state.profit_unlock_period, DEFAULT_PROFIT_UNLOCK_PERIOD);
+    assert_eq!((state.total_assets, h.supply(&keys.shares_mint)), (0, 0));
+    assert!(!state.paused);
+    assert_eq!(
+        state.metadata_hash,
+        metadata_hash(
+            "SVS-2 Test Vault",
+            "csTEST",
+            "https://example.com/svs-2.json"
+        )
+    );
+
+    assert_eq!(h.owner_program(&keys.shares_mint), token_2022::ID);
+    assert_eq!(h.decimals(&keys.shares_mint), 9);
+}
+
+#[test]
+fn test_initialize_with_shares_mint() {
+    let mut h = Harness::new();
+    let asset_mint = h.create_mint(&token::ID, 6);
+    let authority = h.funded_user();
+    let payer = h.payer.pubkey();
+    let mut keys = VaultKeys::derive(&svs_2::ID, &asset_mint, 1, &token::ID);
+    let initialize = |keys: &VaultKeys| {
+        svs2::initialize_with_shares_mint(
+            keys,
+            &payer,
+            &authority.pubkey(),
+            1,
+            "SVS-2 Test Vault".to_string(),
+            "csTEST".to_string(),
+            "https://example.com/svs-2.json".to_string(),
+        )
+    };
+
+    // The vault must be the mint authority
+    keys.shares_mint = h.create_shares_mint(&authority.pubkey(), None);
+    let result = h.send(&[initialize(&keys)], &[&authority]);
+    assert_error(result, VaultError::InvalidSharesMint.into());
+
+    // Extensions outside the allowlist are rejected. Pausable is too, but
+    // the Token-2022 version bundled with LiteSVM cannot create it.
+    let rejected: [(ExtensionType, ExtensionInit<'_>); 6] = [
+        (
+            ExtensionType::TransferFeeConfig,
+            Box::new(|mint| {
+                vec![transfer_fee::instruction::initialize_transfer_fee_config(
+                    &spl_token_2022::ID,
+                    mint,
+                    Some(&payer),
+                    Some(&payer),
+                    100,
+                    u64::MAX,
+                )
+                .unwrap()]
+            }),
+        ),
+        (
+            ExtensionType::TransferHook,
+            Box::new(|mint| {
+                vec![transfer_hook::instruction::initialize(
+                    &spl_token_2022::ID,
+                    mint,
+                    Some(payer),
+                    Some(Pubkey::new_unique()),
+                )
+                .unwrap()]
+            }),
+        ),
+        (
+            ExtensionType::DefaultAccountState,
+            Box::new(|mint| {
+                vec![
+                    default_account_state::instruction::initialize_default_account_state(
+                        &spl_token_2022::ID,
+                        mint,
+                        &AccountState::Frozen,
+                    )
+                    .unwrap(),
+                ]
+            }),
+        ),
+        (
+            ExtensionType::PermanentDelegate,
+            Box::new(|mint| {
+                vec![token_instruction::initialize_permanent_delegate(
+                    &spl_token_2022::ID,
+                    mint,
+                    &payer,
+                )
+                .unwrap()]
+            }),
+        ),
+        (
+            ExtensionType::MintCloseAuthority,
+            Box::new(|mint| {
+                vec![token_instruction::initialize_mint_close_authority(
+                    &spl_token_2022::ID,
+                    mint,
+                    Some(&payer),
+                )
+                .unwrap()]
+            }),
+        ),
+        (
+            ExtensionType::InterestBearingConfig,
+            Box::new(|mint| {
+                vec![interest_bearing_mint::instruction::initialize(
+                    &spl_token_2022::ID,
+                    mint,
+                    Some(payer),
+                    100,
+                )
+                .unwrap()]
+            }),
+        ),
+    ];
+    for (extension, init) in rejected {
+        keys.shares_mint = h.create_shares_mint_with(&keys.vault, None, &[extension], init);
+        let result = h.send(&[initialize(&keys)], &[&authority]);
+        assert_error(result, VaultError::InvalidSharesMint.into());
+    }
+
+    // Metadata is allowed
+    keys.shares_mint = h.create_shares_mint_with(
+        &keys.vault,
+        Some(AUDITOR),
+        &[ExtensionType::MetadataPointer],
+        |mint| {
+            vec![metadata_pointer::instruction::initialize(
+                &spl_token_2022::ID,
+                mint,
+                Some(payer),
+                Some(*mint),
+            )
+            .unwrap()]
+        },
+    );
+    h.send_ok(&[initialize(&keys)], &[&authority]);
+
+    let state = h.confidential_vault(&keys);
+    assert_eq!(state.shares_mint, keys.shares_mint);
+    assert_eq!(state.auditor_elgamal_pubkey, Some(AUDITOR));
+
+    let alice = h.confidential_user(&keys, 1_000_000);
+    alice
+        .deposit(&mut h, &keys, 1_000_000, 1_000_000_000)
+        .expect("deposit into the custom shares mint");
+    assert_eq!(alice.pending_shares(&h), 1_000_000_000);
+}
+
+#[test]
+fn test_configure_deposit_apply_pending_mint() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.confidential_user(keys, 2_000_000);
+    assert_eq!(alice.available_shares(&h), 0);
+
+    alice
+        .deposit(&mut h, keys, 1_000_000, 1_000_000_000)
+        .expect("deposit");
+    // Shares arrive pending and leave no trace in the public balance
+    assert_eq!(alice.pending_shares(&h), 1_000_000_000);
+    assert_eq!(h.balance(&alice.shares_account), 0);
+
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(alice.available_shares(&h), 1_000_000_000);
+    assert_eq!(alice.pending_shares(&h), 0);
+
+    alice
+        .mint(&mut h, keys, 500_000_000, 500_000)
+        .expect("mint");
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(alice.available_shares(&h), 1_500_000_000);
+
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.total_assets, 1_500_000);
+    assert_eq!(h.supply(&keys.shares_mint), 1_500_000_000);
+    assert_eq!(h.balance(&keys.asset_vault), 1_500_000);
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 500_000);
+
+    let result = alice.deposit(&mut h, keys, 999, 0);
+    assert_error(result, VaultError::DepositTooSmall.into());
+}
+
+#[test]
+fn test_deposit_requires_configured_account() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let bob = h.depositor(keys, 1_000_000);
+    h.create_ata(&bob.pubkey(), &keys.shares_mint, &token_2022::ID);
+
+    let result = h.send(
+        &[svs2::deposit(keys, &bob.pubkey(), 1_000_000, 0, None)],
+        &[&bob],
+    );
+    assert!(
+        result.is_err(),
+        "deposit into an unconfigured shares account"
+    );
+    assert_eq!(h.confidential_vault(keys).total_assets, 0);
+}
+
+#[test]
+fn test_withdraw_and_redeem() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.confidential_user(keys, 1_500_000);
+    alice.deposit(&mut h, keys, 1_500_000, 0).expect("deposit");
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(alice.available_shares(&h), 1_500_000_000);
+
+    // Withdraw rounds the burned shares up; the proofs are bound to them
+    alice
+        .withdraw(&mut h, keys, 300_000, 300_000_000)
+        .expect("withdraw");
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 300_000);
+    assert_eq!(alice.available_shares(&h), 1_200_000_000);
+
+    let result = alice.redeem(&mut h, keys, 200_000_000, 200_001);
+    assert_error(result, VaultError::SlippageExceeded.into());
+
+    // Proof contexts passed in each other's place
+    let proven = alice.prove_withdraw(&mut h, 100_000_000);
+    let swapped = WithdrawProofContexts {
+        equality: proven.contexts.range,
+        range: proven.contexts.equality,
+    };
+    let withdraw = svs2::withdraw(
+        keys,
+        &alice.pubkey(),
+        &swapped,
+        100_000,
+        100_000_000,
+        proven.new_decryptable_available_balance,
+        None,
+    );
+    let result = h.send(&[withdraw], &[&alice.wallet]);
+    assert_error(result, VaultError::ProofTypeMismatch.into());
+
+    alice
+        .redeem(&mut h, keys, 200_000_000, 200_000)
+        .expect("redeem");
+    assert_eq!(h.balance(&keys.asset_account(&alice.pubkey())), 500_000);
+    assert_eq!(alice.available_shares(&h), 1_000_000_000);
+
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.total_assets, 1_000_000);
+    assert_eq!(h.supply(&keys.shares_mint), 1_000_000_000);
+    assert_eq!(h.balance(&keys.asset_vault), 1_000_000);
+}
+
+#[test]
+fn test_cpi_guard() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.confidential_user(keys, 1_000_000);
+    alice.deposit(&mut h, keys, 1_000_000, 0).expect("deposit");
+    alice.apply_pending(&mut h, keys);
+
+    // Withdraw and redeem burn from the shares account, which CPI Guard forbids
+    h.enable_cpi_guard(&alice.wallet, &alice.shares_account);
+    let result = alice.withdraw(&mut h, keys, 100_000, 100_000_000);
+    assert_error(result, VaultError::CpiGuardEnabled.into());
+    let result = alice.redeem(&mut h, keys, 100_000_000, 0);
+    assert_error(result, VaultError::CpiGuardEnabled.into());
+    assert_eq!(alice.available_shares(&h), 1_000_000_000);
+}
+
+#[test]
+fn test_withdraw_compute_units() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.confidential_user(keys, 1_000_000);
+    alice.deposit(&mut h, keys, 1_000_000, 0).expect("deposit");
+    alice.apply_pending(&mut h, keys);
+    let limit =
+        |units: u64| ComputeBudgetInstruction::set_compute_unit_limit(units.try_into().unwrap());
+
+    println!("| Instruction | CU |");
+    println!("|-------------|----|");
+    for name in ["withdraw", "redeem"] {
+        let proven = alice.prove_withdraw(&mut h, 100_000_000);
+        let instruction = match name {
+            "withdraw" => svs2::withdraw(
+                keys,
+                &alice.pubkey(),
+                &proven.contexts,
+                100_000,
+                100_000_000,
+                proven.new_decryptable_available_balance,
+                None,
+            ),
+            _ => svs2::redeem(
+                keys,
+                &alice.pubkey(),
+                &proven.contexts,
+                100_000_000,
+                0,
+                proven.new_decryptable_available_balance,
+            ),
+        };
+
+        // Short of the minimum, the guard fails before the first CPI
+        let result = h.send(
+            &[limit(WITHDRAW_MIN_COMPUTE_UNITS - 1), instruction.clone()],
+            &[&alice.wallet],
+        );
+        assert_error(result, VaultError::InsufficientComputeBudget.into());
+
+        let meta = h.send_ok(
+            &[limit(CONFIDENTIAL_COMPUTE_UNITS.into()), instruction],
+            &[&alice.wallet],
+        );
+        println!("| {name} | {} |", meta.compute_units_consumed);
+        assert!(
+            meta.compute_units_consumed <= WITHDRAW_MIN_COMPUTE_UNITS,
+            "{name} used {} CU, more than WITHDRAW_MIN_COMPUTE_UNITS",
+            meta.compute_units_consumed
+        );
+    }
+    assert_eq!(alice.available_shares(&h), 800_000_000);
+}
+
+#[test]
+fn test_deposit_with_permit() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let owner = permit_owner(&mut h, keys, 3_000_000);
+    let relayer = h.funded_user();
+    let expires_at = START_TIMESTAMP + 3_600;
+
+    let instructions = permit(
+        keys,
+        &relayer.pubkey(),
+        &owner,
+        1_000_000,
+        1_000_000_000,
+        0,
+        expires_at,
+    );
+    h.send_ok(&instructions, &[&relayer]);
+    assert_eq!(
+        h.balance(&keys.shares_account(&owner.pubkey())),
+        1_000_000_000
+    );
+    assert_eq!(h.balance(&keys.asset_account(&owner.pubkey())), 2_000_000);
+
+    let (receipt, _) = find_permit_receipt_address(&svs_2::ID, &keys.vault, &owner.pubkey(), 0);
+    let receipt = h.account_data(&receipt);
+    assert_eq!(receipt.len(), PermitReceipt::LEN);
+
+    // The receipt blocks replaying the same nonce
+    let result = h.send(&instructions, &[&relayer]);
+    assert!(result.is_err(), "permit replay");
+
+    // The signature must cover exactly the relayed arguments
+    let [verify, _] = permit(keys, &relayer.pubkey(), &owner, 1_000_000, 0, 1, expires_at);
+    let [_, tampered] = permit(keys, &relayer.pubkey(), &owner, 2_000_000, 0, 1, expires_at);
+    let result = h.send(&[verify, tampered], &[&relayer]);
+    assert_error(result, VaultError::InvalidPermit.into());
+
+    h.set_unix_timestamp(expires_at + 1);
+    let instructions = permit(keys, &relayer.pubkey(), &owner, 1_000_000, 0, 1, expires_at);
+    let result = h.send(&instructions, &[&relayer]);
+    assert_error(result, VaultError::PermitExpired.into());
+
+    // An allowance to the vault PDA itself is not spent by permits
+    h.set_unix_timestamp(START_TIMESTAMP);
+    let bob = h.depositor(keys, 1_000_000);
+    h.create_ata(&bob.pubkey(), &keys.shares_mint, &token_2022::ID);
+    h.approve(
+        &bob,
+        &keys.asset_account(&bob.pubkey()),
+        &keys.vault,
+        1_000_000,
+    );
+    let instructions = permit(keys, &relayer.pubkey(), &bob, 1_000_000, 0, 0, expires_at);
+    let result = h.send(&instructions, &[&relayer]);
+    assert_error(result, TokenError::OwnerMismatch as u32);
+
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.total_assets, 1_000_000);
+    assert_eq!(h.supply(&keys.shares_mint), 1_000_000_000);
+}
+
+#[test]
+fn test_emergency_redeem() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let owner = permit_owner(&mut h, keys, 2_000_000);
+    let relayer = h.funded_user();
+    let instructions = permit(
+        keys,
+        &relayer.pubkey(),
+        &owner,
+        2_000_000,
+        0,
+        0,
+        START_TIMESTAMP,
+    );
+    h.send_ok(&instructions, &[&relayer]);
+
+    let redeem = svs2::emergency_redeem(keys, &owner.pubkey(), 500_000_000);
+    let result = h.send(std::slice::from_ref(&redeem), &[&owner]);
+    assert_error(result, VaultError::VaultNotPaused.into());
+
+    h.send_ok(
+        &[svs2::pause(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+    h.send_ok(&[redeem], &[&owner]);
+
+    // Pro rata against the asset vault balance, without the virtual offset
+    assert_eq!(h.balance(&keys.asset_account(&owner.pubkey())), 500_000);
+    assert_eq!(
+        h.balance(&keys.shares_account(&owner.pubkey())),
+        1_500_000_000
+    );
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.total_assets, 1_500_000);
+    assert_eq!(h.supply(&keys.shares_mint), 1_500_000_000);
+}
+
+#[test]
+fn test_resume_confidential_deposit() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let alice = h.confidential_user(keys, 1_000_000);
+    let (permit_delegate, _) = find_permit_delegate_address(&svs_2::ID, &keys.vault);
+    h.approve(
+        &alice.wallet,
+        &keys.asset_account(&alice.pubkey()),
+        &permit_delegate,
+        1_000_000,
+    );
+    let relayer = h.funded_user();
+    let instructions = permit(
+        keys,
+        &relayer.pubkey(),
+        &alice.wallet,
+        1_000_000,
+        0,
+        0,
+        START_TIMESTAMP,
+    );
+    h.send_ok(&instructions, &[&relayer]);
+    // The permit deposit leaves the shares in the public balance
+    assert_eq!(h.balance(&alice.shares_account), 1_000_000_000);
+    assert_eq!(alice.pending_shares(&h), 0);
+
+    let resume = svs2::resume_confidential_deposit(keys, &alice.pubkey(), 1_000_000_001);
+    let result = h.send(&[resume], &[&alice.wallet]);
+    assert_error(result, VaultError::InsufficientShares.into());
+
+    let sequence = h.confidential_vault(keys).event_sequence;
+    let resume = svs2::resume_confidential_deposit(keys, &alice.pubkey(), 1_000_000_000);
+    h.send_ok(&[resume], &[&alice.wallet]);
+    assert_eq!(h.balance(&alice.shares_account), 0);
+    assert_eq!(alice.pending_shares(&h), 1_000_000_000);
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(alice.available_shares(&h), 1_000_000_000);
+
+    // Only the event is recorded; the vault totals already include the shares
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.event_sequence, sequence + 1);
+    assert_eq!(h.supply(&keys.shares_mint), 1_000_000_000);
+}
+
+#[test]
+fn test_harvest_streams_profit() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+    let alice = h.confidential_user(keys, 1_000_000);
+    alice.deposit(&mut h, keys, 1_000_000, 0).expect("deposit");
+
+    let (harvest_authority, _) = find_harvest_authority_address(&svs_2::ID, &keys.vault);
+    let rewards = h.create_token_account(&harvest_authority, &keys.asset_mint, &token::ID);
+    h.mint_to(&keys.asset_mint, &rewards, 600_000);
+    // The default adapter is the system program; asset rewards are not swapped
+    let harvest = |caller: &Pubkey, reward_account: &Pubkey| {
+        svs2::harvest(
+            keys,
+            caller,
+            reward_account,
+            &system_program::ID,
+            600_000,
+            &[],
+        )
+    };
+
+    let stranger = h.funded_user();
+    let result = h.send(&[harvest(&stranger.pubkey(), &rewards)], &[&stranger]);
+    assert_error(result, VaultError::Unauthorized.into());
+
+    // Rewards must sit with the harvest authority, even ones the vault owns
+    let vault_rewards = h.create_token_account(&keys.vault, &keys.asset_mint, &token::ID);
+    h.mint_to(&keys.asset_mint, &vault_rewards, 600_000);
+    let result = h.send(&[harvest(&authority, &vault_rewards)], &[&vault.authority]);
+    assert_error(result, VaultError::Unauthorized.into());
+
+    let result = h.send(
+        &[harvest(&authority, &keys.asset_vault)],
+        &[&vault.authority],
+    );
+    assert_error(result, VaultError::InvalidRewardAccount.into());
+
+    h.send_ok(&[harvest(&authority, &rewards)], &[&vault.authority]);
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.total_assets, 1_600_000);
+    assert_eq!(state.locked_profit, 600_000);
+    assert_eq!(state.last_harvest_ts, START_TIMESTAMP);
+    assert_eq!(h.balance(&rewards), 0);
+    assert_eq!(h.balance(&keys.asset_vault), 1_600_000);
+
+    // The profit unlocks linearly over the unlock period
+    for (total_assets, redeemed) in [
+        (1_000_000, 1_000_000),
+        (1_300_000, 1_299_999),
+        (1_600_000, 1_599_999),
+    ] {
+        assert_eq!(h.view(keys, &View::TotalAssets), total_assets);
+        assert_eq!(h.view(keys, &View::PreviewRedeem(1_000_000_000)), redeemed);
+        h.warp(DEFAULT_PROFIT_UNLOCK_PERIOD / 2);
+    }
+
+    let result = h.send(&[harvest(&authority, &rewards)], &[&vault.authority]);
+    assert_error(result, VaultError::ZeroAmount.into());
+}
+
+#[test]
+fn test_harvest_swap_adapter() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+    let alice = h.confidential_user(keys, 1_000_000);
+    alice.deposit(&mut h, keys, 1_000_000, 0).expect("deposit");
+    h.send_ok(
+        &[svs2::set_harvest_config(
+            keys,
+            &authority,
+            mock_swap_adapter::ID,
+            Pubkey::default(),
+            DEFAULT_PROFIT_UNLOCK_PERIOD,
+        )],
+        &[&vault.authority],
+    );
+
+    let (harvest_authority, _) = find_harvest_authority_address(&svs_2::ID, &keys.vault);
+    let reward_mint = h.create_mint(&token::ID, 6);
+    let rewards = h.create_token_account(&harvest_authority, &reward_mint, &token::ID);
+    h.mint_to(&reward_mint, &rewards, 500_000);
+    let (pool, _) = Pubkey::find_program_address(&[POOL_SEED], &mock_swap_adapter::ID);
+    let pool_rewards = h.create_token_account(&pool, &reward_mint, &token::ID);
+    let pool_assets = h.create_token_account(&pool, &keys.asset_mint, &token::ID);
+    h.mint_to(&keys.asset_mint, &pool_assets, 10_000_000);
+    // Harvest passes the first three accounts itself
+    let adapter_accounts = mock_swap_adapter::accounts::Swap {
+        authority: harvest_authority,
+        reward_token_account: rewards,
+        asset_vault: keys.asset_vault,
+        reward_mint,
+        asset_mint: keys.asset_mint,
+        pool,
+        pool_rewards,
+        pool_assets,
+        token_program: token::ID,
+    }
+    .to_account_metas(None)
+    .split_off(3);
+    let harvest = |attack_authority: Option<AccountMeta>| {
+        let accounts: Vec<_> = adapter_accounts
+            .iter()
+            .cloned()
+            .chain(attack_authority)
+            .collect();
+        svs2::harvest(
+            keys,
+            &authority,
+            &rewards,
+            &mock_swap_adapter::ID,
+            500_000,
+            &accounts,
+        )
+    };
+
+    // An adapter trying to empty the asset vault gets nowhere: the harvest
+    // authority does not own it, and neither the vault's signature nor the
+    // caller's is passed on
+    let result = h.send(
+        &[harvest(Some(AccountMeta::new_readonly(
+            harvest_authority,
+            false,
+        )))],
+        &[&vault.authority],
+    );
+    assert_error(result, TokenError::OwnerMismatch as u32);
+    for attack_authority in [
+        AccountMeta::new_readonly(keys.vault, false),
+        AccountMeta::new_readonly(authority, true),
+    ] {
+        let result = h.send(&[harvest(Some(attack_authority))], &[&vault.authority]);
+        assert_instruction_error(result, InstructionError::PrivilegeEscalation);
+    }
+    assert_eq!(h.balance(&keys.asset_vault), 1_000_000);
+    assert_eq!(h.balance(&rewards), 500_000);
+
+    // The honest swap is recorded from the asset vault balance
+    h.send_ok(&[harvest(None)], &[&vault.authority]);
+    assert_eq!(h.balance(&keys.asset_vault), 1_500_000);
+    assert_eq!(h.balance(&rewards), 0);
+    assert_eq!(h.balance(&pool_rewards), 500_000);
+    assert_eq!(h.supply(&keys.shares_mint), 1_000_000_000);
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.total_assets, 1_500_000);
+    assert_eq!(state.locked_profit, 500_000);
+}
+
+#[test]
+fn test_guardian_and_admin() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+    let guardian = h.funded_user();
+    let harvester = h.funded_user();
+
+    h.send_ok(
+        &[svs2::set_guardian(keys, &authority, guardian.pubkey())],
+        &[&vault.authority],
+    );
+    h.send_ok(&[svs2::pause(keys, &guardian.pubkey())], &[&guardian]);
+    assert!(h.confidential_vault(keys).paused);
+    let result = h.send(&[svs2::pause(keys, &guardian.pubkey())], &[&guardian]);
+    assert_error(result, VaultError::AlreadyPaused.into());
+
+    // Pausing is all the guardian can do
+    let result = h.send(&[svs2::unpause(keys, &guardian.pubkey())], &[&guardian]);
+    assert_error(result, VaultError::Unauthorized.into());
+    h.send_ok(&[svs2::unpause(keys, &authority)], &[&vault.authority]);
+    assert!(!h.confidential_vault(keys).paused);
+
+    let result = h.send(
+        &[svs2::set_harvest_config(
+            keys,
+            &authority,
+            Pubkey::default(),
+            harvester.pubkey(),
+            -1,
+        )],
+        &[&vault.authority],
+    );
+    assert_error(result, VaultError::InvalidProfitUnlockPeriod.into());
+    h.send_ok(
+        &[svs2::set_harvest_config(
+            keys,
+            &authority,
+            Pubkey::default(),
+            harvester.pubkey(),
+            3_600,
+        )],
+        &[&vault.authority],
+    );
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.harvester, harvester.pubkey());
+    assert_eq!(state.profit_unlock_period, 3_600);
+
+    // The harvester may harvest but not administer
+    let (harvest_authority, _) = find_harvest_authority_address(&svs_2::ID, &keys.vault);
+    let rewards = h.create_token_account(&harvest_authority, &keys.asset_mint, &token::ID);
+    h.mint_to(&keys.asset_mint, &rewards, 1_000);
+    h.send_ok(
+        &[svs2::harvest(
+            keys,
+            &harvester.pubkey(),
+            &rewards,
+            &system_program::ID,
+            0,
+            &[],
+        )],
+        &[&harvester],
+    );
+    let result = h.send(&[svs2::pause(keys, &harvester.pubkey())], &[&harvester]);
+    assert_error(result, VaultError::Unauthorized.into());
+
+    let new_authority = h.funded_user();
+    h.send_ok(
+        &[svs2::transfer_authority(
+            keys,
+            &authority,
+            new_authority.pubkey(),
+        )],
+        &[&vault.authority],
+    );
+    assert_eq!(h.confidential_vault(keys).authority, new_authority.pubkey());
+    let result = h.send(&[svs2::sync(keys, &authority)], &[&vault.authority]);
+    assert_error(result, VaultError::Unauthorized.into());
+}
+
+/// `instruction` executed by the mock governance program, signed by its PDA
+fn governance_execute(instruction: Instruction) -> Instruction {
+    let (governance, _) = Pubkey::find_program_address(&[GOVERNANCE_SEED], &mock_governance::ID);
+    let mut accounts = mock_governance::accounts::Execute {
+        governance,
+        target_program: instruction.program_id,
+    }
+    .to_account_metas(None);
+    // The PDA signs inside the CPI, not the transaction
+    accounts.extend(instruction.accounts.into_iter().map(|meta| AccountMeta {
+        is_signer: meta.is_signer && meta.pubkey != governance,
+        ..meta
+    }));
+    Instruction {
+        program_id: mock_governance::ID,
+        accounts,
+        data: mock_governance::instruction::Execute {
+            data: instruction.data,
+        }
+        .data(),
+    }
+}
+
+#[test]
+fn test_pda_authority() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let (governance, _) = Pubkey::find_program_address(&[GOVERNANCE_SEED], &mock_governance::ID);
+    h.send_ok(
+        &[svs2::transfer_authority(
+            keys,
+            &vault.authority.pubkey(),
+            governance,
+        )],
+        &[&vault.authority],
+    );
+
+    h.send_ok(&[governance_execute(svs2::pause(keys, &governance))], &[]);
+    assert!(h.confidential_vault(keys).paused);
+    h.send_ok(&[governance_execute(svs2::unpause(keys, &governance))], &[]);
+    assert!(!h.confidential_vault(keys).paused);
+
+    let new_authority = h.funded_user();
+    h.send_ok(
+        &[governance_execute(svs2::transfer_authority(
+            keys,
+            &governance,
+            new_authority.pubkey(),
+        ))],
+        &[],
+    );
+    assert_eq!(h.confidential_vault(keys).authority, new_authority.pubkey());
+
+    // Still signed by the PDA, which is no longer the authority
+    let result = h.send(&[governance_execute(svs2::pause(keys, &governance))], &[]);
+    assert_error(result, VaultError::Unauthorized.into());
+}
+
+#[test]
+fn test_sync_and_views() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let owner = permit_owner(&mut h, keys, 2_000_000);
+    let owner_key = owner.pubkey();
+    let relayer = h.funded_user();
+    let instructions = permit(
+        keys,
+        &relayer.pubkey(),
+        &owner,
+        2_000_000,
+        0,
+        0,
+        START_TIMESTAMP,
+    );
+    h.send_ok(&instructions, &[&relayer]);
+
+    let donor = h.depositor(keys, 500_000);
+    h.transfer(
+        &donor,
+        &keys.asset_account(&donor.pubkey()),
+        &keys.asset_mint,
+        &keys.asset_vault,
+        500_000,
+    );
+    h.send_ok(
+        &[svs2::sync(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+    assert_eq!(h.confidential_vault(keys).total_assets, 2_500_000);
+
+    // 2_500_000 assets backing 2 * 10^9 shares; the owner's are public
+    for (view, expected) in [
+        (View::PreviewDeposit(1_000_000), 800_000_079),
+        (View::PreviewMint(1_000_000_000), 1_250_000),
+        (View::PreviewWithdraw(1_000_000), 800_000_080),
+        (View::PreviewRedeem(1_000_000_000), 1_249_999),
+        (View::ConvertToShares(1_000_000), 800_000_079),
+        (View::ConvertToAssets(1_000_000_000), 1_249_999),
+        (View::SharePrice, 1_249_999_875_000),
+        (View::TotalAssets, 2_500_000),
+        (View::MaxDeposit, u64::MAX),
+        (View::MaxMint, u64::MAX),
+        (View::MaxWithdraw(owner_key), 2_499_999),
+        (View::MaxRedeem(owner_key), 2_000_000_000),
+    ] {
+        assert_eq!(h.view(keys, &view), expected, "{view:?}");
+    }
+
+    h.send_ok(
+        &[svs2::pause(keys, &vault.authority.pubkey())],
+        &[&vault.authority],
+    );
+    for view in [
+        View::MaxDeposit,
+        View::MaxMint,
+        View::MaxWithdraw(owner_key),
+        View::MaxRedeem(owner_key),
+    ] {
+        assert_eq!(h.view(keys, &view), 0, "{view:?} while paused");
+    }
+}
+
+/// `assets` of the `Deposit` or `Withdraw` event in `logs`
+fn event_assets(logs: &[String]) -> u64 {
+    decode_logs(logs, &[svs_2::ID])
+        .into_iter()
+        .find_map(|(_, event)| match event {
+            VaultEvent::Deposit(deposit) => Some(deposit.assets),
+            VaultEvent::Withdraw(withdraw) => Some(withdraw.assets),
+            _ => None,
+        })
+        .expect("deposit or withdraw event")
+}
+
+#[test]
+fn test_transfer_fee_asset() {
+    let mut h = Harness::new();
+    // 1% on every transfer
+    let asset_mint = h.create_transfer_fee_mint(6, 100, 1_000_000_000_000);
+    let vault = h.create_svs2_vault(&asset_mint, 1, None);
+    let keys = &vault.keys;
+    let alice = h.confidential_user(keys, 1_200_000);
+    let alice_assets = keys.asset_account(&alice.pubkey());
+
+    // Shares are priced on the 990_000 that reaches the vault; the
+    // conversion views ignore the fee
+    assert_eq!(h.view(keys, &View::PreviewDeposit(1_000_000)), 990_000_000);
+    assert_eq!(
+        h.view(keys, &View::ConvertToShares(1_000_000)),
+        1_000_000_000
+    );
+    let meta = alice
+        .deposit(&mut h, keys, 1_000_000, 990_000_000)
+        .expect("deposit");
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(alice.available_shares(&h), 990_000_000);
+    assert_eq!(h.confidential_vault(keys).total_assets, 990_000);
+    assert_eq!(h.balance(&keys.asset_vault), 990_000);
+    assert_eq!(event_assets(&meta.logs), 990_000);
+
+    // Withdrawing 99_000 sends 100_000 so the user nets exactly 99_000
+    assert_eq!(h.view(keys, &View::PreviewWithdraw(99_000)), 100_000_000);
+    let meta = alice
+        .withdraw(&mut h, keys, 99_000, 100_000_000)
+        .expect("withdraw");
+    assert_eq!(h.balance(&alice_assets), 299_000);
+    assert_eq!(h.confidential_vault(keys).total_assets, 890_000);
+    assert_eq!(alice.available_shares(&h), 890_000_000);
+    assert_eq!(event_assets(&meta.logs), 100_000);
+
+    // Minting costs the price of the shares grossed up for the fee
+    let assets_in = h.view(keys, &View::PreviewMint(100_000_000));
+    assert!(assets_in > 100_000);
+    let meta = alice
+        .mint(&mut h, keys, 100_000_000, assets_in)
+        .expect("mint");
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(h.balance(&alice_assets), 299_000 - assets_in);
+    assert_eq!(h.confidential_vault(keys).total_assets, 990_000);
+    assert_eq!(event_assets(&meta.logs), 100_000);
+
+    // Redeeming pays the price of the shares, less the fee
+    let assets_out = h.view(keys, &View::PreviewRedeem(100_000_000));
+    assert_eq!(assets_out, 99_000);
+    let meta = alice
+        .redeem(&mut h, keys, 100_000_000, assets_out)
+        .expect("redeem");
+    assert_eq!(h.balance(&alice_assets), 299_000 - assets_in + assets_out);
+    assert_eq!(h.confidential_vault(keys).total_assets, 890_000);
+    assert_eq!(event_assets(&meta.logs), 100_000);
+
+    // The fee-adjusted previews are what the slippage bounds are checked against
+    let result = alice.redeem(&mut h, keys, 100_000_000, assets_out + 1);
+    assert_error(result, VaultError::SlippageExceeded.into());
+}
+
+#[test]
+fn test_migrate_vault() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+
+    // The first release ended the vault at confidential_authority and 32
+    // reserved bytes, which the current layout reads as an unset guardian
+    let legacy_len = 252;
+    let mut account = h.svm.get_account(&keys.vault).expect("vault");
+    account.data.truncate(legacy_len);
+    account.lamports = h.svm.minimum_balance_for_rent_exemption(legacy_len);
+    h.svm.set_account(keys.vault, account).expect("set vault");
+
+    let result = h.send(&[svs2::pause(keys, &authority)], &[&vault.authority]);
+    assert_error(
+        result,
+        anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into(),
+    );
+
+    let payer = h.funded_user();
+    h.send_ok(&[svs2::migrate_vault(keys, &payer.pubkey())], &[&payer]);
+    let account = h.svm.get_account(&keys.vault).expect("vault");
+    assert_eq!(account.data.len(), ConfidentialVault::LEN);
+    assert_eq!(
+        account.lamports,
+        h.svm
+            .minimum_balance_for_rent_exemption(ConfidentialVault::LEN)
+    );
+
+    let state = h.confidential_vault(keys);
+    assert_eq!(state.authority, authority);
+    assert_eq!(state.shares_mint, keys.shares_mint);
+    assert_eq!(state.guardian, Pubkey::default());
+    assert_eq!(state.profit_unlock_period, DEFAULT_PROFIT_UNLOCK_PERIOD);
+    assert_eq!((state.event_sequence, state.op_counter), (0, 1));
+
+    h.send_ok(&[svs2::pause(keys, &authority)], &[&vault.authority]);
+    assert!(h.confidential_vault(keys).paused);
+
+    let result = h.send(&[svs2::migrate_vault(keys, &payer.pubkey())], &[&payer]);
+    assert_error(result, VaultError::VaultAlreadyMigrated.into());
+}
+
+#[test]
+fn test_protocol_pause() {
+    let (mut h, vault) = setup();
+    let keys = &vault.keys;
+    let authority = vault.authority.pubkey();
+    let alice = h.confidential_user(keys, 1_000_000);
+    alice.deposit(&mut h, keys, 1_000_000, 0).expect("deposit");
+    let (harvest_authority, _) = find_harvest_authority_address(&svs_2::ID, &keys.vault);
+    let rewards = h.create_token_account(&harvest_authority, &keys.asset_mint, &token::ID);
+    h.mint_to(&keys.asset_mint, &rewards, 100_000);
+    let bob = h.depositor(keys, 1_000_000);
+    h.create_ata(&bob.pubkey(), &keys.shares_mint, &token_2022::ID);
+    let bob_keys = ConfidentialKeys::derive(&bob, &keys.shares_account(&bob.pubkey()))
+        .expect("derive confidential keys");
+
+    let config_authority = h.payer.pubkey();
+    h.send_ok(
+        &[
+            svs2::initialize_config(&svs_2::ID, &config_authority, 1_000, 9, 9),
+            svs2::pause_protocol(&svs_2::ID, &config_authority),
+        ],
+        &[],
+    );
+
+    let application = alice
+        .keys
+        .apply_pending(&alice.state(&h))
+        .expect("apply pending balance");
+    let proof = PubkeyValidityProofData::new(&bob_keys.elgamal).expect("pubkey validity proof");
+    let paused = [
+        (
+            vec![svs2::apply_pending(
+                keys,
+                &alice.pubkey(),
+                application.new_decryptable_available_balance,
+                application.expected_pending_balance_credit_counter,
+            )],
+            &alice.wallet,
+        ),
+        (
+            vec![svs2::resume_confidential_deposit(keys, &alice.pubkey(), 1)],
+            &alice.wallet,
+        ),
+        (
+            vec![
+                ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof),
+                svs2::configure_account(
+                    keys,
+                    &bob.pubkey(),
+                    None,
+                    bob_keys.encrypt_balance(0),
+                    PubkeyValidityProof::InstructionOffset(-1),
+                ),
+            ],
+            &bob,
+        ),
+        (
+            vec![svs2::harvest(
+                keys,
+                &authority,
+                &rewards,
+                &system_program::ID,
+                100_000,
+                &[],
+            )],
+            &vault.authority,
+        ),
+    ];
+    for (instructions, signer) in paused {
+        let result = h.send(&instructions, &[signer]);
+        assert_error(result, VaultError::ProtocolPaused.into());
+    }
+    let result = h.send(&[svs2::pause_protocol(&svs_2::ID, &config_authority)], &[]);
+    assert_error(result, VaultError::AlreadyPaused.into());
+
+    // Once resumed, the same instructions go through
+    let resumed = h.send_ok(
+        &[svs2::unpause_protocol(&svs_2::ID, &config_authority)],
+        &[],
+    );
+    let events = decode_logs(&resumed.logs, &[svs_2::ID]);
+    assert!(matches!(
+        &events[..],
+        [(_, VaultEvent::ProtocolStatusChanged(event))]
+            if !event.paused && event.caller == config_authority && event.sequence == 3
+    ));
+    alice.apply_pending(&mut h, keys);
+    assert_eq!(alice.available_shares(&h), 1_000_000_000);
+}
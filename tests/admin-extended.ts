@@ -41,6 +41,10 @@ describe("Admin Extended", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -112,10 +116,11 @@ describe("Admin Extended", () => {
 
     // Initialize vault
     await program.methods
-      .initialize(vaultId, "Admin Test Vault", "admVault", "https://example.com")
+      .initialize(vaultId, "Admin Test Vault", "admVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -142,6 +147,7 @@ describe("Admin Extended", () => {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigPDA()[0],
       })
       .rpc();
 
@@ -176,6 +182,7 @@ describe("Admin Extended", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject deposit when paused");
@@ -201,6 +208,7 @@ describe("Admin Extended", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject mint when paused");
@@ -224,6 +232,7 @@ describe("Admin Extended", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject withdraw when paused");
@@ -247,6 +256,7 @@ describe("Admin Extended", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject redeem when paused");
@@ -263,6 +273,7 @@ describe("Admin Extended", () => {
         .accountsStrict({
           vault: vault,
           sharesMint: sharesMint,
+          config: getConfigPDA()[0],
         })
         .simulate();
 
@@ -300,7 +311,7 @@ describe("Admin Extended", () => {
           .rpc();
         expect.fail("Should reject double pause");
       } catch (err: any) {
-        expect(err.toString()).to.include("VaultPaused");
+        expect(err.toString()).to.include("AlreadyPaused");
         console.log("  Double pause correctly rejected");
       }
 
@@ -326,7 +337,7 @@ describe("Admin Extended", () => {
           .rpc();
         expect.fail("Should reject unpause when not paused");
       } catch (err: any) {
-        expect(err.toString()).to.include("VaultPaused");
+        expect(err.toString()).to.include("VaultNotPaused");
         console.log("  Double unpause correctly rejected");
       }
     });
@@ -365,6 +376,7 @@ describe("Admin Extended", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -397,10 +409,11 @@ describe("Admin Extended", () => {
       );
 
       await program.methods
-        .initialize(transferVaultId, "Transfer Test Vault", "trfVault", "https://example.com")
+        .initialize(transferVaultId, "Transfer Test Vault", "trfVault", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: transferVault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: transferSharesMint,
           assetVault: transferAssetVault,
@@ -30,6 +30,10 @@ describe("Different Decimals", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -90,10 +94,11 @@ describe("Different Decimals", () => {
     );
 
     await program.methods
-      .initialize(vaultId, `Vault ${decimals}d`, `v${decimals}d`, "https://example.com")
+      .initialize(vaultId, `Vault ${decimals}d`, `v${decimals}d`, "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -147,6 +152,7 @@ describe("Different Decimals", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -185,6 +191,7 @@ describe("Different Decimals", () => {
           userSharesAccount: ctx.userSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -226,6 +233,7 @@ describe("Different Decimals", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -254,6 +262,7 @@ describe("Different Decimals", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -299,6 +308,7 @@ describe("Different Decimals", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -332,6 +342,7 @@ describe("Different Decimals", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -370,6 +381,7 @@ describe("Different Decimals", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
 
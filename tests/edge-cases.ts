@@ -38,6 +38,10 @@ describe("svs-1 edge cases", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -99,10 +103,11 @@ describe("svs-1 edge cases", () => {
 
     // Initialize vault
     await program.methods
-      .initialize(vaultId, "Edge Case Vault", "ecVault", "https://example.com")
+      .initialize(vaultId, "Edge Case Vault", "ecVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -132,6 +137,7 @@ describe("svs-1 edge cases", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject zero deposit");
@@ -157,6 +163,7 @@ describe("svs-1 edge cases", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject zero mint");
@@ -185,6 +192,7 @@ describe("svs-1 edge cases", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject small deposit");
@@ -212,6 +220,7 @@ describe("svs-1 edge cases", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
     });
@@ -233,6 +242,7 @@ describe("svs-1 edge cases", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject due to slippage");
@@ -259,6 +269,7 @@ describe("svs-1 edge cases", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject due to slippage");
@@ -283,6 +294,7 @@ describe("svs-1 edge cases", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject due to slippage");
@@ -309,6 +321,7 @@ describe("svs-1 edge cases", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject due to slippage");
@@ -337,6 +350,7 @@ describe("svs-1 edge cases", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject insufficient shares");
@@ -363,6 +377,7 @@ describe("svs-1 edge cases", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject exceeding vault assets");
@@ -425,10 +440,11 @@ describe("svs-1 edge cases", () => {
       });
 
       await program.methods
-        .initialize(vaultId3, "Second Vault", "sv2", "https://example.com")
+        .initialize(vaultId3, "Second Vault", "sv2", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: vault3,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: sharesMint3,
           assetVault: assetVault3,
@@ -44,6 +44,10 @@ describe("Full Lifecycle", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -140,10 +144,11 @@ describe("Full Lifecycle", () => {
 
     // Initialize vault
     await program.methods
-      .initialize(vaultId, "Lifecycle Vault", "lcVault", "https://example.com")
+      .initialize(vaultId, "Lifecycle Vault", "lcVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -178,6 +183,7 @@ describe("Full Lifecycle", () => {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigPDA()[0],
       })
       .rpc();
 
@@ -230,6 +236,7 @@ describe("Full Lifecycle", () => {
         userSharesAccount: userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID,
         token2022Program: TOKEN_2022_PROGRAM_ID,
+        config: getConfigPDA()[0],
       })
       .rpc();
 
@@ -272,6 +279,7 @@ describe("Full Lifecycle", () => {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigPDA()[0],
       })
       .rpc();
 
@@ -300,6 +308,7 @@ describe("Full Lifecycle", () => {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigPDA()[0],
       })
       .rpc();
     console.log("1. Deposit: 10,000 assets");
@@ -320,6 +329,7 @@ describe("Full Lifecycle", () => {
         token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        config: getConfigPDA()[0],
       })
       .rpc();
     console.log("2. Mint: 5,000 shares");
@@ -338,6 +348,7 @@ describe("Full Lifecycle", () => {
         userSharesAccount: userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID,
         token2022Program: TOKEN_2022_PROGRAM_ID,
+        config: getConfigPDA()[0],
       })
       .rpc();
     console.log("3. Withdraw: 1,000 assets");
@@ -356,6 +367,7 @@ describe("Full Lifecycle", () => {
         userSharesAccount: userSharesAccount,
         assetTokenProgram: TOKEN_PROGRAM_ID,
         token2022Program: TOKEN_2022_PROGRAM_ID,
+        config: getConfigPDA()[0],
       })
       .rpc();
     console.log("4. Redeem: 1,000 shares");
@@ -389,10 +401,11 @@ describe("Full Lifecycle", () => {
     );
 
     await program.methods
-      .initialize(stressVaultId, "Stress Vault", "strVault", "https://example.com")
+      .initialize(stressVaultId, "Stress Vault", "strVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: stressVault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: stressSharesMint,
         assetVault: stressAssetVault,
@@ -421,6 +434,7 @@ describe("Full Lifecycle", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
     }
@@ -441,6 +455,7 @@ describe("Full Lifecycle", () => {
           userSharesAccount: stressUserSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
     }
@@ -462,6 +477,7 @@ describe("Full Lifecycle", () => {
           userSharesAccount: stressUserSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
       shares = await getAccount(connection, stressUserSharesAccount, undefined, TOKEN_2022_PROGRAM_ID);
@@ -486,6 +502,7 @@ describe("Full Lifecycle", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
     }
@@ -531,10 +548,11 @@ describe("Full Lifecycle", () => {
       );
 
       await program.methods
-        .initialize(exitVaultId, "Exit Vault", "extVault", "https://example.com")
+        .initialize(exitVaultId, "Exit Vault", "extVault", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: exitVault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: exitSharesMint,
           assetVault: exitAssetVault,
@@ -563,6 +581,7 @@ describe("Full Lifecycle", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -582,6 +601,7 @@ describe("Full Lifecycle", () => {
           userSharesAccount: exitUserSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -623,6 +643,7 @@ describe("Full Lifecycle", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -39,6 +39,10 @@ describe("Invariants", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -99,10 +103,11 @@ describe("Invariants", () => {
     );
 
     await program.methods
-      .initialize(vaultId, "Invariant Vault", "invVault", "https://example.com")
+      .initialize(vaultId, "Invariant Vault", "invVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -137,6 +142,7 @@ describe("Invariants", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -172,6 +178,7 @@ describe("Invariants", () => {
           userSharesAccount: userSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -204,6 +211,7 @@ describe("Invariants", () => {
           userSharesAccount: userSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -238,6 +246,7 @@ describe("Invariants", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -278,10 +287,11 @@ describe("Invariants", () => {
       );
 
       await program.methods
-        .initialize(freshVaultId, "Rounding Vault", "rndVault", "https://example.com")
+        .initialize(freshVaultId, "Rounding Vault", "rndVault", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: freshVault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: freshSharesMint,
           assetVault: freshAssetVault,
@@ -308,6 +318,7 @@ describe("Invariants", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
     });
@@ -332,6 +343,7 @@ describe("Invariants", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -362,6 +374,7 @@ describe("Invariants", () => {
           userSharesAccount: freshUserSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -392,6 +405,7 @@ describe("Invariants", () => {
             userSharesAccount: userSharesAccount,
             assetTokenProgram: TOKEN_PROGRAM_ID,
             token2022Program: TOKEN_2022_PROGRAM_ID,
+            config: getConfigPDA()[0],
           })
           .rpc();
 
@@ -419,10 +433,11 @@ describe("Invariants", () => {
       );
 
       await program.methods
-        .initialize(testVaultId, "NoFreeMoney Vault", "nfmVault", "https://example.com")
+        .initialize(testVaultId, "NoFreeMoney Vault", "nfmVault", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: testVault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: testSharesMint,
           assetVault: testAssetVault,
@@ -452,6 +467,7 @@ describe("Invariants", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -471,6 +487,7 @@ describe("Invariants", () => {
           userSharesAccount: testUserSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -530,6 +547,7 @@ describe("Invariants", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject when paused");
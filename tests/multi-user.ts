@@ -50,6 +50,10 @@ describe("Multi-User Scenarios", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -161,10 +165,11 @@ describe("Multi-User Scenarios", () => {
 
     // Initialize vault
     await program.methods
-      .initialize(vaultId, "Multi-User Vault", "muVault", "https://example.com")
+      .initialize(vaultId, "Multi-User Vault", "muVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -201,6 +206,7 @@ describe("Multi-User Scenarios", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -223,6 +229,7 @@ describe("Multi-User Scenarios", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .signers([userB])
         .rpc();
@@ -261,6 +268,7 @@ describe("Multi-User Scenarios", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -302,6 +310,7 @@ describe("Multi-User Scenarios", () => {
           userSharesAccount: userASharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -318,6 +327,7 @@ describe("Multi-User Scenarios", () => {
           userSharesAccount: userBSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .signers([userB])
         .rpc();
@@ -354,6 +364,7 @@ describe("Multi-User Scenarios", () => {
           userSharesAccount: userASharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -384,6 +395,7 @@ describe("Multi-User Scenarios", () => {
           userSharesAccount: userBSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .signers([userB])
         .rpc();
@@ -419,6 +431,7 @@ describe("Multi-User Scenarios", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -437,6 +450,7 @@ describe("Multi-User Scenarios", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .signers([userB])
         .rpc();
@@ -483,6 +497,7 @@ describe("Multi-User Scenarios", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -520,6 +535,7 @@ describe("Multi-User Scenarios", () => {
           userSharesAccount: userASharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .signers([userA])
         .rpc();
@@ -39,6 +39,10 @@ describe("svs-1", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -113,10 +117,11 @@ describe("svs-1", () => {
   describe("Initialize", () => {
     it("creates a new vault", async () => {
       const tx = await program.methods
-        .initialize(vaultId, "SVS Vault", "svVault", "https://example.com/vault.json")
+        .initialize(vaultId, "SVS Vault", "svVault", "https://example.com/vault.json", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: vault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: sharesMint,
           assetVault: assetVault,
@@ -159,6 +164,7 @@ describe("svs-1", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -195,6 +201,7 @@ describe("svs-1", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -226,6 +233,7 @@ describe("svs-1", () => {
           userSharesAccount: userSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -261,6 +269,7 @@ describe("svs-1", () => {
           userSharesAccount: userSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -293,6 +302,7 @@ describe("svs-1", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -334,6 +344,7 @@ describe("svs-1", () => {
             token2022Program: TOKEN_2022_PROGRAM_ID,
             associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
+            config: getConfigPDA()[0],
           })
           .rpc();
         expect.fail("Should reject when paused");
@@ -366,6 +377,7 @@ describe("svs-1", () => {
         .accountsStrict({
           vault: vault,
           sharesMint: sharesMint,
+          config: getConfigPDA()[0],
         })
         .simulate();
 
@@ -42,6 +42,10 @@ describe("Yield and Sync", () => {
     );
   };
 
+  const getConfigPDA = (): [PublicKey, number] => {
+    return PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+  };
+
   const getSharesMintPDA = (vault: PublicKey): [PublicKey, number] => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("shares"), vault.toBuffer()],
@@ -139,10 +143,11 @@ describe("Yield and Sync", () => {
 
     // Initialize vault
     await program.methods
-      .initialize(vaultId, "Yield Vault", "yldVault", "https://example.com")
+      .initialize(vaultId, "Yield Vault", "yldVault", "https://example.com", false)
       .accountsStrict({
         authority: payer.publicKey,
         vault: vault,
+        config: getConfigPDA()[0],
         assetMint: assetMint,
         sharesMint: sharesMint,
         assetVault: assetVault,
@@ -177,6 +182,7 @@ describe("Yield and Sync", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
     });
@@ -265,10 +271,11 @@ describe("Yield and Sync", () => {
       );
 
       await program.methods
-        .initialize(yieldVaultId, "Yield Test Vault", "ytVault", "https://example.com")
+        .initialize(yieldVaultId, "Yield Test Vault", "ytVault", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: yieldVault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: yieldSharesMint,
           assetVault: yieldAssetVault,
@@ -299,6 +306,7 @@ describe("Yield and Sync", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -359,6 +367,7 @@ describe("Yield and Sync", () => {
           userSharesAccount: yieldUserSharesAccount,
           assetTokenProgram: TOKEN_PROGRAM_ID,
           token2022Program: TOKEN_2022_PROGRAM_ID,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -399,10 +408,11 @@ describe("Yield and Sync", () => {
       );
 
       await program.methods
-        .initialize(priceVaultId, "Price Track Vault", "ptVault", "https://example.com")
+        .initialize(priceVaultId, "Price Track Vault", "ptVault", "https://example.com", false)
         .accountsStrict({
           authority: payer.publicKey,
           vault: priceVault,
+          config: getConfigPDA()[0],
           assetMint: assetMint,
           sharesMint: priceSharesMint,
           assetVault: priceAssetVault,
@@ -432,6 +442,7 @@ describe("Yield and Sync", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -499,6 +510,7 @@ describe("Yield and Sync", () => {
           token2022Program: TOKEN_2022_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          config: getConfigPDA()[0],
         })
         .rpc();
 
@@ -0,0 +1,102 @@
+//! Token-2022 extension checks shared by the Solana Vault Standard programs
+//!
+//! Both programs run these before moving user funds. They report plain
+//! `ProgramError`s; each program maps the outcome to its own `VaultError`,
+//! as it does for the vault math of `svs-math`.
+
+use spl_token_2022::{
+    extension::{cpi_guard::CpiGuard, BaseStateWithExtensions, StateWithExtensions},
+    solana_program::{account_info::AccountInfo, program_error::ProgramError},
+    state::Account,
+};
+
+/// Whether `account` is a Token-2022 account with CPI Guard enabled.
+///
+/// CPI Guard rejects owner-authorized transfers and burns that arrive via
+/// CPI, which is exactly how the vaults move user funds. SPL Token accounts
+/// have no extensions, so they never have it.
+pub fn cpi_guard_enabled(account: &AccountInfo) -> Result<bool, ProgramError> {
+    if account.owner != &spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<Account>::unpack(&data)?;
+
+    Ok(state
+        .get_extension::<CpiGuard>()
+        .is_ok_and(|cpi_guard| bool::from(cpi_guard.lock_cpi)))
+}
+
+#[cfg(test)]
+mod tests {
+    use spl_token_2022::{
+        extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut},
+        solana_program::{program_pack::Pack, pubkey::Pubkey},
+        state::AccountState,
+    };
+
+    use super::*;
+
+    /// Initialized token account data, with CPI Guard set to `lock_cpi` if given
+    fn token_account(lock_cpi: Option<bool>) -> Vec<u8> {
+        let extensions = match lock_cpi {
+            Some(_) => vec![ExtensionType::CpiGuard],
+            None => vec![],
+        };
+        let len = ExtensionType::try_calculate_account_len::<Account>(&extensions).unwrap();
+        let mut data = vec![0u8; len];
+
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        if let Some(lock_cpi) = lock_cpi {
+            state.init_extension::<CpiGuard>(true).unwrap().lock_cpi = lock_cpi.into();
+        }
+        state.base = Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        state.pack_base();
+        if !extensions.is_empty() {
+            state.init_account_type().unwrap();
+        }
+        data
+    }
+
+    fn check(owner: &Pubkey, mut data: Vec<u8>) -> Result<bool, ProgramError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            owner,
+            false,
+            0,
+        );
+        cpi_guard_enabled(&account)
+    }
+
+    #[test]
+    fn test_cpi_guard_enabled() {
+        let token_2022 = spl_token_2022::ID;
+        assert_eq!(check(&token_2022, token_account(Some(true))), Ok(true));
+        assert_eq!(check(&token_2022, token_account(Some(false))), Ok(false));
+        assert_eq!(check(&token_2022, token_account(None)), Ok(false));
+    }
+
+    #[test]
+    fn test_spl_token_accounts_are_skipped() {
+        // Never parsed, so even garbage data passes
+        let spl_token = Pubkey::new_unique();
+        assert_eq!(check(&spl_token, vec![0xff; Account::LEN]), Ok(false));
+    }
+
+    #[test]
+    fn test_rejects_non_token_accounts() {
+        assert!(check(&spl_token_2022::ID, vec![0; 10]).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Vaults for scripted attack scenarios
+//!
+//! An attack needs an empty vault, or a victim next to the attacker, so each
+//! scenario runs on an [`Arena`] of its own rather than the tracked vault.
+//! Every operation reports what it moved from the token balances and checks
+//! the new totals against the share price [`Model`]; the scenarios bound the
+//! attacker's profit with the exact virtual totals from [`Arena::virtual_totals`].
+
+use borsh::BorshDeserialize;
+use trident_fuzz::fuzzing::*;
+
+use crate::{
+    model::{Change, Model},
+    token::{self, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+    types::{self, svs_1},
+    ACTOR_LAMPORTS, SHARES_DECIMALS, VAULT_ID,
+};
+
+/// A depositor of an arena
+#[derive(Clone, Copy)]
+pub struct Actor {
+    key: Pubkey,
+    asset_account: Pubkey,
+    shares_account: Pubkey,
+}
+
+/// A fresh SVS-1 vault and its authority
+pub struct Arena {
+    authority: Pubkey,
+    vault: Pubkey,
+    asset_mint: Pubkey,
+    shares_mint: Pubkey,
+    asset_vault: Pubkey,
+    config: Pubkey,
+    model: Model,
+}
+
+impl Arena {
+    /// Initialize a vault for a fresh asset mint with `9 - decimals_offset`
+    /// decimals
+    pub fn new(trident: &mut Trident, decimals_offset: u8) -> Self {
+        let authority = Pubkey::new_unique();
+        let asset_mint = Pubkey::new_unique();
+        trident.airdrop(&authority, ACTOR_LAMPORTS);
+
+        let program_id = svs_1::program_id();
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"vault", asset_mint.as_ref(), &VAULT_ID.to_le_bytes()],
+            &program_id,
+        );
+        let (shares_mint, _) =
+            Pubkey::find_program_address(&[b"shares", vault.as_ref()], &program_id);
+        let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let arena = Self {
+            authority,
+            vault,
+            asset_mint,
+            shares_mint,
+            asset_vault: token::ata(&vault, &asset_mint, &TOKEN_PROGRAM_ID),
+            config,
+            model: Model::new(decimals_offset),
+        };
+
+        let setup = token::create_mint(
+            &authority,
+            &asset_mint,
+            &authority,
+            SHARES_DECIMALS - decimals_offset,
+        );
+        assert!(
+            execute(trident, &setup, "Asset setup"),
+            "Asset mint setup failed"
+        );
+
+        let initialize = svs_1::InitializeInstruction::data(svs_1::InitializeInstructionData::new(
+            VAULT_ID,
+            "Attack Vault".to_string(),
+            "atVLT".to_string(),
+            String::new(),
+            false,
+        ))
+        .accounts(svs_1::InitializeInstructionAccounts::new(
+            authority,
+            vault,
+            config,
+            asset_mint,
+            shares_mint,
+            arena.asset_vault,
+            TOKEN_PROGRAM_ID,
+        ))
+        .instruction();
+        assert!(
+            execute(trident, &[initialize], "Initialize"),
+            "Invariant: initialize succeeds for asset decimals up to 9"
+        );
+        arena
+    }
+
+    /// A new depositor holding `assets`
+    pub fn actor(&self, trident: &mut Trident, assets: u64) -> Actor {
+        let key = Pubkey::new_unique();
+        trident.airdrop(&key, ACTOR_LAMPORTS);
+        let actor = Actor {
+            key,
+            asset_account: token::ata(&key, &self.asset_mint, &TOKEN_PROGRAM_ID),
+            shares_account: token::ata(&key, &self.shares_mint, &TOKEN_2022_PROGRAM_ID),
+        };
+
+        let fund = [
+            token::create_ata(&key, &key, &self.asset_mint, &TOKEN_PROGRAM_ID),
+            token::mint_to(
+                &self.asset_mint,
+                &actor.asset_account,
+                &self.authority,
+                assets,
+            ),
+        ];
+        assert!(
+            execute(trident, &fund, "Fund actor"),
+            "Funding an actor failed"
+        );
+        actor
+    }
+
+    /// Deposit `assets`, returning the shares minted or `None` if rejected
+    pub fn deposit(&mut self, trident: &mut Trident, actor: &Actor, assets: u64) -> Option<u64> {
+        let deposit =
+            svs_1::DepositInstruction::data(svs_1::DepositInstructionData::new(assets, 0))
+                .accounts(svs_1::DepositInstructionAccounts::new(
+                    actor.key,
+                    self.vault,
+                    self.asset_mint,
+                    actor.asset_account,
+                    self.asset_vault,
+                    self.shares_mint,
+                    actor.shares_account,
+                    TOKEN_PROGRAM_ID,
+                    self.config,
+                ))
+                .instruction();
+        let (spent, minted) = self.user_operation(trident, actor, deposit, "Deposit")?;
+        assert_eq!(
+            spent, assets as i128,
+            "Invariant: deposit takes exactly the assets"
+        );
+        self.observe(trident, Change::SharesRounded);
+        Some(minted as u64)
+    }
+
+    /// Mint `shares`, returning the assets paid or `None` if rejected
+    pub fn mint(&mut self, trident: &mut Trident, actor: &Actor, shares: u64) -> Option<u64> {
+        let mint = svs_1::MintInstruction::data(svs_1::MintInstructionData::new(shares, u64::MAX))
+            .accounts(svs_1::MintInstructionAccounts::new(
+                actor.key,
+                self.vault,
+                self.asset_mint,
+                actor.asset_account,
+                self.asset_vault,
+                self.shares_mint,
+                actor.shares_account,
+                TOKEN_PROGRAM_ID,
+                self.config,
+            ))
+            .instruction();
+        let (spent, minted) = self.user_operation(trident, actor, mint, "Mint")?;
+        assert_eq!(
+            minted, shares as i128,
+            "Invariant: mint mints exactly the shares"
+        );
+        self.observe(trident, Change::AssetsRounded);
+        Some(spent as u64)
+    }
+
+    /// Withdraw `assets`, returning the shares burned or `None` if rejected
+    pub fn withdraw(&mut self, trident: &mut Trident, actor: &Actor, assets: u64) -> Option<u64> {
+        let withdraw =
+            svs_1::WithdrawInstruction::data(svs_1::WithdrawInstructionData::new(assets, u64::MAX))
+                .accounts(svs_1::WithdrawInstructionAccounts::new(
+                    actor.key,
+                    self.vault,
+                    self.asset_mint,
+                    actor.asset_account,
+                    self.asset_vault,
+                    self.shares_mint,
+                    actor.shares_account,
+                    TOKEN_PROGRAM_ID,
+                    self.config,
+                ))
+                .instruction();
+        let (spent, minted) = self.user_operation(trident, actor, withdraw, "Withdraw")?;
+        assert_eq!(
+            -spent, assets as i128,
+            "Invariant: withdraw pays exactly the assets"
+        );
+        self.observe(trident, Change::SharesRounded);
+        Some(-minted as u64)
+    }
+
+    /// Redeem `shares`, returning the assets paid or `None` if rejected
+    pub fn redeem(&mut self, trident: &mut Trident, actor: &Actor, shares: u64) -> Option<u64> {
+        let redeem = svs_1::RedeemInstruction::data(svs_1::RedeemInstructionData::new(shares, 0))
+            .accounts(svs_1::RedeemInstructionAccounts::new(
+                actor.key,
+                self.vault,
+                self.asset_mint,
+                actor.asset_account,
+                self.asset_vault,
+                self.shares_mint,
+                actor.shares_account,
+                TOKEN_PROGRAM_ID,
+                self.config,
+            ))
+            .instruction();
+        let (spent, minted) = self.user_operation(trident, actor, redeem, "Redeem")?;
+        assert_eq!(
+            -minted, shares as i128,
+            "Invariant: redeem burns exactly the shares"
+        );
+        self.observe(trident, Change::AssetsRounded);
+        Some(-spent as u64)
+    }
+
+    /// Redeem every share `actor` holds, returning the assets paid
+    pub fn exit(&mut self, trident: &mut Trident, actor: &Actor) -> u64 {
+        let shares = self.shares(trident, actor);
+        if shares == 0 {
+            return 0;
+        }
+        self.redeem(trident, actor, shares)
+            .expect("Invariant: an unpaused vault redeems every held share")
+    }
+
+    /// Mint `assets` straight to the asset vault
+    pub fn donate(&mut self, trident: &mut Trident, assets: u64) {
+        let donate = token::mint_to(&self.asset_mint, &self.asset_vault, &self.authority, assets);
+        assert!(execute(trident, &[donate], "Donate"), "Donation failed");
+        self.observe(trident, Change::Unchanged);
+    }
+
+    /// Sync the total assets with the asset vault balance, as the authority
+    pub fn sync(&mut self, trident: &mut Trident) {
+        let donations = token::balance(trident, &self.asset_vault) - self.total_assets(trident);
+        let sync = svs_1::SyncInstruction::data(svs_1::SyncInstructionData::new())
+            .accounts(svs_1::SyncInstructionAccounts::new(
+                self.authority,
+                self.vault,
+                self.asset_vault,
+            ))
+            .instruction();
+        assert!(
+            execute(trident, &[sync], "Sync"),
+            "Invariant: the authority can sync, paused or not"
+        );
+        self.observe(trident, Change::Synced(donations));
+    }
+
+    /// Pause or unpause the vault, as the authority
+    pub fn set_paused(&mut self, trident: &mut Trident, paused: bool) {
+        let (instruction, name) = if paused {
+            let pause = svs_1::PauseInstruction::data(svs_1::PauseInstructionData::new())
+                .accounts(svs_1::PauseInstructionAccounts::new(
+                    self.authority,
+                    self.vault,
+                ))
+                .instruction();
+            (pause, "Pause")
+        } else {
+            let unpause = svs_1::UnpauseInstruction::data(svs_1::UnpauseInstructionData::new())
+                .accounts(svs_1::UnpauseInstructionAccounts::new(
+                    self.authority,
+                    self.vault,
+                ))
+                .instruction();
+            (unpause, "Unpause")
+        };
+        assert!(
+            execute(trident, &[instruction], name),
+            "Invariant: the authority can {name} the vault"
+        );
+        self.observe(trident, Change::Unchanged);
+    }
+
+    pub fn assets(&self, trident: &mut Trident, actor: &Actor) -> u64 {
+        token::balance(trident, &actor.asset_account)
+    }
+
+    pub fn shares(&self, trident: &mut Trident, actor: &Actor) -> u64 {
+        token::balance(trident, &actor.shares_account)
+    }
+
+    /// `(total_assets + 1, total_shares + 10^offset)` read from the chain
+    pub fn virtual_totals(&self, trident: &mut Trident) -> (u128, u128) {
+        let vault = self.vault_state(trident);
+        (
+            u128::from(vault.total_assets) + 1,
+            u128::from(token::supply(trident, &self.shares_mint))
+                + 10u128.pow(vault.decimals_offset.into()),
+        )
+    }
+
+    /// Send `instruction` signed by `actor`, returning the assets it took
+    /// from and the shares it minted to the actor, negative when paid out
+    /// and burned
+    fn user_operation(
+        &mut self,
+        trident: &mut Trident,
+        actor: &Actor,
+        instruction: Instruction,
+        name: &str,
+    ) -> Option<(i128, i128)> {
+        let assets = i128::from(self.assets(trident, actor));
+        let shares = i128::from(self.shares(trident, actor));
+        if !execute(trident, &[instruction], name) {
+            self.observe(trident, Change::Unchanged);
+            return None;
+        }
+        Some((
+            assets - i128::from(self.assets(trident, actor)),
+            i128::from(self.shares(trident, actor)) - shares,
+        ))
+    }
+
+    fn observe(&mut self, trident: &mut Trident, change: Change) {
+        let total_assets = self.total_assets(trident);
+        assert!(
+            token::balance(trident, &self.asset_vault) >= total_assets,
+            "Invariant: asset vault balance covers the total assets"
+        );
+        let supply = token::supply(trident, &self.shares_mint);
+        self.model.observe(total_assets, supply, change);
+    }
+
+    fn total_assets(&self, trident: &mut Trident) -> u64 {
+        self.vault_state(trident).total_assets
+    }
+
+    fn vault_state(&self, trident: &mut Trident) -> types::Vault {
+        let account = trident.get_account(&self.vault);
+        // Skip the Anchor discriminator
+        types::Vault::try_from_slice(&account.data()[8..]).expect("vault account")
+    }
+}
+
+/// Process `instructions` in one transaction, true if it succeeded
+fn execute(trident: &mut Trident, instructions: &[Instruction], name: &str) -> bool {
+    trident
+        .process_transaction(instructions, Some(name))
+        .is_success()
+}
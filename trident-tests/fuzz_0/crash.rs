@@ -0,0 +1,138 @@
+//! Crash artifacts
+//!
+//! Every iteration records the flows it ran with their fuzzed amounts. When
+//! an invariant panics, the panic hook writes them to
+//! `crashes/<unix time>-<nanos>.crash`, and `crash-to-test` in the
+//! `test-harness` crate turns the file into a regression test.
+//!
+//! The format is line based: `#` comments (the panic message), then
+//! `decimals_offset <n>` and one `<flow> <amount>` line per step; `pause`,
+//! `unpause` and `sync` take no amount.
+//!
+//! Attack scenarios run on vaults of their own that the format can't
+//! describe, so no artifact is written while one runs; their panic messages
+//! carry the fuzzed parameters instead.
+
+use std::{
+    cell::RefCell,
+    fmt::Write as _,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A flow that changed or checked the tracked vault
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    Deposit(u64),
+    Redeem(u64),
+    Withdraw(u64),
+    Mint(u64),
+    Pause,
+    Unpause,
+    Donate(u64),
+    Sync,
+    ConversionCheck(u64),
+}
+
+#[derive(Default)]
+struct Iteration {
+    decimals_offset: u8,
+    steps: Vec<Step>,
+    in_scenario: bool,
+}
+
+thread_local! {
+    static ITERATION: RefCell<Iteration> = RefCell::default();
+}
+
+/// Forget the previous iteration's steps
+pub fn start_iteration() {
+    ITERATION.with_borrow_mut(|iteration| *iteration = Iteration::default());
+}
+
+pub fn set_decimals_offset(decimals_offset: u8) {
+    ITERATION.with_borrow_mut(|iteration| iteration.decimals_offset = decimals_offset);
+}
+
+pub fn record(step: Step) {
+    ITERATION.with_borrow_mut(|iteration| iteration.steps.push(step));
+}
+
+/// Suppress artifacts until the returned guard drops
+pub fn scenario() -> ScenarioGuard {
+    ITERATION.with_borrow_mut(|iteration| iteration.in_scenario = true);
+    ScenarioGuard
+}
+
+/// Running attack scenario, see [`scenario`]
+pub struct ScenarioGuard;
+
+impl Drop for ScenarioGuard {
+    fn drop(&mut self) {
+        // The hook has already run when a panic unwinds through here
+        let _ = ITERATION.try_with(|iteration| {
+            if let Ok(mut iteration) = iteration.try_borrow_mut() {
+                iteration.in_scenario = false;
+            }
+        });
+    }
+}
+
+/// Write the current iteration to a crash artifact on every panic, then run
+/// the default hook
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match write_artifact(info) {
+            Ok(Some(path)) => eprintln!("Crash artifact written to {}", path.display()),
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to write crash artifact: {e}"),
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_artifact(info: &PanicHookInfo) -> std::io::Result<Option<PathBuf>> {
+    let Ok(Some(artifact)) = ITERATION.try_with(|iteration| {
+        let iteration = iteration.try_borrow().ok()?;
+        if iteration.in_scenario {
+            return None;
+        }
+        Some(render(&iteration, &info.to_string()))
+    }) else {
+        return Ok(None);
+    };
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("crashes");
+    fs::create_dir_all(&dir)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let path = dir.join(format!("{}-{}.crash", now.as_secs(), now.subsec_nanos()));
+    fs::write(&path, artifact)?;
+    Ok(Some(path))
+}
+
+fn render(iteration: &Iteration, panic_message: &str) -> String {
+    let mut artifact = String::new();
+    for line in panic_message.lines() {
+        let _ = writeln!(artifact, "# {line}");
+    }
+    let _ = writeln!(artifact, "decimals_offset {}", iteration.decimals_offset);
+    for step in &iteration.steps {
+        let _ = match step {
+            Step::Deposit(assets) => writeln!(artifact, "deposit {assets}"),
+            Step::Redeem(shares) => writeln!(artifact, "redeem {shares}"),
+            Step::Withdraw(assets) => writeln!(artifact, "withdraw {assets}"),
+            Step::Mint(shares) => writeln!(artifact, "mint {shares}"),
+            Step::Pause => writeln!(artifact, "pause"),
+            Step::Unpause => writeln!(artifact, "unpause"),
+            Step::Donate(assets) => writeln!(artifact, "donate {assets}"),
+            Step::Sync => writeln!(artifact, "sync"),
+            Step::ConversionCheck(assets) => writeln!(artifact, "conversion_check {assets}"),
+        };
+    }
+    artifact
+}
@@ -0,0 +1,102 @@
+//! Analytic model of the vault's share price
+//!
+//! The tracker predicts every amount with `svs-math`, the conversions the
+//! program itself runs, so a bug in them would go unnoticed. The model
+//! instead follows the totals read from the chain and checks each change
+//! against the exact virtual share price, `(total_assets + 1) /
+//! (total_shares + 10^offset)`, in `i128` rationals. What a change adds to
+//! the virtual assets beyond the new virtual shares' worth at the old price
+//! is the value the vault keeps, bounded by how the program rounded:
+//!
+//! | Change | Value kept by the vault |
+//! |--------|-------------------------|
+//! | `deposit`, `withdraw` | At least 0, less than one share's worth |
+//! | `mint`, `redeem` | At least 0, less than one asset |
+//! | `sync` | Exactly the synced donations |
+//! | Anything else | 0, the totals don't change |
+//!
+//! The vault never keeps a negative value, so the share price never falls.
+
+/// How the program rounded the flow's change to the totals
+#[derive(Clone, Copy, Debug)]
+pub enum Change {
+    /// Totals must not change
+    Unchanged,
+    /// Shares rounded in the vault's favor, as by `deposit` and `withdraw`
+    SharesRounded,
+    /// Assets rounded in the vault's favor, as by `mint` and `redeem`
+    AssetsRounded,
+    /// Donations counted in the total assets by `sync`
+    Synced(u64),
+}
+
+/// Totals last read from the chain
+#[derive(Default, Clone, Copy)]
+pub struct Model {
+    total_assets: u64,
+    total_shares: u64,
+    decimals_offset: u8,
+}
+
+impl Model {
+    /// Model of a new, empty vault
+    pub fn new(decimals_offset: u8) -> Self {
+        Self {
+            total_assets: 0,
+            total_shares: 0,
+            decimals_offset,
+        }
+    }
+
+    /// Check the on-chain totals after `change` against the previous ones,
+    /// then follow them
+    pub fn observe(&mut self, total_assets: u64, total_shares: u64, change: Change) {
+        let (assets_before, shares_before) = self.virtual_totals();
+        *self = Self {
+            total_assets,
+            total_shares,
+            ..*self
+        };
+        let (assets_after, shares_after) = self.virtual_totals();
+
+        // Value kept, scaled by the virtual shares before the change so the
+        // comparison stays exact
+        let kept = mul(assets_after, shares_before) - mul(shares_after, assets_before);
+        assert!(
+            kept >= 0,
+            "Invariant: share price never falls ({change:?} lost {kept} scaled assets)"
+        );
+        match change {
+            Change::Unchanged => assert_eq!(
+                (assets_after, shares_after),
+                (assets_before, shares_before),
+                "Invariant: totals don't change"
+            ),
+            Change::SharesRounded => assert!(
+                kept < assets_before,
+                "Invariant: shares rounding keeps less than one share's worth"
+            ),
+            Change::AssetsRounded => assert!(
+                kept < shares_before,
+                "Invariant: assets rounding keeps less than one asset"
+            ),
+            Change::Synced(donations) => assert_eq!(
+                kept,
+                mul(donations.into(), shares_before),
+                "Invariant: sync adds exactly the donations' value"
+            ),
+        }
+    }
+
+    /// `(total_assets + 1, total_shares + 10^offset)`
+    fn virtual_totals(&self) -> (i128, i128) {
+        (
+            i128::from(self.total_assets) + 1,
+            i128::from(self.total_shares) + 10i128.pow(self.decimals_offset.into()),
+        )
+    }
+}
+
+fn mul(a: i128, b: i128) -> i128 {
+    a.checked_mul(b).expect("model overflow")
+}
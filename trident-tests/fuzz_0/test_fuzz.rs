@@ -1,15 +1,62 @@
+use attack::Arena;
+use borsh::BorshDeserialize;
+use crash::Step;
 use fuzz_accounts::*;
+use model::{Change, Model};
+use svs_math::{convert_to_assets, convert_to_shares, Rounding};
+use token::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
 use trident_fuzz::fuzzing::*;
+use types::svs_1;
+mod attack;
+mod crash;
 mod fuzz_accounts;
+mod model;
+mod token;
 mod types;
 
-/// Vault state tracking for invariant checks
+/// Decimals of the vault's shares mint; assets have `9 - decimals_offset`
+const SHARES_DECIMALS: u8 = 9;
+/// Smallest deposit the program accepts
+const MIN_DEPOSIT_AMOUNT: u64 = 1000;
+const VAULT_ID: u64 = 1;
+/// Most shares one deposit mints at the initial price, so that the supply
+/// stays within `u64` over an iteration's flows
+const MAX_DEPOSIT_SHARES: u64 = 10_000_000_000_000_000;
+/// Assets minted to the depositor, enough for every deposit of an iteration
+const USER_ASSETS: u64 = u64::MAX / 2;
+/// Most dust round trips of one grinding scenario
+const MAX_GRINDING_ROUNDS: u8 = 10;
+/// Lamports for the authority's and depositor's rent
+const ACTOR_LAMPORTS: u64 = 10_000_000_000;
+
+/// Accounts of the fuzzed vault and its only depositor
+#[derive(Clone, Copy)]
+struct Fixture {
+    authority: Pubkey,
+    vault: Pubkey,
+    asset_mint: Pubkey,
+    shares_mint: Pubkey,
+    asset_vault: Pubkey,
+    config: Pubkey,
+    user: Pubkey,
+    user_asset_account: Pubkey,
+    user_shares_account: Pubkey,
+}
+
+/// Expected vault state, checked against the program's after every flow
 #[derive(Default, Clone)]
 struct VaultTracker {
-    initialized: bool,
+    fixture: Option<Fixture>,
     total_assets: u64,
     total_shares: u64,
     decimals_offset: u8,
+    paused: bool,
+    /// Assets donated to the asset vault since the last sync
+    unsynced_donations: u64,
+    /// Whether a sync has counted donations in the total assets
+    synced_donations: bool,
+    /// Exact share price model of the on-chain totals
+    model: Model,
 }
 
 #[derive(FuzzTestMethods)]
@@ -32,60 +79,183 @@ impl FuzzTest {
     #[init]
     fn start(&mut self) {
         self.vault_tracker = VaultTracker::default();
+        crash::start_iteration();
     }
 
-    /// Initialize vault - this sets up the test environment
+    /// Initialize an SVS-1 vault for a fresh asset mint with fuzzed decimals,
+    /// and fund its depositor
     #[flow]
     fn flow_initialize(&mut self) {
-        if self.vault_tracker.initialized {
+        if self.vault_tracker.fixture.is_some() {
             return;
         }
 
-        // For now, just mark as initialized and track basic state
-        // Full instruction building requires proper account setup
-        self.vault_tracker.initialized = true;
-        self.vault_tracker.decimals_offset = 3;
+        let asset_decimals = rand::random::<u8>() % (SHARES_DECIMALS + 1);
+        let decimals_offset = SHARES_DECIMALS - asset_decimals;
+        crash::set_decimals_offset(decimals_offset);
+
+        let authority = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let asset_mint = Pubkey::new_unique();
+        self.trident.airdrop(&authority, ACTOR_LAMPORTS);
+        self.trident.airdrop(&user, ACTOR_LAMPORTS);
+
+        let program_id = svs_1::program_id();
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"vault", asset_mint.as_ref(), &VAULT_ID.to_le_bytes()],
+            &program_id,
+        );
+        let (shares_mint, _) =
+            Pubkey::find_program_address(&[b"shares", vault.as_ref()], &program_id);
+        let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let fixture = Fixture {
+            authority,
+            vault,
+            asset_mint,
+            shares_mint,
+            asset_vault: token::ata(&vault, &asset_mint, &TOKEN_PROGRAM_ID),
+            config,
+            user,
+            user_asset_account: token::ata(&user, &asset_mint, &TOKEN_PROGRAM_ID),
+            user_shares_account: token::ata(&user, &shares_mint, &TOKEN_2022_PROGRAM_ID),
+        };
+
+        let mut setup =
+            token::create_mint(&authority, &asset_mint, &authority, asset_decimals).to_vec();
+        setup.push(token::create_ata(
+            &authority,
+            &user,
+            &asset_mint,
+            &TOKEN_PROGRAM_ID,
+        ));
+        setup.push(token::mint_to(
+            &asset_mint,
+            &fixture.user_asset_account,
+            &authority,
+            USER_ASSETS,
+        ));
+        assert!(
+            self.execute(&setup, "Asset setup"),
+            "Asset mint setup failed"
+        );
+
+        let initialize = svs_1::InitializeInstruction::data(svs_1::InitializeInstructionData::new(
+            VAULT_ID,
+            "Fuzz Vault".to_string(),
+            "fzVLT".to_string(),
+            String::new(),
+            false,
+        ))
+        .accounts(svs_1::InitializeInstructionAccounts::new(
+            authority,
+            vault,
+            config,
+            asset_mint,
+            shares_mint,
+            fixture.asset_vault,
+            TOKEN_PROGRAM_ID,
+        ))
+        .instruction();
+        assert!(
+            self.execute(&[initialize], "Initialize"),
+            "Invariant: initialize succeeds for asset decimals up to 9"
+        );
+
+        let state = self.vault_state(&vault);
+        assert_eq!(
+            state.decimals_offset, decimals_offset,
+            "Invariant: decimals offset is 9 - asset decimals"
+        );
+        assert_eq!(state.authority, authority);
+
+        self.vault_tracker.fixture = Some(fixture);
+        self.vault_tracker.decimals_offset = decimals_offset;
+        self.vault_tracker.model = Model::new(decimals_offset);
+        self.assert_vault_state(&fixture, Change::Unchanged);
     }
 
-    /// Test deposit invariants with fuzzed values
+    /// Deposit fuzzed assets, with the expected shares as the slippage bound
     #[flow]
     fn flow_deposit(&mut self) {
-        if !self.vault_tracker.initialized {
+        let Some(fixture) = self.vault_tracker.fixture else {
             return;
-        }
+        };
 
         // Generate random deposit amount
-        let fuzz_assets: u64 = rand::random::<u64>() % 1_000_000_000_000;
-        let assets = fuzz_assets.max(1001);
+        let fuzz_assets: u64 =
+            rand::random::<u64>() % max_fuzzed_assets(self.vault_tracker.decimals_offset);
+        let assets = fuzz_assets.max(MIN_DEPOSIT_AMOUNT + 1);
+        crash::record(Step::Deposit(assets));
 
-        // Track state changes
         let assets_before = self.vault_tracker.total_assets;
         let shares_before = self.vault_tracker.total_shares;
 
         // Calculate expected shares (floor rounding)
-        let expected_shares = self.calculate_shares_for_assets(assets, assets_before, shares_before);
+        let expected_shares =
+            self.calculate_shares_for_assets(assets, assets_before, shares_before);
 
-        // Update tracker
-        self.vault_tracker.total_assets = self.vault_tracker.total_assets.saturating_add(assets);
-        self.vault_tracker.total_shares = self.vault_tracker.total_shares.saturating_add(expected_shares);
+        // Invariant: shares should be positive for non-zero deposits, unless
+        // synced donations raised the share price above the deposit
+        assert!(
+            expected_shares > 0
+                || assets_before == 0 && shares_before == 0
+                || self.vault_tracker.synced_donations,
+            "Invariant: positive deposit should yield positive shares"
+        );
 
-        // Invariant: shares should be positive for non-zero deposits
-        if assets > 0 {
-            assert!(expected_shares > 0 || assets_before == 0 && shares_before == 0,
-                "Invariant: positive deposit should yield positive shares");
+        let deposit = svs_1::DepositInstruction::data(svs_1::DepositInstructionData::new(
+            assets,
+            expected_shares,
+        ))
+        .accounts(svs_1::DepositInstructionAccounts::new(
+            fixture.user,
+            fixture.vault,
+            fixture.asset_mint,
+            fixture.user_asset_account,
+            fixture.asset_vault,
+            fixture.shares_mint,
+            fixture.user_shares_account,
+            TOKEN_PROGRAM_ID,
+            fixture.config,
+        ))
+        .instruction();
+        if self.assert_rejected_while_paused(&fixture, deposit.clone(), "Deposit") {
+            return;
         }
+        let user_assets = token::balance(&mut self.trident, &fixture.user_asset_account);
+        assert!(
+            self.execute(&[deposit], "Deposit"),
+            "Invariant: deposit at the expected share price succeeds"
+        );
+        assert_eq!(
+            user_assets - token::balance(&mut self.trident, &fixture.user_asset_account),
+            assets,
+            "Invariant: deposit takes exactly the deposited assets"
+        );
+
+        self.vault_tracker.total_assets = assets_before
+            .checked_add(assets)
+            .expect("total assets overflow");
+        self.vault_tracker.total_shares = shares_before
+            .checked_add(expected_shares)
+            .expect("total shares overflow");
+        self.assert_vault_state(&fixture, Change::SharesRounded);
     }
 
-    /// Test redeem invariants with fuzzed values
+    /// Redeem fuzzed shares, with the expected assets as the slippage bound
     #[flow]
     fn flow_redeem(&mut self) {
-        if !self.vault_tracker.initialized || self.vault_tracker.total_shares == 0 {
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+        if self.vault_tracker.total_shares == 0 {
             return;
         }
 
         // Generate random redeem amount (within available shares)
         let fuzz_shares: u64 = rand::random::<u64>() % self.vault_tracker.total_shares;
         let shares = fuzz_shares.max(1);
+        crash::record(Step::Redeem(shares));
 
         // Calculate expected assets (floor rounding)
         let expected_assets = self.calculate_assets_for_shares_floor(
@@ -94,25 +264,294 @@ impl FuzzTest {
             self.vault_tracker.total_shares,
         );
 
-        // Update tracker
-        self.vault_tracker.total_shares = self.vault_tracker.total_shares.saturating_sub(shares);
-        self.vault_tracker.total_assets = self.vault_tracker.total_assets.saturating_sub(expected_assets);
-
         // Invariant: assets received should not exceed what's in vault
-        assert!(expected_assets <= self.vault_tracker.total_assets.saturating_add(expected_assets),
-            "Invariant: cannot redeem more assets than available");
+        assert!(
+            expected_assets <= self.vault_tracker.total_assets,
+            "Invariant: cannot redeem more assets than available"
+        );
+
+        let redeem = svs_1::RedeemInstruction::data(svs_1::RedeemInstructionData::new(
+            shares,
+            expected_assets,
+        ))
+        .accounts(svs_1::RedeemInstructionAccounts::new(
+            fixture.user,
+            fixture.vault,
+            fixture.asset_mint,
+            fixture.user_asset_account,
+            fixture.asset_vault,
+            fixture.shares_mint,
+            fixture.user_shares_account,
+            TOKEN_PROGRAM_ID,
+            fixture.config,
+        ))
+        .instruction();
+        if self.assert_rejected_while_paused(&fixture, redeem.clone(), "Redeem") {
+            return;
+        }
+        let user_assets = token::balance(&mut self.trident, &fixture.user_asset_account);
+        assert!(
+            self.execute(&[redeem], "Redeem"),
+            "Invariant: redeeming held shares at the expected price succeeds"
+        );
+        assert_eq!(
+            token::balance(&mut self.trident, &fixture.user_asset_account) - user_assets,
+            expected_assets,
+            "Invariant: redeem pays exactly the expected assets"
+        );
+
+        self.vault_tracker.total_shares -= shares;
+        self.vault_tracker.total_assets -= expected_assets;
+        self.assert_vault_state(&fixture, Change::AssetsRounded);
+    }
+
+    /// Withdraw fuzzed assets, with the expected shares as the slippage bound
+    #[flow]
+    fn flow_withdraw(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+        let total_assets = self.vault_tracker.total_assets;
+        let total_shares = self.vault_tracker.total_shares;
+
+        // The most the depositor's shares can withdraw
+        let max_withdraw =
+            self.calculate_assets_for_shares_floor(total_shares, total_assets, total_shares);
+        if max_withdraw == 0 {
+            return;
+        }
+        let assets = rand::random::<u64>() % max_withdraw + 1;
+        crash::record(Step::Withdraw(assets));
+
+        // Calculate expected shares (ceiling rounding)
+        let expected_shares =
+            self.calculate_shares_for_assets_ceil(assets, total_assets, total_shares);
+
+        // Invariant: withdrawing no more than the shares are worth burns no
+        // more than the held shares
+        assert!(
+            expected_shares <= total_shares,
+            "Invariant: withdrawing the shares' value burns at most the held shares"
+        );
+        // Invariant: burned shares are worth at least the withdrawn assets
+        assert!(
+            self.calculate_assets_for_shares_floor(expected_shares, total_assets, total_shares)
+                >= assets,
+            "Invariant: withdraw burns at least the withdrawn assets' worth of shares"
+        );
+
+        let withdraw = svs_1::WithdrawInstruction::data(svs_1::WithdrawInstructionData::new(
+            assets,
+            expected_shares,
+        ))
+        .accounts(svs_1::WithdrawInstructionAccounts::new(
+            fixture.user,
+            fixture.vault,
+            fixture.asset_mint,
+            fixture.user_asset_account,
+            fixture.asset_vault,
+            fixture.shares_mint,
+            fixture.user_shares_account,
+            TOKEN_PROGRAM_ID,
+            fixture.config,
+        ))
+        .instruction();
+        if self.assert_rejected_while_paused(&fixture, withdraw.clone(), "Withdraw") {
+            return;
+        }
+        let user_assets = token::balance(&mut self.trident, &fixture.user_asset_account);
+        assert!(
+            self.execute(&[withdraw], "Withdraw"),
+            "Invariant: withdrawing at the expected share price succeeds"
+        );
+        assert_eq!(
+            token::balance(&mut self.trident, &fixture.user_asset_account) - user_assets,
+            assets,
+            "Invariant: withdraw pays exactly the withdrawn assets"
+        );
+
+        self.vault_tracker.total_shares -= expected_shares;
+        self.vault_tracker.total_assets -= assets;
+        self.assert_vault_state(&fixture, Change::SharesRounded);
+    }
+
+    /// Mint fuzzed shares, with the expected assets as the slippage bound
+    #[flow]
+    fn flow_mint(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+        let total_assets = self.vault_tracker.total_assets;
+        let total_shares = self.vault_tracker.total_shares;
+
+        // Shares worth at most a deposit's assets, so the cost stays in range
+        // whatever donations did to the share price
+        let max_shares = self.calculate_shares_for_assets(
+            max_fuzzed_assets(self.vault_tracker.decimals_offset),
+            total_assets,
+            total_shares,
+        );
+        if max_shares == 0 {
+            return;
+        }
+        let shares = rand::random::<u64>() % max_shares + 1;
+        crash::record(Step::Mint(shares));
+
+        // Calculate expected assets (ceiling rounding)
+        let expected_assets =
+            self.calculate_assets_for_shares_ceil(shares, total_assets, total_shares);
+
+        // Invariant: paid assets are worth at least the minted shares
+        assert!(
+            self.calculate_shares_for_assets(expected_assets, total_assets, total_shares) >= shares,
+            "Invariant: mint charges at least the minted shares' worth of assets"
+        );
+
+        let mint =
+            svs_1::MintInstruction::data(svs_1::MintInstructionData::new(shares, expected_assets))
+                .accounts(svs_1::MintInstructionAccounts::new(
+                    fixture.user,
+                    fixture.vault,
+                    fixture.asset_mint,
+                    fixture.user_asset_account,
+                    fixture.asset_vault,
+                    fixture.shares_mint,
+                    fixture.user_shares_account,
+                    TOKEN_PROGRAM_ID,
+                    fixture.config,
+                ))
+                .instruction();
+        if self.assert_rejected_while_paused(&fixture, mint.clone(), "Mint") {
+            return;
+        }
+        let user_assets = token::balance(&mut self.trident, &fixture.user_asset_account);
+        assert!(
+            self.execute(&[mint], "Mint"),
+            "Invariant: minting at the expected share price succeeds"
+        );
+        assert_eq!(
+            user_assets - token::balance(&mut self.trident, &fixture.user_asset_account),
+            expected_assets,
+            "Invariant: mint takes exactly the expected assets"
+        );
+
+        self.vault_tracker.total_assets = total_assets
+            .checked_add(expected_assets)
+            .expect("total assets overflow");
+        self.vault_tracker.total_shares = total_shares
+            .checked_add(shares)
+            .expect("total shares overflow");
+        self.assert_vault_state(&fixture, Change::AssetsRounded);
+    }
+
+    /// Pause or unpause the vault, interleaving with the other flows
+    #[flow]
+    fn flow_pause(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+
+        let pause = rand::random::<bool>();
+        let (instruction, name) = if pause {
+            crash::record(Step::Pause);
+            let pause = svs_1::PauseInstruction::data(svs_1::PauseInstructionData::new())
+                .accounts(svs_1::PauseInstructionAccounts::new(
+                    fixture.authority,
+                    fixture.vault,
+                ))
+                .instruction();
+            (pause, "Pause")
+        } else {
+            crash::record(Step::Unpause);
+            let unpause = svs_1::UnpauseInstruction::data(svs_1::UnpauseInstructionData::new())
+                .accounts(svs_1::UnpauseInstructionAccounts::new(
+                    fixture.authority,
+                    fixture.vault,
+                ))
+                .instruction();
+            (unpause, "Unpause")
+        };
+
+        let changes_state = pause != self.vault_tracker.paused;
+        assert_eq!(
+            self.execute(&[instruction], name),
+            changes_state,
+            "Invariant: pause and unpause succeed only when they change the state"
+        );
+        self.vault_tracker.paused = pause;
+        self.assert_vault_state(&fixture, Change::Unchanged);
+    }
+
+    /// Donate fuzzed assets straight to the asset vault, bypassing deposit
+    #[flow]
+    fn flow_donate(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+
+        let assets =
+            (rand::random::<u64>() % max_fuzzed_assets(self.vault_tracker.decimals_offset)).max(1);
+        crash::record(Step::Donate(assets));
+
+        let donate = token::mint_to(
+            &fixture.asset_mint,
+            &fixture.asset_vault,
+            &fixture.authority,
+            assets,
+        );
+        assert!(self.execute(&[donate], "Donate"), "Donation failed");
+
+        self.vault_tracker.unsynced_donations = self
+            .vault_tracker
+            .unsynced_donations
+            .checked_add(assets)
+            .expect("donations overflow");
+        // Invariant: donations don't change the recorded total assets
+        self.assert_vault_state(&fixture, Change::Unchanged);
+    }
+
+    /// Sync the recorded total assets with the asset vault balance
+    #[flow]
+    fn flow_sync(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+        crash::record(Step::Sync);
+
+        let sync = svs_1::SyncInstruction::data(svs_1::SyncInstructionData::new())
+            .accounts(svs_1::SyncInstructionAccounts::new(
+                fixture.authority,
+                fixture.vault,
+                fixture.asset_vault,
+            ))
+            .instruction();
+        assert!(
+            self.execute(&[sync], "Sync"),
+            "Invariant: the authority can sync, paused or not"
+        );
+
+        let donations = std::mem::take(&mut self.vault_tracker.unsynced_donations);
+        self.vault_tracker.total_assets = self
+            .vault_tracker
+            .total_assets
+            .checked_add(donations)
+            .expect("total assets overflow");
+        self.vault_tracker.synced_donations |= donations > 0;
+        // Invariant: sync counts every donation, and only donations
+        self.assert_vault_state(&fixture, Change::Synced(donations));
     }
 
     /// Test conversion consistency
     #[flow]
     fn flow_conversion_check(&mut self) {
-        if !self.vault_tracker.initialized {
+        if self.vault_tracker.fixture.is_none() {
             return;
         }
 
         // Random amount to test conversion
         let test_amount: u64 = rand::random::<u64>() % 1_000_000_000;
         let test_amount = test_amount.max(1);
+        crash::record(Step::ConversionCheck(test_amount));
 
         // Convert assets -> shares -> assets
         let shares = self.calculate_shares_for_assets(
@@ -129,55 +568,384 @@ impl FuzzTest {
             );
 
             // Invariant: Round-trip should not create assets (rounding favors vault)
-            assert!(assets_back <= test_amount,
-                "Invariant: round-trip should not create free assets");
+            assert!(
+                assets_back <= test_amount,
+                "Invariant: round-trip should not create free assets"
+            );
         }
     }
 
+    /// First-depositor inflation: the attacker seeds an empty vault and
+    /// donates to raise the share price, which the authority syncs before a
+    /// victim deposits
+    #[flow]
+    fn flow_attack_inflation(&mut self) {
+        let _scenario = crash::scenario();
+        let decimals_offset = rand::random::<u8>() % (SHARES_DECIMALS + 1);
+        let max_assets = max_fuzzed_assets(decimals_offset);
+        let seed = MIN_DEPOSIT_AMOUNT + rand::random::<u64>() % MIN_DEPOSIT_AMOUNT;
+        let donation = rand::random::<u64>() % max_assets + 1;
+        let deposit = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+        let scenario = format!(
+            "offset {decimals_offset}, seed {seed}, donation {donation}, deposit {deposit}"
+        );
+
+        let trident = &mut self.trident;
+        let mut arena = Arena::new(trident, decimals_offset);
+        let attacker = arena.actor(trident, USER_ASSETS);
+        let victim = arena.actor(trident, USER_ASSETS);
+        arena
+            .deposit(trident, &attacker, seed)
+            .expect("Seeding an empty vault failed");
+        arena.donate(trident, donation);
+        arena.sync(trident);
+
+        let (virtual_assets, virtual_shares) = arena.virtual_totals(trident);
+        arena
+            .deposit(trident, &victim, deposit)
+            .expect("Invariant: an unpaused vault accepts deposits");
+        arena.exit(trident, &attacker);
+        arena.exit(trident, &victim);
+
+        assert!(
+            arena.assets(trident, &attacker) <= USER_ASSETS,
+            "Invariant: inflating the share price never profits the attacker ({scenario})"
+        );
+        let loss = USER_ASSETS - arena.assets(trident, &victim);
+        assert!(
+            u128::from(loss) * virtual_shares < virtual_assets + virtual_shares,
+            "Invariant: the victim loses less than one share's worth plus one asset ({scenario})"
+        );
+    }
+
+    /// Sync sandwich: with donations waiting in the asset vault, the attacker
+    /// deposits right before the authority syncs them and redeems right after
+    #[flow]
+    fn flow_attack_sync_sandwich(&mut self) {
+        let _scenario = crash::scenario();
+        let decimals_offset = rand::random::<u8>() % (SHARES_DECIMALS + 1);
+        let max_assets = max_fuzzed_assets(decimals_offset);
+        let holding = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+        let donation = rand::random::<u64>() % max_assets + 1;
+        let deposit = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+        let scenario = format!(
+            "offset {decimals_offset}, holding {holding}, donation {donation}, deposit {deposit}"
+        );
+
+        let trident = &mut self.trident;
+        let mut arena = Arena::new(trident, decimals_offset);
+        let holder = arena.actor(trident, USER_ASSETS);
+        let attacker = arena.actor(trident, USER_ASSETS);
+        let held_shares = arena
+            .deposit(trident, &holder, holding)
+            .expect("Invariant: an unpaused vault accepts deposits");
+        let (virtual_assets, virtual_shares) = arena.virtual_totals(trident);
+        let held_value = u128::from(held_shares) * virtual_assets / virtual_shares;
+
+        arena.donate(trident, donation);
+        let shares = arena
+            .deposit(trident, &attacker, deposit)
+            .expect("Invariant: an unpaused vault accepts deposits");
+        let (_, virtual_shares) = arena.virtual_totals(trident);
+        arena.sync(trident);
+        arena.exit(trident, &attacker);
+
+        let profit = i128::from(arena.assets(trident, &attacker)) - i128::from(USER_ASSETS);
+        assert!(
+            within_pro_rata(profit, donation, shares, virtual_shares),
+            "Invariant: the attacker captures at most its shares' part of the donations \
+             ({scenario}, profit {profit})"
+        );
+        assert!(
+            u128::from(arena.exit(trident, &holder)) >= held_value,
+            "Invariant: the sandwich doesn't cost the holder ({scenario})"
+        );
+    }
+
+    /// Dust grinding: the attacker repeats the smallest round trips around a
+    /// holder's deposit, hoping rounding pays out more than it takes
+    #[flow]
+    fn flow_attack_dust_grinding(&mut self) {
+        let _scenario = crash::scenario();
+        let decimals_offset = rand::random::<u8>() % (SHARES_DECIMALS + 1);
+        let max_assets = max_fuzzed_assets(decimals_offset);
+        let holding = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+        // A synced donation moves the share price off its initial ratio
+        let donation = rand::random::<u64>() % max_assets * u64::from(rand::random::<bool>());
+        let rounds = rand::random::<u8>() % MAX_GRINDING_ROUNDS + 1;
+        let scenario = format!(
+            "offset {decimals_offset}, holding {holding}, donation {donation}, rounds {rounds}"
+        );
+
+        let trident = &mut self.trident;
+        let mut arena = Arena::new(trident, decimals_offset);
+        let holder = arena.actor(trident, USER_ASSETS);
+        let attacker = arena.actor(trident, USER_ASSETS);
+        arena
+            .deposit(trident, &holder, holding)
+            .expect("Invariant: an unpaused vault accepts deposits");
+        if donation > 0 {
+            arena.donate(trident, donation);
+            arena.sync(trident);
+        }
+
+        for _ in 0..rounds {
+            let dust = rand::random::<u64>() % MIN_DEPOSIT_AMOUNT + 1;
+            // Rejections leave the vault unchanged, which the arena checks
+            match rand::random::<u8>() % 4 {
+                0 => {
+                    arena.deposit(trident, &attacker, MIN_DEPOSIT_AMOUNT + dust - 1);
+                }
+                1 => {
+                    arena.mint(trident, &attacker, dust);
+                }
+                2 => {
+                    arena.withdraw(trident, &attacker, dust);
+                }
+                _ => {
+                    let shares = arena.shares(trident, &attacker).min(dust);
+                    if shares > 0 {
+                        arena.redeem(trident, &attacker, shares);
+                    }
+                }
+            }
+        }
+        arena.exit(trident, &attacker);
+
+        assert!(
+            arena.assets(trident, &attacker) <= USER_ASSETS,
+            "Invariant: rounding never pays the attacker ({scenario})"
+        );
+    }
+
+    /// Pause timing: the vault is paused while donations are synced; the
+    /// attacker holding shares and a latecomer try to move around the sync
+    #[flow]
+    fn flow_attack_pause_timing(&mut self) {
+        let _scenario = crash::scenario();
+        let decimals_offset = rand::random::<u8>() % (SHARES_DECIMALS + 1);
+        let max_assets = max_fuzzed_assets(decimals_offset);
+        let holding = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+        let donation = rand::random::<u64>() % max_assets + 1;
+        let deposit = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+        let scenario = format!(
+            "offset {decimals_offset}, holding {holding}, donation {donation}, deposit {deposit}"
+        );
+
+        let trident = &mut self.trident;
+        let mut arena = Arena::new(trident, decimals_offset);
+        let holder = arena.actor(trident, USER_ASSETS);
+        let attacker = arena.actor(trident, USER_ASSETS);
+        let latecomer = arena.actor(trident, USER_ASSETS);
+        arena
+            .deposit(trident, &holder, holding)
+            .expect("Invariant: an unpaused vault accepts deposits");
+        let shares = arena
+            .deposit(trident, &attacker, deposit)
+            .expect("Invariant: an unpaused vault accepts deposits");
+
+        arena.set_paused(trident, true);
+        arena.donate(trident, donation);
+        let (_, virtual_shares) = arena.virtual_totals(trident);
+        assert!(
+            arena.deposit(trident, &latecomer, deposit).is_none(),
+            "Invariant: deposits fail while the vault is paused ({scenario})"
+        );
+        assert!(
+            arena.redeem(trident, &attacker, shares).is_none(),
+            "Invariant: redemptions fail while the vault is paused ({scenario})"
+        );
+        arena.sync(trident);
+        arena.set_paused(trident, false);
+        arena.exit(trident, &attacker);
+
+        let profit = i128::from(arena.assets(trident, &attacker)) - i128::from(USER_ASSETS);
+        assert!(
+            within_pro_rata(profit, donation, shares, virtual_shares),
+            "Invariant: the attacker captures at most its shares' part of the donations \
+             ({scenario}, profit {profit})"
+        );
+        assert_eq!(
+            arena.assets(trident, &latecomer),
+            USER_ASSETS,
+            "Invariant: the latecomer neither paid nor gained ({scenario})"
+        );
+    }
+
     #[end]
     fn end(&mut self) {
-        if self.vault_tracker.initialized {
-            // Final invariant: shares/assets relationship
-            let offset_multiplier = 10u64.pow(self.vault_tracker.decimals_offset as u32);
+        let Some(fixture) = self.vault_tracker.fixture else {
+            return;
+        };
+        self.assert_vault_state(&fixture, Change::Unchanged);
 
-            // Invariant: Total shares should have reasonable bounds
-            let max_theoretical_shares = self.vault_tracker.total_assets
-                .saturating_mul(offset_multiplier)
-                .saturating_add(offset_multiplier);
+        // Final invariant: shares/assets relationship
+        let offset_multiplier = 10u64.pow(self.vault_tracker.decimals_offset as u32);
 
-            assert!(
-                self.vault_tracker.total_shares <= max_theoretical_shares.saturating_add(1000),
-                "Invariant: shares exceed theoretical maximum"
-            );
+        // Invariant: Total shares should have reasonable bounds
+        let max_theoretical_shares = self
+            .vault_tracker
+            .total_assets
+            .saturating_mul(offset_multiplier)
+            .saturating_add(offset_multiplier);
+
+        assert!(
+            self.vault_tracker.total_shares <= max_theoretical_shares.saturating_add(1000),
+            "Invariant: shares exceed theoretical maximum"
+        );
+    }
+
+    /// Process `instructions` in one transaction, true if it succeeded
+    fn execute(&mut self, instructions: &[Instruction], name: &str) -> bool {
+        self.trident
+            .process_transaction(instructions, Some(name))
+            .is_success()
+    }
+
+    fn vault_state(&mut self, vault: &Pubkey) -> types::Vault {
+        let account = self.trident.get_account(vault);
+        // Skip the Anchor discriminator
+        types::Vault::try_from_slice(&account.data()[8..]).expect("vault account")
+    }
+
+    /// While the vault is paused, `instruction` must fail and leave the vault
+    /// unchanged; true if the vault was paused
+    fn assert_rejected_while_paused(
+        &mut self,
+        fixture: &Fixture,
+        instruction: Instruction,
+        name: &str,
+    ) -> bool {
+        if !self.vault_tracker.paused {
+            return false;
         }
+        assert!(
+            !self.execute(&[instruction], name),
+            "Invariant: {name} fails while the vault is paused"
+        );
+        self.assert_vault_state(fixture, Change::Unchanged);
+        true
+    }
+
+    /// The program's state matches the tracker: pause flag, recorded and held
+    /// assets, shares supply, and the depositor holding every share. Its
+    /// totals must also have moved with the share price as `change` allows,
+    /// see [`Model::observe`].
+    fn assert_vault_state(&mut self, fixture: &Fixture, change: Change) {
+        let vault = self.vault_state(&fixture.vault);
+        let supply = token::supply(&mut self.trident, &fixture.shares_mint);
+        self.vault_tracker
+            .model
+            .observe(vault.total_assets, supply, change);
+
+        assert_eq!(
+            vault.paused, self.vault_tracker.paused,
+            "Invariant: pause state follows pause and unpause"
+        );
+        assert_eq!(
+            vault.total_assets, self.vault_tracker.total_assets,
+            "Invariant: total assets follow deposits, redemptions and syncs"
+        );
+        assert_eq!(
+            token::balance(&mut self.trident, &fixture.asset_vault),
+            vault.total_assets + self.vault_tracker.unsynced_donations,
+            "Invariant: asset vault balance matches total assets plus unsynced donations"
+        );
+
+        assert_eq!(
+            supply, self.vault_tracker.total_shares,
+            "Invariant: shares supply follows deposits, mints, withdrawals and redemptions"
+        );
+        assert_eq!(
+            token::balance(&mut self.trident, &fixture.user_shares_account),
+            supply,
+            "Invariant: the depositor holds every share"
+        );
     }
 
     // Helper: Calculate shares for given assets (floor rounding - deposit)
-    fn calculate_shares_for_assets(&self, assets: u64, total_assets: u64, total_shares: u64) -> u64 {
-        let offset = 10u64.pow(self.vault_tracker.decimals_offset as u32);
-        let virtual_shares = total_shares.saturating_add(offset);
-        let virtual_assets = total_assets.saturating_add(1);
+    fn calculate_shares_for_assets(
+        &self,
+        assets: u64,
+        total_assets: u64,
+        total_shares: u64,
+    ) -> u64 {
+        convert_to_shares(
+            assets,
+            total_assets,
+            total_shares,
+            self.vault_tracker.decimals_offset,
+            Rounding::Floor,
+        )
+        .expect("Deposit conversion overflowed")
+    }
 
-        (assets as u128)
-            .saturating_mul(virtual_shares as u128)
-            .checked_div(virtual_assets as u128)
-            .unwrap_or(0) as u64
+    // Helper: Calculate shares for given assets (ceiling rounding - withdraw)
+    fn calculate_shares_for_assets_ceil(
+        &self,
+        assets: u64,
+        total_assets: u64,
+        total_shares: u64,
+    ) -> u64 {
+        convert_to_shares(
+            assets,
+            total_assets,
+            total_shares,
+            self.vault_tracker.decimals_offset,
+            Rounding::Ceiling,
+        )
+        .expect("Withdraw conversion overflowed")
     }
 
     // Helper: Calculate assets for given shares (floor rounding - redeem)
-    fn calculate_assets_for_shares_floor(&self, shares: u64, total_assets: u64, total_shares: u64) -> u64 {
-        let offset = 10u64.pow(self.vault_tracker.decimals_offset as u32);
-        let virtual_shares = total_shares.saturating_add(offset);
-        let virtual_assets = total_assets.saturating_add(1);
+    fn calculate_assets_for_shares_floor(
+        &self,
+        shares: u64,
+        total_assets: u64,
+        total_shares: u64,
+    ) -> u64 {
+        convert_to_assets(
+            shares,
+            total_assets,
+            total_shares,
+            self.vault_tracker.decimals_offset,
+            Rounding::Floor,
+        )
+        .expect("Redeem conversion overflowed")
+    }
 
-        (shares as u128)
-            .saturating_mul(virtual_assets as u128)
-            .checked_div(virtual_shares as u128)
-            .unwrap_or(0) as u64
+    // Helper: Calculate assets for given shares (ceiling rounding - mint)
+    fn calculate_assets_for_shares_ceil(
+        &self,
+        shares: u64,
+        total_assets: u64,
+        total_shares: u64,
+    ) -> u64 {
+        convert_to_assets(
+            shares,
+            total_assets,
+            total_shares,
+            self.vault_tracker.decimals_offset,
+            Rounding::Ceiling,
+        )
+        .expect("Mint conversion overflowed")
     }
 }
 
+/// Most assets one fuzzed deposit or donation moves, see
+/// [`MAX_DEPOSIT_SHARES`]
+fn max_fuzzed_assets(decimals_offset: u8) -> u64 {
+    MAX_DEPOSIT_SHARES / 10u64.pow(decimals_offset.into())
+}
+
+/// Whether `profit` is at most the share of `donations` that `shares` of
+/// `virtual_shares` are entitled to
+fn within_pro_rata(profit: i128, donations: u64, shares: u64, virtual_shares: u128) -> bool {
+    profit <= 0 || profit as u128 * virtual_shares <= u128::from(donations) * u128::from(shares)
+}
+
 fn main() {
+    crash::install_panic_hook();
     // Run 1000 iterations with up to 100 flows per iteration
     FuzzTest::fuzz(1000, 100);
 }
@@ -0,0 +1,88 @@
+//! Token instructions and account readers
+//!
+//! The fuzzer builds its token setup from the SPL instruction builders and
+//! reads balances straight from account data, so every flow goes through the
+//! same token programs as the vault's CPIs.
+
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    solana_program::program_pack::Pack,
+    state::{Account, Mint},
+};
+use trident_fuzz::fuzzing::*;
+
+/// SPL Token program, which holds the fuzzed asset mints
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBDf2Jn9");
+/// Token-2022 program, which holds the vault's shares mint
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Lamports funding every account the setup creates
+const ACCOUNT_LAMPORTS: u64 = 10_000_000;
+
+/// Create an asset mint with `authority` as mint authority
+pub fn create_mint(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    decimals: u8,
+) -> [Instruction; 2] {
+    [
+        solana_system_interface::instruction::create_account(
+            payer,
+            mint,
+            ACCOUNT_LAMPORTS,
+            Mint::LEN as u64,
+            &TOKEN_PROGRAM_ID,
+        ),
+        spl_token_2022::instruction::initialize_mint2(
+            &TOKEN_PROGRAM_ID,
+            mint,
+            authority,
+            None,
+            decimals,
+        )
+        .expect("initialize_mint2"),
+    ]
+}
+
+/// Associated token account of `owner`
+pub fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+pub fn create_ata(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    create_associated_token_account_idempotent(payer, owner, mint, token_program)
+}
+
+pub fn mint_to(mint: &Pubkey, account: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    spl_token_2022::instruction::mint_to(&TOKEN_PROGRAM_ID, mint, account, authority, &[], amount)
+        .expect("mint_to")
+}
+
+/// Balance of a token account of either program, 0 if it does not exist
+pub fn balance(trident: &mut Trident, account: &Pubkey) -> u64 {
+    let account = trident.get_account(account);
+    if account.data().is_empty() {
+        return 0;
+    }
+    StateWithExtensions::<Account>::unpack(account.data())
+        .expect("token account")
+        .base
+        .amount
+}
+
+pub fn supply(trident: &mut Trident, mint: &Pubkey) -> u64 {
+    StateWithExtensions::<Mint>::unpack(trident.get_account(mint).data())
+        .expect("mint")
+        .base
+        .supply
+}
@@ -256,6 +256,8 @@ pub mod svs_1 {
         pub associated_token_program: AccountMeta,
 
         pub system_program: AccountMeta,
+
+        pub config: AccountMeta,
     }
 
     /// Account pubkeys for Deposit instruction
@@ -276,6 +278,8 @@ pub mod svs_1 {
         pub user_shares_account: Pubkey,
 
         pub asset_token_program: Pubkey,
+
+        pub config: Pubkey,
     }
 
     impl DepositInstructionAccounts {
@@ -295,6 +299,8 @@ pub mod svs_1 {
             user_shares_account: Pubkey,
 
             asset_token_program: Pubkey,
+
+            config: Pubkey,
         ) -> Self {
             Self {
                 user,
@@ -312,6 +318,8 @@ pub mod svs_1 {
                 user_shares_account,
 
                 asset_token_program,
+
+                config,
             }
         }
     }
@@ -380,6 +388,8 @@ pub mod svs_1 {
             self.accounts.system_program =
                 AccountMeta::new_readonly(pubkey!("11111111111111111111111111111111"), false);
 
+            self.accounts.config = AccountMeta::new_readonly(accounts.config, false);
+
             self
         }
 
@@ -413,6 +423,8 @@ pub mod svs_1 {
 
             metas.push(self.accounts.system_program.clone());
 
+            metas.push(self.accounts.config.clone());
+
             metas.extend(self.remaining_accounts.clone());
             metas
         }
@@ -446,6 +458,8 @@ pub mod svs_1 {
 
         pub vault: AccountMeta,
 
+        pub config: AccountMeta,
+
         pub asset_mint: AccountMeta,
 
         pub shares_mint: AccountMeta,
@@ -470,6 +484,8 @@ pub mod svs_1 {
 
         pub vault: Pubkey,
 
+        pub config: Pubkey,
+
         pub asset_mint: Pubkey,
 
         pub shares_mint: Pubkey,
@@ -485,6 +501,8 @@ pub mod svs_1 {
 
             vault: Pubkey,
 
+            config: Pubkey,
+
             asset_mint: Pubkey,
 
             shares_mint: Pubkey,
@@ -498,6 +516,8 @@ pub mod svs_1 {
 
                 vault,
 
+                config,
+
                 asset_mint,
 
                 shares_mint,
@@ -519,10 +539,18 @@ pub mod svs_1 {
         pub symbol: String,
 
         pub uri: String,
+
+        pub non_transferable: bool,
     }
 
     impl InitializeInstructionData {
-        pub fn new(vault_id: u64, name: String, symbol: String, uri: String) -> Self {
+        pub fn new(
+            vault_id: u64,
+            name: String,
+            symbol: String,
+            uri: String,
+            non_transferable: bool,
+        ) -> Self {
             Self {
                 vault_id,
 
@@ -531,6 +559,8 @@ pub mod svs_1 {
                 symbol,
 
                 uri,
+
+                non_transferable,
             }
         }
     }
@@ -554,6 +584,8 @@ pub mod svs_1 {
 
             self.accounts.vault = AccountMeta::new(accounts.vault, false);
 
+            self.accounts.config = AccountMeta::new_readonly(accounts.config, false);
+
             self.accounts.asset_mint = AccountMeta::new_readonly(accounts.asset_mint, false);
 
             self.accounts.shares_mint = AccountMeta::new(accounts.shares_mint, false);
@@ -596,6 +628,8 @@ pub mod svs_1 {
 
             metas.push(self.accounts.vault.clone());
 
+            metas.push(self.accounts.config.clone());
+
             metas.push(self.accounts.asset_mint.clone());
 
             metas.push(self.accounts.shares_mint.clone());
@@ -1060,6 +1094,8 @@ pub mod svs_1 {
         pub associated_token_program: AccountMeta,
 
         pub system_program: AccountMeta,
+
+        pub config: AccountMeta,
     }
 
     /// Account pubkeys for Mint instruction
@@ -1080,6 +1116,8 @@ pub mod svs_1 {
         pub user_shares_account: Pubkey,
 
         pub asset_token_program: Pubkey,
+
+        pub config: Pubkey,
     }
 
     impl MintInstructionAccounts {
@@ -1099,6 +1137,8 @@ pub mod svs_1 {
             user_shares_account: Pubkey,
 
             asset_token_program: Pubkey,
+
+            config: Pubkey,
         ) -> Self {
             Self {
                 user,
@@ -1116,6 +1156,8 @@ pub mod svs_1 {
                 user_shares_account,
 
                 asset_token_program,
+
+                config,
             }
         }
     }
@@ -1184,6 +1226,8 @@ pub mod svs_1 {
             self.accounts.system_program =
                 AccountMeta::new_readonly(pubkey!("11111111111111111111111111111111"), false);
 
+            self.accounts.config = AccountMeta::new_readonly(accounts.config, false);
+
             self
         }
 
@@ -1217,6 +1261,8 @@ pub mod svs_1 {
 
             metas.push(self.accounts.system_program.clone());
 
+            metas.push(self.accounts.config.clone());
+
             metas.extend(self.remaining_accounts.clone());
             metas
         }
@@ -1731,6 +1777,8 @@ pub mod svs_1 {
         pub asset_token_program: AccountMeta,
 
         pub token_2022_program: AccountMeta,
+
+        pub config: AccountMeta,
     }
 
     /// Account pubkeys for Redeem instruction
@@ -1751,6 +1799,8 @@ pub mod svs_1 {
         pub user_shares_account: Pubkey,
 
         pub asset_token_program: Pubkey,
+
+        pub config: Pubkey,
     }
 
     impl RedeemInstructionAccounts {
@@ -1770,6 +1820,8 @@ pub mod svs_1 {
             user_shares_account: Pubkey,
 
             asset_token_program: Pubkey,
+
+            config: Pubkey,
         ) -> Self {
             Self {
                 user,
@@ -1787,6 +1839,8 @@ pub mod svs_1 {
                 user_shares_account,
 
                 asset_token_program,
+
+                config,
             }
         }
     }
@@ -1847,6 +1901,8 @@ pub mod svs_1 {
                 false,
             );
 
+            self.accounts.config = AccountMeta::new_readonly(accounts.config, false);
+
             self
         }
 
@@ -1876,6 +1932,8 @@ pub mod svs_1 {
 
             metas.push(self.accounts.token_2022_program.clone());
 
+            metas.push(self.accounts.config.clone());
+
             metas.extend(self.remaining_accounts.clone());
             metas
         }
@@ -2306,6 +2364,8 @@ pub mod svs_1 {
         pub asset_token_program: AccountMeta,
 
         pub token_2022_program: AccountMeta,
+
+        pub config: AccountMeta,
     }
 
     /// Account pubkeys for Withdraw instruction
@@ -2326,6 +2386,8 @@ pub mod svs_1 {
         pub user_shares_account: Pubkey,
 
         pub asset_token_program: Pubkey,
+
+        pub config: Pubkey,
     }
 
     impl WithdrawInstructionAccounts {
@@ -2345,6 +2407,8 @@ pub mod svs_1 {
             user_shares_account: Pubkey,
 
             asset_token_program: Pubkey,
+
+            config: Pubkey,
         ) -> Self {
             Self {
                 user,
@@ -2362,6 +2426,8 @@ pub mod svs_1 {
                 user_shares_account,
 
                 asset_token_program,
+
+                config,
             }
         }
     }
@@ -2422,6 +2488,8 @@ pub mod svs_1 {
                 false,
             );
 
+            self.accounts.config = AccountMeta::new_readonly(accounts.config, false);
+
             self
         }
 
@@ -2451,6 +2519,8 @@ pub mod svs_1 {
 
             metas.push(self.accounts.token_2022_program.clone());
 
+            metas.push(self.accounts.config.clone());
+
             metas.extend(self.remaining_accounts.clone());
             metas
         }
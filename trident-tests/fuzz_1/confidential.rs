@@ -0,0 +1,121 @@
+//! Mock proof contexts and checks of confidential balances
+//!
+//! Token-2022 accepts a context state account owned by the ZK ElGamal proof
+//! program as a verified proof and only checks its context against the token
+//! account. The fuzzer writes the withdraw contexts straight into the SVM
+//! rather than generating range proofs, which would take far longer than
+//! the flows. Nothing is proven, so the flows must only burn shares the
+//! tracker knows are available.
+//!
+//! Balances are checked against the tracker without solving a discrete log:
+//! the difference between a ciphertext and the expected amount must decrypt
+//! to the identity point, whatever the balance.
+
+use bytemuck::Zeroable;
+use solana_zk_sdk::{
+    encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalKeypair},
+        pedersen::Pedersen,
+        pod::{
+            elgamal::{PodElGamalCiphertext, PodElGamalPubkey},
+            pedersen::PodPedersenCommitment,
+        },
+    },
+    zk_elgamal_proof_program::{
+        proof_data::{
+            BatchedRangeProofContext, CiphertextCommitmentEqualityProofContext, ProofType,
+        },
+        state::ProofContextState,
+    },
+};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount, BaseStateWithExtensions,
+        BaseStateWithExtensionsMut, StateWithExtensions, StateWithExtensionsMut,
+    },
+    solana_program::pubkey::Pubkey,
+    state::Account,
+};
+use spl_token_confidential_transfer_ciphertext_arithmetic as ciphertext_arithmetic;
+
+/// Bit length Token-2022 requires of a withdraw's remaining balance
+const REMAINING_BALANCE_BIT_LENGTH: u8 = 64;
+
+/// Data of the equality and range proof context accounts for withdrawing
+/// `amount` of `available_balance`, `remaining` being left
+pub fn withdraw_contexts(
+    authority: &Pubkey,
+    elgamal: &ElGamalKeypair,
+    available_balance: &PodElGamalCiphertext,
+    amount: u64,
+    remaining: u64,
+) -> [Vec<u8>; 2] {
+    let (commitment, _) = Pedersen::new(remaining);
+    let commitment = PodPedersenCommitment::from(commitment);
+    let equality = CiphertextCommitmentEqualityProofContext {
+        pubkey: PodElGamalPubkey::from(*elgamal.pubkey()),
+        // What Token-2022 computes from the account's available balance
+        ciphertext: ciphertext_arithmetic::subtract_from(available_balance, amount)
+            .expect("remaining balance ciphertext"),
+        commitment,
+    };
+
+    let mut range = BatchedRangeProofContext::zeroed();
+    range.commitments[0] = commitment;
+    range.bit_lengths[0] = REMAINING_BALANCE_BIT_LENGTH;
+
+    [
+        ProofContextState::encode(
+            authority,
+            ProofType::CiphertextCommitmentEquality,
+            &equality,
+        ),
+        ProofContextState::encode(authority, ProofType::BatchedRangeProofU64, &range),
+    ]
+}
+
+/// Confidential transfer extension of a shares account
+pub fn extension(data: &[u8]) -> ConfidentialTransferAccount {
+    *StateWithExtensions::<Account>::unpack(data)
+        .expect("shares account")
+        .get_extension::<ConfidentialTransferAccount>()
+        .expect("confidential transfer extension")
+}
+
+/// Non-confidential balance of a shares account
+pub fn public_balance(data: &[u8]) -> u64 {
+    StateWithExtensions::<Account>::unpack(data)
+        .expect("shares account")
+        .base
+        .amount
+}
+
+/// Overwrite the pending balance credit counter of a shares account
+pub fn set_pending_credits(data: &mut [u8], credits: u64) {
+    StateWithExtensionsMut::<Account>::unpack(data)
+        .expect("shares account")
+        .get_extension_mut::<ConfidentialTransferAccount>()
+        .expect("confidential transfer extension")
+        .pending_balance_credit_counter = credits.into();
+}
+
+/// Whether `ciphertext` encrypts `amount` under `elgamal`
+pub fn encrypts(elgamal: &ElGamalKeypair, ciphertext: &PodElGamalCiphertext, amount: u64) -> bool {
+    ciphertext_arithmetic::subtract_from(ciphertext, amount)
+        .and_then(|difference| ElGamalCiphertext::try_from(difference).ok())
+        .is_some_and(|difference| {
+            let message = difference.commitment.get_point()
+                - elgamal.secret().get_scalar() * difference.handle.get_point();
+            message == Default::default()
+        })
+}
+
+/// Pending balance of `extension` as one ciphertext, `lo + 2^16 * hi`
+pub fn pending_balance(extension: &ConfidentialTransferAccount) -> PodElGamalCiphertext {
+    ciphertext_arithmetic::add_with_lo_hi(
+        &PodElGamalCiphertext::zeroed(),
+        &extension.pending_balance_lo,
+        &extension.pending_balance_hi,
+    )
+    .expect("pending balance ciphertext")
+}
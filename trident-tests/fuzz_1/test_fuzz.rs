@@ -0,0 +1,519 @@
+use confidential::{encrypts, extension, pending_balance, public_balance};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_zk_sdk::{
+    encryption::{
+        auth_encryption::{AeCiphertext, AeKey},
+        elgamal::ElGamalKeypair,
+    },
+    zk_elgamal_proof_program::{
+        self, instruction::ProofInstruction, proof_data::PubkeyValidityProofData,
+    },
+};
+use spl_token_2022::extension::confidential_transfer::{
+    ConfidentialTransferAccount, DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER,
+    MAXIMUM_DEPOSIT_TRANSFER_AMOUNT,
+};
+use svs_client::{
+    svs2::{self, PubkeyValidityProof, WithdrawProofContexts},
+    ConfidentialVault, VaultKeys,
+};
+use svs_math::{convert_to_assets, convert_to_shares, Rounding};
+use token::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use trident_fuzz::fuzzing::*;
+mod confidential;
+#[path = "../fuzz_0/token.rs"]
+mod token;
+
+/// SVS-2 program, as deployed in `Trident.toml`
+const PROGRAM_ID: Pubkey = pubkey!("3UrYrxh1HmVgq7WPygZ5x1gNEaWFwqTMs7geNqMnsrtD");
+/// Decimals of the vault's shares mint; assets have `9 - decimals_offset`
+const SHARES_DECIMALS: u8 = 9;
+/// Smallest deposit the program accepts
+const MIN_DEPOSIT_AMOUNT: u64 = 1000;
+const VAULT_ID: u64 = 1;
+/// Pending credits Token-2022 allows before `apply_pending`, as configured by
+/// the program
+const MAX_PENDING_CREDITS: u64 = DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER;
+/// Assets minted to the depositor, enough for every deposit of an iteration
+const USER_ASSETS: u64 = u64::MAX / 2;
+/// Lamports for the authority's and depositor's rent
+const ACTOR_LAMPORTS: u64 = 10_000_000_000;
+/// Lamports of a mock proof context account
+const CONTEXT_LAMPORTS: u64 = 10_000_000;
+/// Compute limit of every transaction; the confidential Token-2022 CPIs need
+/// more than the default
+const COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Accounts and encryption keys of the fuzzed vault and its only depositor
+#[derive(Clone)]
+struct Fixture {
+    keys: VaultKeys,
+    user: Pubkey,
+    shares_account: Pubkey,
+    elgamal: ElGamalKeypair,
+    ae: AeKey,
+}
+
+/// Expected vault and shares account state, checked against the program's
+/// after every flow
+#[derive(Default, Clone)]
+struct VaultTracker {
+    fixture: Option<Fixture>,
+    total_assets: u64,
+    total_shares: u64,
+    decimals_offset: u8,
+    /// Shares moved to the available balance and not redeemed
+    available_shares: u64,
+    /// Shares credited since the last `apply_pending`
+    pending_shares: u64,
+    /// Credits to the pending balance since the last `apply_pending`
+    pending_credits: u64,
+}
+
+#[derive(FuzzTestMethods)]
+struct FuzzTest {
+    trident: Trident,
+    vault_tracker: VaultTracker,
+}
+
+#[flow_executor]
+impl FuzzTest {
+    fn new() -> Self {
+        Self {
+            trident: Trident::default(),
+            vault_tracker: VaultTracker::default(),
+        }
+    }
+
+    #[init]
+    fn start(&mut self) {
+        self.vault_tracker = VaultTracker::default();
+    }
+
+    /// Initialize an SVS-2 vault for a fresh asset mint with fuzzed decimals,
+    /// fund its depositor and configure their shares account
+    #[flow]
+    fn flow_initialize(&mut self) {
+        if self.vault_tracker.fixture.is_some() {
+            return;
+        }
+
+        let asset_decimals = rand::random::<u8>() % (SHARES_DECIMALS + 1);
+        let decimals_offset = SHARES_DECIMALS - asset_decimals;
+
+        let authority = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let asset_mint = Pubkey::new_unique();
+        self.trident.airdrop(&authority, ACTOR_LAMPORTS);
+        self.trident.airdrop(&user, ACTOR_LAMPORTS);
+
+        let keys = VaultKeys::derive(&PROGRAM_ID, &asset_mint, VAULT_ID, &TOKEN_PROGRAM_ID);
+        let fixture = Fixture {
+            keys,
+            user,
+            shares_account: keys.shares_account(&user),
+            elgamal: ElGamalKeypair::new_rand(),
+            ae: AeKey::new_rand(),
+        };
+
+        let mut setup =
+            token::create_mint(&authority, &asset_mint, &authority, asset_decimals).to_vec();
+        setup.push(token::create_ata(
+            &authority,
+            &user,
+            &asset_mint,
+            &TOKEN_PROGRAM_ID,
+        ));
+        setup.push(token::mint_to(
+            &asset_mint,
+            &keys.asset_account(&user),
+            &authority,
+            USER_ASSETS,
+        ));
+        assert!(
+            self.execute(&setup, "Asset setup"),
+            "Asset mint setup failed"
+        );
+
+        let initialize = svs2::initialize(
+            &keys,
+            &authority,
+            &authority,
+            VAULT_ID,
+            "Fuzz Confidential Vault".to_string(),
+            "fzcVLT".to_string(),
+            String::new(),
+            None,
+        );
+        assert!(
+            self.execute(&[initialize], "Initialize"),
+            "Invariant: initialize succeeds for asset decimals up to 9"
+        );
+        assert_eq!(
+            self.vault_state(&keys.vault).decimals_offset,
+            decimals_offset,
+            "Invariant: decimals offset is 9 - asset decimals"
+        );
+
+        // The pubkey validity proof is cheap, so configure with a real one
+        let proof = PubkeyValidityProofData::new(&fixture.elgamal).expect("pubkey validity proof");
+        let configure = [
+            token::create_ata(&user, &user, &keys.shares_mint, &TOKEN_2022_PROGRAM_ID),
+            ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof),
+            svs2::configure_account(
+                &keys,
+                &user,
+                None,
+                fixture.ae.encrypt(0).to_bytes(),
+                PubkeyValidityProof::InstructionOffset(-1),
+            ),
+        ];
+        assert!(
+            self.execute(&configure, "Configure account"),
+            "Configuring the shares account failed"
+        );
+
+        self.vault_tracker.decimals_offset = decimals_offset;
+        self.vault_tracker.fixture = Some(fixture.clone());
+        self.assert_vault_state(&fixture);
+    }
+
+    /// Deposit fuzzed assets, crediting the expected shares to the pending
+    /// balance unless they exceed Token-2022's deposit or credit limits
+    #[flow]
+    fn flow_deposit(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture.clone() else {
+            return;
+        };
+
+        // Up to about twice the shares one confidential deposit can credit
+        let max_assets = 2 * MAXIMUM_DEPOSIT_TRANSFER_AMOUNT
+            / 10u64.pow(self.vault_tracker.decimals_offset.into());
+        let assets = (rand::random::<u64>() % max_assets).max(MIN_DEPOSIT_AMOUNT);
+
+        let expected_shares = convert_to_shares(
+            assets,
+            self.vault_tracker.total_assets,
+            self.vault_tracker.total_shares,
+            self.vault_tracker.decimals_offset,
+            Rounding::Floor,
+        )
+        .expect("Deposit conversion overflowed");
+        assert!(
+            expected_shares > 0,
+            "Invariant: positive deposit should yield positive shares"
+        );
+
+        let deposit = svs2::deposit(&fixture.keys, &fixture.user, assets, expected_shares, None);
+        if expected_shares > MAXIMUM_DEPOSIT_TRANSFER_AMOUNT
+            || self.vault_tracker.pending_credits >= MAX_PENDING_CREDITS
+        {
+            assert!(
+                !self.execute(&[deposit], "Deposit"),
+                "Invariant: deposits beyond 48-bit shares or the pending credit limit fail"
+            );
+            self.assert_vault_state(&fixture);
+            return;
+        }
+
+        let user_asset_account = fixture.keys.asset_account(&fixture.user);
+        let user_assets = token::balance(&mut self.trident, &user_asset_account);
+        assert!(
+            self.execute(&[deposit], "Deposit"),
+            "Invariant: deposit at the expected share price succeeds"
+        );
+        assert_eq!(
+            user_assets - token::balance(&mut self.trident, &user_asset_account),
+            assets,
+            "Invariant: deposit takes exactly the deposited assets"
+        );
+
+        let tracker = &mut self.vault_tracker;
+        tracker.total_assets = tracker
+            .total_assets
+            .checked_add(assets)
+            .expect("total assets overflow");
+        tracker.total_shares = tracker
+            .total_shares
+            .checked_add(expected_shares)
+            .expect("total shares overflow");
+        tracker.pending_shares += expected_shares;
+        tracker.pending_credits += 1;
+        self.assert_vault_state(&fixture);
+    }
+
+    /// Move the pending shares to the available balance, with or without
+    /// pending credits
+    #[flow]
+    fn flow_apply_pending(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture.clone() else {
+            return;
+        };
+        self.apply_pending(&fixture);
+    }
+
+    /// Bring the pending credit counter to one below or at Token-2022's
+    /// limit, which would otherwise take tens of thousands of deposits
+    #[flow]
+    fn flow_fill_pending_credits(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture.clone() else {
+            return;
+        };
+        let credits = MAX_PENDING_CREDITS - rand::random::<u64>() % 2;
+        if credits <= self.vault_tracker.pending_credits {
+            return;
+        }
+
+        let mut account = self.trident.get_account(&fixture.shares_account);
+        let mut data = account.data().to_vec();
+        confidential::set_pending_credits(&mut data, credits);
+        account.set_data_from_slice(&data);
+        self.trident
+            .set_account_custom(&fixture.shares_account, &account);
+
+        self.vault_tracker.pending_credits = credits;
+        self.assert_vault_state(&fixture);
+    }
+
+    /// Redeem fuzzed available shares with mock proofs, with the expected
+    /// assets as the slippage bound
+    #[flow]
+    fn flow_redeem(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture.clone() else {
+            return;
+        };
+        let available = self.vault_tracker.available_shares;
+        if available == 0 {
+            return;
+        }
+
+        let shares = rand::random::<u64>() % available + 1;
+        let expected_assets = convert_to_assets(
+            shares,
+            self.vault_tracker.total_assets,
+            self.vault_tracker.total_shares,
+            self.vault_tracker.decimals_offset,
+            Rounding::Floor,
+        )
+        .expect("Redeem conversion overflowed");
+        assert!(
+            expected_assets <= self.vault_tracker.total_assets,
+            "Invariant: cannot redeem more assets than available"
+        );
+
+        let contexts = self.mock_withdraw_contexts(&fixture, shares);
+        let redeem = svs2::redeem(
+            &fixture.keys,
+            &fixture.user,
+            &contexts,
+            shares,
+            expected_assets,
+            fixture.ae.encrypt(available - shares).to_bytes(),
+        );
+        let user_asset_account = fixture.keys.asset_account(&fixture.user);
+        let user_assets = token::balance(&mut self.trident, &user_asset_account);
+        assert!(
+            self.execute(&[redeem], "Redeem"),
+            "Invariant: redeeming available shares at the expected price succeeds"
+        );
+        assert_eq!(
+            token::balance(&mut self.trident, &user_asset_account) - user_assets,
+            expected_assets,
+            "Invariant: redeem pays exactly the expected assets"
+        );
+
+        let tracker = &mut self.vault_tracker;
+        tracker.total_assets -= expected_assets;
+        tracker.total_shares -= shares;
+        tracker.available_shares -= shares;
+        self.assert_vault_state(&fixture);
+    }
+
+    /// Prove a redeem against the available balance, apply the pending shares,
+    /// then redeem with the outdated proofs
+    #[flow]
+    fn flow_redeem_stale_proof(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture.clone() else {
+            return;
+        };
+        let available = self.vault_tracker.available_shares;
+        if available == 0 || self.vault_tracker.pending_shares == 0 {
+            return;
+        }
+
+        let shares = rand::random::<u64>() % available + 1;
+        let contexts = self.mock_withdraw_contexts(&fixture, shares);
+        self.apply_pending(&fixture);
+
+        let redeem = svs2::redeem(
+            &fixture.keys,
+            &fixture.user,
+            &contexts,
+            shares,
+            0,
+            fixture.ae.encrypt(available - shares).to_bytes(),
+        );
+        assert!(
+            !self.execute(&[redeem], "Redeem"),
+            "Invariant: redeem rejects proofs of a balance apply_pending has changed"
+        );
+        self.assert_vault_state(&fixture);
+    }
+
+    #[end]
+    fn end(&mut self) {
+        let Some(fixture) = self.vault_tracker.fixture.clone() else {
+            return;
+        };
+        self.assert_vault_state(&fixture);
+    }
+
+    /// Process `instructions` in one transaction with the confidential
+    /// compute limit, true if it succeeded
+    fn execute(&mut self, instructions: &[Instruction], name: &str) -> bool {
+        let mut transaction = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            COMPUTE_UNITS,
+        )];
+        transaction.extend_from_slice(instructions);
+        self.trident
+            .process_transaction(&transaction, Some(name))
+            .is_success()
+    }
+
+    /// Apply the tracked pending shares and credits
+    fn apply_pending(&mut self, fixture: &Fixture) {
+        let tracker = &self.vault_tracker;
+        let available = tracker.available_shares + tracker.pending_shares;
+        let credits = tracker.pending_credits;
+
+        let apply = svs2::apply_pending(
+            &fixture.keys,
+            &fixture.user,
+            fixture.ae.encrypt(available).to_bytes(),
+            credits,
+        );
+        assert!(
+            self.execute(&[apply], "Apply pending"),
+            "Invariant: apply_pending succeeds, pending credits or not"
+        );
+        let extension = self.shares_extension(fixture);
+        assert_eq!(
+            (
+                u64::from(extension.actual_pending_balance_credit_counter),
+                u64::from(extension.expected_pending_balance_credit_counter),
+            ),
+            (credits, credits),
+            "Invariant: apply_pending records the credits it applied"
+        );
+
+        let tracker = &mut self.vault_tracker;
+        tracker.available_shares = available;
+        tracker.pending_shares = 0;
+        tracker.pending_credits = 0;
+        self.assert_vault_state(fixture);
+    }
+
+    /// Write equality and range proof contexts for redeeming `shares` of the
+    /// tracked available balance
+    fn mock_withdraw_contexts(&mut self, fixture: &Fixture, shares: u64) -> WithdrawProofContexts {
+        let contexts = confidential::withdraw_contexts(
+            &fixture.user,
+            &fixture.elgamal,
+            &self.shares_extension(fixture).available_balance,
+            shares,
+            self.vault_tracker.available_shares - shares,
+        );
+        let [equality, range] = contexts.map(|data| {
+            let address = Pubkey::new_unique();
+            let mut account = AccountSharedData::new(
+                CONTEXT_LAMPORTS,
+                data.len(),
+                &zk_elgamal_proof_program::id(),
+            );
+            account.set_data_from_slice(&data);
+            self.trident.set_account_custom(&address, &account);
+            address
+        });
+        WithdrawProofContexts { equality, range }
+    }
+
+    fn vault_state(&mut self, vault: &Pubkey) -> ConfidentialVault {
+        let account = self.trident.get_account(vault);
+        svs_client::decode_confidential_vault(vault, account.data()).expect("vault account")
+    }
+
+    fn shares_extension(&mut self, fixture: &Fixture) -> ConfidentialTransferAccount {
+        extension(self.trident.get_account(&fixture.shares_account).data())
+    }
+
+    /// The program's state matches the tracker: recorded and held assets,
+    /// shares supply, and the depositor's confidential balances
+    fn assert_vault_state(&mut self, fixture: &Fixture) {
+        let vault = self.vault_state(&fixture.keys.vault);
+        let tracker = self.vault_tracker.clone();
+        assert_eq!(
+            vault.total_assets, tracker.total_assets,
+            "Invariant: total assets follow deposits and redemptions"
+        );
+        assert_eq!(
+            token::balance(&mut self.trident, &fixture.keys.asset_vault),
+            vault.total_assets,
+            "Invariant: asset vault balance matches total assets"
+        );
+        assert_eq!(
+            token::supply(&mut self.trident, &fixture.keys.shares_mint),
+            tracker.total_shares,
+            "Invariant: shares supply follows deposits and redemptions"
+        );
+        assert_eq!(
+            tracker.available_shares + tracker.pending_shares,
+            tracker.total_shares,
+            "Invariant: the depositor's confidential balances hold every share"
+        );
+
+        let data = self
+            .trident
+            .get_account(&fixture.shares_account)
+            .data()
+            .to_vec();
+        assert_eq!(
+            public_balance(&data),
+            0,
+            "Invariant: no shares are left in the public balance"
+        );
+        let extension = extension(&data);
+        assert_eq!(
+            u64::from(extension.pending_balance_credit_counter),
+            tracker.pending_credits,
+            "Invariant: every credited deposit counts one pending credit"
+        );
+        assert!(
+            encrypts(
+                &fixture.elgamal,
+                &pending_balance(&extension),
+                tracker.pending_shares
+            ),
+            "Invariant: pending balance encrypts the shares credited since apply_pending"
+        );
+        assert!(
+            encrypts(
+                &fixture.elgamal,
+                &extension.available_balance,
+                tracker.available_shares
+            ),
+            "Invariant: available balance encrypts the applied shares not redeemed"
+        );
+        assert_eq!(
+            AeCiphertext::try_from(extension.decryptable_available_balance)
+                .ok()
+                .and_then(|balance| balance.decrypt(&fixture.ae)),
+            Some(tracker.available_shares),
+            "Invariant: decryptable balance matches the available balance"
+        );
+    }
+}
+
+fn main() {
+    // Run 1000 iterations with up to 100 flows per iteration
+    FuzzTest::fuzz(1000, 100);
+}